@@ -0,0 +1,256 @@
+//! OS accessibility settings the canvas adapts to (reduced motion, high
+//! contrast, a screen reader running) and spoken announcements, e.g. of
+//! selection changes. Settings are polled, since not every platform notifies
+//! about them, and sent as `accessibility-changed` when they change.
+//! Announcements go through VoiceOver on macOS; elsewhere `announce` reports
+//! that it delivered nothing and the frontend uses an ARIA live region, which
+//! the webview exposes to Narrator and Orca.
+
+use std::{sync::Mutex, thread, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::error::{Error, Result};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Longest announcement, in characters; screen readers cut long ones anyway.
+const MAX_ANNOUNCEMENT: usize = 500;
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessibilitySettings {
+    pub reduced_motion: bool,
+    pub high_contrast: bool,
+    pub screen_reader: bool,
+}
+
+#[derive(Default)]
+pub struct Accessibility(Mutex<AccessibilitySettings>);
+
+impl Accessibility {
+    pub fn get(&self) -> AccessibilitySettings {
+        *self.0.lock().unwrap()
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    /// Waits for the screen reader to finish what it is saying
+    #[default]
+    Polite,
+    /// Interrupts it
+    Assertive,
+}
+
+fn read(app: &AppHandle) {
+    let settings = platform::settings();
+    let accessibility = app.state::<Accessibility>();
+    let changed = std::mem::replace(&mut *accessibility.0.lock().unwrap(), settings) != settings;
+    if changed {
+        tracing::info!(
+            reduced_motion = settings.reduced_motion,
+            high_contrast = settings.high_contrast,
+            screen_reader = settings.screen_reader,
+            "accessibility settings changed"
+        );
+        let _ = app.emit("accessibility-changed", settings);
+    }
+}
+
+/// Start following the OS accessibility settings.
+pub fn start(app: &AppHandle) {
+    read(app);
+    let handle = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(POLL_INTERVAL);
+        read(&handle);
+    });
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2::{rc::Retained, runtime::AnyObject, MainThreadMarker};
+    use objc2_app_kit::{
+        NSAccessibilityAnnouncementKey, NSAccessibilityAnnouncementRequestedNotification,
+        NSAccessibilityPostNotificationWithUserInfo, NSAccessibilityPriorityHigh, NSAccessibilityPriorityKey,
+        NSAccessibilityPriorityMedium, NSApplication, NSWorkspace,
+    };
+    use objc2_foundation::{NSDictionary, NSNumber, NSString};
+    use tauri::AppHandle;
+
+    use super::{AccessibilitySettings, Priority};
+    use crate::error::Result;
+
+    pub fn settings() -> AccessibilitySettings {
+        let workspace = NSWorkspace::sharedWorkspace();
+        AccessibilitySettings {
+            reduced_motion: workspace.accessibilityDisplayShouldReduceMotion(),
+            high_contrast: workspace.accessibilityDisplayShouldIncreaseContrast(),
+            screen_reader: workspace.isVoiceOverEnabled(),
+        }
+    }
+
+    pub fn announce(app: &AppHandle, text: &str, priority: Priority) -> Result<bool> {
+        let text = text.to_string();
+        app.run_on_main_thread(move || {
+            let Some(mtm) = MainThreadMarker::new() else {
+                return;
+            };
+            let level = match priority {
+                Priority::Polite => NSAccessibilityPriorityMedium,
+                Priority::Assertive => NSAccessibilityPriorityHigh,
+            };
+            let message: Retained<AnyObject> = NSString::from_str(&text).into();
+            let level: Retained<AnyObject> = NSNumber::new_isize(level.0).into();
+            unsafe {
+                let info = NSDictionary::from_slices(
+                    &[NSAccessibilityAnnouncementKey, NSAccessibilityPriorityKey],
+                    &[&*message, &*level],
+                );
+                NSAccessibilityPostNotificationWithUserInfo(
+                    &NSApplication::sharedApplication(mtm),
+                    NSAccessibilityAnnouncementRequestedNotification,
+                    Some(&info),
+                );
+            }
+        })?;
+        Ok(true)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::ffi::c_void;
+
+    use tauri::AppHandle;
+    use windows::Win32::UI::{
+        Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW},
+        WindowsAndMessaging::{
+            SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION, SPI_GETHIGHCONTRAST, SPI_GETSCREENREADER,
+            SYSTEM_PARAMETERS_INFO_ACTION, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+        },
+    };
+
+    use super::{AccessibilitySettings, Priority};
+    use crate::error::Result;
+
+    fn get(action: SYSTEM_PARAMETERS_INFO_ACTION, size: u32, value: *mut c_void) -> bool {
+        unsafe { SystemParametersInfoW(action, size, Some(value), SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0)) }.is_ok()
+    }
+
+    fn flag(action: SYSTEM_PARAMETERS_INFO_ACTION) -> Option<bool> {
+        // A BOOL
+        let mut value = 0i32;
+        get(action, 0, std::ptr::from_mut(&mut value).cast()).then_some(value != 0)
+    }
+
+    fn high_contrast() -> bool {
+        let mut contrast = HIGHCONTRASTW {
+            cbSize: size_of::<HIGHCONTRASTW>() as u32,
+            ..Default::default()
+        };
+        let size = contrast.cbSize;
+        get(SPI_GETHIGHCONTRAST, size, std::ptr::from_mut(&mut contrast).cast())
+            && contrast.dwFlags.contains(HCF_HIGHCONTRASTON)
+    }
+
+    pub fn settings() -> AccessibilitySettings {
+        AccessibilitySettings {
+            // Off when the user turned off "Show animations in Windows"
+            reduced_motion: flag(SPI_GETCLIENTAREAANIMATION).is_some_and(|animate| !animate),
+            high_contrast: high_contrast(),
+            screen_reader: flag(SPI_GETSCREENREADER).unwrap_or(false),
+        }
+    }
+
+    pub fn announce(_app: &AppHandle, _text: &str, _priority: Priority) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+/// Settings come from the desktop portal, which reads them from whichever
+/// desktop is running, and the screen reader from the AT-SPI bus.
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::time::Duration;
+
+    use dbus::{
+        arg::{RefArg, Variant},
+        blocking::{stdintf::org_freedesktop_dbus::Properties, Connection},
+    };
+    use tauri::AppHandle;
+
+    use super::{AccessibilitySettings, Priority};
+    use crate::error::Result;
+
+    const PORTAL: &str = "org.freedesktop.portal.Desktop";
+    const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+    const PORTAL_SETTINGS: &str = "org.freedesktop.portal.Settings";
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    fn portal_setting(connection: &Connection, namespace: &str, key: &str) -> Option<u64> {
+        let proxy = connection.with_proxy(PORTAL, PORTAL_PATH, TIMEOUT);
+        // `Read` wraps the value in a second variant, which `as_u64` sees through
+        let (value,): (Variant<Box<dyn RefArg>>,) =
+            proxy.method_call(PORTAL_SETTINGS, "Read", (namespace, key)).ok()?;
+        value.0.as_u64()
+    }
+
+    pub fn settings() -> AccessibilitySettings {
+        let Ok(connection) = Connection::new_session() else {
+            return AccessibilitySettings::default();
+        };
+        let screen_reader = connection
+            .with_proxy("org.a11y.Bus", "/org/a11y/bus", TIMEOUT)
+            .get::<bool>("org.a11y.Status", "ScreenReaderEnabled")
+            .unwrap_or(false);
+        AccessibilitySettings {
+            reduced_motion: portal_setting(&connection, "org.gnome.desktop.interface", "enable-animations")
+                .is_some_and(|animate| animate == 0),
+            // 1 asks for more contrast
+            high_contrast: portal_setting(&connection, "org.freedesktop.appearance", "contrast") == Some(1),
+            screen_reader,
+        }
+    }
+
+    pub fn announce(_app: &AppHandle, _text: &str, _priority: Priority) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod platform {
+    use tauri::AppHandle;
+
+    use super::{AccessibilitySettings, Priority};
+    use crate::error::Result;
+
+    pub fn settings() -> AccessibilitySettings {
+        AccessibilitySettings::default()
+    }
+
+    pub fn announce(_app: &AppHandle, _text: &str, _priority: Priority) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+#[tauri::command]
+pub fn get_accessibility_settings(accessibility: State<'_, Accessibility>) -> AccessibilitySettings {
+    accessibility.get()
+}
+
+/// Have the screen reader speak `text`. Returns whether the OS took the
+/// announcement; when it didn't, the frontend announces it through the page.
+#[tauri::command]
+pub fn announce(app: AppHandle, text: String, priority: Option<Priority>) -> Result<bool> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(true);
+    }
+    if text.chars().count() > MAX_ANNOUNCEMENT {
+        return Err(Error::Accessibility(format!("announcements are limited to {MAX_ANNOUNCEMENT} characters")));
+    }
+    platform::announce(&app, text, priority.unwrap_or_default())
+}
@@ -0,0 +1,226 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{
+    http::{self, header, StatusCode},
+    ipc::Response,
+    AppHandle, Manager, Runtime, State, UriSchemeContext, UriSchemeResponder,
+};
+
+use crate::{
+    board::MAX_ASSET_SIZE,
+    error::{Error, Result},
+    persist,
+};
+
+/// Serves `boardkit-asset://localhost/<hash>` (`http://boardkit-asset.localhost/<hash>` on Windows).
+pub const PROTOCOL: &str = "boardkit-asset";
+const ASSETS_DIR: &str = "assets";
+const REFERENCES_STORE: &str = "asset-references.json";
+/// Unreferenced blobs younger than this survive collection, so an image pasted
+/// into a board that hasn't reported its references yet isn't lost.
+const GC_GRACE_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Content-addressed blob store in the app data dir: each asset is saved once
+/// under its SHA-256 hash, however many boards use it.
+pub struct AssetStore {
+    dir: PathBuf,
+    references_path: PathBuf,
+    /// Hashes each board references, keyed by board id
+    references: Mutex<BTreeMap<String, BTreeSet<String>>>,
+}
+
+#[derive(Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+    pub removed: u32,
+    pub freed_bytes: u64,
+}
+
+/// Reject anything but a lowercase SHA-256 hex digest, which also keeps
+/// hashes from escaping the store directory.
+pub fn validate_hash(hash: &str) -> Result<()> {
+    if hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)) {
+        Ok(())
+    } else {
+        Err(Error::NotFound(format!("asset {hash}")))
+    }
+}
+
+impl AssetStore {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let dir = app.path().app_data_dir()?.join(ASSETS_DIR);
+        fs::create_dir_all(&dir)?;
+        let references_path = persist::app_data_file(app, REFERENCES_STORE)?;
+        let references = persist::read_json(&references_path);
+        Ok(Self {
+            dir,
+            references_path,
+            references: Mutex::new(references),
+        })
+    }
+
+    /// Blobs are sharded by the first two hex digits to keep directories small.
+    pub fn blob_path(&self, hash: &str) -> Result<PathBuf> {
+        validate_hash(hash)?;
+        Ok(self.dir.join(&hash[..2]).join(hash))
+    }
+
+    pub fn store(&self, data: &[u8]) -> Result<String> {
+        if data.len() as u64 > MAX_ASSET_SIZE {
+            return Err(Error::TooLarge {
+                size: data.len() as u64,
+                max: MAX_ASSET_SIZE,
+            });
+        }
+        let hash = format!("{:x}", Sha256::digest(data));
+        let path = self.blob_path(&hash)?;
+        if !path.exists() {
+            fs::create_dir_all(path.parent().unwrap_or(&self.dir))?;
+            persist::write_atomic(&path, data)?;
+        }
+        Ok(hash)
+    }
+
+    pub fn get(&self, hash: &str) -> Result<Vec<u8>> {
+        let path = self.blob_path(hash)?;
+        fs::read(&path).map_err(|err| Error::from_io(err, hash))
+    }
+
+    pub fn set_references(&self, board_id: String, hashes: BTreeSet<String>) -> Result<()> {
+        let mut references = self.references.lock().unwrap();
+        if hashes.is_empty() {
+            references.remove(&board_id);
+        } else {
+            references.insert(board_id, hashes);
+        }
+        persist::write_json(&self.references_path, &*references)
+    }
+
+    /// Give a copied board the same references as the board it was copied from.
+    pub fn copy_references(&self, from: &str, to: &str) -> Result<()> {
+        let hashes = self.references.lock().unwrap().get(from).cloned().unwrap_or_default();
+        self.set_references(to.to_string(), hashes)
+    }
+
+    /// Delete blobs no board references, past the grace period.
+    pub fn collect_garbage(&self) -> Result<GcReport> {
+        let referenced: BTreeSet<String> = self.references.lock().unwrap().values().flatten().cloned().collect();
+        let now = SystemTime::now();
+        let mut report = GcReport::default();
+        for shard in fs::read_dir(&self.dir)?.filter_map(|entry| entry.ok()) {
+            let Ok(blobs) = fs::read_dir(shard.path()) else {
+                continue;
+            };
+            for blob in blobs.filter_map(|entry| entry.ok()) {
+                let name = blob.file_name().to_string_lossy().into_owned();
+                if validate_hash(&name).is_err() || referenced.contains(&name) {
+                    continue;
+                }
+                let Ok(metadata) = blob.metadata() else {
+                    continue;
+                };
+                let age = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| now.duration_since(modified).ok())
+                    .unwrap_or_default();
+                if age < GC_GRACE_PERIOD {
+                    continue;
+                }
+                if fs::remove_file(blob.path()).is_ok() {
+                    report.removed += 1;
+                    report.freed_bytes += metadata.len();
+                }
+            }
+            // Only succeeds once the shard is empty
+            let _ = fs::remove_dir(shard.path());
+        }
+        Ok(report)
+    }
+}
+
+/// Content type from the file signature; the store keeps bytes only.
+fn sniff_mime(data: &[u8]) -> &'static str {
+    match data {
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [0xff, 0xd8, 0xff, ..] => "image/jpeg",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        _ if data.starts_with(b"<svg") || data.starts_with(b"<?xml") => "image/svg+xml",
+        [b'%', b'P', b'D', b'F', ..] => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+fn protocol_response(app: &AppHandle<impl Runtime>, hash: &str) -> http::Result<http::Response<Vec<u8>>> {
+    let data = app.try_state::<AssetStore>().map(|store| store.get(hash));
+    match data {
+        Some(Ok(data)) => http::Response::builder()
+            .header(header::CONTENT_TYPE, sniff_mime(&data))
+            // Content-addressed, so a URL's bytes never change
+            .header(header::CACHE_CONTROL, "public, max-age=31536000, immutable")
+            .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .body(data),
+        Some(Err(_)) => http::Response::builder().status(StatusCode::NOT_FOUND).body(Vec::new()),
+        None => http::Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .body(Vec::new()),
+    }
+}
+
+/// Handler for [`PROTOCOL`]: lets the webview load images straight from the
+/// store instead of holding them as data URLs. Reads happen off the main thread.
+pub fn handle_protocol<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: http::Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    let hash = request.uri().path().trim_start_matches('/').to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let response = protocol_response(&app, &hash).unwrap_or_else(|_| {
+            let mut response = http::Response::new(Vec::new());
+            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            response
+        });
+        responder.respond(response);
+    });
+}
+
+/// Store asset bytes and return their content hash; storing identical bytes
+/// again is a no-op that returns the same hash.
+#[tauri::command]
+pub fn store_asset(store: State<'_, AssetStore>, data: Vec<u8>) -> Result<String> {
+    store.store(&data)
+}
+
+#[tauri::command]
+pub fn get_asset(store: State<'_, AssetStore>, hash: String) -> Result<Response> {
+    Ok(Response::new(store.get(&hash)?))
+}
+
+/// Record the asset hashes a board currently uses; an empty list forgets the board.
+#[tauri::command]
+pub fn set_board_asset_references(
+    store: State<'_, AssetStore>,
+    board_id: String,
+    hashes: Vec<String>,
+) -> Result<()> {
+    store.set_references(board_id, hashes.into_iter().collect())
+}
+
+#[tauri::command]
+pub async fn collect_asset_garbage(app: AppHandle) -> Result<GcReport> {
+    tauri::async_runtime::spawn_blocking(move || app.state::<AssetStore>().collect_garbage())
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
@@ -0,0 +1,307 @@
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Mutex,
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use subtle::ConstantTimeEq;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{
+    document::BoardDocument,
+    error::{Error, Result},
+    export::{
+        pdf::{self, PdfExportOptions},
+        png::{self, PngExportOptions},
+        svg::{self, ExportLayout, SvgExportOptions},
+    },
+    http::{self, Request},
+    persist,
+    settings::Settings,
+    windows,
+};
+
+/// Where tools find the port and token of the running server. Only readable
+/// by the user, see [`persist::write_private`].
+const DISCOVERY_FILE: &str = "automation.json";
+const MAX_CONNECTIONS: usize = 16;
+const MAX_BODY: usize = 16 * 1024 * 1024;
+/// How long the board window gets to answer a forwarded request.
+const WINDOW_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Contents of `automation.json`.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationInfo {
+    /// `http://127.0.0.1:<port>/v1`
+    pub url: String,
+    /// Sent as `Authorization: Bearer <token>`
+    pub token: String,
+    pub pid: u32,
+}
+
+#[derive(Deserialize)]
+struct AutomationRequest {
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Request forwarded to the board window as `automation-request`.
+#[derive(Clone, Serialize)]
+struct WindowRequest<'a> {
+    id: u64,
+    method: &'a str,
+    params: &'a Value,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportParams {
+    format: ExportFormat,
+    path: String,
+    #[serde(default)]
+    pixel_ratio: Option<f32>,
+    /// Saved .boardkit file to read image assets from
+    #[serde(default)]
+    board_path: Option<String>,
+    /// Background, padding and trimming of PNG and SVG exports
+    #[serde(flatten)]
+    layout: ExportLayout,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ExportFormat {
+    Png,
+    Svg,
+    Pdf,
+}
+
+struct Running {
+    info: AutomationInfo,
+    server: http::Server,
+}
+
+/// Local automation endpoint, so scripts and end-to-end tests can drive the
+/// app the way AppleScript or COM automation would. Off unless the
+/// `automationEnabled` setting is on.
+///
+/// Requests are `POST /v1` with `{"method": ..., "params": {...}}` and get
+/// `{"result": ...}` or `{"error": {"kind", "message"}}` back. The server only
+/// listens on the loopback interface and every request needs the token from
+/// `automation.json`, so web pages cannot reach it either.
+///
+/// Methods answered natively: `app.info` and
+/// `board.export {format: png|svg|pdf, path, pixelRatio?, boardPath?}`.
+/// Everything else is run by the focused board window, see
+/// `useAutomation.ts`: `board.document`, `board.open {path}`, `board.save`,
+/// `elements.list`, `elements.create {element}`, `elements.update {id, changes}`,
+/// `elements.move {id, x, y}`, `elements.delete {ids}`, `elements.group {ids}`,
+/// `elements.ungroup {groupId}` and `selection.set {ids}`.
+#[derive(Default)]
+pub struct Automation {
+    running: Mutex<Option<Running>>,
+    pending: Mutex<HashMap<u64, mpsc::SyncSender<Result<Value>>>>,
+    next_id: AtomicU64,
+}
+
+impl Automation {
+    /// Start or stop the server to match the setting.
+    pub fn apply(&self, app: &AppHandle, enabled: bool) -> Result<()> {
+        let running = self.running.lock().unwrap().is_some();
+        match (enabled, running) {
+            (true, false) => self.start(app),
+            (false, true) => {
+                self.stop(app);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn start(&self, app: &AppHandle) -> Result<()> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let port = listener.local_addr()?.port();
+        let info = AutomationInfo {
+            url: format!("http://127.0.0.1:{port}/v1"),
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            pid: std::process::id(),
+        };
+        persist::write_private(&discovery_file(app)?, &serde_json::to_vec_pretty(&info)?)?;
+
+        let (app, token) = (app.clone(), info.token.clone());
+        let busy = |stream: &TcpStream| {
+            respond(stream, "503 Service Unavailable", &error_body(&Error::Automation("busy".to_string())))
+        };
+        let server = http::Server::start(listener, "automation", MAX_CONNECTIONS, busy, move |stream, request| {
+            handle(stream, request, &app, &token)
+        });
+        tracing::info!(port, "automation server started");
+        *self.running.lock().unwrap() = Some(Running { info, server });
+        Ok(())
+    }
+
+    /// Stop the server and remove the discovery file.
+    pub fn stop(&self, app: &AppHandle) {
+        let Some(running) = self.running.lock().unwrap().take() else {
+            return;
+        };
+        running.server.stop();
+        if let Ok(path) = discovery_file(app) {
+            let _ = std::fs::remove_file(path);
+        }
+        tracing::info!("automation server stopped");
+    }
+
+    fn info(&self) -> Option<AutomationInfo> {
+        self.running.lock().unwrap().as_ref().map(|running| running.info.clone())
+    }
+
+    /// Run `method` in the board window and wait for its answer.
+    fn forward(&self, app: &AppHandle, method: &str, params: &Value) -> Result<Value> {
+        let window =
+            windows::target_window(app).ok_or_else(|| Error::Automation("no board window is open".to_string()))?;
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = mpsc::sync_channel(1);
+        self.pending.lock().unwrap().insert(id, sender);
+        let sent = window.emit_to(window.label(), "automation-request", WindowRequest { id, method, params });
+        let answer = sent.map_err(Error::from).and_then(|()| {
+            receiver.recv_timeout(WINDOW_TIMEOUT).map_err(|_| Error::Automation(format!("{method} timed out")))
+        });
+        self.pending.lock().unwrap().remove(&id);
+        answer?
+    }
+
+    fn respond(&self, id: u64, answer: Result<Value>) {
+        if let Some(sender) = self.pending.lock().unwrap().remove(&id) {
+            let _ = sender.try_send(answer);
+        }
+    }
+}
+
+fn discovery_file(app: &AppHandle) -> Result<PathBuf> {
+    persist::app_data_file(app, DISCOVERY_FILE)
+}
+
+/// Start the server at launch when automation was left on.
+pub fn start_if_enabled(app: &AppHandle) {
+    let enabled = app.state::<Settings>().get().automation_enabled;
+    if let Err(err) = app.state::<Automation>().apply(app, enabled) {
+        tracing::warn!("failed to start the automation server: {err}");
+    }
+}
+
+fn respond(stream: &TcpStream, status: &str, body: &Value) -> std::io::Result<()> {
+    http::respond(stream, status, "application/json", body.to_string().as_bytes(), false)
+}
+
+fn error_body(err: &Error) -> Value {
+    json!({ "error": err })
+}
+
+fn handle(stream: &TcpStream, mut request: Request<'_>, app: &AppHandle, expected: &str) -> std::io::Result<()> {
+    if request.target != "/v1" {
+        return respond(stream, "404 Not Found", &error_body(&Error::NotFound(request.target)));
+    }
+    if request.method != "POST" {
+        return respond(stream, "405 Method Not Allowed", &error_body(&Error::Automation("use POST".to_string())));
+    }
+    // Constant-time, so response timings don't give the token away
+    let token = request.header("authorization").and_then(|header| header.strip_prefix("Bearer ")).unwrap_or_default();
+    if !bool::from(token.as_bytes().ct_eq(expected.as_bytes())) {
+        let err = Error::PermissionDenied("missing or wrong token".to_string());
+        return respond(stream, "401 Unauthorized", &error_body(&err));
+    }
+    let content_length = request.content_length();
+    if content_length > MAX_BODY {
+        let err = Error::TooLarge {
+            size: content_length as u64,
+            max: MAX_BODY as u64,
+        };
+        return respond(stream, "413 Payload Too Large", &error_body(&err));
+    }
+
+    let body = request.read_body(content_length)?;
+    let request: AutomationRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(err) => return respond(stream, "400 Bad Request", &error_body(&Error::from(err))),
+    };
+    tracing::debug!(method = request.method, "automation request");
+    match execute(app, &request.method, &request.params) {
+        Ok(result) => respond(stream, "200 OK", &json!({ "result": result })),
+        Err(err) => respond(stream, "200 OK", &error_body(&err)),
+    }
+}
+
+fn execute(app: &AppHandle, method: &str, params: &Value) -> Result<Value> {
+    let automation = app.state::<Automation>();
+    match method {
+        "app.info" => Ok(json!({
+            "version": app.package_info().version.to_string(),
+            "windows": windows::open_windows(app).into_iter().map(|(label, _)| label).collect::<Vec<_>>(),
+        })),
+        "board.export" => {
+            let params: ExportParams = serde_json::from_value(params.clone())?;
+            let document = automation.forward(app, "board.document", &Value::Null)?;
+            let document: BoardDocument = serde_json::from_value(document)?;
+            export(&document, params)?;
+            Ok(Value::Null)
+        }
+        _ => automation.forward(app, method, params),
+    }
+}
+
+fn export(document: &BoardDocument, params: ExportParams) -> Result<()> {
+    let path = Path::new(&params.path);
+    let bytes = match params.format {
+        ExportFormat::Svg => {
+            let options = SvgExportOptions {
+                board_path: params.board_path,
+                layout: params.layout,
+                ..SvgExportOptions::default()
+            };
+            return svg::write_svg(document, &params.path, options);
+        }
+        ExportFormat::Png => {
+            let defaults = PngExportOptions::default();
+            let options = PngExportOptions {
+                board_path: params.board_path,
+                pixel_ratio: params.pixel_ratio.unwrap_or(defaults.pixel_ratio),
+                layout: params.layout,
+            };
+            png::render_png(document, &options)?
+        }
+        ExportFormat::Pdf => {
+            let options = PdfExportOptions {
+                board_path: params.board_path,
+                ..PdfExportOptions::default()
+            };
+            pdf::render_pdf(document, &options)?
+        }
+    };
+    persist::write_atomic(path, &bytes).map_err(|err| Error::from_io(err, &params.path))
+}
+
+/// Where tools find the running server, if automation is on.
+#[tauri::command]
+pub fn get_automation_status(automation: State<'_, Automation>) -> Option<AutomationInfo> {
+    automation.info()
+}
+
+/// Answer of the board window to an `automation-request`.
+#[tauri::command]
+pub fn automation_respond(automation: State<'_, Automation>, id: u64, result: Option<Value>, error: Option<String>) {
+    let answer = match error {
+        Some(message) => Err(Error::Automation(message)),
+        None => Ok(result.unwrap_or(Value::Null)),
+    };
+    automation.respond(id, answer);
+}
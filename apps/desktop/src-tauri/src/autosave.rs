@@ -0,0 +1,186 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    error::{Error, Result},
+    migrations,
+    persist,
+    power::Power,
+    settings::Settings,
+};
+
+const RECOVERY_DIR: &str = "recovery";
+
+/// A recovery file on disk: the last autosaved state of a board with unsaved changes.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecoveryEntry {
+    board_id: String,
+    source_path: Option<String>,
+    saved_at: u64,
+    document: Value,
+}
+
+/// Metadata about a recovery file left behind by a previous session.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveredBoard {
+    pub board_id: String,
+    pub source_path: Option<String>,
+    pub saved_at: u64,
+    pub title: Option<String>,
+}
+
+impl From<&RecoveryEntry> for RecoveredBoard {
+    fn from(entry: &RecoveryEntry) -> Self {
+        Self {
+            board_id: entry.board_id.clone(),
+            source_path: entry.source_path.clone(),
+            saved_at: entry.saved_at,
+            title: entry.document["meta"]["title"].as_str().map(str::to_string),
+        }
+    }
+}
+
+struct Inner {
+    dir: PathBuf,
+    pending: Mutex<HashMap<String, RecoveryEntry>>,
+    // Serializes disk writes against clears so a flush can't resurrect a cleared file
+    io_lock: Mutex<()>,
+}
+
+impl Inner {
+    fn entry_path(&self, board_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", persist::sanitize_file_stem(board_id)))
+    }
+
+    fn flush(&self) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        let _io = self.io_lock.lock().unwrap();
+        for (board_id, entry) in pending {
+            if let Err(err) = persist::write_json(&self.entry_path(&board_id), &entry) {
+                tracing::error!("failed to write recovery file for {board_id}: {err}");
+            }
+        }
+    }
+
+    fn clear(&self, board_id: &str) -> Result<()> {
+        self.pending.lock().unwrap().remove(board_id);
+        let _io = self.io_lock.lock().unwrap();
+        match fs::remove_file(self.entry_path(board_id)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Periodically persists queued board state to the recovery directory so
+/// unsaved work survives a crash.
+pub struct Autosave {
+    inner: Arc<Inner>,
+    recovered: Mutex<Vec<RecoveredBoard>>,
+}
+
+impl Autosave {
+    /// Open the recovery directory and collect files orphaned by the previous session.
+    pub fn init(app: &AppHandle) -> Result<Self> {
+        let dir = app.path().app_data_dir()?.join(RECOVERY_DIR);
+        fs::create_dir_all(&dir)?;
+
+        let mut recovered: Vec<RecoveredBoard> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| fs::read(entry.path()).ok())
+            .filter_map(|bytes| serde_json::from_slice::<RecoveryEntry>(&bytes).ok())
+            .map(|entry| RecoveredBoard::from(&entry))
+            .collect();
+        recovered.sort_by_key(|board| std::cmp::Reverse(board.saved_at));
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                dir,
+                pending: Mutex::new(HashMap::new()),
+                io_lock: Mutex::new(()),
+            }),
+            recovered: Mutex::new(recovered),
+        })
+    }
+
+    /// The interval is read every tick so settings changes apply without a restart.
+    pub fn spawn_worker(&self, app: &AppHandle) {
+        let inner = Arc::clone(&self.inner);
+        let app = app.clone();
+        thread::spawn(move || loop {
+            let interval = app.state::<Settings>().get().autosave_interval_secs;
+            thread::sleep(Duration::from_secs(interval));
+            // Held back on battery saver and while asleep; quitting or going to sleep still flushes
+            if !app.try_state::<Power>().is_some_and(|power| power.autosave_paused()) {
+                inner.flush();
+            }
+        });
+    }
+
+    /// Write any queued state immediately (used on exit).
+    pub fn flush(&self) {
+        self.inner.flush();
+    }
+
+    pub fn recovered(&self) -> Vec<RecoveredBoard> {
+        self.recovered.lock().unwrap().clone()
+    }
+
+    /// Drop what was queued or written for a board, whose changes were saved
+    /// or thrown away.
+    pub fn discard(&self, board_id: &str) -> Result<()> {
+        self.recovered.lock().unwrap().retain(|board| board.board_id != board_id);
+        self.inner.clear(board_id)
+    }
+}
+
+/// Queue the current state of a board; it is written on the next autosave tick.
+#[tauri::command]
+pub fn queue_autosave(
+    autosave: State<'_, Autosave>,
+    board_id: String,
+    source_path: Option<String>,
+    document: Value,
+) {
+    let entry = RecoveryEntry {
+        board_id: board_id.clone(),
+        source_path,
+        saved_at: persist::now_millis(),
+        document,
+    };
+    autosave.inner.pending.lock().unwrap().insert(board_id, entry);
+}
+
+/// Drop the recovery file for a board once its changes are saved or the
+/// user declines to restore it.
+#[tauri::command]
+pub fn clear_autosave(autosave: State<'_, Autosave>, board_id: String) -> Result<()> {
+    autosave.discard(&board_id)
+}
+
+#[tauri::command]
+pub fn list_recovered_boards(autosave: State<'_, Autosave>) -> Vec<RecoveredBoard> {
+    autosave.recovered()
+}
+
+#[tauri::command]
+pub fn restore_recovered_board(autosave: State<'_, Autosave>, board_id: String) -> Result<Value> {
+    let path = autosave.inner.entry_path(&board_id);
+    let bytes = fs::read(&path).map_err(|err| Error::from_io(err, &board_id))?;
+    let mut entry: RecoveryEntry = serde_json::from_slice(&bytes)?;
+    migrations::migrate(&mut entry.document)?;
+    Ok(entry.document)
+}
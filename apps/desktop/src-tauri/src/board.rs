@@ -0,0 +1,801 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{self, Cursor, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tauri::{ipc::Response, Emitter, Window};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    document::{Asset, CURRENT_DOCUMENT_VERSION},
+    encryption,
+    error::{Error, Result},
+    journal,
+    migrations::{self, AppliedMigration},
+    persist, watcher,
+};
+
+const BOARD_JSON_NAME: &str = "board.json";
+const MANIFEST_NAME: &str = "manifest.json";
+const ASSETS_FOLDER: &str = "assets/";
+const MANIFEST_FORMAT_VERSION: u32 = 1;
+/// Matches `MAX_IMAGE_SIZE` in `@boardkit/core`.
+pub const MAX_ASSET_SIZE: u64 = 10 * 1024 * 1024;
+/// Image types the board can display, with the extension used inside the container.
+const SUPPORTED_IMAGE_TYPES: &[(&str, &str)] = &[
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("image/svg+xml", "svg"),
+];
+pub const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024; // 50MB, matches the frontend import limit
+
+fn check_size(size: u64) -> Result<()> {
+    if size > MAX_FILE_SIZE {
+        return Err(Error::TooLarge {
+            size,
+            max: MAX_FILE_SIZE,
+        });
+    }
+    Ok(())
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::InvalidBoard(message.into())
+}
+
+fn read_entry<R: Read + io::Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<Vec<u8>> {
+    let entry = archive.by_name(name).map_err(|_| invalid(format!("missing {name}")))?;
+    check_size(entry.size())?;
+
+    // Bound the read so a forged entry size can't inflate past the limit
+    let mut data = Vec::new();
+    entry.take(MAX_FILE_SIZE + 1).read_to_end(&mut data)?;
+    check_size(data.len() as u64)?;
+    Ok(data)
+}
+
+fn read_manifest<R: Read + io::Seek>(archive: &mut ZipArchive<R>) -> Option<Manifest> {
+    serde_json::from_slice(&read_entry(archive, MANIFEST_NAME).ok()?).ok()
+}
+
+pub fn new_board_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Stable id recorded in a container's manifest.
+pub fn board_id(bytes: &[u8]) -> Option<String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).ok()?;
+    read_manifest(&mut archive)?.board_id
+}
+
+/// The container's id, or a new one for files saved before boards had ids.
+fn board_id_or_new(bytes: &[u8]) -> String {
+    board_id(bytes).unwrap_or_else(new_board_id)
+}
+
+/// Stable id of the saved board at `path`, read from its manifest without
+/// loading the rest of the container. `None` for encrypted boards and files
+/// saved before boards had ids.
+pub fn read_board_id(path: &str) -> Option<String> {
+    let file = fs::File::open(path).ok()?;
+    let mut archive = ZipArchive::new(io::BufReader::new(file)).ok()?;
+    read_manifest(&mut archive)?.board_id
+}
+
+fn has_journal(bytes: &[u8]) -> bool {
+    ZipArchive::new(Cursor::new(bytes)).is_ok_and(|archive| archive.file_names().any(journal::is_entry))
+}
+
+/// Parse `board.json` out of a .boardkit ZIP container, with its journal
+/// replayed, without validating it.
+fn read_document(bytes: &[u8]) -> Result<Value> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|_| invalid("not a ZIP container"))?;
+    let json = read_entry(&mut archive, BOARD_JSON_NAME)?;
+    let mut document = serde_json::from_slice(&json).map_err(|_| invalid("corrupted JSON"))?;
+
+    let mut entries: Vec<String> = archive
+        .file_names()
+        .filter(|name| journal::is_entry(name))
+        .map(str::to_string)
+        .collect();
+    entries.sort();
+    for name in entries {
+        journal::apply(&mut document, &read_entry(&mut archive, &name)?)?;
+    }
+    Ok(document)
+}
+
+/// Check that `bytes` is a .boardkit ZIP container holding a structurally
+/// valid `board.json`, and return the parsed document.
+pub fn validate_container(bytes: &[u8]) -> Result<Value> {
+    let document = read_document(bytes)?;
+    validate_document(&document)?;
+    Ok(document)
+}
+
+/// Copy the container with the given entries added or replaced. Everything
+/// else is copied without recompressing, except JSON stored uncompressed:
+/// the frontend leaves compression to the save worker, and images gain
+/// nothing from it. The journal is dropped, so the caller must write a
+/// `board.json` with it replayed (see `finalize_container`).
+fn rewrite_container(bytes: &[u8], entries: &BTreeMap<String, Vec<u8>>) -> Result<Vec<u8>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|_| invalid("not a ZIP container"))?;
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, data) in entries {
+        writer.start_file(name.as_str(), options).map_err(io::Error::from)?;
+        writer.write_all(data)?;
+    }
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index).map_err(io::Error::from)?;
+        if entries.contains_key(entry.name()) || journal::is_entry(entry.name()) {
+            continue;
+        }
+        if entry.compression() == CompressionMethod::Stored && entry.name().ends_with(".json") {
+            let name = entry.name().to_string();
+            drop(entry);
+            let mut data = Vec::new();
+            archive.by_index(index).map_err(io::Error::from)?.take(MAX_FILE_SIZE + 1).read_to_end(&mut data)?;
+            writer.start_file(name, options).map_err(io::Error::from)?;
+            writer.write_all(&data)?;
+        } else {
+            writer.raw_copy_file(entry).map_err(io::Error::from)?;
+        }
+    }
+    Ok(writer.finish().map_err(io::Error::from)?.into_inner())
+}
+
+/// Upgrade the container's document to the current schema version, returning
+/// the (possibly rewritten) container and the migrations that ran.
+pub fn migrate_container(bytes: Vec<u8>) -> Result<(Vec<u8>, Vec<AppliedMigration>)> {
+    let mut document = read_document(&bytes)?;
+    let applied = migrations::migrate(&mut document)?;
+    validate_document(&document)?;
+    // A journal is folded in too, so the frontend only ever sees board.json
+    if applied.is_empty() && !has_journal(&bytes) {
+        return Ok((bytes, applied));
+    }
+    let board_id = board_id_or_new(&bytes);
+    Ok((finalize_container(&bytes, &document, true, board_id)?, applied))
+}
+
+/// Parse a container's document and migrate it to the current schema,
+/// returning the migrations that ran.
+pub fn read_migrated(bytes: &[u8]) -> Result<(Value, Vec<AppliedMigration>)> {
+    let mut document = read_document(bytes)?;
+    let applied = migrations::migrate(&mut document)?;
+    validate_document(&document)?;
+    Ok((document, applied))
+}
+
+/// Read a saved board's document, migrated to the current schema.
+pub fn read_board_document(path: &str) -> Result<Value> {
+    let mut document = read_document(&read_board(path)?)?;
+    migrations::migrate(&mut document)?;
+    validate_document(&document)?;
+    Ok(document)
+}
+
+fn validate_document(document: &Value) -> Result<()> {
+    let Some(root) = document.as_object() else {
+        return Err(invalid("document must be an object"));
+    };
+    if !root.get("version").is_some_and(Value::is_u64) {
+        return Err(invalid("missing document version"));
+    }
+    if !root.get("meta").is_some_and(Value::is_object) {
+        return Err(invalid("missing document meta"));
+    }
+    if !root.get("board").is_some_and(Value::is_object) {
+        return Err(invalid("missing board state"));
+    }
+    Ok(())
+}
+
+/// Read a file from disk, refusing anything over the board size limit.
+pub fn read_board(path: &str) -> Result<Vec<u8>> {
+    recover_append(Path::new(path)).map_err(|err| Error::from_io(err, path))?;
+    let metadata = fs::metadata(path).map_err(|err| Error::from_io(err, path))?;
+    check_size(metadata.len())?;
+    fs::read(path).map_err(|err| Error::from_io(err, path))
+}
+
+/// Asset id of an `assets/<id>.<ext>` container entry.
+fn asset_id_for_entry(name: &str) -> Option<&str> {
+    let filename = name.strip_prefix(ASSETS_FOLDER).filter(|filename| !filename.is_empty())?;
+    match filename.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => Some(stem),
+        _ => Some(filename),
+    }
+}
+
+pub fn extension_for_mime(mime_type: &str) -> Option<&'static str> {
+    SUPPORTED_IMAGE_TYPES
+        .iter()
+        .find(|(mime, _)| *mime == mime_type)
+        .map(|(_, extension)| *extension)
+}
+
+/// MIME type of a supported image, judged by file extension.
+pub fn image_mime_for_path(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    let extension = if extension == "jpeg" { "jpg" } else { extension.as_str() };
+    SUPPORTED_IMAGE_TYPES
+        .iter()
+        .find(|(_, ext)| *ext == extension)
+        .map(|(mime, _)| *mime)
+}
+
+/// Read the embedded assets of a .boardkit file, keyed by asset id
+/// (`assets/<id>.<ext>` in the container).
+pub fn read_assets(path: &str) -> Result<BTreeMap<String, Vec<u8>>> {
+    let bytes = read_board(path)?;
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|_| invalid("not a ZIP container"))?;
+    let mut assets = BTreeMap::new();
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index).map_err(|err| invalid(err.to_string()))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(asset_id) = asset_id_for_entry(entry.name()).map(str::to_string) else {
+            continue;
+        };
+        check_size(entry.size())?;
+        let mut data = Vec::new();
+        entry.take(MAX_FILE_SIZE + 1).read_to_end(&mut data)?;
+        check_size(data.len() as u64)?;
+        assets.insert(asset_id, data);
+    }
+    Ok(assets)
+}
+
+// ============================================================================
+// Manifest
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestAsset {
+    pub id: String,
+    pub path: String,
+    pub mime_type: String,
+    pub size: u64,
+}
+
+/// `manifest.json`: a table of contents for tools that read the container
+/// without parsing the whole document. Contains no timestamps, so saving an
+/// unchanged board produces the same bytes.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub format: String,
+    pub format_version: u32,
+    pub document_version: u32,
+    pub generator: String,
+    /// Assigned when the board is created and kept across renames and moves,
+    /// so links and recent boards can find it again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub board_id: Option<String>,
+    pub assets: Vec<ManifestAsset>,
+}
+
+fn build_manifest(bytes: &[u8], document: &Value, board_id: String) -> Result<Manifest> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|_| invalid("not a ZIP container"))?;
+    let registry = document.pointer("/assets/assets");
+    let mut assets = Vec::new();
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index).map_err(io::Error::from)?;
+        let Some(asset_id) = asset_id_for_entry(entry.name()) else {
+            continue;
+        };
+        let mime_type = registry
+            .and_then(|registry| registry.get(asset_id))
+            .and_then(|asset| asset.get("mimeType"))
+            .and_then(Value::as_str)
+            .unwrap_or("application/octet-stream");
+        assets.push(ManifestAsset {
+            id: asset_id.to_string(),
+            path: entry.name().to_string(),
+            mime_type: mime_type.to_string(),
+            size: entry.size(),
+        });
+    }
+    assets.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(Manifest {
+        format: "boardkit".to_string(),
+        format_version: MANIFEST_FORMAT_VERSION,
+        document_version: migrations::document_version(document).unwrap_or(CURRENT_DOCUMENT_VERSION),
+        generator: format!("Boardkit {}", env!("CARGO_PKG_VERSION")),
+        board_id: Some(board_id),
+        assets,
+    })
+}
+
+/// Regenerate `manifest.json` (and optionally `board.json`) inside a container.
+/// `board.json` is always written when the container has a journal to fold in.
+fn finalize_container(bytes: &[u8], document: &Value, write_document: bool, board_id: String) -> Result<Vec<u8>> {
+    let mut entries = BTreeMap::new();
+    if write_document || has_journal(bytes) {
+        entries.insert(BOARD_JSON_NAME.to_string(), serde_json::to_vec(document)?);
+    }
+    let manifest = build_manifest(bytes, document, board_id)?;
+    entries.insert(MANIFEST_NAME.to_string(), serde_json::to_vec_pretty(&manifest)?);
+    rewrite_container(bytes, &entries)
+}
+
+/// A fresh container holding just `document` and its manifest.
+pub fn create_container(document: &Value) -> Result<Vec<u8>> {
+    let empty = ZipWriter::new(Cursor::new(Vec::new()))
+        .finish()
+        .map_err(io::Error::from)?
+        .into_inner();
+    finalize_container(&empty, document, true, new_board_id())
+}
+
+fn edit_container_as(bytes: &[u8], edit: impl FnOnce(&mut Value), board_id: String) -> Result<(Vec<u8>, Value)> {
+    let mut document = read_document(bytes)?;
+    migrations::migrate(&mut document)?;
+    edit(&mut document);
+    validate_document(&document)?;
+    Ok((finalize_container(bytes, &document, true, board_id)?, document))
+}
+
+/// Edit the document inside a container, keeping its assets.
+pub fn edit_container(bytes: &[u8], edit: impl FnOnce(&mut Value)) -> Result<(Vec<u8>, Value)> {
+    edit_container_as(bytes, edit, board_id_or_new(bytes))
+}
+
+/// Container of a board merged from two versions: `ours` with `document` in
+/// it, plus the asset files only `theirs` has.
+pub fn merge_containers(ours: &[u8], theirs: &[u8], document: &Value) -> Result<Vec<u8>> {
+    let present: BTreeSet<String> = ZipArchive::new(Cursor::new(ours))
+        .map_err(|_| invalid("not a ZIP container"))?
+        .file_names()
+        .map(str::to_string)
+        .collect();
+    let mut archive = ZipArchive::new(Cursor::new(theirs)).map_err(|_| invalid("not a ZIP container"))?;
+    let mut entries = BTreeMap::new();
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index).map_err(|err| invalid(err.to_string()))?;
+        let name = entry.name().to_string();
+        if entry.is_dir() || asset_id_for_entry(&name).is_none() || present.contains(&name) {
+            continue;
+        }
+        check_size(entry.size())?;
+        let mut data = Vec::new();
+        entry.take(MAX_FILE_SIZE + 1).read_to_end(&mut data)?;
+        check_size(data.len() as u64)?;
+        entries.insert(name, data);
+    }
+    validate_document(document)?;
+    finalize_container(&rewrite_container(ours, &entries)?, document, true, board_id_or_new(ours))
+}
+
+/// Like `edit_container`, for a copy that becomes a board of its own and so
+/// gets a new id.
+pub fn copy_container(bytes: &[u8], edit: impl FnOnce(&mut Value)) -> Result<(Vec<u8>, Value)> {
+    edit_container_as(bytes, edit, new_board_id())
+}
+
+/// Edit the document of a saved board in place, keeping its assets.
+pub fn update_board_document(path: &str, edit: impl FnOnce(&mut Value)) -> Result<Value> {
+    let (bytes, document) = edit_container(&read_board(path)?, edit)?;
+    write_board(path, &bytes)?;
+    Ok(document)
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BoardMigrated {
+    path: String,
+    migrations: Vec<AppliedMigration>,
+}
+
+/// Tell the window a board was upgraded while loading.
+pub fn emit_migrated(window: &Window, path: String, migrations: Vec<AppliedMigration>) {
+    if !migrations.is_empty() {
+        let _ = window.emit_to(window.label(), "board-migrated", BoardMigrated { path, migrations });
+    }
+}
+
+/// Read a .boardkit file, migrate it to the current schema and return the
+/// container bytes. Emits `board-migrated` when an older file was upgraded.
+#[tauri::command]
+pub fn load_board(window: Window, path: String) -> Result<Response> {
+    let bytes = read_board(&path)?;
+    if encryption::is_encrypted(&bytes) {
+        return Err(Error::PasswordRequired(path));
+    }
+    let (bytes, migrations) = migrate_container(bytes)?;
+    tracing::info!(path, migrations = migrations.len(), "board loaded");
+    emit_migrated(&window, path, migrations);
+    Ok(Response::new(bytes))
+}
+
+pub fn write_board(path: &str, bytes: &[u8]) -> Result<()> {
+    check_size(bytes.len() as u64)?;
+    watcher::expect_write(Path::new(path));
+    persist::write_atomic(Path::new(path), bytes).map_err(|err| Error::from_io(err, path))
+}
+
+/// Sibling file holding what an append overwrites: the board's length
+/// before it, the length of its old central directory, then the directory.
+fn append_undo_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".append.tmp");
+    path.with_file_name(name)
+}
+
+fn encode_append_undo(len: u64, tail: &[u8]) -> Vec<u8> {
+    let mut undo = Vec::with_capacity(16 + tail.len());
+    undo.extend_from_slice(&len.to_le_bytes());
+    undo.extend_from_slice(&(tail.len() as u64).to_le_bytes());
+    undo.extend_from_slice(tail);
+    undo
+}
+
+/// The board length and central directory of a complete undo file. One cut
+/// short was being written when the app stopped, before the board was touched.
+fn decode_append_undo(undo: &[u8]) -> Option<(u64, &[u8])> {
+    let len = u64::from_le_bytes(undo.get(..8)?.try_into().ok()?);
+    let tail_len = u64::from_le_bytes(undo.get(8..16)?.try_into().ok()?);
+    let tail = undo.get(16..)?;
+    (tail.len() as u64 == tail_len && tail_len <= len).then_some((len, tail))
+}
+
+/// Undo an append that didn't complete, putting the old central directory
+/// back and cutting off what was written after it.
+fn restore_append(board: &Path, undo_path: &Path) -> io::Result<()> {
+    let undo = fs::read(undo_path)?;
+    if let Some((len, tail)) = decode_append_undo(&undo) {
+        let mut file = fs::OpenOptions::new().write(true).open(board)?;
+        file.seek(SeekFrom::Start(len - tail.len() as u64))?;
+        file.write_all(tail)?;
+        file.set_len(len)?;
+        file.sync_all()?;
+    }
+    fs::remove_file(undo_path)
+}
+
+/// Roll back an append a crash interrupted, which left its undo file behind.
+fn recover_append(board: &Path) -> io::Result<()> {
+    let undo_path = append_undo_path(board);
+    if !undo_path.exists() {
+        return Ok(());
+    }
+    tracing::warn!(path = %board.display(), "rolling back an interrupted journal append");
+    watcher::expect_write(board);
+    restore_append(board, &undo_path)
+}
+
+/// Append a journal entry to the board at `path`, along with the images in
+/// `bytes` (the saved container) that the file doesn't have yet. Returns the
+/// number of bytes written, leaving the file untouched when there is nothing
+/// to add.
+///
+/// The entries go where the central directory was, followed by a new one, so
+/// only the additions and the directory are written. The old directory is
+/// kept in an undo file until the append is on disk: a failure midway rolls
+/// it back right away, a crash the next time the board is read.
+pub fn append_to_board(path: &str, bytes: &[u8], entry: Option<(&str, &[u8])>) -> Result<u64> {
+    append_to_board_through(path, bytes, entry, |file| file)
+}
+
+/// [`append_to_board`], writing through whatever `wrap` makes of the board
+/// file so tests can make writes fail.
+fn append_to_board_through<W: Read + Write + Seek>(
+    path: &str,
+    bytes: &[u8],
+    entry: Option<(&str, &[u8])>,
+    wrap: impl FnOnce(fs::File) -> W,
+) -> Result<u64> {
+    let board = Path::new(path);
+    recover_append(board).map_err(|err| Error::from_io(err, path))?;
+    let mut original = fs::File::open(board).map_err(|err| Error::from_io(err, path))?;
+    let before = original.metadata()?.len();
+    let archive = ZipArchive::new(io::BufReader::new(&original)).map_err(|_| invalid("not a ZIP container"))?;
+    let existing: Vec<String> = archive.file_names().map(str::to_string).collect();
+    // New entries overwrite the old central directory, which is rewritten after them
+    let rewritten_from = archive.central_directory_start();
+    drop(archive);
+    let mut saved = ZipArchive::new(Cursor::new(bytes)).map_err(|_| invalid("not a ZIP container"))?;
+    let added: Vec<usize> = (0..saved.len())
+        .filter(|&index| {
+            saved
+                .name_for_index(index)
+                .is_some_and(|name| asset_id_for_entry(name).is_some() && !existing.iter().any(|known| known == name))
+        })
+        .collect();
+    if added.is_empty() && entry.is_none() {
+        return Ok(0);
+    }
+    let growth: u64 = entry.map_or(0, |(_, data)| data.len() as u64)
+        + added
+            .iter()
+            .filter_map(|&index| saved.by_index_raw(index).ok().map(|entry| entry.compressed_size()))
+            .sum::<u64>();
+    check_size(before + growth)?;
+
+    let undo_path = append_undo_path(board);
+    let mut tail = Vec::new();
+    original.seek(SeekFrom::Start(rewritten_from))?;
+    original.read_to_end(&mut tail)?;
+    drop(original);
+    let undo = encode_append_undo(before, &tail);
+    let written_undo = fs::File::create(&undo_path).and_then(|mut file| {
+        file.write_all(&undo)?;
+        file.sync_all()
+    });
+    if let Err(err) = written_undo {
+        let _ = fs::remove_file(&undo_path);
+        return Err(Error::from_io(err, path));
+    }
+
+    watcher::expect_write(board);
+    let result = (|| {
+        let file = fs::OpenOptions::new().read(true).write(true).open(board)?;
+        let mut writer = ZipWriter::new_append(wrap(file)).map_err(io::Error::from)?;
+        for &index in &added {
+            writer
+                .raw_copy_file(saved.by_index_raw(index).map_err(io::Error::from)?)
+                .map_err(io::Error::from)?;
+        }
+        if let Some((name, data)) = entry {
+            let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+            writer.start_file(name, options).map_err(io::Error::from)?;
+            writer.write_all(data)?;
+        }
+        writer.finish().map_err(io::Error::from)?;
+        let file = fs::File::open(board)?;
+        file.sync_all()?;
+        Ok::<_, io::Error>(file.metadata()?.len().saturating_sub(rewritten_from))
+    })();
+    match result {
+        Ok(written) => {
+            fs::remove_file(&undo_path)?;
+            Ok(written)
+        }
+        Err(err) => {
+            if let Err(restore_err) = restore_append(board, &undo_path) {
+                tracing::error!(path, "failed to roll back a journal append: {restore_err}");
+            }
+            Err(Error::from_io(err, path))
+        }
+    }
+}
+
+/// Validate a container produced by the frontend for the board at `path` and
+/// refresh its manifest. The frontend doesn't keep the manifest, so the
+/// board's id is carried over from the file being replaced.
+pub fn finalize_payload(payload: &[u8], path: &str) -> Result<(Vec<u8>, Value)> {
+    check_size(payload.len() as u64)?;
+    let document = validate_container(payload)?;
+    let board_id = board_id(payload).or_else(|| read_board_id(path)).unwrap_or_else(new_board_id);
+    Ok((finalize_container(payload, &document, false, board_id)?, document))
+}
+
+/// Embed an image in a saved board and register it in the document.
+/// Content already present in the board is reused instead of stored twice.
+#[tauri::command]
+pub fn add_board_asset(path: String, filename: String, mime_type: String, data: Vec<u8>) -> Result<Asset> {
+    let extension =
+        extension_for_mime(&mime_type).ok_or_else(|| invalid(format!("unsupported image type {mime_type}")))?;
+    if data.len() as u64 > MAX_ASSET_SIZE {
+        return Err(Error::TooLarge {
+            size: data.len() as u64,
+            max: MAX_ASSET_SIZE,
+        });
+    }
+
+    let bytes = read_board(&path)?;
+    let mut document = validate_container(&bytes)?;
+    let hash = format!("{:x}", Sha256::digest(&data));
+    let registry = document
+        .as_object_mut()
+        .ok_or_else(|| invalid("document must be an object"))?
+        .entry("assets")
+        .or_insert_with(|| json!({ "assets": {} }))
+        .as_object_mut()
+        .ok_or_else(|| invalid("malformed asset registry"))?
+        .entry("assets")
+        .or_insert_with(|| json!({}));
+
+    let existing = registry
+        .as_object()
+        .and_then(|assets| assets.values().find(|asset| asset.get("hash").and_then(Value::as_str) == Some(&hash)));
+    if let Some(existing) = existing {
+        return Ok(serde_json::from_value(existing.clone())?);
+    }
+
+    // Content-derived ids keep re-adding the same image stable
+    let asset = Asset {
+        id: hash[..21].to_string(),
+        filename,
+        mime_type,
+        size: data.len() as u64,
+        hash,
+        created_at: persist::now_millis(),
+    };
+    registry
+        .as_object_mut()
+        .ok_or_else(|| invalid("malformed asset registry"))?
+        .insert(asset.id.clone(), serde_json::to_value(&asset)?);
+
+    let entries = BTreeMap::from([(format!("{ASSETS_FOLDER}{}.{extension}", asset.id), data)]);
+    let with_asset = rewrite_container(&bytes, &entries)?;
+    write_board(&path, &finalize_container(&with_asset, &document, true, board_id_or_new(&bytes))?)?;
+    Ok(asset)
+}
+
+/// One embedded image of a saved board, read straight from the file without
+/// loading the rest of the container.
+#[tauri::command]
+pub fn read_board_asset(path: String, asset_id: String) -> Result<Response> {
+    let file = fs::File::open(&path).map_err(|err| Error::from_io(err, &path))?;
+    let mut archive = ZipArchive::new(file).map_err(|_| invalid("not a ZIP container"))?;
+    let name = archive
+        .file_names()
+        .find(|name| asset_id_for_entry(name) == Some(asset_id.as_str()))
+        .map(str::to_string)
+        .ok_or_else(|| Error::NotFound(format!("{path}: asset {asset_id}")))?;
+    Ok(Response::new(read_entry(&mut archive, &name)?))
+}
+
+/// Copy an embedded asset out of a saved board to `destination`.
+#[tauri::command]
+pub fn extract_board_asset(path: String, asset_id: String, destination: String) -> Result<()> {
+    let data = read_assets(&path)?
+        .remove(&asset_id)
+        .ok_or_else(|| Error::NotFound(format!("{path}: asset {asset_id}")))?;
+    persist::write_atomic(Path::new(&destination), &data).map_err(|err| Error::from_io(err, &destination))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> Value {
+        json!({
+            "version": CURRENT_DOCUMENT_VERSION,
+            "meta": { "title": "Test", "createdAt": 0, "updatedAt": 0 },
+            "board": { "elements": [], "widgets": [] },
+        })
+    }
+
+    fn saved_board(dir: &Path) -> String {
+        let path = dir.join("board.boardkit");
+        fs::write(&path, create_container(&document()).unwrap()).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    /// A journal entry renaming the board.
+    fn entry(title: &str) -> Vec<u8> {
+        let mut renamed = document();
+        renamed["meta"]["title"] = json!(title);
+        serde_json::to_vec(&journal::diff(&document(), &renamed)).unwrap()
+    }
+
+    #[test]
+    fn appended_journal_is_replayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = saved_board(dir.path());
+        let bytes = fs::read(&path).unwrap();
+        let written = append_to_board(&path, &bytes, Some(("journal/00000001.json", &entry("Renamed")))).unwrap();
+        assert!(written > 0);
+        assert_eq!(read_board_document(&path).unwrap()["meta"]["title"], "Renamed");
+        assert!(!append_undo_path(Path::new(&path)).exists());
+    }
+
+    #[test]
+    fn nothing_to_append_leaves_the_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = saved_board(dir.path());
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!(append_to_board(&path, &bytes, None).unwrap(), 0);
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+    }
+
+    /// Board file that takes `room` more bytes, then fails like a full disk,
+    /// or like the app being killed when `crash` is set.
+    struct FullDisk {
+        file: fs::File,
+        room: usize,
+        crash: bool,
+    }
+
+    impl Read for FullDisk {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.file.read(buf)
+        }
+    }
+
+    impl Write for FullDisk {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.room == 0 {
+                // Unwinding drops the writer, whose finishing writes must not get through
+                if std::mem::take(&mut self.crash) {
+                    panic!("killed mid-append");
+                }
+                return Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"));
+            }
+            let written = self.file.write(&buf[..buf.len().min(self.room)])?;
+            self.room -= written;
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.file.flush()
+        }
+    }
+
+    impl Seek for FullDisk {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.file.seek(pos)
+        }
+    }
+
+    /// A board with one journal entry, titled "First", and its bytes.
+    fn journaled_board(dir: &Path) -> (String, Vec<u8>, Vec<u8>) {
+        let path = saved_board(dir);
+        let bytes = fs::read(&path).unwrap();
+        append_to_board(&path, &bytes, Some(("journal/00000001.json", &entry("First")))).unwrap();
+        let before = fs::read(&path).unwrap();
+        (path, bytes, before)
+    }
+
+    #[test]
+    fn failed_append_rolls_the_board_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, bytes, before) = journaled_board(dir.path());
+        let second = entry("Second");
+        let result = append_to_board_through(&path, &bytes, Some(("journal/00000002.json", &second)), |file| {
+            FullDisk { file, room: 40, crash: false }
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read(&path).unwrap(), before);
+        assert!(!append_undo_path(Path::new(&path)).exists());
+        assert_eq!(read_board_document(&path).unwrap()["meta"]["title"], "First");
+        append_to_board(&path, &bytes, Some(("journal/00000002.json", &second))).unwrap();
+        assert_eq!(read_board_document(&path).unwrap()["meta"]["title"], "Second");
+    }
+
+    #[test]
+    fn append_cut_short_by_a_crash_is_rolled_back_on_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, bytes, before) = journaled_board(dir.path());
+        let second = entry("Second");
+        let crashed = std::panic::catch_unwind(|| {
+            append_to_board_through(&path, &bytes, Some(("journal/00000002.json", &second)), |file| {
+                FullDisk { file, room: 40, crash: true }
+            })
+        });
+
+        assert!(crashed.is_err());
+        assert_ne!(fs::read(&path).unwrap(), before);
+        assert!(append_undo_path(Path::new(&path)).exists());
+        assert_eq!(read_board_document(&path).unwrap()["meta"]["title"], "First");
+        assert_eq!(fs::read(&path).unwrap(), before);
+        assert!(!append_undo_path(Path::new(&path)).exists());
+    }
+
+    /// The app stopped while writing the undo file, before touching the board.
+    #[test]
+    fn incomplete_undo_file_is_discarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, _, before) = journaled_board(dir.path());
+        let undo = encode_append_undo(before.len() as u64, &before[before.len() - 100..]);
+        fs::write(append_undo_path(Path::new(&path)), &undo[..undo.len() - 10]).unwrap();
+
+        assert_eq!(read_board_document(&path).unwrap()["meta"]["title"], "First");
+        assert_eq!(fs::read(&path).unwrap(), before);
+        assert!(!append_undo_path(Path::new(&path)).exists());
+    }
+}
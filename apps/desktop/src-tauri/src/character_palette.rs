@@ -0,0 +1,51 @@
+use tauri::AppHandle;
+
+use crate::error::{Error, Result};
+
+/// AppKit puts the palette next to the focused text field's caret and sends
+/// the picked character to it.
+#[cfg(target_os = "macos")]
+pub fn show(app: &AppHandle) -> Result<()> {
+    use objc2::MainThreadMarker;
+    use objc2_app_kit::NSApplication;
+
+    app.run_on_main_thread(|| {
+        if let Some(mtm) = MainThreadMarker::new() {
+            NSApplication::sharedApplication(mtm).orderFrontCharacterPalette(None);
+        }
+    })?;
+    Ok(())
+}
+
+/// The emoji panel has no public API, so press its Win+. shortcut; it opens
+/// at the caret of whatever has focus, which is still the board window.
+#[cfg(target_os = "windows")]
+pub fn show(_app: &AppHandle) -> Result<()> {
+    const SCRIPT: &str = "$k = Add-Type -PassThru -Name Keys -Namespace Boardkit -MemberDefinition \
+        '[DllImport(\"user32.dll\")] public static extern void keybd_event(byte k, byte s, uint f, UIntPtr e);'; \
+        $k::keybd_event(0x5B, 0, 0, [UIntPtr]::Zero); $k::keybd_event(0xBE, 0, 0, [UIntPtr]::Zero); \
+        $k::keybd_event(0xBE, 0, 2, [UIntPtr]::Zero); $k::keybd_event(0x5B, 0, 2, [UIntPtr]::Zero)";
+    std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-WindowStyle", "Hidden", "-Command", SCRIPT])
+        .spawn()?;
+    Ok(())
+}
+
+/// Linux desktops have no common palette that types into the focused field;
+/// the IBus emoji chooser comes closest, copying the pick to the clipboard.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn show(_app: &AppHandle) -> Result<()> {
+    match std::process::Command::new("ibus").arg("emoji").spawn() {
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            Err(Error::CharacterPalette("no emoji picker installed (install IBus)".to_string()))
+        }
+        Err(err) => Err(Error::Io(err)),
+    }
+}
+
+/// Open the system emoji and symbol picker for the focused text field.
+#[tauri::command]
+pub fn show_character_palette(app: AppHandle) -> Result<()> {
+    show(&app)
+}
@@ -0,0 +1,407 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    board, diff,
+    document::BoardDocument,
+    error::{Error, Result},
+    export::{
+        html::render_html,
+        markdown::{render_markdown, MarkdownExportOptions},
+        parse_svg,
+        pdf::{render_pdf, PdfExportOptions},
+        png::rasterize,
+        svg::{render_svg, write_svg, Resources, SvgExportOptions},
+    },
+    file_open, headless, merge, persist,
+    search::SearchIndex,
+};
+
+/// Must match `identifier` in tauri.conf.json: the CLI never starts Tauri, so
+/// it finds the app data dir the way Tauri does.
+const APP_IDENTIFIER: &str = "com.boardkit.app";
+const SEARCH_DB: &str = "search.sqlite";
+const DEFAULT_TITLE: &str = "Untitled Board";
+const DEFAULT_SEARCH_LIMIT: u32 = 20;
+
+const USAGE: &str = "\
+Usage:
+  boardkit export <file> [--format png|svg|pdf|html|md] [--output <path>] [--scale <ratio>]
+  boardkit new [<path>] [--title <title>]
+  boardkit search <query> [--dir <folder>] [--limit <count>] [--json]
+  boardkit diff <before> <after>
+  boardkit merge <base> <ours> <theirs> [--output <path>]
+  boardkit --headless-serve [--port <port>]
+
+Without a command, Boardkit opens normally.";
+
+enum Command {
+    Export {
+        file: String,
+        format: Option<Format>,
+        output: Option<PathBuf>,
+        scale: f32,
+    },
+    New {
+        path: Option<PathBuf>,
+        title: Option<String>,
+    },
+    Search {
+        query: String,
+        dir: Option<PathBuf>,
+        limit: u32,
+        json: bool,
+    },
+    Diff {
+        before: String,
+        after: String,
+    },
+    Merge {
+        base: String,
+        ours: String,
+        theirs: String,
+        output: Option<String>,
+    },
+    Serve {
+        port: Option<u16>,
+    },
+    Help,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Png,
+    Svg,
+    Pdf,
+    Html,
+    #[serde(alias = "md")]
+    Markdown,
+}
+
+impl Format {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "svg" => Some(Self::Svg),
+            "pdf" => Some(Self::Pdf),
+            "html" | "htm" => Some(Self::Html),
+            "md" | "markdown" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Svg => "image/svg+xml",
+            Self::Pdf => "application/pdf",
+            Self::Html => "text/html",
+            Self::Markdown => "text/markdown",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Svg => "svg",
+            Self::Pdf => "pdf",
+            Self::Html => "html",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+/// Run `boardkit <command>` when the process was started with one, before
+/// anything of the GUI exists, so CI can render boards on machines without a
+/// display. Returns the exit code, or `None` to start the app as usual.
+pub fn run() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match args.first().map(String::as_str) {
+        Some("export" | "new" | "search" | "diff" | "merge" | "--headless-serve" | "help" | "--help" | "-h") => {
+            parse(&args)
+        }
+        _ => return None,
+    };
+    attach_console();
+    let result = match command {
+        Ok(Command::Help) => {
+            println!("{USAGE}");
+            return Some(0);
+        }
+        Ok(command) => execute(command),
+        Err(message) => {
+            eprintln!("boardkit: {message}\n\n{USAGE}");
+            return Some(2);
+        }
+    };
+    Some(match result {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("boardkit: {err}");
+            1
+        }
+    })
+}
+
+/// Release builds on Windows have no console of their own; print to the one
+/// the command was typed in.
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    let _ = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
+
+fn parse(args: &[String]) -> std::result::Result<Command, String> {
+    let mut positional = Vec::new();
+    let mut options = std::collections::HashMap::new();
+    let mut flags = Vec::new();
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--json" => flags.push("json"),
+            "--help" | "-h" => return Ok(Command::Help),
+            name if name.starts_with("--") => {
+                let value = rest.next().ok_or_else(|| format!("{name} needs a value"))?;
+                options.insert(&name[2..], value.clone());
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+    let mut take = |name: &str| options.remove(name);
+
+    let command = match args[0].as_str() {
+        "export" => Command::Export {
+            file: positional.first().cloned().ok_or("export needs a board file")?,
+            format: take("format")
+                .map(|name| Format::parse(&name).ok_or(format!("unknown format {name}")))
+                .transpose()?,
+            output: take("output").map(PathBuf::from),
+            scale: take("scale")
+                .map(|scale| scale.parse().map_err(|_| format!("invalid scale {scale}")))
+                .transpose()?
+                .unwrap_or(2.0),
+        },
+        "new" => Command::New {
+            path: positional.first().map(PathBuf::from),
+            title: take("title"),
+        },
+        "search" => Command::Search {
+            query: (!positional.is_empty()).then(|| positional.join(" ")).ok_or("search needs a query")?,
+            dir: take("dir").map(PathBuf::from),
+            limit: take("limit")
+                .map(|limit| limit.parse().map_err(|_| format!("invalid limit {limit}")))
+                .transpose()?
+                .unwrap_or(DEFAULT_SEARCH_LIMIT),
+            json: flags.contains(&"json"),
+        },
+        "diff" => match positional.as_slice() {
+            [before, after] => Command::Diff {
+                before: before.clone(),
+                after: after.clone(),
+            },
+            _ => return Err("diff needs two board files".to_string()),
+        },
+        "merge" => match positional.as_slice() {
+            [base, ours, theirs] => Command::Merge {
+                base: base.clone(),
+                ours: ours.clone(),
+                theirs: theirs.clone(),
+                output: take("output"),
+            },
+            _ => return Err("merge needs the base, our and their board files".to_string()),
+        },
+        "--headless-serve" => Command::Serve {
+            port: take("port")
+                .map(|port| port.parse().map_err(|_| format!("invalid port {port}")))
+                .transpose()?,
+        },
+        _ => Command::Help,
+    };
+    if let Some(name) = options.keys().next() {
+        return Err(format!("unknown option --{name}"));
+    }
+    Ok(command)
+}
+
+fn execute(command: Command) -> Result<()> {
+    match command {
+        Command::Export {
+            file,
+            format,
+            output,
+            scale,
+        } => {
+            let format = format
+                .or_else(|| output.as_ref().and_then(|path| path.extension()?.to_str().and_then(Format::parse)))
+                .unwrap_or(Format::Png);
+            let output = output.unwrap_or_else(|| Path::new(&file).with_extension(format.extension()));
+            export(&file, format, &output, scale)?;
+            println!("{}", output.display());
+        }
+        Command::New { path, title } => {
+            let title = title.unwrap_or_else(|| {
+                let stem = path.as_ref().and_then(|path| path.file_stem()).map(|stem| stem.to_string_lossy());
+                stem.map_or_else(|| DEFAULT_TITLE.to_string(), |stem| stem.into_owned())
+            });
+            let path = path.unwrap_or_else(|| PathBuf::from(format!("{title}.boardkit")));
+            if path.exists() {
+                return Err(Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", path.display()),
+                )));
+            }
+            let document = serde_json::to_value(BoardDocument::new(title, persist::now_millis()))?;
+            board::write_board(&path.to_string_lossy(), &board::create_container(&document)?)?;
+            println!("{}", path.display());
+        }
+        Command::Search {
+            query,
+            dir,
+            limit,
+            json,
+        } => {
+            let index = match dir {
+                Some(dir) => index_folder(&dir)?,
+                None => {
+                    let path = app_data_dir().map(|dir| dir.join(SEARCH_DB)).filter(|path| path.exists());
+                    let path = path.ok_or_else(|| {
+                        Error::NotFound("search index; open Boardkit once or pass --dir <folder>".to_string())
+                    })?;
+                    SearchIndex::open_at(&path)?
+                }
+            };
+            let hits = index.search(&query, limit)?;
+            let mut out = std::io::stdout().lock();
+            if json {
+                writeln!(out, "{}", serde_json::to_string_pretty(&hits)?)?;
+            } else {
+                for hit in hits {
+                    writeln!(out, "{}\t{}\t{}", hit.path, hit.title, hit.snippet.replace(['\n', '\t'], " "))?;
+                }
+            }
+        }
+        Command::Diff { before, after } => {
+            let (before, after) = (board::read_board_document(&before)?, board::read_board_document(&after)?);
+            println!("{}", serde_json::to_string_pretty(&diff::diff_documents(&before, &after))?);
+        }
+        // Git merge driver style: the result replaces `ours` and conflicts fail the merge
+        Command::Merge {
+            base,
+            ours,
+            theirs,
+            output,
+        } => {
+            let result = merge::merge_files(&base, &ours, &theirs, Some(output.as_deref().unwrap_or(&ours)))?;
+            if !result.conflicts.is_empty() {
+                println!("{}", serde_json::to_string_pretty(&result.conflicts)?);
+                return Err(Error::MergeConflicts(result.conflicts.len()));
+            }
+        }
+        Command::Serve { port: Some(port) } => headless::serve_tcp(port)?,
+        Command::Serve { port: None } => headless::serve_stdio()?,
+        Command::Help => println!("{USAGE}"),
+    }
+    Ok(())
+}
+
+fn export(file: &str, format: Format, output: &Path, scale: f32) -> Result<()> {
+    let document: BoardDocument = serde_json::from_value(board::read_board_document(file)?)?;
+    let output_str = output.to_string_lossy().into_owned();
+    let bytes = match format {
+        Format::Svg => {
+            let options = SvgExportOptions {
+                board_path: Some(file.to_string()),
+                ..SvgExportOptions::default()
+            };
+            return write_svg(&document, &output_str, options);
+        }
+        // Pictures of the frames are written next to the Markdown file
+        Format::Markdown => {
+            let options = MarkdownExportOptions {
+                board_path: Some(file.to_string()),
+                scale,
+                ..MarkdownExportOptions::default()
+            };
+            render_markdown(&document, output, &options)?.into_bytes()
+        }
+        _ => render(&document, format, Some(file), scale)?,
+    };
+    persist::write_atomic(output, &bytes).map_err(|err| Error::from_io(err, &output_str))
+}
+
+/// Render a board with the native export pipeline. Images come from the
+/// saved board at `board_path`, if any.
+pub fn render(document: &BoardDocument, format: Format, board_path: Option<&str>, scale: f32) -> Result<Vec<u8>> {
+    let empty = || Error::Render("board is empty".to_string());
+    Ok(match format {
+        Format::Png => {
+            let svg = render_svg(document, &Resources::load(board_path, true)?).ok_or_else(empty)?;
+            rasterize(&parse_svg(&svg)?, scale)?
+        }
+        Format::Svg => render_svg(document, &Resources::load(board_path, true)?).ok_or_else(empty)?.into_bytes(),
+        Format::Pdf => {
+            let options = PdfExportOptions {
+                board_path: board_path.map(str::to_string),
+                ..PdfExportOptions::default()
+            };
+            render_pdf(document, &options)?
+        }
+        Format::Html => render_html(document, Resources::load(board_path, true)?).ok_or_else(empty)?.into_bytes(),
+        Format::Markdown => {
+            let path = Path::new(board_path.unwrap_or("board.md"));
+            let options = MarkdownExportOptions {
+                board_path: board_path.map(str::to_string),
+                images: false,
+                scale,
+            };
+            render_markdown(document, path, &options)?.into_bytes()
+        }
+    })
+}
+
+/// Index the boards under `dir` into a throwaway in-memory index.
+fn index_folder(dir: &Path) -> Result<SearchIndex> {
+    let index = SearchIndex::open_at(Path::new(":memory:"))?;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let dir_str = dir.to_string_lossy();
+        for entry in fs::read_dir(&dir).map_err(|err| Error::from_io(err, &dir_str))?.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if file_open::is_board_file(&path) {
+                let path = path.to_string_lossy();
+                match board::read_board_document(&path) {
+                    Ok(document) => index.index_document(&path, &document)?,
+                    Err(err) => eprintln!("boardkit: skipping {path}: {err}"),
+                }
+            }
+        }
+    }
+    Ok(index)
+}
+
+/// Tauri's `app_data_dir()`: the platform data dir joined with the identifier.
+fn app_data_dir() -> Option<PathBuf> {
+    let home = || std::env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| home().map(|home| home.join(".local/share")))
+    };
+    base.map(|base| base.join(APP_IDENTIFIER))
+}
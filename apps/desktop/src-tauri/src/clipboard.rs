@@ -0,0 +1,167 @@
+use std::{borrow::Cow, collections::HashSet, fs, sync::Mutex};
+
+use arboard::{Clipboard, ImageData};
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Response;
+
+use crate::{
+    board::{self, MAX_ASSET_SIZE},
+    document::BoardDocument,
+    error::{Error, Result},
+    export::{
+        parse_svg,
+        png::rasterize_pixmap,
+        svg::{render_svg, Resources},
+    },
+};
+
+/// A file copied in Finder/Explorer.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardFile {
+    path: String,
+    name: String,
+    /// Set for image types the board can embed
+    mime_type: Option<&'static str>,
+    size: u64,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+fn default_pixel_ratio() -> f32 {
+    2.0
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionPayload {
+    board_json: BoardDocument,
+    /// Element and widget ids to render; empty copies the whole board
+    #[serde(default)]
+    selection: Vec<String>,
+    /// Saved .boardkit file to read image assets from
+    #[serde(default)]
+    board_path: Option<String>,
+    #[serde(default = "default_pixel_ratio")]
+    pixel_ratio: f32,
+}
+
+/// On Linux the copying process serves clipboard contents itself, so one
+/// handle is kept for the app's lifetime rather than dropped after each copy.
+static CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
+
+fn with_clipboard<T>(f: impl FnOnce(&mut Clipboard) -> std::result::Result<T, arboard::Error>) -> Result<Option<T>> {
+    let mut guard = CLIPBOARD.lock().unwrap();
+    let clipboard = match guard.as_mut() {
+        Some(clipboard) => clipboard,
+        None => guard.insert(Clipboard::new().map_err(|err| Error::Clipboard(err.to_string()))?),
+    };
+    match f(clipboard) {
+        Ok(value) => Ok(Some(value)),
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(err) => Err(Error::Clipboard(err.to_string())),
+    }
+}
+
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgba))
+        .map_err(|err| Error::Clipboard(err.to_string()))?;
+    Ok(png)
+}
+
+/// Read the clipboard image (e.g. a screenshot) as PNG bytes. The response is
+/// empty when the clipboard holds no image.
+#[tauri::command]
+pub async fn read_clipboard_image() -> Result<Response> {
+    let png = tauri::async_runtime::spawn_blocking(|| {
+        let Some(image) = with_clipboard(|clipboard| clipboard.get_image())? else {
+            return Ok(Vec::new());
+        };
+        let png = encode_png(image.width as u32, image.height as u32, &image.bytes)?;
+        if png.len() as u64 > MAX_ASSET_SIZE {
+            return Err(Error::TooLarge {
+                size: png.len() as u64,
+                max: MAX_ASSET_SIZE,
+            });
+        }
+        Ok(png)
+    })
+    .await
+    .map_err(|err| Error::Clipboard(err.to_string()))??;
+    Ok(Response::new(png))
+}
+
+/// Files on the clipboard, in copy order. Entries that no longer exist are skipped.
+#[tauri::command]
+pub fn read_clipboard_files() -> Result<Vec<ClipboardFile>> {
+    let paths = with_clipboard(|clipboard| clipboard.get().file_list())?.unwrap_or_default();
+    Ok(paths
+        .into_iter()
+        .filter_map(|path| {
+            let metadata = fs::metadata(&path).ok().filter(|metadata| metadata.is_file())?;
+            Some(ClipboardFile {
+                mime_type: board::image_mime_for_path(&path),
+                name: path.file_name()?.to_string_lossy().into_owned(),
+                path: path.to_string_lossy().into_owned(),
+                size: metadata.len(),
+            })
+        })
+        .collect())
+}
+
+fn render_selection(payload: SelectionPayload, embed_fonts: bool) -> Result<String> {
+    let SelectionPayload {
+        board_json: mut document,
+        selection,
+        board_path,
+        ..
+    } = payload;
+    if !selection.is_empty() {
+        document.retain_items(&selection.iter().map(String::as_str).collect::<HashSet<_>>());
+    }
+    let resources = Resources::load(board_path.as_deref(), embed_fonts)?;
+    render_svg(&document, &resources).ok_or_else(|| Error::Render("nothing selected to copy".to_string()))
+}
+
+/// Render the selection natively and put it on the OS clipboard: PNG as an
+/// image for chat and slide apps, SVG as markup text for design tools.
+#[tauri::command]
+pub async fn copy_selection_as_image(format: ImageFormat, payload: SelectionPayload) -> Result<()> {
+    tauri::async_runtime::spawn_blocking(move || match format {
+        ImageFormat::Svg => {
+            let svg = render_selection(payload, true)?;
+            with_clipboard(|clipboard| clipboard.set_text(svg)).map(drop)
+        }
+        ImageFormat::Png => {
+            let pixel_ratio = payload.pixel_ratio;
+            let pixmap = rasterize_pixmap(&parse_svg(&render_selection(payload, false)?)?, pixel_ratio)?;
+            let rgba = pixmap
+                .pixels()
+                .iter()
+                .flat_map(|pixel| {
+                    let color = pixel.demultiply();
+                    [color.red(), color.green(), color.blue(), color.alpha()]
+                })
+                .collect();
+            let image = ImageData {
+                width: pixmap.width() as usize,
+                height: pixmap.height() as usize,
+                bytes: Cow::Owned(rgba),
+            };
+            with_clipboard(|clipboard| clipboard.set_image(image)).map(drop)
+        }
+    })
+    .await
+    .map_err(|err| Error::Clipboard(err.to_string()))?
+}
@@ -0,0 +1,162 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc, Mutex,
+};
+
+use serde::Deserialize;
+use tauri::{
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    AppHandle, LogicalPosition, Manager, State, Window,
+};
+
+use crate::error::{Error, Result};
+
+/// Ids of context menu items are `context:<menu>:<item>` so a click from a
+/// menu that was already replaced is ignored.
+pub const CONTEXT_ITEM_PREFIX: &str = "context:";
+/// Selections are delivered through the event loop slightly after the popup
+/// closes; anything later means the menu was dismissed.
+#[cfg(not(target_os = "linux"))]
+const SELECTION_GRACE: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// JSON description of a context menu entry, as sent by the frontend.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ContextMenuItem {
+    Item {
+        id: String,
+        label: String,
+        #[serde(default = "enabled")]
+        enabled: bool,
+        accelerator: Option<String>,
+    },
+    Check {
+        id: String,
+        label: String,
+        checked: bool,
+        #[serde(default = "enabled")]
+        enabled: bool,
+    },
+    Submenu {
+        label: String,
+        items: Vec<ContextMenuItem>,
+        #[serde(default = "enabled")]
+        enabled: bool,
+    },
+    Separator,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+struct Pending {
+    menu_id: u64,
+    sender: mpsc::Sender<String>,
+}
+
+/// The open context menu, if any. Only one can be shown at a time.
+#[derive(Default)]
+pub struct ContextMenus {
+    next_id: AtomicU64,
+    pending: Mutex<Option<Pending>>,
+}
+
+impl ContextMenus {
+    /// Route a clicked context item to the `show_context_menu` call waiting for it.
+    pub fn select(&self, id: &str) {
+        let Some((menu_id, item)) = id.split_once(':') else {
+            return;
+        };
+        let mut pending = self.pending.lock().unwrap();
+        if pending.as_ref().is_some_and(|pending| pending.menu_id.to_string() == menu_id) {
+            if let Some(pending) = pending.take() {
+                let _ = pending.sender.send(item.to_string());
+            }
+        }
+    }
+}
+
+fn build_items(
+    app: &AppHandle,
+    menu_id: u64,
+    items: &[ContextMenuItem],
+) -> Result<Vec<Box<dyn IsMenuItem<tauri::Wry>>>> {
+    let item_id = |id: &str| format!("{CONTEXT_ITEM_PREFIX}{menu_id}:{id}");
+    let mut built: Vec<Box<dyn IsMenuItem<tauri::Wry>>> = Vec::with_capacity(items.len());
+    for item in items {
+        match item {
+            ContextMenuItem::Item {
+                id,
+                label,
+                enabled,
+                accelerator,
+            } => built.push(Box::new(MenuItem::with_id(app, item_id(id), label, *enabled, accelerator.as_deref())?)),
+            ContextMenuItem::Check {
+                id,
+                label,
+                checked,
+                enabled,
+            } => built.push(Box::new(CheckMenuItem::with_id(
+                app,
+                item_id(id),
+                label,
+                *enabled,
+                *checked,
+                None::<&str>,
+            )?)),
+            ContextMenuItem::Submenu { label, items, enabled } => {
+                let children = build_items(app, menu_id, items)?;
+                let children: Vec<&dyn IsMenuItem<tauri::Wry>> = children.iter().map(|child| child.as_ref()).collect();
+                built.push(Box::new(Submenu::with_items(app, label, *enabled, &children)?));
+            }
+            ContextMenuItem::Separator => built.push(Box::new(PredefinedMenuItem::separator(app)?)),
+        }
+    }
+    Ok(built)
+}
+
+/// Show a native popup menu at `position` (logical pixels, relative to the
+/// window) and return the id of the chosen item, or `None` when dismissed.
+#[tauri::command]
+pub async fn show_context_menu(
+    app: AppHandle,
+    window: Window,
+    items: Vec<ContextMenuItem>,
+    position: LogicalPosition<f64>,
+) -> Result<Option<String>> {
+    if items.is_empty() {
+        return Ok(None);
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let context_menus: State<'_, ContextMenus> = app.state();
+        let menu_id = context_menus.next_id.fetch_add(1, Ordering::Relaxed);
+        let built = build_items(&app, menu_id, &items)?;
+        let built: Vec<&dyn IsMenuItem<tauri::Wry>> = built.iter().map(|item| item.as_ref()).collect();
+        let menu = Menu::with_items(&app, &built)?;
+
+        // Replacing the pending entry drops the previous sender, resolving that call with `None`
+        let (sender, receiver) = mpsc::channel();
+        *context_menus.pending.lock().unwrap() = Some(Pending { menu_id, sender });
+
+        // Blocks until the menu closes on macOS and Windows; returns immediately on Linux
+        window.popup_menu_at(&menu, position)?;
+
+        #[cfg(not(target_os = "linux"))]
+        let chosen = {
+            let chosen = receiver.recv_timeout(SELECTION_GRACE).ok();
+            let mut pending = context_menus.pending.lock().unwrap();
+            if pending.as_ref().is_some_and(|pending| pending.menu_id == menu_id) {
+                *pending = None;
+            }
+            chosen
+        };
+        // GTK gives no dismissal signal, so wait until an item is chosen or another menu opens
+        #[cfg(target_os = "linux")]
+        let chosen = receiver.recv().ok();
+
+        Ok(chosen)
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
@@ -0,0 +1,125 @@
+use std::{path::Path, sync::Mutex};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State, Url};
+
+use crate::{board, file_open, windows, workspace};
+
+pub const SCHEME: &str = "boardkit";
+
+/// Target of a `boardkit://open?board=<path>&element=<id>&id=<board id>` link.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardLink {
+    pub board: String,
+    pub element_id: Option<String>,
+    /// Finds the board again if it was renamed or moved since the link was made
+    #[serde(skip)]
+    pub board_id: Option<String>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    ready: bool,
+    pending: Vec<BoardLink>,
+}
+
+/// Links that arrived before the frontend was listening.
+#[derive(Default)]
+pub struct DeepLinks(Mutex<QueueState>);
+
+/// Read a `boardkit://open` URL without checking that its board exists.
+pub fn parse_board_link(url: &Url) -> Option<BoardLink> {
+    if url.scheme() != SCHEME || url.host_str() != Some("open") {
+        return None;
+    }
+    let mut link = BoardLink {
+        board: String::new(),
+        element_id: None,
+        board_id: None,
+    };
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "board" => link.board = value.into_owned(),
+            "element" => link.element_id = Some(value.into_owned()).filter(|id| !id.is_empty()),
+            "id" => link.board_id = Some(value.into_owned()).filter(|id| !id.is_empty()),
+            _ => {}
+        }
+    }
+    (!link.board.is_empty() || link.board_id.is_some()).then_some(link)
+}
+
+/// Links are clickable from any web page, so only existing board files are
+/// accepted. A board no longer at the linked path is looked up by its id.
+fn parse(app: &AppHandle, url: &Url) -> Option<BoardLink> {
+    let mut link = parse_board_link(url)?;
+    if let Some(board_id) = link.board_id.as_deref().filter(|_| !Path::new(&link.board).exists()) {
+        match workspace::resolve_board(app, board_id) {
+            Ok(Some(path)) => link.board = path,
+            Ok(None) => {}
+            Err(err) => tracing::warn!("failed to look up board {board_id}: {err}"),
+        }
+    }
+    let board = Path::new(&link.board);
+    (board.is_absolute() && file_open::is_board_file(board)).then_some(link)
+}
+
+/// Route links to the window showing their board, else the main window.
+pub fn handle_urls(app: &AppHandle, urls: Vec<Url>) {
+    for url in urls {
+        let Some(link) = parse(app, &url) else {
+            tracing::warn!("ignoring {url}");
+            continue;
+        };
+        if windows::focus_board(app, &link.board) {
+            if let Some(window) = windows::target_window(app) {
+                let _ = window.emit_to(window.label(), "open-deep-link", link);
+            }
+            continue;
+        }
+
+        let queue = app.state::<DeepLinks>();
+        let mut state = queue.0.lock().unwrap();
+        if !state.ready {
+            state.pending.push(link);
+            continue;
+        }
+        let Some(window) = windows::target_window(app) else {
+            // No board window is listening: queue it for a new main window
+            state.ready = false;
+            state.pending.push(link);
+            drop(state);
+            windows::reopen_main_window(app);
+            continue;
+        };
+        drop(state);
+        windows::focus_window(&window);
+        let _ = window.emit_to(window.label(), "open-deep-link", link);
+    }
+}
+
+pub fn board_link(path: &str, element_id: Option<&str>) -> Url {
+    let mut url = Url::parse(&format!("{SCHEME}://open")).expect("static URL is valid");
+    url.query_pairs_mut().append_pair("board", path);
+    if let Some(element_id) = element_id {
+        url.query_pairs_mut().append_pair("element", element_id);
+    }
+    if let Some(board_id) = board::read_board_id(path) {
+        url.query_pairs_mut().append_pair("id", &board_id);
+    }
+    url
+}
+
+/// Build a link that reopens `path`, optionally scrolled to an element.
+#[tauri::command]
+pub fn create_board_link(path: String, element_id: Option<String>) -> String {
+    board_link(&path, element_id.as_deref()).to_string()
+}
+
+/// Return links queued during launch; later ones arrive as `open-deep-link` events.
+#[tauri::command]
+pub fn take_pending_deep_links(queue: State<'_, DeepLinks>) -> Vec<BoardLink> {
+    let mut state = queue.0.lock().unwrap();
+    state.ready = true;
+    std::mem::take(&mut state.pending)
+}
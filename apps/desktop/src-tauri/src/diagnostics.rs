@@ -0,0 +1,192 @@
+use std::{
+    backtrace::Backtrace,
+    collections::VecDeque,
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::{layer::Context, Layer};
+
+use crate::{error::Result, persist};
+
+const DIAGNOSTICS_STORE: &str = "diagnostics.json";
+const CRASHES_DIR: &str = "crashes";
+const MAX_RECENT_EVENTS: usize = 50;
+
+/// The last log events, kept in memory so a crash report shows what led up to it.
+static RECENT_EVENTS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Tracing layer feeding [`RECENT_EVENTS`].
+pub struct RecentEvents;
+
+struct EventFormatter(String);
+
+impl Visit for EventFormatter {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0.push_str(&format!(" {value:?}"));
+        } else {
+            self.0.push_str(&format!(" {}={value:?}", field.name()));
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RecentEvents {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = EventFormatter(format!("{} {} {}", persist::now_millis(), metadata.level(), metadata.target()));
+        event.record(&mut line);
+        if let Ok(mut events) = RECENT_EVENTS.lock() {
+            if events.len() == MAX_RECENT_EVENTS {
+                events.pop_front();
+            }
+            events.push_back(line.0);
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct DiagnosticsSettings {
+    enabled: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CrashReport {
+    created_at: u64,
+    version: String,
+    os: String,
+    arch: String,
+    thread: Option<String>,
+    message: String,
+    location: Option<String>,
+    backtrace: String,
+    recent_events: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashSummary {
+    created_at: u64,
+    version: String,
+    message: String,
+}
+
+/// Opt-in crash reporting. When enabled, a panic writes a report to the
+/// crashes directory; nothing leaves the machine unless the user exports it.
+pub struct Diagnostics {
+    store_path: PathBuf,
+    crashes_dir: PathBuf,
+    enabled: Arc<AtomicBool>,
+}
+
+impl Diagnostics {
+    /// Load the setting and install the panic hook, which stays inert while
+    /// diagnostics are disabled.
+    pub fn install(app: &AppHandle) -> Result<Self> {
+        let store_path = persist::app_data_file(app, DIAGNOSTICS_STORE)?;
+        let settings: DiagnosticsSettings = persist::read_json(&store_path);
+        let crashes_dir = app.path().app_data_dir()?.join(CRASHES_DIR);
+        fs::create_dir_all(&crashes_dir)?;
+        let enabled = Arc::new(AtomicBool::new(settings.enabled));
+
+        let version = app.package_info().version.to_string();
+        let hook_enabled = Arc::clone(&enabled);
+        let hook_dir = crashes_dir.clone();
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if hook_enabled.load(Ordering::Relaxed) {
+                let payload = info.payload();
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                let report = CrashReport {
+                    created_at: persist::now_millis(),
+                    version: version.clone(),
+                    os: std::env::consts::OS.to_string(),
+                    arch: std::env::consts::ARCH.to_string(),
+                    thread: std::thread::current().name().map(str::to_string),
+                    message,
+                    location: info.location().map(|location| location.to_string()),
+                    backtrace: Backtrace::force_capture().to_string(),
+                    recent_events: RECENT_EVENTS.lock().map(|events| events.iter().cloned().collect()).unwrap_or_default(),
+                };
+                let path = hook_dir.join(format!("crash-{}.json", report.created_at));
+                let _ = persist::write_json(&path, &report);
+            }
+            default_hook(info);
+        }));
+
+        Ok(Self { store_path, crashes_dir, enabled })
+    }
+
+    /// Crash report files left by previous sessions, oldest first.
+    pub fn reports(&self) -> Vec<PathBuf> {
+        let mut reports: Vec<PathBuf> = fs::read_dir(&self.crashes_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        reports.sort();
+        reports
+    }
+
+    pub fn pending(&self) -> Vec<CrashSummary> {
+        self.reports()
+            .iter()
+            .filter_map(|path| read_report(path))
+            .map(|report| CrashSummary {
+                created_at: report.created_at,
+                version: report.version,
+                message: report.message,
+            })
+            .collect()
+    }
+
+    fn clear(&self) -> Result<()> {
+        for path in self.reports() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+fn read_report(path: &Path) -> Option<CrashReport> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+#[tauri::command]
+pub fn get_diagnostics_enabled(diagnostics: State<'_, Diagnostics>) -> bool {
+    diagnostics.enabled.load(Ordering::Relaxed)
+}
+
+/// Opting out also deletes any reports that were already written.
+#[tauri::command]
+pub fn set_diagnostics_enabled(diagnostics: State<'_, Diagnostics>, enabled: bool) -> Result<()> {
+    persist::write_json(&diagnostics.store_path, &DiagnosticsSettings { enabled })?;
+    diagnostics.enabled.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        diagnostics.clear()?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn dismiss_crash_reports(diagnostics: State<'_, Diagnostics>) -> Result<()> {
+    diagnostics.clear()
+}
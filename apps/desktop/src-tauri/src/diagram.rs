@@ -0,0 +1,401 @@
+use std::{
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+};
+
+use layout::{
+    backends::svg::SVGWriter,
+    core::{
+        base::Orientation,
+        color::Color,
+        style::{LineStyleKind, StyleAttr},
+    },
+    gv::{DotParser, GraphBuilder},
+    std_shapes::{
+        render::get_shape_size,
+        shapes::{Arrow, Element, LineEndKind, ShapeKind},
+    },
+    topo::layout::VisualGraph,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    assets::AssetStore,
+    error::{Error, Result},
+    export,
+};
+
+const FONT_SIZE: usize = 14;
+const INK: &str = "#27272a";
+const NODE_FILL: &str = "#f4f4f5";
+/// The layout engine writes Times, which looks out of place next to board text.
+const SOURCE_FONT: &str = "font-family: Times, serif;";
+const BOARD_FONT: &str = "font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif;";
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagramKind {
+    /// Mermaid flowchart (`graph` / `flowchart`) syntax
+    Mermaid,
+    /// Graphviz DOT
+    #[serde(alias = "graphviz")]
+    Dot,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiagramOptions {
+    /// Also rasterize the diagram and put the PNG in the asset store
+    pub store: bool,
+    /// Pixels per SVG unit for the stored PNG
+    pub scale: f32,
+}
+
+impl Default for DiagramOptions {
+    fn default() -> Self {
+        Self {
+            store: false,
+            scale: 2.0,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedDiagram {
+    svg: String,
+    width: f32,
+    height: f32,
+    /// Asset hash of the PNG rendering, when `store` was set
+    hash: Option<String>,
+}
+
+fn diagram_error(message: impl Into<String>) -> Error {
+    Error::Render(message.into())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum NodeShape {
+    Box,
+    Rounded,
+    Circle,
+    DoubleCircle,
+}
+
+#[derive(Clone, Copy)]
+enum EdgeLine {
+    Normal,
+    Dotted,
+    Thick,
+}
+
+struct MermaidNode {
+    label: String,
+    shape: NodeShape,
+}
+
+struct MermaidEdge {
+    from: usize,
+    to: usize,
+    label: String,
+    line: EdgeLine,
+    arrow: bool,
+}
+
+/// Node shape delimiters, longest first so `((` wins over `(`.
+const SHAPE_DELIMITERS: &[(&str, &str, NodeShape)] = &[
+    ("(((", ")))", NodeShape::DoubleCircle),
+    ("((", "))", NodeShape::Circle),
+    ("([", "])", NodeShape::Rounded),
+    ("[[", "]]", NodeShape::Box),
+    ("[(", ")]", NodeShape::Box),
+    ("{{", "}}", NodeShape::Box),
+    ("(", ")", NodeShape::Rounded),
+    ("[", "]", NodeShape::Box),
+    ("{", "}", NodeShape::Box),
+    (">", "]", NodeShape::Box),
+];
+
+/// Operators closing the `A -- text --> B` form.
+const TEXT_EDGE_ENDS: &[&str] = &["-->", "---", "==>", "===", "-.->", ".->", "-.-"];
+
+/// Statements that only style or group nodes; the layout ignores them.
+const IGNORED_STATEMENTS: &[&str] =
+    &["%%", "classDef", "class ", "style ", "linkStyle", "click ", "subgraph", "direction"];
+
+/// The flowchart subset of Mermaid: nodes with the common shapes, chained and
+/// `&`-grouped edges, edge labels and solid, dotted or thick lines. Subgraphs
+/// are flattened and styling is ignored.
+#[derive(Default)]
+struct Flowchart {
+    nodes: Vec<MermaidNode>,
+    ids: HashMap<String, usize>,
+    edges: Vec<MermaidEdge>,
+}
+
+struct Statement<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Statement<'a> {
+    fn skip_space(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        match self.rest.strip_prefix(prefix) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn take_until(&mut self, end: &str) -> Result<&'a str> {
+        let (taken, rest) = self
+            .rest
+            .split_once(end)
+            .ok_or_else(|| diagram_error(format!("missing `{end}`")))?;
+        self.rest = rest;
+        Ok(taken)
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        let end = self.rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(self.rest.len());
+        let (id, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        (!id.is_empty()).then_some(id)
+    }
+
+    /// `-->`, `---`, `-.->`, `==>`, optionally with `|label|` after it or
+    /// `-- label -->` around the label.
+    fn edge(&mut self) -> Result<Option<(String, EdgeLine, bool)>> {
+        self.skip_space();
+        let start = self.rest;
+        self.eat("<");
+        let run = self.rest.find(|c: char| !matches!(c, '-' | '=' | '.')).unwrap_or(self.rest.len());
+        let operator = &self.rest[..run];
+        if run < 2 {
+            self.rest = start;
+            return Ok(None);
+        }
+        self.rest = &self.rest[run..];
+        let line = if operator.contains('.') {
+            EdgeLine::Dotted
+        } else if operator.contains('=') {
+            EdgeLine::Thick
+        } else {
+            EdgeLine::Normal
+        };
+        let mut arrow = self.eat(">");
+        if !arrow && (self.rest.starts_with("x ") || self.rest.starts_with("o ")) {
+            // Cross and circle heads are drawn as plain arrows
+            self.rest = &self.rest[1..];
+            arrow = true;
+        }
+
+        let mut label = String::new();
+        if !arrow && matches!(operator, "--" | "==" | "-.") && self.rest.starts_with(char::is_whitespace) {
+            let (index, end) = TEXT_EDGE_ENDS
+                .iter()
+                .filter_map(|end| self.rest.find(end).map(|index| (index, *end)))
+                .min_by_key(|(index, _)| *index)
+                .ok_or_else(|| diagram_error("unterminated edge label"))?;
+            label = self.rest[..index].trim().to_string();
+            self.rest = &self.rest[index + end.len()..];
+            arrow = end.ends_with('>');
+        }
+        self.skip_space();
+        if self.eat("|") {
+            label = self.take_until("|")?.trim().to_string();
+        }
+        Ok(Some((label, line, arrow)))
+    }
+}
+
+fn clean_label(label: &str) -> String {
+    let label = label.trim().trim_matches('"').trim_matches(|c| c == '/' || c == '\\');
+    label.replace("<br>", "\n").replace("<br/>", "\n").replace("<br />", "\n")
+}
+
+impl Flowchart {
+    fn node(&mut self, statement: &mut Statement) -> Result<usize> {
+        statement.skip_space();
+        let id = statement
+            .identifier()
+            .ok_or_else(|| diagram_error(format!("expected a node at `{}`", statement.rest.trim())))?;
+        let mut defined = None;
+        for (open, close, shape) in SHAPE_DELIMITERS {
+            if statement.eat(open) {
+                defined = Some((clean_label(statement.take_until(close)?), *shape));
+                break;
+            }
+        }
+        // Class shorthand, `A:::important`
+        if statement.eat(":::") {
+            statement.identifier();
+        }
+
+        let index = *self.ids.entry(id.to_string()).or_insert_with(|| {
+            self.nodes.push(MermaidNode {
+                label: id.to_string(),
+                shape: NodeShape::Box,
+            });
+            self.nodes.len() - 1
+        });
+        if let Some((label, shape)) = defined {
+            self.nodes[index] = MermaidNode { label, shape };
+        }
+        Ok(index)
+    }
+
+    /// `A` or `A & B & C`
+    fn group(&mut self, statement: &mut Statement) -> Result<Vec<usize>> {
+        let mut group = vec![self.node(statement)?];
+        loop {
+            statement.skip_space();
+            if !statement.eat("&") {
+                return Ok(group);
+            }
+            group.push(self.node(statement)?);
+        }
+    }
+
+    fn statement(&mut self, text: &str) -> Result<()> {
+        let mut statement = Statement { rest: text };
+        let mut sources = self.group(&mut statement)?;
+        while let Some((label, line, arrow)) = statement.edge()? {
+            let targets = self.group(&mut statement)?;
+            for &from in &sources {
+                for &to in &targets {
+                    self.edges.push(MermaidEdge {
+                        from,
+                        to,
+                        label: label.clone(),
+                        line,
+                        arrow,
+                    });
+                }
+            }
+            sources = targets;
+        }
+        statement.skip_space();
+        if !statement.rest.is_empty() {
+            return Err(diagram_error(format!("unexpected `{}`", statement.rest)));
+        }
+        Ok(())
+    }
+}
+
+fn parse_mermaid(source: &str) -> Result<VisualGraph> {
+    let mut lines = source.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with("%%"));
+    let header = lines.next().ok_or_else(|| diagram_error("the diagram is empty"))?;
+    let mut words = header.split_whitespace();
+    if !matches!(words.next(), Some("graph" | "flowchart")) {
+        return Err(diagram_error("only Mermaid flowcharts (`graph` or `flowchart`) are supported"));
+    }
+    // No bottom-up or right-to-left ranking in the layout engine
+    let orientation = match words.next().map(|direction| direction.trim_end_matches(';')) {
+        Some("LR" | "RL") => Orientation::LeftToRight,
+        _ => Orientation::TopToBottom,
+    };
+
+    let mut chart = Flowchart::default();
+    for line in lines {
+        for statement in line.split(';').map(str::trim).filter(|statement| !statement.is_empty()) {
+            if statement == "end" || IGNORED_STATEMENTS.iter().any(|prefix| statement.starts_with(prefix)) {
+                continue;
+            }
+            chart.statement(statement)?;
+        }
+    }
+
+    let mut graph = VisualGraph::new(orientation);
+    let handles: Vec<_> = chart
+        .nodes
+        .iter()
+        .map(|node| {
+            let shape = match node.shape {
+                NodeShape::Circle => ShapeKind::new_circle(&node.label),
+                NodeShape::DoubleCircle => ShapeKind::new_double_circle(&node.label),
+                NodeShape::Box | NodeShape::Rounded => ShapeKind::new_box(&node.label),
+            };
+            let rounded = if node.shape == NodeShape::Rounded { 12 } else { 0 };
+            let look = StyleAttr::new(Color::fast(INK), 1, Some(Color::fast(NODE_FILL)), rounded, FONT_SIZE);
+            let round = matches!(node.shape, NodeShape::Circle | NodeShape::DoubleCircle);
+            let size = get_shape_size(orientation, &shape, FONT_SIZE, round);
+            graph.add_node(Element::create(shape, look, orientation, size))
+        })
+        .collect();
+    for edge in &chart.edges {
+        let (style, width) = match edge.line {
+            EdgeLine::Normal => (LineStyleKind::Normal, 1),
+            EdgeLine::Dotted => (LineStyleKind::Dashed, 1),
+            EdgeLine::Thick => (LineStyleKind::Normal, 3),
+        };
+        let end = if edge.arrow { LineEndKind::Arrow } else { LineEndKind::None };
+        let look = StyleAttr::new(Color::fast(INK), width, None, 0, FONT_SIZE);
+        let arrow = Arrow::new(LineEndKind::None, end, style, &edge.label, &look, &None, &None);
+        graph.add_edge(arrow, handles[edge.from], handles[edge.to]);
+    }
+    Ok(graph)
+}
+
+fn parse_dot(source: &str) -> Result<VisualGraph> {
+    let graph = DotParser::new(source).process().map_err(|err| diagram_error(format!("invalid DOT: {err}")))?;
+    let mut builder = GraphBuilder::new();
+    builder.visit_graph(&graph);
+    Ok(builder.get())
+}
+
+/// Lay a diagram out and write it as SVG.
+pub fn render_svg(source: &str, kind: DiagramKind) -> Result<String> {
+    // The layout engine asserts on input it can't handle; report that as a
+    // bad diagram instead of taking the command down
+    let rendered = panic::catch_unwind(AssertUnwindSafe(|| -> Result<String> {
+        let mut graph = match kind {
+            DiagramKind::Mermaid => parse_mermaid(source)?,
+            DiagramKind::Dot => parse_dot(source)?,
+        };
+        if graph.num_nodes() == 0 {
+            return Err(diagram_error("the diagram has no nodes"));
+        }
+        let mut writer = SVGWriter::new();
+        graph.do_it(false, false, false, &mut writer);
+        Ok(writer.finalize())
+    }))
+    .map_err(|_| diagram_error("the diagram could not be laid out"))??;
+    Ok(rendered.replace(SOURCE_FONT, BOARD_FONT))
+}
+
+/// Compile Mermaid or DOT source to SVG offline. With `store`, also put a PNG
+/// rendering in the asset store so it can be placed on a board as an image.
+#[tauri::command]
+pub async fn render_diagram(
+    app: AppHandle,
+    source: String,
+    kind: DiagramKind,
+    options: Option<DiagramOptions>,
+) -> Result<RenderedDiagram> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        let svg = render_svg(&source, kind)?;
+        let tree = export::parse_svg(&svg)?;
+        let hash = if options.store {
+            let png = export::png::rasterize(&tree, options.scale.clamp(0.5, 8.0))?;
+            Some(app.state::<AssetStore>().store(&png)?)
+        } else {
+            None
+        };
+        Ok(RenderedDiagram {
+            width: tree.size().width(),
+            height: tree.size().height(),
+            svg,
+            hash,
+        })
+    })
+    .await
+    .map_err(|err| diagram_error(err.to_string()))?
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    board,
+    error::{Error, Result},
+};
+
+/// Lists under `/board` diffed item by item, with the field naming each item's type.
+pub const ITEM_LISTS: [(&str, &str, &str); 3] = [
+    ("element", "/board/elements", "type"),
+    ("widget", "/board/widgets", "moduleId"),
+    ("connection", "/board/connections", "sourceType"),
+];
+
+/// One property that differs, by JSON pointer from the item (`/rect/x`).
+#[derive(Serialize)]
+pub struct PropertyChange {
+    pub path: String,
+    /// `null` when the property was added
+    pub before: Value,
+    /// `null` when the property was removed
+    pub after: Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemChange {
+    pub id: String,
+    /// `element`, `widget` or `connection`.
+    pub kind: &'static str,
+    /// Element type, widget module id, or the connection's source type.
+    pub item_type: String,
+    /// The item as it was, for removed items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    /// The item as it is, for added items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+    /// What changed, for modified items
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<PropertyChange>,
+}
+
+/// What actually changed between two versions of a board, item by item,
+/// instead of a diff of the JSON text.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardDiff {
+    /// Board-level properties outside the item lists: title, background, grid…
+    pub board_changes: Vec<PropertyChange>,
+    pub added: Vec<ItemChange>,
+    pub removed: Vec<ItemChange>,
+    pub modified: Vec<ItemChange>,
+}
+
+pub fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Leaf-level differences between two values. Arrays are compared whole:
+/// their items have no identity to line them up by.
+fn diff_values(path: &str, before: &Value, after: &Value, out: &mut Vec<PropertyChange>) {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            for (key, old) in before {
+                let child = format!("{path}/{}", escape_pointer(key));
+                diff_values(&child, old, after.get(key).unwrap_or(&Value::Null), out);
+            }
+            for (key, new) in after.iter().filter(|(key, _)| !before.contains_key(*key)) {
+                diff_values(&format!("{path}/{}", escape_pointer(key)), &Value::Null, new, out);
+            }
+        }
+        _ if before != after => out.push(PropertyChange {
+            path: path.to_string(),
+            before: before.clone(),
+            after: after.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn items(document: &Value) -> BTreeMap<(&'static str, String), (String, &Value)> {
+    let mut items = BTreeMap::new();
+    for (kind, pointer, type_field) in ITEM_LISTS {
+        for item in document.pointer(pointer).and_then(Value::as_array).into_iter().flatten() {
+            let Some(id) = item.get("id").and_then(Value::as_str) else {
+                continue;
+            };
+            let item_type = item.get(type_field).and_then(Value::as_str).unwrap_or_default().to_string();
+            items.insert((kind, id.to_string()), (item_type, item));
+        }
+    }
+    items
+}
+
+/// Everything of the document but its item lists and edit timestamps.
+pub fn board_properties(document: &Value) -> Value {
+    let mut document = document.clone();
+    if let Some(board) = document.get_mut("board").and_then(Value::as_object_mut) {
+        for (_, pointer, _) in ITEM_LISTS {
+            board.remove(pointer.trim_start_matches("/board/"));
+        }
+    }
+    if let Some(meta) = document.get_mut("meta").and_then(Value::as_object_mut) {
+        meta.remove("updatedAt");
+    }
+    document
+}
+
+/// Structured diff of two board documents.
+pub fn diff_documents(before: &Value, after: &Value) -> BoardDiff {
+    let mut diff = BoardDiff {
+        board_changes: Vec::new(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        modified: Vec::new(),
+    };
+    diff_values("", &board_properties(before), &board_properties(after), &mut diff.board_changes);
+
+    let mut old_items = items(before);
+    for ((kind, id), (item_type, item)) in items(after) {
+        let change = |before, after, changes| ItemChange {
+            id: id.clone(),
+            kind,
+            item_type: item_type.clone(),
+            before,
+            after,
+            changes,
+        };
+        match old_items.remove(&(kind, id.clone())) {
+            None => diff.added.push(change(None, Some(item.clone()), Vec::new())),
+            Some((_, previous)) if previous != item => {
+                let mut changes = Vec::new();
+                diff_values("", previous, item, &mut changes);
+                diff.modified.push(change(None, None, changes));
+            }
+            Some(_) => {}
+        }
+    }
+    diff.removed = old_items
+        .into_iter()
+        .map(|((kind, id), (item_type, item))| ItemChange {
+            id,
+            kind,
+            item_type,
+            before: Some(item.clone()),
+            after: None,
+            changes: Vec::new(),
+        })
+        .collect();
+    diff
+}
+
+/// What changed from the board at `path_a` to the one at `path_b`, e.g. two
+/// checkouts of the same board for Git integration.
+#[tauri::command]
+pub async fn diff_boards(path_a: String, path_b: String) -> Result<BoardDiff> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let before = board::read_board_document(&path_a)?;
+        let after = board::read_board_document(&path_b)?;
+        Ok(diff_documents(&before, &after))
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rect(id: &str, x: f64) -> Value {
+        json!({ "id": id, "type": "rectangle", "rect": { "x": x, "y": 0, "width": 10, "height": 10 }, "style": {} })
+    }
+
+    fn document(elements: Vec<Value>) -> Value {
+        json!({
+            "version": 4,
+            "meta": { "title": "Board", "updatedAt": 1 },
+            "board": { "elements": elements, "widgets": [], "background": { "pattern": "dots" } },
+        })
+    }
+
+    fn paths(changes: &[PropertyChange]) -> Vec<&str> {
+        changes.iter().map(|change| change.path.as_str()).collect()
+    }
+
+    #[test]
+    fn identical_boards_have_no_changes() {
+        let before = document(vec![rect("a", 0.0)]);
+        let mut after = before.clone();
+        // Saving again only bumps the edit time
+        after["meta"]["updatedAt"] = json!(2);
+        let diff = diff_documents(&before, &after);
+        assert!(diff.board_changes.is_empty() && diff.added.is_empty());
+        assert!(diff.removed.is_empty() && diff.modified.is_empty());
+    }
+
+    #[test]
+    fn added_and_removed_elements() {
+        let diff = diff_documents(&document(vec![rect("a", 0.0)]), &document(vec![rect("b", 0.0)]));
+        assert!(diff.modified.is_empty());
+        let [added] = &diff.added[..] else { panic!("expected one added element") };
+        assert_eq!((added.id.as_str(), added.kind, added.item_type.as_str()), ("b", "element", "rectangle"));
+        assert_eq!(added.after.as_ref(), Some(&rect("b", 0.0)));
+        let [removed] = &diff.removed[..] else { panic!("expected one removed element") };
+        assert_eq!(removed.id, "a");
+        assert_eq!(removed.before.as_ref(), Some(&rect("a", 0.0)));
+    }
+
+    #[test]
+    fn moved_elements_report_their_position() {
+        let diff = diff_documents(&document(vec![rect("a", 0.0)]), &document(vec![rect("a", 40.0)]));
+        let [moved] = &diff.modified[..] else { panic!("expected one modified element") };
+        assert!(moved.before.is_none() && moved.after.is_none());
+        let [change] = &moved.changes[..] else { panic!("expected one change") };
+        assert_eq!(change.path, "/rect/x");
+        assert_eq!((&change.before, &change.after), (&json!(0.0), &json!(40.0)));
+    }
+
+    #[test]
+    fn reordering_elements_is_not_a_change() {
+        let before = document(vec![rect("a", 0.0), rect("b", 0.0)]);
+        let after = document(vec![rect("b", 0.0), rect("a", 0.0)]);
+        let diff = diff_documents(&before, &after);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.modified.is_empty());
+    }
+
+    #[test]
+    fn modified_elements_list_added_changed_and_removed_properties() {
+        let before = rect("a", 0.0);
+        let mut after = before.clone();
+        after["rect"]["width"] = json!(20);
+        after.as_object_mut().unwrap().remove("style");
+        after["label/~"] = json!("x");
+        let diff = diff_documents(&document(vec![before]), &document(vec![after]));
+        let [modified] = &diff.modified[..] else { panic!("expected one modified element") };
+        assert_eq!(paths(&modified.changes), ["/rect/width", "/style", "/label~1~0"]);
+        assert_eq!(modified.changes[1].after, Value::Null);
+        assert_eq!(modified.changes[2].before, Value::Null);
+    }
+
+    #[test]
+    fn widgets_connections_and_board_properties() {
+        let before = document(Vec::new());
+        let mut after = before.clone();
+        after["meta"]["title"] = json!("Renamed");
+        after["board"]["background"]["pattern"] = json!("grid");
+        after["board"]["widgets"] = json!([{ "id": "w", "moduleId": "todo" }]);
+        after["board"]["connections"] = json!([{ "id": "c", "sourceType": "element" }]);
+        let diff = diff_documents(&before, &after);
+        assert_eq!(paths(&diff.board_changes), ["/board/background/pattern", "/meta/title"]);
+        let added: Vec<_> = diff.added.iter().map(|item| (item.kind, item.item_type.as_str())).collect();
+        assert_eq!(added, [("connection", "element"), ("widget", "todo")]);
+    }
+
+    #[test]
+    fn items_without_ids_are_skipped() {
+        let diff = diff_documents(&document(Vec::new()), &document(vec![json!({ "type": "rectangle" })]));
+        assert!(diff.added.is_empty());
+    }
+}
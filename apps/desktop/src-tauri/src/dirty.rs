@@ -0,0 +1,151 @@
+use std::{collections::HashSet, path::Path, sync::Mutex};
+
+use tauri::{AppHandle, Emitter, Manager, State, Window, WindowEvent};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult};
+
+use crate::{autosave::Autosave, taskbar, windows::BoardWindows};
+
+const SAVE: &str = "Save";
+const DONT_SAVE: &str = "Don't Save";
+const CANCEL: &str = "Cancel";
+
+#[derive(Default)]
+struct DirtyState {
+    /// Windows with unsaved changes
+    dirty: HashSet<String>,
+    /// Windows waiting for the frontend to save before closing
+    closing: HashSet<String>,
+    /// Quitting, once the last window is gone
+    quitting: bool,
+}
+
+/// Unsaved-changes state per window, so closing or quitting can ask first.
+#[derive(Default)]
+pub struct DirtyWindows(Mutex<DirtyState>);
+
+impl DirtyWindows {
+    pub fn has_unsaved_changes(&self) -> bool {
+        !self.0.lock().unwrap().dirty.is_empty()
+    }
+}
+
+/// Badge the dock/taskbar icon with the number of windows with unsaved changes.
+fn show_unsaved(app: &AppHandle, dirty: &DirtyWindows) {
+    let count = dirty.0.lock().unwrap().dirty.len();
+    taskbar::set_badge(app, taskbar::UNSAVED, count as i64);
+}
+
+fn confirm_close(window: &Window) {
+    let name = window
+        .state::<BoardWindows>()
+        .board(window.label())
+        .and_then(|path| Path::new(&path).file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "this board".to_string());
+    let target = window.clone();
+    window
+        .dialog()
+        .message("Your changes will be lost if you don't save them.")
+        .title(format!("Do you want to save the changes made to “{name}”?"))
+        .kind(MessageDialogKind::Warning)
+        .parent(window)
+        .buttons(MessageDialogButtons::YesNoCancelCustom(
+            SAVE.to_string(),
+            DONT_SAVE.to_string(),
+            CANCEL.to_string(),
+        ))
+        .show_with_result(move |result| {
+            let dirty = target.state::<DirtyWindows>();
+            let label = target.label().to_string();
+            // Platforms report custom buttons either by text or by position
+            match result {
+                MessageDialogResult::Yes => {}
+                MessageDialogResult::Custom(text) if text == SAVE => {}
+                MessageDialogResult::No => return discard(&target, &dirty),
+                MessageDialogResult::Custom(text) if text == DONT_SAVE => return discard(&target, &dirty),
+                // Cancelling one board's prompt cancels the quit
+                _ => {
+                    dirty.0.lock().unwrap().quitting = false;
+                    return;
+                }
+            }
+            dirty.0.lock().unwrap().closing.insert(label);
+            let _ = target.emit_to(target.label(), "save-before-close", ());
+        });
+}
+
+fn discard(window: &Window, dirty: &DirtyWindows) {
+    // Changes the user chose not to save aren't offered back after a restart
+    if let Some(path) = window.state::<BoardWindows>().board(window.label()) {
+        if let Err(err) = window.state::<Autosave>().discard(&path) {
+            tracing::warn!("failed to drop the recovery file of {path}: {err}");
+        }
+    }
+    let mut state = dirty.0.lock().unwrap();
+    state.dirty.remove(window.label());
+    state.closing.remove(window.label());
+    drop(state);
+    show_unsaved(window.app_handle(), dirty);
+    let _ = window.destroy();
+}
+
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    let Some(dirty) = window.try_state::<DirtyWindows>() else {
+        return;
+    };
+    match event {
+        WindowEvent::CloseRequested { api, .. } if dirty.0.lock().unwrap().dirty.contains(window.label()) => {
+            api.prevent_close();
+            confirm_close(window);
+        }
+        WindowEvent::Destroyed => {
+            let mut state = dirty.0.lock().unwrap();
+            state.dirty.remove(window.label());
+            state.closing.remove(window.label());
+            let quitting = state.quitting;
+            drop(state);
+            show_unsaved(window.app_handle(), &dirty);
+            // macOS keeps running without windows, so a quit has to be finished here
+            let app = window.app_handle();
+            if quitting && app.webview_windows().keys().all(|label| label == window.label()) {
+                app.exit(0);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Ask every window to close, so each one with unsaved changes prompts.
+/// The app exits once the last window is gone, unless a prompt is cancelled.
+pub fn close_all_windows(app: &AppHandle) {
+    app.state::<DirtyWindows>().0.lock().unwrap().quitting = true;
+    for window in app.webview_windows().values() {
+        let _ = window.close();
+    }
+}
+
+/// Mark a window as having unsaved changes. Clearing the flag while the window
+/// is waiting on "Save" finishes closing it; setting it again (the save failed)
+/// keeps the window open.
+#[tauri::command]
+pub fn set_dirty(app: AppHandle, windows: State<'_, DirtyWindows>, window_label: String, dirty: bool) {
+    let mut state = windows.0.lock().unwrap();
+    if dirty {
+        // The save before closing failed, so the window and the app stay open
+        if state.closing.remove(&window_label) {
+            state.quitting = false;
+        }
+        state.dirty.insert(window_label);
+        drop(state);
+        show_unsaved(&app, &windows);
+        return;
+    }
+    state.dirty.remove(&window_label);
+    let closing = state.closing.remove(&window_label);
+    drop(state);
+    show_unsaved(&app, &windows);
+    if closing {
+        if let Some(window) = app.get_webview_window(&window_label) {
+            let _ = window.destroy();
+        }
+    }
+}
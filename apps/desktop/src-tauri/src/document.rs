@@ -0,0 +1,522 @@
+//! Rust mirror of the `BoardkitDocument` model in `@boardkit/core`.
+//!
+//! Field names and defaults follow `packages/core/src/types` so documents
+//! round-trip between the webview and native subsystems unchanged.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Mirrors `CURRENT_DOCUMENT_VERSION` in `@boardkit/core`.
+pub const CURRENT_DOCUMENT_VERSION: u32 = 4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn center(&self) -> Point {
+        Point {
+            x: self.x + self.width / 2.0,
+            y: self.y + self.height / 2.0,
+        }
+    }
+
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect {
+            x,
+            y,
+            width: self.right().max(other.right()) - x,
+            height: self.bottom().max(other.bottom()) - y,
+        }
+    }
+
+    /// Axis-aligned bounds of this rect rotated by `angle` radians around its center.
+    pub fn rotated_bounds(&self, angle: f64) -> Rect {
+        if angle == 0.0 {
+            return *self;
+        }
+        let center = self.center();
+        let (sin, cos) = angle.sin_cos();
+        let corners = [
+            (self.x, self.y),
+            (self.right(), self.y),
+            (self.right(), self.bottom()),
+            (self.x, self.bottom()),
+        ];
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for (x, y) in corners {
+            let rx = cos * (x - center.x) - sin * (y - center.y) + center.x;
+            let ry = sin * (x - center.x) + cos * (y - center.y) + center.y;
+            min_x = min_x.min(rx);
+            min_y = min_y.min(ry);
+            max_x = max_x.max(rx);
+            max_y = max_y.max(ry);
+        }
+        Rect {
+            x: min_x,
+            y: min_y,
+            width: max_x - min_x,
+            height: max_y - min_y,
+        }
+    }
+}
+
+// ============================================================================
+// Elements
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StrokeDash {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ElementStyle {
+    pub stroke_color: String,
+    pub stroke_width: f64,
+    pub fill_color: Option<String>,
+    pub opacity: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stroke_dash: Option<StrokeDash>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roughness: Option<f64>,
+}
+
+impl Default for ElementStyle {
+    fn default() -> Self {
+        Self {
+            stroke_color: "#ffffff".to_string(),
+            stroke_width: 2.0,
+            fill_color: None,
+            opacity: 1.0,
+            stroke_dash: Some(StrokeDash::Solid),
+            roughness: Some(1.0),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Element {
+    pub id: String,
+    #[serde(default)]
+    pub rect: Rect,
+    #[serde(default)]
+    pub z_index: f64,
+    #[serde(default)]
+    pub style: ElementStyle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angle: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locked: Option<bool>,
+    #[serde(flatten)]
+    pub kind: ElementKind,
+}
+
+impl Element {
+    /// Bounds including rotation, as used for export cropping.
+    pub fn bounds(&self) -> Rect {
+        self.rect.rotated_bounds(self.angle.unwrap_or(0.0))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ElementKind {
+    Rectangle(ShapeProps),
+    Ellipse(ShapeProps),
+    Line(LineProps),
+    Arrow(LineProps),
+    Draw(DrawProps),
+    Text(TextProps),
+    Image(ImageProps),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShapeProps {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corner_radius: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArrowHead {
+    None,
+    #[default]
+    End,
+    Start,
+    Both,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnchorPosition {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    #[default]
+    Center,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArrowBinding {
+    pub element_id: String,
+    #[serde(default)]
+    pub anchor: AnchorPosition,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<Point>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LinePoints {
+    pub start: Point,
+    pub end: Point,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineProps {
+    pub points: LinePoints,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arrow_head: Option<ArrowHead>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_binding: Option<ArrowBinding>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_binding: Option<ArrowBinding>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DrawProps {
+    #[serde(default)]
+    pub points: Vec<Point>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FontFamily {
+    #[default]
+    System,
+    Handwritten,
+    Code,
+    Serif,
+}
+
+impl FontFamily {
+    /// CSS font stack, mirroring `FONT_FAMILY_CSS` in `@boardkit/core`.
+    pub fn css(self) -> &'static str {
+        match self {
+            FontFamily::System => "system-ui, -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif",
+            FontFamily::Handwritten => "Caveat, 'Segoe Print', 'Bradley Hand', cursive",
+            FontFamily::Code => "ui-monospace, SFMono-Regular, 'SF Mono', Menlo, Monaco, Consolas, monospace",
+            FontFamily::Serif => "'Libre Baskerville', Georgia, 'Times New Roman', serif",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FontWeight {
+    #[default]
+    Normal,
+    Medium,
+    Bold,
+}
+
+impl FontWeight {
+    pub fn css(self) -> &'static str {
+        match self {
+            FontWeight::Normal => "400",
+            FontWeight::Medium => "500",
+            FontWeight::Bold => "700",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+fn default_font_size() -> f64 {
+    16.0
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextProps {
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub font_family: FontFamily,
+    #[serde(default = "default_font_size")]
+    pub font_size: f64,
+    #[serde(default)]
+    pub font_weight: FontWeight,
+    #[serde(default)]
+    pub text_align: TextAlign,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageObjectFit {
+    #[default]
+    Contain,
+    Cover,
+    Fill,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageProps {
+    pub asset_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_fit: Option<ImageObjectFit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flip_x: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flip_y: Option<bool>,
+}
+
+// ============================================================================
+// Widgets, groups and connections
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Widget {
+    pub id: String,
+    pub module_id: String,
+    pub rect: Rect,
+    #[serde(default)]
+    pub z_index: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+}
+
+impl Widget {
+    /// Rendered bounds: content scale grows the widget from its top-left corner.
+    pub fn bounds(&self) -> Rect {
+        let scale = self.scale.unwrap_or(1.0);
+        Rect {
+            width: self.rect.width * scale,
+            height: self.rect.height * scale,
+            ..self.rect
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementGroup {
+    pub id: String,
+    pub member_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_group_id: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionTargetType {
+    #[default]
+    Element,
+    Widget,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStyle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stroke_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stroke_width: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Connection {
+    pub id: String,
+    pub source_id: String,
+    #[serde(default)]
+    pub source_type: ConnectionTargetType,
+    pub target_id: String,
+    #[serde(default)]
+    pub target_type: ConnectionTargetType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<ConnectionStyle>,
+}
+
+// ============================================================================
+// Board and document
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+    pub zoom: f64,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self { x: 0.0, y: 0.0, zoom: 1.0 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoardBackground {
+    pub pattern: String,
+    pub color: String,
+}
+
+impl Default for BoardBackground {
+    fn default() -> Self {
+        Self {
+            pattern: "dots".to_string(),
+            color: "auto".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardState {
+    #[serde(default)]
+    pub viewport: Viewport,
+    #[serde(default)]
+    pub widgets: Vec<Widget>,
+    #[serde(default)]
+    pub elements: Vec<Element>,
+    #[serde(default)]
+    pub background: BoardBackground,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<ElementGroup>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connections: Option<Vec<Connection>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canvas_settings: Option<Value>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentMeta {
+    pub title: String,
+    #[serde(default)]
+    pub created_at: u64,
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Asset {
+    pub id: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub size: u64,
+    pub hash: String,
+    #[serde(default)]
+    pub created_at: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetRegistry {
+    #[serde(default)]
+    pub assets: BTreeMap<String, Asset>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardDocument {
+    pub version: u32,
+    pub meta: DocumentMeta,
+    pub board: BoardState,
+    #[serde(default)]
+    pub modules: BTreeMap<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_sharing: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assets: Option<AssetRegistry>,
+}
+
+impl BoardDocument {
+    /// Empty board, equivalent to `createEmptyDocument` in `@boardkit/core`.
+    pub fn new(title: impl Into<String>, now: u64) -> Self {
+        Self {
+            version: CURRENT_DOCUMENT_VERSION,
+            meta: DocumentMeta {
+                title: title.into(),
+                created_at: now,
+                updated_at: now,
+            },
+            board: BoardState {
+                connections: Some(Vec::new()),
+                canvas_settings: Some(json!({ "zoomSensitivity": 0.002, "snapToGrid": true, "gridSpacing": 20 })),
+                ..BoardState::default()
+            },
+            modules: BTreeMap::new(),
+            data_sharing: Some(json!({ "permissions": [], "links": [] })),
+            assets: Some(AssetRegistry::default()),
+        }
+    }
+
+    /// Keep only the elements and widgets whose ids are in `ids`. Connections
+    /// to removed items are skipped by the renderers.
+    pub fn retain_items(&mut self, ids: &HashSet<&str>) {
+        self.board.elements.retain(|element| ids.contains(element.id.as_str()));
+        self.board.widgets.retain(|widget| ids.contains(widget.id.as_str()));
+    }
+
+    /// Bounds of all elements and widgets, or `None` for an empty board.
+    pub fn content_bounds(&self) -> Option<Rect> {
+        self.board
+            .elements
+            .iter()
+            .map(Element::bounds)
+            .chain(self.board.widgets.iter().map(Widget::bounds))
+            .reduce(|acc, rect| acc.union(&rect))
+    }
+}
@@ -0,0 +1,161 @@
+use std::{fs, io::Read, path::Path};
+
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use tauri::{ipc::Response, AppHandle, Manager, Window};
+
+use crate::{
+    board,
+    error::{Error, Result},
+    history,
+    links::LinkGraph,
+    locks,
+    search::SearchIndex,
+    secrets,
+    thumbnails::Thumbnails,
+};
+
+/// Encrypted boards keep the `.boardkit` extension; this prefix tells them
+/// apart from plain ZIP containers.
+const MAGIC: &[u8] = b"BOARDKIT-ENC1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN + NONCE_LEN;
+
+type Key = [u8; KEY_LEN];
+
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Check the magic bytes without reading the whole board.
+pub fn is_encrypted_file(path: &Path) -> bool {
+    let mut magic = [0; MAGIC.len()];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|()| is_encrypted(&magic))
+}
+
+fn salt_of(bytes: &[u8]) -> Result<&[u8]> {
+    if !is_encrypted(bytes) || bytes.len() < HEADER_LEN {
+        return Err(Error::InvalidBoard("not an encrypted board".to_string()));
+    }
+    Ok(&bytes[MAGIC.len()..MAGIC.len() + SALT_LEN])
+}
+
+fn derive_key(password: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = [0; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|err| Error::InvalidBoard(err.to_string()))?;
+    Ok(key)
+}
+
+/// Derived keys are cached per salt, so a board stays unlocked across
+/// renames but a new password never matches an old entry.
+fn key_secret_name(salt: &[u8]) -> String {
+    let salt: String = salt.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("board-key:{salt}")
+}
+
+fn cached_key(salt: &[u8]) -> Result<Option<Key>> {
+    let Some(encoded) = secrets::get(&key_secret_name(salt))? else {
+        return Ok(None);
+    };
+    Ok(STANDARD.decode(encoded).ok().and_then(|key| key.try_into().ok()))
+}
+
+fn cache_key(salt: &[u8], key: &Key) -> Result<()> {
+    secrets::store(&key_secret_name(salt), &STANDARD.encode(key))
+}
+
+fn encrypt(key: &Key, salt: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| Error::InvalidBoard("encryption failed".to_string()))?;
+    let mut bytes = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(salt);
+    bytes.extend_from_slice(&nonce);
+    bytes.extend_from_slice(&ciphertext);
+    Ok(bytes)
+}
+
+fn decrypt(key: &Key, bytes: &[u8]) -> Result<Vec<u8>> {
+    salt_of(bytes)?;
+    let nonce = XNonce::from_slice(&bytes[MAGIC.len() + SALT_LEN..HEADER_LEN]);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(nonce, &bytes[HEADER_LEN..])
+        .map_err(|_| Error::WrongPassword)
+}
+
+/// Encrypt and write a board. With a password, a fresh salt is drawn and the
+/// derived key cached; without one, the board keeps its current key.
+#[tauri::command]
+pub fn save_board_encrypted(
+    app: AppHandle,
+    window: Window,
+    path: String,
+    payload: Vec<u8>,
+    password: Option<String>,
+) -> Result<()> {
+    let (bytes, _) = board::finalize_payload(&payload, &path)?;
+    locks::lock_for_save(&window, &path)?;
+    let (salt, key) = match password {
+        Some(password) => {
+            let mut salt = [0; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            let key = derive_key(&password, &salt)?;
+            cache_key(&salt, &key)?;
+            (salt.to_vec(), key)
+        }
+        None => {
+            let current = board::read_board(&path)?;
+            let salt = salt_of(&current)?.to_vec();
+            let key = cached_key(&salt)?.ok_or_else(|| Error::PasswordRequired(path.clone()))?;
+            (salt, key)
+        }
+    };
+    board::write_board(&path, &encrypt(&key, &salt, &bytes)?)?;
+    // History, thumbnails, search and links hold plaintext copies of the board
+    history::remove(Path::new(&path))?;
+    app.state::<Thumbnails>().remove(&path)?;
+    app.state::<SearchIndex>().remove(&path)?;
+    app.state::<LinkGraph>().remove(&path)?;
+    tracing::info!(path, "encrypted board saved");
+    Ok(())
+}
+
+/// Decrypt a board with `password`, or with its cached key when omitted.
+#[tauri::command]
+pub fn load_board_encrypted(window: Window, path: String, password: Option<String>) -> Result<Response> {
+    let bytes = board::read_board(&path)?;
+    let salt = salt_of(&bytes)?;
+    let key = match &password {
+        Some(password) => derive_key(password, salt)?,
+        None => cached_key(salt)?.ok_or_else(|| Error::PasswordRequired(path.clone()))?,
+    };
+    let container = decrypt(&key, &bytes)?;
+    if password.is_some() {
+        cache_key(salt, &key)?;
+    }
+
+    let (container, migrations) = board::migrate_container(container)?;
+    tracing::info!(path, migrations = migrations.len(), "encrypted board loaded");
+    board::emit_migrated(&window, path, migrations);
+    Ok(Response::new(container))
+}
+
+/// Drop the cached key so the next open asks for the password again.
+#[tauri::command]
+pub fn forget_board_key(path: String) -> Result<()> {
+    let bytes = board::read_board(&path)?;
+    secrets::delete(&key_secret_name(salt_of(&bytes)?))
+}
@@ -0,0 +1,138 @@
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+/// Error returned by every invoke handler.
+///
+/// Serialized as `{ kind, message }` so the frontend can branch on `kind`
+/// without parsing human-readable strings.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("file not found: {0}")]
+    NotFound(String),
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    #[error("file too large: {size} bytes (maximum is {max} bytes)")]
+    TooLarge { size: u64, max: u64 },
+    #[error("invalid .boardkit file: {0}")]
+    InvalidBoard(String),
+    #[error("board version {version} is newer than supported version {supported}; update Boardkit to open it")]
+    UnsupportedVersion { version: u32, supported: u32 },
+    #[error("export failed: {0}")]
+    Render(String),
+    #[error("import failed: {0}")]
+    Import(String),
+    #[error("invalid shortcut: {0}")]
+    InvalidShortcut(String),
+    #[error("shortcut conflict: {0}")]
+    ShortcutConflict(String),
+    #[error("clipboard unavailable: {0}")]
+    Clipboard(String),
+    #[error("board is encrypted: {0}")]
+    PasswordRequired(String),
+    #[error("wrong password")]
+    WrongPassword,
+    #[error("keychain unavailable: {0}")]
+    Keychain(String),
+    #[error("unknown menu item: {0}")]
+    UnknownMenuItem(String),
+    #[error("invalid settings: {0}")]
+    InvalidSettings(String),
+    #[error("{path} is being edited by {owner}")]
+    BoardLocked { path: String, owner: String },
+    #[error("screenshot failed: {0}")]
+    Screenshot(String),
+    #[error("spell checking failed: {0}")]
+    Spellcheck(String),
+    #[error("no spelling dictionary for {lang}; add {lang}.aff and {lang}.dic to {dir}")]
+    MissingDictionary { lang: String, dir: String },
+    #[error("text recognition failed: {0}")]
+    Ocr(String),
+    #[error("update failed: {0}")]
+    Updater(String),
+    #[error("updates are not configured in this build: plugins.updater.pubkey has no signing key")]
+    UpdaterNotConfigured,
+    #[error("character palette unavailable: {0}")]
+    CharacterPalette(String),
+    #[error("color picking failed: {0}")]
+    Eyedropper(String),
+    #[error("plugin error: {0}")]
+    Plugin(String),
+    #[error("automation failed: {0}")]
+    Automation(String),
+    #[error("announcement failed: {0}")]
+    Accessibility(String),
+    #[error("{0} merge conflicts; the merged board keeps our side of each")]
+    MergeConflicts(usize),
+    #[error("git failed: {0}")]
+    Git(String),
+    #[error(transparent)]
+    GlobalShortcut(#[from] tauri_plugin_global_shortcut::Error),
+    #[error(transparent)]
+    Database(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Tauri(#[from] tauri::Error),
+}
+
+impl Error {
+    /// Attach the offending path to common filesystem failures.
+    pub fn from_io(err: std::io::Error, path: &str) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => Error::NotFound(path.to_string()),
+            std::io::ErrorKind::PermissionDenied => Error::PermissionDenied(path.to_string()),
+            _ => Error::Io(err),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Error::NotFound(_) => "not_found",
+            Error::PermissionDenied(_) => "permission_denied",
+            Error::TooLarge { .. } => "too_large",
+            Error::InvalidBoard(_) => "invalid_board",
+            Error::UnsupportedVersion { .. } => "unsupported_version",
+            Error::Render(_) => "render",
+            Error::Import(_) => "import",
+            Error::InvalidShortcut(_) => "invalid_shortcut",
+            Error::ShortcutConflict(_) => "shortcut_conflict",
+            Error::Clipboard(_) => "clipboard",
+            Error::PasswordRequired(_) => "password_required",
+            Error::WrongPassword => "wrong_password",
+            Error::Keychain(_) => "keychain",
+            Error::UnknownMenuItem(_) => "unknown_menu_item",
+            Error::InvalidSettings(_) => "invalid_settings",
+            Error::BoardLocked { .. } => "board_locked",
+            Error::Screenshot(_) => "screenshot",
+            Error::Spellcheck(_) => "spellcheck",
+            Error::MissingDictionary { .. } => "missing_dictionary",
+            Error::Ocr(_) => "ocr",
+            Error::Updater(_) => "updater",
+            Error::UpdaterNotConfigured => "updater_not_configured",
+            Error::CharacterPalette(_) => "character_palette",
+            Error::Eyedropper(_) => "eyedropper",
+            Error::Plugin(_) => "plugin",
+            Error::Automation(_) => "automation",
+            Error::Accessibility(_) => "accessibility",
+            Error::MergeConflicts(_) => "merge_conflicts",
+            Error::Git(_) => "git",
+            Error::GlobalShortcut(_) => "shortcut",
+            Error::Database(_) => "database",
+            Error::Io(_) => "io",
+            Error::Json(_) => "json",
+            Error::Tauri(_) => "tauri",
+        }
+    }
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Error", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
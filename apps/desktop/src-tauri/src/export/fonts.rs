@@ -0,0 +1,196 @@
+use std::fmt::Write;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use resvg::usvg::{
+    fontdb::Style,
+    tiny_skia_path::{Path, PathSegment},
+    Group, Node,
+};
+use serde::Serialize;
+
+use super::svg::{escape, num};
+use crate::document::{BoardDocument, ElementKind, FontFamily};
+
+// Subset ranges copied from the `@font-face` rules in `styles/globals.css`
+const LATIN_RANGE: &str = "U+0000-00FF, U+0131, U+0152-0153, U+02BB-02BC, U+02C6, U+02DA, U+02DC, U+0304, U+0308, U+0329, U+2000-206F, U+20AC, U+2122, U+2191, U+2193, U+2212, U+2215, U+FEFF, U+FFFD";
+const LATIN_EXT_RANGE: &str = "U+0100-02BA, U+02BD-02C5, U+02C7-02CC, U+02CE-02D7, U+02DD-02FF, U+0304, U+0308, U+0329, U+1D00-1DBF, U+1E00-1E9F, U+1EF2-1EFF, U+2020, U+20A0-20AB, U+20AD-20C0, U+2113, U+2C60-2C7F, U+A720-A7FF";
+
+struct BundledFont {
+    family: &'static str,
+    weight: &'static str,
+    unicode_range: &'static str,
+    data: &'static [u8],
+}
+
+const CAVEAT: &[BundledFont] = &[
+    BundledFont {
+        family: "Caveat",
+        weight: "400 700",
+        unicode_range: LATIN_EXT_RANGE,
+        data: include_bytes!("../../../src/assets/fonts/Caveat-LatinExt.woff2"),
+    },
+    BundledFont {
+        family: "Caveat",
+        weight: "400 700",
+        unicode_range: LATIN_RANGE,
+        data: include_bytes!("../../../src/assets/fonts/Caveat-Latin.woff2"),
+    },
+];
+
+const LIBRE_BASKERVILLE: &[BundledFont] = &[
+    BundledFont {
+        family: "Libre Baskerville",
+        weight: "400 700",
+        unicode_range: LATIN_EXT_RANGE,
+        data: include_bytes!("../../../src/assets/fonts/LibreBaskerville-Regular-LatinExt.woff2"),
+    },
+    BundledFont {
+        family: "Libre Baskerville",
+        weight: "400 700",
+        unicode_range: LATIN_RANGE,
+        data: include_bytes!("../../../src/assets/fonts/LibreBaskerville-Regular-Latin.woff2"),
+    },
+];
+
+/// Fonts the app bundles for a family; system and code text use the
+/// viewer's own fonts, like the webview does.
+fn bundled(family: FontFamily) -> &'static [BundledFont] {
+    match family {
+        FontFamily::Handwritten => CAVEAT,
+        FontFamily::Serif => LIBRE_BASKERVILLE,
+        FontFamily::System | FontFamily::Code => &[],
+    }
+}
+
+/// Whether exports can carry the family's fonts along.
+pub fn is_bundled(family: FontFamily) -> bool {
+    !bundled(family).is_empty()
+}
+
+fn write_path(d: &mut String, path: &Path) {
+    for segment in path.segments() {
+        let _ = match segment {
+            PathSegment::MoveTo(p) => write!(d, "M{} {}", num(p.x.into()), num(p.y.into())),
+            PathSegment::LineTo(p) => write!(d, "L{} {}", num(p.x.into()), num(p.y.into())),
+            PathSegment::QuadTo(c, p) => write!(
+                d,
+                "Q{} {} {} {}",
+                num(c.x.into()),
+                num(c.y.into()),
+                num(p.x.into()),
+                num(p.y.into())
+            ),
+            PathSegment::CubicTo(c1, c2, p) => write!(
+                d,
+                "C{} {} {} {} {} {}",
+                num(c1.x.into()),
+                num(c1.y.into()),
+                num(c2.x.into()),
+                num(c2.y.into()),
+                num(p.x.into()),
+                num(p.y.into())
+            ),
+            PathSegment::Close => write!(d, "Z"),
+        };
+    }
+}
+
+fn collect_outlines(group: &Group, d: &mut String) {
+    for node in group.children() {
+        match node {
+            Node::Group(group) => collect_outlines(group, d),
+            Node::Text(text) => collect_outlines(text.flattened(), d),
+            Node::Path(path) => {
+                if let Some(path) = path.data().clone().transform(path.abs_transform()) {
+                    write_path(d, &path);
+                }
+            }
+            Node::Image(_) => {}
+        }
+    }
+}
+
+/// Shape an SVG `<text>` element in board coordinates with the installed
+/// fonts and return the outlines of its glyphs as one `<path>`, so only the
+/// glyphs the text uses end up in the file. `None` when nothing could be
+/// shaped, e.g. no installed font covers the text.
+pub fn outline_text(text: &str, fill: &str) -> Option<String> {
+    let svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">{text}</svg>"#);
+    let tree = super::parse_svg(&svg).ok()?;
+    let mut d = String::new();
+    collect_outlines(tree.root(), &mut d);
+    (!d.is_empty()).then(|| format!(r#"<path d="{d}" fill="{}"/>"#, escape(fill)))
+}
+
+/// An installed font face, as offered in font pickers.
+#[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemFont {
+    family: String,
+    /// `normal`, `italic` or `oblique`
+    style: &'static str,
+    /// CSS weight, 100 to 900
+    weight: u16,
+    monospaced: bool,
+}
+
+/// Font faces installed on this machine, sorted by family.
+#[tauri::command]
+pub async fn list_system_fonts() -> Vec<SystemFont> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let mut fonts: Vec<SystemFont> = super::font_database()
+            .faces()
+            .filter_map(|face| {
+                let (family, _) = face.families.first()?;
+                Some(SystemFont {
+                    family: family.clone(),
+                    style: match face.style {
+                        Style::Normal => "normal",
+                        Style::Italic => "italic",
+                        Style::Oblique => "oblique",
+                    },
+                    weight: face.weight.0,
+                    monospaced: face.monospaced,
+                })
+            })
+            .collect();
+        fonts.sort();
+        fonts.dedup();
+        fonts
+    })
+    .await
+    .unwrap_or_default()
+}
+
+/// Build a `<style>` block with `@font-face` rules for the bundled fonts the
+/// board's text uses, or an empty string when none are needed.
+pub fn font_face_style(document: &BoardDocument) -> String {
+    let mut families: Vec<FontFamily> = document
+        .board
+        .elements
+        .iter()
+        .filter_map(|element| match &element.kind {
+            ElementKind::Text(text) => Some(text.font_family),
+            _ => None,
+        })
+        .collect();
+    // Fixed order keeps the output byte-identical across exports
+    families.sort_by_key(|family| *family as u8);
+    families.dedup();
+
+    let mut css = String::new();
+    for font in families.into_iter().flat_map(bundled) {
+        let _ = write!(
+            css,
+            "@font-face{{font-family:'{}';font-style:normal;font-weight:{};src:url(data:font/woff2;base64,{}) format('woff2');unicode-range:{};}}",
+            font.family,
+            font.weight,
+            STANDARD.encode(font.data),
+            font.unicode_range
+        );
+    }
+    if css.is_empty() {
+        return css;
+    }
+    format!("<defs><style>{css}</style></defs>")
+}
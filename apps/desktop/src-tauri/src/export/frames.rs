@@ -0,0 +1,215 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Window};
+
+use super::{
+    markdown::{area, contains, reading_order},
+    parse_svg,
+    png::rasterize,
+    preset::ExportFormat,
+    svg::{render_svg_view, Resources},
+};
+use crate::{
+    document::{BoardDocument, Element, ElementKind, Rect},
+    error::{Error, Result},
+    notifications::{self, Notification},
+    persist, taskbar,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FrameExportOptions {
+    /// Output pixels per board unit for PNG
+    pub scale: f32,
+    /// Draw the board background; transparent otherwise (PNG and SVG)
+    pub background: bool,
+    /// Saved .boardkit file to read image assets from
+    pub board_path: Option<String>,
+}
+
+impl Default for FrameExportOptions {
+    fn default() -> Self {
+        Self {
+            scale: 2.0,
+            background: true,
+            board_path: None,
+        }
+    }
+}
+
+/// A slide of the board: like in the Markdown export, a labelled rectangle
+/// or ellipse with other items inside it.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Frame {
+    pub id: String,
+    pub label: String,
+    pub rect: Rect,
+}
+
+fn frame_label(element: &Element) -> Option<&str> {
+    match &element.kind {
+        ElementKind::Rectangle(shape) | ElementKind::Ellipse(shape) => {
+            shape.label.as_deref().map(str::trim).filter(|label| !label.is_empty())
+        }
+        _ => None,
+    }
+}
+
+/// The board's frames in reading order, which is also presentation order.
+pub fn frames(document: &BoardDocument) -> Vec<Frame> {
+    let items: Vec<(&str, Rect)> = document
+        .board
+        .elements
+        .iter()
+        .map(|element| (element.id.as_str(), element.bounds()))
+        .chain(document.board.widgets.iter().map(|widget| (widget.id.as_str(), widget.bounds())))
+        .collect();
+    let mut frames: Vec<Frame> = document
+        .board
+        .elements
+        .iter()
+        .filter_map(|element| {
+            let label = frame_label(element)?;
+            let rect = element.bounds();
+            items
+                .iter()
+                .any(|(id, bounds)| *id != element.id && area(bounds) < area(&rect) && contains(&rect, bounds))
+                .then(|| Frame {
+                    id: element.id.clone(),
+                    label: label.to_string(),
+                    rect,
+                })
+        })
+        .collect();
+    frames.sort_by(|a, b| reading_order(&a.rect, &b.rect));
+    frames
+}
+
+/// What a presenter reads under a frame: its text and note content in
+/// reading order, one paragraph each.
+pub fn notes(document: &BoardDocument, frame: &Frame) -> String {
+    let element_text = document.board.elements.iter().filter_map(|element| match &element.kind {
+        ElementKind::Text(text) if element.id != frame.id => Some((element.bounds(), text.content.as_str())),
+        _ => None,
+    });
+    let widget_text = document.board.widgets.iter().filter_map(|widget| {
+        let state = document.modules.get(&widget.id)?;
+        let text = state.get("content").or_else(|| state.get("title"))?.as_str()?;
+        Some((widget.bounds(), text))
+    });
+    let mut paragraphs: Vec<(Rect, &str)> = element_text
+        .chain(widget_text)
+        .filter(|(bounds, text)| !text.trim().is_empty() && contains(&frame.rect, bounds))
+        .collect();
+    paragraphs.sort_by(|a, b| reading_order(&a.0, &b.0));
+    paragraphs.iter().map(|(_, text)| text.trim()).collect::<Vec<_>>().join("\n\n")
+}
+
+/// Render one frame: just what lies inside it, cropped to its rect. PDFs get
+/// a single page the size of the frame.
+pub fn render_frame(
+    document: &BoardDocument,
+    frame: &Frame,
+    format: ExportFormat,
+    resources: &Resources,
+    scale: f32,
+) -> Result<Vec<u8>> {
+    if !(frame.rect.width > 0.0 && frame.rect.height > 0.0) {
+        return Err(Error::Render(format!("frame {:?} is empty", frame.label)));
+    }
+    let svg = render_svg_view(document, resources, &frame.rect);
+    match format {
+        ExportFormat::Svg => Ok(svg.into_bytes()),
+        ExportFormat::Png => rasterize(&parse_svg(&svg)?, scale),
+        ExportFormat::Pdf => svg2pdf::to_pdf(
+            &parse_svg(&svg)?,
+            svg2pdf::ConversionOptions::default(),
+            svg2pdf::PageOptions::default(),
+        )
+        .map_err(|err| Error::Render(err.to_string())),
+    }
+}
+
+fn load_resources(format: ExportFormat, options: &FrameExportOptions) -> Result<Resources> {
+    let mut resources = Resources::load(options.board_path.as_deref(), matches!(format, ExportFormat::Svg))?;
+    resources.transparent = !options.background;
+    Ok(resources)
+}
+
+fn write(path: &Path, bytes: &[u8]) -> Result<()> {
+    persist::write_atomic(path, bytes).map_err(|err| Error::from_io(err, &path.to_string_lossy()))
+}
+
+/// The board's frames, for picking one to export or present.
+#[tauri::command]
+pub fn list_frames(board_json: BoardDocument) -> Vec<Frame> {
+    frames(&board_json)
+}
+
+/// Render a single frame of the board natively and write it to `path`.
+#[tauri::command]
+pub async fn export_frame(
+    board_json: BoardDocument,
+    frame_id: String,
+    format: ExportFormat,
+    path: String,
+    options: Option<FrameExportOptions>,
+) -> Result<()> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        let frame = frames(&board_json)
+            .into_iter()
+            .find(|frame| frame.id == frame_id)
+            .ok_or_else(|| Error::NotFound(format!("frame {frame_id}")))?;
+        let resources = load_resources(format, &options)?;
+        write(
+            Path::new(&path),
+            &render_frame(&board_json, &frame, format, &resources, options.scale)?,
+        )
+    })
+    .await
+    .map_err(|err| Error::Render(err.to_string()))?
+}
+
+/// Export every frame into `dir` as `slide-1.<ext>`, `slide-2.<ext>`, ... in
+/// presentation order, returning the files written.
+#[tauri::command]
+pub async fn export_frames(
+    window: Window,
+    board_json: BoardDocument,
+    format: ExportFormat,
+    dir: String,
+    options: Option<FrameExportOptions>,
+) -> Result<Vec<String>> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        let frames = frames(&board_json);
+        if frames.is_empty() {
+            return Err(Error::NotFound("frames on this board".to_string()));
+        }
+        let progress = taskbar::Progress::start(&window);
+        let resources = load_resources(format, &options)?;
+        let mut written = Vec::with_capacity(frames.len());
+        for (index, frame) in frames.iter().enumerate() {
+            let path: PathBuf = Path::new(&dir).join(format!("slide-{}.{}", index + 1, format.extension()));
+            write(&path, &render_frame(&board_json, frame, format, &resources, options.scale)?)?;
+            written.push(path.to_string_lossy().into_owned());
+            progress.set(index + 1, frames.len());
+        }
+        drop(progress);
+        tracing::info!(dir, frames = written.len(), "frames exported");
+        notifications::notify(
+            window.app_handle(),
+            Notification {
+                title: "Slides exported".to_string(),
+                body: format!("Exported {} slides to {dir}", written.len()),
+                board: options.board_path.clone(),
+            },
+        );
+        Ok(written)
+    })
+    .await
+    .map_err(|err| Error::Render(err.to_string()))?
+}
@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{
+    frames::frames,
+    svg::{background_color, escape, render_svg_view, Resources, EXPORT_PADDING},
+};
+use crate::{
+    document::{BoardDocument, Rect},
+    error::{Error, Result},
+    persist,
+};
+
+/// Page with the embedded viewer; `{{NAME}}` placeholders are filled in by
+/// [`render_html`].
+const VIEWER: &str = include_str!("viewer.html");
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HtmlExportOptions {
+    /// Saved .boardkit file to read image assets from
+    pub board_path: Option<String>,
+    pub embed_fonts: bool,
+}
+
+impl Default for HtmlExportOptions {
+    fn default() -> Self {
+        Self {
+            board_path: None,
+            embed_fonts: true,
+        }
+    }
+}
+
+/// Replace every `{{NAME}}` of the template in one pass, so values that
+/// happen to contain a placeholder are left alone.
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len() + values.iter().map(|(_, value)| value.len()).sum::<usize>());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let name = after.find("}}").map(|end| &after[..end]);
+        match name.and_then(|name| values.iter().find(|(key, _)| *key == name)) {
+            Some((key, value)) => {
+                out.push_str(value);
+                rest = &after[key.len() + 2..];
+            }
+            None => {
+                out.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Render the board as a single HTML page: the board's SVG with images and
+/// fonts inlined, a pan and zoom viewer, and a sidebar listing its frames in
+/// reading order. Returns `None` when the board has nothing to draw.
+pub fn render_html(document: &BoardDocument, mut resources: Resources) -> Option<String> {
+    let bounds = document.content_bounds()?;
+    let content = Rect {
+        x: bounds.x - EXPORT_PADDING,
+        y: bounds.y - EXPORT_PADDING,
+        width: bounds.width + EXPORT_PADDING * 2.0,
+        height: bounds.height + EXPORT_PADDING * 2.0,
+    };
+    // The page paints the background, as the viewer shows beyond the content
+    resources.transparent = true;
+    let svg = render_svg_view(document, &resources, &content);
+    let data = json!({ "content": content, "frames": frames(document) });
+    // Keep `</script>` inside labels from closing the data block
+    let data = data.to_string().replace("</", "<\\/");
+    let title = escape(document.meta.title.trim());
+    let title = if title.is_empty() { "Board".to_string() } else { title };
+    Some(fill(
+        VIEWER,
+        &[
+            ("TITLE", &title),
+            ("BACKGROUND", &escape(background_color(document))),
+            ("SVG", &svg),
+            ("DATA", &data),
+        ],
+    ))
+}
+
+fn write_html(document: &BoardDocument, path: &str, options: HtmlExportOptions) -> Result<()> {
+    let resources = Resources::load(options.board_path.as_deref(), options.embed_fonts)?;
+    let html = render_html(document, resources).ok_or_else(|| Error::Render("board is empty".to_string()))?;
+    persist::write_atomic(Path::new(path), html.as_bytes()).map_err(|err| Error::from_io(err, path))
+}
+
+/// Write the board to `path` as a self-contained, read-only HTML page that
+/// opens in any browser, for sharing with people who don't have the app.
+#[tauri::command]
+pub async fn export_html(board_json: BoardDocument, path: String, options: Option<HtmlExportOptions>) -> Result<()> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || write_html(&board_json, &path, options))
+        .await
+        .map_err(|err| Error::Render(err.to_string()))?
+}
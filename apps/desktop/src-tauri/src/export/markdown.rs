@@ -0,0 +1,411 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{
+    parse_svg,
+    png::rasterize,
+    svg::{render_svg, Resources},
+};
+use crate::{
+    board,
+    document::{BoardDocument, Element, ElementKind, Rect, Widget},
+    error::{Error, Result},
+    persist,
+};
+
+/// Headings deeper than this are flattened, as Markdown stops at `######`.
+const MAX_HEADING_LEVEL: usize = 6;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MarkdownExportOptions {
+    /// Saved board holding the image assets
+    pub board_path: Option<String>,
+    /// Write an overview, one picture per frame and the board's images next
+    /// to the Markdown file, and link them
+    pub images: bool,
+    /// Pixel ratio of the rendered pictures
+    pub scale: f32,
+}
+
+impl Default for MarkdownExportOptions {
+    fn default() -> Self {
+        Self {
+            board_path: None,
+            images: true,
+            scale: 2.0,
+        }
+    }
+}
+
+/// A board item in reading order. Labelled shapes that contain other items
+/// act as frames and become headings.
+struct Item<'a> {
+    id: &'a str,
+    bounds: Rect,
+    source: Source<'a>,
+}
+
+enum Source<'a> {
+    Element(&'a Element),
+    Widget(&'a Widget, Option<&'a Value>),
+}
+
+impl Item<'_> {
+    fn frame_label(&self) -> Option<&str> {
+        match &self.source {
+            Source::Element(Element {
+                kind: ElementKind::Rectangle(shape) | ElementKind::Ellipse(shape),
+                ..
+            }) => shape.label.as_deref().map(str::trim).filter(|label| !label.is_empty()),
+            _ => None,
+        }
+    }
+
+    /// Short name used when listing connections.
+    fn name(&self) -> String {
+        let name = match &self.source {
+            Source::Element(element) => match &element.kind {
+                ElementKind::Text(text) => text.content.lines().next().unwrap_or_default().to_string(),
+                ElementKind::Rectangle(shape) | ElementKind::Ellipse(shape) => shape.label.clone().unwrap_or_default(),
+                ElementKind::Image(image) => image.alt.clone().unwrap_or_else(|| "Image".to_string()),
+                _ => String::new(),
+            },
+            Source::Widget(widget, state) => state
+                .and_then(|state| state.get("title").or_else(|| state.get("content")))
+                .and_then(Value::as_str)
+                .and_then(|text| text.lines().find(|line| !line.trim().is_empty()))
+                .map(str::to_string)
+                .unwrap_or_else(|| widget.module_id.clone()),
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            format!("`{}`", self.id)
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+pub(super) fn contains(outer: &Rect, inner: &Rect) -> bool {
+    let center = inner.center();
+    center.x >= outer.x && center.x <= outer.right() && center.y >= outer.y && center.y <= outer.bottom()
+}
+
+pub(super) fn area(rect: &Rect) -> f64 {
+    rect.width * rect.height
+}
+
+/// Top-to-bottom, then left-to-right for items roughly on the same row.
+pub(super) fn reading_order(a: &Rect, b: &Rect) -> std::cmp::Ordering {
+    let row = (a.y / 40.0).round().total_cmp(&(b.y / 40.0).round());
+    row.then(a.x.total_cmp(&b.x))
+}
+
+/// Prefix every line after the first so multi-line text stays inside its bullet.
+fn indent_continuation(text: &str, indent: &str) -> String {
+    let mut lines = text.trim().lines();
+    let mut out = lines.next().unwrap_or_default().to_string();
+    for line in lines {
+        out.push('\n');
+        out.push_str(indent);
+        out.push_str(line);
+    }
+    out
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+/// Sticky-note style widgets as Markdown: notes as text, todos as task lists,
+/// kanban boards as one sub-list per column.
+fn write_widget(out: &mut String, widget: &Widget, state: Option<&Value>, indent: &str) {
+    let Some(state) = state else {
+        let _ = writeln!(out, "{indent}- {}", widget.module_id);
+        return;
+    };
+    match widget.module_id.as_str() {
+        "todo" => {
+            let title = str_field(state, "title");
+            let _ = writeln!(out, "{indent}- {}", if title.is_empty() { "To-do" } else { title });
+            let items = state.get("items").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+            write_todo_items(out, items, None, &format!("{indent}  "));
+        }
+        "kanban" => {
+            let title = str_field(state, "title");
+            let _ = writeln!(out, "{indent}- {}", if title.is_empty() { "Kanban" } else { title });
+            let items = state.get("items").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+            for column in state.get("columns").and_then(Value::as_array).into_iter().flatten() {
+                let _ = writeln!(out, "{indent}  - **{}**", str_field(column, "title"));
+                let column_id = str_field(column, "id");
+                for item in items
+                    .iter()
+                    .filter(|item| str_field(item, "columnId") == column_id)
+                    .filter(|item| !item.get("archived").and_then(Value::as_bool).unwrap_or(false))
+                {
+                    let _ = writeln!(out, "{indent}    - {}", str_field(item, "title"));
+                }
+            }
+        }
+        _ => {
+            let content = str_field(state, "content");
+            if !content.trim().is_empty() {
+                let _ = writeln!(out, "{indent}- {}", indent_continuation(content, &format!("{indent}  ")));
+            }
+        }
+    }
+}
+
+fn write_todo_items(out: &mut String, items: &[Value], parent: Option<&str>, indent: &str) {
+    for item in items.iter().filter(|item| item.get("parentId").and_then(Value::as_str) == parent) {
+        let done = item.get("completed").and_then(Value::as_bool).unwrap_or(false);
+        let _ = writeln!(out, "{indent}- [{}] {}", if done { "x" } else { " " }, str_field(item, "label"));
+        write_todo_items(out, items, Some(str_field(item, "id")), &format!("{indent}  "));
+    }
+}
+
+struct Writer<'a> {
+    document: &'a BoardDocument,
+    items: Vec<Item<'a>>,
+    /// Index of the innermost frame holding each item
+    parents: Vec<Option<usize>>,
+    resources: Resources,
+    options: &'a MarkdownExportOptions,
+    /// Folder for pictures and its name as written in links
+    images_dir: PathBuf,
+    images_link: String,
+    written_images: HashSet<String>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(document: &'a BoardDocument, path: &Path, options: &'a MarkdownExportOptions) -> Result<Self> {
+        let mut items: Vec<Item> = document
+            .board
+            .elements
+            .iter()
+            .filter(|element| !matches!(element.kind, ElementKind::Line(_) | ElementKind::Arrow(_) | ElementKind::Draw(_)))
+            .map(|element| Item {
+                id: &element.id,
+                bounds: element.bounds(),
+                source: Source::Element(element),
+            })
+            .chain(document.board.widgets.iter().map(|widget| Item {
+                id: &widget.id,
+                bounds: widget.bounds(),
+                source: Source::Widget(widget, document.modules.get(&widget.id)),
+            }))
+            .collect();
+        items.sort_by(|a, b| reading_order(&a.bounds, &b.bounds));
+
+        // Smallest labelled shape strictly larger than the item that contains it
+        let parents = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                items
+                    .iter()
+                    .enumerate()
+                    .filter(|(other, frame)| {
+                        *other != index
+                            && frame.frame_label().is_some()
+                            && area(&frame.bounds) > area(&item.bounds)
+                            && contains(&frame.bounds, &item.bounds)
+                    })
+                    .min_by(|(_, a), (_, b)| area(&a.bounds).total_cmp(&area(&b.bounds)))
+                    .map(|(other, _)| other)
+            })
+            .collect();
+
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let images_link = format!("{}-images", persist::sanitize_file_stem(&stem));
+        let resources = match (&options.board_path, options.images) {
+            (Some(board_path), true) => Resources::load(Some(board_path), false)?,
+            _ => Resources::default(),
+        };
+        Ok(Self {
+            document,
+            items,
+            parents,
+            resources,
+            options,
+            images_dir: path.with_file_name(&images_link),
+            images_link,
+            written_images: HashSet::new(),
+        })
+    }
+
+    /// Save a picture next to the Markdown file and return its relative link.
+    fn write_image(&mut self, name: &str, bytes: &[u8]) -> Result<String> {
+        let mut file_name = name.to_string();
+        let mut counter = 2;
+        while self.written_images.contains(&file_name) {
+            let (stem, extension) = name.rsplit_once('.').unwrap_or((name, "png"));
+            file_name = format!("{stem}-{counter}.{extension}");
+            counter += 1;
+        }
+        fs::create_dir_all(&self.images_dir)?;
+        let path = self.images_dir.join(&file_name);
+        persist::write_atomic(&path, bytes).map_err(|err| Error::from_io(err, &path.to_string_lossy()))?;
+        self.written_images.insert(file_name.clone());
+        Ok(format!("{}/{}", self.images_link, file_name))
+    }
+
+    /// Render only `ids` (or the whole board) to PNG; `None` when nothing is visible.
+    fn render(&mut self, name: &str, ids: Option<&HashSet<&str>>) -> Result<Option<String>> {
+        let mut document = self.document.clone();
+        if let Some(ids) = ids {
+            document.retain_items(ids);
+        }
+        let Some(svg) = render_svg(&document, &self.resources) else {
+            return Ok(None);
+        };
+        let png = rasterize(&parse_svg(&svg)?, self.options.scale)?;
+        self.write_image(&format!("{}.png", persist::sanitize_file_stem(name)), &png).map(Some)
+    }
+
+    fn descendants(&self, frame: usize, out: &mut HashSet<&'a str>) {
+        for (index, parent) in self.parents.iter().enumerate() {
+            if *parent == Some(frame) {
+                out.insert(self.items[index].id);
+                self.descendants(index, out);
+            }
+        }
+    }
+
+    fn write_children(&mut self, out: &mut String, parent: Option<usize>, level: usize) -> Result<()> {
+        let children: Vec<usize> = (0..self.items.len()).filter(|index| self.parents[*index] == parent).collect();
+        // Loose items first, then sub-frames, so each heading's text sits right under it
+        for &index in &children {
+            if !self.is_frame(index) {
+                self.write_item(out, index)?;
+            }
+        }
+        for &index in &children {
+            if self.is_frame(index) {
+                self.write_frame(out, index, level)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_frame(&self, index: usize) -> bool {
+        self.items[index].frame_label().is_some() && self.parents.contains(&Some(index))
+    }
+
+    fn write_frame(&mut self, out: &mut String, index: usize, level: usize) -> Result<()> {
+        let label = self.items[index].frame_label().unwrap_or_default().to_string();
+        let _ = writeln!(out, "\n{} {}\n", "#".repeat(level.min(MAX_HEADING_LEVEL)), label.replace('\n', " "));
+        if self.options.images {
+            let mut ids = HashSet::from([self.items[index].id]);
+            self.descendants(index, &mut ids);
+            if let Some(link) = self.render(&label, Some(&ids))? {
+                let _ = writeln!(out, "![{label}]({link})\n");
+            }
+        }
+        self.write_children(out, Some(index), level + 1)
+    }
+
+    fn write_item(&mut self, out: &mut String, index: usize) -> Result<()> {
+        match self.items[index].source {
+            Source::Element(element) => match &element.kind {
+                ElementKind::Text(text) if !text.content.trim().is_empty() => {
+                    let _ = writeln!(out, "- {}", indent_continuation(&text.content, "  "));
+                }
+                ElementKind::Rectangle(shape) | ElementKind::Ellipse(shape) => {
+                    if let Some(label) = shape.label.as_deref().filter(|label| !label.trim().is_empty()) {
+                        let _ = writeln!(out, "- {}", indent_continuation(label, "  "));
+                    }
+                }
+                ElementKind::Image(image) => {
+                    let alt = image.alt.clone().unwrap_or_else(|| "Image".to_string());
+                    let asset = self.resources.images.get(&image.asset_id).cloned();
+                    match asset.filter(|_| self.options.images) {
+                        Some(bytes) => {
+                            let extension = self
+                                .document
+                                .assets
+                                .as_ref()
+                                .and_then(|assets| assets.assets.get(&image.asset_id))
+                                .and_then(|asset| board::extension_for_mime(&asset.mime_type))
+                                .unwrap_or("png");
+                            let name = format!("{}.{extension}", persist::sanitize_file_stem(&image.asset_id));
+                            let link = self.write_image(&name, &bytes)?;
+                            let _ = writeln!(out, "- ![{alt}]({link})");
+                        }
+                        None => {
+                            let _ = writeln!(out, "- {alt}");
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Source::Widget(widget, state) => write_widget(out, widget, state, ""),
+        }
+        Ok(())
+    }
+
+    /// Connections and bound arrows as "A → B" lines.
+    fn write_connections(&self, out: &mut String) {
+        let names: HashMap<&str, String> = self.items.iter().map(|item| (item.id, item.name())).collect();
+        let name = |id: &str| names.get(id).cloned().unwrap_or_else(|| format!("`{id}`"));
+        let mut lines = Vec::new();
+        for connection in self.document.board.connections.iter().flatten() {
+            let label = connection.label.as_deref().map(|label| format!(": {label}")).unwrap_or_default();
+            lines.push(format!("- {} → {}{label}", name(&connection.source_id), name(&connection.target_id)));
+        }
+        for element in &self.document.board.elements {
+            if let ElementKind::Arrow(line) | ElementKind::Line(line) = &element.kind {
+                if let (Some(start), Some(end)) = (&line.start_binding, &line.end_binding) {
+                    lines.push(format!("- {} → {}", name(&start.element_id), name(&end.element_id)));
+                }
+            }
+        }
+        if !lines.is_empty() {
+            let _ = writeln!(out, "\n## Connections\n");
+            for line in lines {
+                let _ = writeln!(out, "{line}");
+            }
+        }
+    }
+}
+
+/// Markdown outline of a board: frames become headings (nested frames nest),
+/// text, notes and lists become bullets in reading order, and connectors are
+/// listed at the end.
+pub fn render_markdown(document: &BoardDocument, path: &Path, options: &MarkdownExportOptions) -> Result<String> {
+    let mut writer = Writer::new(document, path, options)?;
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}", document.meta.title.replace('\n', " "));
+    if options.images {
+        if let Some(link) = writer.render("board", None)? {
+            let _ = writeln!(out, "\n![{}]({link})", document.meta.title);
+        }
+    }
+    out.push('\n');
+    writer.write_children(&mut out, None, 2)?;
+    writer.write_connections(&mut out);
+    Ok(out)
+}
+
+#[tauri::command]
+pub async fn export_markdown(
+    board_json: BoardDocument,
+    path: String,
+    options: Option<MarkdownExportOptions>,
+) -> Result<()> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        let markdown = render_markdown(&board_json, Path::new(&path), &options)?;
+        persist::write_atomic(Path::new(&path), markdown.as_bytes()).map_err(|err| Error::from_io(err, &path))
+    })
+    .await
+    .map_err(|err| Error::Render(err.to_string()))?
+}
@@ -0,0 +1,75 @@
+pub mod fonts;
+pub mod frames;
+pub mod html;
+pub mod markdown;
+pub mod pdf;
+pub mod png;
+pub mod preset;
+pub mod print;
+pub mod svg;
+
+use std::sync::{Arc, OnceLock};
+
+use resvg::usvg::{
+    self,
+    fontdb::{Database, Family},
+};
+
+use crate::error::{Error, Result};
+
+fn has_family(fonts: &Database, name: &str) -> bool {
+    fonts.faces().any(|face| face.families.iter().any(|(family, _)| family == name))
+}
+
+/// Point generic families (`sans-serif`, `serif`, ...) at an installed face when
+/// fontdb's defaults are missing, otherwise text using them is dropped.
+fn ensure_generic_families(fonts: &mut Database) {
+    let Some(fallback) = fonts
+        .faces()
+        .find_map(|face| face.families.first().map(|(family, _)| family.clone()))
+    else {
+        return;
+    };
+
+    if !has_family(fonts, fonts.family_name(&Family::SansSerif)) {
+        fonts.set_sans_serif_family(fallback.clone());
+    }
+    let sans_serif = fonts.family_name(&Family::SansSerif).to_string();
+    if !has_family(fonts, fonts.family_name(&Family::Serif)) {
+        fonts.set_serif_family(sans_serif.clone());
+    }
+    if !has_family(fonts, fonts.family_name(&Family::Monospace)) {
+        fonts.set_monospace_family(sans_serif.clone());
+    }
+    if !has_family(fonts, fonts.family_name(&Family::Cursive)) {
+        fonts.set_cursive_family(sans_serif.clone());
+    }
+    if !has_family(fonts, fonts.family_name(&Family::Fantasy)) {
+        fonts.set_fantasy_family(sans_serif);
+    }
+}
+
+/// System fonts are expensive to enumerate, so load them once per process.
+fn font_database() -> Arc<Database> {
+    static FONTS: OnceLock<Arc<Database>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let mut fonts = Database::new();
+            fonts.load_system_fonts();
+            ensure_generic_families(&mut fonts);
+            Arc::new(fonts)
+        })
+        .clone()
+}
+
+/// Parse SVG produced by [`svg::render_svg`] into a render tree.
+pub fn parse_svg(source: &str) -> Result<usvg::Tree> {
+    let fontdb = font_database();
+    let options = usvg::Options {
+        // Used for text whose font-family list matches nothing installed
+        font_family: fontdb.family_name(&Family::SansSerif).to_string(),
+        fontdb,
+        ..usvg::Options::default()
+    };
+    usvg::Tree::from_str(source, &options).map_err(|err| Error::Render(err.to_string()))
+}
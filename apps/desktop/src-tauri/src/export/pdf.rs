@@ -0,0 +1,378 @@
+use std::{collections::HashMap, fmt::Write, path::Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use pdf_writer::{Chunk, Content, Finish, Name, Pdf, Rect, Ref, TextStr};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::{
+    parse_svg,
+    svg::{escape, render_svg, Resources},
+};
+use crate::{
+    assets::{self, AssetStore},
+    document::{BoardDocument, FontFamily},
+    error::{Error, Result},
+    persist,
+};
+
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+/// Board units are CSS pixels (96 per inch); PDF uses points (72 per inch).
+const POINTS_PER_PX: f32 = 0.75;
+/// Refuse exports that would explode into an unprintable number of pages.
+const MAX_PAGES: u32 = 500;
+/// Height in points of the header and footer bands taken off the printable area.
+const BAND_HEIGHT: f32 = 24.0;
+const BAND_FONT_SIZE: f32 = 9.0;
+/// Widest a logo may get in the header band, in points.
+const LOGO_WIDTH: f32 = 96.0;
+/// Longest header or footer, in characters.
+pub const MAX_BRANDING_TEXT: usize = 200;
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageSize {
+    A4,
+    Letter,
+    /// Width and height in millimetres
+    Custom { width: f32, height: f32 },
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+/// Branding printed on every page: a header with a logo in its right corner
+/// and a footer. Header and footer text may use `{title}`, `{page}` and
+/// `{pages}`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PdfBranding {
+    pub header: Option<String>,
+    pub footer: Option<String>,
+    /// Asset store hash of the logo image
+    pub logo: Option<String>,
+}
+
+impl PdfBranding {
+    fn header(&self) -> Option<&str> {
+        self.header.as_deref().filter(|text| !text.trim().is_empty())
+    }
+
+    fn footer(&self) -> Option<&str> {
+        self.footer.as_deref().filter(|text| !text.trim().is_empty())
+    }
+
+    /// Reason the branding can't be used, if any.
+    pub fn validate(&self) -> Option<String> {
+        let too_long =
+            |text: &Option<String>| text.as_ref().is_some_and(|text| text.chars().count() > MAX_BRANDING_TEXT);
+        if too_long(&self.header) || too_long(&self.footer) {
+            return Some(format!("headers and footers are limited to {MAX_BRANDING_TEXT} characters"));
+        }
+        match &self.logo {
+            Some(hash) if assets::validate_hash(hash).is_err() => Some(format!("invalid logo asset {hash:?}")),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PdfExportOptions {
+    pub page_size: PageSize,
+    pub orientation: Orientation,
+    /// Page margin in millimetres
+    pub margin: f32,
+    /// Board zoom on paper; 1 prints board pixels at 96 DPI
+    pub scale: f32,
+    /// Shrink the board onto a single page instead of tiling
+    pub fit_to_page: bool,
+    /// Saved .boardkit file to read image assets from
+    pub board_path: Option<String>,
+    #[serde(flatten)]
+    pub branding: PdfBranding,
+    /// The logo's bytes, see [`PdfExportOptions::load_logo`]
+    #[serde(skip)]
+    pub logo_image: Option<Vec<u8>>,
+}
+
+impl Default for PdfExportOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::A4,
+            orientation: Orientation::Portrait,
+            margin: 10.0,
+            scale: 1.0,
+            fit_to_page: false,
+            board_path: None,
+            branding: PdfBranding::default(),
+            logo_image: None,
+        }
+    }
+}
+
+impl PdfExportOptions {
+    /// Page width and height in points.
+    fn page_points(&self) -> (f32, f32) {
+        let (width, height) = match self.page_size {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::Letter => (612.0, 792.0),
+            PageSize::Custom { width, height } => (width * POINTS_PER_MM, height * POINTS_PER_MM),
+        };
+        let (short, long) = (width.min(height), width.max(height));
+        match self.orientation {
+            Orientation::Portrait => (short, long),
+            Orientation::Landscape => (long, short),
+        }
+    }
+
+    /// Read the branding logo from the asset store, if there is one.
+    pub fn load_logo(&mut self, assets: &AssetStore) -> Result<()> {
+        if let Some(hash) = &self.branding.logo {
+            self.logo_image = Some(assets.get(hash)?);
+        }
+        Ok(())
+    }
+}
+
+fn logo_mime_type(data: &[u8]) -> Result<&'static str> {
+    if let Ok(format) = image::guess_format(data) {
+        return Ok(format.to_mime_type());
+    }
+    let head = String::from_utf8_lossy(&data[..data.len().min(512)]);
+    if head.contains("<svg") {
+        Ok("image/svg+xml")
+    } else {
+        Err(Error::Render("the logo is not an image".to_string()))
+    }
+}
+
+/// Fill in the placeholders of a header or footer.
+fn branding_text(template: &str, title: &str, page: usize, pages: usize) -> String {
+    template
+        .replace("{title}", title)
+        .replace("{page}", &page.to_string())
+        .replace("{pages}", &pages.to_string())
+}
+
+fn svg_page((page_width, page_height): (f32, f32), body: &str) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{page_width}" height="{page_height}" viewBox="0 0 {page_width} {page_height}">{body}</svg>"#
+    )
+}
+
+/// The header and footer text of one page as SVG, page sized and in points.
+fn branding_svg(
+    branding: &PdfBranding,
+    title: &str,
+    (page, pages): (usize, usize),
+    page_size: (f32, f32),
+    margin: f32,
+) -> String {
+    let font = escape(FontFamily::System.css());
+    let baseline = BAND_HEIGHT / 2.0 + BAND_FONT_SIZE * 0.35;
+    let mut body = String::new();
+    if let Some(header) = branding.header() {
+        let _ = write!(
+            body,
+            r##"<text x="{margin}" y="{}" font-family="{font}" font-size="{BAND_FONT_SIZE}" fill="#52525b">{}</text>"##,
+            margin + baseline,
+            escape(&branding_text(header, title, page, pages))
+        );
+    }
+    if let Some(footer) = branding.footer() {
+        let _ = write!(
+            body,
+            r##"<text x="{}" y="{}" font-family="{font}" font-size="{BAND_FONT_SIZE}" text-anchor="middle" fill="#52525b">{}</text>"##,
+            page_size.0 / 2.0,
+            page_size.1 - margin - BAND_HEIGHT + baseline,
+            escape(&branding_text(footer, title, page, pages))
+        );
+    }
+    svg_page(page_size, &body)
+}
+
+/// The logo in the header's right corner as SVG, page sized and in points.
+fn logo_svg(logo: &[u8], page_size: (f32, f32), margin: f32) -> Result<String> {
+    let image = format!(
+        r#"<image x="{}" y="{}" width="{LOGO_WIDTH}" height="{}" preserveAspectRatio="xMaxYMid meet" href="data:{};base64,{}"/>"#,
+        page_size.0 - margin - LOGO_WIDTH,
+        margin + 3.0,
+        BAND_HEIGHT - 6.0,
+        logo_mime_type(logo)?,
+        STANDARD.encode(logo)
+    );
+    Ok(svg_page(page_size, &image))
+}
+
+/// Convert SVG into a PDF XObject, numbered from `alloc`.
+fn svg_x_object(svg: &str, alloc: &mut Ref) -> Result<(Chunk, Ref)> {
+    let (chunk, root) = svg2pdf::to_chunk(&parse_svg(svg)?, svg2pdf::ConversionOptions::default())
+        .map_err(|err| Error::Render(err.to_string()))?;
+    let mut renumbered = HashMap::new();
+    let chunk = chunk.renumber(|old| *renumbered.entry(old).or_insert_with(|| alloc.bump()));
+    Ok((chunk, renumbered[&root]))
+}
+
+pub fn render_pdf(document: &BoardDocument, options: &PdfExportOptions) -> Result<Vec<u8>> {
+    let (page_width, page_height) = options.page_points();
+    let margin = options.margin.max(0.0) * POINTS_PER_MM;
+    let header_band = if options.branding.header().is_some() || options.logo_image.is_some() {
+        BAND_HEIGHT
+    } else {
+        0.0
+    };
+    let footer_band = if options.branding.footer().is_some() { BAND_HEIGHT } else { 0.0 };
+    let printable_width = page_width - margin * 2.0;
+    let printable_height = page_height - margin * 2.0 - header_band - footer_band;
+    let (printable_bottom, printable_top) = (margin + footer_band, page_height - margin - header_band);
+    if !(printable_width > 0.0 && printable_height > 0.0) {
+        return Err(Error::Render("margins leave no printable area".to_string()));
+    }
+    if !(options.scale.is_finite() && options.scale > 0.0) {
+        return Err(Error::Render(format!("invalid scale {}", options.scale)));
+    }
+
+    let resources = Resources::load(options.board_path.as_deref(), false)?;
+    let svg = render_svg(document, &resources).ok_or_else(|| Error::Render("board is empty".to_string()))?;
+    let tree = parse_svg(&svg)?;
+
+    let size = tree.size();
+    let mut scale = options.scale * POINTS_PER_PX;
+    if options.fit_to_page {
+        scale = (printable_width / size.width()).min(printable_height / size.height());
+    }
+    let (content_width, content_height) = (size.width() * scale, size.height() * scale);
+    // Small epsilon so rounding doesn't spill a sliver onto an extra page
+    let columns = ((content_width / printable_width) - 0.001).ceil().max(1.0) as u32;
+    let rows = ((content_height / printable_height) - 0.001).ceil().max(1.0) as u32;
+    if columns * rows > MAX_PAGES {
+        return Err(Error::Render(format!(
+            "export would need {} pages; lower the scale or use fit to page",
+            columns * rows
+        )));
+    }
+
+    let (chunk, board_ref) =
+        svg2pdf::to_chunk(&tree, svg2pdf::ConversionOptions::default()).map_err(|err| Error::Render(err.to_string()))?;
+
+    let mut alloc = Ref::new(1);
+    let catalog_ref = alloc.bump();
+    let page_tree_ref = alloc.bump();
+    let info_ref = alloc.bump();
+    let mut renumbered = HashMap::new();
+    let chunk = chunk.renumber(|old| *renumbered.entry(old).or_insert_with(|| alloc.bump()));
+    let board_ref = renumbered[&board_ref];
+
+    let page_count = (columns * rows) as usize;
+    let page_refs: Vec<(Ref, Ref)> = (0..page_count).map(|_| (alloc.bump(), alloc.bump())).collect();
+
+    let mut branding_chunks = Vec::new();
+    let logo_ref = match &options.logo_image {
+        Some(logo) => {
+            let (chunk, logo_ref) = svg_x_object(&logo_svg(logo, (page_width, page_height), margin)?, &mut alloc)?;
+            branding_chunks.push(chunk);
+            Some(logo_ref)
+        }
+        None => None,
+    };
+    // Pages whose header and footer read the same share them
+    let mut text_refs = Vec::with_capacity(page_count);
+    if options.branding.header().is_some() || options.branding.footer().is_some() {
+        let mut rendered: HashMap<String, Ref> = HashMap::new();
+        for page in 1..=page_count {
+            let pages = (page, page_count);
+            let svg = branding_svg(&options.branding, &document.meta.title, pages, (page_width, page_height), margin);
+            let text_ref = match rendered.get(&svg) {
+                Some(text_ref) => *text_ref,
+                None => {
+                    let (chunk, text_ref) = svg_x_object(&svg, &mut alloc)?;
+                    branding_chunks.push(chunk);
+                    rendered.insert(svg, text_ref);
+                    text_ref
+                }
+            };
+            text_refs.push(text_ref);
+        }
+    }
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_ref).pages(page_tree_ref);
+    pdf.pages(page_tree_ref)
+        .kids(page_refs.iter().map(|(page, _)| *page))
+        .count(page_refs.len() as i32);
+    pdf.document_info(info_ref).title(TextStr(&document.meta.title));
+
+    let board_name = Name(b"Board");
+    let (logo_name, text_name) = (Name(b"Logo"), Name(b"Branding"));
+    // Pages run left to right, then top to bottom, like reading the board
+    for (index, (page_ref, content_ref)) in page_refs.iter().enumerate() {
+        let (column, row) = (index as u32 % columns, index as u32 / columns);
+        let x = margin - column as f32 * printable_width;
+        let y = printable_top + row as f32 * printable_height - content_height;
+
+        let mut content = Content::new();
+        content.save_state();
+        content
+            .rect(margin, printable_bottom, printable_width, printable_height)
+            .clip_nonzero()
+            .end_path();
+        content.transform([content_width, 0.0, 0.0, content_height, x, y]);
+        content.x_object(board_name);
+        content.restore_state();
+        let text_ref = text_refs.get(index).copied();
+        for name in [logo_ref.map(|_| logo_name), text_ref.map(|_| text_name)].into_iter().flatten() {
+            content.save_state();
+            content.transform([page_width, 0.0, 0.0, page_height, 0.0, 0.0]);
+            content.x_object(name);
+            content.restore_state();
+        }
+        pdf.stream(*content_ref, &content.finish());
+
+        let mut page = pdf.page(*page_ref);
+        page.media_box(Rect::new(0.0, 0.0, page_width, page_height));
+        page.parent(page_tree_ref);
+        page.contents(*content_ref);
+        let mut resources = page.resources();
+        let mut x_objects = resources.x_objects();
+        x_objects.pair(board_name, board_ref);
+        if let Some(logo_ref) = logo_ref {
+            x_objects.pair(logo_name, logo_ref);
+        }
+        if let Some(text_ref) = text_ref {
+            x_objects.pair(text_name, text_ref);
+        }
+        x_objects.finish();
+        resources.finish();
+        page.finish();
+    }
+
+    pdf.extend(&chunk);
+    for chunk in &branding_chunks {
+        pdf.extend(chunk);
+    }
+    Ok(pdf.finish())
+}
+
+/// Render the board as vector PDF, tiled across as many pages as needed, and write it to `path`.
+#[tauri::command]
+pub async fn export_board_pdf(
+    assets: State<'_, AssetStore>,
+    board_json: BoardDocument,
+    path: String,
+    options: Option<PdfExportOptions>,
+) -> Result<()> {
+    let mut options = options.unwrap_or_default();
+    options.load_logo(&assets)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let pdf = render_pdf(&board_json, &options)?;
+        persist::write_atomic(Path::new(&path), &pdf).map_err(|err| Error::from_io(err, &path))
+    })
+    .await
+    .map_err(|err| Error::Render(err.to_string()))?
+}
@@ -0,0 +1,77 @@
+use resvg::{tiny_skia, usvg};
+use serde::Deserialize;
+use tauri::ipc::Response;
+
+use super::{
+    parse_svg,
+    svg::{render_svg_layout, ExportLayout, Resources},
+};
+use crate::{
+    document::BoardDocument,
+    error::{Error, Result},
+};
+
+/// Upper bound on output pixels (~16k x 16k) to keep memory use predictable.
+const MAX_PIXELS: u64 = 268_435_456;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PngExportOptions {
+    /// Saved .boardkit file to read image assets from
+    pub board_path: Option<String>,
+    /// Output pixels per board unit (2 = retina), independent of the webview's canvas limits
+    pub pixel_ratio: f32,
+    #[serde(flatten)]
+    pub layout: ExportLayout,
+}
+
+impl Default for PngExportOptions {
+    fn default() -> Self {
+        Self {
+            board_path: None,
+            pixel_ratio: 2.0,
+            layout: ExportLayout::default(),
+        }
+    }
+}
+
+/// Rasterize a parsed SVG tree at the given scale into a premultiplied pixmap.
+pub fn rasterize_pixmap(tree: &usvg::Tree, scale: f32) -> Result<tiny_skia::Pixmap> {
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(Error::Render(format!("invalid scale {scale}")));
+    }
+    let size = tree.size();
+    let width = (size.width() * scale).ceil() as u32;
+    let height = (size.height() * scale).ceil() as u32;
+    if u64::from(width) * u64::from(height) > MAX_PIXELS {
+        return Err(Error::Render(format!("export of {width}x{height} pixels is too large")));
+    }
+
+    let mut pixmap = tiny_skia::Pixmap::new(width.max(1), height.max(1))
+        .ok_or_else(|| Error::Render(format!("cannot allocate a {width}x{height} image")))?;
+    resvg::render(tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    Ok(pixmap)
+}
+
+/// Rasterize a parsed SVG tree at the given scale and encode it as PNG.
+pub fn rasterize(tree: &usvg::Tree, scale: f32) -> Result<Vec<u8>> {
+    rasterize_pixmap(tree, scale)?
+        .encode_png()
+        .map_err(|err| Error::Render(err.to_string()))
+}
+
+pub fn render_png(document: &BoardDocument, options: &PngExportOptions) -> Result<Vec<u8>> {
+    let resources = Resources::load(options.board_path.as_deref(), false)?;
+    let svg = render_svg_layout(document, &resources, &options.layout, 1.0)?;
+    rasterize(&parse_svg(&svg)?, options.pixel_ratio)
+}
+
+/// Render the board natively and return PNG bytes.
+#[tauri::command]
+pub async fn export_board_png(board_json: BoardDocument, options: Option<PngExportOptions>) -> Result<Response> {
+    let options = options.unwrap_or_default();
+    let png = tauri::async_runtime::spawn_blocking(move || render_png(&board_json, &options))
+        .await
+        .map_err(|err| Error::Render(err.to_string()))??;
+    Ok(Response::new(png))
+}
@@ -0,0 +1,267 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, Window};
+
+use super::{
+    parse_svg,
+    pdf::{render_pdf, PdfBranding, PdfExportOptions},
+    png::rasterize,
+    svg::{render_svg, Resources},
+};
+use crate::{
+    assets::AssetStore,
+    board,
+    document::BoardDocument,
+    error::{Error, Result},
+    notifications::{self, Notification},
+    persist,
+    settings::Settings,
+    taskbar,
+};
+
+/// Largest preset scale, so a typo can't request a multi-gigabyte image.
+pub const MAX_PRESET_SCALE: f32 = 8.0;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Png,
+    Svg,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Svg => "svg",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_background() -> bool {
+    true
+}
+
+/// Named export settings, stored in settings.json.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPreset {
+    pub name: String,
+    pub format: ExportFormat,
+    /// Pixel ratio for PNG, zoom on paper for PDF; SVG is resolution independent
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Draw the board background; transparent otherwise (PNG and SVG)
+    #[serde(default = "default_background")]
+    pub background: bool,
+    /// Only export the selected items. Saved boards have no selection, so
+    /// batch exports always render whole boards.
+    #[serde(default)]
+    pub selection_only: bool,
+    /// Header, footer and logo on every page (PDF)
+    #[serde(default)]
+    pub branding: PdfBranding,
+}
+
+impl ExportPreset {
+    fn new(name: &str, format: ExportFormat, scale: f32) -> Self {
+        Self {
+            name: name.to_string(),
+            format,
+            scale,
+            background: true,
+            selection_only: false,
+            branding: PdfBranding::default(),
+        }
+    }
+}
+
+pub fn default_presets() -> Vec<ExportPreset> {
+    vec![
+        ExportPreset::new("PNG (2x)", ExportFormat::Png, 2.0),
+        ExportPreset::new("SVG", ExportFormat::Svg, 1.0),
+        ExportPreset::new("PDF", ExportFormat::Pdf, 1.0),
+    ]
+}
+
+/// Reason a preset list can't be saved, if any.
+pub fn validate_presets(presets: &[ExportPreset]) -> Option<String> {
+    let mut names = HashSet::new();
+    for preset in presets {
+        if preset.name.trim().is_empty() {
+            return Some("export preset names must not be empty".to_string());
+        }
+        if !names.insert(preset.name.as_str()) {
+            return Some(format!("duplicate export preset {:?}", preset.name));
+        }
+        if !(preset.scale.is_finite() && preset.scale > 0.0 && preset.scale <= MAX_PRESET_SCALE) {
+            return Some(format!("export preset {:?} scale must be between 0 and {MAX_PRESET_SCALE}", preset.name));
+        }
+        if let Some(reason) = preset.branding.validate() {
+            return Some(format!("export preset {:?}: {reason}", preset.name));
+        }
+    }
+    None
+}
+
+fn find_preset(settings: &Settings, name: &str) -> Result<ExportPreset> {
+    settings
+        .get()
+        .export_presets
+        .into_iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| Error::Render(format!("unknown export preset {name:?}")))
+}
+
+/// Render a board with a preset. `board_path` is the saved file holding its
+/// image assets; `assets` holds the branding logo.
+pub fn render_with_preset(
+    document: &BoardDocument,
+    board_path: Option<&str>,
+    preset: &ExportPreset,
+    assets: &AssetStore,
+) -> Result<Vec<u8>> {
+    match preset.format {
+        ExportFormat::Pdf => {
+            let mut options = PdfExportOptions {
+                scale: preset.scale,
+                board_path: board_path.map(str::to_string),
+                branding: preset.branding.clone(),
+                ..PdfExportOptions::default()
+            };
+            options.load_logo(assets)?;
+            render_pdf(document, &options)
+        }
+        ExportFormat::Png | ExportFormat::Svg => {
+            let mut resources = Resources::load(board_path, matches!(preset.format, ExportFormat::Svg))?;
+            resources.transparent = !preset.background;
+            let svg = render_svg(document, &resources).ok_or_else(|| Error::Render("board is empty".to_string()))?;
+            match preset.format {
+                ExportFormat::Png => rasterize(&parse_svg(&svg)?, preset.scale),
+                _ => Ok(svg.into_bytes()),
+            }
+        }
+    }
+}
+
+/// Export the open board with the named preset, honouring `selectionOnly`.
+#[tauri::command]
+pub async fn export_with_preset(
+    window: Window,
+    board_json: BoardDocument,
+    path: String,
+    preset: String,
+    board_path: Option<String>,
+    selection: Option<Vec<String>>,
+) -> Result<()> {
+    let preset = find_preset(&window.state::<Settings>(), &preset)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut document = board_json;
+        if preset.selection_only {
+            let selection = selection.unwrap_or_default();
+            document.retain_items(&selection.iter().map(String::as_str).collect());
+        }
+        let bytes = render_with_preset(&document, board_path.as_deref(), &preset, &window.state::<AssetStore>())?;
+        persist::write_atomic(Path::new(&path), &bytes).map_err(|err| Error::from_io(err, &path))
+    })
+    .await
+    .map_err(|err| Error::Render(err.to_string()))?
+}
+
+/// Outcome of one board in a batch export, also sent as `batch-export-progress`.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchExportItem {
+    path: String,
+    output: Option<String>,
+    error: Option<String>,
+    completed: usize,
+    total: usize,
+}
+
+/// `<stem>.<ext>` in `dir`, numbered when boards from different folders share a name.
+fn output_path(dir: &Path, board_path: &str, extension: &str, taken: &mut BTreeSet<PathBuf>) -> PathBuf {
+    let stem = Path::new(board_path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "board".to_string());
+    let mut candidate = dir.join(format!("{stem}.{extension}"));
+    let mut counter = 2;
+    while taken.contains(&candidate) {
+        candidate = dir.join(format!("{stem} ({counter}).{extension}"));
+        counter += 1;
+    }
+    taken.insert(candidate.clone());
+    candidate
+}
+
+fn export_board_file(board_path: &str, output: &Path, preset: &ExportPreset, assets: &AssetStore) -> Result<()> {
+    let document: BoardDocument = serde_json::from_value(board::read_board_document(board_path)?)?;
+    let bytes = render_with_preset(&document, Some(board_path), preset, assets)?;
+    persist::write_atomic(output, &bytes).map_err(|err| Error::from_io(err, &output.to_string_lossy()))
+}
+
+/// Export saved boards into `output_dir` with the named preset. A board that
+/// fails doesn't stop the batch; its error is reported in the results.
+#[tauri::command]
+pub async fn batch_export(
+    window: Window,
+    paths: Vec<String>,
+    preset: String,
+    output_dir: String,
+) -> Result<Vec<BatchExportItem>> {
+    let preset = find_preset(&window.state::<Settings>(), &preset)?;
+    let output_dir = PathBuf::from(output_dir);
+    std::fs::create_dir_all(&output_dir).map_err(|err| Error::from_io(err, &output_dir.to_string_lossy()))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let total = paths.len();
+        let progress = taskbar::Progress::start(&window);
+        let assets = window.state::<AssetStore>();
+        let mut taken = BTreeSet::new();
+        let mut results = Vec::with_capacity(total);
+        for (index, path) in paths.into_iter().enumerate() {
+            let output = output_path(&output_dir, &path, preset.format.extension(), &mut taken);
+            let error = export_board_file(&path, &output, &preset, &assets).err();
+            if let Some(err) = &error {
+                tracing::warn!(path, "batch export failed: {err}");
+            }
+            let item = BatchExportItem {
+                output: error.is_none().then(|| output.to_string_lossy().into_owned()),
+                error: error.map(|err| err.to_string()),
+                path,
+                completed: index + 1,
+                total,
+            };
+            let _ = window.emit_to(window.label(), "batch-export-progress", item.clone());
+            progress.set(index + 1, total);
+            results.push(item);
+        }
+        drop(progress);
+        let failed = results.iter().filter(|item| item.error.is_some()).count();
+        notifications::notify(
+            window.app_handle(),
+            Notification {
+                title: "Batch export finished".to_string(),
+                body: match failed {
+                    0 => format!("Exported {total} boards to {}", output_dir.display()),
+                    failed => format!("{failed} of {total} boards failed to export"),
+                },
+                board: None,
+            },
+        );
+        results
+    })
+    .await
+    .map_err(|err| Error::Render(err.to_string()))
+}
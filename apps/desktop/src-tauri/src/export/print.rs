@@ -0,0 +1,77 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use tauri::State;
+
+use super::pdf::{render_pdf, PdfExportOptions};
+use crate::{
+    assets::AssetStore,
+    document::BoardDocument,
+    error::{Error, Result},
+    persist,
+};
+
+/// Temp folder for spooled PDFs; each board overwrites its previous print.
+const PRINT_DIR: &str = "boardkit-print";
+
+fn spool(document: &BoardDocument, options: &PdfExportOptions) -> Result<PathBuf> {
+    let dir = env::temp_dir().join(PRINT_DIR);
+    fs::create_dir_all(&dir)?;
+    let stem = persist::sanitize_file_stem(&document.meta.title);
+    let path = dir.join(format!("{}.pdf", if stem.is_empty() { "board" } else { &stem }));
+    persist::write_atomic(&path, &render_pdf(document, options)?)?;
+    Ok(path)
+}
+
+/// The print-documents Apple event makes Preview show its print panel.
+#[cfg(target_os = "macos")]
+fn print_command(path: &Path) -> Command {
+    let mut command = Command::new("osascript");
+    command
+        .args(["-e", "on run argv", "-e", r#"tell application "Preview" to print (POSIX file (item 1 of argv))"#])
+        .args(["-e", "end run"])
+        .arg(path);
+    command
+}
+
+#[cfg(target_os = "windows")]
+fn print_command(path: &Path) -> Command {
+    // The path goes through the environment, out of reach of PowerShell quoting
+    let mut command = Command::new("powershell");
+    command
+        .args(["-NoProfile", "-Command", "Start-Process -FilePath $env:BOARDKIT_PRINT_PATH -Verb Print"])
+        .env("BOARDKIT_PRINT_PATH", path);
+    command
+}
+
+/// Linux has no system print dialog, so hand the PDF to the default viewer.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn print_command(path: &Path) -> Command {
+    let mut command = Command::new("xdg-open");
+    command.arg(path);
+    command
+}
+
+/// Render the board through the PDF pipeline and send it to the OS print flow.
+#[tauri::command]
+pub async fn print_board(
+    assets: State<'_, AssetStore>,
+    board_json: BoardDocument,
+    options: Option<PdfExportOptions>,
+) -> Result<()> {
+    let mut options = options.unwrap_or_default();
+    options.load_logo(&assets)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = spool(&board_json, &options)?;
+        let status = print_command(&path).status()?;
+        if !status.success() {
+            return Err(Error::Render(format!("print command exited with {status}")));
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|err| Error::Render(err.to_string()))?
+}
@@ -0,0 +1,662 @@
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+
+use super::{
+    fonts::{font_face_style, is_bundled, outline_text},
+    parse_svg,
+};
+use crate::{
+    board,
+    document::{
+        ArrowHead, BoardDocument, ConnectionTargetType, Element, ElementKind, ElementStyle, FontFamily,
+        ImageObjectFit, ImageProps, Point, Rect, StrokeDash, TextAlign, TextProps, Widget,
+    },
+    error::{Error, Result},
+    persist,
+};
+
+/// Margin around the content bounds, matching the webview export.
+pub const EXPORT_PADDING: f64 = 20.0;
+/// Used when the board background is `auto` (theme-dependent); element colors
+/// are authored against the dark theme, so exports use its background.
+const AUTO_BACKGROUND: &str = "#0a0a0a";
+const CONNECTION_COLOR: &str = "#a1a1aa";
+const ARROW_HEAD_LENGTH: f64 = 12.0;
+const TEXT_PADDING: f64 = 4.0;
+const TEXT_LINE_HEIGHT: f64 = 1.4;
+const LABEL_FONT_SIZE: f64 = 14.0;
+
+/// Data the renderer can inline beyond the document itself.
+#[derive(Default)]
+pub struct Resources {
+    /// Image asset bytes keyed by asset id; images without data render as placeholders
+    pub images: BTreeMap<String, Vec<u8>>,
+    /// Inline the bundled fonts used by text, and outline text in system fonts,
+    /// so the file renders the same anywhere
+    pub embed_fonts: bool,
+    /// Leave out the board background fill
+    pub transparent: bool,
+}
+
+impl Resources {
+    /// Collect image data from the saved board at `board_path`, if any.
+    pub fn load(board_path: Option<&str>, embed_fonts: bool) -> Result<Self> {
+        let images = match board_path {
+            Some(path) => board::read_assets(path)?,
+            None => BTreeMap::new(),
+        };
+        Ok(Self {
+            images,
+            embed_fonts,
+            transparent: false,
+        })
+    }
+}
+
+/// Format a coordinate with at most two decimals so output is stable and compact.
+pub(super) fn num(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    let mut text = format!("{rounded:.2}");
+    while text.ends_with('0') {
+        text.pop();
+    }
+    if text.ends_with('.') {
+        text.pop();
+    }
+    if text == "-0" {
+        text = "0".to_string();
+    }
+    text
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn stroke_attrs(style: &ElementStyle) -> String {
+    let mut attrs = format!(
+        r#"stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round""#,
+        escape(&style.stroke_color),
+        num(style.stroke_width)
+    );
+    let width = style.stroke_width;
+    match style.stroke_dash.unwrap_or_default() {
+        StrokeDash::Solid => {}
+        StrokeDash::Dashed => {
+            let _ = write!(attrs, r#" stroke-dasharray="{} {}""#, num(width * 4.0), num(width * 2.0));
+        }
+        StrokeDash::Dotted => {
+            let _ = write!(attrs, r#" stroke-dasharray="{} {}""#, num(width), num(width * 2.0));
+        }
+    }
+    attrs
+}
+
+fn fill_attr(style: &ElementStyle) -> String {
+    match &style.fill_color {
+        Some(fill) => format!(r#"fill="{}""#, escape(fill)),
+        None => r#"fill="none""#.to_string(),
+    }
+}
+
+/// Open a group carrying the element's rotation and opacity.
+fn open_group(out: &mut String, element: &Element) {
+    let mut attrs = format!(r#" data-id="{}""#, escape(&element.id));
+    let angle = element.angle.unwrap_or(0.0);
+    if angle != 0.0 {
+        let center = element.rect.center();
+        let _ = write!(
+            attrs,
+            r#" transform="rotate({} {} {})""#,
+            num(angle.to_degrees()),
+            num(center.x),
+            num(center.y)
+        );
+    }
+    if element.style.opacity < 1.0 {
+        let _ = write!(attrs, r#" opacity="{}""#, num(element.style.opacity));
+    }
+    let _ = write!(out, "<g{attrs}>");
+}
+
+fn arrow_head(out: &mut String, tip: Point, from: Point, style: &ElementStyle) {
+    let angle = (tip.y - from.y).atan2(tip.x - from.x);
+    let spread = std::f64::consts::PI / 6.0;
+    let left = Point {
+        x: tip.x - ARROW_HEAD_LENGTH * (angle - spread).cos(),
+        y: tip.y - ARROW_HEAD_LENGTH * (angle - spread).sin(),
+    };
+    let right = Point {
+        x: tip.x - ARROW_HEAD_LENGTH * (angle + spread).cos(),
+        y: tip.y - ARROW_HEAD_LENGTH * (angle + spread).sin(),
+    };
+    let solid = ElementStyle {
+        stroke_dash: None,
+        ..style.clone()
+    };
+    let _ = write!(
+        out,
+        r#"<path d="M{} {} L{} {} L{} {}" fill="none" {}/>"#,
+        num(left.x),
+        num(left.y),
+        num(tip.x),
+        num(tip.y),
+        num(right.x),
+        num(right.y),
+        stroke_attrs(&solid)
+    );
+}
+
+/// Greedy word wrap using an average glyph width; good enough to keep
+/// exported text inside its box without a full text layout engine.
+fn wrap_lines(text: &str, font_size: f64, max_width: f64) -> Vec<String> {
+    let max_chars = ((max_width / (font_size * 0.55)).floor() as usize).max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let candidate_len = line.chars().count() + usize::from(!line.is_empty()) + word.chars().count();
+            if !line.is_empty() && candidate_len > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn text_lines(out: &mut String, lines: &[String], x: f64, first_baseline: f64, line_height: f64) {
+    for (index, line) in lines.iter().enumerate() {
+        let _ = write!(
+            out,
+            r#"<tspan x="{}" y="{}">{}</tspan>"#,
+            num(x),
+            num(first_baseline + index as f64 * line_height),
+            escape(line)
+        );
+    }
+}
+
+/// System fonts can't travel with the file, so when fonts are embedded, text
+/// set in them becomes the outlines of the glyphs it uses.
+fn push_text(out: &mut String, resources: &Resources, family: FontFamily, fill: &str, text: String) {
+    let outlined = (resources.embed_fonts && !is_bundled(family))
+        .then(|| outline_text(&text, fill))
+        .flatten();
+    out.push_str(&outlined.unwrap_or(text));
+}
+
+fn text_element(out: &mut String, resources: &Resources, rect: &Rect, style: &ElementStyle, text: &TextProps) {
+    let content = if text.content.is_empty() { "Text" } else { &text.content };
+    let inner_width = (rect.width - TEXT_PADDING * 2.0).max(1.0);
+    let lines = wrap_lines(content, text.font_size, inner_width);
+    let (anchor, x) = match text.text_align {
+        TextAlign::Left => ("start", rect.x + TEXT_PADDING),
+        TextAlign::Center => ("middle", rect.x + rect.width / 2.0),
+        TextAlign::Right => ("end", rect.right() - TEXT_PADDING),
+    };
+    let line_height = text.font_size * TEXT_LINE_HEIGHT;
+    // Baseline sits roughly 0.8em below the top of a CSS line box
+    let first_baseline = rect.y + TEXT_PADDING + (line_height - text.font_size) / 2.0 + text.font_size * 0.8;
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<text font-family="{}" font-size="{}" font-weight="{}" text-anchor="{}" fill="{}" xml:space="preserve">"#,
+        escape(text.font_family.css()),
+        num(text.font_size),
+        text.font_weight.css(),
+        anchor,
+        escape(&style.stroke_color)
+    );
+    text_lines(&mut svg, &lines, x, first_baseline, line_height);
+    svg.push_str("</text>");
+    push_text(out, resources, text.font_family, &style.stroke_color, svg);
+}
+
+fn shape_label(out: &mut String, resources: &Resources, rect: &Rect, style: &ElementStyle, label: &str) {
+    if label.is_empty() {
+        return;
+    }
+    let lines = wrap_lines(label, LABEL_FONT_SIZE, (rect.width - 16.0).max(1.0));
+    let line_height = LABEL_FONT_SIZE * TEXT_LINE_HEIGHT;
+    let block_height = line_height * lines.len() as f64;
+    let first_baseline =
+        rect.center().y - block_height / 2.0 + (line_height - LABEL_FONT_SIZE) / 2.0 + LABEL_FONT_SIZE * 0.8;
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<text font-family="{}" font-size="{}" text-anchor="middle" fill="{}">"#,
+        escape(FontFamily::System.css()),
+        num(LABEL_FONT_SIZE),
+        escape(&style.stroke_color)
+    );
+    text_lines(&mut svg, &lines, rect.center().x, first_baseline, line_height);
+    svg.push_str("</text>");
+    push_text(out, resources, FontFamily::System, &style.stroke_color, svg);
+}
+
+fn image_placeholder(out: &mut String, rect: &Rect) {
+    let _ = write!(
+        out,
+        r##"<rect x="{}" y="{}" width="{}" height="{}" rx="2" fill="#27272a" stroke="#52525b" stroke-width="1"/>"##,
+        num(rect.x),
+        num(rect.y),
+        num(rect.width),
+        num(rect.height)
+    );
+}
+
+fn image_element(out: &mut String, document: &BoardDocument, resources: &Resources, rect: &Rect, image: &ImageProps) {
+    let mime_type = document
+        .assets
+        .as_ref()
+        .and_then(|registry| registry.assets.get(&image.asset_id))
+        .map(|asset| asset.mime_type.as_str());
+    let (Some(data), Some(mime_type)) = (resources.images.get(&image.asset_id), mime_type) else {
+        image_placeholder(out, rect);
+        return;
+    };
+
+    let aspect = match image.object_fit.unwrap_or_default() {
+        ImageObjectFit::Contain => "xMidYMid meet",
+        ImageObjectFit::Cover => "xMidYMid slice",
+        ImageObjectFit::Fill => "none",
+    };
+    let mut attrs = String::new();
+    let scale_x = if image.flip_x.unwrap_or(false) { -1.0 } else { 1.0 };
+    let scale_y = if image.flip_y.unwrap_or(false) { -1.0 } else { 1.0 };
+    if scale_x < 0.0 || scale_y < 0.0 {
+        // Mirror around the image center
+        let center = rect.center();
+        let _ = write!(
+            attrs,
+            r#" transform="matrix({} 0 0 {} {} {})""#,
+            num(scale_x),
+            num(scale_y),
+            num(center.x * (1.0 - scale_x)),
+            num(center.y * (1.0 - scale_y))
+        );
+    }
+    let _ = write!(
+        out,
+        r#"<image x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="{}"{} href="data:{};base64,{}"/>"#,
+        num(rect.x),
+        num(rect.y),
+        num(rect.width),
+        num(rect.height),
+        aspect,
+        attrs,
+        escape(mime_type),
+        STANDARD.encode(data)
+    );
+}
+
+fn render_element(out: &mut String, document: &BoardDocument, resources: &Resources, element: &Element) {
+    let rect = &element.rect;
+    let style = &element.style;
+    open_group(out, element);
+    match &element.kind {
+        ElementKind::Rectangle(shape) => {
+            let _ = write!(
+                out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" {} {}/>"#,
+                num(rect.x),
+                num(rect.y),
+                num(rect.width),
+                num(rect.height),
+                num(shape.corner_radius.unwrap_or(0.0)),
+                fill_attr(style),
+                stroke_attrs(style)
+            );
+            shape_label(out, resources, rect, style, shape.label.as_deref().unwrap_or_default());
+        }
+        ElementKind::Ellipse(shape) => {
+            let center = rect.center();
+            let _ = write!(
+                out,
+                r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}" {} {}/>"#,
+                num(center.x),
+                num(center.y),
+                num(rect.width / 2.0),
+                num(rect.height / 2.0),
+                fill_attr(style),
+                stroke_attrs(style)
+            );
+            shape_label(out, resources, rect, style, shape.label.as_deref().unwrap_or_default());
+        }
+        ElementKind::Line(line) | ElementKind::Arrow(line) => {
+            let (start, end) = (line.points.start, line.points.end);
+            let _ = write!(
+                out,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+                num(start.x),
+                num(start.y),
+                num(end.x),
+                num(end.y),
+                stroke_attrs(style)
+            );
+            if matches!(element.kind, ElementKind::Arrow(_)) {
+                let head = line.arrow_head.unwrap_or_default();
+                if matches!(head, ArrowHead::End | ArrowHead::Both) {
+                    arrow_head(out, end, start, style);
+                }
+                if matches!(head, ArrowHead::Start | ArrowHead::Both) {
+                    arrow_head(out, start, end, style);
+                }
+            }
+        }
+        ElementKind::Draw(draw) => {
+            if let Some((first, rest)) = draw.points.split_first() {
+                let mut d = format!("M{} {}", num(first.x), num(first.y));
+                for point in rest {
+                    let _ = write!(d, " L{} {}", num(point.x), num(point.y));
+                }
+                let _ = write!(out, r#"<path d="{}" fill="none" {}/>"#, d, stroke_attrs(style));
+            }
+        }
+        ElementKind::Text(text) => text_element(out, resources, rect, style, text),
+        ElementKind::Image(image) => image_element(out, document, resources, rect, image),
+    }
+    out.push_str("</g>");
+}
+
+/// Widgets are live Vue modules; natively they are drawn as labelled cards.
+fn render_widget(out: &mut String, widget: &Widget) {
+    let rect = widget.bounds();
+    let _ = write!(
+        out,
+        r##"<g data-id="{}"><rect x="{}" y="{}" width="{}" height="{}" rx="8" fill="#18181b" stroke="#3f3f46" stroke-width="1"/>"##,
+        escape(&widget.id),
+        num(rect.x),
+        num(rect.y),
+        num(rect.width),
+        num(rect.height)
+    );
+    let _ = write!(
+        out,
+        r##"<text x="{}" y="{}" font-family="{}" font-size="12" fill="#a1a1aa">{}</text></g>"##,
+        num(rect.x + 12.0),
+        num(rect.y + 22.0),
+        escape(crate::document::FontFamily::System.css()),
+        escape(&widget.module_id)
+    );
+}
+
+fn target_bounds(document: &BoardDocument, id: &str, kind: ConnectionTargetType) -> Option<Rect> {
+    match kind {
+        ConnectionTargetType::Element => document.board.elements.iter().find(|e| e.id == id).map(|e| e.rect),
+        ConnectionTargetType::Widget => document.board.widgets.iter().find(|w| w.id == id).map(Widget::bounds),
+    }
+}
+
+/// Anchor on the side of `rect` facing `toward`, as in `orthogonalRouter.ts`.
+fn anchor(rect: &Rect, toward: Point) -> (Point, bool) {
+    let center = rect.center();
+    let (dx, dy) = (toward.x - center.x, toward.y - center.y);
+    if dx.abs() > dy.abs() {
+        let x = if dx > 0.0 { rect.right() } else { rect.x };
+        (Point { x, y: center.y }, true)
+    } else {
+        let y = if dy > 0.0 { rect.bottom() } else { rect.y };
+        (Point { x: center.x, y }, false)
+    }
+}
+
+fn render_connections(out: &mut String, document: &BoardDocument) {
+    for connection in document.board.connections.iter().flatten() {
+        let source = target_bounds(document, &connection.source_id, connection.source_type);
+        let target = target_bounds(document, &connection.target_id, connection.target_type);
+        let (Some(source), Some(target)) = (source, target) else {
+            continue;
+        };
+
+        let (start, horizontal) = anchor(&source, target.center());
+        let (end, _) = anchor(&target, source.center());
+        let (d, last_bend) = if horizontal {
+            let mid_x = (start.x + end.x) / 2.0;
+            (
+                format!("M{} {} H{} V{} H{}", num(start.x), num(start.y), num(mid_x), num(end.y), num(end.x)),
+                Point { x: mid_x, y: end.y },
+            )
+        } else {
+            let mid_y = (start.y + end.y) / 2.0;
+            (
+                format!("M{} {} V{} H{} V{}", num(start.x), num(start.y), num(mid_y), num(end.x), num(end.y)),
+                Point { x: end.x, y: mid_y },
+            )
+        };
+
+        let conn_style = connection.style.clone().unwrap_or_default();
+        let style = ElementStyle {
+            stroke_color: conn_style.stroke_color.unwrap_or_else(|| CONNECTION_COLOR.to_string()),
+            stroke_width: conn_style.stroke_width.unwrap_or(2.0),
+            ..ElementStyle::default()
+        };
+        let _ = write!(
+            out,
+            r#"<g data-id="{}"><path d="{}" fill="none" {}/>"#,
+            escape(&connection.id),
+            d,
+            stroke_attrs(&style)
+        );
+        arrow_head(out, end, last_bend, &style);
+        out.push_str("</g>");
+    }
+}
+
+/// Resolve the board background to a concrete color.
+pub fn background_color(document: &BoardDocument) -> &str {
+    match document.board.background.color.as_str() {
+        "auto" | "" => AUTO_BACKGROUND,
+        color => color,
+    }
+}
+
+/// Render the board as a standalone SVG document cropped to its content.
+/// Returns `None` when the board has nothing to draw.
+pub fn render_svg(document: &BoardDocument, resources: &Resources) -> Option<String> {
+    let bounds = document.content_bounds()?;
+    let view = Rect {
+        x: bounds.x - EXPORT_PADDING,
+        y: bounds.y - EXPORT_PADDING,
+        width: bounds.width + EXPORT_PADDING * 2.0,
+        height: bounds.height + EXPORT_PADDING * 2.0,
+    };
+    Some(render_svg_view(document, resources, &view))
+}
+
+/// How a board export is framed: what it is cropped to and what surrounds it.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportLayout {
+    /// Leave out the board background, e.g. for pasting onto a slide
+    pub transparent: bool,
+    /// Margin around the content, in board units
+    pub padding: f64,
+    /// Crop to what is actually drawn, strokes, arrow heads and overflowing
+    /// text included, rather than to the items' rectangles
+    pub trim: bool,
+}
+
+impl Default for ExportLayout {
+    fn default() -> Self {
+        Self {
+            transparent: false,
+            padding: EXPORT_PADDING,
+            trim: false,
+        }
+    }
+}
+
+/// Largest margin an export accepts, in board units.
+const MAX_PADDING: f64 = 10_000.0;
+
+/// The bounds of what `view` of the board actually draws, without its background.
+fn drawn_bounds(document: &BoardDocument, resources: &Resources, view: &Rect) -> Result<Option<Rect>> {
+    let svg = render_svg_view(
+        document,
+        &Resources {
+            images: resources.images.clone(),
+            embed_fonts: false,
+            transparent: true,
+        },
+        view,
+    );
+    let drawn = parse_svg(&svg)?.root().abs_stroke_bounding_box();
+    // The tree is in output pixels, placed at the view's origin
+    Ok((drawn.width() > 0.0 || drawn.height() > 0.0).then(|| Rect {
+        x: view.x + f64::from(drawn.x()),
+        y: view.y + f64::from(drawn.y()),
+        width: f64::from(drawn.width()),
+        height: f64::from(drawn.height()),
+    }))
+}
+
+/// Render the board cropped to its content as `layout` frames it, at `scale`
+/// output units per board unit.
+pub fn render_svg_layout(
+    document: &BoardDocument,
+    resources: &Resources,
+    layout: &ExportLayout,
+    scale: f64,
+) -> Result<String> {
+    if !(layout.padding.is_finite() && (0.0..=MAX_PADDING).contains(&layout.padding)) {
+        return Err(Error::Render(format!("padding must be between 0 and {MAX_PADDING}")));
+    }
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(Error::Render(format!("invalid scale {scale}")));
+    }
+    let mut bounds = document.content_bounds().ok_or_else(|| Error::Render("board is empty".to_string()))?;
+    if layout.trim {
+        bounds = drawn_bounds(document, resources, &bounds)?.unwrap_or(bounds);
+    }
+    let view = Rect {
+        x: bounds.x - layout.padding,
+        y: bounds.y - layout.padding,
+        width: bounds.width + layout.padding * 2.0,
+        height: bounds.height + layout.padding * 2.0,
+    };
+    let resources = Resources {
+        images: resources.images.clone(),
+        embed_fonts: resources.embed_fonts,
+        transparent: resources.transparent || layout.transparent,
+    };
+    Ok(render_svg_scaled(document, &resources, &view, scale))
+}
+
+/// Render the part of the board inside `view`, e.g. one frame.
+pub fn render_svg_view(document: &BoardDocument, resources: &Resources, view: &Rect) -> String {
+    render_svg_scaled(document, resources, view, 1.0)
+}
+
+/// `render_svg_view` at `scale` output units per board unit.
+fn render_svg_scaled(document: &BoardDocument, resources: &Resources, view: &Rect, scale: f64) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        num(view.width * scale),
+        num(view.height * scale),
+        num(view.x),
+        num(view.y),
+        num(view.width),
+        num(view.height)
+    );
+    if resources.embed_fonts {
+        out.push_str(&font_face_style(document));
+    }
+    if !resources.transparent {
+        let _ = write!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            num(view.x),
+            num(view.y),
+            num(view.width),
+            num(view.height),
+            escape(background_color(document))
+        );
+    }
+
+    render_connections(&mut out, document);
+
+    // Elements and widgets share one z-index space
+    enum Item<'a> {
+        Element(&'a Element),
+        Widget(&'a Widget),
+    }
+    let mut items: Vec<(f64, Item)> = document
+        .board
+        .elements
+        .iter()
+        .map(|element| (element.z_index, Item::Element(element)))
+        .chain(document.board.widgets.iter().map(|widget| (widget.z_index, Item::Widget(widget))))
+        .collect();
+    items.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    for (_, item) in items {
+        match item {
+            Item::Element(element) => render_element(&mut out, document, resources, element),
+            Item::Widget(widget) => render_widget(&mut out, widget),
+        }
+    }
+
+    out.push_str("</svg>");
+    out
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SvgExportOptions {
+    /// Saved .boardkit file to read image assets from
+    pub board_path: Option<String>,
+    pub embed_fonts: bool,
+    #[serde(flatten)]
+    pub layout: ExportLayout,
+    /// Size of the drawing per board unit; the content is scaled, not cropped
+    pub scale: f64,
+}
+
+impl Default for SvgExportOptions {
+    fn default() -> Self {
+        Self {
+            board_path: None,
+            embed_fonts: true,
+            layout: ExportLayout::default(),
+            scale: 1.0,
+        }
+    }
+}
+
+pub fn write_svg(document: &BoardDocument, path: &str, options: SvgExportOptions) -> Result<()> {
+    let resources = Resources::load(options.board_path.as_deref(), options.embed_fonts)?;
+    let svg = render_svg_layout(document, &resources, &options.layout, options.scale)?;
+    persist::write_atomic(Path::new(path), svg.as_bytes()).map_err(|err| Error::from_io(err, path))
+}
+
+/// Render the board natively and write it to `path` as a standalone SVG file.
+/// Output only depends on the document and its assets, so re-exporting an
+/// unchanged board produces an identical file.
+#[tauri::command]
+pub async fn export_board_svg(board_json: BoardDocument, path: String, options: Option<SvgExportOptions>) -> Result<()> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || write_svg(&board_json, &path, options))
+        .await
+        .map_err(|err| Error::Render(err.to_string()))?
+}
@@ -0,0 +1,121 @@
+use tauri::AppHandle;
+
+#[cfg(not(target_os = "macos"))]
+use crate::error::Error;
+use crate::error::Result;
+
+#[cfg(not(target_os = "macos"))]
+fn eyedropper_error(message: impl Into<String>) -> Error {
+    Error::Eyedropper(message.into())
+}
+
+/// `#rrggbb` from a picker tool's output, which tools print in various cases.
+#[cfg(not(target_os = "macos"))]
+fn parse_hex(output: &str) -> Option<String> {
+    let hex = output.trim().strip_prefix('#')?;
+    (hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit())).then(|| format!("#{}", hex.to_ascii_lowercase()))
+}
+
+/// Run a picker that prints the color it picked and nothing when cancelled.
+#[cfg(not(target_os = "macos"))]
+fn run(mut command: std::process::Command) -> Result<Option<String>> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    match command.output() {
+        Ok(output) if output.status.success() => Ok(parse_hex(&String::from_utf8_lossy(&output.stdout))),
+        Ok(_) => Ok(None),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            Err(eyedropper_error(format!("{program} is not installed")))
+        }
+        Err(err) => Err(Error::Io(err)),
+    }
+}
+
+/// AppKit's sampler shows the system magnifier loupe and reports the color
+/// in the display's color space, converted here to sRGB like CSS colors.
+#[cfg(target_os = "macos")]
+async fn sample(app: &AppHandle) -> Result<Option<String>> {
+    use std::sync::Mutex;
+
+    use block2::RcBlock;
+    use objc2_app_kit::{NSColor, NSColorSampler, NSColorSpace};
+
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    app.run_on_main_thread(move || {
+        let sampler = NSColorSampler::new();
+        let sender = Mutex::new(Some(sender));
+        // The block keeps the sampler alive until the user picks or cancels
+        let retained = sampler.clone();
+        let handler = RcBlock::new(move |color: *mut NSColor| {
+            let _ = &retained;
+            // SAFETY: AppKit passes a valid color, or null when cancelled
+            let hex = unsafe { color.as_ref() }
+                .and_then(|color| color.colorUsingColorSpace(&NSColorSpace::sRGBColorSpace()))
+                .map(|color| {
+                    let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+                    format!(
+                        "#{:02x}{:02x}{:02x}",
+                        channel(color.redComponent()),
+                        channel(color.greenComponent()),
+                        channel(color.blueComponent())
+                    )
+                });
+            if let Some(sender) = sender.lock().unwrap().take() {
+                let _ = sender.send(hex);
+            }
+        });
+        // SAFETY: the handler matches the selection handler's signature
+        unsafe { sampler.showSamplerWithSelectionHandler(&handler) };
+    })?;
+    Ok(receiver.await.unwrap_or_default())
+}
+
+/// Windows has no system sampler, so freeze the screen in a fullscreen
+/// window and read the pixel under the click; Escape cancels.
+#[cfg(target_os = "windows")]
+async fn sample(_app: &AppHandle) -> Result<Option<String>> {
+    const SCRIPT: &str = "Add-Type -AssemblyName System.Windows.Forms,System.Drawing; \
+        $b = [System.Windows.Forms.SystemInformation]::VirtualScreen; \
+        $bmp = New-Object System.Drawing.Bitmap $b.Width, $b.Height; \
+        [System.Drawing.Graphics]::FromImage($bmp).CopyFromScreen($b.Left, $b.Top, 0, 0, $bmp.Size); \
+        $f = New-Object System.Windows.Forms.Form; \
+        $f.FormBorderStyle = 'None'; $f.StartPosition = 'Manual'; $f.Bounds = $b; $f.TopMost = $true; \
+        $f.BackgroundImage = $bmp; $f.Cursor = [System.Windows.Forms.Cursors]::Cross; $f.KeyPreview = $true; \
+        $f.Add_MouseClick({ $c = $bmp.GetPixel($_.X, $_.Y); \
+          $script:picked = '#{0:x2}{1:x2}{2:x2}' -f $c.R, $c.G, $c.B; $f.Close() }); \
+        $f.Add_KeyDown({ if ($_.KeyCode -eq 'Escape') { $f.Close() } }); \
+        [void]$f.ShowDialog(); if ($script:picked) { $script:picked }";
+    let mut command = std::process::Command::new("powershell");
+    command.args(["-NoProfile", "-STA", "-Command", SCRIPT]);
+    tauri::async_runtime::spawn_blocking(move || run(command))
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// Linux has no common sampling API, so use whichever picker is installed.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+async fn sample(_app: &AppHandle) -> Result<Option<String>> {
+    use std::process::Command;
+
+    tauri::async_runtime::spawn_blocking(|| {
+        let mut xcolor = Command::new("xcolor");
+        xcolor.args(["--format", "hex"]);
+        let mut hyprpicker = Command::new("hyprpicker");
+        hyprpicker.args(["--format", "hex"]);
+        for command in [xcolor, hyprpicker] {
+            match run(command) {
+                Err(Error::Eyedropper(reason)) => tracing::debug!("eyedropper: {reason}"),
+                result => return result,
+            }
+        }
+        Err(eyedropper_error("no color picker found (install xcolor or hyprpicker)"))
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// Let the user pick a color from anywhere on screen. Resolves to `#rrggbb`,
+/// or `null` when they cancel.
+#[tauri::command]
+pub async fn pick_screen_color(app: AppHandle) -> Result<Option<String>> {
+    sample(&app).await
+}
@@ -0,0 +1,127 @@
+use std::{fs, path::PathBuf};
+
+use serde::Serialize;
+use tauri::{DragDropEvent, Emitter, LogicalPosition, Manager, Window, WindowEvent};
+
+use crate::{
+    assets::AssetStore,
+    board,
+    error::{Error, Result},
+    file_open,
+    image_pipeline::{self, ImageOptimization},
+    settings::Settings,
+};
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum DroppedKind {
+    Image,
+    Pdf,
+    Board,
+}
+
+/// A dropped file after ingestion. Images and PDFs are copied into the asset
+/// store and referenced by hash; boards keep their path so they can be opened,
+/// and PDFs too so their pages can be rendered with `import_pdf`.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DroppedFile {
+    kind: DroppedKind,
+    name: String,
+    mime_type: &'static str,
+    size: u64,
+    /// Size before image optimization
+    original_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FilesDropped {
+    /// Drop point in CSS pixels, relative to the webview
+    position: LogicalPosition<f64>,
+    files: Vec<DroppedFile>,
+}
+
+fn is_pdf(path: &std::path::Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+}
+
+/// Returns `None` for file types the board can't use.
+fn ingest(store: &AssetStore, optimization: &ImageOptimization, path: PathBuf) -> Result<Option<DroppedFile>> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if file_open::is_board_file(&path) {
+        let size = fs::metadata(&path)?.len();
+        return Ok(Some(DroppedFile {
+            kind: DroppedKind::Board,
+            name,
+            mime_type: "application/zip",
+            size,
+            original_size: size,
+            hash: None,
+            path: Some(path.to_string_lossy().into_owned()),
+        }));
+    }
+
+    let (kind, mime_type) = match board::image_mime_for_path(&path) {
+        Some(mime_type) => (DroppedKind::Image, mime_type),
+        None if is_pdf(&path) => (DroppedKind::Pdf, "application/pdf"),
+        None => return Ok(None),
+    };
+    let mut data = fs::read(&path).map_err(|err| Error::from_io(err, &path.to_string_lossy()))?;
+    let (mut name, mut mime_type, original_size) = (name, mime_type, data.len() as u64);
+    if matches!(kind, DroppedKind::Image) {
+        let optimized = image_pipeline::optimize(&data, optimization).unwrap_or_else(|err| {
+            tracing::warn!(name, "image optimization failed: {err}");
+            None
+        });
+        if let Some(optimized) = optimized {
+            name = image_pipeline::renamed(&name, optimized.mime_type);
+            mime_type = optimized.mime_type;
+            data = optimized.bytes;
+        }
+    }
+    Ok(Some(DroppedFile {
+        path: matches!(kind, DroppedKind::Pdf).then(|| path.to_string_lossy().into_owned()),
+        kind,
+        name,
+        mime_type,
+        size: data.len() as u64,
+        original_size,
+        hash: Some(store.store(&data)?),
+    }))
+}
+
+/// Ingest files dropped on a window and emit `files-dropped` to it. Reading
+/// and hashing happen off the event loop so large drops don't freeze the UI.
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    let WindowEvent::DragDrop(DragDropEvent::Drop { paths, position }) = event else {
+        return;
+    };
+    let scale = window.scale_factor().unwrap_or(1.0);
+    let position = position.to_logical(scale);
+    let (window, paths) = (window.clone(), paths.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = window.state::<AssetStore>();
+        let optimization = window.state::<Settings>().get().image_optimization;
+        let files: Vec<DroppedFile> = paths
+            .into_iter()
+            .filter_map(|path| {
+                let shown = path.display().to_string();
+                ingest(&store, &optimization, path).unwrap_or_else(|err| {
+                    tracing::warn!("skipping {shown}: {err}");
+                    None
+                })
+            })
+            .collect();
+        if !files.is_empty() {
+            let _ = window.emit_to(window.label(), "files-dropped", FilesDropped { position, files });
+        }
+    });
+}
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use crate::error::Result;
+
+/// Finder keeps custom icons with the file itself, so the preview follows the
+/// board when it is copied or synced. Setting one touches the file's
+/// attributes, which the board watcher would otherwise report.
+#[cfg(target_os = "macos")]
+fn set_icon(board: &Path, thumbnail: Option<&Path>) -> Result<()> {
+    use objc2::AllocAnyThread;
+    use objc2_app_kit::{NSImage, NSWorkspace, NSWorkspaceIconCreationOptions};
+    use objc2_foundation::NSString;
+
+    use crate::{error::Error, watcher};
+
+    let image = match thumbnail {
+        Some(thumbnail) => Some(
+            NSImage::initWithContentsOfFile(NSImage::alloc(), &NSString::from_str(&thumbnail.to_string_lossy()))
+                .ok_or_else(|| Error::Render(format!("cannot load {}", thumbnail.display())))?,
+        ),
+        None => None,
+    };
+    watcher::expect_write(board);
+    let path = NSString::from_str(&board.to_string_lossy());
+    let options = NSWorkspaceIconCreationOptions(0);
+    if !NSWorkspace::sharedWorkspace().setIcon_forFile_options(image.as_deref(), &path, options) {
+        return Err(Error::Render(format!("cannot set the icon of {}", board.display())));
+    }
+    Ok(())
+}
+
+/// Explorer only draws per-file previews through a shell thumbnail handler,
+/// a COM DLL Boardkit doesn't ship, so boards keep the association's icon.
+#[cfg(target_os = "windows")]
+fn set_icon(_board: &Path, _thumbnail: Option<&Path>) -> Result<()> {
+    Ok(())
+}
+
+/// GNOME-based file managers (Files, Nemo, Caja) read the custom icon from
+/// GIO metadata, kept outside the file.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn set_icon(board: &Path, thumbnail: Option<&Path>) -> Result<()> {
+    use std::process::Command;
+
+    use crate::error::Error;
+
+    let mut command = Command::new("gio");
+    command.arg("set");
+    match thumbnail {
+        Some(thumbnail) => {
+            let uri = tauri::Url::from_file_path(thumbnail)
+                .map_err(|_| Error::Render(format!("cannot link to {}", thumbnail.display())))?;
+            command.arg(board).args(["metadata::custom-icon", uri.as_str()]);
+        }
+        None => {
+            command.args(["--type", "unset"]).arg(board).arg("metadata::custom-icon");
+        }
+    }
+    match command.output() {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(Error::Render(String::from_utf8_lossy(&output.stderr).trim().to_string())),
+        // Without GIO there is no file manager to show the icon either
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(Error::Io(err)),
+    }
+}
+
+/// Show `thumbnail` as the board file's icon in the system file manager, or
+/// go back to the generic document icon when there is none.
+pub fn attach(board: &str, thumbnail: Option<&Path>) {
+    if let Err(err) = set_icon(Path::new(board), thumbnail) {
+        tracing::debug!("failed to update the file icon of {board}: {err}");
+    }
+}
@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::{error::Result, search};
+
+/// Spotlight and desktop search only need enough text to match on; longer
+/// boards are cut here rather than bloating every save.
+const MAX_TEXT_LEN: usize = 64 * 1024;
+
+/// What system search learns about a board: the same text Boardkit's own
+/// index holds, flattened.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+struct BoardMetadata {
+    title: String,
+    text: String,
+}
+
+fn board_metadata(document: &Value) -> BoardMetadata {
+    let title = document.pointer("/meta/title").and_then(Value::as_str).unwrap_or_default().to_string();
+    let mut text = String::new();
+    for (_, row) in search::document_text(document) {
+        if text.len() + row.len() + 1 > MAX_TEXT_LEN {
+            break;
+        }
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&row);
+    }
+    BoardMetadata { title, text }
+}
+
+/// Spotlight imports `com.apple.metadata:` attributes, binary plists, as if
+/// an importer had produced them.
+#[cfg(target_os = "macos")]
+fn write(board: &Path, metadata: &BoardMetadata) -> Result<()> {
+    use crate::error::Error;
+
+    for (name, value) in [("kMDItemTitle", &metadata.title), ("kMDItemTextContent", &metadata.text)] {
+        let mut plist = Vec::new();
+        plist::to_writer_binary(&mut plist, value).map_err(|err| Error::Render(err.to_string()))?;
+        xattr::set(board, format!("com.apple.metadata:{name}"), &plist)?;
+    }
+    Ok(())
+}
+
+/// Windows Search reads properties through a property handler registered for
+/// the file type, which Boardkit doesn't ship; boards are found by name only.
+#[cfg(target_os = "windows")]
+fn write(_board: &Path, _metadata: &BoardMetadata) -> Result<()> {
+    Ok(())
+}
+
+/// Baloo and Tracker index the freedesktop comment attribute.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn write(board: &Path, metadata: &BoardMetadata) -> Result<()> {
+    const EOPNOTSUPP: i32 = 95;
+
+    let comment = [metadata.title.as_str(), metadata.text.as_str()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    match xattr::set(board, "user.xdg.comment", comment.as_bytes()) {
+        // Filesystems without user attributes (tmpfs, some network mounts)
+        Err(err) if err.raw_os_error() == Some(EOPNOTSUPP) => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+/// Attach the board's title and text to its file so system search (Spotlight,
+/// Baloo/Tracker) finds boards by content. Best-effort, like the search index.
+pub fn write_in_background(path: String, document: Value) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let metadata = board_metadata(&document);
+        let board = Path::new(&path);
+        crate::watcher::expect_write(board);
+        if let Err(err) = write(board, &metadata) {
+            tracing::debug!("failed to write search metadata for {path}: {err}");
+        }
+    });
+}
@@ -0,0 +1,87 @@
+use std::{path::Path, sync::Mutex};
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{jump_list, windows};
+
+#[derive(Default)]
+struct QueueState {
+    // Set once the frontend has drained the queue and is listening for events
+    ready: bool,
+    pending: Vec<String>,
+}
+
+/// Board files the OS asked us to open before the frontend was listening.
+#[derive(Default)]
+pub struct FileOpenQueue(Mutex<QueueState>);
+
+pub fn is_board_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("boardkit"))
+}
+
+/// Board files passed on the command line (Windows/Linux file association launches).
+/// Relative paths are resolved against `cwd`, the directory the process was launched from.
+pub fn board_files_from_args(args: impl IntoIterator<Item = String>, cwd: &Path) -> Vec<String> {
+    args.into_iter()
+        .skip(1)
+        .map(|arg| cwd.join(arg))
+        .filter(|path| is_board_file(path))
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// A second launch forwards its arguments here instead of starting a new process.
+pub fn handle_second_instance(app: &AppHandle, args: Vec<String>, cwd: String) {
+    if args.iter().any(|arg| arg == jump_list::NEW_BOARD_ARG) {
+        jump_list::new_board(app);
+        return;
+    }
+    let paths = board_files_from_args(args, Path::new(&cwd));
+    if paths.is_empty() {
+        if let Some(window) = windows::target_window(app) {
+            windows::focus_window(&window);
+        }
+        return;
+    }
+    open_board_files(app, paths);
+}
+
+/// Forward board files to the webview, or queue them until it is ready.
+pub fn open_board_files(app: &AppHandle, paths: Vec<String>) {
+    if paths.is_empty() {
+        return;
+    }
+
+    let queue = app.state::<FileOpenQueue>();
+    let mut state = queue.0.lock().unwrap();
+    if !state.ready {
+        state.pending.extend(paths);
+        return;
+    }
+    let Some(window) = windows::target_window(app) else {
+        // No board window is listening: queue them for a new main window
+        state.ready = false;
+        state.pending.extend(paths);
+        drop(state);
+        windows::reopen_main_window(app);
+        return;
+    };
+    drop(state);
+
+    windows::focus_window(&window);
+    // Boards already open in a window are brought forward instead of reloaded
+    for path in paths.into_iter().filter(|path| !windows::focus_board(app, path)) {
+        let _ = window.emit_to(window.label(), "open-board-file", path);
+    }
+}
+
+/// Return files queued during launch; later opens arrive as `open-board-file` events.
+#[tauri::command]
+pub fn take_pending_board_files(queue: State<'_, FileOpenQueue>) -> Vec<String> {
+    let mut state = queue.0.lock().unwrap();
+    state.ready = true;
+    std::mem::take(&mut state.pending)
+}
@@ -0,0 +1,506 @@
+//! Optional Git integration for the boards directory, so a team can keep its
+//! boards in a repository without leaving the app. Runs the user's `git`, so
+//! their configuration, credentials and SSH agent apply as in a terminal.
+//!
+//! `git_init_workspace` registers `boardkit merge` as the merge driver of
+//! `.boardkit` files and ignores the history, locks and temporary files
+//! Boardkit keeps next to boards. Pulls then merge boards item by item (see
+//! `merge::merge_documents`). A board where both sides changed something
+//! differently is left conflicted, with the merged board keeping our side in
+//! the working tree. [`GitStatus::conflicts`] lists what conflicts; saving the board and
+//! committing concludes the merge.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    error::{Error, Result},
+    merge::{self, MergeConflict},
+    workspace::{self, Workspace, BOARD_EXTENSION},
+};
+
+const ATTRIBUTES_FILE: &str = ".gitattributes";
+/// Boards are ZIP containers: never convert line endings, merge with Boardkit.
+const BOARD_ATTRIBUTES: &str = "*.boardkit -text merge=boardkit";
+const IGNORE_FILE: &str = ".gitignore";
+/// What Boardkit keeps next to the boards for this machine only: version
+/// history and undo logs, edit locks another clone would take as live, and
+/// temporary files of interrupted writes.
+const IGNORED: &[&str] = &[".boardkit-history/", "*.boardkit.lock", "*.boardkit.tmp", "*.append.tmp"];
+const MERGE_DRIVER: &str = "merge.boardkit";
+
+/// Unique names for the conflict stages written out to merge.
+static STAGE_FILES: AtomicU64 = AtomicU64::new(0);
+
+fn git_error(message: impl Into<String>) -> Error {
+    Error::Git(message.into())
+}
+
+/// Run `git` in `dir` and return its standard output.
+fn git(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        // Fail instead of waiting on a credential prompt nobody can see
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => git_error("git is not installed"),
+            _ => Error::Io(err),
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let message = if stderr.trim().is_empty() { stdout.trim() } else { stderr.trim() };
+        return Err(git_error(message.to_string()));
+    }
+    Ok(output.stdout)
+}
+
+fn git_text(dir: &Path, args: &[&str]) -> Result<String> {
+    Ok(String::from_utf8_lossy(&git(dir, args)?).trim().to_string())
+}
+
+/// Root of the repository holding `dir`, if any.
+fn repository_root(dir: &Path) -> Option<PathBuf> {
+    git_text(dir, &["rev-parse", "--show-toplevel"]).ok().map(PathBuf::from)
+}
+
+fn is_board(path: &str) -> bool {
+    Path::new(path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case(BOARD_EXTENSION))
+}
+
+/// A file a pull left conflicted.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitConflict {
+    pub path: String,
+    /// What both sides changed differently, for boards; empty for other files
+    /// and boards added on both sides
+    pub board_conflicts: Vec<MergeConflict>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStatus {
+    /// Root of the repository holding the boards directory
+    pub root: String,
+    /// `None` on a detached HEAD
+    pub branch: Option<String>,
+    pub upstream: Option<String>,
+    /// Commits not pushed yet
+    pub ahead: u32,
+    /// Commits not pulled yet, as of the last fetch
+    pub behind: u32,
+    /// Files in the boards directory with uncommitted changes
+    pub changed: Vec<String>,
+    pub conflicts: Vec<GitConflict>,
+}
+
+/// Write one stage of a conflicted file out to merge it, `None` when that
+/// side doesn't have the file.
+fn write_stage(root: &Path, stage: u8, path: &str) -> Option<PathBuf> {
+    let bytes = git(root, &["show", &format!(":{stage}:{path}")]).ok()?;
+    let n = STAGE_FILES.fetch_add(1, Ordering::SeqCst);
+    let file = env::temp_dir().join(format!("boardkit-git-{}-{n}.{BOARD_EXTENSION}", std::process::id()));
+    fs::write(&file, bytes).ok()?;
+    Some(file)
+}
+
+/// Property-level conflicts of a board, from the three versions Git keeps
+/// while the merge is unresolved.
+fn board_conflicts(root: &Path, path: &str) -> Vec<MergeConflict> {
+    let stages: Vec<Option<PathBuf>> = (1..=3).map(|stage| write_stage(root, stage, path)).collect();
+    let conflicts = match &stages[..] {
+        [Some(base), Some(ours), Some(theirs)] => {
+            let as_str = |path: &Path| path.to_string_lossy().into_owned();
+            merge::merge_files(&as_str(base), &as_str(ours), &as_str(theirs), None)
+                .map(|result| result.conflicts)
+                .unwrap_or_else(|err| {
+                    tracing::warn!("failed to merge the versions of {path}: {err}");
+                    Vec::new()
+                })
+        }
+        _ => Vec::new(),
+    };
+    for file in stages.into_iter().flatten() {
+        let _ = fs::remove_file(file);
+    }
+    conflicts
+}
+
+/// Status of the repository holding `dir`, from `git status --porcelain=v2`.
+fn status(dir: &Path) -> Result<GitStatus> {
+    let root = repository_root(dir).ok_or_else(|| git_error("the boards directory is not in a Git repository"))?;
+    let output = git(dir, &["status", "--porcelain=v2", "--branch", "-z", "--", "."])?;
+    let output = String::from_utf8_lossy(&output);
+    let mut status = GitStatus {
+        root: root.to_string_lossy().into_owned(),
+        branch: None,
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+        changed: Vec::new(),
+        conflicts: Vec::new(),
+    };
+    let absolute = |path: &str| root.join(path).to_string_lossy().into_owned();
+    let mut entries = output.split('\0').filter(|entry| !entry.is_empty());
+    while let Some(entry) = entries.next() {
+        let (kind, rest) = entry.split_once(' ').unwrap_or((entry, ""));
+        match kind {
+            "#" => match rest.split_once(' ') {
+                Some(("branch.head", head)) if head != "(detached)" => status.branch = Some(head.to_string()),
+                Some(("branch.upstream", upstream)) => status.upstream = Some(upstream.to_string()),
+                Some(("branch.ab", counts)) => {
+                    for count in counts.split(' ') {
+                        let value = count.get(1..).and_then(|value| value.parse().ok()).unwrap_or(0);
+                        if count.starts_with('+') {
+                            status.ahead = value;
+                        } else {
+                            status.behind = value;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            // Changed, renamed (followed by the old path), unmerged and untracked
+            "1" => status.changed.extend(rest.splitn(8, ' ').nth(7).map(absolute)),
+            "2" => {
+                status.changed.extend(rest.splitn(9, ' ').nth(8).map(absolute));
+                entries.next();
+            }
+            "u" => {
+                if let Some(path) = rest.splitn(10, ' ').nth(9) {
+                    status.conflicts.push(GitConflict {
+                        path: absolute(path),
+                        board_conflicts: if is_board(path) { board_conflicts(&root, path) } else { Vec::new() },
+                    });
+                }
+            }
+            "?" => status.changed.push(absolute(rest)),
+            _ => {}
+        }
+    }
+    Ok(status)
+}
+
+/// Point the `boardkit` merge driver at this executable, which may have moved
+/// since the last time.
+fn configure_merge_driver(dir: &Path) -> Result<()> {
+    let exe = env::current_exe()?;
+    let driver = format!("\"{}\" merge %O %A %B", exe.to_string_lossy().replace('\\', "/"));
+    git(dir, &["config", &format!("{MERGE_DRIVER}.name"), "Boardkit board merge"])?;
+    git(dir, &["config", &format!("{MERGE_DRIVER}.driver"), &driver])?;
+    Ok(())
+}
+
+/// Add whichever of `lines` `file` lacks, keeping what the user wrote there.
+fn ensure_lines(file: &Path, lines: &[&str]) -> Result<()> {
+    let existing = fs::read_to_string(file).unwrap_or_default();
+    let missing: Vec<&str> =
+        lines.iter().copied().filter(|line| !existing.lines().any(|present| present.trim() == *line)).collect();
+    if !missing.is_empty() {
+        let separator = if existing.is_empty() || existing.ends_with('\n') { "" } else { "\n" };
+        fs::write(file, format!("{existing}{separator}{}\n", missing.join("\n")))?;
+    }
+    Ok(())
+}
+
+fn init(dir: &Path) -> Result<GitStatus> {
+    if repository_root(dir).is_none() {
+        git(dir, &["init"])?;
+        tracing::info!("created a Git repository for the boards directory");
+    }
+    configure_merge_driver(dir)?;
+    ensure_lines(&dir.join(ATTRIBUTES_FILE), &[BOARD_ATTRIBUTES])?;
+    ensure_lines(&dir.join(IGNORE_FILE), IGNORED)?;
+    status(dir)
+}
+
+fn commit(dir: &Path, path: Option<&str>, message: &str) -> Result<GitStatus> {
+    let message = message.trim();
+    if message.is_empty() {
+        return Err(git_error("a commit message is required"));
+    }
+    // Repositories set up outside the app may not ignore Boardkit's own files
+    ensure_lines(&dir.join(IGNORE_FILE), IGNORED)?;
+    // One board, or everything in the boards directory. Staging a conflicted
+    // board marks it resolved, and committing concludes the merge.
+    let pathspec = path.unwrap_or(".");
+    git(dir, &["add", "--all", "--", pathspec])?;
+    let merging = git(dir, &["rev-parse", "--quiet", "--verify", "MERGE_HEAD"]).is_ok();
+    if !merging && git(dir, &["diff", "--cached", "--quiet", "--", pathspec]).is_ok() {
+        return Err(git_error("nothing to commit"));
+    }
+    match path {
+        // Leave whatever else the user staged out of a board's commit. A merge
+        // can only be concluded whole.
+        Some(path) if !merging => git(dir, &["commit", "--quiet", "--message", message, "--", path])?,
+        _ => git(dir, &["commit", "--quiet", "--message", message])?,
+    };
+    tracing::info!("committed boards");
+    status(dir)
+}
+
+/// Pull with a merge, so boards go through the merge driver. A pull that
+/// leaves conflicts returns them rather than failing.
+fn pull(dir: &Path) -> Result<GitStatus> {
+    configure_merge_driver(dir)?;
+    let pulled = git(dir, &["pull", "--no-rebase", "--no-edit"]);
+    let status = status(dir)?;
+    match pulled {
+        Err(_) if !status.conflicts.is_empty() => {
+            tracing::info!(conflicts = status.conflicts.len(), "pull left conflicts");
+            Ok(status)
+        }
+        Err(err) => Err(err),
+        Ok(_) => Ok(status),
+    }
+}
+
+/// The remote a branch without upstream is first pushed to: `remote` when
+/// given, else `origin` or the only remote there is.
+fn push_remote(dir: &Path, remote: Option<&str>) -> Result<String> {
+    let remotes = git_text(dir, &["remote"])?;
+    let remotes: Vec<&str> = remotes.lines().collect();
+    match remote {
+        Some(remote) if remotes.contains(&remote) => Ok(remote.to_string()),
+        Some(remote) => Err(git_error(format!("there is no remote called {remote}"))),
+        None if remotes.contains(&"origin") => Ok("origin".to_string()),
+        None => match remotes[..] {
+            [] => Err(git_error("add a remote to push to")),
+            [only] => Ok(only.to_string()),
+            _ => Err(git_error("choose which remote to push to")),
+        },
+    }
+}
+
+/// Push, setting the upstream on the first push of a branch.
+fn push(dir: &Path, remote: Option<&str>) -> Result<GitStatus> {
+    let before = status(dir)?;
+    match (&before.upstream, &before.branch) {
+        (Some(_), _) if remote.is_none() => git(dir, &["push"])?,
+        (_, Some(branch)) => git(dir, &["push", "--set-upstream", &push_remote(dir, remote)?, branch])?,
+        (_, None) => return Err(git_error("check out a branch to push")),
+    };
+    status(dir)
+}
+
+fn in_workspace(app: &AppHandle, run: impl FnOnce(&Path) -> Result<GitStatus>) -> Result<GitStatus> {
+    let status = run(&app.state::<Workspace>().directory())?;
+    // Pulling and merging rewrite boards
+    if let Err(err) = workspace::rescan(app) {
+        tracing::warn!("failed to rescan the boards directory: {err}");
+    }
+    Ok(status)
+}
+
+/// Make the boards directory a Git repository, unless it already is in one,
+/// and set up board merging.
+#[tauri::command]
+pub async fn git_init_workspace(app: AppHandle) -> Result<GitStatus> {
+    tauri::async_runtime::spawn_blocking(move || in_workspace(&app, init))
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// Commit the changes to the board at `path`, or to every board when omitted.
+#[tauri::command]
+pub async fn git_commit_board(app: AppHandle, message: String, path: Option<String>) -> Result<GitStatus> {
+    tauri::async_runtime::spawn_blocking(move || in_workspace(&app, |dir| commit(dir, path.as_deref(), &message)))
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+#[tauri::command]
+pub async fn git_pull(app: AppHandle) -> Result<GitStatus> {
+    tauri::async_runtime::spawn_blocking(move || in_workspace(&app, pull))
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// Push the current branch, to `remote` when it has no upstream yet or one
+/// is given.
+#[tauri::command]
+pub async fn git_push(app: AppHandle, remote: Option<String>) -> Result<GitStatus> {
+    tauri::async_runtime::spawn_blocking(move || in_workspace(&app, |dir| push(dir, remote.as_deref())))
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// Branch, pending changes and conflicts of the boards directory's repository.
+#[tauri::command]
+pub async fn get_git_status(app: AppHandle) -> Result<GitStatus> {
+    tauri::async_runtime::spawn_blocking(move || status(&app.state::<Workspace>().directory()))
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{board, document::CURRENT_DOCUMENT_VERSION};
+
+    fn repository() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "--quiet", "--initial-branch", "main"]).unwrap();
+        git(dir.path(), &["config", "user.name", "Test"]).unwrap();
+        git(dir.path(), &["config", "user.email", "test@example.com"]).unwrap();
+        dir
+    }
+
+    fn write_board(dir: &Path, name: &str, title: &str) -> String {
+        let document = json!({
+            "version": CURRENT_DOCUMENT_VERSION,
+            "meta": { "title": title, "createdAt": 0, "updatedAt": 0 },
+            "board": { "elements": [], "widgets": [] },
+        });
+        let path = dir.join(name).to_string_lossy().into_owned();
+        fs::write(&path, board::create_container(&document).unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn status_lists_changed_boards() {
+        let dir = repository();
+        write_board(dir.path(), "a.boardkit", "A");
+        let status = status(dir.path()).unwrap();
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.upstream, None);
+        assert_eq!(status.changed, [Path::new(&status.root).join("a.boardkit").to_string_lossy()]);
+        assert!(status.conflicts.is_empty());
+    }
+
+    #[test]
+    fn outside_a_repository_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(status(dir.path()), Err(Error::Git(_))));
+    }
+
+    #[test]
+    fn commit_adds_every_board() {
+        let dir = repository();
+        write_board(dir.path(), "a.boardkit", "A");
+        write_board(dir.path(), "b.boardkit", "B");
+        assert!(commit(dir.path(), None, "  ").is_err());
+        let status = commit(dir.path(), None, "Add boards").unwrap();
+        assert!(status.changed.is_empty());
+        assert_eq!(git_text(dir.path(), &["log", "--format=%s"]).unwrap(), "Add boards");
+        let again = commit(dir.path(), None, "Again");
+        assert!(matches!(again, Err(Error::Git(message)) if message == "nothing to commit"));
+    }
+
+    #[test]
+    fn commit_with_a_path_adds_only_that_board() {
+        let dir = repository();
+        let a = write_board(dir.path(), "a.boardkit", "A");
+        write_board(dir.path(), "b.boardkit", "B");
+        let b = dir.path().join("b.boardkit");
+        git(dir.path(), &["add", "--", &b.to_string_lossy()]).unwrap();
+        let status = commit(dir.path(), Some(&a), "Add A").unwrap();
+        assert!(status.changed.iter().any(|path| path.ends_with("b.boardkit")));
+        let committed = git_text(dir.path(), &["show", "--name-only", "--format=", "HEAD"]).unwrap();
+        assert_eq!(committed, "a.boardkit");
+        // What was staged before stays staged
+        let staged = git_text(dir.path(), &["diff", "--cached", "--name-only"]).unwrap();
+        assert_eq!(staged, "b.boardkit");
+    }
+
+    #[test]
+    fn commit_leaves_out_files_local_to_this_machine() {
+        let dir = repository();
+        let a = write_board(dir.path(), "a.boardkit", "A");
+        fs::create_dir_all(dir.path().join(".boardkit-history/a.boardkit")).unwrap();
+        fs::write(dir.path().join(".boardkit-history/a.boardkit/undo.zip"), b"undo").unwrap();
+        fs::write(format!("{a}.lock"), b"{}").unwrap();
+        fs::write(format!("{a}.tmp"), b"partial").unwrap();
+        fs::write(format!("{a}.append.tmp"), b"partial").unwrap();
+        commit(dir.path(), None, "Add A").unwrap();
+
+        let committed = git_text(dir.path(), &["ls-files"]).unwrap();
+        assert_eq!(committed.lines().collect::<Vec<_>>(), [IGNORE_FILE, "a.boardkit"]);
+    }
+
+    #[test]
+    fn init_sets_up_board_merging() {
+        let dir = tempfile::tempdir().unwrap();
+        init(dir.path()).unwrap();
+        // Again, without adding the attributes twice
+        init(dir.path()).unwrap();
+        let attributes = fs::read_to_string(dir.path().join(ATTRIBUTES_FILE)).unwrap();
+        assert_eq!(attributes, format!("{BOARD_ATTRIBUTES}\n"));
+        let ignored = fs::read_to_string(dir.path().join(IGNORE_FILE)).unwrap();
+        assert_eq!(ignored, format!("{}\n", IGNORED.join("\n")));
+        let driver = git_text(dir.path(), &["config", "merge.boardkit.driver"]).unwrap();
+        assert!(driver.ends_with(" merge %O %A %B"));
+    }
+
+    #[test]
+    fn ignore_file_keeps_the_users_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(IGNORE_FILE);
+        fs::write(&file, "*.bak\n*.boardkit.lock").unwrap();
+        ensure_lines(&file, IGNORED).unwrap();
+        let lines = fs::read_to_string(&file).unwrap();
+        let lines: Vec<&str> = lines.lines().collect();
+        assert_eq!(lines, ["*.bak", "*.boardkit.lock", ".boardkit-history/", "*.boardkit.tmp", "*.append.tmp"]);
+    }
+
+    #[test]
+    fn push_picks_a_remote_for_a_new_branch() {
+        let dir = repository();
+        write_board(dir.path(), "a.boardkit", "A");
+        commit(dir.path(), None, "Add A").unwrap();
+        assert!(matches!(push(dir.path(), None), Err(Error::Git(message)) if message == "add a remote to push to"));
+
+        let remote = tempfile::tempdir().unwrap();
+        git(remote.path(), &["init", "--quiet", "--bare"]).unwrap();
+        git(dir.path(), &["remote", "add", "shared", &remote.path().to_string_lossy()]).unwrap();
+        assert!(push(dir.path(), Some("origin")).is_err());
+        let status = push(dir.path(), None).unwrap();
+        assert_eq!(status.upstream.as_deref(), Some("shared/main"));
+        assert_eq!(status.ahead, 0);
+    }
+
+    /// Without the merge driver, which would run this test binary, Git leaves
+    /// the board conflicted as it would for a failed driver.
+    #[test]
+    fn conflicts_list_what_both_sides_changed() {
+        let dir = repository();
+        let path = write_board(dir.path(), "a.boardkit", "Base");
+        commit(dir.path(), None, "Base").unwrap();
+        git(dir.path(), &["checkout", "--quiet", "-b", "theirs"]).unwrap();
+        write_board(dir.path(), "a.boardkit", "Theirs");
+        commit(dir.path(), None, "Theirs").unwrap();
+        git(dir.path(), &["checkout", "--quiet", "main"]).unwrap();
+        write_board(dir.path(), "a.boardkit", "Ours");
+        commit(dir.path(), None, "Ours").unwrap();
+        assert!(git(dir.path(), &["merge", "--quiet", "theirs"]).is_err());
+
+        let status = status(dir.path()).unwrap();
+        let [conflict] = &status.conflicts[..] else { panic!("expected one conflict") };
+        assert_eq!(fs::canonicalize(&conflict.path).unwrap(), fs::canonicalize(&path).unwrap());
+        let [board_conflict] = &conflict.board_conflicts[..] else { panic!("expected one board conflict") };
+        assert_eq!(board_conflict.path, "/meta/title");
+        assert_eq!(
+            (&board_conflict.base, &board_conflict.ours, &board_conflict.theirs),
+            (&Value::from("Base"), &Value::from("Ours"), &Value::from("Theirs"))
+        );
+
+        // Committing the board concludes the merge
+        write_board(dir.path(), "a.boardkit", "Ours");
+        let status = commit(dir.path(), None, "Merge").unwrap();
+        assert!(status.conflicts.is_empty());
+        assert_eq!(git_text(dir.path(), &["rev-list", "--count", "--merges", "HEAD"]).unwrap(), "1");
+    }
+}
@@ -0,0 +1,568 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Above this the force layout's pairwise pass gets noticeably slow.
+const MAX_NODES: usize = 2000;
+/// Barycenter sweeps when ordering the nodes of each layer.
+const ORDERING_SWEEPS: usize = 8;
+const OVERLAP_PASSES: usize = 50;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutNode {
+    id: String,
+    /// Current position, used as the force layout's starting point and to
+    /// keep the result where the nodes were
+    #[serde(default)]
+    x: f64,
+    #[serde(default)]
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutEdge {
+    source: String,
+    target: String,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutAlgorithm {
+    /// Sugiyama-style layers following edge direction, for flows and dependencies
+    Layered,
+    /// Spring simulation, for networks without a clear direction
+    Force,
+    /// Parents centred over their children, for hierarchies and mind maps
+    Tree,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutDirection {
+    Down,
+    Right,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LayoutOptions {
+    /// Which way edges point in the layered and tree layouts
+    pub direction: LayoutDirection,
+    /// Gap between neighbouring nodes
+    pub node_spacing: f64,
+    /// Gap between layers (layered) or levels (tree)
+    pub layer_spacing: f64,
+    /// Simulation steps for the force layout
+    pub iterations: u32,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            direction: LayoutDirection::Down,
+            node_spacing: 40.0,
+            layer_spacing: 80.0,
+            iterations: 300,
+        }
+    }
+}
+
+/// New top-left corner of a node.
+#[derive(Serialize)]
+pub struct NodePosition {
+    id: String,
+    x: f64,
+    y: f64,
+}
+
+/// Nodes by index, with sizes along the layout's cross axis (`width`) and
+/// main axis (`height`), so "right" layouts reuse the "down" code.
+struct Graph {
+    sizes: Vec<(f64, f64)>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Graph {
+    fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    fn adjacency(&self, edges: &[(usize, usize)]) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+        let mut successors = vec![Vec::new(); self.len()];
+        let mut predecessors = vec![Vec::new(); self.len()];
+        for &(from, to) in edges {
+            successors[from].push(to);
+            predecessors[to].push(from);
+        }
+        (successors, predecessors)
+    }
+}
+
+/// Depth-first order in which every edge that isn't part of a cycle points
+/// forward; edges pointing backward are the ones to reverse.
+fn forward_order(graph: &Graph) -> Vec<usize> {
+    let (successors, _) = graph.adjacency(&graph.edges);
+    let mut visited = vec![false; graph.len()];
+    let mut post_order = Vec::with_capacity(graph.len());
+    for start in 0..graph.len() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut stack = vec![(start, 0)];
+        while let Some((node, next)) = stack.last_mut() {
+            match successors[*node].get(*next) {
+                Some(&child) => {
+                    *next += 1;
+                    if !visited[child] {
+                        visited[child] = true;
+                        stack.push((child, 0));
+                    }
+                }
+                None => {
+                    post_order.push(*node);
+                    stack.pop();
+                }
+            }
+        }
+    }
+    post_order.reverse();
+    post_order
+}
+
+/// Place layers along the main axis and nodes within them, pulling each node
+/// towards the average position of its neighbours in the previous layer.
+fn layered(graph: &Graph, options: &LayoutOptions) -> Vec<(f64, f64)> {
+    let order = forward_order(graph);
+    let mut rank = vec![0; graph.len()];
+    for (position, &node) in order.iter().enumerate() {
+        rank[node] = position;
+    }
+    let edges: Vec<(usize, usize)> = graph
+        .edges
+        .iter()
+        .map(|&(from, to)| if rank[from] < rank[to] { (from, to) } else { (to, from) })
+        .collect();
+    let (successors, predecessors) = graph.adjacency(&edges);
+
+    // Longest path from the sources
+    let mut layer_of = vec![0; graph.len()];
+    for &node in &order {
+        for &next in &successors[node] {
+            layer_of[next] = layer_of[next].max(layer_of[node] + 1);
+        }
+    }
+    let layer_count = layer_of.iter().max().map_or(0, |max| max + 1);
+    let mut layers: Vec<Vec<usize>> = vec![Vec::new(); layer_count];
+    for &node in &order {
+        layers[layer_of[node]].push(node);
+    }
+
+    // Barycenter ordering, alternating downward and upward sweeps
+    let mut index_in_layer = vec![0.0; graph.len()];
+    for layer in &layers {
+        for (index, &node) in layer.iter().enumerate() {
+            index_in_layer[node] = index as f64;
+        }
+    }
+    for sweep in 0..ORDERING_SWEEPS {
+        let downward = sweep % 2 == 0;
+        let neighbours = if downward { &predecessors } else { &successors };
+        let sequence: Vec<usize> = if downward { (1..layer_count).collect() } else { (0..layer_count).rev().collect() };
+        for layer in sequence {
+            let key = |node: usize| match neighbours[node].len() {
+                0 => index_in_layer[node],
+                count => neighbours[node].iter().map(|&other| index_in_layer[other]).sum::<f64>() / count as f64,
+            };
+            let mut keyed: Vec<(f64, usize)> = layers[layer].iter().map(|&node| (key(node), node)).collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[layer] = keyed.into_iter().map(|(_, node)| node).collect();
+            for (index, &node) in layers[layer].iter().enumerate() {
+                index_in_layer[node] = index as f64;
+            }
+        }
+    }
+
+    let mut centers = vec![(0.0, 0.0); graph.len()];
+    let mut main = 0.0;
+    for layer in &layers {
+        let depth = layer.iter().map(|&node| graph.sizes[node].1).fold(0.0, f64::max);
+        let mut next_free: Option<f64> = None;
+        for &node in layer {
+            let (width, _) = graph.sizes[node];
+            // Predecessors are all in earlier layers, so already placed
+            let placed: Vec<f64> = predecessors[node].iter().map(|&other| centers[other].0).collect();
+            let packed = next_free.map_or(width / 2.0, |free| free + width / 2.0);
+            let wanted = if placed.is_empty() { packed } else { placed.iter().sum::<f64>() / placed.len() as f64 };
+            let cross = wanted.max(packed);
+            centers[node] = (cross, main + depth / 2.0);
+            next_free = Some(cross + width / 2.0 + options.node_spacing);
+        }
+        main += depth + options.layer_spacing;
+    }
+    centers
+}
+
+/// Tidy tree: each subtree gets a band wide enough for its children side by
+/// side and the parent sits centred over them. Nodes the edges don't reach from a
+/// root (cycles) start trees of their own.
+fn tree(graph: &Graph, options: &LayoutOptions) -> Vec<(f64, f64)> {
+    let (successors, predecessors) = graph.adjacency(&graph.edges);
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); graph.len()];
+    let mut depth = vec![0; graph.len()];
+    let mut seen = vec![false; graph.len()];
+    let mut roots = Vec::new();
+    let candidates = (0..graph.len()).filter(|&node| predecessors[node].is_empty()).chain(0..graph.len());
+    for root in candidates.collect::<Vec<_>>() {
+        if seen[root] {
+            continue;
+        }
+        seen[root] = true;
+        roots.push(root);
+        let mut queue = VecDeque::from([root]);
+        while let Some(node) = queue.pop_front() {
+            for &child in &successors[node] {
+                if !seen[child] {
+                    seen[child] = true;
+                    depth[child] = depth[node] + 1;
+                    children[node].push(child);
+                    queue.push_back(child);
+                }
+            }
+        }
+    }
+
+    let level_count = depth.iter().max().map_or(0, |max| max + 1);
+    let mut level_depth = vec![0.0_f64; level_count];
+    for node in 0..graph.len() {
+        level_depth[depth[node]] = level_depth[depth[node]].max(graph.sizes[node].1);
+    }
+    let mut level_start = vec![0.0; level_count];
+    for level in 1..level_count {
+        level_start[level] = level_start[level - 1] + level_depth[level - 1] + options.layer_spacing;
+    }
+
+    // Subtree widths, children before parents
+    let mut band = vec![0.0_f64; graph.len()];
+    let mut by_depth: Vec<usize> = (0..graph.len()).collect();
+    by_depth.sort_by_key(|&node| std::cmp::Reverse(depth[node]));
+    for &node in &by_depth {
+        let kids = &children[node];
+        let spread = kids.iter().map(|&child| band[child]).sum::<f64>()
+            + options.node_spacing * kids.len().saturating_sub(1) as f64;
+        band[node] = graph.sizes[node].0.max(spread);
+    }
+
+    let mut centers = vec![(0.0, 0.0); graph.len()];
+    let mut left = 0.0;
+    for &root in &roots {
+        let mut stack = vec![(root, left)];
+        while let Some((node, start)) = stack.pop() {
+            let level = depth[node];
+            centers[node] = (start + band[node] / 2.0, level_start[level] + level_depth[level] / 2.0);
+            let kids = &children[node];
+            let spread = kids.iter().map(|&child| band[child]).sum::<f64>()
+                + options.node_spacing * kids.len().saturating_sub(1) as f64;
+            let mut child_start = start + (band[node] - spread) / 2.0;
+            for &child in kids {
+                stack.push((child, child_start));
+                child_start += band[child] + options.node_spacing;
+            }
+        }
+        left += band[root] + options.node_spacing * 2.0;
+    }
+    centers
+}
+
+/// Fruchterman-Reingold: nodes repel each other, edges pull their ends
+/// together, and the step size cools so the layout settles.
+fn force(graph: &Graph, starts: Vec<(f64, f64)>, options: &LayoutOptions) -> Vec<(f64, f64)> {
+    let count = graph.len();
+    let average_size = graph.sizes.iter().map(|(width, height)| (width + height) / 2.0).sum::<f64>() / count as f64;
+    let k = average_size + options.node_spacing;
+    let mut centers = starts;
+    // Stacked nodes (new boards, pasted content) would never separate
+    let distinct: HashSet<(i64, i64)> = centers.iter().map(|&(x, y)| (x as i64, y as i64)).collect();
+    if distinct.len() < count {
+        let radius = k * count as f64 / std::f64::consts::TAU;
+        for (index, center) in centers.iter_mut().enumerate() {
+            let angle = index as f64 / count as f64 * std::f64::consts::TAU;
+            *center = (radius * angle.cos(), radius * angle.sin());
+        }
+    }
+
+    let iterations = options.iterations.clamp(1, 2000);
+    let mut temperature = k * (count as f64).sqrt();
+    let cooling = temperature / iterations as f64;
+    for _ in 0..iterations {
+        let mut moves = vec![(0.0, 0.0); count];
+        for a in 0..count {
+            for b in a + 1..count {
+                let (dx, dy) = (centers[a].0 - centers[b].0, centers[a].1 - centers[b].1);
+                let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+                let push = k * k / distance;
+                let (fx, fy) = (dx / distance * push, dy / distance * push);
+                moves[a].0 += fx;
+                moves[a].1 += fy;
+                moves[b].0 -= fx;
+                moves[b].1 -= fy;
+            }
+        }
+        for &(from, to) in &graph.edges {
+            let (dx, dy) = (centers[from].0 - centers[to].0, centers[from].1 - centers[to].1);
+            let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+            let pull = distance * distance / k;
+            let (fx, fy) = (dx / distance * pull, dy / distance * pull);
+            moves[from].0 -= fx;
+            moves[from].1 -= fy;
+            moves[to].0 += fx;
+            moves[to].1 += fy;
+        }
+        // Light gravity keeps disconnected pieces from drifting apart
+        let (cx, cy) = centroid(&centers);
+        for (center, step) in centers.iter_mut().zip(&mut moves) {
+            step.0 -= (center.0 - cx) * 0.05;
+            step.1 -= (center.1 - cy) * 0.05;
+            let length = (step.0 * step.0 + step.1 * step.1).sqrt().max(0.01);
+            let scale = length.min(temperature) / length;
+            center.0 += step.0 * scale;
+            center.1 += step.1 * scale;
+        }
+        temperature = (temperature - cooling).max(1.0);
+    }
+    remove_overlaps(graph, &mut centers, options.node_spacing);
+    centers
+}
+
+fn centroid(centers: &[(f64, f64)]) -> (f64, f64) {
+    let count = centers.len().max(1) as f64;
+    let (x, y) = centers.iter().fold((0.0, 0.0), |(x, y), center| (x + center.0, y + center.1));
+    (x / count, y / count)
+}
+
+/// Push overlapping nodes apart along the axis where they overlap least.
+fn remove_overlaps(graph: &Graph, centers: &mut [(f64, f64)], gap: f64) {
+    for _ in 0..OVERLAP_PASSES {
+        let mut moved = false;
+        for a in 0..centers.len() {
+            for b in a + 1..centers.len() {
+                let overlap_x = (graph.sizes[a].0 + graph.sizes[b].0) / 2.0 + gap / 2.0
+                    - (centers[a].0 - centers[b].0).abs();
+                let overlap_y = (graph.sizes[a].1 + graph.sizes[b].1) / 2.0 + gap / 2.0
+                    - (centers[a].1 - centers[b].1).abs();
+                if overlap_x <= 0.0 || overlap_y <= 0.0 {
+                    continue;
+                }
+                moved = true;
+                if overlap_x < overlap_y {
+                    let shift = if centers[a].0 < centers[b].0 { -overlap_x / 2.0 } else { overlap_x / 2.0 };
+                    centers[a].0 += shift;
+                    centers[b].0 -= shift;
+                } else {
+                    let shift = if centers[a].1 < centers[b].1 { -overlap_y / 2.0 } else { overlap_y / 2.0 };
+                    centers[a].1 += shift;
+                    centers[b].1 -= shift;
+                }
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+}
+
+/// Positions for `nodes`, starting at the top-left corner of their current
+/// bounds.
+fn layout(
+    nodes: Vec<LayoutNode>,
+    edges: Vec<LayoutEdge>,
+    algorithm: LayoutAlgorithm,
+    options: LayoutOptions,
+) -> Result<Vec<NodePosition>> {
+    if nodes.len() > MAX_NODES {
+        return Err(Error::TooLarge {
+            size: nodes.len() as u64,
+            max: MAX_NODES as u64,
+        });
+    }
+    if nodes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let index: HashMap<&str, usize> = nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+    let mut seen = HashSet::new();
+    let edges: Vec<(usize, usize)> = edges
+        .iter()
+        .filter_map(|edge| Some((*index.get(edge.source.as_str())?, *index.get(edge.target.as_str())?)))
+        .filter(|&(from, to)| from != to && seen.insert((from, to)))
+        .collect();
+
+    // Right-pointing layouts are down-pointing ones with the axes swapped
+    let swap = matches!(options.direction, LayoutDirection::Right)
+        && matches!(algorithm, LayoutAlgorithm::Layered | LayoutAlgorithm::Tree);
+    let oriented = |(x, y): (f64, f64)| if swap { (y, x) } else { (x, y) };
+    let graph = Graph {
+        sizes: nodes.iter().map(|node| oriented((node.width.max(1.0), node.height.max(1.0)))).collect(),
+        edges,
+    };
+    let centers = match algorithm {
+        LayoutAlgorithm::Layered => layered(&graph, &options),
+        LayoutAlgorithm::Tree => tree(&graph, &options),
+        LayoutAlgorithm::Force => {
+            let starts = nodes.iter().map(|node| (node.x + node.width / 2.0, node.y + node.height / 2.0)).collect();
+            force(&graph, starts, &options)
+        }
+    };
+
+    let corners: Vec<(f64, f64)> = centers
+        .into_iter()
+        .zip(&nodes)
+        .map(|(center, node)| {
+            let (x, y) = oriented(center);
+            (x - node.width / 2.0, y - node.height / 2.0)
+        })
+        .collect();
+    let min = |values: &mut dyn Iterator<Item = f64>| values.fold(f64::INFINITY, f64::min);
+    let (offset_x, offset_y) = (
+        min(&mut nodes.iter().map(|node| node.x)) - min(&mut corners.iter().map(|corner| corner.0)),
+        min(&mut nodes.iter().map(|node| node.y)) - min(&mut corners.iter().map(|corner| corner.1)),
+    );
+    Ok(nodes
+        .into_iter()
+        .zip(corners)
+        .map(|(node, (x, y))| NodePosition {
+            id: node.id,
+            x: x + offset_x,
+            y: y + offset_y,
+        })
+        .collect())
+}
+
+/// Compute tidy positions for connected nodes. Edges to ids that aren't in
+/// `nodes` are ignored, so callers can pass every connection on the board.
+/// The result starts at the top-left corner of the nodes' current bounds.
+#[tauri::command]
+pub async fn layout_graph(
+    nodes: Vec<LayoutNode>,
+    edges: Vec<LayoutEdge>,
+    algorithm: LayoutAlgorithm,
+    options: Option<LayoutOptions>,
+) -> Result<Vec<NodePosition>> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || layout(nodes, edges, algorithm, options))
+        .await
+        .map_err(|err| Error::Render(err.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: f64 = 100.0;
+    const HEIGHT: f64 = 50.0;
+
+    fn nodes(ids: &[&str]) -> Vec<LayoutNode> {
+        ids.iter()
+            .map(|id| LayoutNode {
+                id: id.to_string(),
+                x: 0.0,
+                y: 0.0,
+                width: WIDTH,
+                height: HEIGHT,
+            })
+            .collect()
+    }
+
+    fn edges(pairs: &[(&str, &str)]) -> Vec<LayoutEdge> {
+        pairs
+            .iter()
+            .map(|(source, target)| LayoutEdge {
+                source: source.to_string(),
+                target: target.to_string(),
+            })
+            .collect()
+    }
+
+    fn positions(result: Result<Vec<NodePosition>>) -> Vec<(String, f64, f64)> {
+        result.unwrap().into_iter().map(|position| (position.id, position.x, position.y)).collect()
+    }
+
+    fn expected(positions: &[(&str, f64, f64)]) -> Vec<(String, f64, f64)> {
+        positions.iter().map(|&(id, x, y)| (id.to_string(), x, y)).collect()
+    }
+
+    #[test]
+    fn layers_follow_edge_direction() {
+        let graph = || (nodes(&["a", "b", "c", "d"]), edges(&[("a", "b"), ("b", "c"), ("a", "d")]));
+        let (nodes, edges) = graph();
+        let down = layout(nodes, edges, LayoutAlgorithm::Layered, LayoutOptions::default());
+        // Each layer is the node height plus the layer spacing below the last
+        assert_eq!(
+            positions(down),
+            expected(&[("a", 0.0, 0.0), ("b", 140.0, 130.0), ("c", 140.0, 260.0), ("d", 0.0, 130.0)])
+        );
+
+        let (nodes, edges) = graph();
+        let options = LayoutOptions {
+            direction: LayoutDirection::Right,
+            ..LayoutOptions::default()
+        };
+        let right = layout(nodes, edges, LayoutAlgorithm::Layered, options);
+        assert_eq!(
+            positions(right),
+            expected(&[("a", 0.0, 0.0), ("b", 180.0, 90.0), ("c", 360.0, 90.0), ("d", 180.0, 0.0)])
+        );
+    }
+
+    #[test]
+    fn cycles_self_loops_and_unknown_ends_are_tolerated() {
+        let edges = edges(&[("a", "b"), ("b", "a"), ("a", "a"), ("a", "missing"), ("a", "b")]);
+        let layered = positions(layout(nodes(&["a", "b"]), edges, LayoutAlgorithm::Layered, LayoutOptions::default()));
+        assert_eq!(layered, expected(&[("a", 0.0, 0.0), ("b", 0.0, 130.0)]));
+    }
+
+    #[test]
+    fn parents_are_centred_over_their_subtrees() {
+        let edges = edges(&[("r", "x"), ("r", "y"), ("x", "z")]);
+        let tree = layout(nodes(&["r", "x", "y", "z"]), edges, LayoutAlgorithm::Tree, LayoutOptions::default());
+        assert_eq!(
+            positions(tree),
+            expected(&[("r", 70.0, 0.0), ("x", 0.0, 130.0), ("y", 140.0, 130.0), ("z", 0.0, 260.0)])
+        );
+    }
+
+    #[test]
+    fn force_layout_separates_stacked_nodes() {
+        let ids = ["a", "b", "c", "d", "e", "f"];
+        let mut stacked = nodes(&ids);
+        for node in &mut stacked {
+            (node.x, node.y) = (500.0, 300.0);
+        }
+        let ring = edges(&[("a", "b"), ("b", "c"), ("c", "d"), ("d", "e"), ("e", "f"), ("f", "a")]);
+        let placed = positions(layout(stacked, ring, LayoutAlgorithm::Force, LayoutOptions::default()));
+
+        for (index, (a, ax, ay)) in placed.iter().enumerate() {
+            for (b, bx, by) in &placed[index + 1..] {
+                let apart = (ax - bx).abs() >= WIDTH || (ay - by).abs() >= HEIGHT;
+                assert!(apart, "{a} at ({ax}, {ay}) overlaps {b} at ({bx}, {by})");
+            }
+        }
+        let min_x = placed.iter().map(|(_, x, _)| *x).fold(f64::INFINITY, f64::min);
+        let min_y = placed.iter().map(|(_, _, y)| *y).fold(f64::INFINITY, f64::min);
+        assert_eq!((min_x.round(), min_y.round()), (500.0, 300.0));
+    }
+
+    #[test]
+    fn too_many_nodes_are_refused() {
+        let ids: Vec<String> = (0..=MAX_NODES).map(|index| index.to_string()).collect();
+        let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+        let result = layout(nodes(&ids), Vec::new(), LayoutAlgorithm::Tree, LayoutOptions::default());
+        assert!(matches!(result, Err(Error::TooLarge { .. })));
+        assert!(positions(layout(Vec::new(), Vec::new(), LayoutAlgorithm::Force, LayoutOptions::default())).is_empty());
+    }
+}
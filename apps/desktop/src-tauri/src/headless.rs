@@ -0,0 +1,156 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    board,
+    cli::{self, Format},
+    document::BoardDocument,
+    error::{Error, Result},
+};
+
+/// Longest request line accepted, boards with embedded images included.
+const MAX_REQUEST: u64 = 64 * 1024 * 1024;
+
+/// One line of the protocol: a board to render, inline or as a saved file.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RenderRequest {
+    /// Echoed back so clients can pipeline requests
+    #[serde(default)]
+    id: Value,
+    format: Format,
+    /// Board document, as in a .boardkit file
+    #[serde(default)]
+    board: Option<BoardDocument>,
+    /// Saved .boardkit file; also where images of an inline `board` come from
+    #[serde(default)]
+    file: Option<String>,
+    /// Pixel ratio of PNG renders
+    #[serde(default)]
+    scale: Option<f32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RenderResponse {
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mime_type: Option<&'static str>,
+    /// Base64 of the rendered file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
+fn render(request: RenderRequest) -> Result<(Format, Vec<u8>)> {
+    let document = match (request.board, &request.file) {
+        (Some(document), _) => document,
+        (None, Some(file)) => serde_json::from_value(board::read_board_document(file)?)?,
+        (None, None) => return Err(Error::Render("request needs a board or a file".to_string())),
+    };
+    let bytes = cli::render(&document, request.format, request.file.as_deref(), request.scale.unwrap_or(2.0))?;
+    Ok((request.format, bytes))
+}
+
+fn respond(line: &str) -> RenderResponse {
+    let request: RenderRequest = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(err) => {
+            return RenderResponse {
+                id: Value::Null,
+                mime_type: None,
+                data: None,
+                error: Some(err.into()),
+            }
+        }
+    };
+    let id = request.id.clone();
+    match render(request) {
+        Ok((format, bytes)) => RenderResponse {
+            id,
+            mime_type: Some(format.mime_type()),
+            data: Some(STANDARD.encode(bytes)),
+            error: None,
+        },
+        Err(err) => RenderResponse {
+            id,
+            mime_type: None,
+            data: None,
+            error: Some(err),
+        },
+    }
+}
+
+/// Answer requests one line at a time until the input ends. Each request is
+/// a JSON object on one line and gets one JSON line back, in order:
+///
+/// `{"id": 1, "format": "png", "file": "docs/flow.boardkit"}` →
+/// `{"id": 1, "mimeType": "image/png", "data": "<base64>"}`, or
+/// `{"id": 1, "error": {"kind", "message"}}`. `board` may carry the document
+/// inline instead of `file`; formats are png, svg, pdf, html and md.
+fn serve_lines(input: impl Read, mut output: impl Write) -> std::io::Result<()> {
+    let mut reader = BufReader::new(input);
+    loop {
+        let mut line = String::new();
+        if reader.by_ref().take(MAX_REQUEST).read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let response = if line.ends_with('\n') || (line.len() as u64) < MAX_REQUEST {
+            if line.trim().is_empty() {
+                continue;
+            }
+            respond(&line)
+        } else {
+            // Drop the rest of the oversized line before answering
+            reader.skip_until(b'\n')?;
+            RenderResponse {
+                id: Value::Null,
+                mime_type: None,
+                data: None,
+                error: Some(Error::TooLarge {
+                    size: line.len() as u64,
+                    max: MAX_REQUEST,
+                }),
+            }
+        };
+        serde_json::to_writer(&mut output, &response)?;
+        output.write_all(b"\n")?;
+        output.flush()?;
+    }
+}
+
+/// `boardkit --headless-serve`: render requests from stdin to stdout, for
+/// documentation builds that keep one renderer around.
+pub fn serve_stdio() -> Result<()> {
+    serve_lines(std::io::stdin().lock(), std::io::stdout().lock())?;
+    Ok(())
+}
+
+/// `boardkit --headless-serve --port <port>`: the same protocol over TCP on
+/// the loopback interface, one thread per connection. Prints the address it
+/// listens on, which matters with port 0.
+pub fn serve_tcp(port: u16) -> Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    println!("{}", listener.local_addr()?);
+    std::io::stdout().flush()?;
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        std::thread::spawn(move || {
+            if let Err(err) = serve_connection(&stream) {
+                eprintln!("boardkit: render connection closed: {err}");
+            }
+        });
+    }
+    Ok(())
+}
+
+fn serve_connection(stream: &TcpStream) -> std::io::Result<()> {
+    serve_lines(stream, stream)
+}
@@ -0,0 +1,175 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Window};
+
+use crate::{
+    board,
+    diff::{self, BoardDiff},
+    error::{Error, Result},
+    links, locks, persist, search, thumbnails,
+};
+
+const HISTORY_DIR: &str = ".boardkit-history";
+const INDEX_NAME: &str = "index.json";
+const MAX_VERSIONS: usize = 50;
+
+/// One saved copy of a board, listed oldest first in the history index.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Version {
+    id: String,
+    pub created_at: u64,
+    pub size: u64,
+    /// Hash of the board state, so saves that only touch metadata don't pile up copies.
+    hash: String,
+    pub element_count: usize,
+    pub widget_count: usize,
+}
+
+/// `<board dir>/.boardkit-history/<board file name>/`, next to the board so
+/// history travels with the folder.
+pub fn history_dir(path: &Path) -> PathBuf {
+    let parent = path.parent().unwrap_or_else(|| Path::new(""));
+    parent.join(HISTORY_DIR).join(path.file_name().unwrap_or_default())
+}
+
+fn read_index(dir: &Path) -> Vec<Version> {
+    persist::read_json(&dir.join(INDEX_NAME))
+}
+
+/// Saved versions of the board at `path`, oldest first.
+pub fn versions(path: &Path) -> Vec<Version> {
+    read_index(&history_dir(path))
+}
+
+fn board_hash(document: &Value) -> String {
+    let state = serde_json::to_vec(&document["board"]).unwrap_or_default();
+    Sha256::digest(&state).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn count(document: &Value, pointer: &str) -> usize {
+    document.pointer(pointer).and_then(Value::as_array).map_or(0, Vec::len)
+}
+
+/// Store `bytes` as the newest version of the board at `path`, unless the
+/// board state is identical to the latest version.
+pub fn record(path: &str, bytes: &[u8], document: &Value) -> Result<()> {
+    let dir = history_dir(Path::new(path));
+    fs::create_dir_all(&dir)?;
+    let mut versions = read_index(&dir);
+
+    let hash = board_hash(document);
+    if versions.last().is_some_and(|latest| latest.hash == hash) {
+        return Ok(());
+    }
+    let mut created_at = persist::now_millis();
+    if let Some(latest) = versions.last() {
+        created_at = created_at.max(latest.created_at + 1);
+    }
+    let version = Version {
+        id: created_at.to_string(),
+        created_at,
+        size: bytes.len() as u64,
+        hash,
+        element_count: count(document, "/board/elements"),
+        widget_count: count(document, "/board/widgets"),
+    };
+    persist::write_atomic(&dir.join(format!("{}.boardkit", version.id)), bytes)?;
+    versions.push(version);
+
+    let excess = versions.len().saturating_sub(MAX_VERSIONS);
+    for old in versions.drain(..excess) {
+        let _ = fs::remove_file(dir.join(format!("{}.boardkit", old.id)));
+    }
+    persist::write_json(&dir.join(INDEX_NAME), &versions)
+}
+
+/// Record a version of a just-saved board off the command thread.
+pub fn record_in_background(path: String, bytes: Vec<u8>, document: Value) {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(err) = record(&path, &bytes, &document) {
+            tracing::warn!("failed to snapshot {path}: {err}");
+        }
+    });
+}
+
+/// Move a board's history along with a renamed board.
+pub fn rename(from: &Path, to: &Path) -> Result<()> {
+    let (from, to) = (history_dir(from), history_dir(to));
+    if from.exists() && from != to {
+        let _ = fs::remove_dir_all(&to);
+        fs::rename(from, to)?;
+    }
+    Ok(())
+}
+
+pub fn remove(path: &Path) -> Result<()> {
+    let dir = history_dir(path);
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+/// Path to a listed version's copy, rejecting ids that aren't in the index.
+fn version_path(path: &str, id: &str) -> Result<PathBuf> {
+    let dir = history_dir(Path::new(path));
+    if !read_index(&dir).iter().any(|version| version.id == id) {
+        return Err(Error::NotFound(format!("version {id} of {path}")));
+    }
+    Ok(dir.join(format!("{id}.boardkit")))
+}
+
+/// Saved versions of a board, newest first.
+#[tauri::command]
+pub fn list_versions(path: String) -> Vec<Version> {
+    let mut versions = versions(Path::new(&path));
+    versions.reverse();
+    versions
+}
+
+/// Replace the board with one of its versions. The current file is recorded
+/// first so the restore itself can be rolled back.
+#[tauri::command]
+pub async fn restore_version(app: AppHandle, window: Window, path: String, id: String) -> Result<()> {
+    locks::lock_for_save(&window, &path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let version = version_path(&path, &id)?;
+        let current = board::read_board(&path)?;
+        record(&path, &current, &board::read_board_document(&path)?)?;
+
+        let version = version.to_string_lossy();
+        let bytes = board::read_board(&version)?;
+        let document = board::read_board_document(&version)?;
+        board::write_board(&path, &bytes)?;
+        tracing::info!(path, id, "board restored from history");
+        thumbnails::generate_in_background(&app, path.clone(), document.clone());
+        links::record_in_background(&app, path.clone(), document.clone());
+        search::index_in_background(&app, path, document);
+        Ok(())
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// Elements, widgets and connections that changed between version `from` and version `to`,
+/// or the board as currently saved when `to` is omitted.
+#[tauri::command]
+pub async fn diff_versions(path: String, from: String, to: Option<String>) -> Result<BoardDiff> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let before = board::read_board_document(&version_path(&path, &from)?.to_string_lossy())?;
+        let after = match to {
+            Some(to) => board::read_board_document(&version_path(&path, &to)?.to_string_lossy())?,
+            None => board::read_board_document(&path)?,
+        };
+        Ok(diff::diff_documents(&before, &after))
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
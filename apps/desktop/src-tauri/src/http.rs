@@ -0,0 +1,137 @@
+//! The small HTTP/1.1 server behind the automation endpoint and board
+//! sharing. One thread per connection, one request per connection: both only
+//! ever see a handful of clients, which doesn't warrant an async stack.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Headers past this many are left unread.
+const MAX_HEADERS: usize = 100;
+
+/// A request whose line and headers were read; the body is left to the handler.
+pub struct Request<'a> {
+    pub method: String,
+    pub target: String,
+    headers: Vec<(String, String)>,
+    reader: BufReader<&'a TcpStream>,
+}
+
+impl<'a> Request<'a> {
+    fn read(stream: &'a TcpStream) -> io::Result<Self> {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut headers = Vec::new();
+        for _ in 0..MAX_HEADERS {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        let mut parts = request_line.split_whitespace();
+        Ok(Self {
+            method: parts.next().unwrap_or_default().to_string(),
+            target: parts.next().unwrap_or_default().to_string(),
+            headers,
+            reader,
+        })
+    }
+
+    /// The first header called `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// `Content-Length`, or `usize::MAX` when it is unreadable so size checks reject it.
+    pub fn content_length(&self) -> usize {
+        self.header("content-length").map_or(0, |value| value.parse().unwrap_or(usize::MAX))
+    }
+
+    pub fn read_body(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        let mut body = vec![0; len];
+        self.reader.read_exact(&mut body)?;
+        Ok(body)
+    }
+}
+
+/// Write a complete response and close the exchange. `head` leaves the body out.
+pub fn respond(mut stream: &TcpStream, status: &str, content_type: &str, body: &[u8], head: bool) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\n\
+         X-Content-Type-Options: nosniff\r\nReferrer-Policy: no-referrer\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    if !head {
+        stream.write_all(body)?;
+    }
+    stream.flush()
+}
+
+/// A running server, accepting until [`Server::stop`].
+pub struct Server {
+    port: u16,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Server {
+    /// Serve `listener` in the background. Each connection runs `handle` on its
+    /// own thread, or `busy` once `max_connections` are open. `name` labels the
+    /// server in the logs.
+    pub fn start<H, B>(listener: TcpListener, name: &'static str, max_connections: usize, busy: B, handle: H) -> Self
+    where
+        H: Fn(&TcpStream, Request<'_>) -> io::Result<()> + Send + Sync + 'static,
+        B: Fn(&TcpStream) -> io::Result<()> + Send + Sync + 'static,
+    {
+        let port = listener.local_addr().map_or(0, |addr| addr.port());
+        let stopped = Arc::new(AtomicBool::new(false));
+        let accepting = stopped.clone();
+        let handlers = Arc::new((handle, busy, AtomicUsize::new(0)));
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accepting.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let handlers = handlers.clone();
+                std::thread::spawn(move || {
+                    let (handle, busy, connections) = &*handlers;
+                    let open = connections.fetch_add(1, Ordering::SeqCst);
+                    let result = if open >= max_connections {
+                        busy(&stream)
+                    } else {
+                        stream
+                            .set_read_timeout(Some(READ_TIMEOUT))
+                            .and_then(|()| handle(&stream, Request::read(&stream)?))
+                    };
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    if let Err(err) = result {
+                        tracing::debug!("{name} connection closed: {err}");
+                    }
+                });
+            }
+        });
+        Self { port, stopped }
+    }
+
+    /// Stop accepting connections; those already open run to completion.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect_timeout(&SocketAddr::from((Ipv4Addr::LOCALHOST, self.port)), READ_TIMEOUT);
+    }
+}
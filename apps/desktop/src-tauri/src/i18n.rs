@@ -0,0 +1,186 @@
+//! Translations of the native menus. Locale files are bundled under
+//! `locales/` as `<language>.json`, e.g. `fr.json` or `pt-BR.json`, each an
+//! object mapping an English label to its translation. Labels a locale
+//! doesn't translate stay in English, and the language follows the system
+//! until the user picks one with `set_app_language`.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    error::{Error, Result},
+    menu,
+    settings::Settings,
+};
+
+const LOCALES_DIR: &str = "locales";
+/// The language the labels are written in, which needs no locale file.
+const SOURCE_LANGUAGE: &str = "en";
+
+/// `fr_FR.UTF-8` or `fr-fr` as `fr-FR`.
+fn normalize(tag: &str) -> String {
+    let tag = tag.split(['.', '@']).next().unwrap_or(tag).replace('_', "-");
+    match tag.split_once('-') {
+        Some((language, region)) => format!("{}-{}", language.to_lowercase(), region.to_uppercase()),
+        None => tag.to_lowercase(),
+    }
+}
+
+pub struct I18n {
+    dir: Option<PathBuf>,
+    /// The language shown and its translations
+    current: Mutex<(String, HashMap<String, String>)>,
+}
+
+impl I18n {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let dir = app.path().resource_dir().ok().map(|dir| dir.join(LOCALES_DIR));
+        let i18n = Self {
+            dir,
+            current: Mutex::new((SOURCE_LANGUAGE.to_string(), HashMap::new())),
+        };
+        let language = app.state::<Settings>().get().language;
+        i18n.set_language(language.as_deref());
+        Ok(i18n)
+    }
+
+    /// The locale file for `language`, or for the language without its region.
+    fn locale_file(&self, language: &str) -> Option<(String, PathBuf)> {
+        let dir = self.dir.as_deref()?;
+        let base = language.split('-').next().unwrap_or(language);
+        [language, base]
+            .into_iter()
+            .map(|candidate| (candidate.to_string(), dir.join(format!("{candidate}.json"))))
+            .find(|(_, path)| path.is_file())
+    }
+
+    fn read_strings(path: &Path) -> HashMap<String, String> {
+        fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| serde_json::from_str(&contents).map_err(|err| err.to_string()))
+            .unwrap_or_else(|err| {
+                tracing::warn!("failed to read the locale file {}: {err}", path.display());
+                HashMap::new()
+            })
+    }
+
+    /// Switch to `language`, or to the system's with `None`. Returns the
+    /// language now shown, which is English when there is no locale file.
+    fn set_language(&self, language: Option<&str>) -> String {
+        let requested = normalize(&language.map(str::to_string).unwrap_or_else(platform::system_language));
+        let (language, strings) = match self.locale_file(&requested) {
+            Some((language, path)) => (language, Self::read_strings(&path)),
+            None => (SOURCE_LANGUAGE.to_string(), HashMap::new()),
+        };
+        tracing::info!(requested, language, "menu language set");
+        *self.current.lock().unwrap() = (language.clone(), strings);
+        language
+    }
+
+    /// Languages with a bundled locale file, English included.
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages = vec![SOURCE_LANGUAGE.to_string()];
+        if let Some(entries) = self.dir.as_deref().and_then(|dir| fs::read_dir(dir).ok()) {
+            for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    languages.extend(path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string));
+                }
+            }
+        }
+        languages.sort();
+        languages.dedup();
+        languages
+    }
+
+    fn translation(&self, text: &str) -> Option<String> {
+        self.current.lock().unwrap().1.get(text).cloned()
+    }
+}
+
+/// `text` in the menu language.
+pub fn tr(app: &AppHandle, text: &str) -> String {
+    translated(app, text).unwrap_or_else(|| text.to_string())
+}
+
+/// `text` in the menu language, if it is translated. For predefined items,
+/// whose untranslated labels are best left to the OS.
+pub fn translated(app: &AppHandle, text: &str) -> Option<String> {
+    app.try_state::<I18n>().and_then(|i18n| i18n.translation(text))
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2_foundation::NSLocale;
+
+    pub fn system_language() -> String {
+        NSLocale::preferredLanguages()
+            .firstObject()
+            .map_or_else(|| super::SOURCE_LANGUAGE.to_string(), |language| language.to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+    const LOCALE_NAME_MAX_LENGTH: usize = 85;
+
+    pub fn system_language() -> String {
+        let mut name = [0u16; LOCALE_NAME_MAX_LENGTH];
+        // The length includes the terminating null
+        let len = unsafe { GetUserDefaultLocaleName(&mut name) };
+        if len > 1 {
+            String::from_utf16_lossy(&name[..len as usize - 1])
+        } else {
+            super::SOURCE_LANGUAGE.to_string()
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use std::env;
+
+    /// The locale variables in the order POSIX gives them precedence.
+    pub fn system_language() -> String {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+            .unwrap_or_else(|| super::SOURCE_LANGUAGE.to_string())
+    }
+}
+
+/// Show the native menus in `lang`, e.g. `fr` or `pt-BR`; `None` follows the
+/// system language. Returns the language shown, English when `lang` has no
+/// translations.
+#[tauri::command]
+pub fn set_app_language(
+    app: AppHandle,
+    i18n: State<'_, I18n>,
+    settings: State<'_, Settings>,
+    lang: Option<String>,
+) -> Result<String> {
+    let lang = lang.map(|lang| normalize(lang.trim())).filter(|lang| !lang.is_empty());
+    if let Some(lang) = &lang {
+        if !lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(Error::InvalidSettings(format!("invalid language {lang:?}")));
+        }
+    }
+    settings.update(|values| values.language = lang.clone())?;
+    let shown = i18n.set_language(lang.as_deref());
+    menu::refresh(&app)?;
+    Ok(shown)
+}
+
+/// Languages the native menus can be shown in, for the language picker.
+#[tauri::command]
+pub fn get_app_languages(i18n: State<'_, I18n>) -> Vec<String> {
+    i18n.languages()
+}
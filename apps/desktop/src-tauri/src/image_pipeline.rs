@@ -0,0 +1,195 @@
+use std::io::Cursor;
+
+use image::{
+    codecs::{
+        jpeg::JpegEncoder,
+        png::{CompressionType, FilterType, PngEncoder},
+        webp::WebPEncoder,
+    },
+    imageops, DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    assets::AssetStore,
+    board,
+    error::{Error, Result},
+    settings::Settings,
+};
+
+pub const MAX_DIMENSION_RANGE: std::ops::RangeInclusive<u32> = 256..=16384;
+const JPEG_QUALITY: u8 = 85;
+/// Decoding limit, so a tiny file claiming huge dimensions can't exhaust memory.
+const MAX_DECODED_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Lossless re-encoding used for PNG and WebP sources. JPEG sources stay JPEG,
+/// since photos grow several times over when stored losslessly.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptimizedFormat {
+    Png,
+    Webp,
+}
+
+/// How pasted and dropped images are processed before they reach a board.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImageOptimization {
+    pub enabled: bool,
+    /// Longest side in pixels; larger images are downscaled to fit
+    pub max_dimension: u32,
+    pub format: OptimizedFormat,
+}
+
+impl Default for ImageOptimization {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_dimension: 2560,
+            format: OptimizedFormat::Webp,
+        }
+    }
+}
+
+/// Result of running an image through [`optimize`].
+pub struct Optimized {
+    pub bytes: Vec<u8>,
+    pub mime_type: &'static str,
+    pub width: u32,
+    pub height: u32,
+}
+
+fn decode_error(err: image::ImageError) -> Error {
+    Error::Import(format!("cannot read image: {err}"))
+}
+
+fn encode(image: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let result = match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY)),
+        ImageFormat::WebP => {
+            DynamicImage::ImageRgba8(image.to_rgba8()).write_with_encoder(WebPEncoder::new_lossless(&mut bytes))
+        }
+        _ => image.write_with_encoder(PngEncoder::new_with_quality(
+            &mut bytes,
+            CompressionType::Best,
+            FilterType::Adaptive,
+        )),
+    };
+    result.map_err(|err| Error::Import(format!("cannot encode image: {err}")))?;
+    Ok(bytes)
+}
+
+/// Downscale an image to the configured maximum, apply its EXIF orientation
+/// and re-encode it, which drops EXIF and other metadata (camera, GPS).
+/// Returns `None` for images left alone: GIFs (which may be animated), SVGs,
+/// unknown formats, or when optimization is off.
+pub fn optimize(data: &[u8], options: &ImageOptimization) -> Result<Option<Optimized>> {
+    if !options.enabled {
+        return Ok(None);
+    }
+    let mut reader = ImageReader::new(Cursor::new(data)).with_guessed_format().map_err(Error::Io)?;
+    let source_format = match reader.format() {
+        Some(format @ (ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP)) => format,
+        _ => return Ok(None),
+    };
+    let mut limits = Limits::default();
+    limits.max_alloc = Some(MAX_DECODED_BYTES);
+    reader.limits(limits);
+
+    let mut decoder = reader.into_decoder().map_err(decode_error)?;
+    let orientation = decoder.orientation().map_err(decode_error)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+    image.apply_orientation(orientation);
+
+    let resized = image.width().max(image.height()) > options.max_dimension;
+    if resized {
+        image = image.resize(options.max_dimension, options.max_dimension, imageops::FilterType::Lanczos3);
+    }
+
+    let target = match (source_format, options.format) {
+        (ImageFormat::Jpeg, _) => ImageFormat::Jpeg,
+        (_, OptimizedFormat::Png) => ImageFormat::Png,
+        (_, OptimizedFormat::Webp) => ImageFormat::WebP,
+    };
+    let bytes = encode(&image, target)?;
+    // Lossless sources that only got bigger are kept as they were
+    if !resized && target != ImageFormat::Jpeg && bytes.len() >= data.len() {
+        return Ok(None);
+    }
+    Ok(Some(Optimized {
+        bytes,
+        mime_type: target.to_mime_type(),
+        width: image.width(),
+        height: image.height(),
+    }))
+}
+
+/// An optimized image in the asset store, with the space saved.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizedImage {
+    hash: String,
+    name: String,
+    mime_type: String,
+    original_size: u64,
+    optimized_size: u64,
+}
+
+/// Rename `name` to the extension of the format it was re-encoded as.
+pub fn renamed(name: &str, mime_type: &str) -> String {
+    let extension = board::extension_for_mime(mime_type).unwrap_or("png");
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    format!("{stem}.{extension}")
+}
+
+/// Optimize an image with the user's settings and store the result (or the
+/// original, when it can't be improved) in the asset store.
+#[tauri::command]
+pub async fn optimize_image(
+    app: AppHandle,
+    data: Vec<u8>,
+    name: String,
+    mime_type: String,
+) -> Result<OptimizedImage> {
+    let options = app.state::<Settings>().get().image_optimization;
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = app.state::<AssetStore>();
+        let original_size = data.len() as u64;
+        let optimized = optimize(&data, &options).unwrap_or_else(|err| {
+            tracing::warn!(name, "image optimization failed: {err}");
+            None
+        });
+        let image = match optimized {
+            Some(optimized) => {
+                tracing::info!(
+                    name,
+                    original_size,
+                    optimized_size = optimized.bytes.len(),
+                    "image optimized to {}x{}",
+                    optimized.width,
+                    optimized.height
+                );
+                OptimizedImage {
+                    hash: store.store(&optimized.bytes)?,
+                    name: renamed(&name, optimized.mime_type),
+                    mime_type: optimized.mime_type.to_string(),
+                    original_size,
+                    optimized_size: optimized.bytes.len() as u64,
+                }
+            }
+            None => OptimizedImage {
+                hash: store.store(&data)?,
+                name,
+                mime_type,
+                original_size,
+                optimized_size: original_size,
+            },
+        };
+        Ok(image)
+    })
+    .await
+    .map_err(|err| Error::Import(err.to_string()))?
+}
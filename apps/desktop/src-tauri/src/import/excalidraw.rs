@@ -0,0 +1,274 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Deserialize;
+
+use super::{points_bounds, title_from_path};
+use crate::{
+    board,
+    document::{
+        AnchorPosition, ArrowBinding, ArrowHead, BoardDocument, DrawProps, Element, ElementKind, ElementStyle,
+        FontFamily, FontWeight, LinePoints, LineProps, Point, Rect, ShapeProps, StrokeDash, TextAlign, TextProps,
+    },
+    error::{Error, Result},
+    persist,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExcalidrawFile {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    elements: Vec<ExcalidrawElement>,
+    #[serde(default)]
+    app_state: AppState,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct AppState {
+    view_background_color: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Binding {
+    element_id: String,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ExcalidrawElement {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    angle: f64,
+    stroke_color: Option<String>,
+    background_color: Option<String>,
+    stroke_width: Option<f64>,
+    stroke_style: Option<String>,
+    roughness: Option<f64>,
+    opacity: Option<f64>,
+    is_deleted: bool,
+    locked: bool,
+    roundness: Option<serde_json::Value>,
+    /// Offsets from `(x, y)`
+    points: Vec<[f64; 2]>,
+    start_binding: Option<Binding>,
+    end_binding: Option<Binding>,
+    start_arrowhead: Option<String>,
+    end_arrowhead: Option<String>,
+    text: Option<String>,
+    original_text: Option<String>,
+    font_size: Option<f64>,
+    font_family: Option<u32>,
+    text_align: Option<String>,
+    container_id: Option<String>,
+}
+
+impl ExcalidrawElement {
+    fn rect(&self) -> Rect {
+        Rect {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    fn absolute_points(&self) -> Vec<Point> {
+        self.points
+            .iter()
+            .map(|[x, y]| Point {
+                x: self.x + x,
+                y: self.y + y,
+            })
+            .collect()
+    }
+
+    fn content(&self) -> String {
+        self.original_text
+            .clone()
+            .or_else(|| self.text.clone())
+            .unwrap_or_default()
+    }
+
+    fn style(&self) -> ElementStyle {
+        let defaults = ElementStyle::default();
+        ElementStyle {
+            stroke_color: self.stroke_color.clone().unwrap_or(defaults.stroke_color),
+            stroke_width: self.stroke_width.unwrap_or(defaults.stroke_width),
+            fill_color: self.background_color.clone().filter(|color| color != "transparent"),
+            opacity: self.opacity.map_or(1.0, |opacity| (opacity / 100.0).clamp(0.0, 1.0)),
+            stroke_dash: Some(match self.stroke_style.as_deref() {
+                Some("dashed") => StrokeDash::Dashed,
+                Some("dotted") => StrokeDash::Dotted,
+                _ => StrokeDash::Solid,
+            }),
+            roughness: self.roughness.or(defaults.roughness),
+        }
+    }
+}
+
+fn arrow_head(start: Option<&str>, end: Option<&str>) -> ArrowHead {
+    match (start.is_some(), end.is_some()) {
+        (true, true) => ArrowHead::Both,
+        (true, false) => ArrowHead::Start,
+        (false, true) => ArrowHead::End,
+        (false, false) => ArrowHead::None,
+    }
+}
+
+fn font_family(family: Option<u32>) -> FontFamily {
+    match family {
+        // Virgil and Excalifont
+        Some(1 | 5) => FontFamily::Handwritten,
+        // Cascadia and Comic Shanns
+        Some(3 | 8) => FontFamily::Code,
+        _ => FontFamily::System,
+    }
+}
+
+fn binding(binding: Option<&Binding>, imported: &HashSet<&str>) -> Option<ArrowBinding> {
+    binding
+        .filter(|binding| imported.contains(binding.element_id.as_str()))
+        .map(|binding| ArrowBinding {
+            element_id: binding.element_id.clone(),
+            anchor: AnchorPosition::Center,
+            offset: None,
+        })
+}
+
+fn convert_kind(
+    element: &ExcalidrawElement,
+    labels: &BTreeMap<&str, String>,
+    imported: &HashSet<&str>,
+) -> Option<ElementKind> {
+    let shape = || ShapeProps {
+        label: labels.get(element.id.as_str()).cloned(),
+        corner_radius: element
+            .roundness
+            .as_ref()
+            .filter(|roundness| !roundness.is_null())
+            .map(|_| (element.width.min(element.height) * 0.25).min(32.0)),
+    };
+    match element.kind.as_str() {
+        // Diamonds have no boardkit equivalent; a rectangle keeps their label and bounds
+        "rectangle" | "diamond" => Some(ElementKind::Rectangle(shape())),
+        "ellipse" => Some(ElementKind::Ellipse(ShapeProps {
+            corner_radius: None,
+            ..shape()
+        })),
+        "arrow" | "line" => {
+            let points = element.absolute_points();
+            let (first, last) = (*points.first()?, *points.last()?);
+            if element.kind == "line" && points.len() > 2 {
+                // Polylines only survive as freehand paths
+                return Some(ElementKind::Draw(DrawProps { points }));
+            }
+            let props = LineProps {
+                points: LinePoints {
+                    start: first,
+                    end: last,
+                },
+                arrow_head: (element.kind == "arrow")
+                    .then(|| arrow_head(element.start_arrowhead.as_deref(), element.end_arrowhead.as_deref())),
+                start_binding: binding(element.start_binding.as_ref(), imported),
+                end_binding: binding(element.end_binding.as_ref(), imported),
+            };
+            Some(if element.kind == "arrow" {
+                ElementKind::Arrow(props)
+            } else {
+                ElementKind::Line(props)
+            })
+        }
+        "freedraw" => Some(ElementKind::Draw(DrawProps {
+            points: element.absolute_points(),
+        })),
+        "text" => Some(ElementKind::Text(TextProps {
+            content: element.content(),
+            font_family: font_family(element.font_family),
+            font_size: element.font_size.unwrap_or(20.0),
+            text_align: match element.text_align.as_deref() {
+                Some("center") => TextAlign::Center,
+                Some("right") => TextAlign::Right,
+                _ => TextAlign::Left,
+            },
+            font_weight: FontWeight::Normal,
+        })),
+        _ => None,
+    }
+}
+
+/// Convert a parsed Excalidraw scene. Images, frames and embeds have no
+/// counterpart on the board and are dropped.
+fn convert(file: ExcalidrawFile, title: String) -> BoardDocument {
+    let live: Vec<&ExcalidrawElement> = file.elements.iter().filter(|element| !element.is_deleted).collect();
+
+    // Text bound to a shape becomes that shape's label
+    let shape_ids: HashSet<&str> = live
+        .iter()
+        .filter(|element| matches!(element.kind.as_str(), "rectangle" | "diamond" | "ellipse"))
+        .map(|element| element.id.as_str())
+        .collect();
+    let mut labels: BTreeMap<&str, String> = BTreeMap::new();
+    for element in &live {
+        if let Some(container) = element.container_id.as_deref().filter(|id| shape_ids.contains(id)) {
+            if element.kind == "text" {
+                labels.insert(container, element.content());
+            }
+        }
+    }
+    let imported: HashSet<&str> = live.iter().map(|element| element.id.as_str()).collect();
+
+    let mut document = BoardDocument::new(title, persist::now_millis());
+    for element in &live {
+        if element.kind == "text"
+            && element
+                .container_id
+                .as_deref()
+                .is_some_and(|id| labels.contains_key(id))
+        {
+            continue;
+        }
+        let Some(kind) = convert_kind(element, &labels, &imported) else {
+            continue;
+        };
+        let rect = match &kind {
+            ElementKind::Line(_) | ElementKind::Arrow(_) | ElementKind::Draw(_) => {
+                points_bounds(&element.absolute_points())
+            }
+            _ => element.rect(),
+        };
+        document.board.elements.push(Element {
+            id: element.id.clone(),
+            rect,
+            // Excalidraw paints in array order
+            z_index: document.board.elements.len() as f64,
+            style: element.style(),
+            angle: (element.angle != 0.0).then_some(element.angle),
+            locked: element.locked.then_some(true),
+            kind,
+        });
+    }
+    if let Some(color) = file.app_state.view_background_color {
+        document.board.background.color = color;
+    }
+    document
+}
+
+/// Read an .excalidraw file and convert it into a boardkit document.
+#[tauri::command]
+pub fn import_excalidraw(path: String) -> Result<BoardDocument> {
+    let bytes = board::read_board(&path)?;
+    let file: ExcalidrawFile =
+        serde_json::from_slice(&bytes).map_err(|err| Error::Import(format!("not an Excalidraw file: {err}")))?;
+    if file.kind != "excalidraw" {
+        return Err(Error::Import("not an Excalidraw file".to_string()));
+    }
+    Ok(convert(file, title_from_path(&path)))
+}
@@ -0,0 +1,815 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::{Cursor, Read},
+};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::{frame, heading, note, title_from_path};
+use crate::{
+    board,
+    document::{
+        BoardDocument, Connection, ConnectionTargetType, Element, ElementKind, ElementStyle, FontFamily, FontWeight,
+        Rect, ShapeProps, TextAlign, TextProps,
+    },
+    error::{Error, Result},
+    persist,
+};
+
+/// Magic bytes of Figma's and FigJam's binary canvas.
+const MAGICS: &[&[u8; 8]] = &[b"fig-jam.", b"fig-kiwi"];
+/// Canvas entry of the ZIP container newer files are wrapped in.
+const CANVAS_ENTRY: &str = "canvas.fig";
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const SECTION_TITLE_HEIGHT: f64 = 28.0;
+const STAMP_SIZE: f64 = 48.0;
+
+fn invalid(detail: &str) -> Error {
+    Error::Import(format!("not a FigJam file: {detail}"))
+}
+
+// ============================================================================
+// Kiwi decoding
+// ============================================================================
+
+// FigJam files are Kiwi messages (github.com/evanw/kiwi): the schema travels in
+// the file itself, so fields are looked up by name rather than hard-coded.
+
+#[derive(Clone, Copy, PartialEq)]
+enum DefinitionKind {
+    Enum,
+    Struct,
+    Message,
+}
+
+struct Field {
+    name: String,
+    /// Built-in types are negative, definitions are indexes
+    type_id: i32,
+    is_array: bool,
+    value: u32,
+}
+
+struct Definition {
+    name: String,
+    kind: DefinitionKind,
+    fields: Vec<Field>,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        let byte = *self.bytes.get(self.offset).ok_or_else(|| invalid("truncated data"))?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.offset.checked_add(len).filter(|end| *end <= self.bytes.len());
+        let end = end.ok_or_else(|| invalid("truncated data"))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn var_u64(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Ok(value)
+    }
+
+    fn var_uint(&mut self) -> Result<u32> {
+        Ok(self.var_u64()? as u32)
+    }
+
+    fn var_int(&mut self) -> Result<i32> {
+        let value = self.var_uint()?;
+        Ok(if value & 1 == 0 { (value >> 1) as i32 } else { !((value >> 1) as i32) })
+    }
+
+    fn var_i64(&mut self) -> Result<i64> {
+        let value = self.var_u64()?;
+        Ok(if value & 1 == 0 { (value >> 1) as i64 } else { !((value >> 1) as i64) })
+    }
+
+    /// Kiwi floats drop to one byte for zero; others have their exponent
+    /// rotated to the front.
+    fn var_float(&mut self) -> Result<f32> {
+        let first = self.byte()?;
+        if first == 0 {
+            return Ok(0.0);
+        }
+        let rest = self.take(3)?;
+        let bits = u32::from_le_bytes([first, rest[0], rest[1], rest[2]]);
+        Ok(f32::from_bits(bits.rotate_left(23)))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.bytes[self.offset..].iter().position(|byte| *byte == 0).ok_or_else(|| invalid("bad string"))?;
+        let text = String::from_utf8_lossy(self.take(len)?).into_owned();
+        self.offset += 1;
+        Ok(text)
+    }
+}
+
+fn decode_schema(bytes: &[u8]) -> Result<Vec<Definition>> {
+    let mut reader = Reader::new(bytes);
+    let count = reader.var_uint()?;
+    let mut definitions = Vec::new();
+    for _ in 0..count {
+        let name = reader.string()?;
+        let kind = match reader.byte()? {
+            0 => DefinitionKind::Enum,
+            1 => DefinitionKind::Struct,
+            2 => DefinitionKind::Message,
+            _ => return Err(invalid("unknown schema definition")),
+        };
+        let field_count = reader.var_uint()?;
+        let mut fields = Vec::new();
+        for _ in 0..field_count {
+            fields.push(Field {
+                name: reader.string()?,
+                type_id: reader.var_int()?,
+                is_array: reader.byte()? & 1 == 1,
+                value: reader.var_uint()?,
+            });
+        }
+        definitions.push(Definition { name, kind, fields });
+    }
+    Ok(definitions)
+}
+
+struct Decoder<'a> {
+    definitions: &'a [Definition],
+}
+
+impl Decoder<'_> {
+    fn value(&self, reader: &mut Reader, field: &Field) -> Result<Value> {
+        if !field.is_array {
+            return self.single(reader, field.type_id);
+        }
+        let len = reader.var_uint()? as usize;
+        if field.type_id == -2 {
+            return Ok(Value::from(reader.take(len)?.to_vec()));
+        }
+        let mut items = Vec::with_capacity(len.min(reader.bytes.len()));
+        for _ in 0..len {
+            items.push(self.single(reader, field.type_id)?);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn single(&self, reader: &mut Reader, type_id: i32) -> Result<Value> {
+        Ok(match type_id {
+            -1 => Value::Bool(reader.byte()? != 0),
+            -2 => Value::from(reader.byte()?),
+            -3 => Value::from(reader.var_int()?),
+            -4 => Value::from(reader.var_uint()?),
+            -5 => Value::from(f64::from(reader.var_float()?)),
+            -6 => Value::String(reader.string()?),
+            -7 => Value::from(reader.var_i64()?),
+            -8 => Value::from(reader.var_u64()?),
+            index => {
+                let definition = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| self.definitions.get(index))
+                    .ok_or_else(|| invalid("unknown schema type"))?;
+                self.definition(reader, definition)?
+            }
+        })
+    }
+
+    fn definition(&self, reader: &mut Reader, definition: &Definition) -> Result<Value> {
+        match definition.kind {
+            DefinitionKind::Enum => {
+                let value = reader.var_uint()?;
+                let name = definition.fields.iter().find(|field| field.value == value).map(|field| field.name.clone());
+                Ok(name.map_or(Value::from(value), Value::String))
+            }
+            DefinitionKind::Struct => {
+                let mut object = Map::new();
+                for field in &definition.fields {
+                    object.insert(field.name.clone(), self.value(reader, field)?);
+                }
+                Ok(Value::Object(object))
+            }
+            DefinitionKind::Message => {
+                let mut object = Map::new();
+                loop {
+                    let id = reader.var_uint()?;
+                    if id == 0 {
+                        break;
+                    }
+                    let field = definition
+                        .fields
+                        .iter()
+                        .find(|field| field.value == id)
+                        .ok_or_else(|| invalid("field missing from the schema"))?;
+                    object.insert(field.name.clone(), self.value(reader, field)?);
+                }
+                Ok(Value::Object(object))
+            }
+        }
+    }
+}
+
+/// Chunks are raw deflate; recent files switched to Zstandard.
+fn inflate(chunk: &[u8]) -> Result<Vec<u8>> {
+    if chunk.starts_with(ZSTD_MAGIC) {
+        return Err(Error::Import(
+            "this FigJam file uses Zstandard compression, which the importer does not read yet".to_string(),
+        ));
+    }
+    let mut bytes = Vec::new();
+    flate2::read::DeflateDecoder::new(chunk)
+        .read_to_end(&mut bytes)
+        .map_err(|err| invalid(&format!("corrupt chunk: {err}")))?;
+    Ok(bytes)
+}
+
+/// Unwrap the ZIP container, then decode the message with the embedded schema.
+fn decode_file(bytes: Vec<u8>) -> Result<Value> {
+    let bytes = if bytes.starts_with(b"PK") {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|err| invalid(&err.to_string()))?;
+        let mut entry = archive.by_name(CANVAS_ENTRY).map_err(|_| invalid("no canvas in the archive"))?;
+        let mut canvas = Vec::new();
+        entry.read_to_end(&mut canvas)?;
+        canvas
+    } else {
+        bytes
+    };
+    if bytes.len() < 12 || !MAGICS.iter().any(|magic| bytes.starts_with(*magic)) {
+        return Err(invalid("unrecognised header"));
+    }
+    let mut chunks = Vec::new();
+    let mut reader = Reader::new(&bytes[12..]);
+    while reader.offset < reader.bytes.len() && chunks.len() < 2 {
+        let len = reader.take(4)?;
+        let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+        chunks.push(reader.take(len)?);
+    }
+    let [schema, message, ..] = chunks[..] else {
+        return Err(invalid("missing schema or canvas"));
+    };
+    let definitions = decode_schema(&inflate(schema)?)?;
+    let root = definitions
+        .iter()
+        .find(|definition| definition.name == "Message" && definition.kind == DefinitionKind::Message)
+        .ok_or_else(|| invalid("no root message in the schema"))?;
+    let message = inflate(message)?;
+    Decoder {
+        definitions: &definitions,
+    }
+    .definition(&mut Reader::new(&message), root)
+}
+
+// ============================================================================
+// Conversion
+// ============================================================================
+
+/// Node types with no boardkit counterpart, and how many were dropped.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmappedType {
+    kind: String,
+    count: usize,
+}
+
+/// The converted board plus the node types it had to leave out.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigJamImport {
+    document: BoardDocument,
+    unmapped: Vec<UnmappedType>,
+}
+
+fn guid(value: &Value) -> Option<String> {
+    let session = value.get("sessionID")?.as_u64()?;
+    let local = value.get("localID")?.as_u64()?;
+    Some(format!("{session}:{local}"))
+}
+
+fn number(node: &Value, pointer: &str) -> Option<f64> {
+    node.pointer(pointer).and_then(Value::as_f64)
+}
+
+fn kind(node: &Value) -> &str {
+    node.get("type").and_then(Value::as_str).unwrap_or_default()
+}
+
+fn text(node: &Value) -> String {
+    node.pointer("/textData/characters").and_then(Value::as_str).unwrap_or_default().trim().to_string()
+}
+
+fn name(node: &Value) -> String {
+    node.get("name").and_then(Value::as_str).unwrap_or_default().trim().to_string()
+}
+
+/// First visible solid paint, as a hex colour.
+fn paint(node: &Value, key: &str) -> Option<String> {
+    let paints = node.get(key)?.as_array()?;
+    let color = paints
+        .iter()
+        .filter(|paint| paint.get("visible").and_then(Value::as_bool) != Some(false))
+        .find(|paint| paint.get("type").and_then(Value::as_str).is_none_or(|kind| kind == "SOLID"))?
+        .get("color")?;
+    let channel = |key: &str| {
+        let value = color.get(key).and_then(Value::as_f64).unwrap_or_default();
+        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    };
+    Some(format!("#{:02x}{:02x}{:02x}", channel("r"), channel("g"), channel("b")))
+}
+
+struct Node<'a> {
+    value: &'a Value,
+    parent: Option<String>,
+    /// Fractional index ordering siblings, back to front
+    position: String,
+}
+
+struct Converter<'a> {
+    nodes: HashMap<String, Node<'a>>,
+    children: HashMap<String, Vec<String>>,
+    document: BoardDocument,
+    imported: HashSet<String>,
+    connectors: Vec<&'a Value>,
+    unmapped: BTreeMap<String, usize>,
+}
+
+impl<'a> Converter<'a> {
+    fn new(changes: &'a [Value], title: String) -> Self {
+        let mut nodes = HashMap::new();
+        for value in changes {
+            let Some(id) = value.get("guid").and_then(guid) else { continue };
+            let parent = value.pointer("/parentIndex/guid").and_then(guid);
+            let position = value.pointer("/parentIndex/position").and_then(Value::as_str).unwrap_or_default();
+            nodes.insert(
+                id,
+                Node {
+                    value,
+                    parent,
+                    position: position.to_string(),
+                },
+            );
+        }
+        let mut children: HashMap<String, Vec<String>> = HashMap::new();
+        for (id, node) in &nodes {
+            if let Some(parent) = &node.parent {
+                children.entry(parent.clone()).or_default().push(id.clone());
+            }
+        }
+        for siblings in children.values_mut() {
+            siblings.sort_by(|a, b| nodes[a].position.cmp(&nodes[b].position).then_with(|| a.cmp(b)));
+        }
+        Self {
+            nodes,
+            children,
+            document: BoardDocument::new(title, persist::now_millis()),
+            imported: HashSet::new(),
+            connectors: Vec::new(),
+            unmapped: BTreeMap::new(),
+        }
+    }
+
+    /// The first page users can see; FigJam keeps internal canvases too.
+    fn page(&self) -> Option<String> {
+        let document = self.nodes.iter().find(|(_, node)| kind(node.value) == "DOCUMENT").map(|(id, _)| id)?;
+        self.children.get(document)?.iter().find(|id| {
+            let node = self.nodes[*id].value;
+            kind(node) == "CANVAS" && node.get("internalOnly").and_then(Value::as_bool) != Some(true)
+        })
+        .cloned()
+    }
+
+    /// Canvas bounds: transforms are relative to the parent, so offsets add up
+    /// to the page.
+    fn rect(&self, id: &str) -> Rect {
+        let node = &self.nodes[id];
+        let (mut x, mut y) = (0.0, 0.0);
+        let mut current = Some(node);
+        while let Some(ancestor) = current.filter(|ancestor| !matches!(kind(ancestor.value), "CANVAS" | "DOCUMENT")) {
+            x += number(ancestor.value, "/transform/m02").unwrap_or_default();
+            y += number(ancestor.value, "/transform/m12").unwrap_or_default();
+            current = ancestor.parent.as_ref().and_then(|parent| self.nodes.get(parent));
+        }
+        Rect {
+            x,
+            y,
+            width: number(node.value, "/size/x").unwrap_or_default(),
+            height: number(node.value, "/size/y").unwrap_or_default(),
+        }
+    }
+
+    fn angle(node: &Value) -> Option<f64> {
+        let cos = number(node, "/transform/m00")?;
+        let sin = number(node, "/transform/m10")?;
+        Some(sin.atan2(cos)).filter(|angle| angle.abs() > 1e-6)
+    }
+
+    fn push(&mut self, element: Element) {
+        self.imported.insert(element.id.clone());
+        self.document.board.elements.push(element);
+    }
+
+    fn z_index(&self) -> f64 {
+        self.document.board.elements.len() as f64
+    }
+
+    fn shape(&self, id: &str, value: &Value, ellipse: bool, label: String) -> Element {
+        let defaults = ElementStyle::default();
+        let props = ShapeProps {
+            label: Some(label).filter(|label| !label.is_empty()),
+            corner_radius: None,
+        };
+        Element {
+            id: id.to_string(),
+            rect: self.rect(id),
+            z_index: self.z_index(),
+            style: ElementStyle {
+                stroke_color: paint(value, "strokePaints").unwrap_or(defaults.stroke_color.clone()),
+                stroke_width: number(value, "/strokeWeight").unwrap_or(defaults.stroke_width),
+                fill_color: paint(value, "fillPaints"),
+                roughness: Some(0.0),
+                ..defaults
+            },
+            angle: Self::angle(value),
+            locked: None,
+            kind: if ellipse { ElementKind::Ellipse(props) } else { ElementKind::Rectangle(props) },
+        }
+    }
+
+    /// Convert a node, then its children in stacking order.
+    fn visit(&mut self, id: &str) {
+        let value = self.nodes[id].value;
+        if value.get("visible").and_then(Value::as_bool) == Some(false) {
+            return;
+        }
+        let z_index = self.z_index();
+        match kind(value) {
+            "STICKY" => {
+                let rect = self.rect(id);
+                let fill = paint(value, "fillPaints");
+                let element = note(id.to_string(), rect, &text(value), fill.as_deref(), z_index);
+                self.push(Element {
+                    angle: Self::angle(value),
+                    ..element
+                });
+            }
+            "SHAPE_WITH_TEXT" => {
+                let shape = value.get("shapeWithTextType").and_then(Value::as_str).unwrap_or_default();
+                let mut element = self.shape(id, value, shape == "ELLIPSE", text(value));
+                if let ElementKind::Rectangle(props) = &mut element.kind {
+                    props.corner_radius = (shape == "ROUNDED_RECTANGLE").then_some(12.0);
+                }
+                self.push(element);
+            }
+            "RECTANGLE" | "ROUNDED_RECTANGLE" => self.push(self.shape(id, value, false, String::new())),
+            "ELLIPSE" => self.push(self.shape(id, value, true, String::new())),
+            "STAMP" => {
+                let rect = self.rect(id);
+                let rect = Rect {
+                    width: if rect.width > 0.0 { rect.width } else { STAMP_SIZE },
+                    height: if rect.height > 0.0 { rect.height } else { STAMP_SIZE },
+                    ..rect
+                };
+                let mut element = self.shape(id, value, true, name(value));
+                element.rect = rect;
+                element.style.fill_color = element.style.fill_color.or(Some("#fde68a".to_string()));
+                self.push(element);
+            }
+            "TEXT" => {
+                let defaults = ElementStyle::default();
+                let element = Element {
+                    id: id.to_string(),
+                    rect: self.rect(id),
+                    z_index,
+                    style: ElementStyle {
+                        stroke_color: paint(value, "fillPaints").unwrap_or(defaults.stroke_color.clone()),
+                        fill_color: None,
+                        ..defaults
+                    },
+                    angle: Self::angle(value),
+                    locked: None,
+                    kind: ElementKind::Text(TextProps {
+                        content: text(value),
+                        font_family: FontFamily::System,
+                        font_size: number(value, "/fontSize").unwrap_or(16.0),
+                        font_weight: FontWeight::Normal,
+                        text_align: match value.get("textAlignHorizontal").and_then(Value::as_str) {
+                            Some("CENTER") => TextAlign::Center,
+                            Some("RIGHT") => TextAlign::Right,
+                            _ => TextAlign::Left,
+                        },
+                    }),
+                };
+                self.push(element);
+            }
+            "SECTION" | "FRAME" => {
+                let rect = self.rect(id);
+                self.push(frame(id.to_string(), rect, z_index));
+                let title = name(value);
+                if !title.is_empty() {
+                    let title_rect = Rect {
+                        y: rect.y - SECTION_TITLE_HEIGHT - 4.0,
+                        height: SECTION_TITLE_HEIGHT,
+                        ..rect
+                    };
+                    self.document.board.elements.push(heading(
+                        format!("{id}-title"),
+                        title_rect,
+                        &title,
+                        18.0,
+                        z_index + 1.0,
+                    ));
+                }
+            }
+            "CONNECTOR" => self.connectors.push(value),
+            // Pure containers: their children are converted below
+            "GROUP" | "CANVAS" | "DOCUMENT" => {}
+            other => {
+                *self.unmapped.entry(other.to_string()).or_default() += 1;
+                // Children of an unknown container are dropped with it
+                return;
+            }
+        }
+        for child in self.children.get(id).cloned().unwrap_or_default() {
+            self.visit(&child);
+        }
+    }
+
+    /// Connectors between imported nodes become connections; dangling ones
+    /// count as unmapped.
+    fn connect(&mut self) {
+        let mut connections = Vec::new();
+        for value in std::mem::take(&mut self.connectors) {
+            let end = |key: &str| {
+                value.pointer(&format!("/{key}/endpointNodeID")).and_then(guid).filter(|id| self.imported.contains(id))
+            };
+            let (Some(source_id), Some(target_id)) = (end("connectorStart"), end("connectorEnd")) else {
+                *self.unmapped.entry("CONNECTOR (unattached)".to_string()).or_default() += 1;
+                continue;
+            };
+            let label = text(value);
+            connections.push(Connection {
+                id: value.get("guid").and_then(guid).unwrap_or_default(),
+                source_id,
+                source_type: ConnectionTargetType::Element,
+                target_id,
+                target_type: ConnectionTargetType::Element,
+                label: Some(label).filter(|label| !label.is_empty()),
+                style: None,
+            });
+        }
+        self.document.board.connections = Some(connections);
+    }
+}
+
+/// Convert the first page of a decoded canvas message.
+fn convert(message: &Value, title: String) -> Result<FigJamImport> {
+    let changes = message
+        .get("nodeChanges")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("the canvas has no nodes"))?;
+    let mut converter = Converter::new(changes, title);
+    let page = converter.page().ok_or_else(|| invalid("the canvas has no pages"))?;
+    converter.visit(&page);
+    converter.connect();
+    if !converter.unmapped.is_empty() {
+        tracing::info!(unmapped = ?converter.unmapped, "FigJam nodes without a boardkit counterpart were dropped");
+    }
+    Ok(FigJamImport {
+        document: converter.document,
+        unmapped: converter.unmapped.into_iter().map(|(kind, count)| UnmappedType { kind, count }).collect(),
+    })
+}
+
+/// Read a FigJam file (`.jam`, as saved with "Save local copy") and convert
+/// its first page. Stickies, shapes, stamps, text and sections are mapped;
+/// other node types are counted so the frontend can say what was left out.
+#[tauri::command]
+pub fn import_figjam(path: String) -> Result<FigJamImport> {
+    let message = decode_file(board::read_board(&path)?)?;
+    convert(&message, title_from_path(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::FRAC_PI_2, io::Write};
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn deflate(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn var_uint(out: &mut Vec<u8>, mut value: u32) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn var_int(out: &mut Vec<u8>, value: i32) {
+        var_uint(out, if value < 0 { (!value as u32) << 1 | 1 } else { (value as u32) << 1 });
+    }
+
+    fn string(out: &mut Vec<u8>, text: &str) {
+        out.extend_from_slice(text.as_bytes());
+        out.push(0);
+    }
+
+    /// (name, type id, is array)
+    type TestField = (&'static str, i32, bool);
+
+    /// Schema: 0 `GUID` struct, 1 `NodeChange` message, 2 `Message` root.
+    fn schema() -> Vec<u8> {
+        let definitions: &[(&str, u8, &[TestField])] = &[
+            ("GUID", 1, &[("sessionID", -4, false), ("localID", -4, false)]),
+            ("NodeChange", 2, &[("guid", 0, false), ("type", -6, false), ("opacity", -5, false)]),
+            ("Message", 2, &[("nodeChanges", 1, true)]),
+        ];
+        let mut out = Vec::new();
+        var_uint(&mut out, definitions.len() as u32);
+        for (name, kind, fields) in definitions {
+            string(&mut out, name);
+            out.push(*kind);
+            var_uint(&mut out, fields.len() as u32);
+            for (value, (name, type_id, is_array)) in (1..).zip(fields.iter()) {
+                string(&mut out, name);
+                var_int(&mut out, *type_id);
+                out.push(u8::from(*is_array));
+                var_uint(&mut out, value);
+            }
+        }
+        out
+    }
+
+    fn message() -> Vec<u8> {
+        let mut out = Vec::new();
+        var_uint(&mut out, 1);
+        var_uint(&mut out, 2);
+        for (local, kind) in [(1, "DOCUMENT"), (300, "STICKY")] {
+            var_uint(&mut out, 1);
+            var_uint(&mut out, 0);
+            var_uint(&mut out, local);
+            var_uint(&mut out, 2);
+            string(&mut out, kind);
+            var_uint(&mut out, 3);
+            out.extend_from_slice(&0.5f32.to_bits().rotate_right(23).to_le_bytes());
+            var_uint(&mut out, 0);
+        }
+        var_uint(&mut out, 0);
+        out
+    }
+
+    fn canvas(schema: &[u8], message: &[u8]) -> Vec<u8> {
+        let mut out = b"fig-jam.".to_vec();
+        out.extend_from_slice(&[1, 0, 0, 0]);
+        for chunk in [schema, message] {
+            out.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            out.extend_from_slice(chunk);
+        }
+        out
+    }
+
+    #[test]
+    fn kiwi_messages_are_decoded_with_their_schema() {
+        let decoded = decode_file(canvas(&deflate(&schema()), &deflate(&message()))).unwrap();
+        let node = |local, kind| json!({ "guid": { "sessionID": 0, "localID": local }, "type": kind, "opacity": 0.5 });
+        assert_eq!(decoded, json!({ "nodeChanges": [node(1, "DOCUMENT"), node(300, "STICKY")] }));
+    }
+
+    #[test]
+    fn zipped_canvases_are_unwrapped() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file(CANVAS_ENTRY, zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(&canvas(&deflate(&schema()), &deflate(&message()))).unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+
+        let decoded = decode_file(bytes).unwrap();
+        assert_eq!(decoded["nodeChanges"].as_array().map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn unreadable_files_are_rejected() {
+        assert!(matches!(decode_file(b"not a canvas".to_vec()), Err(Error::Import(_))));
+        let zstd = [ZSTD_MAGIC, &[0; 8]].concat();
+        let Err(Error::Import(message)) = decode_file(canvas(&zstd, &zstd)) else {
+            panic!("Zstandard chunks should be refused");
+        };
+        assert!(message.contains("Zstandard"), "{message}");
+    }
+
+    fn node(session: u64, local: u64, kind: &str, parent: Option<(u64, u64)>, position: &str, extra: Value) -> Value {
+        let mut node = json!({ "guid": { "sessionID": session, "localID": local }, "type": kind });
+        if let Some((session, local)) = parent {
+            node["parentIndex"] = json!({ "guid": { "sessionID": session, "localID": local }, "position": position });
+        }
+        node.as_object_mut().unwrap().extend(extra.as_object().cloned().unwrap_or_default());
+        node
+    }
+
+    fn transform(x: f64, y: f64) -> Value {
+        json!({ "m00": 1, "m10": 0, "m02": x, "m12": y })
+    }
+
+    fn solid(r: f64, g: f64, b: f64) -> Value {
+        json!([{ "type": "SOLID", "color": { "r": r, "g": g, "b": b, "a": 1 } }])
+    }
+
+    #[test]
+    fn the_first_visible_page_is_converted() {
+        let page = Some((0, 2));
+        let changes = json!({ "nodeChanges": [
+            node(0, 0, "DOCUMENT", None, "", json!({})),
+            node(0, 1, "CANVAS", Some((0, 0)), "!", json!({ "internalOnly": true })),
+            node(0, 2, "CANVAS", Some((0, 0)), "\"", json!({})),
+            node(2, 1, "STICKY", Some((0, 1)), "a", json!({ "size": { "x": 10, "y": 10 } })),
+            node(1, 1, "SECTION", page, "a", json!({
+                "name": "Ideas", "transform": transform(100.0, 100.0), "size": { "x": 400, "y": 300 },
+            })),
+            node(1, 2, "STICKY", Some((1, 1)), "a", json!({
+                "transform": transform(10.0, 20.0), "size": { "x": 100, "y": 100 },
+                "textData": { "characters": " Hi " }, "fillPaints": solid(1.0, 0.0, 0.0),
+            })),
+            node(1, 3, "SHAPE_WITH_TEXT", page, "b", json!({
+                "shapeWithTextType": "ELLIPSE", "textData": { "characters": "Hub" },
+                "transform": { "m00": 0, "m10": 1, "m02": 600, "m12": 0 }, "size": { "x": 80, "y": 80 },
+                "strokePaints": solid(0.0, 0.0, 1.0), "strokeWeight": 3,
+            })),
+            node(1, 4, "CONNECTOR", page, "c", json!({
+                "connectorStart": { "endpointNodeID": { "sessionID": 1, "localID": 2 } },
+                "connectorEnd": { "endpointNodeID": { "sessionID": 1, "localID": 3 } },
+                "textData": { "characters": "next" },
+            })),
+            node(1, 5, "CONNECTOR", page, "d", json!({
+                "connectorStart": { "endpointNodeID": { "sessionID": 1, "localID": 2 } },
+                "connectorEnd": { "endpointNodeID": { "sessionID": 9, "localID": 9 } },
+            })),
+            node(1, 6, "WIDGET", page, "e", json!({})),
+            node(1, 7, "STICKY", Some((1, 6)), "a", json!({})),
+            node(1, 8, "STICKY", page, "f", json!({ "visible": false })),
+        ] });
+        let FigJamImport { document, unmapped } = convert(&changes, "Jam".to_string()).unwrap();
+
+        let rect = |x, y, width, height| Rect { x, y, width, height };
+        let expected = vec![
+            frame("1:1".to_string(), rect(100.0, 100.0, 400.0, 300.0), 0.0),
+            heading("1:1-title".to_string(), rect(100.0, 68.0, 400.0, SECTION_TITLE_HEIGHT), "Ideas", 18.0, 1.0),
+            note("1:2".to_string(), rect(110.0, 120.0, 100.0, 100.0), "Hi", Some("#ff0000"), 2.0),
+            Element {
+                id: "1:3".to_string(),
+                rect: rect(600.0, 0.0, 80.0, 80.0),
+                z_index: 3.0,
+                style: ElementStyle {
+                    stroke_color: "#0000ff".to_string(),
+                    stroke_width: 3.0,
+                    fill_color: None,
+                    roughness: Some(0.0),
+                    ..ElementStyle::default()
+                },
+                angle: Some(FRAC_PI_2),
+                locked: None,
+                kind: ElementKind::Ellipse(ShapeProps {
+                    label: Some("Hub".to_string()),
+                    corner_radius: None,
+                }),
+            },
+        ];
+        assert_eq!(document.board.elements, expected);
+        assert_eq!(
+            document.board.connections,
+            Some(vec![Connection {
+                id: "1:4".to_string(),
+                source_id: "1:2".to_string(),
+                source_type: ConnectionTargetType::Element,
+                target_id: "1:3".to_string(),
+                target_type: ConnectionTargetType::Element,
+                label: Some("next".to_string()),
+                style: None,
+            }])
+        );
+        let unmapped: Vec<(&str, usize)> = unmapped.iter().map(|kind| (kind.kind.as_str(), kind.count)).collect();
+        assert_eq!(unmapped, vec![("CONNECTOR (unattached)", 1), ("WIDGET", 1)]);
+    }
+}
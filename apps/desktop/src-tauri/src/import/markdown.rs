@@ -0,0 +1,246 @@
+use super::{frame, heading, note, title_from_path};
+use crate::{
+    board,
+    document::{BoardDocument, Rect},
+    error::{Error, Result},
+    persist,
+};
+
+const NOTE_WIDTH: f64 = 180.0;
+const NOTE_HEIGHT: f64 = 120.0;
+const NOTE_GAP: f64 = 20.0;
+const NOTES_PER_ROW: usize = 4;
+const FRAME_PADDING: f64 = 24.0;
+const FRAME_HEADER: f64 = 56.0;
+const FRAMES_PER_ROW: usize = 3;
+const FRAME_GAP: f64 = 60.0;
+
+/// A heading and the notes written under it.
+#[derive(Default)]
+struct Section {
+    title: Option<String>,
+    notes: Vec<String>,
+}
+
+fn strip_inline(text: &str) -> String {
+    text.replace("**", "").replace("__", "").replace('`', "").trim().to_string()
+}
+
+/// Bullet or numbered list marker at the start of a trimmed line.
+fn list_item(line: &str) -> Option<&str> {
+    if let Some(rest) = ["- ", "* ", "+ "].iter().find_map(|marker| line.strip_prefix(marker)) {
+        return Some(rest);
+    }
+    let digits = line.find(|c: char| !c.is_ascii_digit()).filter(|end| *end > 0)?;
+    line[digits..].strip_prefix(". ").or_else(|| line[digits..].strip_prefix(") "))
+}
+
+/// Split an outline into sections. Every heading starts a section (the first
+/// `#` heading names the board instead); top-level bullets and paragraphs
+/// become notes and nested bullets are appended to the note above them.
+fn parse(source: &str) -> (Option<String>, Vec<Section>) {
+    let mut title = None;
+    let mut sections = vec![Section::default()];
+    let mut in_code = false;
+    for raw in source.lines() {
+        let line = raw.trim_end();
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || line.trim().is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim_start();
+
+        if let Some(text) = trimmed.strip_prefix('#') {
+            let level = 1 + text.len() - text.trim_start_matches('#').len();
+            let text = strip_inline(text.trim_start_matches('#'));
+            if level == 1 && title.is_none() && sections.iter().all(|section| section.notes.is_empty()) {
+                title = Some(text);
+            } else {
+                sections.push(Section {
+                    title: Some(text),
+                    notes: Vec::new(),
+                });
+            }
+            continue;
+        }
+
+        let section = sections.last_mut().expect("there is always a section");
+        let item = list_item(trimmed).map(|rest| rest.trim_start_matches("[ ] ").trim_start_matches("[x] "));
+        match (item, section.notes.last_mut()) {
+            (Some(text), Some(last)) if indent >= 2 => {
+                last.push_str("\n• ");
+                last.push_str(&strip_inline(text));
+            }
+            (Some(text), _) => section.notes.push(strip_inline(text)),
+            (None, _) => section.notes.push(strip_inline(trimmed)),
+        }
+    }
+    sections.retain(|section| section.title.is_some() || !section.notes.is_empty());
+    (title, sections)
+}
+
+/// Lay sections out as frames in rows, each holding a grid of notes.
+fn layout(title: String, sections: Vec<Section>) -> BoardDocument {
+    let mut document = BoardDocument::new(title, persist::now_millis());
+    let elements = &mut document.board.elements;
+    let mut next_id = 0;
+    let mut id = || {
+        next_id += 1;
+        format!("md-{next_id}")
+    };
+
+    let (mut x, mut y, mut row_height) = (0.0, 0.0, 0.0_f64);
+    for (index, section) in sections.iter().enumerate() {
+        if index > 0 && index % FRAMES_PER_ROW == 0 {
+            x = 0.0;
+            y += row_height + FRAME_GAP;
+            row_height = 0.0;
+        }
+        let columns = section.notes.len().clamp(1, NOTES_PER_ROW);
+        let rows = section.notes.len().div_ceil(NOTES_PER_ROW).max(1);
+        let width = FRAME_PADDING * 2.0 + columns as f64 * NOTE_WIDTH + (columns - 1) as f64 * NOTE_GAP;
+        let height = FRAME_HEADER + FRAME_PADDING + rows as f64 * NOTE_HEIGHT + (rows - 1) as f64 * NOTE_GAP;
+
+        if let Some(section_title) = &section.title {
+            let z_index = elements.len() as f64;
+            elements.push(frame(id(), Rect { x, y, width, height }, z_index));
+            let rect = Rect {
+                x: x + FRAME_PADDING,
+                y: y + 16.0,
+                width: width - FRAME_PADDING * 2.0,
+                height: 30.0,
+            };
+            let z_index = elements.len() as f64;
+            elements.push(heading(id(), rect, section_title, 22.0, z_index));
+        }
+        for (position, text) in section.notes.iter().enumerate() {
+            let (column, row) = (position % NOTES_PER_ROW, position / NOTES_PER_ROW);
+            let rect = Rect {
+                x: x + FRAME_PADDING + column as f64 * (NOTE_WIDTH + NOTE_GAP),
+                y: y + FRAME_HEADER + row as f64 * (NOTE_HEIGHT + NOTE_GAP),
+                width: NOTE_WIDTH,
+                height: NOTE_HEIGHT,
+            };
+            let z_index = elements.len() as f64;
+            elements.push(note(id(), rect, text, None, z_index));
+        }
+        x += width + FRAME_GAP;
+        row_height = row_height.max(height);
+    }
+    document
+}
+
+/// Turn a Markdown outline (an agenda, meeting notes) into a board: headings
+/// become frames and list items become sticky notes laid out in a grid.
+#[tauri::command]
+pub fn import_markdown(path: String) -> Result<BoardDocument> {
+    let bytes = board::read_board(&path)?;
+    let source = String::from_utf8(bytes).map_err(|_| Error::Import("not a UTF-8 text file".to_string()))?;
+    let (title, sections) = parse(&source);
+    if sections.is_empty() {
+        return Err(Error::Import("the file has no headings or list items".to_string()));
+    }
+    Ok(layout(title.unwrap_or_else(|| title_from_path(&path)), sections))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(title: Option<&str>, notes: &[&str]) -> Section {
+        Section {
+            title: title.map(str::to_string),
+            notes: notes.iter().map(|note| note.to_string()).collect(),
+        }
+    }
+
+    /// (heading, notes)
+    type Parsed = (Option<String>, Vec<String>);
+
+    fn parsed(source: &str) -> (Option<String>, Vec<Parsed>) {
+        let (title, sections) = parse(source);
+        (title, sections.into_iter().map(|section| (section.title, section.notes)).collect())
+    }
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    #[test]
+    fn headings_start_sections_of_notes() {
+        let source = "# Retro\n\
+            Intro paragraph\n\
+            \n\
+            ## Went well\n\
+            - **Shipped** v2\n  \
+              - on time\n\
+            1. Fixed `bug`\n\
+            ```\n\
+            - not a note\n\
+            ```\n\
+            ### Improve\n\
+            * [ ] Tests\n\
+            2) [x] Docs\n\
+            ## Empty\n";
+        let strings = |items: &[&str]| items.iter().map(|item| item.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            parsed(source),
+            (
+                Some("Retro".to_string()),
+                vec![
+                    (None, strings(&["Intro paragraph"])),
+                    (Some("Went well".to_string()), strings(&["Shipped v2\n• on time", "Fixed bug"])),
+                    (Some("Improve".to_string()), strings(&["Tests", "Docs"])),
+                    (Some("Empty".to_string()), Vec::new()),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn a_heading_after_notes_is_a_section() {
+        let (title, sections) = parsed("- first\n# Later\n- second\n");
+        assert_eq!(title, None);
+        assert_eq!(sections[1].0.as_deref(), Some("Later"));
+    }
+
+    #[test]
+    fn sections_are_laid_out_as_frames_in_rows() {
+        let sections = vec![
+            section(None, &["loose"]),
+            section(Some("Ideas"), &["1", "2", "3", "4", "5"]),
+            section(Some("Two"), &["a"]),
+            section(Some("Wrapped"), &[]),
+        ];
+        let document = layout("Board".to_string(), sections);
+        let placed: Vec<(&str, Rect)> =
+            document.board.elements.iter().map(|element| (element.id.as_str(), element.rect)).collect();
+
+        let note = |x, y| rect(x, y, NOTE_WIDTH, NOTE_HEIGHT);
+        assert_eq!(
+            placed,
+            vec![
+                // No frame for notes above the first heading
+                ("md-1", note(24.0, 56.0)),
+                // Four notes a row, two rows
+                ("md-2", rect(288.0, 0.0, 828.0, 340.0)),
+                ("md-3", rect(312.0, 16.0, 780.0, 30.0)),
+                ("md-4", note(312.0, 56.0)),
+                ("md-5", note(512.0, 56.0)),
+                ("md-6", note(712.0, 56.0)),
+                ("md-7", note(912.0, 56.0)),
+                ("md-8", note(312.0, 196.0)),
+                ("md-9", rect(1176.0, 0.0, 228.0, 200.0)),
+                ("md-10", rect(1200.0, 16.0, 180.0, 30.0)),
+                ("md-11", note(1200.0, 56.0)),
+                // A fourth frame starts the next row, under the tallest one
+                ("md-12", rect(0.0, 400.0, 228.0, 200.0)),
+                ("md-13", rect(24.0, 416.0, 180.0, 30.0)),
+            ]
+        );
+    }
+}
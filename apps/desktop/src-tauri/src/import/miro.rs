@@ -0,0 +1,571 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{frame, heading, note, title_from_path};
+use crate::{
+    board,
+    document::{
+        BoardDocument, Connection, ConnectionTargetType, Element, ElementKind, ElementStyle, FontFamily, FontWeight,
+        Rect, ShapeProps, StrokeDash, TextAlign, TextProps,
+    },
+    error::{Error, Result},
+    persist,
+};
+
+/// Miro's default sticky note size, for exports that only give a width.
+const DEFAULT_STICKY_SIZE: f64 = 199.0;
+const FRAME_TITLE_HEIGHT: f64 = 28.0;
+
+/// Named sticky note colours of the REST API.
+const STICKY_COLORS: &[(&str, &str)] = &[
+    ("gray", "#f5f6f8"),
+    ("light_yellow", "#fff9b1"),
+    ("yellow", "#f5d128"),
+    ("orange", "#ff9d48"),
+    ("light_green", "#d5f692"),
+    ("green", "#c9df56"),
+    ("dark_green", "#93d275"),
+    ("cyan", "#67c6c0"),
+    ("light_pink", "#ffcee0"),
+    ("pink", "#ea94bb"),
+    ("violet", "#be88c7"),
+    ("red", "#f16c7f"),
+    ("light_blue", "#a6ccf5"),
+    ("blue", "#6cd8fa"),
+    ("dark_blue", "#9ea9ff"),
+    ("black", "#1a1a1a"),
+];
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Position {
+    x: f64,
+    y: f64,
+    relative_to: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Geometry {
+    width: Option<f64>,
+    height: Option<f64>,
+    rotation: Option<f64>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ItemData {
+    content: Option<String>,
+    title: Option<String>,
+    description: Option<String>,
+    shape: Option<String>,
+}
+
+/// REST API styles hold numbers as strings; the v1 export uses numbers.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ItemStyle {
+    fill_color: Option<String>,
+    background_color: Option<String>,
+    fill_opacity: Option<Value>,
+    border_color: Option<String>,
+    border_width: Option<Value>,
+    border_style: Option<String>,
+    color: Option<String>,
+    text_color: Option<String>,
+    font_size: Option<Value>,
+    text_align: Option<String>,
+    shape_type: Option<Value>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Reference {
+    id: Value,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Caption {
+    content: String,
+}
+
+/// One board item, from the REST API (v2) or a v1 `widgets` export. Both put
+/// positions at the item's centre.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct MiroItem {
+    id: Value,
+    #[serde(rename = "type")]
+    kind: String,
+    data: ItemData,
+    style: ItemStyle,
+    position: Option<Position>,
+    geometry: Geometry,
+    parent: Option<Reference>,
+    start_item: Option<Reference>,
+    end_item: Option<Reference>,
+    captions: Option<Vec<Caption>>,
+    // v1
+    x: Option<f64>,
+    y: Option<f64>,
+    width: Option<f64>,
+    height: Option<f64>,
+    rotation: Option<f64>,
+    scale: Option<f64>,
+    text: Option<String>,
+    title: Option<String>,
+    start_widget: Option<Reference>,
+    end_widget: Option<Reference>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Sticky,
+    Card,
+    Shape,
+    Text,
+    Frame,
+    Connector,
+}
+
+fn id_string(id: &Value) -> Option<String> {
+    match id {
+        Value::String(id) if !id.is_empty() => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+fn number(value: &Option<Value>) -> Option<f64> {
+    match value.as_ref()? {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Text of Miro's rich-text HTML: paragraphs and breaks become line breaks.
+fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        if tag.starts_with("br") || tag == "/p" || tag == "/li" || tag == "/div" {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&");
+    text.trim().to_string()
+}
+
+fn color(value: Option<&str>) -> Option<String> {
+    let value = value?.trim();
+    if value.is_empty() || value == "transparent" {
+        return None;
+    }
+    if value.starts_with('#') {
+        return Some(value.to_string());
+    }
+    STICKY_COLORS.iter().find(|(name, _)| *name == value).map(|(_, hex)| hex.to_string())
+}
+
+impl MiroItem {
+    fn kind(&self) -> Option<Kind> {
+        Some(match self.kind.to_ascii_lowercase().as_str() {
+            "sticky_note" | "sticker" => Kind::Sticky,
+            "card" | "app_card" => Kind::Card,
+            "shape" => Kind::Shape,
+            "text" => Kind::Text,
+            "frame" => Kind::Frame,
+            "connector" | "line" => Kind::Connector,
+            _ => return None,
+        })
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        id_string(&self.parent.as_ref()?.id)
+    }
+
+    /// Bounds on the canvas, given the bounds of frames this item may be
+    /// positioned relative to.
+    fn rect(&self, frames: &HashMap<String, Rect>) -> Rect {
+        let (mut x, mut y) = match &self.position {
+            Some(position) => (position.x, position.y),
+            None => (self.x.unwrap_or_default(), self.y.unwrap_or_default()),
+        };
+        let scale = self.scale.unwrap_or(1.0);
+        let width = self.geometry.width.or(self.width).map(|width| width * scale);
+        let height = self.geometry.height.or(self.height).map(|height| height * scale);
+        let (width, height) = match (width, height) {
+            (Some(width), Some(height)) => (width, height),
+            (Some(size), None) | (None, Some(size)) => (size, size),
+            (None, None) => (DEFAULT_STICKY_SIZE, DEFAULT_STICKY_SIZE),
+        };
+        let relative = self.position.as_ref().and_then(|position| position.relative_to.as_deref());
+        if relative == Some("parent_top_left") {
+            if let Some(parent) = self.parent_id().and_then(|id| frames.get(&id)) {
+                x += parent.x;
+                y += parent.y;
+            }
+        }
+        Rect {
+            x: x - width / 2.0,
+            y: y - height / 2.0,
+            width,
+            height,
+        }
+    }
+
+    fn angle(&self) -> Option<f64> {
+        self.geometry.rotation.or(self.rotation).filter(|degrees| *degrees != 0.0).map(f64::to_radians)
+    }
+
+    fn content(&self) -> String {
+        let html = self.data.content.as_deref().or(self.text.as_deref()).unwrap_or_default();
+        plain_text(html)
+    }
+
+    fn title(&self) -> String {
+        plain_text(self.data.title.as_deref().or(self.title.as_deref()).unwrap_or_default())
+    }
+
+    fn fill(&self) -> Option<String> {
+        color(self.style.fill_color.as_deref().or(self.style.background_color.as_deref()))
+    }
+
+    fn shape_style(&self) -> ElementStyle {
+        let defaults = ElementStyle::default();
+        ElementStyle {
+            stroke_color: color(self.style.border_color.as_deref()).unwrap_or(defaults.stroke_color),
+            stroke_width: number(&self.style.border_width).unwrap_or(defaults.stroke_width),
+            fill_color: self.fill(),
+            opacity: number(&self.style.fill_opacity).map_or(1.0, |opacity| opacity.clamp(0.0, 1.0)),
+            stroke_dash: Some(match self.style.border_style.as_deref() {
+                Some("dashed") => StrokeDash::Dashed,
+                Some("dotted") => StrokeDash::Dotted,
+                _ => StrokeDash::Solid,
+            }),
+            roughness: Some(0.0),
+        }
+    }
+
+    fn shape_kind(&self) -> ElementKind {
+        let shape = self.data.shape.clone().or_else(|| self.style.shape_type.as_ref().and_then(id_string));
+        let label = Some(self.content()).filter(|label| !label.is_empty());
+        match shape.as_deref() {
+            Some("circle" | "oval" | "ellipse") => ElementKind::Ellipse(ShapeProps {
+                label,
+                corner_radius: None,
+            }),
+            Some("round_rectangle") => ElementKind::Rectangle(ShapeProps {
+                label,
+                corner_radius: Some(12.0),
+            }),
+            // Triangles, stars, callouts... keep their label and bounds
+            _ => ElementKind::Rectangle(ShapeProps {
+                label,
+                corner_radius: None,
+            }),
+        }
+    }
+
+    fn text_element(&self, id: String, rect: Rect, z_index: f64) -> Element {
+        let defaults = ElementStyle::default();
+        Element {
+            id,
+            rect,
+            z_index,
+            style: ElementStyle {
+                stroke_color: color(self.style.color.as_deref().or(self.style.text_color.as_deref()))
+                    .unwrap_or(defaults.stroke_color.clone()),
+                fill_color: None,
+                ..defaults
+            },
+            angle: self.angle(),
+            locked: None,
+            kind: ElementKind::Text(TextProps {
+                content: self.content(),
+                font_family: FontFamily::System,
+                font_size: number(&self.style.font_size).unwrap_or(14.0),
+                font_weight: FontWeight::Normal,
+                text_align: match self.style.text_align.as_deref() {
+                    Some("center") => TextAlign::Center,
+                    Some("right") => TextAlign::Right,
+                    _ => TextAlign::Left,
+                },
+            }),
+        }
+    }
+}
+
+/// The item list of a backup: a bare array, or under `data`, `items` or
+/// `widgets`.
+fn items(file: Value) -> Option<Vec<MiroItem>> {
+    let list = match file {
+        Value::Array(list) => list,
+        Value::Object(mut fields) => match ["data", "items", "widgets"].iter().find_map(|key| fields.remove(*key)) {
+            Some(Value::Array(list)) => list,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(list.into_iter().filter_map(|item| serde_json::from_value(item).ok()).collect())
+}
+
+/// Convert Miro items: sticky notes and cards become notes, shapes and text
+/// keep their look, frames become titled outlines and connectors between
+/// imported items become connections. Images, embeds and documents are
+/// dropped.
+fn convert(items: Vec<MiroItem>, title: String) -> BoardDocument {
+    let mut document = BoardDocument::new(title, persist::now_millis());
+    let classified: Vec<(Kind, String, &MiroItem)> = items
+        .iter()
+        .filter_map(|item| Some((item.kind()?, id_string(&item.id)?, item)))
+        .collect();
+    let skipped: BTreeMap<&str, usize> = items.iter().filter(|item| item.kind().is_none()).fold(
+        BTreeMap::new(),
+        |mut skipped, item| {
+            *skipped.entry(item.kind.as_str()).or_default() += 1;
+            skipped
+        },
+    );
+    if !skipped.is_empty() {
+        tracing::info!(?skipped, "Miro items without a boardkit counterpart were dropped");
+    }
+
+    // Frames first: children may be positioned relative to them, and they sit below
+    let frames: HashMap<String, Rect> = classified
+        .iter()
+        .filter(|(kind, ..)| *kind == Kind::Frame)
+        .map(|(_, id, item)| (id.clone(), item.rect(&HashMap::new())))
+        .collect();
+    for (_, id, item) in classified.iter().filter(|(kind, ..)| *kind == Kind::Frame) {
+        let rect = frames[id];
+        let z_index = document.board.elements.len() as f64;
+        document.board.elements.push(frame(id.clone(), rect, z_index));
+        let title = item.title();
+        if !title.is_empty() {
+            let title_rect = Rect {
+                y: rect.y - FRAME_TITLE_HEIGHT - 4.0,
+                height: FRAME_TITLE_HEIGHT,
+                ..rect
+            };
+            document.board.elements.push(heading(format!("{id}-title"), title_rect, &title, 18.0, z_index + 1.0));
+        }
+    }
+
+    for (kind, id, item) in &classified {
+        let rect = item.rect(&frames);
+        let z_index = document.board.elements.len() as f64;
+        let element = match kind {
+            Kind::Sticky => {
+                let fill = item.fill().or_else(|| color(Some("light_yellow")));
+                Element {
+                    angle: item.angle(),
+                    ..note(id.clone(), rect, &item.content(), fill.as_deref(), z_index)
+                }
+            }
+            Kind::Card => {
+                let description = item.data.description.as_deref().map(plain_text).unwrap_or_default();
+                let label = [item.title(), description].into_iter().filter(|part| !part.is_empty()).collect::<Vec<_>>();
+                Element {
+                    angle: item.angle(),
+                    ..note(id.clone(), rect, &label.join("\n"), Some("#ffffff"), z_index)
+                }
+            }
+            Kind::Shape => Element {
+                id: id.clone(),
+                rect,
+                z_index,
+                style: item.shape_style(),
+                angle: item.angle(),
+                locked: None,
+                kind: item.shape_kind(),
+            },
+            Kind::Text => item.text_element(id.clone(), rect, z_index),
+            Kind::Frame | Kind::Connector => continue,
+        };
+        document.board.elements.push(element);
+    }
+
+    let imported: HashSet<&str> = document.board.elements.iter().map(|element| element.id.as_str()).collect();
+    let end = |reference: Option<&Reference>| {
+        reference.and_then(|reference| id_string(&reference.id)).filter(|id| imported.contains(id.as_str()))
+    };
+    let connections: Vec<Connection> = classified
+        .iter()
+        .filter(|(kind, ..)| *kind == Kind::Connector)
+        .filter_map(|(_, id, item)| {
+            let source_id = end(item.start_item.as_ref().or(item.start_widget.as_ref()))?;
+            let target_id = end(item.end_item.as_ref().or(item.end_widget.as_ref()))?;
+            let captions: Vec<String> =
+                item.captions.iter().flatten().map(|caption| plain_text(&caption.content)).collect();
+            Some(Connection {
+                id: id.clone(),
+                source_id,
+                source_type: ConnectionTargetType::Element,
+                target_id,
+                target_type: ConnectionTargetType::Element,
+                label: Some(captions.join(" ")).filter(|label| !label.trim().is_empty()),
+                style: None,
+            })
+        })
+        .collect();
+    document.board.connections = Some(connections);
+    document
+}
+
+/// Read a Miro board backup (the JSON item list of the REST API, or an older
+/// `widgets` export) and convert it into a boardkit document.
+#[tauri::command]
+pub fn import_miro(path: String) -> Result<BoardDocument> {
+    let bytes = board::read_board(&path)?;
+    let file: Value =
+        serde_json::from_slice(&bytes).map_err(|err| Error::Import(format!("not a Miro export: {err}")))?;
+    let items = items(file).ok_or_else(|| Error::Import("not a Miro export: no board items".to_string()))?;
+    if items.is_empty() {
+        return Err(Error::Import("the Miro export has no items".to_string()));
+    }
+    Ok(convert(items, title_from_path(&path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    #[test]
+    fn rest_api_items_are_converted() {
+        let file = json!({ "data": [
+            {
+                "id": "1", "type": "frame", "data": { "title": "Sprint" },
+                "position": { "x": 500, "y": 500 }, "geometry": { "width": 400, "height": 300 },
+            },
+            {
+                "id": "2", "type": "sticky_note", "data": { "content": "<p>Ship &amp; tell</p>" },
+                "style": { "fillColor": "light_pink" }, "parent": { "id": "1" },
+                "position": { "x": 50, "y": 50, "relativeTo": "parent_top_left" },
+                "geometry": { "width": 100, "height": 100 },
+            },
+            {
+                "id": "3", "type": "shape", "data": { "content": "<p>Hub</p>", "shape": "circle" },
+                "style": {
+                    "borderColor": "#ff0000", "borderWidth": "4", "borderStyle": "dashed",
+                    "fillColor": "#00ff00", "fillOpacity": "0.5",
+                },
+                "position": { "x": 1000, "y": 0 }, "geometry": { "width": 100, "height": 50 },
+            },
+            {
+                "id": "4", "type": "text", "data": { "content": "Note<br>Two" },
+                "style": { "color": "#123456", "fontSize": "24", "textAlign": "center" },
+                "position": { "x": 0, "y": 0 }, "geometry": { "width": 200, "height": 40 },
+            },
+            {
+                "id": "5", "type": "connector", "startItem": { "id": "2" }, "endItem": { "id": "3" },
+                "captions": [{ "content": "<p>next</p>" }],
+            },
+            { "id": "6", "type": "connector", "startItem": { "id": "2" }, "endItem": { "id": "99" } },
+            { "id": "7", "type": "image", "position": { "x": 0, "y": 0 } },
+        ] });
+        let document = convert(items(file).unwrap(), "Miro".to_string());
+
+        let expected = vec![
+            frame("1".to_string(), rect(300.0, 350.0, 400.0, 300.0), 0.0),
+            heading("1-title".to_string(), rect(300.0, 318.0, 400.0, FRAME_TITLE_HEIGHT), "Sprint", 18.0, 1.0),
+            note("2".to_string(), rect(300.0, 350.0, 100.0, 100.0), "Ship & tell", Some("#ffcee0"), 2.0),
+            Element {
+                id: "3".to_string(),
+                rect: rect(950.0, -25.0, 100.0, 50.0),
+                z_index: 3.0,
+                style: ElementStyle {
+                    stroke_color: "#ff0000".to_string(),
+                    stroke_width: 4.0,
+                    fill_color: Some("#00ff00".to_string()),
+                    opacity: 0.5,
+                    stroke_dash: Some(StrokeDash::Dashed),
+                    roughness: Some(0.0),
+                },
+                angle: None,
+                locked: None,
+                kind: ElementKind::Ellipse(ShapeProps {
+                    label: Some("Hub".to_string()),
+                    corner_radius: None,
+                }),
+            },
+            Element {
+                id: "4".to_string(),
+                rect: rect(-100.0, -20.0, 200.0, 40.0),
+                z_index: 4.0,
+                style: ElementStyle {
+                    stroke_color: "#123456".to_string(),
+                    ..ElementStyle::default()
+                },
+                angle: None,
+                locked: None,
+                kind: ElementKind::Text(TextProps {
+                    content: "Note\nTwo".to_string(),
+                    font_family: FontFamily::System,
+                    font_size: 24.0,
+                    font_weight: FontWeight::Normal,
+                    text_align: TextAlign::Center,
+                }),
+            },
+        ];
+        assert_eq!(document.board.elements, expected);
+        assert_eq!(
+            document.board.connections,
+            Some(vec![Connection {
+                id: "5".to_string(),
+                source_id: "2".to_string(),
+                source_type: ConnectionTargetType::Element,
+                target_id: "3".to_string(),
+                target_type: ConnectionTargetType::Element,
+                label: Some("next".to_string()),
+                style: None,
+            }])
+        );
+    }
+
+    #[test]
+    fn v1_widgets_are_converted() {
+        let file = json!({ "widgets": [{
+            "id": 42, "type": "sticker", "text": "Hi", "style": { "backgroundColor": "#ff9d48" },
+            "x": 0, "y": 0, "width": 100, "scale": 2, "rotation": 90,
+        }] });
+        let document = convert(items(file).unwrap(), "Miro".to_string());
+
+        let expected = Element {
+            angle: Some(90f64.to_radians()),
+            ..note("42".to_string(), rect(-100.0, -100.0, 200.0, 200.0), "Hi", Some("#ff9d48"), 0.0)
+        };
+        assert_eq!(document.board.elements, vec![expected]);
+    }
+
+    #[test]
+    fn files_without_items_are_not_miro_exports() {
+        assert!(items(json!({ "elements": [] })).is_none());
+        assert!(items(json!("board")).is_none());
+    }
+}
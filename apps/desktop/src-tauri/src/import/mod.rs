@@ -0,0 +1,113 @@
+//! Converters from other whiteboard formats into the boardkit document model.
+
+pub mod excalidraw;
+pub mod figjam;
+pub mod markdown;
+pub mod miro;
+pub mod opml;
+pub mod pdf;
+pub mod tabular;
+pub mod tldraw;
+
+use std::path::Path;
+
+use crate::document::{
+    Element, ElementKind, ElementStyle, FontFamily, FontWeight, Point, Rect, ShapeProps, StrokeDash, TextAlign,
+    TextProps,
+};
+
+/// Sticky-note colour for generated cards.
+const NOTE_FILL: &str = "#fef08a";
+const NOTE_INK: &str = "#27272a";
+
+/// Board title derived from the imported file's name.
+fn title_from_path(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or("Imported board")
+        .to_string()
+}
+
+/// Smallest rect containing every point.
+fn points_bounds(points: &[Point]) -> Rect {
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+    for point in points {
+        min_x = min_x.min(point.x);
+        min_y = min_y.min(point.y);
+        max_x = max_x.max(point.x);
+        max_y = max_y.max(point.y);
+    }
+    if points.is_empty() {
+        return Rect::default();
+    }
+    Rect {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x,
+        height: max_y - min_y,
+    }
+}
+
+/// Card with its text as the shape label, like a sticky note.
+fn note(id: String, rect: Rect, label: &str, fill: Option<&str>, z_index: f64) -> Element {
+    Element {
+        id,
+        rect,
+        z_index,
+        style: ElementStyle {
+            stroke_color: NOTE_INK.to_string(),
+            stroke_width: 1.0,
+            fill_color: Some(fill.unwrap_or(NOTE_FILL).to_string()),
+            roughness: Some(0.0),
+            ..ElementStyle::default()
+        },
+        angle: None,
+        locked: None,
+        kind: ElementKind::Rectangle(ShapeProps {
+            label: Some(label.to_string()),
+            corner_radius: Some(6.0),
+        }),
+    }
+}
+
+/// Dashed outline grouping cards under a heading.
+fn frame(id: String, rect: Rect, z_index: f64) -> Element {
+    Element {
+        id,
+        rect,
+        z_index,
+        style: ElementStyle {
+            stroke_color: "#a1a1aa".to_string(),
+            stroke_width: 1.0,
+            stroke_dash: Some(StrokeDash::Dashed),
+            roughness: Some(0.0),
+            ..ElementStyle::default()
+        },
+        angle: None,
+        locked: None,
+        kind: ElementKind::Rectangle(ShapeProps {
+            label: None,
+            corner_radius: Some(12.0),
+        }),
+    }
+}
+
+fn heading(id: String, rect: Rect, content: &str, font_size: f64, z_index: f64) -> Element {
+    Element {
+        id,
+        rect,
+        z_index,
+        style: ElementStyle::default(),
+        angle: None,
+        locked: None,
+        kind: ElementKind::Text(TextProps {
+            content: content.to_string(),
+            font_family: FontFamily::System,
+            font_size,
+            font_weight: FontWeight::Bold,
+            text_align: TextAlign::Left,
+        }),
+    }
+}
@@ -0,0 +1,261 @@
+use std::f64::consts::TAU;
+
+use super::{note, title_from_path};
+use crate::{
+    board,
+    document::{BoardDocument, Connection, ConnectionTargetType, Element, Rect},
+    error::{Error, Result},
+    persist,
+};
+
+const NODE_WIDTH: f64 = 180.0;
+const NODE_HEIGHT: f64 = 72.0;
+const ROOT_WIDTH: f64 = 240.0;
+const ROOT_HEIGHT: f64 = 96.0;
+/// Distance between rings; widened when the outer ring would be too crowded.
+const RING_GAP: f64 = 280.0;
+/// Arc length each leaf needs on its ring.
+const LEAF_ARC: f64 = 200.0;
+const ROOT_FILL: &str = "#e4e4e7";
+/// Each first-level branch and everything under it shares a colour.
+const BRANCH_FILLS: &[&str] = &["#fef08a", "#bbf7d0", "#bfdbfe", "#fecaca", "#e9d5ff", "#fed7aa"];
+
+/// One `<outline>` and its children.
+struct Node {
+    text: String,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn leaves(&self) -> usize {
+        self.children.iter().map(Node::leaves).sum::<usize>().max(1)
+    }
+
+    fn depth(&self) -> usize {
+        1 + self.children.iter().map(Node::depth).max().unwrap_or(0)
+    }
+}
+
+fn parse_outline(outline: roxmltree::Node) -> Node {
+    let text = outline.attribute("text").or_else(|| outline.attribute("title")).unwrap_or_default().trim();
+    // OmniOutliner and others keep a note per row in `_note`
+    let text = match outline.attribute("_note").map(str::trim).filter(|note| !note.is_empty()) {
+        Some(note) => format!("{text}\n{note}"),
+        None => text.to_string(),
+    };
+    Node {
+        text,
+        children: outlines(outline),
+    }
+}
+
+fn outlines(parent: roxmltree::Node) -> Vec<Node> {
+    parent.children().filter(|child| child.has_tag_name("outline")).map(parse_outline).collect()
+}
+
+/// The outline tree under a single root. Several top-level outlines hang off
+/// a root named after the document.
+fn parse(source: &str, fallback_title: String) -> Result<(String, Node)> {
+    let document = roxmltree::Document::parse(source).map_err(|err| Error::Import(format!("invalid OPML: {err}")))?;
+    let opml = document.root_element();
+    if !opml.has_tag_name("opml") {
+        return Err(Error::Import("not an OPML file".to_string()));
+    }
+    let title = opml
+        .children()
+        .find(|child| child.has_tag_name("head"))
+        .and_then(|head| head.children().find(|child| child.has_tag_name("title")))
+        .and_then(|title| title.text())
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .map(str::to_string);
+    let body = opml
+        .children()
+        .find(|child| child.has_tag_name("body"))
+        .ok_or_else(|| Error::Import("the OPML file has no body".to_string()))?;
+    let mut top = outlines(body);
+    let root = match top.len() {
+        0 => return Err(Error::Import("the OPML file has no outlines".to_string())),
+        1 => top.remove(0),
+        _ => Node {
+            text: title.clone().unwrap_or_else(|| fallback_title.clone()),
+            children: top,
+        },
+    };
+    let title = title.unwrap_or_else(|| if root.text.is_empty() { fallback_title } else { root.text.clone() });
+    Ok((title, root))
+}
+
+struct Layout {
+    elements: Vec<Element>,
+    connections: Vec<Connection>,
+    ring: f64,
+}
+
+impl Layout {
+    /// Place `node` in the middle of its wedge, then split the wedge among its
+    /// children by how many leaves each one has.
+    fn place(&mut self, node: &Node, depth: usize, start: f64, span: f64, fill: Option<&'static str>) -> String {
+        let id = format!("opml-{}", self.elements.len() + 1);
+        let (width, height) = if depth == 0 { (ROOT_WIDTH, ROOT_HEIGHT) } else { (NODE_WIDTH, NODE_HEIGHT) };
+        let angle = start + span / 2.0;
+        let radius = depth as f64 * self.ring;
+        let rect = Rect {
+            x: radius * angle.cos() - width / 2.0,
+            y: radius * angle.sin() - height / 2.0,
+            width,
+            height,
+        };
+        let z_index = self.elements.len() as f64;
+        self.elements.push(note(id.clone(), rect, &node.text, Some(fill.unwrap_or(ROOT_FILL)), z_index));
+
+        let total = node.leaves() as f64;
+        let mut child_start = start;
+        for (index, child) in node.children.iter().enumerate() {
+            let child_span = span * child.leaves() as f64 / total;
+            let child_fill = fill.or(Some(BRANCH_FILLS[index % BRANCH_FILLS.len()]));
+            let child_id = self.place(child, depth + 1, child_start, child_span, child_fill);
+            self.connections.push(Connection {
+                id: format!("opml-link-{}", self.connections.len() + 1),
+                source_id: id.clone(),
+                source_type: ConnectionTargetType::Element,
+                target_id: child_id,
+                target_type: ConnectionTargetType::Element,
+                label: None,
+                style: None,
+            });
+            child_start += child_span;
+        }
+        id
+    }
+}
+
+/// Lay the tree out radially: the root in the middle, each level on a ring
+/// further out, every subtree in its own wedge so branches never cross.
+fn layout(title: String, root: &Node) -> BoardDocument {
+    let depth = root.depth().saturating_sub(1).max(1);
+    // The outermost ring must have room for every leaf side by side
+    let ring = RING_GAP.max(root.leaves() as f64 * LEAF_ARC / TAU / depth as f64);
+    let mut layout = Layout {
+        elements: Vec::new(),
+        connections: Vec::new(),
+        ring,
+    };
+    // Wedges start at the bottom, so a lone branch sits straight above the root
+    layout.place(root, 0, -0.75 * TAU, TAU, None);
+
+    let mut document = BoardDocument::new(title, persist::now_millis());
+    document.board.elements = layout.elements;
+    document.board.connections = Some(layout.connections);
+    document
+}
+
+/// Turn an OPML outline exported from a mind-mapping or outliner app into a
+/// mind map of connected sticky notes.
+#[tauri::command]
+pub fn import_opml(path: String) -> Result<BoardDocument> {
+    let bytes = board::read_board(&path)?;
+    let source = String::from_utf8(bytes).map_err(|_| Error::Import("not a UTF-8 text file".to_string()))?;
+    let (title, root) = parse(&source, title_from_path(&path))?;
+    Ok(layout(title, &root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{ElementKind, ShapeProps};
+
+    fn label(element: &Element) -> &str {
+        match &element.kind {
+            ElementKind::Rectangle(ShapeProps { label, .. }) => label.as_deref().unwrap_or_default(),
+            kind => panic!("notes are rectangles, got {kind:?}"),
+        }
+    }
+
+    fn center(element: &Element) -> (f64, f64) {
+        let center = element.rect.center();
+        ((center.x * 1e6).round() / 1e6, (center.y * 1e6).round() / 1e6)
+    }
+
+    fn import(source: &str) -> Result<BoardDocument> {
+        let (title, root) = parse(source, "file".to_string())?;
+        Ok(layout(title, &root))
+    }
+
+    #[test]
+    fn outlines_become_a_radial_mind_map() {
+        let document = import(
+            r#"<?xml version="1.0"?>
+            <opml version="2.0">
+              <head><title> Plan </title></head>
+              <body>
+                <outline text="A" _note="detail"><outline title="A1"/></outline>
+                <outline text="B"/>
+              </body>
+            </opml>"#,
+        )
+        .unwrap();
+
+        assert_eq!(document.meta.title, "Plan");
+        let elements = &document.board.elements;
+        let notes: Vec<_> = elements
+            .iter()
+            .map(|element| (element.id.as_str(), label(element), element.style.fill_color.as_deref(), center(element)))
+            .collect();
+        let ring = RING_GAP;
+        assert_eq!(
+            notes,
+            vec![
+                ("opml-1", "Plan", Some(ROOT_FILL), (0.0, 0.0)),
+                ("opml-2", "A\ndetail", Some(BRANCH_FILLS[0]), (-ring, 0.0)),
+                ("opml-3", "A1", Some(BRANCH_FILLS[0]), (-2.0 * ring, 0.0)),
+                ("opml-4", "B", Some(BRANCH_FILLS[1]), (ring, 0.0)),
+            ]
+        );
+        assert_eq!((elements[0].rect.width, elements[0].rect.height), (ROOT_WIDTH, ROOT_HEIGHT));
+        assert_eq!((elements[1].rect.width, elements[1].rect.height), (NODE_WIDTH, NODE_HEIGHT));
+
+        let links: Vec<(&str, &str)> = document
+            .board
+            .connections
+            .iter()
+            .flatten()
+            .map(|link| (link.source_id.as_str(), link.target_id.as_str()))
+            .collect();
+        assert_eq!(links, vec![("opml-2", "opml-3"), ("opml-1", "opml-2"), ("opml-1", "opml-4")]);
+    }
+
+    #[test]
+    fn a_single_outline_is_the_root() {
+        let document = import(r#"<opml><body><outline text="Topic"><outline text="Idea"/></outline></body></opml>"#);
+        let document = document.unwrap();
+        assert_eq!(document.meta.title, "Topic");
+        let labels: Vec<&str> = document.board.elements.iter().map(label).collect();
+        assert_eq!(labels, vec!["Topic", "Idea"]);
+        // A lone branch sits straight above the root
+        assert_eq!(center(&document.board.elements[1]), (0.0, -RING_GAP));
+    }
+
+    #[test]
+    fn crowded_rings_are_widened() {
+        let leaves = "<outline text=\"leaf\"/>".repeat(20);
+        let document = import(&format!("<opml><body><outline text=\"Hub\">{leaves}</outline></body></opml>")).unwrap();
+        let ring = 20.0 * LEAF_ARC / TAU;
+        for leaf in &document.board.elements[1..] {
+            let (x, y) = center(leaf);
+            assert!((x.hypot(y) - ring).abs() < 1e-3, "leaf at ({x}, {y}) is off the {ring} ring");
+        }
+    }
+
+    #[test]
+    fn files_without_outlines_are_rejected() {
+        for source in [
+            "<html><body/></html>",
+            "<opml><head/></opml>",
+            "<opml><body></body></opml>",
+            "<opml><body>",
+        ] {
+            assert!(matches!(import(source), Err(Error::Import(_))), "{source}");
+        }
+    }
+}
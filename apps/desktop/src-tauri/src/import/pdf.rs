@@ -0,0 +1,152 @@
+use std::{fs, path::PathBuf};
+
+use pdfium_render::prelude::{PdfRenderConfig, Pdfium, PdfiumError};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::title_from_path;
+use crate::{
+    assets::AssetStore,
+    error::{Error, Result},
+};
+
+/// Longest side of a rendered page in pixels, whatever the page size or scale.
+const MAX_PAGE_PIXELS: i32 = 4096;
+/// Vertical space between stacked pages, in board units.
+const PAGE_GAP: f64 = 24.0;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PdfImportOptions {
+    /// Pixels per PDF point; 2 renders at 144 DPI so pages stay sharp when zoomed
+    pub scale: f32,
+    /// 1-based page numbers to import, all pages when omitted
+    pub pages: Option<Vec<u16>>,
+    pub password: Option<String>,
+}
+
+impl Default for PdfImportOptions {
+    fn default() -> Self {
+        Self {
+            scale: 2.0,
+            pages: None,
+            password: None,
+        }
+    }
+}
+
+/// A rendered page stored in the asset store, with where to put it on the
+/// board. Positions stack the pages top to bottom from the origin, so the
+/// caller only has to offset them to the drop point.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfPageImage {
+    page: u16,
+    hash: String,
+    pixel_width: u32,
+    pixel_height: u32,
+    x: f64,
+    y: f64,
+    /// Board size, one unit per PDF point
+    width: f64,
+    height: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfImport {
+    title: String,
+    page_count: u16,
+    pages: Vec<PdfPageImage>,
+}
+
+fn pdf_error(err: PdfiumError) -> Error {
+    Error::Import(format!("cannot read PDF: {err}"))
+}
+
+/// PDFium ships next to the app in bundles; development builds use the system copy.
+fn bind_pdfium(app: &AppHandle) -> Result<Pdfium> {
+    let bundled = app
+        .path()
+        .resource_dir()
+        .map(|dir| Pdfium::pdfium_platform_library_name_at_path(&dir))
+        .unwrap_or_else(|_| PathBuf::new());
+    let bindings = Pdfium::bind_to_library(&bundled)
+        .or_else(|_| Pdfium::bind_to_system_library())
+        .map_err(|err| Error::Import(format!("PDF support is unavailable: {err}")))?;
+    Ok(Pdfium::new(bindings))
+}
+
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgba))
+        .map_err(|err| Error::Import(err.to_string()))?;
+    Ok(png)
+}
+
+fn import(app: &AppHandle, path: &str, options: &PdfImportOptions) -> Result<PdfImport> {
+    if !(options.scale.is_finite() && options.scale > 0.0) {
+        return Err(Error::Import(format!("invalid scale {}", options.scale)));
+    }
+    fs::metadata(path).map_err(|err| Error::from_io(err, path))?;
+    let pdfium = bind_pdfium(app)?;
+    let document = pdfium.load_pdf_from_file(path, options.password.as_deref()).map_err(pdf_error)?;
+
+    let page_count = document.pages().len();
+    let numbers = match &options.pages {
+        Some(pages) => pages.clone(),
+        None => (1..=page_count).collect(),
+    };
+    if let Some(number) = numbers.iter().find(|number| **number == 0 || **number > page_count) {
+        return Err(Error::Import(format!("page {number} is out of range (the PDF has {page_count} pages)")));
+    }
+
+    let config = PdfRenderConfig::new()
+        .scale_page_by_factor(options.scale)
+        .set_maximum_width(MAX_PAGE_PIXELS)
+        .set_maximum_height(MAX_PAGE_PIXELS)
+        .render_form_data(true);
+    let store = app.state::<AssetStore>();
+    let mut pages = Vec::with_capacity(numbers.len());
+    let mut y = 0.0;
+    for number in numbers {
+        let page = document.pages().get(number - 1).map_err(pdf_error)?;
+        let bitmap = page.render_with_config(&config).map_err(pdf_error)?;
+        let (pixel_width, pixel_height) = (bitmap.width() as u32, bitmap.height() as u32);
+        let hash = store.store(&encode_png(pixel_width, pixel_height, &bitmap.as_rgba_bytes())?)?;
+        let (width, height) = (f64::from(page.width().value), f64::from(page.height().value));
+        pages.push(PdfPageImage {
+            page: number,
+            hash,
+            pixel_width,
+            pixel_height,
+            x: 0.0,
+            y,
+            width,
+            height,
+        });
+        y += height + PAGE_GAP;
+    }
+
+    tracing::info!(path, pages = pages.len(), "PDF imported");
+    Ok(PdfImport {
+        title: title_from_path(path),
+        page_count,
+        pages,
+    })
+}
+
+/// Render PDF pages into the asset store as PNG images, returning their hashes
+/// and board placement so the frontend can add one image element per page.
+#[tauri::command]
+pub async fn import_pdf(app: AppHandle, path: String, options: Option<PdfImportOptions>) -> Result<PdfImport> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || import(&app, &path, &options))
+        .await
+        .map_err(|err| Error::Import(err.to_string()))?
+}
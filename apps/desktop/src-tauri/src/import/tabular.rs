@@ -0,0 +1,386 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{note, title_from_path};
+use crate::{
+    board,
+    document::{BoardDocument, Rect},
+    error::{Error, Result},
+    persist,
+};
+
+/// Rows beyond this are left out, so a large export can't bury the board.
+const MAX_ROWS: usize = 1000;
+const SAMPLE_ROWS: usize = 5;
+const CARD_WIDTH: f64 = 220.0;
+const CARD_HEIGHT: f64 = 140.0;
+const CELL_WIDTH: f64 = 160.0;
+const CELL_HEIGHT: f64 = 48.0;
+const GAP: f64 = 20.0;
+const HEADER_FILL: &str = "#d4d4d8";
+/// Fills handed out to the distinct values of the color column, in order.
+const PALETTE: &[&str] = &[
+    "#fef08a", "#bbf7d0", "#bfdbfe", "#fecaca", "#e9d5ff", "#fed7aa", "#a5f3fc", "#fbcfe8",
+];
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TabularLayout {
+    /// One sticky note per row
+    #[default]
+    Cards,
+    /// One cell per value, with a header row
+    Table,
+}
+
+/// Which columns go where. Unknown column names are an error so typos surface.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TabularMapping {
+    /// Card heading; the first column when omitted
+    pub title_column: Option<String>,
+    /// Shown under the heading as `column: value`; the next three columns when omitted
+    pub body_columns: Option<Vec<String>>,
+    /// Rows sharing a value in this column share a colour
+    pub color_column: Option<String>,
+    pub layout: TabularLayout,
+    /// Cards per row in the grid
+    pub columns_per_row: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorLegend {
+    value: String,
+    color: &'static str,
+}
+
+/// The generated board plus what the frontend needs to preview the mapping.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabularImport {
+    columns: Vec<String>,
+    row_count: usize,
+    /// First rows, in column order
+    sample: Vec<Vec<String>>,
+    truncated: bool,
+    legend: Vec<ColorLegend>,
+    document: BoardDocument,
+}
+
+struct Table {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn column(&self, name: &str) -> Result<usize> {
+        self.columns
+            .iter()
+            .position(|column| column == name)
+            .ok_or_else(|| Error::Import(format!("no column named {name:?}")))
+    }
+}
+
+fn read_csv(bytes: &[u8]) -> Result<Table> {
+    // Excel and Numbers add a byte order mark
+    let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+    // European spreadsheets export with semicolons, some tools with tabs; ties go to commas
+    let header = bytes.split(|byte| *byte == b'\n').next().unwrap_or_default();
+    let count = |delimiter: u8| header.iter().filter(|byte| **byte == delimiter).count();
+    let delimiter = [b'\t', b';', b','].into_iter().max_by_key(|delimiter| count(*delimiter)).unwrap_or(b',');
+    let mut reader = csv::ReaderBuilder::new().delimiter(delimiter).flexible(true).from_reader(bytes);
+    let columns = reader
+        .headers()
+        .map_err(|err| Error::Import(format!("invalid CSV: {err}")))?
+        .iter()
+        .map(|column| column.trim().to_string())
+        .collect();
+    let rows = reader
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(str::to_string).collect())
+                .map_err(|err| Error::Import(format!("invalid CSV: {err}")))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Table { columns, rows })
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// An array of objects, either at the top level or under the first key holding one.
+fn read_json(bytes: &[u8]) -> Result<Table> {
+    let value: Value = serde_json::from_slice(bytes).map_err(|err| Error::Import(format!("invalid JSON: {err}")))?;
+    let records = match &value {
+        Value::Array(records) => records,
+        Value::Object(fields) => fields
+            .values()
+            .find_map(|field| field.as_array().filter(|items| items.iter().all(Value::is_object)))
+            .ok_or_else(|| Error::Import("no array of records found".to_string()))?,
+        _ => return Err(Error::Import("expected an array of records".to_string())),
+    };
+    let mut columns: Vec<String> = Vec::new();
+    for record in records.iter().filter_map(Value::as_object) {
+        for key in record.keys() {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+    }
+    let rows = records
+        .iter()
+        .filter_map(Value::as_object)
+        .map(|record| columns.iter().map(|column| record.get(column).map(cell).unwrap_or_default()).collect())
+        .collect();
+    Ok(Table { columns, rows })
+}
+
+fn read_table(path: &str) -> Result<Table> {
+    let bytes = board::read_board(path)?;
+    let is_json = Path::new(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    let table = if is_json { read_json(&bytes)? } else { read_csv(&bytes)? };
+    if table.columns.is_empty() {
+        return Err(Error::Import("the file has no columns".to_string()));
+    }
+    Ok(table)
+}
+
+fn value_at(row: &[String], index: usize) -> &str {
+    row.get(index).map(|value| value.trim()).unwrap_or_default()
+}
+
+fn build(table: &Table, mapping: &TabularMapping, title: String) -> Result<TabularImport> {
+    let title_index = match &mapping.title_column {
+        Some(column) => table.column(column)?,
+        None => 0,
+    };
+    let body_indices: Vec<usize> = match &mapping.body_columns {
+        Some(columns) => columns.iter().map(|column| table.column(column)).collect::<Result<_>>()?,
+        None => (0..table.columns.len()).filter(|index| *index != title_index).take(3).collect(),
+    };
+    let color_index = mapping.color_column.as_deref().map(|column| table.column(column)).transpose()?;
+
+    let rows = &table.rows[..table.rows.len().min(MAX_ROWS)];
+    let mut colors: BTreeMap<String, &'static str> = BTreeMap::new();
+    let mut legend = Vec::new();
+    let mut fill_for = |row: &[String]| -> Option<&'static str> {
+        let value = value_at(row, color_index?);
+        let next = PALETTE[colors.len() % PALETTE.len()];
+        Some(*colors.entry(value.to_string()).or_insert_with(|| {
+            legend.push(ColorLegend {
+                value: value.to_string(),
+                color: next,
+            });
+            next
+        }))
+    };
+
+    let mut document = BoardDocument::new(title, persist::now_millis());
+    let elements = &mut document.board.elements;
+    let mut push = |rect: Rect, label: &str, fill: Option<&str>| {
+        let id = format!("row-{}", elements.len() + 1);
+        let z_index = elements.len() as f64;
+        elements.push(note(id, rect, label, fill, z_index));
+    };
+
+    match mapping.layout {
+        TabularLayout::Cards => {
+            let per_row = mapping.columns_per_row.unwrap_or(5).max(1);
+            for (index, row) in rows.iter().enumerate() {
+                let mut label = value_at(row, title_index).to_string();
+                for &body in &body_indices {
+                    let value = value_at(row, body);
+                    if !value.is_empty() {
+                        label.push_str(&format!("\n{}: {value}", table.columns[body]));
+                    }
+                }
+                let rect = Rect {
+                    x: (index % per_row) as f64 * (CARD_WIDTH + GAP),
+                    y: (index / per_row) as f64 * (CARD_HEIGHT + GAP),
+                    width: CARD_WIDTH,
+                    height: CARD_HEIGHT,
+                };
+                let fill = fill_for(row);
+                push(rect, &label, fill);
+            }
+        }
+        TabularLayout::Table => {
+            let shown: Vec<usize> = std::iter::once(title_index).chain(body_indices.iter().copied()).collect();
+            let cell_rect = |column: usize, row: usize| Rect {
+                x: column as f64 * CELL_WIDTH,
+                y: row as f64 * CELL_HEIGHT,
+                width: CELL_WIDTH,
+                height: CELL_HEIGHT,
+            };
+            for (column, &index) in shown.iter().enumerate() {
+                push(cell_rect(column, 0), &table.columns[index], Some(HEADER_FILL));
+            }
+            for (row_index, row) in rows.iter().enumerate() {
+                let fill = fill_for(row);
+                for (column, &index) in shown.iter().enumerate() {
+                    push(cell_rect(column, row_index + 1), value_at(row, index), fill);
+                }
+            }
+        }
+    }
+
+    Ok(TabularImport {
+        columns: table.columns.clone(),
+        row_count: table.rows.len(),
+        sample: table.rows.iter().take(SAMPLE_ROWS).cloned().collect(),
+        truncated: table.rows.len() > MAX_ROWS,
+        legend,
+        document,
+    })
+}
+
+/// Turn a CSV or JSON file into cards or a table on a new board. The response
+/// also describes the data (columns, sample rows, colour legend) so the
+/// frontend can preview a mapping and call again with a different one.
+#[tauri::command]
+pub async fn import_tabular(path: String, mapping: Option<TabularMapping>) -> Result<TabularImport> {
+    let mapping = mapping.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || build(&read_table(&path)?, &mapping, title_from_path(&path)))
+        .await
+        .map_err(|err| Error::Import(err.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        document::{ElementKind, ShapeProps},
+        import::NOTE_FILL,
+    };
+
+    fn table(columns: &[&str], rows: &[&[&str]]) -> Table {
+        Table {
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+            rows: rows.iter().map(|row| row.iter().map(|value| value.to_string()).collect()).collect(),
+        }
+    }
+
+    /// (label, fill, x, y) of every generated note.
+    fn notes(import: &TabularImport) -> Vec<(&str, Option<&str>, f64, f64)> {
+        import
+            .document
+            .board
+            .elements
+            .iter()
+            .map(|element| {
+                let ElementKind::Rectangle(ShapeProps { label, .. }) = &element.kind else {
+                    panic!("notes are rectangles, got {:?}", element.kind);
+                };
+                let label = label.as_deref().unwrap_or_default();
+                (label, element.style.fill_color.as_deref(), element.rect.x, element.rect.y)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn csv_delimiters_are_detected() {
+        let table = read_csv(b"\xef\xbb\xbfName; Team ;Note\nAda;Core;\"multi\nline; quoted\"\nGrace;Infra\n").unwrap();
+        assert_eq!(table.columns, vec!["Name", "Team", "Note"]);
+        assert_eq!(table.rows, vec![vec!["Ada", "Core", "multi\nline; quoted"], vec!["Grace", "Infra"]]);
+
+        let table = read_csv(b"a\tb\n1\t2\n").unwrap();
+        assert_eq!((table.columns.len(), table.rows[0].len()), (2, 2));
+    }
+
+    #[test]
+    fn json_records_are_found_and_flattened() {
+        let json = br#"{ "meta": { "count": 2 }, "items": [
+            { "name": "Ada", "age": 36 },
+            { "name": "Grace", "active": true, "age": null }
+        ] }"#;
+        let table = read_json(json).unwrap();
+        // Keys of each record come sorted, columns new in later records go last
+        assert_eq!(table.columns, vec!["age", "name", "active"]);
+        assert_eq!(table.rows, vec![vec!["36", "Ada", ""], vec!["", "Grace", "true"]]);
+
+        assert!(matches!(read_json(b"42"), Err(Error::Import(_))));
+        assert!(matches!(read_json(br#"{ "count": 2 }"#), Err(Error::Import(_))));
+    }
+
+    #[test]
+    fn rows_become_coloured_cards() {
+        let table = table(
+            &["Task", "Owner", "Status", "Due", "Id"],
+            &[&["Write", "Ada", "todo", "", "1"], &["Test", "Grace", "done", "May", "2"], &["Ship", "", "todo"]],
+        );
+        let mapping = TabularMapping {
+            color_column: Some("Status".to_string()),
+            columns_per_row: Some(2),
+            ..TabularMapping::default()
+        };
+        let import = build(&table, &mapping, "Tasks".to_string()).unwrap();
+
+        let (step_x, step_y) = (CARD_WIDTH + GAP, CARD_HEIGHT + GAP);
+        assert_eq!(
+            notes(&import),
+            vec![
+                ("Write\nOwner: Ada\nStatus: todo", Some(PALETTE[0]), 0.0, 0.0),
+                ("Test\nOwner: Grace\nStatus: done\nDue: May", Some(PALETTE[1]), step_x, 0.0),
+                ("Ship\nStatus: todo", Some(PALETTE[0]), 0.0, step_y),
+            ]
+        );
+        let legend: Vec<(&str, &str)> = import.legend.iter().map(|entry| (entry.value.as_str(), entry.color)).collect();
+        assert_eq!(legend, vec![("todo", PALETTE[0]), ("done", PALETTE[1])]);
+        assert_eq!((import.row_count, import.truncated, import.sample.len()), (3, false, 3));
+    }
+
+    #[test]
+    fn table_layout_has_a_header_row() {
+        let table = table(&["Name", "Team", "Room"], &[&["Ada", "Core", "1"]]);
+        let mapping = TabularMapping {
+            title_column: Some("Team".to_string()),
+            body_columns: Some(vec!["Name".to_string()]),
+            layout: TabularLayout::Table,
+            ..TabularMapping::default()
+        };
+        let import = build(&table, &mapping, "People".to_string()).unwrap();
+        assert_eq!(
+            notes(&import),
+            vec![
+                ("Team", Some(HEADER_FILL), 0.0, 0.0),
+                ("Name", Some(HEADER_FILL), CELL_WIDTH, 0.0),
+                ("Core", Some(NOTE_FILL), 0.0, CELL_HEIGHT),
+                ("Ada", Some(NOTE_FILL), CELL_WIDTH, CELL_HEIGHT),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_columns_are_an_error() {
+        let table = table(&["Name"], &[&["Ada"]]);
+        let mapping = TabularMapping {
+            color_column: Some("name".to_string()),
+            ..TabularMapping::default()
+        };
+        let Err(Error::Import(message)) = build(&table, &mapping, String::new()) else {
+            panic!("a misspelled column should be refused");
+        };
+        assert!(message.contains("\"name\""), "{message}");
+    }
+
+    #[test]
+    fn large_files_are_truncated() {
+        let rows: Vec<Vec<String>> = (0..MAX_ROWS + 1).map(|row| vec![row.to_string()]).collect();
+        let table = Table {
+            columns: vec!["Row".to_string()],
+            rows,
+        };
+        let import = build(&table, &TabularMapping::default(), String::new()).unwrap();
+        assert_eq!(import.document.board.elements.len(), MAX_ROWS);
+        assert_eq!((import.row_count, import.truncated, import.sample.len()), (MAX_ROWS + 1, true, SAMPLE_ROWS));
+    }
+}
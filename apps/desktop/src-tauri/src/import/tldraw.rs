@@ -0,0 +1,907 @@
+//! Interchange with tldraw's `.tldr` files, in both directions.
+//!
+//! Exports target the tldraw 2.x/3.x record schema (arrow bindings stored as
+//! separate records); imports also accept the older inline arrow bindings.
+
+use std::{collections::HashMap, path::Path};
+
+use serde_json::{json, Map, Value};
+
+use super::{points_bounds, title_from_path};
+use crate::{
+    board,
+    document::{
+        AnchorPosition, ArrowBinding, ArrowHead, BoardDocument, DrawProps, Element, ElementKind, ElementStyle,
+        FontFamily, FontWeight, LinePoints, LineProps, Point, Rect, ShapeProps, StrokeDash, TextAlign, TextProps,
+    },
+    error::{Error, Result},
+    persist,
+};
+
+const PAGE_ID: &str = "page:page";
+const SHAPE_PREFIX: &str = "shape:";
+const NOTE_SIZE: f64 = 200.0;
+const NOTE_FILL: &str = "#fcc419";
+
+/// tldraw's named colors with their light-theme solid values.
+const PALETTE: &[(&str, &str)] = &[
+    ("black", "#1d1d1d"),
+    ("grey", "#9fa8b2"),
+    ("light-violet", "#e085f4"),
+    ("violet", "#ae3ec9"),
+    ("blue", "#4465e9"),
+    ("light-blue", "#4ba1f1"),
+    ("yellow", "#f1ac4b"),
+    ("orange", "#e16919"),
+    ("green", "#099268"),
+    ("light-green", "#4cb05e"),
+    ("light-red", "#f87777"),
+    ("red", "#e03131"),
+    ("white", "#ffffff"),
+];
+
+/// (size, stroke width, font size)
+const SIZES: &[(&str, f64, f64)] = &[("s", 2.0, 18.0), ("m", 3.5, 24.0), ("l", 5.0, 36.0), ("xl", 10.0, 44.0)];
+
+/// Schema versions of the records written by [`export_tldraw`].
+const SCHEMA_SEQUENCES: &[(&str, u32)] = &[
+    ("com.tldraw.store", 4),
+    ("com.tldraw.asset", 1),
+    ("com.tldraw.camera", 1),
+    ("com.tldraw.document", 2),
+    ("com.tldraw.instance", 25),
+    ("com.tldraw.instance_page_state", 5),
+    ("com.tldraw.page", 1),
+    ("com.tldraw.instance_presence", 5),
+    ("com.tldraw.pointer", 1),
+    ("com.tldraw.shape", 4),
+    ("com.tldraw.shape.group", 0),
+    ("com.tldraw.shape.text", 2),
+    ("com.tldraw.shape.draw", 2),
+    ("com.tldraw.shape.geo", 9),
+    ("com.tldraw.shape.note", 7),
+    ("com.tldraw.shape.line", 5),
+    ("com.tldraw.shape.frame", 0),
+    ("com.tldraw.shape.arrow", 5),
+    ("com.tldraw.binding.arrow", 0),
+];
+
+fn rotate(point: Point, angle: f64) -> Point {
+    let (sin, cos) = angle.sin_cos();
+    Point {
+        x: point.x * cos - point.y * sin,
+        y: point.x * sin + point.y * cos,
+    }
+}
+
+fn add(a: Point, b: Point) -> Point {
+    Point { x: a.x + b.x, y: a.y + b.y }
+}
+
+fn color_hex(name: &str) -> &'static str {
+    PALETTE
+        .iter()
+        .find(|(color, _)| *color == name)
+        .map_or(PALETTE[0].1, |(_, hex)| hex)
+}
+
+fn parse_hex(hex: &str) -> Option<(i32, i32, i32)> {
+    let hex = hex.strip_prefix('#')?;
+    let channel = |range| i32::from_str_radix(hex.get(range)?, 16).ok();
+    match hex.len() {
+        6 | 8 => Some((channel(0..2)?, channel(2..4)?, channel(4..6)?)),
+        3 => Some((channel(0..1)? * 17, channel(1..2)? * 17, channel(2..3)? * 17)),
+        _ => None,
+    }
+}
+
+/// Nearest named tldraw color; tldraw cannot store arbitrary colors.
+fn color_name(hex: &str) -> &'static str {
+    let Some((r, g, b)) = parse_hex(hex) else {
+        return PALETTE[0].0;
+    };
+    PALETTE
+        .iter()
+        .min_by_key(|(_, candidate)| {
+            let (cr, cg, cb) = parse_hex(candidate).unwrap_or_default();
+            (r - cr).pow(2) + (g - cg).pow(2) + (b - cb).pow(2)
+        })
+        .map_or(PALETTE[0].0, |(name, _)| name)
+}
+
+fn size_for_stroke(width: f64) -> &'static str {
+    SIZES
+        .iter()
+        .min_by(|a, b| (a.1 - width).abs().total_cmp(&(b.1 - width).abs()))
+        .map_or("m", |size| size.0)
+}
+
+fn size_for_font(font_size: f64) -> &'static str {
+    SIZES
+        .iter()
+        .min_by(|a, b| (a.2 - font_size).abs().total_cmp(&(b.2 - font_size).abs()))
+        .map_or("m", |size| size.0)
+}
+
+/// Fractional index keys ("a1", "a2", ... "b10", ...) in paint order.
+fn index_key(position: usize) -> String {
+    const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+    let mut position = position + 1;
+    let (head, width) = match position {
+        0..62 => ('a', 1),
+        62..3844 => ('b', 2),
+        _ => ('c', 3),
+    };
+    let mut digits = vec![b'0'; width];
+    for slot in digits.iter_mut().rev() {
+        *slot = DIGITS[position % 62];
+        position /= 62;
+    }
+    format!("{head}{}", String::from_utf8_lossy(&digits))
+}
+
+// ============================================================================
+// Import
+// ============================================================================
+
+fn number(value: &Value, key: &str) -> f64 {
+    value.get(key).and_then(Value::as_f64).unwrap_or(0.0)
+}
+
+fn string<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+/// Plain text of a shape label, from `text` or newer TipTap-style `richText`.
+fn label_text(props: &Value) -> String {
+    fn collect(node: &Value, out: &mut String) {
+        if let Some(text) = node.get("text").and_then(Value::as_str) {
+            out.push_str(text);
+        }
+        if let Some(children) = node.get("content").and_then(Value::as_array) {
+            for (index, child) in children.iter().enumerate() {
+                if index > 0 && child.get("type").and_then(Value::as_str) == Some("paragraph") {
+                    out.push('\n');
+                }
+                collect(child, out);
+            }
+        }
+    }
+    if let Some(text) = props.get("text").and_then(Value::as_str) {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    if let Some(rich) = props.get("richText") {
+        collect(rich, &mut out);
+    }
+    out
+}
+
+fn strip_prefix(id: &str) -> String {
+    id.strip_prefix(SHAPE_PREFIX).unwrap_or(id).to_string()
+}
+
+fn style_from_props(props: &Value, opacity: f64) -> ElementStyle {
+    let size = SIZES.iter().find(|size| size.0 == string(props, "size")).unwrap_or(&SIZES[1]);
+    let color = color_hex(string(props, "color")).to_string();
+    ElementStyle {
+        fill_color: match string(props, "fill") {
+            "" | "none" => None,
+            _ => Some(color.clone()),
+        },
+        stroke_color: color,
+        stroke_width: size.1,
+        opacity,
+        stroke_dash: Some(match string(props, "dash") {
+            "dashed" => StrokeDash::Dashed,
+            "dotted" => StrokeDash::Dotted,
+            _ => StrokeDash::Solid,
+        }),
+        roughness: Some(if string(props, "dash") == "draw" { 1.0 } else { 0.0 }),
+    }
+}
+
+fn font_family(font: &str) -> FontFamily {
+    match font {
+        "draw" => FontFamily::Handwritten,
+        "serif" => FontFamily::Serif,
+        "mono" => FontFamily::Code,
+        _ => FontFamily::System,
+    }
+}
+
+struct Importer<'a> {
+    shapes: HashMap<&'a str, &'a Value>,
+    /// (arrow id, terminal) -> bound shape id
+    bindings: HashMap<(String, String), String>,
+}
+
+impl Importer<'_> {
+    /// Page-space origin and rotation of a shape, following group and frame parents.
+    fn page_transform(&self, shape: &Value, depth: usize) -> (Point, f64) {
+        let local = Point {
+            x: number(shape, "x"),
+            y: number(shape, "y"),
+        };
+        let rotation = number(shape, "rotation");
+        let parent = self.shapes.get(string(shape, "parentId"));
+        match parent {
+            Some(parent) if depth < 32 => {
+                let (origin, parent_rotation) = self.page_transform(parent, depth + 1);
+                (add(origin, rotate(local, parent_rotation)), parent_rotation + rotation)
+            }
+            _ => (local, rotation),
+        }
+    }
+
+    fn binding(&self, arrow_id: &str, terminal: &str, legacy: &Value) -> Option<ArrowBinding> {
+        let target = self
+            .bindings
+            .get(&(arrow_id.to_string(), terminal.to_string()))
+            .cloned()
+            .or_else(|| legacy.get("boundShapeId").and_then(Value::as_str).map(str::to_string))?;
+        self.shapes.contains_key(target.as_str()).then(|| ArrowBinding {
+            element_id: strip_prefix(&target),
+            anchor: AnchorPosition::Center,
+            offset: None,
+        })
+    }
+
+    fn convert(&self, shape: &Value) -> Option<(Rect, f64, ElementKind)> {
+        let props = shape.get("props").unwrap_or(&Value::Null);
+        let (origin, rotation) = self.page_transform(shape, 0);
+        let to_page = |x: f64, y: f64| add(origin, rotate(Point { x, y }, rotation));
+        // tldraw rotates around the top-left corner, boardkit around the center
+        let boxed = |width: f64, height: f64| {
+            let center = to_page(width / 2.0, height / 2.0);
+            Rect {
+                x: center.x - width / 2.0,
+                y: center.y - height / 2.0,
+                width,
+                height,
+            }
+        };
+        let scale = props.get("scale").and_then(Value::as_f64).unwrap_or(1.0);
+
+        match string(shape, "type") {
+            "geo" => {
+                let label = Some(label_text(props)).filter(|label| !label.is_empty());
+                let rect = boxed(number(props, "w"), number(props, "h") + number(props, "growY"));
+                let shape = ShapeProps {
+                    label,
+                    corner_radius: None,
+                };
+                let kind = match string(props, "geo") {
+                    "ellipse" | "oval" => ElementKind::Ellipse(shape),
+                    _ => ElementKind::Rectangle(shape),
+                };
+                Some((rect, rotation, kind))
+            }
+            "note" => {
+                let label = Some(label_text(props)).filter(|label| !label.is_empty());
+                let size = NOTE_SIZE * scale;
+                let rect = boxed(size, size + number(props, "growY"));
+                let kind = ElementKind::Rectangle(ShapeProps {
+                    label,
+                    corner_radius: Some(4.0),
+                });
+                Some((rect, rotation, kind))
+            }
+            "text" => {
+                let font_size = SIZES.iter().find(|size| size.0 == string(props, "size")).unwrap_or(&SIZES[1]).2 * scale;
+                let content = label_text(props);
+                let lines = content.split('\n').count().max(1) as f64;
+                let width = props
+                    .get("w")
+                    .and_then(Value::as_f64)
+                    .unwrap_or(font_size * content.chars().count() as f64 * 0.55);
+                let rect = boxed(width.max(font_size), lines * font_size * 1.35);
+                let kind = ElementKind::Text(TextProps {
+                    content,
+                    font_family: font_family(string(props, "font")),
+                    font_size,
+                    font_weight: FontWeight::Normal,
+                    text_align: match string(props, "textAlign") {
+                        "middle" => TextAlign::Center,
+                        "end" => TextAlign::Right,
+                        _ => TextAlign::Left,
+                    },
+                });
+                Some((rect, rotation, kind))
+            }
+            "arrow" => {
+                let id = string(shape, "id");
+                let (start, end) = (props.get("start")?, props.get("end")?);
+                let points = LinePoints {
+                    start: to_page(number(start, "x"), number(start, "y")),
+                    end: to_page(number(end, "x"), number(end, "y")),
+                };
+                let has_head = |key| !matches!(string(props, key), "" | "none");
+                let arrow_head = match (has_head("arrowheadStart"), has_head("arrowheadEnd")) {
+                    (true, true) => ArrowHead::Both,
+                    (true, false) => ArrowHead::Start,
+                    (false, true) => ArrowHead::End,
+                    (false, false) => ArrowHead::None,
+                };
+                let rect = points_bounds(&[points.start, points.end]);
+                let kind = ElementKind::Arrow(LineProps {
+                    points,
+                    arrow_head: Some(arrow_head),
+                    start_binding: self.binding(id, "start", start),
+                    end_binding: self.binding(id, "end", end),
+                });
+                Some((rect, 0.0, kind))
+            }
+            "line" => {
+                let mut handles: Vec<&Value> = match props.get("points") {
+                    Some(Value::Object(points)) => points.values().collect(),
+                    Some(Value::Array(points)) => points.iter().collect(),
+                    _ => Vec::new(),
+                };
+                handles.sort_by(|a, b| string(a, "index").cmp(string(b, "index")));
+                let points: Vec<Point> = handles
+                    .iter()
+                    .map(|handle| to_page(number(handle, "x"), number(handle, "y")))
+                    .collect();
+                let rect = points_bounds(&points);
+                let kind = match points.as_slice() {
+                    [start, end] => ElementKind::Line(LineProps {
+                        points: LinePoints { start: *start, end: *end },
+                        ..LineProps::default()
+                    }),
+                    [] | [_] => return None,
+                    _ => ElementKind::Draw(DrawProps { points }),
+                };
+                Some((rect, 0.0, kind))
+            }
+            "draw" | "highlight" => {
+                let points: Vec<Point> = props
+                    .get("segments")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|segment| segment.get("points").and_then(Value::as_array))
+                    .flatten()
+                    .map(|point| to_page(number(point, "x") * scale, number(point, "y") * scale))
+                    .collect();
+                if points.is_empty() {
+                    return None;
+                }
+                Some((points_bounds(&points), 0.0, ElementKind::Draw(DrawProps { points })))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Convert the first page of a parsed `.tldr` file. Images, frames and
+/// embeds have no counterpart on the board and are dropped; shapes inside
+/// frames and groups are kept.
+fn convert(file: &Value, title: String) -> Result<BoardDocument> {
+    let records = file
+        .get("records")
+        .and_then(Value::as_array)
+        .ok_or_else(|| Error::Import("not a tldraw file".to_string()))?;
+    let by_type = |type_name: &'static str| {
+        records
+            .iter()
+            .filter(move |record| string(record, "typeName") == type_name)
+    };
+
+    let mut pages: Vec<&Value> = by_type("page").collect();
+    pages.sort_by(|a, b| string(a, "index").cmp(string(b, "index")));
+    let page_id = pages.first().map_or(PAGE_ID, |page| string(page, "id"));
+
+    let shapes: HashMap<&str, &Value> = by_type("shape").map(|shape| (string(shape, "id"), shape)).collect();
+    let bindings = by_type("binding")
+        .filter(|binding| string(binding, "type") == "arrow")
+        .map(|binding| {
+            let terminal = binding.get("props").map(|props| string(props, "terminal")).unwrap_or_default();
+            (
+                (string(binding, "fromId").to_string(), terminal.to_string()),
+                string(binding, "toId").to_string(),
+            )
+        })
+        .collect();
+    let importer = Importer { shapes, bindings };
+
+    let on_page = |shape: &Value| {
+        let mut current = shape;
+        for _ in 0..32 {
+            let parent = string(current, "parentId");
+            match importer.shapes.get(parent) {
+                Some(next) => current = next,
+                None => return parent == page_id,
+            }
+        }
+        false
+    };
+
+    // Paint order is the fractional index within each parent; sorting the
+    // flattened keys keeps siblings ordered and is close enough across parents
+    let mut ordered: Vec<&Value> = importer.shapes.values().copied().filter(|shape| on_page(shape)).collect();
+    ordered.sort_by(|a, b| string(a, "index").cmp(string(b, "index")).then(string(a, "id").cmp(string(b, "id"))));
+
+    let mut document = BoardDocument::new(title, persist::now_millis());
+    for shape in ordered {
+        let Some((rect, rotation, kind)) = importer.convert(shape) else {
+            continue;
+        };
+        let props = shape.get("props").unwrap_or(&Value::Null);
+        let opacity = shape.get("opacity").and_then(Value::as_f64).unwrap_or(1.0);
+        let mut style = style_from_props(props, opacity);
+        if string(shape, "type") == "note" {
+            style.fill_color = Some(NOTE_FILL.to_string());
+        }
+        document.board.elements.push(Element {
+            id: strip_prefix(string(shape, "id")),
+            rect,
+            z_index: document.board.elements.len() as f64,
+            style,
+            angle: (rotation != 0.0).then_some(rotation),
+            locked: shape.get("isLocked").and_then(Value::as_bool).filter(|locked| *locked),
+            kind,
+        });
+    }
+    Ok(document)
+}
+
+/// Read a `.tldr` file and convert it into a boardkit document.
+#[tauri::command]
+pub fn import_tldraw(path: String) -> Result<BoardDocument> {
+    let bytes = board::read_board(&path)?;
+    let file: Value =
+        serde_json::from_slice(&bytes).map_err(|err| Error::Import(format!("not a tldraw file: {err}")))?;
+    convert(&file, title_from_path(&path))
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+fn shape_record(element: &Element, index: usize, kind: &str, origin: Point, rotation: f64, props: Value) -> Value {
+    json!({
+        "typeName": "shape",
+        "id": format!("{SHAPE_PREFIX}{}", element.id),
+        "type": kind,
+        "x": origin.x,
+        "y": origin.y,
+        "rotation": rotation,
+        "index": index_key(index),
+        "parentId": PAGE_ID,
+        "isLocked": element.locked.unwrap_or(false),
+        "opacity": element.style.opacity,
+        "meta": {},
+        "props": props,
+    })
+}
+
+fn base_props(style: &ElementStyle) -> Map<String, Value> {
+    let mut props = Map::new();
+    props.insert("color".into(), json!(color_name(&style.stroke_color)));
+    props.insert("size".into(), json!(size_for_stroke(style.stroke_width)));
+    props.insert(
+        "dash".into(),
+        json!(match (style.stroke_dash.unwrap_or_default(), style.roughness.unwrap_or(0.0) > 0.0) {
+            (StrokeDash::Dashed, _) => "dashed",
+            (StrokeDash::Dotted, _) => "dotted",
+            (StrokeDash::Solid, true) => "draw",
+            (StrokeDash::Solid, false) => "solid",
+        }),
+    );
+    props.insert(
+        "fill".into(),
+        json!(if style.fill_color.is_some() { "solid" } else { "none" }),
+    );
+    props.insert("scale".into(), json!(1));
+    props
+}
+
+fn export_font(family: FontFamily) -> &'static str {
+    match family {
+        FontFamily::Handwritten => "draw",
+        FontFamily::Serif => "serif",
+        FontFamily::Code => "mono",
+        FontFamily::System => "sans",
+    }
+}
+
+/// Top-left origin tldraw expects for a center-rotated boardkit rect.
+fn rotated_origin(rect: &Rect, angle: f64) -> Point {
+    let center = rect.center();
+    let offset = rotate(
+        Point {
+            x: rect.width / 2.0,
+            y: rect.height / 2.0,
+        },
+        angle,
+    );
+    Point {
+        x: center.x - offset.x,
+        y: center.y - offset.y,
+    }
+}
+
+fn element_records(element: &Element, index: usize, records: &mut Vec<Value>) {
+    let rect = &element.rect;
+    let angle = element.angle.unwrap_or(0.0);
+    let mut props = base_props(&element.style);
+    match &element.kind {
+        ElementKind::Rectangle(shape) | ElementKind::Ellipse(shape) => {
+            let geo = if matches!(element.kind, ElementKind::Ellipse(_)) { "ellipse" } else { "rectangle" };
+            props.insert("geo".into(), json!(geo));
+            props.insert("w".into(), json!(rect.width));
+            props.insert("h".into(), json!(rect.height));
+            props.insert("text".into(), json!(shape.label.clone().unwrap_or_default()));
+            props.insert("labelColor".into(), json!(color_name(&element.style.stroke_color)));
+            props.insert("font".into(), json!("sans"));
+            props.insert("align".into(), json!("middle"));
+            props.insert("verticalAlign".into(), json!("middle"));
+            props.insert("growY".into(), json!(0));
+            props.insert("url".into(), json!(""));
+            let origin = rotated_origin(rect, angle);
+            records.push(shape_record(element, index, "geo", origin, angle, Value::Object(props)));
+        }
+        ElementKind::Text(text) => {
+            props.remove("dash");
+            props.remove("fill");
+            props.insert("size".into(), json!(size_for_font(text.font_size)));
+            props.insert("font".into(), json!(export_font(text.font_family)));
+            props.insert(
+                "textAlign".into(),
+                json!(match text.text_align {
+                    TextAlign::Left => "start",
+                    TextAlign::Center => "middle",
+                    TextAlign::Right => "end",
+                }),
+            );
+            props.insert("w".into(), json!(rect.width));
+            props.insert("text".into(), json!(text.content));
+            props.insert("autoSize".into(), json!(false));
+            let origin = rotated_origin(rect, angle);
+            records.push(shape_record(element, index, "text", origin, angle, Value::Object(props)));
+        }
+        ElementKind::Line(line) | ElementKind::Arrow(line) => {
+            let origin = line.points.start;
+            let relative = |point: Point| json!({ "x": point.x - origin.x, "y": point.y - origin.y });
+            if let ElementKind::Arrow(_) = element.kind {
+                let head = line.arrow_head.unwrap_or_default();
+                let (start_head, end_head) = match head {
+                    ArrowHead::None => ("none", "none"),
+                    ArrowHead::End => ("none", "arrow"),
+                    ArrowHead::Start => ("arrow", "none"),
+                    ArrowHead::Both => ("arrow", "arrow"),
+                };
+                props.insert("arrowheadStart".into(), json!(start_head));
+                props.insert("arrowheadEnd".into(), json!(end_head));
+                props.insert("start".into(), relative(line.points.start));
+                props.insert("end".into(), relative(line.points.end));
+                props.insert("bend".into(), json!(0));
+                props.insert("text".into(), json!(""));
+                props.insert("labelColor".into(), json!("black"));
+                props.insert("labelPosition".into(), json!(0.5));
+                props.insert("font".into(), json!("draw"));
+                records.push(shape_record(element, index, "arrow", origin, 0.0, Value::Object(props)));
+                for (terminal, binding) in [("start", &line.start_binding), ("end", &line.end_binding)] {
+                    let Some(binding) = binding else {
+                        continue;
+                    };
+                    records.push(json!({
+                        "typeName": "binding",
+                        "id": format!("binding:{}-{terminal}", element.id),
+                        "type": "arrow",
+                        "fromId": format!("{SHAPE_PREFIX}{}", element.id),
+                        "toId": format!("{SHAPE_PREFIX}{}", binding.element_id),
+                        "meta": {},
+                        "props": {
+                            "terminal": terminal,
+                            "normalizedAnchor": { "x": 0.5, "y": 0.5 },
+                            "isExact": false,
+                            "isPrecise": false,
+                        },
+                    }));
+                }
+            } else {
+                props.remove("fill");
+                props.insert("spline".into(), json!("line"));
+                props.insert(
+                    "points".into(),
+                    json!({
+                        "a1": { "id": "a1", "index": "a1", "x": 0, "y": 0 },
+                        "a2": { "id": "a2", "index": "a2", "x": line.points.end.x - origin.x, "y": line.points.end.y - origin.y },
+                    }),
+                );
+                records.push(shape_record(element, index, "line", origin, 0.0, Value::Object(props)));
+            }
+        }
+        ElementKind::Draw(draw) => {
+            let Some(origin) = draw.points.first().copied() else {
+                return;
+            };
+            let points: Vec<Value> = draw
+                .points
+                .iter()
+                .map(|point| json!({ "x": point.x - origin.x, "y": point.y - origin.y, "z": 0.5 }))
+                .collect();
+            props.insert("segments".into(), json!([{ "type": "free", "points": points }]));
+            props.insert("isComplete".into(), json!(true));
+            props.insert("isClosed".into(), json!(false));
+            props.insert("isPen".into(), json!(false));
+            records.push(shape_record(element, index, "draw", origin, 0.0, Value::Object(props)));
+        }
+        // Assets live in the .boardkit container and can't be referenced from a .tldr file
+        ElementKind::Image(_) => {}
+    }
+}
+
+/// Build a `.tldr` file from a board. Widgets and images are left out.
+fn to_tldraw(document: &BoardDocument) -> Value {
+    let mut records = vec![
+        json!({
+            "typeName": "document",
+            "id": "document:document",
+            "gridSize": 10,
+            "name": document.meta.title,
+            "meta": {},
+        }),
+        json!({
+            "typeName": "page",
+            "id": PAGE_ID,
+            "name": "Page 1",
+            "index": "a1",
+            "meta": {},
+        }),
+    ];
+
+    let mut elements: Vec<&Element> = document.board.elements.iter().collect();
+    elements.sort_by(|a, b| a.z_index.total_cmp(&b.z_index));
+    for (index, element) in elements.into_iter().enumerate() {
+        element_records(element, index, &mut records);
+    }
+
+    let sequences: Map<String, Value> = SCHEMA_SEQUENCES
+        .iter()
+        .map(|(name, version)| (name.to_string(), json!(version)))
+        .collect();
+    json!({
+        "tldrawFileFormatVersion": 1,
+        "schema": { "schemaVersion": 2, "sequences": sequences },
+        "records": records,
+    })
+}
+
+/// Write the board to `path` as a `.tldr` file.
+#[tauri::command]
+pub fn export_tldraw(board_json: BoardDocument, path: String) -> Result<()> {
+    let bytes = serde_json::to_vec_pretty(&to_tldraw(&board_json))?;
+    persist::write_atomic(Path::new(&path), &bytes).map_err(|err| Error::from_io(err, &path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(color: &str, stroke_width: f64, fill: bool, dash: StrokeDash) -> ElementStyle {
+        ElementStyle {
+            stroke_color: color.to_string(),
+            stroke_width,
+            fill_color: fill.then(|| color.to_string()),
+            opacity: 1.0,
+            stroke_dash: Some(dash),
+            roughness: Some(0.0),
+        }
+    }
+
+    fn element(id: &str, rect: Rect, z_index: f64, style: ElementStyle, kind: ElementKind) -> Element {
+        Element {
+            id: id.to_string(),
+            rect,
+            z_index,
+            style,
+            angle: None,
+            locked: None,
+            kind,
+        }
+    }
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    fn bound_to(id: &str) -> Option<ArrowBinding> {
+        Some(ArrowBinding {
+            element_id: id.to_string(),
+            anchor: AnchorPosition::Center,
+            offset: None,
+        })
+    }
+
+    #[test]
+    fn shapes_of_the_first_page_are_imported() {
+        let file = json!({
+            "records": [
+                { "typeName": "page", "id": "page:other", "index": "a2" },
+                { "typeName": "page", "id": "page:main", "index": "a1" },
+                {
+                    "typeName": "shape", "id": "shape:box", "type": "geo", "parentId": "page:main", "index": "a1",
+                    "x": 10, "y": 20, "rotation": 0,
+                    "props": {
+                        "geo": "rectangle", "w": 100, "h": 50, "color": "red", "fill": "solid", "dash": "dashed",
+                        "size": "l",
+                        "richText": { "type": "doc", "content": [
+                            { "type": "paragraph", "content": [{ "type": "text", "text": "Hello" }] },
+                            { "type": "paragraph", "content": [{ "type": "text", "text": "World" }] },
+                        ] },
+                    },
+                },
+                {
+                    "typeName": "shape", "id": "shape:group", "type": "group", "parentId": "page:main",
+                    "index": "a2", "x": 100, "y": 100, "rotation": 0, "props": {},
+                },
+                {
+                    "typeName": "shape", "id": "shape:note", "type": "note", "parentId": "shape:group",
+                    "index": "a1", "x": 10, "y": 10, "rotation": 0,
+                    "props": { "text": "Todo", "color": "yellow", "size": "m" },
+                },
+                {
+                    "typeName": "shape", "id": "shape:arrow", "type": "arrow", "parentId": "page:main",
+                    "index": "a3", "x": 200, "y": 0, "rotation": 0,
+                    "props": {
+                        "start": { "x": 0, "y": 0, "boundShapeId": "shape:note" },
+                        "end": { "x": 50, "y": 0 },
+                        "arrowheadStart": "none", "arrowheadEnd": "arrow", "color": "black", "size": "m",
+                    },
+                },
+                {
+                    "typeName": "binding", "id": "binding:end", "type": "arrow", "fromId": "shape:arrow",
+                    "toId": "shape:box", "props": { "terminal": "end" },
+                },
+                {
+                    "typeName": "shape", "id": "shape:picture", "type": "image", "parentId": "page:main",
+                    "index": "a4", "x": 0, "y": 0, "props": { "w": 10, "h": 10 },
+                },
+                {
+                    "typeName": "shape", "id": "shape:elsewhere", "type": "geo", "parentId": "page:other",
+                    "index": "a1", "x": 0, "y": 0, "props": { "geo": "rectangle", "w": 10, "h": 10 },
+                },
+            ],
+        });
+        let document = convert(&file, "Imported".to_string()).unwrap();
+
+        let note_style = ElementStyle {
+            fill_color: Some(NOTE_FILL.to_string()),
+            ..style("#f1ac4b", 3.5, false, StrokeDash::Solid)
+        };
+        let expected = vec![
+            element(
+                "box",
+                rect(10.0, 20.0, 100.0, 50.0),
+                0.0,
+                style("#e03131", 5.0, true, StrokeDash::Dashed),
+                ElementKind::Rectangle(ShapeProps {
+                    label: Some("Hello\nWorld".to_string()),
+                    corner_radius: None,
+                }),
+            ),
+            element(
+                "note",
+                rect(110.0, 110.0, NOTE_SIZE, NOTE_SIZE),
+                1.0,
+                note_style,
+                ElementKind::Rectangle(ShapeProps {
+                    label: Some("Todo".to_string()),
+                    corner_radius: Some(4.0),
+                }),
+            ),
+            element(
+                "arrow",
+                rect(200.0, 0.0, 50.0, 0.0),
+                2.0,
+                style("#1d1d1d", 3.5, false, StrokeDash::Solid),
+                ElementKind::Arrow(LineProps {
+                    points: LinePoints {
+                        start: Point { x: 200.0, y: 0.0 },
+                        end: Point { x: 250.0, y: 0.0 },
+                    },
+                    arrow_head: Some(ArrowHead::End),
+                    start_binding: bound_to("note"),
+                    end_binding: bound_to("box"),
+                }),
+            ),
+        ];
+        assert_eq!(document.meta.title, "Imported");
+        assert_eq!(document.board.elements, expected);
+    }
+
+    #[test]
+    fn files_without_records_are_rejected() {
+        assert!(matches!(convert(&json!({ "type": "excalidraw" }), String::new()), Err(Error::Import(_))));
+    }
+
+    #[test]
+    fn exported_boards_import_unchanged() {
+        let blue = || style("#4465e9", 3.5, false, StrokeDash::Solid);
+        let elements = vec![
+            element(
+                "a",
+                rect(0.0, 0.0, 100.0, 80.0),
+                0.0,
+                blue(),
+                ElementKind::Rectangle(ShapeProps {
+                    label: Some("A".to_string()),
+                    corner_radius: None,
+                }),
+            ),
+            element(
+                "b",
+                rect(200.0, 0.0, 80.0, 80.0),
+                1.0,
+                style("#099268", 2.0, true, StrokeDash::Dotted),
+                ElementKind::Ellipse(ShapeProps::default()),
+            ),
+            element(
+                "t",
+                rect(0.0, 200.0, 160.0, 24.0 * 1.35),
+                2.0,
+                blue(),
+                ElementKind::Text(TextProps {
+                    content: "Caption".to_string(),
+                    font_family: FontFamily::Serif,
+                    font_size: 24.0,
+                    font_weight: FontWeight::Normal,
+                    text_align: TextAlign::Center,
+                }),
+            ),
+            element(
+                "c",
+                rect(100.0, 40.0, 100.0, 0.0),
+                3.0,
+                blue(),
+                ElementKind::Arrow(LineProps {
+                    points: LinePoints {
+                        start: Point { x: 100.0, y: 40.0 },
+                        end: Point { x: 200.0, y: 40.0 },
+                    },
+                    arrow_head: Some(ArrowHead::End),
+                    start_binding: bound_to("a"),
+                    end_binding: bound_to("b"),
+                }),
+            ),
+        ];
+        let mut document = BoardDocument::new("Round trip", 1);
+        document.board.elements = elements.clone();
+
+        let imported = convert(&to_tldraw(&document), "Round trip".to_string()).unwrap();
+        assert_eq!(imported.board.elements, elements);
+    }
+
+    #[test]
+    fn rotated_shapes_keep_their_center() {
+        let mut document = BoardDocument::new("Rotated", 1);
+        document.board.elements.push(Element {
+            angle: Some(0.5),
+            ..element(
+                "r",
+                rect(40.0, 60.0, 100.0, 20.0),
+                0.0,
+                ElementStyle::default(),
+                ElementKind::Rectangle(ShapeProps::default()),
+            )
+        });
+
+        let imported = convert(&to_tldraw(&document), String::new()).unwrap();
+        let [element] = imported.board.elements.as_slice() else {
+            panic!("expected one element, got {:?}", imported.board.elements);
+        };
+        assert_eq!(element.angle, Some(0.5));
+        let (expected, actual) = (document.board.elements[0].rect, element.rect);
+        for (expected, actual) in [
+            (expected.x, actual.x),
+            (expected.y, actual.y),
+            (expected.width, actual.width),
+            (expected.height, actual.height),
+        ] {
+            assert!((expected - actual).abs() < 1e-9, "{expected} != {actual}");
+        }
+    }
+}
@@ -0,0 +1,492 @@
+//! Incremental saves. Instead of rewriting the whole container after every
+//! edit, the save worker appends the changes since the last save as a
+//! `journal/<seq>.json` entry, plus any newly embedded images, and rewrites
+//! the container in full once the journal grows too long.
+//!
+//! Readers replay the journal over `board.json` (see `board::read_document`),
+//! and every full rewrite folds it back in, so tools that read `board.json`
+//! directly only lag behind until the next compaction.
+
+use std::{collections::HashMap, fs, sync::Mutex, time::SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    board,
+    error::{Error, Result},
+};
+
+pub const JOURNAL_FOLDER: &str = "journal/";
+/// Compact after this many appended saves, bounding the replay on open.
+const MAX_ENTRIES: usize = 200;
+/// Compact once the journal outweighs 1/N of the file, so appending never
+/// costs more overall than rewriting would.
+const MAX_JOURNAL_SHARE: u64 = 4;
+
+/// One change to the document, addressed by JSON pointer.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum Op {
+    /// Set an object key, or replace an array item.
+    Set { path: String, value: Value },
+    Remove { path: String },
+    /// Replace `remove` items of the array at `path`, from `index`, with `insert`.
+    Splice {
+        path: String,
+        index: usize,
+        remove: usize,
+        insert: Vec<Value>,
+    },
+}
+
+pub fn is_entry(name: &str) -> bool {
+    name.starts_with(JOURNAL_FOLDER)
+}
+
+/// Zero-padded so entries sort in replay order.
+fn entry_name(seq: usize) -> String {
+    format!("{JOURNAL_FOLDER}{seq:08}.json")
+}
+
+fn push_key(path: &str, key: &str) -> String {
+    format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+fn diff_into(before: &Value, after: &Value, path: &str, ops: &mut Vec<Op>) {
+    if before == after {
+        return;
+    }
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            for key in before.keys().filter(|key| !after.contains_key(*key)) {
+                ops.push(Op::Remove {
+                    path: push_key(path, key),
+                });
+            }
+            for (key, value) in after {
+                match before.get(key) {
+                    Some(old) => diff_into(old, value, &push_key(path, key), ops),
+                    None => ops.push(Op::Set {
+                        path: push_key(path, key),
+                        value: value.clone(),
+                    }),
+                }
+            }
+        }
+        (Value::Array(before), Value::Array(after)) => {
+            // Trim the common ends; adding, deleting or reordering a few
+            // elements then only touches the span in between
+            let prefix = before.iter().zip(after).take_while(|(old, new)| old == new).count();
+            let max_suffix = before.len().min(after.len()) - prefix;
+            let suffix = before
+                .iter()
+                .rev()
+                .zip(after.iter().rev())
+                .take(max_suffix)
+                .take_while(|(old, new)| old == new)
+                .count();
+            let old = &before[prefix..before.len() - suffix];
+            let new = &after[prefix..after.len() - suffix];
+            if old.len() == new.len() {
+                for (offset, (old, new)) in old.iter().zip(new).enumerate() {
+                    diff_into(old, new, &format!("{path}/{}", prefix + offset), ops);
+                }
+            } else {
+                ops.push(Op::Splice {
+                    path: path.to_string(),
+                    index: prefix,
+                    remove: old.len(),
+                    insert: new.to_vec(),
+                });
+            }
+        }
+        _ => ops.push(Op::Set {
+            path: path.to_string(),
+            value: after.clone(),
+        }),
+    }
+}
+
+/// Operations turning `before` into `after`.
+pub fn diff(before: &Value, after: &Value) -> Vec<Op> {
+    let mut ops = Vec::new();
+    diff_into(before, after, "", &mut ops);
+    ops
+}
+
+fn corrupted(path: &str) -> Error {
+    Error::InvalidBoard(format!("corrupted journal at {path:?}"))
+}
+
+/// Split a pointer into its parent and unescaped last key.
+fn split_pointer(path: &str) -> Option<(&str, String)> {
+    let (parent, key) = path.rsplit_once('/')?;
+    Some((parent, key.replace("~1", "/").replace("~0", "~")))
+}
+
+pub fn apply_op(document: &mut Value, op: Op) -> Result<()> {
+    match op {
+        Op::Set { path, value } if path.is_empty() => *document = value,
+        Op::Set { path, value } => {
+            let (parent, key) = split_pointer(&path).ok_or_else(|| corrupted(&path))?;
+            match document.pointer_mut(parent) {
+                Some(Value::Object(object)) => {
+                    object.insert(key, value);
+                }
+                Some(Value::Array(items)) => {
+                    let index: usize = key.parse().map_err(|_| corrupted(&path))?;
+                    match index.cmp(&items.len()) {
+                        std::cmp::Ordering::Less => items[index] = value,
+                        std::cmp::Ordering::Equal => items.push(value),
+                        std::cmp::Ordering::Greater => return Err(corrupted(&path)),
+                    }
+                }
+                _ => return Err(corrupted(&path)),
+            }
+        }
+        Op::Remove { path } => {
+            let (parent, key) = split_pointer(&path).ok_or_else(|| corrupted(&path))?;
+            let removed = match document.pointer_mut(parent) {
+                Some(Value::Object(object)) => object.remove(&key).is_some(),
+                Some(Value::Array(items)) => match key.parse::<usize>() {
+                    Ok(index) if index < items.len() => {
+                        items.remove(index);
+                        true
+                    }
+                    _ => false,
+                },
+                _ => false,
+            };
+            if !removed {
+                return Err(corrupted(&path));
+            }
+        }
+        Op::Splice {
+            path,
+            index,
+            remove,
+            insert,
+        } => {
+            let Some(Value::Array(items)) = document.pointer_mut(&path) else {
+                return Err(corrupted(&path));
+            };
+            if index + remove > items.len() {
+                return Err(corrupted(&path));
+            }
+            items.splice(index..index + remove, insert);
+        }
+    }
+    Ok(())
+}
+
+/// Replay one journal entry over the document.
+pub fn apply(document: &mut Value, entry: &[u8]) -> Result<()> {
+    let ops: Vec<Op> = serde_json::from_slice(entry).map_err(|_| corrupted(""))?;
+    ops.into_iter().try_for_each(|op| apply_op(document, op))
+}
+
+/// A board as this process last wrote it. The journal is only appended to
+/// while the file is still exactly that, so a write by anything else falls
+/// back to a full rewrite.
+struct Tracked {
+    document: Value,
+    len: u64,
+    modified: SystemTime,
+    entries: usize,
+    journal_size: u64,
+}
+
+impl Tracked {
+    fn matches(&self, metadata: &fs::Metadata) -> bool {
+        metadata.len() == self.len && metadata.modified().is_ok_and(|modified| modified == self.modified)
+    }
+}
+
+pub struct Saved {
+    /// Size of the board file afterwards.
+    pub size: u64,
+    /// Bytes written to disk by this save.
+    pub written: u64,
+    pub incremental: bool,
+}
+
+/// Boards saved this session, keyed by path. The first save of a board in
+/// a session always writes a snapshot, which also folds in any journal left
+/// by the previous one.
+#[derive(Default)]
+pub struct Journals {
+    boards: Mutex<HashMap<String, Tracked>>,
+}
+
+impl Journals {
+    /// Save `document` to `path`, by appending to the journal when possible
+    /// and otherwise by writing `bytes`, the complete container.
+    pub fn save(&self, path: &str, bytes: &[u8], document: &Value) -> Result<Saved> {
+        let mut boards = self.boards.lock().unwrap();
+        let metadata = fs::metadata(path).ok();
+        let tracked = boards
+            .remove(path)
+            .filter(|tracked| metadata.as_ref().is_some_and(|metadata| tracked.matches(metadata)))
+            .filter(|tracked| tracked.entries < MAX_ENTRIES);
+
+        if let Some(mut tracked) = tracked {
+            let ops = diff(&tracked.document, document);
+            let entry = (!ops.is_empty()).then(|| serde_json::to_vec(&ops)).transpose()?;
+            let within_share = (tracked.journal_size + entry.as_ref().map_or(0, |entry| entry.len() as u64))
+                * MAX_JOURNAL_SHARE
+                <= tracked.len;
+            if within_share {
+                let name = entry_name(tracked.entries + 1);
+                match board::append_to_board(path, bytes, entry.as_ref().map(|entry| (name.as_str(), &entry[..]))) {
+                    Ok(written) => {
+                        let metadata = fs::metadata(path)?;
+                        tracked.len = metadata.len();
+                        tracked.modified = metadata.modified()?;
+                        tracked.entries += usize::from(entry.is_some());
+                        tracked.journal_size += written;
+                        tracked.document = document.clone();
+                        boards.insert(path.to_string(), tracked);
+                        return Ok(Saved {
+                            size: metadata.len(),
+                            written,
+                            incremental: true,
+                        });
+                    }
+                    // A failed append is rolled back; the snapshot below saves
+                    // the changes instead
+                    Err(err) => tracing::warn!(path, "appending to the journal failed: {err}"),
+                }
+            }
+        }
+
+        board::write_board(path, bytes)?;
+        let metadata = fs::metadata(path)?;
+        boards.insert(
+            path.to_string(),
+            Tracked {
+                document: document.clone(),
+                len: metadata.len(),
+                modified: metadata.modified()?,
+                entries: 0,
+                journal_size: 0,
+            },
+        );
+        Ok(Saved {
+            size: metadata.len(),
+            written: bytes.len() as u64,
+            incremental: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::document::CURRENT_DOCUMENT_VERSION;
+
+    fn round_trip(before: Value, after: Value) -> Vec<Op> {
+        let ops = diff(&before, &after);
+        // Through JSON, as the entries are stored
+        let entry = serde_json::to_vec(&ops).unwrap();
+        let mut replayed = before;
+        apply(&mut replayed, &entry).unwrap();
+        assert_eq!(replayed, after);
+        ops
+    }
+
+    #[test]
+    fn equal_documents_need_no_ops() {
+        let document = json!({ "a": [1, { "b": 2 }] });
+        assert!(round_trip(document.clone(), document).is_empty());
+    }
+
+    #[test]
+    fn object_edits_round_trip() {
+        let ops = round_trip(
+            json!({ "keep": 1, "change": { "deep": "old" }, "drop": true }),
+            json!({ "keep": 1, "change": { "deep": "new" }, "add": [1] }),
+        );
+        assert_eq!(ops.len(), 3);
+        assert!(matches!(&ops[0], Op::Remove { path } if path == "/drop"));
+    }
+
+    #[test]
+    fn keys_are_escaped() {
+        round_trip(json!({ "a/b": 1, "c~d": 1 }), json!({ "a/b": 2, "c~d": 2, "e/~f": 3 }));
+    }
+
+    #[test]
+    fn replacing_the_root_or_changing_types_round_trips() {
+        round_trip(json!([1, 2]), json!({ "now": "an object" }));
+        round_trip(json!({ "a": [1] }), json!({ "a": "text" }));
+    }
+
+    #[test]
+    fn array_item_edits_stay_in_place() {
+        let ops = round_trip(
+            json!({ "items": [{ "id": 1, "x": 0 }, { "id": 2, "x": 0 }] }),
+            json!({ "items": [{ "id": 1, "x": 0 }, { "id": 2, "x": 5 }] }),
+        );
+        assert!(matches!(&ops[..], [Op::Set { path, .. }] if path == "/items/1/x"));
+    }
+
+    #[test]
+    fn array_inserts_and_deletes_splice_the_changed_span() {
+        let ops = round_trip(json!([1, 2, 3, 4]), json!([1, 9, 9, 2, 3, 4]));
+        assert!(matches!(&ops[..], [Op::Splice { index: 1, remove: 0, insert, .. }] if insert.len() == 2));
+        let ops = round_trip(json!([1, 2, 3, 4]), json!([1, 4]));
+        assert!(matches!(&ops[..], [Op::Splice { index: 1, remove: 2, insert, .. }] if insert.is_empty()));
+    }
+
+    #[test]
+    fn array_reorders_and_appends_round_trip() {
+        round_trip(json!([1, 2, 3, 4, 5]), json!([1, 4, 2, 3, 5]));
+        round_trip(json!([1]), json!([1, 2, 3]));
+        round_trip(json!([1, 2, 3]), json!([]));
+        round_trip(json!([1, 1, 1]), json!([1, 1]));
+    }
+
+    #[test]
+    fn corrupted_entries_are_rejected() {
+        let mut document = json!({ "items": [1] });
+        assert!(apply(&mut document, b"not json").is_err());
+        let remove = serde_json::to_vec(&[Op::Remove { path: "/missing".into() }]).unwrap();
+        assert!(apply(&mut document, &remove).is_err());
+        let splice = serde_json::to_vec(&[Op::Splice {
+            path: "/items".into(),
+            index: 1,
+            remove: 1,
+            insert: Vec::new(),
+        }])
+        .unwrap();
+        assert!(apply(&mut document, &splice).is_err());
+        let set = serde_json::to_vec(&[Op::Set {
+            path: "/items/3".into(),
+            value: json!(0),
+        }])
+        .unwrap();
+        assert!(apply(&mut document, &set).is_err());
+        assert_eq!(document, json!({ "items": [1] }));
+    }
+
+    fn document(elements: usize, text: &str) -> Value {
+        json!({
+            "version": CURRENT_DOCUMENT_VERSION,
+            "meta": { "title": "Test", "createdAt": 0, "updatedAt": 0 },
+            "board": {
+                "elements": (0..elements).map(|id| json!({ "id": id, "text": text })).collect::<Vec<_>>(),
+                "widgets": [],
+            },
+        })
+    }
+
+    fn save(journals: &Journals, path: &str, document: &Value) -> Saved {
+        let bytes = board::create_container(document).unwrap();
+        journals.save(path, &bytes, document).unwrap()
+    }
+
+    fn board_path(dir: &tempfile::TempDir) -> String {
+        dir.path().join("board.boardkit").to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn first_save_of_a_session_is_a_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = board_path(&dir);
+        let journals = Journals::default();
+        assert!(!save(&journals, &path, &document(1, "a")).incremental);
+        assert!(save(&journals, &path, &document(2, "a")).incremental);
+        assert!(!save(&Journals::default(), &path, &document(3, "a")).incremental);
+        assert_eq!(board::read_board_document(&path).unwrap(), document(3, "a"));
+    }
+
+    /// Text that doesn't compress, so the file is large enough for the
+    /// journal to stay within its share.
+    fn noise(len: usize) -> String {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                char::from(b'a' + (state % 26) as u8)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compacts_after_max_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = board_path(&dir);
+        let journals = Journals::default();
+        let padding = noise(256 * 1024);
+        save(&journals, &path, &document(1, &padding));
+
+        for step in 0..10 {
+            let mut edited = document(1, &padding);
+            edited["board"]["elements"][0]["x"] = json!(step);
+            assert!(save(&journals, &path, &edited).incremental, "save {step} rewrote the board");
+            assert_eq!(board::read_board_document(&path).unwrap(), edited);
+        }
+        assert_eq!(journals.boards.lock().unwrap()[&path].entries, 10);
+
+        // Skip ahead rather than append all of them
+        journals.boards.lock().unwrap().get_mut(&path).unwrap().entries = MAX_ENTRIES;
+        let compacted = document(2, &padding);
+        assert!(!save(&journals, &path, &compacted).incremental);
+        let archive = zip::ZipArchive::new(fs::File::open(&path).unwrap()).unwrap();
+        assert!(!archive.file_names().any(is_entry));
+        assert_eq!(board::read_board_document(&path).unwrap(), compacted);
+
+        // And the journal starts over
+        let mut edited = compacted;
+        edited["meta"]["title"] = json!("Edited");
+        assert!(save(&journals, &path, &edited).incremental);
+        assert_eq!(journals.boards.lock().unwrap()[&path].entries, 1);
+        assert_eq!(board::read_board_document(&path).unwrap(), edited);
+    }
+
+    #[test]
+    fn compacts_when_the_journal_outweighs_its_share() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = board_path(&dir);
+        let journals = Journals::default();
+        save(&journals, &path, &document(1, "small"));
+        let size = fs::metadata(&path).unwrap().len() as usize;
+
+        // An edit larger than a quarter of the file goes into a snapshot
+        let large = document(1, &"y".repeat(size));
+        let saved = save(&journals, &path, &large);
+        assert!(!saved.incremental);
+        assert_eq!(board::read_board_document(&path).unwrap(), large);
+
+        // Small edits keep appending until they add up to the share
+        let mut saves = 0;
+        loop {
+            let mut edited = large.clone();
+            edited["meta"]["title"] = json!(format!("{saves:0>64}"));
+            let saved = save(&journals, &path, &edited);
+            assert_eq!(board::read_board_document(&path).unwrap(), edited);
+            if !saved.incremental {
+                break;
+            }
+            saves += 1;
+            assert!(saves < MAX_ENTRIES, "the journal never compacted");
+        }
+        assert!(saves > 0);
+    }
+
+    #[test]
+    fn writes_by_others_force_a_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = board_path(&dir);
+        let journals = Journals::default();
+        save(&journals, &path, &document(1, "a"));
+        fs::write(&path, board::create_container(&document(5, "other")).unwrap()).unwrap();
+        assert!(!save(&journals, &path, &document(2, "a")).incremental);
+        assert_eq!(board::read_board_document(&path).unwrap(), document(2, "a"));
+    }
+}
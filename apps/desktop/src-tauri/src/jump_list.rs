@@ -0,0 +1,266 @@
+use std::{path::Path, sync::Mutex};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{file_open, recent::RecentFiles, windows};
+
+/// Passed by the jump list's New Board task; a second launch forwards it.
+pub const NEW_BOARD_ARG: &str = "--new-board";
+
+/// What an entry of the jump list or dock menu opens.
+#[derive(Clone)]
+enum Entry {
+    NewBoard,
+    Board(String),
+}
+
+/// Recent boards the OS lists were last built from, so menu rebuilds for
+/// other reasons don't rewrite them.
+#[derive(Default)]
+pub struct JumpList {
+    applied: Mutex<Option<Vec<String>>>,
+}
+
+fn board_label(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn entries(boards: &[String]) -> Vec<(String, Entry)> {
+    std::iter::once(("New Board".to_string(), Entry::NewBoard))
+        .chain(boards.iter().map(|path| (board_label(path), Entry::Board(path.clone()))))
+        .collect()
+}
+
+/// Ask the frontend for a new board, bringing its window up first since the
+/// app isn't focused when the dock or taskbar is used.
+pub fn new_board(app: &AppHandle) {
+    if let Some(window) = windows::target_window(app) {
+        windows::focus_window(&window);
+        let _ = window.emit_to(window.label(), "menu-new-board", ());
+    }
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn open(app: &AppHandle, entry: Entry) {
+    match entry {
+        Entry::NewBoard => new_board(app),
+        Entry::Board(path) => file_open::open_board_files(app, vec![path]),
+    }
+}
+
+/// The dock menu comes from the app delegate, which tao owns, so the
+/// `applicationDockMenu:` it never implements is added to its class.
+#[cfg(target_os = "macos")]
+mod dock {
+    use std::{
+        cell::RefCell,
+        sync::{Once, OnceLock},
+    };
+
+    use objc2::{
+        ffi,
+        rc::Retained,
+        runtime::{AnyObject, Imp, Sel},
+        sel, MainThreadMarker, MainThreadOnly,
+    };
+    use objc2_app_kit::{NSApplication, NSMenu, NSMenuItem};
+    use objc2_foundation::NSString;
+    use tauri::AppHandle;
+
+    use super::Entry;
+
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+    static INSTALL: Once = Once::new();
+
+    thread_local! {
+        /// The menu AppKit asks for, and what each item's tag opens
+        static DOCK_MENU: RefCell<Option<(Retained<NSMenu>, Vec<Entry>)>> = const { RefCell::new(None) };
+    }
+
+    extern "C-unwind" fn dock_menu(_this: &AnyObject, _cmd: Sel, _sender: &AnyObject) -> *mut NSMenu {
+        DOCK_MENU.with_borrow(|menu| {
+            menu.as_ref()
+                .map_or(std::ptr::null_mut(), |(menu, _)| Retained::as_ptr(menu).cast_mut())
+        })
+    }
+
+    extern "C-unwind" fn select_item(_this: &AnyObject, _cmd: Sel, sender: &NSMenuItem) {
+        let entry = DOCK_MENU.with_borrow(|menu| {
+            let (_, entries) = menu.as_ref()?;
+            entries.get(usize::try_from(sender.tag()).ok()?).cloned()
+        });
+        if let (Some(app), Some(entry)) = (APP.get(), entry) {
+            super::open(app, entry);
+        }
+    }
+
+    fn install(mtm: MainThreadMarker) {
+        let application = NSApplication::sharedApplication(mtm);
+        let Some(delegate) = application.delegate() else {
+            tracing::warn!("dock menu: no app delegate");
+            return;
+        };
+        let object: &AnyObject = (*delegate).as_ref();
+        let class = object.class() as *const _ as *mut _;
+        unsafe {
+            let dock_menu: Imp =
+                std::mem::transmute(dock_menu as extern "C-unwind" fn(&AnyObject, Sel, &AnyObject) -> *mut NSMenu);
+            let select_item: Imp =
+                std::mem::transmute(select_item as extern "C-unwind" fn(&AnyObject, Sel, &NSMenuItem));
+            ffi::class_addMethod(class, sel!(applicationDockMenu:), dock_menu, c"@@:@".as_ptr());
+            ffi::class_addMethod(class, sel!(boardkitDockItemSelected:), select_item, c"v@:@".as_ptr());
+        }
+        // AppKit checks what the delegate responds to when it is set
+        application.setDelegate(None);
+        application.setDelegate(Some(&delegate));
+    }
+
+    pub fn apply(app: &AppHandle, entries: Vec<(String, Entry)>) -> tauri::Result<()> {
+        let _ = APP.set(app.clone());
+        app.run_on_main_thread(move || {
+            let Some(mtm) = MainThreadMarker::new() else {
+                return;
+            };
+            INSTALL.call_once(|| install(mtm));
+            let target = NSApplication::sharedApplication(mtm).delegate();
+            let menu = NSMenu::new(mtm);
+            for (tag, (label, _)) in entries.iter().enumerate() {
+                let item = unsafe {
+                    NSMenuItem::initWithTitle_action_keyEquivalent(
+                        NSMenuItem::alloc(mtm),
+                        &NSString::from_str(label),
+                        Some(sel!(boardkitDockItemSelected:)),
+                        &NSString::new(),
+                    )
+                };
+                item.setTag(tag as isize);
+                unsafe { item.setTarget(target.as_deref().map(|delegate| delegate.as_ref())) };
+                menu.addItem(&item);
+                if tag == 0 && entries.len() > 1 {
+                    menu.addItem(&NSMenuItem::separatorItem(mtm));
+                }
+            }
+            let entries = entries.into_iter().map(|(_, entry)| entry).collect();
+            DOCK_MENU.with_borrow_mut(|menu_slot| *menu_slot = Some((menu, entries)));
+        })
+    }
+}
+
+/// Jump list entries relaunch the app, which forwards the arguments to the
+/// running instance: recent boards as their path, New Board as a flag.
+#[cfg(target_os = "windows")]
+mod taskbar {
+    use ::windows::{
+        core::{Interface, HSTRING},
+        Win32::{
+            Storage::EnhancedStorage::PKEY_Title,
+            System::Com::{
+                CoCreateInstance, CoInitializeEx, StructuredStorage::PROPVARIANT, CLSCTX_INPROC_SERVER,
+                COINIT_APARTMENTTHREADED,
+            },
+            UI::Shell::{
+                Common::{IObjectArray, IObjectCollection},
+                DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW,
+                PropertiesSystem::IPropertyStore, ShellLink,
+            },
+        },
+    };
+
+    use super::{Entry, NEW_BOARD_ARG};
+
+    fn link(title: &str, arguments: &str) -> ::windows::core::Result<IShellLinkW> {
+        unsafe {
+            let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+            let exe = std::env::current_exe()?;
+            link.SetPath(&HSTRING::from(exe.as_os_str()))?;
+            link.SetArguments(&HSTRING::from(arguments))?;
+            link.SetIconLocation(&HSTRING::from(exe.as_os_str()), 0)?;
+            link.SetDescription(&HSTRING::from(title))?;
+            let properties: IPropertyStore = link.cast()?;
+            properties.SetValue(&PKEY_Title, &PROPVARIANT::from(title))?;
+            properties.Commit()?;
+            Ok(link)
+        }
+    }
+
+    fn arguments(link: &IShellLinkW) -> String {
+        let mut buffer = [0u16; 1024];
+        match unsafe { link.GetArguments(&mut buffer) } {
+            Ok(()) => String::from_utf16_lossy(&buffer[..buffer.iter().position(|&c| c == 0).unwrap_or(0)]),
+            Err(_) => String::new(),
+        }
+    }
+
+    fn quoted(path: &str) -> String {
+        format!("\"{path}\"")
+    }
+
+    /// Boards the user removed from the jump list can't be added back until
+    /// they are opened again, or the whole category is rejected.
+    pub fn apply(entries: Vec<(String, Entry)>) -> ::windows::core::Result<()> {
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+            let list: ICustomDestinationList = CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+            let mut max_slots = 0u32;
+            let removed: IObjectArray = list.BeginList(&mut max_slots)?;
+            let removed: Vec<String> = (0..removed.GetCount()?)
+                .filter_map(|index| removed.GetAt::<IShellLinkW>(index).ok())
+                .map(|link| arguments(&link))
+                .collect();
+
+            let tasks: IObjectCollection = CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+            let boards: IObjectCollection = CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+            let mut board_count = 0;
+            for (label, entry) in &entries {
+                match entry {
+                    Entry::NewBoard => tasks.AddObject(&link(label, NEW_BOARD_ARG)?)?,
+                    Entry::Board(path) => {
+                        let arguments = quoted(path);
+                        if board_count < max_slots as usize && !removed.contains(&arguments) {
+                            boards.AddObject(&link(label, &arguments)?)?;
+                            board_count += 1;
+                        }
+                    }
+                }
+            }
+            if board_count > 0 {
+                list.AppendCategory(&HSTRING::from("Recent Boards"), &boards.cast::<IObjectArray>()?)?;
+            }
+            list.AddUserTasks(&tasks.cast::<IObjectArray>()?)?;
+            list.CommitList()
+        }
+    }
+}
+
+/// Rebuild the Windows jump list or macOS dock menu from the recent boards.
+/// Linux launchers only offer the static actions of the desktop entry.
+pub fn refresh(app: &AppHandle) {
+    let Some(jump_list) = app.try_state::<JumpList>() else {
+        return;
+    };
+    let boards = app.try_state::<RecentFiles>().map(|recent| recent.entries()).unwrap_or_default();
+    let mut applied = jump_list.applied.lock().unwrap();
+    if applied.as_ref() == Some(&boards) {
+        return;
+    }
+    let entries = entries(&boards);
+    *applied = Some(boards);
+    drop(applied);
+
+    #[cfg(target_os = "macos")]
+    if let Err(err) = dock::apply(app, entries) {
+        tracing::warn!("failed to update the dock menu: {err}");
+    }
+    // COM calls block on the shell, so keep them off the caller's thread
+    #[cfg(target_os = "windows")]
+    std::thread::spawn(move || {
+        if let Err(err) = taskbar::apply(entries) {
+            tracing::warn!("failed to update the jump list: {err}");
+        }
+    });
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    drop(entries);
+}
@@ -0,0 +1,149 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::Mutex};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    error::{Error, Result},
+    menu, persist,
+    shortcuts::{self, GlobalShortcuts},
+};
+
+const KEYMAP_STORE: &str = "keymap.json";
+
+/// Menu items that can be rebound, with their default accelerators.
+const DEFAULT_KEYMAP: &[(&str, Option<&str>)] = &[
+    ("new_board", Some("CmdOrCtrl+N")),
+    ("open_file", Some("CmdOrCtrl+O")),
+    ("save", Some("CmdOrCtrl+S")),
+    ("export", Some("CmdOrCtrl+Shift+E")),
+    ("print", Some("CmdOrCtrl+P")),
+    ("undo", Some("CmdOrCtrl+Z")),
+    ("redo", Some("CmdOrCtrl+Shift+Z")),
+    ("character_palette", None),
+    ("command_palette", Some("CmdOrCtrl+K")),
+    ("zoom_in", Some("CmdOrCtrl+=")),
+    ("zoom_out", Some("CmdOrCtrl+-")),
+    ("zoom_to_fit", Some("CmdOrCtrl+Shift+1")),
+    ("zoom_to_selection", Some("CmdOrCtrl+Shift+2")),
+    ("reset_view", Some("CmdOrCtrl+0")),
+    ("toggle_grid", None),
+    ("start_presentation", Some("CmdOrCtrl+Alt+P")),
+];
+
+fn default_accelerator(menu_id: &str) -> Option<Option<&'static str>> {
+    DEFAULT_KEYMAP.iter().find(|(id, _)| *id == menu_id).map(|(_, accelerator)| *accelerator)
+}
+
+/// A menu shortcut as reported to the settings UI.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MenuShortcut {
+    menu_id: &'static str,
+    accelerator: Option<String>,
+    is_default: bool,
+}
+
+/// User overrides of menu accelerators, persisted in the app data dir. Only
+/// changed items are stored (`null` unbinds one), so new defaults still apply.
+pub struct Keymap {
+    store_path: PathBuf,
+    overrides: Mutex<BTreeMap<String, Option<String>>>,
+}
+
+impl Keymap {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let store_path = persist::app_data_file(app, KEYMAP_STORE)?;
+        let overrides = persist::read_json(&store_path);
+        Ok(Self {
+            store_path,
+            overrides: Mutex::new(overrides),
+        })
+    }
+
+    /// Effective accelerator of a menu item, `None` when unbound.
+    pub fn accelerator(&self, menu_id: &str) -> Option<String> {
+        match self.overrides.lock().unwrap().get(menu_id) {
+            Some(accelerator) => accelerator.clone(),
+            None => default_accelerator(menu_id).flatten().map(str::to_string),
+        }
+    }
+
+    fn shortcuts(&self) -> Vec<MenuShortcut> {
+        let overrides = self.overrides.lock().unwrap();
+        DEFAULT_KEYMAP
+            .iter()
+            .map(|(menu_id, default)| match overrides.get(*menu_id) {
+                Some(accelerator) => MenuShortcut {
+                    menu_id,
+                    accelerator: accelerator.clone(),
+                    is_default: false,
+                },
+                None => MenuShortcut {
+                    menu_id,
+                    accelerator: default.map(str::to_string),
+                    is_default: true,
+                },
+            })
+            .collect()
+    }
+}
+
+/// Accelerator for `menu_id`, falling back to the default before the keymap is loaded.
+pub fn accelerator(app: &AppHandle, menu_id: &str) -> Option<String> {
+    match app.try_state::<Keymap>() {
+        Some(keymap) => keymap.accelerator(menu_id),
+        None => default_accelerator(menu_id).flatten().map(str::to_string),
+    }
+}
+
+#[tauri::command]
+pub fn get_keymap(keymap: State<'_, Keymap>) -> Vec<MenuShortcut> {
+    keymap.shortcuts()
+}
+
+/// Rebind a menu item (`None` removes its shortcut) and rebuild the menu.
+/// Fails when another menu item or a global shortcut already uses the accelerator.
+#[tauri::command]
+pub fn set_shortcut(
+    app: AppHandle,
+    keymap: State<'_, Keymap>,
+    menu_id: String,
+    accelerator: Option<String>,
+) -> Result<Vec<MenuShortcut>> {
+    let Some(default) = default_accelerator(&menu_id) else {
+        return Err(Error::UnknownMenuItem(menu_id));
+    };
+
+    if let Some(accelerator) = &accelerator {
+        let shortcut = shortcuts::parse(accelerator)?;
+        let taken = |bound: &str| shortcuts::parse(bound).is_ok_and(|bound| bound.id() == shortcut.id());
+        if let Some(other) = keymap
+            .shortcuts()
+            .into_iter()
+            .find(|other| other.menu_id != menu_id && other.accelerator.as_deref().is_some_and(taken))
+        {
+            return Err(Error::ShortcutConflict(format!("{accelerator} is already bound to {}", other.menu_id)));
+        }
+        if let Some((action, _)) = app
+            .state::<GlobalShortcuts>()
+            .bindings()
+            .into_iter()
+            .find(|(_, bound)| taken(bound))
+        {
+            return Err(Error::ShortcutConflict(format!("{accelerator} is already the global shortcut for {action:?}")));
+        }
+    }
+
+    {
+        let mut overrides = keymap.overrides.lock().unwrap();
+        if accelerator.as_deref() == default {
+            overrides.remove(&menu_id);
+        } else {
+            overrides.insert(menu_id, accelerator);
+        }
+        persist::write_json(&keymap.store_path, &*overrides)?;
+    }
+    menu::refresh(&app)?;
+    Ok(keymap.shortcuts())
+}
@@ -1,94 +1,107 @@
-use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
-    Emitter, Manager,
-};
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+mod accessibility;
+mod assets;
+mod automation;
+mod autosave;
+mod board;
+mod character_palette;
+mod cli;
+mod clipboard;
+mod context_menu;
+mod deep_link;
+mod diagnostics;
+mod diagram;
+mod diff;
+mod dirty;
+mod document;
+mod encryption;
+mod error;
+mod export;
+mod eyedropper;
+mod file_drop;
+mod file_icons;
+mod file_metadata;
+mod file_open;
+mod git;
+mod graph_layout;
+mod headless;
+mod history;
+mod http;
+mod i18n;
+mod image_pipeline;
+mod import;
+mod journal;
+mod jump_list;
+mod keymap;
+mod library;
+mod links;
+mod locks;
+mod logging;
+mod menu;
+mod merge;
+mod migrations;
+mod notifications;
+mod ocr;
+mod palette;
+mod persist;
+mod plugins;
+mod power;
+mod presentation;
+mod qr;
+mod quick_capture;
+mod recent;
+mod save_worker;
+mod screenshot;
+mod search;
+mod secrets;
+mod session;
+mod settings;
+mod share;
+mod shortcuts;
+mod spatial;
+mod spellcheck;
+mod stats;
+mod streaming;
+mod taskbar;
+mod templates;
+mod thumbnails;
+mod timeline;
+mod trash;
+mod tray;
+mod undo;
+mod updater;
+mod watcher;
+mod window_state;
+mod windows;
+mod workspace;
 
-fn create_menu(app: &tauri::AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
-    let app_menu = Submenu::with_items(
-        app,
-        "Boardkit",
-        true,
-        &[
-            &PredefinedMenuItem::about(app, Some("About Boardkit"), None)?,
-            &PredefinedMenuItem::separator(app)?,
-            &PredefinedMenuItem::services(app, None)?,
-            &PredefinedMenuItem::separator(app)?,
-            &PredefinedMenuItem::hide(app, None)?,
-            &PredefinedMenuItem::hide_others(app, None)?,
-            &PredefinedMenuItem::show_all(app, None)?,
-            &PredefinedMenuItem::separator(app)?,
-            &PredefinedMenuItem::quit(app, None)?,
-        ],
-    )?;
-
-    let new_board = MenuItem::with_id(app, "new_board", "New Board", true, Some("CmdOrCtrl+N"))?;
-    let open_file = MenuItem::with_id(app, "open_file", "Open...", true, Some("CmdOrCtrl+O"))?;
-    let save = MenuItem::with_id(app, "save", "Save", true, Some("CmdOrCtrl+S"))?;
-    let export = MenuItem::with_id(app, "export", "Export as .boardkit", true, Some("CmdOrCtrl+Shift+E"))?;
-
-    let file_menu = Submenu::with_items(
-        app,
-        "File",
-        true,
-        &[
-            &new_board,
-            &open_file,
-            &PredefinedMenuItem::separator(app)?,
-            &save,
-            &export,
-        ],
-    )?;
-
-    let edit_menu = Submenu::with_items(
-        app,
-        "Edit",
-        true,
-        &[
-            &PredefinedMenuItem::undo(app, None)?,
-            &PredefinedMenuItem::redo(app, None)?,
-            &PredefinedMenuItem::separator(app)?,
-            &PredefinedMenuItem::cut(app, None)?,
-            &PredefinedMenuItem::copy(app, None)?,
-            &PredefinedMenuItem::paste(app, None)?,
-            &PredefinedMenuItem::select_all(app, None)?,
-        ],
-    )?;
-
-    let command_palette = MenuItem::with_id(app, "command_palette", "Command Palette...", true, Some("CmdOrCtrl+K"))?;
-    let reset_view = MenuItem::with_id(app, "reset_view", "Reset View", true, Some("CmdOrCtrl+0"))?;
-
-    let view_menu = Submenu::with_items(
-        app,
-        "View",
-        true,
-        &[
-            &command_palette,
-            &PredefinedMenuItem::separator(app)?,
-            &reset_view,
-            &PredefinedMenuItem::separator(app)?,
-            &PredefinedMenuItem::fullscreen(app, None)?,
-        ],
-    )?;
-
-    let window_menu = Submenu::with_items(
-        app,
-        "Window",
-        true,
-        &[
-            &PredefinedMenuItem::minimize(app, None)?,
-            &PredefinedMenuItem::maximize(app, None)?,
-            &PredefinedMenuItem::separator(app)?,
-            &PredefinedMenuItem::close_window(app, None)?,
-        ],
-    )?;
-
-    Menu::with_items(app, &[&app_menu, &file_menu, &edit_menu, &view_menu, &window_menu])
-}
+use tauri::{webview::PageLoadEvent, Emitter, Manager, RunEvent};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_global_shortcut::ShortcutState;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    if let Some(code) = cli::run() {
+        std::process::exit(code);
+    }
+
     tauri::Builder::default()
+        .manage(file_open::FileOpenQueue::default())
+        .manage(windows::BoardWindows::default())
+        .manage(dirty::DirtyWindows::default())
+        .manage(deep_link::DeepLinks::default())
+        .manage(locks::BoardLocks::default())
+        .manage(spatial::SpatialIndex::default())
+        .manage(presentation::Presentation::default())
+        .manage(automation::Automation::default())
+        .manage(notifications::Notifications::default())
+        .manage(taskbar::Badge::default())
+        .manage(power::Power::default())
+        .manage(accessibility::Accessibility::default())
+        // Must be registered first so a second launch exits before doing any work
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            file_open::handle_second_instance(app, args, cwd);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
@@ -96,57 +109,340 @@ pub fn run() {
             tauri_plugin_global_shortcut::Builder::new()
                 .with_handler(|app, shortcut, event| {
                     if event.state() == ShortcutState::Pressed {
-                        let cmd_shift_space = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::Space);
-                        if shortcut == &cmd_shift_space {
-                            // Show and focus the main window
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                                // Emit event to open command palette
-                                let _ = window.emit("open-command-palette", ());
-                            }
-                        }
+                        shortcuts::handle_shortcut(app, shortcut);
                     }
                 })
                 .build(),
         )
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .register_asynchronous_uri_scheme_protocol(assets::PROTOCOL, assets::handle_protocol)
         .setup(|app| {
+            app.manage(logging::Logs::init(app.handle())?);
+            app.manage(diagnostics::Diagnostics::install(app.handle())?);
+
+            app.manage(settings::Settings::load(app.handle())?);
+            app.manage(recent::RecentFiles::load(app.handle())?);
+            app.manage(spellcheck::SpellChecker::load(app.handle())?);
+
+            app.manage(assets::AssetStore::load(app.handle())?);
+            app.manage(save_worker::SaveWorker::start(app.handle()));
+            app.manage(library::Library::open(app.handle())?);
+            app.manage(links::LinkGraph::open(app.handle())?);
+            app.manage(search::SearchIndex::open(app.handle())?);
+            app.manage(secrets::Secrets::load(app.handle())?);
+            app.manage(plugins::Plugins::load(app.handle())?);
+
+            app.manage(thumbnails::Thumbnails::open(app.handle())?);
+            app.manage(trash::Trash::load(app.handle())?);
+            app.manage(templates::Templates::load(app.handle())?);
+            app.manage(workspace::Workspace::load(app.handle())?);
+            let board_watcher = watcher::BoardWatcher::new(app.handle());
+            if let Err(err) = board_watcher.watch(&app.state::<workspace::Workspace>().directory()) {
+                tracing::warn!("watcher: cannot watch boards directory: {err}");
+            }
+            app.manage(board_watcher);
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Err(err) = workspace::rescan(&handle) {
+                    tracing::error!("workspace: initial scan failed: {err}");
+                }
+            });
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Err(err) = handle.state::<assets::AssetStore>().collect_garbage() {
+                    tracing::error!("assets: garbage collection failed: {err}");
+                }
+            });
+
+            let autosave = autosave::Autosave::init(app.handle())?;
+            autosave.spawn_worker(app.handle());
+            app.manage(autosave);
+            power::start(app.handle());
+            accessibility::start(app.handle());
+            automation::start_if_enabled(app.handle());
+
             // Create and set the menu
-            let menu = create_menu(app.handle())?;
-            app.set_menu(menu)?;
+            app.manage(i18n::I18n::load(app.handle())?);
+            app.manage(keymap::Keymap::load(app.handle())?);
+            app.manage(menu::MenuState::default());
+            app.manage(share::ShareServer::default());
+            app.manage(context_menu::ContextMenus::default());
+            menu::refresh(app.handle())?;
+            tray::init(app.handle())?;
+            app.manage(jump_list::JumpList::default());
+            jump_list::refresh(app.handle());
+
+            app.manage(updater::Updater::load(app.handle())?);
 
-            // Register global shortcut: Cmd+Shift+Space
-            let shortcut = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::Space);
-            app.global_shortcut().register(shortcut)?;
+            app.manage(shortcuts::GlobalShortcuts::load(app.handle()));
+            app.state::<shortcuts::GlobalShortcuts>().register_all(app.handle());
+
+            let cwd = std::env::current_dir().unwrap_or_default();
+            let launch_files = file_open::board_files_from_args(std::env::args(), &cwd);
+
+            app.manage(window_state::WindowState::load(app.handle())?);
+            app.manage(session::Session::load(app.handle())?);
+            let session_board = app.state::<session::Session>().restore(app.handle());
+            if let Some(window) = app.get_webview_window("main") {
+                // The frontend opens the last file passed on launch
+                let main_board = launch_files.last().or(session_board.as_ref());
+                app.state::<window_state::WindowState>().restore(&window, main_board.map(String::as_str));
+            }
+
+            file_open::open_board_files(app.handle(), session_board.into_iter().chain(launch_files).collect());
+
+            // Installers register the scheme; development builds have to do it at runtime
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            if let Err(err) = app.deep_link().register_all() {
+                tracing::warn!("deep_link: failed to register {}: {err}", deep_link::SCHEME);
+            }
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| deep_link::handle_urls(&handle, event.urls()));
+            if let Some(urls) = app.deep_link().get_current()? {
+                deep_link::handle_urls(app.handle(), urls);
+            }
 
             Ok(())
         })
-        .on_menu_event(|app, event| {
-            if let Some(window) = app.get_webview_window("main") {
-                match event.id().as_ref() {
-                    "new_board" => {
-                        let _ = window.emit("menu-new-board", ());
-                    }
-                    "open_file" => {
-                        let _ = window.emit("menu-open-file", ());
-                    }
-                    "save" => {
-                        let _ = window.emit("menu-save", ());
-                    }
-                    "export" => {
-                        let _ = window.emit("menu-export", ());
-                    }
-                    "command_palette" => {
-                        let _ = window.emit("open-command-palette", ());
-                    }
-                    "reset_view" => {
-                        let _ = window.emit("menu-reset-view", ());
+        .on_page_load(|webview, payload| {
+            if payload.event() == PageLoadEvent::Finished {
+                // Offer crash recovery once the frontend is up
+                let recovered = webview.state::<autosave::Autosave>().recovered();
+                if !recovered.is_empty() {
+                    let _ = webview.emit_to(webview.label(), "recovered-board-available", recovered);
+                }
+                // Reports from a crashed session are offered once, in the main window
+                if webview.label() == "main" {
+                    let crashes = webview.state::<diagnostics::Diagnostics>().pending();
+                    if !crashes.is_empty() {
+                        let _ = webview.emit_to(webview.label(), "crash-report-available", crashes);
                     }
-                    _ => {}
                 }
             }
         })
-        .invoke_handler(tauri::generate_handler![])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_window_event(|window, event| {
+            dirty::handle_window_event(window, event);
+            file_drop::handle_window_event(window, event);
+            locks::handle_window_event(window, event);
+            notifications::handle_window_event(window, event);
+            presentation::handle_window_event(window, event);
+            session::handle_window_event(window, event);
+            spatial::handle_window_event(window, event);
+            window_state::handle_window_event(window, event);
+            windows::handle_window_event(window, event);
+        })
+        .on_menu_event(menu::handle_menu_event)
+        .invoke_handler(tauri::generate_handler![
+            accessibility::announce,
+            accessibility::get_accessibility_settings,
+            assets::collect_asset_garbage,
+            assets::get_asset,
+            assets::set_board_asset_references,
+            automation::automation_respond,
+            automation::get_automation_status,
+            assets::store_asset,
+            autosave::queue_autosave,
+            autosave::clear_autosave,
+            autosave::list_recovered_boards,
+            autosave::restore_recovered_board,
+            board::add_board_asset,
+            board::extract_board_asset,
+            board::load_board,
+            board::read_board_asset,
+            character_palette::show_character_palette,
+            clipboard::copy_selection_as_image,
+            clipboard::read_clipboard_files,
+            clipboard::read_clipboard_image,
+            context_menu::show_context_menu,
+            deep_link::create_board_link,
+            deep_link::take_pending_deep_links,
+            diagnostics::dismiss_crash_reports,
+            diagnostics::get_diagnostics_enabled,
+            diagnostics::set_diagnostics_enabled,
+            diagram::render_diagram,
+            diff::diff_boards,
+            dirty::set_dirty,
+            encryption::forget_board_key,
+            encryption::load_board_encrypted,
+            encryption::save_board_encrypted,
+            export::fonts::list_system_fonts,
+            export::frames::export_frame,
+            export::frames::export_frames,
+            export::frames::list_frames,
+            export::html::export_html,
+            export::markdown::export_markdown,
+            export::pdf::export_board_pdf,
+            export::png::export_board_png,
+            export::preset::batch_export,
+            export::preset::export_with_preset,
+            export::print::print_board,
+            export::svg::export_board_svg,
+            eyedropper::pick_screen_color,
+            file_open::take_pending_board_files,
+            git::get_git_status,
+            git::git_commit_board,
+            git::git_init_workspace,
+            git::git_pull,
+            git::git_push,
+            graph_layout::layout_graph,
+            history::diff_versions,
+            history::list_versions,
+            history::restore_version,
+            i18n::get_app_languages,
+            i18n::set_app_language,
+            image_pipeline::optimize_image,
+            import::excalidraw::import_excalidraw,
+            import::figjam::import_figjam,
+            import::markdown::import_markdown,
+            import::miro::import_miro,
+            import::opml::import_opml,
+            import::pdf::import_pdf,
+            import::tabular::import_tabular,
+            import::tldraw::export_tldraw,
+            import::tldraw::import_tldraw,
+            keymap::get_keymap,
+            keymap::set_shortcut,
+            library::get_board_metadata,
+            library::list_board_tags,
+            library::query_boards,
+            library::set_board_color,
+            library::set_board_favorite,
+            library::set_board_field,
+            library::set_board_tags,
+            links::get_backlinks,
+            links::get_broken_links,
+            locks::force_unlock,
+            locks::release_board_lock,
+            logging::export_logs,
+            logging::log_event,
+            menu::set_menu_item_checked,
+            menu::set_menu_item_enabled,
+            menu::set_zoom_indicator,
+            merge::merge_boards,
+            ocr::ocr_asset,
+            palette::palette_query,
+            plugins::enable_plugin,
+            plugins::get_plugins_directory,
+            plugins::list_plugins,
+            plugins::read_plugin_module,
+            plugins::run_plugin_command,
+            power::get_power_state,
+            presentation::get_presentation_slide,
+            presentation::start_presentation,
+            presentation::step_presentation,
+            presentation::stop_presentation,
+            quick_capture::hide_quick_capture,
+            quick_capture::show_quick_capture,
+            quick_capture::submit_quick_capture,
+            recent::add_recent_file,
+            recent::get_recent_files,
+            recent::clear_recent_files,
+            save_worker::save_board,
+            screenshot::capture_screenshot,
+            search::index_board,
+            search::search_boards,
+            secrets::clear_secrets,
+            secrets::delete_secret,
+            secrets::get_secret,
+            secrets::list_secrets,
+            secrets::store_secret,
+            session::save_viewport,
+            session::take_session_viewport,
+            settings::get_settings,
+            settings::update_settings,
+            share::get_share_status,
+            share::start_share_server,
+            share::stop_share_server,
+            shortcuts::get_active_shortcuts,
+            shortcuts::register_global_shortcut,
+            shortcuts::unregister_global_shortcut,
+            spatial::hit_test,
+            spatial::query_viewport,
+            spatial::update_spatial_index,
+            spellcheck::add_to_dictionary,
+            spellcheck::check_text,
+            spellcheck::get_user_dictionary,
+            spellcheck::list_dictionaries,
+            spellcheck::remove_from_dictionary,
+            spellcheck::suggest,
+            stats::get_board_stats,
+            streaming::load_board_streaming,
+            taskbar::set_badge_count,
+            taskbar::set_taskbar_progress,
+            templates::create_board_from_template,
+            templates::delete_template,
+            templates::list_templates,
+            templates::save_as_template,
+            thumbnails::get_board_thumbnail,
+            timeline::get_timeline,
+            timeline::reconstruct_at,
+            trash::empty_trash,
+            trash::list_trashed_boards,
+            tray::set_tray_visibility,
+            undo::apply_history_step,
+            undo::get_persisted_history,
+            undo::persist_history,
+            updater::check_for_updates,
+            updater::download_update,
+            updater::get_update_channel,
+            updater::install_update,
+            updater::set_update_channel,
+            window_state::set_window_board,
+            window_state::set_window_zoom,
+            windows::get_window_board,
+            windows::open_board_window,
+            workspace::create_board,
+            workspace::delete_board,
+            workspace::duplicate_board,
+            workspace::get_boards_directory,
+            workspace::list_boards,
+            workspace::rename_board,
+            workspace::resolve_board_id,
+            workspace::restore_board,
+            workspace::set_boards_directory,
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            // Quit with unsaved boards: close windows one by one so each can prompt
+            RunEvent::ExitRequested { api, code: Some(_), .. }
+                if app.state::<dirty::DirtyWindows>().has_unsaved_changes() =>
+            {
+                api.prevent_exit();
+                app.state::<session::Session>().quitting();
+                dirty::close_all_windows(app);
+            }
+            RunEvent::ExitRequested { code: Some(_), .. } => app.state::<session::Session>().quitting(),
+            // macOS apps keep running with every window closed...
+            #[cfg(target_os = "macos")]
+            RunEvent::ExitRequested { api, code: None, .. } => api.prevent_exit(),
+            // ...until the Dock icon brings one back
+            #[cfg(target_os = "macos")]
+            RunEvent::Reopen {
+                has_visible_windows: false,
+                ..
+            } => {
+                if let Some(window) = windows::target_or_reopen(app) {
+                    windows::focus_window(&window);
+                }
+            }
+            RunEvent::Exit => {
+                app.state::<autosave::Autosave>().flush();
+                app.state::<window_state::WindowState>().save_all();
+                app.state::<locks::BoardLocks>().release_all();
+                app.state::<automation::Automation>().stop(app);
+            }
+            // Finder "Open With" / double-click on a .boardkit file
+            #[cfg(target_os = "macos")]
+            RunEvent::Opened { urls } => {
+                let paths = urls
+                    .iter()
+                    .filter_map(|url| url.to_file_path().ok())
+                    .filter(|path| file_open::is_board_file(path))
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect();
+                file_open::open_board_files(app, paths);
+            }
+            _ => {}
+        });
 }
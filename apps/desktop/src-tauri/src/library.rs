@@ -0,0 +1,378 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::{
+    error::Result,
+    persist,
+    workspace::{BoardInfo, Workspace},
+};
+
+const LIBRARY_DB: &str = "library.sqlite";
+
+/// What the user attached to a board in the library, kept outside the board
+/// file so the library can group and filter without opening any.
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardMetadata {
+    pub tags: Vec<String>,
+    pub favorite: bool,
+    /// Name of a color label, as chosen in the library
+    pub color: Option<String>,
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Per-board tags, favorites, color labels and custom fields, keyed by board
+/// path and backed by SQLite. Rows of trashed boards are kept so restoring
+/// brings them back.
+pub struct Library {
+    conn: Mutex<Connection>,
+}
+
+/// Boards matching every criterion that is set.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BoardFilter {
+    /// Boards carrying all of these tags
+    pub tags: Vec<String>,
+    pub favorite: Option<bool>,
+    pub color: Option<String>,
+    /// Boards whose fields have exactly these values
+    pub fields: BTreeMap<String, String>,
+    /// Case-insensitive match on the title or file name
+    pub text: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryBoard {
+    #[serde(flatten)]
+    pub info: BoardInfo,
+    #[serde(flatten)]
+    pub metadata: BoardMetadata,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    pub tag: String,
+    pub count: u32,
+}
+
+/// Trimmed, without blanks or repeats, in the order given.
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+impl BoardFilter {
+    fn matches(&self, info: &BoardInfo, metadata: &BoardMetadata) -> bool {
+        let has_tag = |wanted: &String| metadata.tags.iter().any(|tag| tag.eq_ignore_ascii_case(wanted));
+        let text = self.text.as_deref().map(str::trim).filter(|text| !text.is_empty());
+        self.tags.iter().all(has_tag)
+            && self.favorite.is_none_or(|favorite| metadata.favorite == favorite)
+            && self.color.as_ref().is_none_or(|color| metadata.color.as_ref() == Some(color))
+            && self.fields.iter().all(|(key, value)| metadata.fields.get(key) == Some(value))
+            && text.is_none_or(|text| {
+                let text = text.to_lowercase();
+                info.title.to_lowercase().contains(&text) || info.name.to_lowercase().contains(&text)
+            })
+    }
+}
+
+impl Library {
+    pub fn open(app: &AppHandle) -> Result<Self> {
+        Self::with_connection(Connection::open(persist::app_data_file(app, LIBRARY_DB)?)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS boards (
+                path TEXT PRIMARY KEY,
+                favorite INTEGER NOT NULL DEFAULT 0,
+                color TEXT
+            );
+            CREATE TABLE IF NOT EXISTS board_tags (
+                path TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                PRIMARY KEY (path, tag)
+            );
+            CREATE INDEX IF NOT EXISTS board_tags_by_tag ON board_tags (tag);
+            CREATE TABLE IF NOT EXISTS board_fields (
+                path TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                PRIMARY KEY (path, key)
+            );",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// Metadata of every board that has any, by path.
+    pub fn all(&self) -> Result<HashMap<String, BoardMetadata>> {
+        let conn = self.conn.lock().unwrap();
+        let mut all: HashMap<String, BoardMetadata> = HashMap::new();
+        let mut boards = conn.prepare("SELECT path, favorite, color FROM boards")?;
+        let mut rows = boards.query([])?;
+        while let Some(row) = rows.next()? {
+            let metadata = all.entry(row.get(0)?).or_default();
+            metadata.favorite = row.get(1)?;
+            metadata.color = row.get(2)?;
+        }
+        let mut tags = conn.prepare("SELECT path, tag FROM board_tags ORDER BY path, position")?;
+        let mut rows = tags.query([])?;
+        while let Some(row) = rows.next()? {
+            all.entry(row.get(0)?).or_default().tags.push(row.get(1)?);
+        }
+        let mut fields = conn.prepare("SELECT path, key, value FROM board_fields")?;
+        let mut rows = fields.query([])?;
+        while let Some(row) = rows.next()? {
+            all.entry(row.get(0)?).or_default().fields.insert(row.get(1)?, row.get(2)?);
+        }
+        Ok(all)
+    }
+
+    pub fn get(&self, path: &str) -> Result<BoardMetadata> {
+        let conn = self.conn.lock().unwrap();
+        let (favorite, color) = conn
+            .query_row("SELECT favorite, color FROM boards WHERE path = ?1", params![path], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()?
+            .unwrap_or_default();
+        let mut tags = conn.prepare("SELECT tag FROM board_tags WHERE path = ?1 ORDER BY position")?;
+        let tags = tags.query_map(params![path], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+        let mut fields = conn.prepare("SELECT key, value FROM board_fields WHERE path = ?1")?;
+        let fields = fields
+            .query_map(params![path], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(BoardMetadata {
+            tags,
+            favorite,
+            color,
+            fields,
+        })
+    }
+
+    pub fn set_tags(&self, path: &str, tags: &[String]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM board_tags WHERE path = ?1", params![path])?;
+        {
+            let mut insert = tx.prepare("INSERT INTO board_tags (path, tag, position) VALUES (?1, ?2, ?3)")?;
+            for (position, tag) in tags.iter().enumerate() {
+                insert.execute(params![path, tag, position])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn set_favorite(&self, path: &str, favorite: bool) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO boards (path, favorite) VALUES (?1, ?2)
+             ON CONFLICT (path) DO UPDATE SET favorite = excluded.favorite",
+            params![path, favorite],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_color(&self, path: &str, color: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO boards (path, color) VALUES (?1, ?2)
+             ON CONFLICT (path) DO UPDATE SET color = excluded.color",
+            params![path, color],
+        )?;
+        Ok(())
+    }
+
+    /// Set a custom field, or clear it when `value` is `None`.
+    pub fn set_field(&self, path: &str, key: &str, value: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        match value {
+            Some(value) => conn.execute(
+                "INSERT INTO board_fields (path, key, value) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (path, key) DO UPDATE SET value = excluded.value",
+                params![path, key, value],
+            )?,
+            None => conn.execute("DELETE FROM board_fields WHERE path = ?1 AND key = ?2", params![path, key])?,
+        };
+        Ok(())
+    }
+
+    /// Follow a board to its new path.
+    pub fn rename(&self, from: &str, to: &str) -> Result<()> {
+        // Clearing the target first would erase the board's own metadata
+        if from == to {
+            return Ok(());
+        }
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for table in ["boards", "board_tags", "board_fields"] {
+            tx.execute(&format!("DELETE FROM {table} WHERE path = ?2"), params![from, to])?;
+            tx.execute(&format!("UPDATE {table} SET path = ?2 WHERE path = ?1"), params![from, to])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Give a copied board the same metadata as the board it was copied from.
+    pub fn copy(&self, from: &str, to: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for table in ["boards", "board_tags", "board_fields"] {
+            tx.execute(&format!("DELETE FROM {table} WHERE path = ?1"), params![to])?;
+        }
+        tx.execute(
+            "INSERT INTO boards (path, favorite, color) SELECT ?2, favorite, color FROM boards WHERE path = ?1",
+            params![from, to],
+        )?;
+        tx.execute(
+            "INSERT INTO board_tags (path, tag, position) SELECT ?2, tag, position FROM board_tags WHERE path = ?1",
+            params![from, to],
+        )?;
+        tx.execute(
+            "INSERT INTO board_fields (path, key, value) SELECT ?2, key, value FROM board_fields WHERE path = ?1",
+            params![from, to],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn remove(&self, path: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for table in ["boards", "board_tags", "board_fields"] {
+            tx.execute(&format!("DELETE FROM {table} WHERE path = ?1"), params![path])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub fn get_board_metadata(library: State<'_, Library>, path: String) -> Result<BoardMetadata> {
+    library.get(&path)
+}
+
+/// Replace a board's tags, trimmed and without repeats.
+#[tauri::command]
+pub fn set_board_tags(library: State<'_, Library>, path: String, tags: Vec<String>) -> Result<BoardMetadata> {
+    let tags = normalize_tags(tags);
+    library.set_tags(&path, &tags)?;
+    library.get(&path)
+}
+
+#[tauri::command]
+pub fn set_board_favorite(library: State<'_, Library>, path: String, favorite: bool) -> Result<BoardMetadata> {
+    library.set_favorite(&path, favorite)?;
+    library.get(&path)
+}
+
+#[tauri::command]
+pub fn set_board_color(library: State<'_, Library>, path: String, color: Option<String>) -> Result<BoardMetadata> {
+    library.set_color(&path, color.as_deref().map(str::trim).filter(|color| !color.is_empty()))?;
+    library.get(&path)
+}
+
+#[tauri::command]
+pub fn set_board_field(
+    library: State<'_, Library>,
+    path: String,
+    key: String,
+    value: Option<String>,
+) -> Result<BoardMetadata> {
+    library.set_field(&path, key.trim(), value.as_deref())?;
+    library.get(&path)
+}
+
+/// Every tag on a board in the workspace, most used first.
+#[tauri::command]
+pub fn list_board_tags(library: State<'_, Library>, workspace: State<'_, Workspace>) -> Result<Vec<TagCount>> {
+    let metadata = library.all()?;
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for info in workspace.boards() {
+        for tag in metadata.get(&info.path).map(|metadata| metadata.tags.as_slice()).unwrap_or_default() {
+            *counts.entry(tag.clone()).or_default() += 1;
+        }
+    }
+    let mut tags: Vec<TagCount> = counts.into_iter().map(|(tag, count)| TagCount { tag, count }).collect();
+    tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    Ok(tags)
+}
+
+/// Boards in the workspace matching `filter`, newest first, with their
+/// metadata. Reads the last workspace scan instead of the board files.
+#[tauri::command]
+pub fn query_boards(
+    library: State<'_, Library>,
+    workspace: State<'_, Workspace>,
+    filter: Option<BoardFilter>,
+) -> Result<Vec<LibraryBoard>> {
+    let filter = filter.unwrap_or_default();
+    let mut metadata = library.all()?;
+    let mut boards: Vec<LibraryBoard> = workspace
+        .boards()
+        .into_iter()
+        .filter_map(|info| {
+            let metadata = metadata.remove(&info.path).unwrap_or_default();
+            filter.matches(&info, &metadata).then_some(LibraryBoard { info, metadata })
+        })
+        .collect();
+    boards.sort_by(|a, b| b.info.modified_at.cmp(&a.info.modified_at).then_with(|| a.info.name.cmp(&b.info.name)));
+    Ok(boards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library_with_metadata(path: &str) -> Library {
+        let library = Library::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        library.set_tags(path, &["planning".to_string(), "q3".to_string()]).unwrap();
+        library.set_favorite(path, true).unwrap();
+        library.set_color(path, Some("teal")).unwrap();
+        library.set_field(path, "owner", Some("ops")).unwrap();
+        library
+    }
+
+    #[test]
+    fn renaming_moves_the_metadata() {
+        let library = library_with_metadata("/boards/a.boardkit");
+        let before = library.get("/boards/a.boardkit").unwrap();
+        library.rename("/boards/a.boardkit", "/boards/b.boardkit").unwrap();
+
+        let after = library.get("/boards/b.boardkit").unwrap();
+        assert_eq!(after.tags, before.tags);
+        assert!(after.favorite);
+        assert_eq!(after.color.as_deref(), Some("teal"));
+        assert_eq!(after.fields, before.fields);
+        assert!(library.get("/boards/a.boardkit").unwrap().tags.is_empty());
+    }
+
+    #[test]
+    fn renaming_to_the_same_path_keeps_the_metadata() {
+        let library = library_with_metadata("/boards/a.boardkit");
+        library.rename("/boards/a.boardkit", "/boards/a.boardkit").unwrap();
+
+        let metadata = library.get("/boards/a.boardkit").unwrap();
+        assert_eq!(metadata.tags, ["planning", "q3"]);
+        assert!(metadata.favorite);
+        assert_eq!(metadata.color.as_deref(), Some("teal"));
+        assert_eq!(metadata.fields.get("owner").map(String::as_str), Some("ops"));
+    }
+}
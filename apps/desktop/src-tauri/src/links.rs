@@ -0,0 +1,379 @@
+use std::{collections::HashMap, path::Path, sync::Mutex};
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State, Url};
+
+use crate::{
+    board,
+    deep_link::{self, BoardLink},
+    error::Result,
+    persist,
+    workspace::Workspace,
+};
+
+const LINKS_DB: &str = "links.sqlite";
+/// Characters that end a link pasted into text or markup
+const LINK_TERMINATORS: &[char] = &['"', '\'', '<', '>', ')', ']', '`'];
+
+/// Graph of `boardkit://` links between boards, rebuilt from each board as
+/// it is saved. Also keeps the id and element ids of every recorded board, so
+/// links follow boards that moved and links to deleted elements show up as
+/// broken, not just links to missing boards.
+pub struct LinkGraph {
+    conn: Mutex<Connection>,
+}
+
+/// An element linking to another board or element.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backlink {
+    /// Board holding the link
+    pub board: String,
+    pub board_title: String,
+    /// Element or widget holding the link
+    pub element_id: Option<String>,
+    pub target_board: String,
+    /// `None` when the link points at the whole board
+    pub target_element_id: Option<String>,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BrokenReason {
+    MissingBoard,
+    MissingElement,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenLink {
+    #[serde(flatten)]
+    pub link: Backlink,
+    pub reason: BrokenReason,
+}
+
+/// Every `boardkit://open` URL in `text`.
+fn links_in(text: &str) -> Vec<BoardLink> {
+    let prefix = format!("{}://open", deep_link::SCHEME);
+    text.match_indices(&prefix)
+        .filter_map(|(start, _)| {
+            let rest = &text[start..];
+            let end = rest.find(|c: char| c.is_whitespace() || LINK_TERMINATORS.contains(&c)).unwrap_or(rest.len());
+            deep_link::parse_board_link(&Url::parse(&rest[..end]).ok()?)
+        })
+        .collect()
+}
+
+fn collect_links(value: &Value, out: &mut Vec<BoardLink>) {
+    match value {
+        Value::String(text) => out.extend(links_in(text)),
+        Value::Array(items) => items.iter().for_each(|item| collect_links(item, out)),
+        Value::Object(fields) => fields.values().for_each(|field| collect_links(field, out)),
+        _ => {}
+    }
+}
+
+/// (element or widget id, link) for every link anywhere in a raw document.
+fn document_links(document: &Value) -> Vec<(Option<String>, BoardLink)> {
+    let mut rows = Vec::new();
+    let board = document.get("board");
+    for key in ["elements", "connections"] {
+        let items = board.and_then(|board| board.get(key)).and_then(Value::as_array);
+        for item in items.into_iter().flatten() {
+            let id = item.get("id").and_then(Value::as_str).map(str::to_string);
+            let mut links = Vec::new();
+            collect_links(item, &mut links);
+            rows.extend(links.into_iter().map(|link| (id.clone(), link)));
+        }
+    }
+    if let Some(modules) = document.get("modules").and_then(Value::as_object) {
+        for (widget_id, state) in modules {
+            let mut links = Vec::new();
+            collect_links(state, &mut links);
+            rows.extend(links.into_iter().map(|link| (Some(widget_id.clone()), link)));
+        }
+    }
+    rows
+}
+
+fn document_element_ids(document: &Value) -> Vec<&str> {
+    let board = document.get("board");
+    let items = ["elements", "connections"]
+        .into_iter()
+        .filter_map(|key| board?.get(key)?.as_array())
+        .flatten()
+        .filter_map(|item| item.get("id")?.as_str());
+    let widgets = document.get("modules").and_then(Value::as_object).into_iter().flat_map(|modules| modules.keys());
+    items.chain(widgets.map(String::as_str)).collect()
+}
+
+impl LinkGraph {
+    pub fn open(app: &AppHandle) -> Result<Self> {
+        Self::with_connection(Connection::open(persist::app_data_file(app, LINKS_DB)?)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS links (
+                source TEXT NOT NULL,
+                source_element TEXT,
+                target TEXT NOT NULL,
+                target_id TEXT,
+                target_element TEXT
+            );
+            CREATE INDEX IF NOT EXISTS links_by_source ON links (source);
+            CREATE INDEX IF NOT EXISTS links_by_target ON links (target, target_element);
+            CREATE TABLE IF NOT EXISTS boards (path TEXT PRIMARY KEY, board_id TEXT);
+            CREATE TABLE IF NOT EXISTS elements (
+                board TEXT NOT NULL,
+                element_id TEXT NOT NULL,
+                PRIMARY KEY (board, element_id)
+            );",
+        )?;
+        // Graphs recorded before boards had ids
+        let has_ids: bool =
+            conn.query_row("SELECT COUNT(*) FROM pragma_table_info('links') WHERE name = 'target_id'", [], |row| {
+                row.get(0)
+            })?;
+        if !has_ids {
+            conn.execute_batch(
+                "ALTER TABLE links ADD COLUMN target_id TEXT;
+                 ALTER TABLE boards ADD COLUMN board_id TEXT;",
+            )?;
+        }
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// Replace the links and element ids recorded for the board at `path`,
+    /// whose manifest holds `board_id`.
+    pub fn record_document(&self, path: &str, board_id: Option<&str>, document: &Value) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM links WHERE source = ?1", params![path])?;
+        tx.execute("DELETE FROM elements WHERE board = ?1", params![path])?;
+        tx.execute(
+            "INSERT INTO boards (path, board_id) VALUES (?1, ?2)
+             ON CONFLICT (path) DO UPDATE SET board_id = excluded.board_id",
+            params![path, board_id],
+        )?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO links (source, source_element, target, target_id, target_element)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for (element_id, link) in document_links(document) {
+                insert.execute(params![path, element_id, link.board, link.board_id, link.element_id])?;
+            }
+            let mut insert = tx.prepare("INSERT OR IGNORE INTO elements (board, element_id) VALUES (?1, ?2)")?;
+            for element_id in document_element_ids(document) {
+                insert.execute(params![path, element_id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Follow a board's own links to its new path. Links other boards hold to
+    /// the old path still find it by id, unless they were made without one.
+    pub fn rename(&self, from: &str, to: &str) -> Result<()> {
+        // Clearing the target first would erase the board's own rows
+        if from == to {
+            return Ok(());
+        }
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM links WHERE source = ?2", params![from, to])?;
+        tx.execute("UPDATE links SET source = ?2 WHERE source = ?1", params![from, to])?;
+        tx.execute("DELETE FROM elements WHERE board = ?2", params![from, to])?;
+        tx.execute("UPDATE elements SET board = ?2 WHERE board = ?1", params![from, to])?;
+        tx.execute("DELETE FROM boards WHERE path = ?2", params![from, to])?;
+        tx.execute("UPDATE boards SET path = ?2 WHERE path = ?1", params![from, to])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn remove(&self, path: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM links WHERE source = ?1", params![path])?;
+        tx.execute("DELETE FROM elements WHERE board = ?1", params![path])?;
+        tx.execute("DELETE FROM boards WHERE path = ?1", params![path])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Links pointing at `board`, by path or id, or only at `element_id` on it.
+    fn links_to(&self, board: &str, element_id: Option<&str>) -> Result<Vec<Backlink>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT source, source_element, target, target_element FROM links
+             WHERE (target = ?1 OR target_id = (SELECT board_id FROM boards WHERE path = ?1))
+                 AND (?2 IS NULL OR target_element = ?2)
+             ORDER BY source, source_element",
+        )?;
+        let links = statement
+            .query_map(params![board, element_id], |row| {
+                Ok(Backlink {
+                    board: row.get(0)?,
+                    board_title: String::new(),
+                    element_id: row.get(1)?,
+                    target_board: row.get(2)?,
+                    target_element_id: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(links)
+    }
+
+    /// Links whose target board is gone from its path and can't be found by
+    /// id, or was recorded without the target element. `source` and `target`
+    /// limit the check to links held by or made to one board.
+    fn broken(&self, source: Option<&str>, target: Option<&str>) -> Result<Vec<BrokenLink>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT source, source_element, target, target_element, resolved,
+                 EXISTS (SELECT 1 FROM elements WHERE board = resolved AND element_id = target_element)
+             FROM (
+                 SELECT links.*, COALESCE(
+                     (SELECT path FROM boards WHERE path = links.target),
+                     (SELECT path FROM boards WHERE board_id = links.target_id)
+                 ) AS resolved
+                 FROM links WHERE (?1 IS NULL OR source = ?1) AND (?2 IS NULL OR target = ?2)
+             )
+             ORDER BY source, source_element",
+        )?;
+        let mut rows = statement.query(params![source, target])?;
+        let mut broken = Vec::new();
+        while let Some(row) = rows.next()? {
+            let link = Backlink {
+                board: row.get(0)?,
+                board_title: String::new(),
+                element_id: row.get(1)?,
+                target_board: row.get(2)?,
+                target_element_id: row.get(3)?,
+            };
+            let (resolved, has_element): (Option<String>, bool) = (row.get(4)?, row.get(5)?);
+            let found = Path::new(&link.target_board).exists()
+                || resolved.as_ref().is_some_and(|path| Path::new(path).exists());
+            let reason = if !found {
+                BrokenReason::MissingBoard
+            } else if resolved.is_some() && link.target_element_id.is_some() && !has_element {
+                BrokenReason::MissingElement
+            } else {
+                continue;
+            };
+            broken.push(BrokenLink { link, reason });
+        }
+        Ok(broken)
+    }
+}
+
+/// Fill in source board titles from the last workspace scan.
+fn with_titles<'a>(app: &AppHandle, links: impl IntoIterator<Item = &'a mut Backlink>) {
+    let titles: HashMap<String, String> = app
+        .try_state::<Workspace>()
+        .map(|workspace| workspace.boards().into_iter().map(|info| (info.path, info.title)).collect())
+        .unwrap_or_default();
+    for link in links {
+        link.board_title = titles.get(&link.board).filter(|title| !title.trim().is_empty()).cloned().unwrap_or_else(
+            || Path::new(&link.board).file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+        );
+    }
+}
+
+/// Tell every window which links broke because `board` moved or went away.
+/// Links that find it by id don't count.
+pub fn notify_broken(app: &AppHandle, board: &str) {
+    let Some(graph) = app.try_state::<LinkGraph>() else {
+        return;
+    };
+    match graph.broken(None, Some(board)) {
+        Ok(mut broken) if !broken.is_empty() => {
+            with_titles(app, broken.iter_mut().map(|broken| &mut broken.link));
+            tracing::info!(path = board, count = broken.len(), "links to board broke");
+            let _ = app.emit("links-broken", broken);
+        }
+        Ok(_) => {}
+        Err(err) => tracing::warn!("failed to look up links to {board}: {err}"),
+    }
+}
+
+/// Re-record a saved board's links off the calling thread; failures are only
+/// logged, like the search index.
+pub fn record_in_background(app: &AppHandle, path: String, document: Value) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(graph) = app.try_state::<LinkGraph>() else {
+            return;
+        };
+        let board_id = board::read_board_id(&path);
+        if let Err(err) = graph.record_document(&path, board_id.as_deref(), &document) {
+            tracing::warn!("failed to record links of {path}: {err}");
+        }
+    });
+}
+
+/// Elements linking to `board`, or to `element_id` on it.
+#[tauri::command]
+pub fn get_backlinks(
+    app: AppHandle,
+    graph: State<'_, LinkGraph>,
+    board: String,
+    element_id: Option<String>,
+) -> Result<Vec<Backlink>> {
+    let mut links = graph.links_to(&board, element_id.as_deref())?;
+    with_titles(&app, &mut links);
+    Ok(links)
+}
+
+/// Links to boards that no longer exist or elements that were deleted, held
+/// by `board` or by any board.
+#[tauri::command]
+pub fn get_broken_links(app: AppHandle, graph: State<'_, LinkGraph>, board: Option<String>) -> Result<Vec<BrokenLink>> {
+    let mut broken = graph.broken(board.as_deref(), None)?;
+    with_titles(&app, broken.iter_mut().map(|broken| &mut broken.link));
+    Ok(broken)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn graph_with_link() -> LinkGraph {
+        let graph = LinkGraph::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let source = json!({
+            "board": {
+                "elements": [{ "id": "note", "content": "see boardkit://open?board=%2Fboards%2Ftarget.boardkit" }]
+            }
+        });
+        graph.record_document("/boards/source.boardkit", Some("source-id"), &source).unwrap();
+        graph
+    }
+
+    fn link_sources(graph: &LinkGraph) -> Vec<(String, Option<String>)> {
+        graph
+            .links_to("/boards/target.boardkit", None)
+            .unwrap()
+            .into_iter()
+            .map(|link| (link.board, link.element_id))
+            .collect()
+    }
+
+    #[test]
+    fn renaming_moves_the_links_a_board_holds() {
+        let graph = graph_with_link();
+        graph.rename("/boards/source.boardkit", "/boards/renamed.boardkit").unwrap();
+        assert_eq!(link_sources(&graph), [("/boards/renamed.boardkit".to_string(), Some("note".to_string()))]);
+    }
+
+    #[test]
+    fn renaming_to_the_same_path_keeps_the_links() {
+        let graph = graph_with_link();
+        graph.rename("/boards/source.boardkit", "/boards/source.boardkit").unwrap();
+        assert_eq!(link_sources(&graph), [("/boards/source.boardkit".to_string(), Some("note".to_string()))]);
+    }
+}
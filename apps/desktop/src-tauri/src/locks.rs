@@ -0,0 +1,196 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, Window, WindowEvent};
+
+use crate::{
+    error::{Error, Result},
+    persist, watcher,
+};
+
+/// A lock not refreshed by a save for this long is considered abandoned
+/// (crashed app, machine gone offline).
+const STALE_AFTER_MS: u64 = 10 * 60 * 1000;
+
+/// Contents of `<board>.lock`, next to the board so sync clients carry it
+/// to other machines too.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LockFile {
+    /// Identifies the running app, so another process never takes it for its own
+    instance: String,
+    window: String,
+    host: Option<String>,
+    pid: u32,
+    updated_at: u64,
+}
+
+impl LockFile {
+    fn describe(&self) -> String {
+        match &self.host {
+            Some(host) => format!("Boardkit on {host} (process {})", self.pid),
+            None => format!("another Boardkit process ({})", self.pid),
+        }
+    }
+}
+
+/// Event payload sent to a window whose save was refused.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BoardLocked {
+    path: String,
+    owner: String,
+}
+
+fn lock_path(board_path: &Path) -> PathBuf {
+    let mut name = board_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    board_path.with_file_name(name)
+}
+
+fn host_name() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .filter(|host| !host.is_empty())
+}
+
+/// Advisory write locks on board files. A window takes a board's lock on its
+/// first save and keeps it (refreshed by every save) until it closes, so two
+/// windows or two machines sharing a synced folder can't overwrite each other.
+pub struct BoardLocks {
+    instance: String,
+    /// Boards locked by this process, with the window holding each
+    held: Mutex<HashMap<PathBuf, String>>,
+}
+
+impl Default for BoardLocks {
+    fn default() -> Self {
+        Self {
+            instance: format!("{}-{}", std::process::id(), persist::now_millis()),
+            held: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl BoardLocks {
+    /// Take or refresh the lock on `path` for `window`, failing with
+    /// [`Error::BoardLocked`] when another window or process holds it.
+    fn acquire(&self, path: &Path, window: &str) -> Result<()> {
+        let mut held = self.held.lock().unwrap();
+        if let Some(owner) = held.get(path).filter(|owner| owner.as_str() != window) {
+            return Err(Error::BoardLocked {
+                path: path.to_string_lossy().into_owned(),
+                owner: format!("another Boardkit window ({owner})"),
+            });
+        }
+
+        let lock_path = lock_path(path);
+        if let Ok(bytes) = fs::read(&lock_path) {
+            if let Ok(existing) = serde_json::from_slice::<LockFile>(&bytes) {
+                let stale = persist::now_millis().saturating_sub(existing.updated_at) > STALE_AFTER_MS;
+                if existing.instance != self.instance && !stale {
+                    return Err(Error::BoardLocked {
+                        path: path.to_string_lossy().into_owned(),
+                        owner: existing.describe(),
+                    });
+                }
+            }
+        }
+
+        let lock = LockFile {
+            instance: self.instance.clone(),
+            window: window.to_string(),
+            host: host_name(),
+            pid: std::process::id(),
+            updated_at: persist::now_millis(),
+        };
+        watcher::expect_write(&lock_path);
+        persist::write_json(&lock_path, &lock)?;
+        held.insert(path.to_path_buf(), window.to_string());
+        Ok(())
+    }
+
+    fn release(&self, path: &Path) {
+        if self.held.lock().unwrap().remove(path).is_some() {
+            let _ = fs::remove_file(lock_path(path));
+        }
+    }
+
+    /// Drop every lock held by `window`.
+    pub fn release_window(&self, window: &str) {
+        let paths: Vec<PathBuf> = self
+            .held
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, owner)| owner.as_str() == window)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in paths {
+            self.release(&path);
+        }
+    }
+
+    /// Remove this process's lock files on exit.
+    pub fn release_all(&self) {
+        let paths: Vec<PathBuf> = self.held.lock().unwrap().keys().cloned().collect();
+        for path in paths {
+            self.release(&path);
+        }
+    }
+}
+
+/// Lock `path` for a save from `window`. When someone else holds it the
+/// window also gets a `board-locked` event, so it can offer `force_unlock`.
+pub fn lock_for_save(window: &Window, path: &str) -> Result<()> {
+    let result = window.state::<BoardLocks>().acquire(Path::new(path), window.label());
+    if let Err(Error::BoardLocked { path, owner }) = &result {
+        tracing::warn!(path, owner, "save refused: board is locked");
+        let payload = BoardLocked {
+            path: path.clone(),
+            owner: owner.clone(),
+        };
+        let _ = window.emit_to(window.label(), "board-locked", payload);
+    }
+    result
+}
+
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::Destroyed = event {
+        if let Some(locks) = window.try_state::<BoardLocks>() {
+            locks.release_window(window.label());
+        }
+    }
+}
+
+/// Release the calling window's lock, e.g. when it switches to another board.
+#[tauri::command]
+pub fn release_board_lock(window: Window, path: String) {
+    let locks = window.state::<BoardLocks>();
+    let path = Path::new(&path);
+    if locks.held.lock().unwrap().get(path).is_some_and(|owner| owner == window.label()) {
+        locks.release(path);
+    }
+}
+
+/// Take over a board's lock regardless of who holds it. Only for when the
+/// holder is known to be gone, since its next save will then be refused.
+#[tauri::command]
+pub fn force_unlock(window: Window, path: String) -> Result<()> {
+    let locks = window.state::<BoardLocks>();
+    let board_path = Path::new(&path);
+    locks.held.lock().unwrap().remove(board_path);
+    match fs::remove_file(lock_path(board_path)) {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(Error::from_io(err, &path)),
+    }
+    tracing::warn!(path, "board lock forcibly removed");
+    locks.acquire(board_path, window.label())
+}
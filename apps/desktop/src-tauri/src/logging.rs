@@ -0,0 +1,116 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{filter::LevelFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt, Layer};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    diagnostics::{self, Diagnostics},
+    error::{Error, Result},
+    persist,
+};
+
+const LOG_FILE_PREFIX: &str = "boardkit";
+const LOG_FILE_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7;
+
+/// Daily-rotated JSON log files in the app log dir. The guard flushes
+/// buffered lines when the app shuts down.
+pub struct Logs {
+    dir: PathBuf,
+    _guard: WorkerGuard,
+}
+
+impl Logs {
+    pub fn init(app: &AppHandle) -> Result<Self> {
+        let dir = app.path().app_log_dir()?;
+        fs::create_dir_all(&dir)?;
+        let appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix(LOG_FILE_SUFFIX)
+            .max_log_files(MAX_LOG_FILES)
+            .build(&dir)
+            .map_err(|err| Error::Io(io::Error::other(err)))?;
+        let (writer, guard) = tracing_appender::non_blocking(appender);
+
+        let file_layer = fmt::layer().json().with_writer(writer).with_filter(LevelFilter::INFO);
+        let stderr_layer = fmt::layer().with_writer(io::stderr).with_filter(LevelFilter::INFO);
+        let recent_layer = diagnostics::RecentEvents.with_filter(LevelFilter::INFO);
+        let subscriber = tracing_subscriber::registry().with(file_layer).with(stderr_layer).with(recent_layer);
+        if let Err(err) = subscriber.try_init() {
+            eprintln!("logging: subscriber already installed: {err}");
+        }
+
+        Ok(Self { dir, _guard: guard })
+    }
+
+    fn files(&self) -> Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.is_file()
+                    && path.extension().is_some_and(|ext| ext == LOG_FILE_SUFFIX)
+                    && path.file_name().is_some_and(|name| name.to_string_lossy().starts_with(LOG_FILE_PREFIX))
+            })
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+/// Record a frontend log line alongside the backend's own events.
+#[tauri::command]
+pub fn log_event(level: LogLevel, message: String, context: Option<Value>) {
+    let context = context.map(|context| context.to_string()).unwrap_or_default();
+    match level {
+        LogLevel::Error => tracing::error!(target: "frontend", context, "{message}"),
+        LogLevel::Warn => tracing::warn!(target: "frontend", context, "{message}"),
+        LogLevel::Info => tracing::info!(target: "frontend", context, "{message}"),
+        LogLevel::Debug => tracing::debug!(target: "frontend", context, "{message}"),
+    }
+}
+
+/// Zip the retained log files and any crash reports into `path` so they can
+/// be attached to a bug report.
+#[tauri::command]
+pub async fn export_logs(app: AppHandle, path: String) -> Result<()> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let files = app.state::<Logs>().files()?;
+        let mut zip = ZipWriter::new(io::Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for file in &files {
+            let name = file.file_name().unwrap_or_default().to_string_lossy();
+            zip.start_file(name, options).map_err(io::Error::from)?;
+            zip.write_all(&fs::read(file)?)?;
+        }
+        for report in app.state::<Diagnostics>().reports() {
+            let name = report.file_name().unwrap_or_default().to_string_lossy();
+            zip.start_file(format!("crashes/{name}"), options).map_err(io::Error::from)?;
+            zip.write_all(&fs::read(&report)?)?;
+        }
+        let bytes = zip.finish().map_err(io::Error::from)?.into_inner();
+        persist::write_atomic(Path::new(&path), &bytes).map_err(|err| Error::from_io(err, &path))
+    })
+    .await
+    .map_err(|err| Error::Io(io::Error::other(err)))?
+}
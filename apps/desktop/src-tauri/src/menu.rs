@@ -0,0 +1,529 @@
+use std::{collections::HashMap, path::Path, sync::Mutex};
+
+use tauri::{
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, MenuItemKind, PredefinedMenuItem, Submenu},
+    AppHandle, Emitter, Manager, State, Window,
+};
+
+use crate::{
+    character_palette,
+    context_menu::{ContextMenus, CONTEXT_ITEM_PREFIX},
+    error::{Error, Result},
+    file_open,
+    i18n::{self, tr},
+    jump_list, keymap,
+    plugins::{self, Plugins, PLUGIN_ITEM_PREFIX},
+    recent::RecentFiles,
+    tray, windows,
+};
+
+const RECENT_ITEM_PREFIX: &str = "open_recent:";
+const WINDOW_ITEM_PREFIX: &str = "focus_window:";
+
+/// Items whose state the frontend drives through `set_menu_item_*`.
+const DYNAMIC_ITEMS: &[&str] = &[
+    "save",
+    "export",
+    "print",
+    "undo",
+    "redo",
+    "toggle_grid",
+    "zoom_in",
+    "zoom_out",
+    "zoom_to_fit",
+    "zoom_to_selection",
+    "start_presentation",
+];
+const ZOOM_INDICATOR_ID: &str = "zoom_indicator";
+
+#[derive(Clone, Copy, Default)]
+struct ItemState {
+    enabled: Option<bool>,
+    checked: Option<bool>,
+}
+
+/// How items are built, for a window that hasn't reported their state.
+const DEFAULT_ITEM_STATE: ItemState = ItemState {
+    enabled: Some(true),
+    checked: Some(false),
+};
+
+impl ItemState {
+    /// Fields the window never set fall back to how items are built, so a
+    /// window that only disabled an item doesn't keep another's check mark.
+    fn or_default_state(self) -> Self {
+        Self {
+            enabled: self.enabled.or(DEFAULT_ITEM_STATE.enabled),
+            checked: self.checked.or(DEFAULT_ITEM_STATE.checked),
+        }
+    }
+}
+
+/// Last state each board window set for its dynamic items, keyed by window
+/// label, since every window shows its own board. The menu is rebuilt
+/// whenever recent files or windows change, so the state is reapplied on
+/// every build instead of living only in the native items.
+#[derive(Default)]
+pub struct MenuState {
+    items: Mutex<HashMap<String, HashMap<String, ItemState>>>,
+    zoom_percent: Mutex<HashMap<String, u32>>,
+}
+
+impl MenuState {
+    fn update(&self, label: &str, id: &str, f: impl FnOnce(&mut ItemState)) -> Result<ItemState> {
+        if !DYNAMIC_ITEMS.contains(&id) {
+            return Err(Error::UnknownMenuItem(id.to_string()));
+        }
+        let mut items = self.items.lock().unwrap();
+        let state = items.entry(label.to_string()).or_default().entry(id.to_string()).or_default();
+        f(state);
+        Ok(*state)
+    }
+
+    /// Drop the state of a closed window.
+    pub fn forget(&self, label: &str) {
+        self.items.lock().unwrap().remove(label);
+        self.zoom_percent.lock().unwrap().remove(label);
+    }
+}
+
+fn zoom_label(app: &AppHandle, label: &str) -> String {
+    let percent = app.try_state::<MenuState>().and_then(|state| state.zoom_percent.lock().unwrap().get(label).copied());
+    tr(app, "Zoom: {percent}%").replace("{percent}", &percent.unwrap_or(100).to_string())
+}
+
+fn find_item(items: Vec<MenuItemKind<tauri::Wry>>, id: &str) -> Option<MenuItemKind<tauri::Wry>> {
+    items.into_iter().find_map(|item| match item.as_submenu() {
+        Some(submenu) => find_item(submenu.items().ok()?, id),
+        None => (item.id() == id).then_some(item),
+    })
+}
+
+fn apply_item_state(menu: &Menu<tauri::Wry>, id: &str, state: ItemState) -> tauri::Result<()> {
+    let Some(item) = find_item(menu.items()?, id) else {
+        return Ok(());
+    };
+    let state = state.or_default_state();
+    if let Some(item) = item.as_menuitem() {
+        if let Some(enabled) = state.enabled {
+            item.set_enabled(enabled)?;
+        }
+    } else if let Some(item) = item.as_check_menuitem() {
+        if let Some(enabled) = state.enabled {
+            item.set_enabled(enabled)?;
+        }
+        if let Some(checked) = state.checked {
+            item.set_checked(checked)?;
+        }
+    }
+    Ok(())
+}
+
+/// Show the state window `label` set in `menu`, items it never set included,
+/// so a menu shared between windows doesn't keep the previous one's.
+fn apply_menu_state(app: &AppHandle, menu: &Menu<tauri::Wry>, label: &str) -> tauri::Result<()> {
+    let Some(state) = app.try_state::<MenuState>() else {
+        return Ok(());
+    };
+    let items = state.items.lock().unwrap().get(label).cloned().unwrap_or_default();
+    for id in DYNAMIC_ITEMS {
+        apply_item_state(menu, id, items.get(*id).copied().unwrap_or(DEFAULT_ITEM_STATE))?;
+    }
+    if let Some(item) = find_item(menu.items()?, ZOOM_INDICATOR_ID) {
+        if let Some(item) = item.as_check_menuitem() {
+            item.set_text(zoom_label(app, label))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn create_recent_menu(app: &AppHandle, title: &str) -> tauri::Result<Submenu<tauri::Wry>> {
+    let entries = app
+        .try_state::<RecentFiles>()
+        .map(|recent| recent.entries())
+        .unwrap_or_default();
+
+    let recent_menu = Submenu::new(app, title, true)?;
+    for path in &entries {
+        let label = Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.clone());
+        let item = MenuItem::with_id(app, format!("{RECENT_ITEM_PREFIX}{path}"), label, true, None::<&str>)?;
+        recent_menu.append(&item)?;
+    }
+    if !entries.is_empty() {
+        recent_menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    let clear_recent =
+        MenuItem::with_id(app, "clear_recent", tr(app, "Clear Menu"), !entries.is_empty(), None::<&str>)?;
+    recent_menu.append(&clear_recent)?;
+
+    Ok(recent_menu)
+}
+
+/// The menu bar as board window `label` shows it, in the app language.
+pub fn create_menu(app: &AppHandle, label: &str) -> tauri::Result<Menu<tauri::Wry>> {
+    // Predefined items keep the OS label unless the app language translates it
+    let native = |text| i18n::translated(app, text);
+    let check_for_updates =
+        MenuItem::with_id(app, "check_for_updates", tr(app, "Check for Updates..."), true, None::<&str>)?;
+    let app_menu = Submenu::with_items(
+        app,
+        "Boardkit",
+        true,
+        &[
+            &PredefinedMenuItem::about(app, Some(&tr(app, "About Boardkit")), None)?,
+            &check_for_updates,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::services(app, native("Services").as_deref())?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::hide(app, native("Hide Boardkit").as_deref())?,
+            &PredefinedMenuItem::hide_others(app, native("Hide Others").as_deref())?,
+            &PredefinedMenuItem::show_all(app, native("Show All").as_deref())?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::quit(app, native("Quit Boardkit").as_deref())?,
+        ],
+    )?;
+
+    let accelerator = |menu_id| keymap::accelerator(app, menu_id);
+    let new_board =
+        MenuItem::with_id(app, "new_board", tr(app, "New Board"), true, accelerator("new_board").as_deref())?;
+    let open_file = MenuItem::with_id(app, "open_file", tr(app, "Open..."), true, accelerator("open_file").as_deref())?;
+    let recent_menu = create_recent_menu(app, &tr(app, "Open Recent"))?;
+    let save = MenuItem::with_id(app, "save", tr(app, "Save"), true, accelerator("save").as_deref())?;
+    let export =
+        MenuItem::with_id(app, "export", tr(app, "Export as .boardkit"), true, accelerator("export").as_deref())?;
+    let print = MenuItem::with_id(app, "print", tr(app, "Print..."), true, accelerator("print").as_deref())?;
+
+    let file_menu = Submenu::with_items(
+        app,
+        tr(app, "File"),
+        true,
+        &[
+            &new_board,
+            &open_file,
+            &recent_menu,
+            &PredefinedMenuItem::separator(app)?,
+            &save,
+            &export,
+            &PredefinedMenuItem::separator(app)?,
+            &print,
+        ],
+    )?;
+
+    // Board undo/redo, so availability can follow the document history
+    let undo = MenuItem::with_id(app, "undo", tr(app, "Undo"), true, accelerator("undo").as_deref())?;
+    let redo = MenuItem::with_id(app, "redo", tr(app, "Redo"), true, accelerator("redo").as_deref())?;
+    let palette_item_accelerator = accelerator("character_palette");
+    let character_palette = MenuItem::with_id(
+        app,
+        "character_palette",
+        tr(app, "Emoji & Symbols"),
+        true,
+        palette_item_accelerator.as_deref(),
+    )?;
+    let edit_menu = Submenu::with_items(
+        app,
+        tr(app, "Edit"),
+        true,
+        &[
+            &undo,
+            &redo,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::cut(app, native("Cut").as_deref())?,
+            &PredefinedMenuItem::copy(app, native("Copy").as_deref())?,
+            &PredefinedMenuItem::paste(app, native("Paste").as_deref())?,
+            &PredefinedMenuItem::select_all(app, native("Select All").as_deref())?,
+            &PredefinedMenuItem::separator(app)?,
+            &character_palette,
+        ],
+    )?;
+
+    let palette_accelerator = accelerator("command_palette");
+    let command_palette =
+        MenuItem::with_id(app, "command_palette", tr(app, "Command Palette..."), true, palette_accelerator.as_deref())?;
+    let reset_view =
+        MenuItem::with_id(app, "reset_view", tr(app, "Reset View"), true, accelerator("reset_view").as_deref())?;
+    let grid_accelerator = accelerator("toggle_grid");
+    let toggle_grid =
+        CheckMenuItem::with_id(app, "toggle_grid", tr(app, "Snap to Grid"), true, false, grid_accelerator.as_deref())?;
+    let zoom_in = MenuItem::with_id(app, "zoom_in", tr(app, "Zoom In"), true, accelerator("zoom_in").as_deref())?;
+    let zoom_out = MenuItem::with_id(app, "zoom_out", tr(app, "Zoom Out"), true, accelerator("zoom_out").as_deref())?;
+    let fit_accelerator = accelerator("zoom_to_fit");
+    let zoom_to_fit = MenuItem::with_id(app, "zoom_to_fit", tr(app, "Zoom to Fit"), true, fit_accelerator.as_deref())?;
+    let selection_accelerator = accelerator("zoom_to_selection");
+    let zoom_to_selection = MenuItem::with_id(
+        app,
+        "zoom_to_selection",
+        tr(app, "Zoom to Selection"),
+        true,
+        selection_accelerator.as_deref(),
+    )?;
+    let presentation_accelerator = accelerator("start_presentation");
+    let start_presentation = MenuItem::with_id(
+        app,
+        "start_presentation",
+        tr(app, "Start Presentation"),
+        true,
+        presentation_accelerator.as_deref(),
+    )?;
+    // Read-only entry showing the current zoom level
+    let zoom_indicator =
+        CheckMenuItem::with_id(app, ZOOM_INDICATOR_ID, zoom_label(app, label), false, true, None::<&str>)?;
+
+    let view_menu = Submenu::with_items(
+        app,
+        tr(app, "View"),
+        true,
+        &[
+            &command_palette,
+            &PredefinedMenuItem::separator(app)?,
+            &zoom_indicator,
+            &zoom_in,
+            &zoom_out,
+            &zoom_to_fit,
+            &zoom_to_selection,
+            &reset_view,
+            &PredefinedMenuItem::separator(app)?,
+            &toggle_grid,
+            &PredefinedMenuItem::separator(app)?,
+            &start_presentation,
+            &PredefinedMenuItem::fullscreen(app, native("Enter Full Screen").as_deref())?,
+        ],
+    )?;
+
+    let window_menu = Submenu::with_items(
+        app,
+        tr(app, "Window"),
+        true,
+        &[
+            &PredefinedMenuItem::minimize(app, native("Minimize").as_deref())?,
+            &PredefinedMenuItem::maximize(app, native("Zoom").as_deref())?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::close_window(app, native("Close Window").as_deref())?,
+        ],
+    )?;
+    let open_windows = windows::open_windows(app);
+    if !open_windows.is_empty() {
+        window_menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    for (label, title) in open_windows {
+        let item = MenuItem::with_id(app, format!("{WINDOW_ITEM_PREFIX}{label}"), title, true, None::<&str>)?;
+        window_menu.append(&item)?;
+    }
+
+    let menu = Menu::with_items(app, &[&app_menu, &file_menu, &edit_menu, &view_menu])?;
+    // Only shown once an enabled plugin asks for a menu item
+    let plugin_commands = app.try_state::<Plugins>().map(|plugins| plugins.menu_commands()).unwrap_or_default();
+    if !plugin_commands.is_empty() {
+        let plugins_menu = Submenu::new(app, tr(app, "Plugins"), true)?;
+        for (plugin, command) in plugin_commands {
+            let id = format!("{PLUGIN_ITEM_PREFIX}{}/{}", plugin.id, command.id);
+            plugins_menu.append(&MenuItem::with_id(app, id, command.title, true, None::<&str>)?)?;
+        }
+        menu.append(&plugins_menu)?;
+    }
+    menu.append(&window_menu)?;
+    apply_menu_state(app, &menu, label)?;
+    Ok(menu)
+}
+
+/// Label of the board window the app menu reflects.
+fn target_label(app: &AppHandle) -> String {
+    windows::target_window(app).map_or_else(|| "main".to_string(), |window| window.label().to_string())
+}
+
+/// Rebuild the menus so dynamic sections (e.g. Open Recent) reflect current
+/// state, along with the tray menu. Outside macOS every board window gets a
+/// menu bar of its own; the app menu, which macOS shows for all windows and
+/// the others fall back to, follows the board window in front.
+pub fn refresh(app: &AppHandle) -> tauri::Result<()> {
+    app.set_menu(create_menu(app, &target_label(app))?)?;
+    #[cfg(not(target_os = "macos"))]
+    for (label, window) in app.webview_windows() {
+        if windows::is_board_window(&label) {
+            window.set_menu(create_menu(app, &label)?)?;
+        }
+    }
+    jump_list::refresh(app);
+    tray::refresh(app)
+}
+
+/// Switch the app menu to the state of the board window that just came to
+/// the front.
+pub fn window_focused(app: &AppHandle, label: &str) {
+    if let Some(menu) = app.menu() {
+        if let Err(err) = apply_menu_state(app, &menu, label) {
+            tracing::warn!("failed to update the menu for {label}: {err}");
+        }
+    }
+}
+
+pub fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+
+    if let Some(item) = id.strip_prefix(CONTEXT_ITEM_PREFIX) {
+        if let Some(context_menus) = app.try_state::<ContextMenus>() {
+            context_menus.select(item);
+        }
+        return;
+    }
+
+    if id == "clear_recent" {
+        if let Some(recent) = app.try_state::<RecentFiles>() {
+            let _ = recent.clear();
+        }
+        let _ = refresh(app);
+        return;
+    }
+
+    // The picker types into whatever has focus, so no need to involve the board
+    if id == "character_palette" {
+        if let Err(err) = character_palette::show(app) {
+            tracing::warn!("failed to open the character palette: {err}");
+        }
+        return;
+    }
+
+    if id == "tray_toggle_window" {
+        tray::toggle_main_window(app);
+        return;
+    }
+
+    if let Some(item) = id.strip_prefix(PLUGIN_ITEM_PREFIX) {
+        plugins::handle_menu_item(app, item);
+        return;
+    }
+
+    if let Some(label) = id.strip_prefix(WINDOW_ITEM_PREFIX) {
+        if let Some(window) = app.get_webview_window(label) {
+            windows::focus_window(&window);
+        }
+        return;
+    }
+
+    let Some(window) = windows::target_window(app) else {
+        // Every board window was closed: bring the main one back, which
+        // picks Open Recent's board up once it's listening
+        match id.strip_prefix(RECENT_ITEM_PREFIX) {
+            Some(path) => file_open::open_board_files(app, vec![path.to_string()]),
+            None => {
+                windows::reopen_main_window(app);
+            }
+        }
+        return;
+    };
+
+    // Items reachable from the tray may target a hidden window
+    if id.starts_with("tray_") || id.starts_with(RECENT_ITEM_PREFIX) {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    if let Some(path) = id.strip_prefix(RECENT_ITEM_PREFIX) {
+        let _ = window.emit_to(window.label(), "menu-open-recent", path);
+        return;
+    }
+
+    match id {
+        "new_board" | "tray_new_board" => {
+            let _ = window.emit_to(window.label(), "menu-new-board", ());
+        }
+        "open_file" => {
+            let _ = window.emit_to(window.label(), "menu-open-file", ());
+        }
+        "save" => {
+            let _ = window.emit_to(window.label(), "menu-save", ());
+        }
+        "export" => {
+            let _ = window.emit_to(window.label(), "menu-export", ());
+        }
+        "print" => {
+            let _ = window.emit_to(window.label(), "menu-print", ());
+        }
+        "command_palette" | "tray_command_palette" => {
+            let _ = window.emit_to(window.label(), "open-command-palette", ());
+        }
+        "check_for_updates" => {
+            let _ = window.emit_to(window.label(), "menu-check-updates", ());
+        }
+        "reset_view" => {
+            let _ = window.emit_to(window.label(), "menu-reset-view", ());
+        }
+        "undo" => {
+            let _ = window.emit_to(window.label(), "menu-undo", ());
+        }
+        "redo" => {
+            let _ = window.emit_to(window.label(), "menu-redo", ());
+        }
+        "toggle_grid" => {
+            let _ = window.emit_to(window.label(), "menu-toggle-grid", ());
+        }
+        "zoom_in" => {
+            let _ = window.emit_to(window.label(), "menu-zoom-in", ());
+        }
+        "zoom_out" => {
+            let _ = window.emit_to(window.label(), "menu-zoom-out", ());
+        }
+        "zoom_to_fit" => {
+            let _ = window.emit_to(window.label(), "menu-zoom-to-fit", ());
+        }
+        "zoom_to_selection" => {
+            let _ = window.emit_to(window.label(), "menu-zoom-to-selection", ());
+        }
+        "start_presentation" => {
+            let _ = window.emit_to(window.label(), "menu-start-presentation", ());
+        }
+        _ => {}
+    }
+}
+
+/// Menus showing the state of `window`: its own menu bar, and the app menu
+/// while it's the board window in front.
+fn menus_showing(app: &AppHandle, window: &Window) -> Vec<Menu<tauri::Wry>> {
+    let own = window.menu();
+    let shared = (target_label(app) == window.label()).then(|| app.menu()).flatten();
+    own.into_iter().chain(shared).collect()
+}
+
+fn set_item_state(window: &Window, menu_state: &MenuState, id: &str, f: impl FnOnce(&mut ItemState)) -> Result<()> {
+    let state = menu_state.update(window.label(), id, f)?;
+    for menu in menus_showing(window.app_handle(), window) {
+        apply_item_state(&menu, id, state)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_menu_item_enabled(
+    window: Window,
+    menu_state: State<'_, MenuState>,
+    id: String,
+    enabled: bool,
+) -> Result<()> {
+    set_item_state(&window, &menu_state, &id, |state| state.enabled = Some(enabled))
+}
+
+#[tauri::command]
+pub fn set_menu_item_checked(
+    window: Window,
+    menu_state: State<'_, MenuState>,
+    id: String,
+    checked: bool,
+) -> Result<()> {
+    set_item_state(&window, &menu_state, &id, |state| state.checked = Some(checked))
+}
+
+/// Show the board's zoom level in the View menu.
+#[tauri::command]
+pub fn set_zoom_indicator(window: Window, menu_state: State<'_, MenuState>, percent: u32) -> Result<()> {
+    menu_state.zoom_percent.lock().unwrap().insert(window.label().to_string(), percent);
+    for menu in menus_showing(window.app_handle(), &window) {
+        let item = find_item(menu.items()?, ZOOM_INDICATOR_ID);
+        if let Some(item) = item.as_ref().and_then(MenuItemKind::as_check_menuitem) {
+            item.set_text(zoom_label(window.app_handle(), window.label()))?;
+        }
+    }
+    Ok(())
+}
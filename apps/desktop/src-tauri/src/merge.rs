@@ -0,0 +1,307 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    board,
+    diff::{board_properties, escape_pointer, ITEM_LISTS},
+    error::{Error, Result},
+    persist,
+};
+
+/// A property both sides changed in different ways. The merged board keeps
+/// `ours`, or the surviving item when one side deleted it.
+#[derive(Serialize)]
+pub struct MergeConflict {
+    /// `board` for board-level properties, else `element`, `widget` or `connection`
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// JSON pointer from the item (`/rect/x`); empty when the whole item conflicts
+    pub path: String,
+    /// `null` where the side doesn't have the property
+    pub base: Value,
+    pub ours: Value,
+    pub theirs: Value,
+}
+
+#[derive(Serialize)]
+pub struct MergeResult {
+    pub document: Value,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// (path, base, ours, theirs) of a conflicting value.
+type Conflict = (String, Value, Value, Value);
+
+/// Three-way merge of one value, `None` meaning absent. Objects merge key by
+/// key; anything else changed on both sides is a conflict. Arrays are merged
+/// whole, like `diff_values` compares them.
+fn merge_values(
+    path: &str,
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Value> {
+    if ours == theirs || base == theirs {
+        return ours.cloned();
+    }
+    if base == ours {
+        return theirs.cloned();
+    }
+    if let (Some(Value::Object(ours)), Some(Value::Object(theirs))) = (ours, theirs) {
+        let empty = Map::new();
+        let base = base.and_then(Value::as_object).unwrap_or(&empty);
+        let mut merged = Map::new();
+        for key in ours.keys().chain(theirs.keys().filter(|key| !ours.contains_key(*key))) {
+            let child = format!("{path}/{}", escape_pointer(key));
+            if let Some(value) = merge_values(&child, base.get(key), ours.get(key), theirs.get(key), conflicts) {
+                merged.insert(key.clone(), value);
+            }
+        }
+        return Some(Value::Object(merged));
+    }
+    let owned = |value: Option<&Value>| value.cloned().unwrap_or(Value::Null);
+    conflicts.push((path.to_string(), owned(base), owned(ours), owned(theirs)));
+    // A deletion on one side loses to an edit on the other
+    ours.or(theirs).cloned()
+}
+
+fn items_by_id<'a>(document: &'a Value, pointer: &str) -> BTreeMap<&'a str, &'a Value> {
+    let list = document.pointer(pointer).and_then(Value::as_array).into_iter().flatten();
+    list.filter_map(|item| Some((item.get("id")?.as_str()?, item))).collect()
+}
+
+/// Merge one item list by id, in `ours` order followed by the items only
+/// `theirs` added. Items without an id are kept as `ours` has them.
+fn merge_items(
+    (kind, pointer, _): (&'static str, &str, &str),
+    base: &Value,
+    ours: &Value,
+    theirs: &Value,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Vec<Value> {
+    let (base_items, their_items) = (items_by_id(base, pointer), items_by_id(theirs, pointer));
+    let our_list: Vec<&Value> = ours.pointer(pointer).and_then(Value::as_array).into_iter().flatten().collect();
+    let our_ids: BTreeSet<&str> = our_list.iter().filter_map(|item| item.get("id")?.as_str()).collect();
+    let their_list = theirs.pointer(pointer).and_then(Value::as_array).into_iter().flatten();
+
+    let mut merged = Vec::new();
+    let mut item_conflicts = Vec::new();
+    let mut merge_one = |id: &str, ours: Option<&Value>| {
+        let (base, theirs) = (base_items.get(id).copied(), their_items.get(id).copied());
+        let item = merge_values("", base, ours, theirs, &mut item_conflicts);
+        conflicts.extend(item_conflicts.drain(..).map(|(path, base, ours, theirs)| MergeConflict {
+            kind,
+            id: Some(id.to_string()),
+            path,
+            base,
+            ours,
+            theirs,
+        }));
+        item
+    };
+    for item in our_list {
+        match item.get("id").and_then(Value::as_str) {
+            Some(id) => merged.extend(merge_one(id, Some(item))),
+            None => merged.push(item.clone()),
+        }
+    }
+    for item in their_list {
+        match item.get("id").and_then(Value::as_str) {
+            Some(id) if !our_ids.contains(id) => merged.extend(merge_one(id, None)),
+            _ => {}
+        }
+    }
+    merged
+}
+
+/// Three-way merge of two boards edited from the same `base`, item by item:
+/// changes only one side made are taken as they are, and properties both
+/// sides changed differently are listed as conflicts for the user to settle.
+pub fn merge_documents(base: &Value, ours: &Value, theirs: &Value) -> MergeResult {
+    let mut conflicts = Vec::new();
+    let mut board_conflicts = Vec::new();
+    let mut document = merge_values(
+        "",
+        Some(&board_properties(base)),
+        Some(&board_properties(ours)),
+        Some(&board_properties(theirs)),
+        &mut board_conflicts,
+    )
+    .unwrap_or(Value::Null);
+    conflicts.extend(board_conflicts.into_iter().map(|(path, base, ours, theirs)| MergeConflict {
+        kind: "board",
+        id: None,
+        path,
+        base,
+        ours,
+        theirs,
+    }));
+
+    for list in ITEM_LISTS {
+        let items = merge_items(list, base, ours, theirs, &mut conflicts);
+        let key = list.1.trim_start_matches("/board/");
+        let present = |document: &Value| document.pointer(list.1).is_some();
+        if let Some(board) = document.get_mut("board").and_then(Value::as_object_mut) {
+            if !items.is_empty() || present(ours) || present(theirs) {
+                board.insert(key.to_string(), Value::Array(items));
+            }
+        }
+    }
+    if let Some(meta) = document.get_mut("meta").and_then(Value::as_object_mut) {
+        meta.insert("updatedAt".to_string(), persist::now_millis().into());
+    }
+    MergeResult { document, conflicts }
+}
+
+/// Merge the board at `theirs` into the one at `ours`, both edited from the
+/// one at `base`, e.g. the three versions Git hands a merge driver or two
+/// copies of a synced board. With `output`, the merged board is written there,
+/// with the images of both sides; otherwise the UI gets the result to review.
+pub fn merge_files(base: &str, ours: &str, theirs: &str, output: Option<&str>) -> Result<MergeResult> {
+    let result = merge_documents(
+        &board::read_board_document(base)?,
+        &board::read_board_document(ours)?,
+        &board::read_board_document(theirs)?,
+    );
+    if let Some(output) = output {
+        let bytes = board::merge_containers(&board::read_board(ours)?, &board::read_board(theirs)?, &result.document)?;
+        board::write_board(output, &bytes)?;
+    }
+    tracing::info!(conflicts = result.conflicts.len(), "boards merged");
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn merge_boards(base: String, ours: String, theirs: String, output: Option<String>) -> Result<MergeResult> {
+    tauri::async_runtime::spawn_blocking(move || merge_files(&base, &ours, &theirs, output.as_deref()))
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn note(id: &str, x: i64, text: &str) -> Value {
+        json!({ "id": id, "type": "text", "rect": { "x": x, "y": 0 }, "text": text })
+    }
+
+    fn document(elements: Vec<Value>) -> Value {
+        json!({
+            "version": 4,
+            "meta": { "title": "Board", "updatedAt": 1 },
+            "board": { "elements": elements, "widgets": [] },
+        })
+    }
+
+    fn elements(result: &MergeResult) -> &Vec<Value> {
+        result.document["board"]["elements"].as_array().unwrap()
+    }
+
+    fn conflict_paths(result: &MergeResult) -> Vec<(Option<&str>, &str)> {
+        result.conflicts.iter().map(|conflict| (conflict.id.as_deref(), conflict.path.as_str())).collect()
+    }
+
+    #[test]
+    fn edits_to_different_elements_merge_cleanly() {
+        let base = document(vec![note("a", 0, "a"), note("b", 0, "b")]);
+        let ours = document(vec![note("a", 10, "a"), note("b", 0, "b")]);
+        let theirs = document(vec![note("a", 0, "a"), note("b", 0, "changed"), note("c", 0, "c")]);
+        let result = merge_documents(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(elements(&result), &vec![note("a", 10, "a"), note("b", 0, "changed"), note("c", 0, "c")]);
+    }
+
+    #[test]
+    fn different_properties_of_one_element_merge_cleanly() {
+        let base = document(vec![note("a", 0, "a")]);
+        let ours = document(vec![note("a", 10, "a")]);
+        let theirs = document(vec![note("a", 0, "edited")]);
+        let result = merge_documents(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(elements(&result), &vec![note("a", 10, "edited")]);
+    }
+
+    #[test]
+    fn both_sides_adding_and_deleting_merge_cleanly() {
+        let base = document(vec![note("a", 0, "a"), note("b", 0, "b")]);
+        let ours = document(vec![note("b", 0, "b"), note("ours", 0, "")]);
+        let theirs = document(vec![note("theirs", 0, ""), note("b", 0, "b")]);
+        let result = merge_documents(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        let ids: Vec<_> = elements(&result).iter().map(|item| item["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["b", "ours", "theirs"]);
+    }
+
+    #[test]
+    fn conflicting_edits_keep_ours() {
+        let base = document(vec![note("a", 0, "base")]);
+        let ours = document(vec![note("a", 5, "ours")]);
+        let theirs = document(vec![note("a", 9, "theirs")]);
+        let result = merge_documents(&base, &ours, &theirs);
+        assert_eq!(elements(&result), &vec![note("a", 5, "ours")]);
+        assert_eq!(conflict_paths(&result), [(Some("a"), "/rect/x"), (Some("a"), "/text")]);
+        let conflict = &result.conflicts[1];
+        assert_eq!(conflict.kind, "element");
+        assert_eq!(
+            (&conflict.base, &conflict.ours, &conflict.theirs),
+            (&json!("base"), &json!("ours"), &json!("theirs"))
+        );
+    }
+
+    #[test]
+    fn the_same_edit_on_both_sides_is_no_conflict() {
+        let base = document(vec![note("a", 0, "a")]);
+        let edited = document(vec![note("a", 3, "a")]);
+        let result = merge_documents(&base, &edited, &edited);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(elements(&result), &vec![note("a", 3, "a")]);
+    }
+
+    #[test]
+    fn deleting_an_element_the_other_side_modified_keeps_it() {
+        let base = document(vec![note("a", 0, "a")]);
+        let modified = document(vec![note("a", 0, "edited")]);
+        let deleted = document(Vec::new());
+
+        for (ours, theirs) in [(&deleted, &modified), (&modified, &deleted)] {
+            let result = merge_documents(&base, ours, theirs);
+            assert_eq!(elements(&result), &vec![note("a", 0, "edited")]);
+            let [conflict] = &result.conflicts[..] else { panic!("expected one conflict") };
+            assert_eq!((conflict.id.as_deref(), conflict.path.as_str()), (Some("a"), ""));
+            assert_eq!(conflict.base, note("a", 0, "a"));
+        }
+    }
+
+    #[test]
+    fn deleting_an_unchanged_element_is_no_conflict() {
+        let base = document(vec![note("a", 0, "a")]);
+        let result = merge_documents(&base, &base, &document(Vec::new()));
+        assert!(result.conflicts.is_empty());
+        assert!(elements(&result).is_empty());
+    }
+
+    #[test]
+    fn board_properties_merge_and_conflict() {
+        let base = document(Vec::new());
+        let mut ours = base.clone();
+        ours["meta"]["title"] = json!("Ours");
+        ours["board"]["background"] = json!({ "pattern": "grid" });
+        let mut theirs = base.clone();
+        theirs["meta"]["title"] = json!("Theirs");
+        theirs["meta"]["description"] = json!("Added");
+        let result = merge_documents(&base, &ours, &theirs);
+        assert_eq!(result.document["meta"]["title"], "Ours");
+        assert_eq!(result.document["meta"]["description"], "Added");
+        assert_eq!(result.document["board"]["background"]["pattern"], "grid");
+        assert_eq!(conflict_paths(&result), [(None, "/meta/title")]);
+        assert_eq!(result.conflicts[0].kind, "board");
+        assert!(result.document["meta"]["updatedAt"].as_u64().unwrap() > 1);
+    }
+}
@@ -0,0 +1,253 @@
+//! Ordered upgrades of `board.json`, mirroring `packages/core/src/migrations`.
+//!
+//! Migrations operate on raw JSON so documents written by any past version can
+//! be upgraded before they are validated or deserialized.
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    document::CURRENT_DOCUMENT_VERSION,
+    error::{Error, Result},
+};
+
+struct Migration {
+    from: u32,
+    description: &'static str,
+    apply: fn(&mut Map<String, Value>),
+}
+
+/// Every step from version 0 to [`CURRENT_DOCUMENT_VERSION`]; append new
+/// entries when the format changes.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "Add elements and board background",
+        apply: |doc| {
+            let board = object_entry(doc, "board");
+            board.entry("elements").or_insert_with(|| json!([]));
+            board
+                .entry("background")
+                .or_insert_with(|| json!({ "pattern": "dots", "color": "auto" }));
+        },
+    },
+    Migration {
+        from: 1,
+        description: "Add inter-module data sharing",
+        apply: |doc| {
+            doc.entry("dataSharing")
+                .or_insert_with(|| json!({ "permissions": [], "links": [] }));
+        },
+    },
+    Migration {
+        from: 2,
+        description: "Add asset registry",
+        apply: |doc| {
+            doc.entry("assets").or_insert_with(|| json!({ "assets": {} }));
+        },
+    },
+    Migration {
+        from: 3,
+        description: "Move grid snapping into canvas settings",
+        apply: |doc| {
+            let board = object_entry(doc, "board");
+            let snap_to_grid = board
+                .get("grid")
+                .and_then(|grid| grid.get("enabled"))
+                .and_then(Value::as_bool)
+                .unwrap_or(true);
+            board.insert(
+                "canvasSettings".to_string(),
+                json!({ "zoomSensitivity": 0.002, "snapToGrid": snap_to_grid, "gridSpacing": 20 }),
+            );
+        },
+    },
+];
+
+/// A migration step that was run on a document.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedMigration {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub description: &'static str,
+}
+
+/// Get `key` as an object, replacing missing or malformed values.
+fn object_entry<'a>(doc: &'a mut Map<String, Value>, key: &str) -> &'a mut Map<String, Value> {
+    let value = doc.entry(key).or_insert_with(|| json!({}));
+    if !value.is_object() {
+        *value = json!({});
+    }
+    value.as_object_mut().expect("value was just made an object")
+}
+
+/// Version of a raw document; files from before versioning count as 0.
+pub fn document_version(document: &Value) -> Option<u32> {
+    match document.get("version") {
+        None => Some(0),
+        Some(version) => version.as_u64().and_then(|version| u32::try_from(version).ok()),
+    }
+}
+
+/// Upgrade `document` in place to the current version and return the steps taken.
+pub fn migrate(document: &mut Value) -> Result<Vec<AppliedMigration>> {
+    let version = document_version(document).ok_or_else(|| Error::InvalidBoard("invalid document version".into()))?;
+    if version > CURRENT_DOCUMENT_VERSION {
+        return Err(Error::UnsupportedVersion {
+            version,
+            supported: CURRENT_DOCUMENT_VERSION,
+        });
+    }
+    let Some(doc) = document.as_object_mut() else {
+        return Err(Error::InvalidBoard("document must be an object".into()));
+    };
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|migration| migration.from >= version) {
+        (migration.apply)(doc);
+        let to_version = migration.from + 1;
+        doc.insert("version".to_string(), json!(to_version));
+        applied.push(AppliedMigration {
+            from_version: migration.from,
+            to_version,
+            description: migration.description,
+        });
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A board as each version wrote it, oldest first.
+    fn fixtures() -> Vec<Value> {
+        vec![
+            json!({
+                "meta": { "title": "Board" },
+                "board": { "widgets": [], "grid": { "enabled": false } },
+            }),
+            json!({
+                "version": 1,
+                "meta": { "title": "Board" },
+                "board": { "widgets": [], "elements": [], "background": { "pattern": "lines", "color": "auto" } },
+            }),
+            json!({
+                "version": 2,
+                "meta": { "title": "Board" },
+                "board": { "widgets": [], "elements": [], "background": { "pattern": "dots", "color": "auto" } },
+                "dataSharing": { "permissions": [{ "id": "p" }], "links": [] },
+            }),
+            json!({
+                "version": 3,
+                "meta": { "title": "Board" },
+                "board": { "widgets": [], "elements": [], "background": { "pattern": "dots", "color": "auto" } },
+                "dataSharing": { "permissions": [], "links": [] },
+                "assets": { "assets": { "a": { "id": "a" } } },
+            }),
+        ]
+    }
+
+    #[test]
+    fn every_version_upgrades_to_the_current_one() {
+        assert_eq!(fixtures().len(), CURRENT_DOCUMENT_VERSION as usize);
+        for (version, mut document) in fixtures().into_iter().enumerate() {
+            let applied = migrate(&mut document).unwrap();
+            assert_eq!(document_version(&document), Some(CURRENT_DOCUMENT_VERSION));
+            assert_eq!(applied.len(), CURRENT_DOCUMENT_VERSION as usize - version);
+            let board = &document["board"];
+            assert!(board["elements"].is_array(), "v{version}");
+            assert!(board["background"].is_object(), "v{version}");
+            assert!(board["canvasSettings"].is_object(), "v{version}");
+            assert!(document["dataSharing"]["links"].is_array(), "v{version}");
+            assert!(document["assets"]["assets"].is_object(), "v{version}");
+        }
+    }
+
+    #[test]
+    fn unversioned_boards_get_every_default() {
+        let mut document = fixtures().remove(0);
+        migrate(&mut document).unwrap();
+        assert_eq!(
+            document,
+            json!({
+                "version": CURRENT_DOCUMENT_VERSION,
+                "meta": { "title": "Board" },
+                "board": {
+                    "widgets": [],
+                    "grid": { "enabled": false },
+                    "elements": [],
+                    "background": { "pattern": "dots", "color": "auto" },
+                    "canvasSettings": { "zoomSensitivity": 0.002, "snapToGrid": false, "gridSpacing": 20 },
+                },
+                "dataSharing": { "permissions": [], "links": [] },
+                "assets": { "assets": {} },
+            })
+        );
+    }
+
+    #[test]
+    fn existing_values_are_kept() {
+        let mut fixtures = fixtures();
+        let (mut v1, mut v2, mut v3) = (fixtures.remove(1), fixtures.remove(1), fixtures.remove(1));
+        migrate(&mut v1).unwrap();
+        migrate(&mut v2).unwrap();
+        migrate(&mut v3).unwrap();
+        assert_eq!(v1["board"]["background"]["pattern"], "lines");
+        assert_eq!(v2["dataSharing"]["permissions"][0]["id"], "p");
+        assert_eq!(v3["assets"]["assets"]["a"]["id"], "a");
+        // Grid snapping defaults to on without a grid setting
+        assert_eq!(v3["board"]["canvasSettings"]["snapToGrid"], true);
+    }
+
+    #[test]
+    fn migrating_again_changes_nothing() {
+        for mut document in fixtures() {
+            migrate(&mut document).unwrap();
+            let migrated = document.clone();
+            assert!(migrate(&mut document).unwrap().is_empty());
+            assert_eq!(document, migrated);
+        }
+    }
+
+    #[test]
+    fn applied_steps_are_reported_in_order() {
+        let mut document = fixtures().remove(1);
+        let applied = migrate(&mut document).unwrap();
+        let steps: Vec<_> = applied
+            .iter()
+            .map(|step| (step.from_version, step.to_version, step.description))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (1, 2, "Add inter-module data sharing"),
+                (2, 3, "Add asset registry"),
+                (3, 4, "Move grid snapping into canvas settings"),
+            ]
+        );
+        let reported = serde_json::to_value(&applied[0]).unwrap();
+        assert_eq!(
+            reported,
+            json!({ "fromVersion": 1, "toVersion": 2, "description": "Add inter-module data sharing" })
+        );
+    }
+
+    #[test]
+    fn newer_and_invalid_versions_are_rejected() {
+        let mut newer = json!({ "version": CURRENT_DOCUMENT_VERSION + 1 });
+        assert!(matches!(migrate(&mut newer), Err(Error::UnsupportedVersion { .. })));
+        assert_eq!(newer, json!({ "version": CURRENT_DOCUMENT_VERSION + 1 }));
+        assert!(migrate(&mut json!({ "version": "1" })).is_err());
+        assert!(migrate(&mut json!({ "version": -1 })).is_err());
+        assert!(migrate(&mut json!([])).is_err());
+    }
+
+    #[test]
+    fn malformed_sections_are_replaced() {
+        let mut document = json!({ "version": 0, "board": "oops" });
+        migrate(&mut document).unwrap();
+        assert!(document["board"]["elements"].is_array());
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Manager, Window, WindowEvent};
+
+use crate::{deep_link, error::Result, settings::Settings, windows};
+
+/// How long after a notification coming back to the app still counts as
+/// clicking it, where the OS doesn't tell us about the click itself.
+const CLICK_THROUGH_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    /// Board to bring up when the notification is clicked
+    pub board: Option<String>,
+}
+
+/// Target of the last notification, for platforms where clicking one only
+/// activates the app.
+#[derive(Default)]
+pub struct Notifications {
+    pending: Mutex<Option<(Instant, Option<String>)>>,
+}
+
+fn open_target(app: &AppHandle, board: Option<&str>) {
+    match board {
+        Some(board) => deep_link::handle_urls(app, vec![deep_link::board_link(board, None)]),
+        None => {
+            if let Some(window) = windows::target_window(app) {
+                windows::focus_window(&window);
+            }
+        }
+    }
+}
+
+/// Coming back to the app shortly after a notification follows it.
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    if !matches!(event, WindowEvent::Focused(true)) {
+        return;
+    }
+    let Some(notifications) = window.try_state::<Notifications>() else {
+        return;
+    };
+    let pending = notifications.pending.lock().unwrap().take();
+    if let Some((at, board)) = pending.filter(|(at, _)| at.elapsed() < CLICK_THROUGH_TIMEOUT) {
+        tracing::debug!(elapsed = ?at.elapsed(), "following notification");
+        open_target(window.app_handle(), board.as_deref());
+    }
+}
+
+/// Notifications delivered through the app's bundle activate it when clicked,
+/// and the next focus picks up the target.
+#[cfg(target_os = "macos")]
+#[allow(deprecated)]
+fn show(app: &AppHandle, notification: Notification) -> Result<()> {
+    use objc2_foundation::{NSString, NSUserNotification, NSUserNotificationCenter};
+
+    *app.state::<Notifications>().pending.lock().unwrap() = Some((Instant::now(), notification.board.clone()));
+    app.run_on_main_thread(move || {
+        let native = NSUserNotification::new();
+        native.setTitle(Some(&NSString::from_str(&notification.title)));
+        native.setInformativeText(Some(&NSString::from_str(&notification.body)));
+        NSUserNotificationCenter::defaultUserNotificationCenter().deliverNotification(&native);
+    })?;
+    Ok(())
+}
+
+/// Toasts are attributed to the app's identifier, which the installer
+/// registers, and open the board's `boardkit://` link when clicked.
+#[cfg(target_os = "windows")]
+fn show(app: &AppHandle, notification: Notification) -> Result<()> {
+    use crate::export::svg::escape;
+
+    let launch = notification
+        .board
+        .as_deref()
+        .map(|board| {
+            format!(
+                r#" activationType="protocol" launch="{}""#,
+                escape(deep_link::board_link(board, None).as_str())
+            )
+        })
+        .unwrap_or_default();
+    let xml = format!(
+        r#"<toast{launch}><visual><binding template="ToastGeneric"><text>{}</text><text>{}</text></binding></visual></toast>"#,
+        escape(&notification.title),
+        escape(&notification.body)
+    );
+    // Passed through the environment, since PowerShell quoting can't be
+    // trusted with board titles and folder names
+    const SCRIPT: &str = "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, \
+        ContentType = WindowsRuntime] > $null; \
+        [Windows.Data.Xml.Dom.XmlDocument, Windows.Data.Xml.Dom.XmlDocument, ContentType = WindowsRuntime] > $null; \
+        $xml = New-Object Windows.Data.Xml.Dom.XmlDocument; $xml.LoadXml($env:BOARDKIT_TOAST_XML); \
+        [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier($env:BOARDKIT_TOAST_APP_ID).Show(\
+        [Windows.UI.Notifications.ToastNotification]::new($xml))";
+    std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-WindowStyle", "Hidden", "-Command", SCRIPT])
+        .env("BOARDKIT_TOAST_XML", xml)
+        .env("BOARDKIT_TOAST_APP_ID", &app.config().identifier)
+        .spawn()?;
+    Ok(())
+}
+
+/// notify-send reports the clicked action when asked to wait; versions
+/// without actions get a plain notification.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn show(app: &AppHandle, notification: Notification) -> Result<()> {
+    use std::process::Command;
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let notify_send = || {
+            let mut command = Command::new("notify-send");
+            command.arg(format!("--app-name={}", app.package_info().name));
+            command
+        };
+        let clicked = notify_send()
+            .args(["--action=default=Open", "--wait", &notification.title, &notification.body])
+            .output();
+        match clicked {
+            Ok(output) if output.status.success() => {
+                if String::from_utf8_lossy(&output.stdout).trim() == "default" {
+                    open_target(&app, notification.board.as_deref());
+                }
+            }
+            Ok(_) => {
+                let _ = notify_send().args([&notification.title, &notification.body]).status();
+            }
+            Err(err) => tracing::debug!("notify-send: {err}"),
+        }
+    });
+    Ok(())
+}
+
+/// Show an OS notification unless the user turned them off or is looking at
+/// the app already, where an in-app toast does the job.
+pub fn notify(app: &AppHandle, notification: Notification) {
+    if !app.state::<Settings>().get().notifications_enabled {
+        return;
+    }
+    if app.webview_windows().values().any(|window| window.is_focused().unwrap_or(false)) {
+        return;
+    }
+    if let Err(err) = show(app, notification) {
+        tracing::warn!("failed to show notification: {err}");
+    }
+}
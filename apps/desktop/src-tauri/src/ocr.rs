@@ -0,0 +1,70 @@
+use std::{path::Path, process::Command};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    assets::AssetStore,
+    error::{Error, Result},
+    search::SearchIndex,
+};
+
+const TESSERACT: &str = "tesseract";
+const DEFAULT_LANG: &str = "eng";
+
+fn ocr_error(msg: impl Into<String>) -> Error {
+    Error::Ocr(msg.into())
+}
+
+/// Tesseract language codes, joined with `+` to recognize several at once.
+fn validate_lang(lang: &str) -> Result<()> {
+    let valid_code = |code: &str| !code.is_empty() && code.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+    if lang.split('+').all(valid_code) {
+        Ok(())
+    } else {
+        Err(ocr_error(format!("invalid language {lang}")))
+    }
+}
+
+/// Run the Tesseract CLI on an image file. It sniffs the format itself, so
+/// the extensionless blobs in the asset store work as is.
+fn recognize(path: &Path, lang: &str) -> Result<String> {
+    let output = match Command::new(TESSERACT).arg(path).arg("stdout").args(["-l", lang]).output() {
+        Ok(output) => output,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(Error::NotFound(format!("{TESSERACT} is not installed")));
+        }
+        Err(err) => return Err(Error::Io(err)),
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ocr_error(stderr.lines().last().unwrap_or("tesseract failed").trim().to_string()));
+    }
+    // Collapse the blank lines Tesseract puts between blocks
+    let text = String::from_utf8_lossy(&output.stdout);
+    Ok(text.lines().map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<_>>().join("\n"))
+}
+
+/// Extract the text in a stored image, such as a pasted screenshot, and keep
+/// it for search. Results are cached per hash, so repeated calls are cheap.
+/// Needs Tesseract on the PATH; without it this fails with `not_found`.
+#[tauri::command]
+pub async fn ocr_asset(app: AppHandle, hash: String, lang: Option<String>) -> Result<String> {
+    let lang = lang.unwrap_or_else(|| DEFAULT_LANG.to_string());
+    validate_lang(&lang)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let index = app.state::<SearchIndex>();
+        if let Some(text) = index.asset_text(&hash)? {
+            return Ok(text);
+        }
+        let path = app.state::<AssetStore>().blob_path(&hash)?;
+        if !path.is_file() {
+            return Err(Error::NotFound(format!("asset {hash}")));
+        }
+        let text = recognize(&path, &lang)?;
+        index.set_asset_text(&hash, &text)?;
+        tracing::info!(hash, chars = text.len(), "recognized text in image");
+        Ok(text)
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
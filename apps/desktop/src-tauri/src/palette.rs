@@ -0,0 +1,175 @@
+use std::{cmp::Reverse, collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::{error::Result, recent::RecentFiles, search::SearchIndex, workspace::Workspace};
+
+const DEFAULT_LIMIT: usize = 30;
+/// Full-text hits to rank alongside commands and boards
+const SEARCH_LIMIT: u32 = 40;
+/// Recently opened boards outrank equally good matches, the newest most
+const RECENT_BOOST: i32 = 120;
+/// Full-text matches rank below names matching as well, in index order
+const ELEMENT_BASE_SCORE: i32 = 300;
+
+/// An action the palette can run, as registered in the frontend.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaletteCommand {
+    id: String,
+    title: String,
+    #[serde(default)]
+    keywords: Vec<String>,
+    #[serde(default)]
+    priority: i32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum PaletteResult {
+    #[serde(rename_all = "camelCase")]
+    Command { id: String, title: String, score: i32 },
+    #[serde(rename_all = "camelCase")]
+    Board {
+        path: String,
+        title: String,
+        recent: bool,
+        score: i32,
+    },
+    #[serde(rename_all = "camelCase")]
+    Element {
+        path: String,
+        board_title: String,
+        element_id: String,
+        snippet: String,
+        score: i32,
+    },
+}
+
+impl PaletteResult {
+    fn score(&self) -> i32 {
+        match self {
+            Self::Command { score, .. } | Self::Board { score, .. } | Self::Element { score, .. } => *score,
+        }
+    }
+}
+
+/// How well `term` matches `text`: whole text, then prefix, word start,
+/// anywhere, and finally as a subsequence, with shorter texts first.
+fn term_score(term: &str, text: &str) -> Option<i32> {
+    let length_penalty = text.chars().count().min(100) as i32;
+    if text == term {
+        return Some(1000);
+    }
+    if text.starts_with(term) {
+        return Some(800 - length_penalty);
+    }
+    if let Some(index) = text.find(term) {
+        let at_word = text[..index].ends_with(|c: char| !c.is_alphanumeric());
+        return Some(if at_word { 600 } else { 400 } - length_penalty);
+    }
+    // Subsequence: every character in order, losing points for each gap
+    let mut gaps = 0;
+    let mut chars = text.chars();
+    for wanted in term.chars() {
+        loop {
+            match chars.next() {
+                Some(c) if c == wanted => break,
+                Some(_) => gaps += 1,
+                None => return None,
+            }
+        }
+    }
+    Some((200 - gaps.min(150) - length_penalty).max(1))
+}
+
+/// Every word of the query has to match one of `texts`; the best match per
+/// word counts.
+fn score(terms: &[String], texts: &[&str]) -> Option<i32> {
+    let texts: Vec<String> = texts.iter().map(|text| text.to_lowercase()).collect();
+    terms.iter().try_fold(0, |total, term| {
+        let best = texts.iter().filter_map(|text| term_score(term, text)).max()?;
+        Some(total + best)
+    })
+}
+
+fn file_stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Rank commands, boards and the text inside boards against `query` in one
+/// pass. Boards come from the recent list and the last workspace scan, so
+/// nothing is read from disk; element matches come from the search index.
+#[tauri::command]
+pub fn palette_query(
+    recent: State<'_, RecentFiles>,
+    workspace: State<'_, Workspace>,
+    index: State<'_, SearchIndex>,
+    query: String,
+    commands: Vec<PaletteCommand>,
+    limit: Option<usize>,
+) -> Result<Vec<PaletteResult>> {
+    let terms: Vec<String> = query.to_lowercase().split_whitespace().map(str::to_string).collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut results: Vec<PaletteResult> = commands
+        .into_iter()
+        .filter_map(|command| {
+            let mut texts = vec![command.title.as_str()];
+            texts.extend(command.keywords.iter().map(String::as_str));
+            let score = score(&terms, &texts)? + command.priority;
+            Some(PaletteResult::Command {
+                id: command.id,
+                title: command.title,
+                score,
+            })
+        })
+        .collect();
+
+    // Boards in both lists keep the workspace title and their recent rank
+    let recent_entries = recent.entries();
+    let mut boards: HashMap<String, (String, Option<usize>)> = HashMap::new();
+    for info in workspace.boards() {
+        boards.insert(info.path, (info.title, None));
+    }
+    for (rank, path) in recent_entries.iter().enumerate() {
+        let title = boards.remove(path).map(|(title, _)| title).unwrap_or_else(|| file_stem(path));
+        boards.insert(path.clone(), (title, Some(rank)));
+    }
+    for (path, (title, recent_rank)) in boards {
+        let name = file_stem(&path);
+        let Some(mut score) = score(&terms, &[title.as_str(), name.as_str()]) else {
+            continue;
+        };
+        if let Some(rank) = recent_rank {
+            score += RECENT_BOOST - rank as i32 * (RECENT_BOOST / recent_entries.len().max(1) as i32);
+        }
+        let title = if title.trim().is_empty() { name } else { title };
+        results.push(PaletteResult::Board {
+            path,
+            title,
+            recent: recent_rank.is_some(),
+            score,
+        });
+    }
+
+    let hits = index.search(&query, SEARCH_LIMIT)?;
+    results.extend(hits.into_iter().enumerate().filter_map(|(rank, hit)| {
+        Some(PaletteResult::Element {
+            element_id: hit.element_id?,
+            path: hit.path,
+            board_title: hit.title,
+            snippet: hit.snippet,
+            score: ELEMENT_BASE_SCORE - rank as i32,
+        })
+    }));
+
+    results.sort_by_key(|result| Reverse(result.score()));
+    results.truncate(limit.unwrap_or(DEFAULT_LIMIT));
+    Ok(results)
+}
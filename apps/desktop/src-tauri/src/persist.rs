@@ -0,0 +1,86 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::Result;
+
+/// Resolve a file inside the app data directory, creating the directory if needed.
+pub fn app_data_file(app: &AppHandle, name: &str) -> Result<PathBuf> {
+    let dir = app.path().app_data_dir()?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(name))
+}
+
+/// Read a JSON store, falling back to the default value when the file is
+/// missing or unreadable so a corrupt store never blocks startup.
+pub fn read_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let bytes = serde_json::to_vec_pretty(value)?;
+    write_atomic(path, &bytes)?;
+    Ok(())
+}
+
+/// Write to a sibling temp file, fsync it, then rename over the target so an
+/// interrupted write never leaves a half-written file behind.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, bytes, |tmp_path| fs::File::create(tmp_path))
+}
+
+/// Like [`write_atomic`], for secrets: on Unix the file is created with mode
+/// 0600, so only the user can read it. On Windows it inherits the ACL of its
+/// folder, which in the app data dir is private to the user as well.
+pub fn write_private(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, bytes, |tmp_path| {
+        // The mode only applies to new files, so never reuse a leftover
+        let _ = fs::remove_file(tmp_path);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(tmp_path)
+    })
+}
+
+fn write_atomic_with(path: &Path, bytes: &[u8], create: impl FnOnce(&Path) -> io::Result<fs::File>) -> io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = create(&tmp_path)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Milliseconds since the Unix epoch, matching the frontend's `Date.now()`.
+pub fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Reduce an identifier to characters that are safe in a file name.
+pub fn sanitize_file_stem(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
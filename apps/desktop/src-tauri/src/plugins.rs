@@ -0,0 +1,272 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{ipc::Response, AppHandle, Emitter, Manager, State};
+
+use crate::{
+    error::{Error, Result},
+    menu, persist, windows,
+};
+
+/// Folder in the app data dir holding one sub-folder per plugin.
+const PLUGINS_DIR: &str = "plugins";
+const MANIFEST_FILE: &str = "plugin.json";
+const MODULE_FILE: &str = "plugin.wasm";
+const ENABLED_STORE: &str = "plugins-enabled.json";
+/// Modules are handed to the webview whole.
+const MAX_MODULE_SIZE: u64 = 32 * 1024 * 1024;
+/// Native menu ids of plugin commands: `plugin:<plugin>/<command>`.
+pub const PLUGIN_ITEM_PREFIX: &str = "plugin:";
+
+/// What a plugin may ask of the host. Plugins only ever see the board state
+/// they are granted, and changes are applied by the app as one undo step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Permission {
+    #[serde(rename = "board:read")]
+    BoardRead,
+    #[serde(rename = "board:write")]
+    BoardWrite,
+}
+
+/// A command a plugin adds to the command palette, and optionally to the
+/// Plugins menu.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginCommand {
+    /// Passed to the module's `run` export
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub subtitle: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub menu: bool,
+}
+
+/// `plugin.json`, next to the module in the plugin's folder.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub commands: Vec<PluginCommand>,
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    #[serde(flatten)]
+    pub manifest: PluginManifest,
+    pub enabled: bool,
+    /// Plugin folder
+    pub path: String,
+}
+
+/// Payload of the `plugin-command` event run by the board window.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginCommandEvent {
+    plugin: String,
+    command: String,
+}
+
+fn unknown_plugin(id: &str) -> Error {
+    Error::NotFound(format!("plugin {id}"))
+}
+
+/// Ids end up in menu ids and event payloads, so keep them to safe characters.
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_' || b == b'.')
+}
+
+fn read_manifest(dir: &Path) -> Result<PluginManifest> {
+    let manifest: PluginManifest = serde_json::from_slice(&fs::read(dir.join(MANIFEST_FILE))?)?;
+    if !valid_id(&manifest.id) {
+        return Err(Error::Plugin(format!("invalid plugin id {:?}", manifest.id)));
+    }
+    if let Some(command) = manifest.commands.iter().find(|command| !valid_id(&command.id)) {
+        return Err(Error::Plugin(format!("invalid command id {:?}", command.id)));
+    }
+    Ok(manifest)
+}
+
+/// WebAssembly plugins installed by the user. Each lives in its own folder
+/// of the plugins dir with a `plugin.json` manifest and a `plugin.wasm`
+/// module; the board window instantiates enabled modules in a sandboxed
+/// worker. Plugins are never fetched from anywhere.
+pub struct Plugins {
+    dir: PathBuf,
+    enabled_path: PathBuf,
+    enabled: Mutex<BTreeSet<String>>,
+}
+
+impl Plugins {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let dir = persist::app_data_file(app, PLUGINS_DIR)?;
+        fs::create_dir_all(&dir)?;
+        let enabled_path = persist::app_data_file(app, ENABLED_STORE)?;
+        Ok(Self {
+            dir,
+            enabled: Mutex::new(persist::read_json(&enabled_path)),
+            enabled_path,
+        })
+    }
+
+    /// Plugins found on disk, by name. Folders with a broken manifest are
+    /// skipped.
+    pub fn list(&self) -> Vec<PluginInfo> {
+        let enabled = self.enabled.lock().unwrap();
+        let mut plugins: Vec<PluginInfo> = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter_map(|path| match read_manifest(&path) {
+                Ok(manifest) => Some(PluginInfo {
+                    enabled: enabled.contains(&manifest.id),
+                    manifest,
+                    path: path.to_string_lossy().into_owned(),
+                }),
+                Err(err) => {
+                    tracing::warn!("skipping plugin in {}: {err}", path.display());
+                    None
+                }
+            })
+            .collect();
+        plugins.sort_by_key(|plugin| plugin.manifest.name.to_lowercase());
+        // Two folders declaring the same id: the first one wins
+        let mut seen = BTreeSet::new();
+        plugins.retain(|plugin| seen.insert(plugin.manifest.id.clone()));
+        plugins
+    }
+
+    fn find(&self, id: &str) -> Result<PluginInfo> {
+        self.list().into_iter().find(|plugin| plugin.manifest.id == id).ok_or_else(|| unknown_plugin(id))
+    }
+
+    fn find_enabled(&self, id: &str) -> Result<PluginInfo> {
+        let plugin = self.find(id)?;
+        if !plugin.enabled {
+            return Err(Error::Plugin(format!("{} is disabled", plugin.manifest.name)));
+        }
+        Ok(plugin)
+    }
+
+    fn set_enabled(&self, id: &str, enabled: bool) -> Result<PluginInfo> {
+        let mut plugin = self.find(id)?;
+        let mut ids = self.enabled.lock().unwrap();
+        if enabled {
+            ids.insert(id.to_string());
+        } else {
+            ids.remove(id);
+        }
+        persist::write_json(&self.enabled_path, &*ids)?;
+        plugin.enabled = enabled;
+        Ok(plugin)
+    }
+
+    /// Commands of enabled plugins that asked for a menu item.
+    pub fn menu_commands(&self) -> Vec<(PluginManifest, PluginCommand)> {
+        self.list()
+            .into_iter()
+            .filter(|plugin| plugin.enabled)
+            .flat_map(|plugin| {
+                let manifest = plugin.manifest;
+                manifest
+                    .commands
+                    .clone()
+                    .into_iter()
+                    .filter(|command| command.menu)
+                    .map(move |command| (manifest.clone(), command))
+            })
+            .collect()
+    }
+
+    fn module(&self, id: &str) -> Result<Vec<u8>> {
+        let plugin = self.find_enabled(id)?;
+        let path = Path::new(&plugin.path).join(MODULE_FILE);
+        let path_str = path.to_string_lossy();
+        let size = fs::metadata(&path).map_err(|err| Error::from_io(err, &path_str))?.len();
+        if size > MAX_MODULE_SIZE {
+            return Err(Error::TooLarge {
+                size,
+                max: MAX_MODULE_SIZE,
+            });
+        }
+        fs::read(&path).map_err(|err| Error::from_io(err, &path_str))
+    }
+}
+
+/// Ask the board window to run a command of an enabled plugin. Used by the
+/// Plugins menu and [`run_plugin_command`].
+pub fn dispatch(app: &AppHandle, plugin: &str, command: &str) -> Result<()> {
+    let info = app.state::<Plugins>().find_enabled(plugin)?;
+    if !info.manifest.commands.iter().any(|known| known.id == command) {
+        return Err(Error::NotFound(format!("command {command} of plugin {plugin}")));
+    }
+    let window = windows::target_window(app).ok_or_else(|| Error::Plugin("no board window is open".to_string()))?;
+    let event = PluginCommandEvent {
+        plugin: plugin.to_string(),
+        command: command.to_string(),
+    };
+    window.emit_to(window.label(), "plugin-command", event)?;
+    Ok(())
+}
+
+/// Handle a click on a `plugin:<plugin>/<command>` menu item.
+pub fn handle_menu_item(app: &AppHandle, item: &str) {
+    let Some((plugin, command)) = item.split_once('/') else {
+        return;
+    };
+    if let Err(err) = dispatch(app, plugin, command) {
+        tracing::warn!("failed to run plugin command {item}: {err}");
+    }
+}
+
+#[tauri::command]
+pub fn list_plugins(plugins: State<'_, Plugins>) -> Vec<PluginInfo> {
+    plugins.list()
+}
+
+#[tauri::command]
+pub fn get_plugins_directory(plugins: State<'_, Plugins>) -> String {
+    plugins.dir.to_string_lossy().into_owned()
+}
+
+/// Turn a plugin on or off. Board windows load or unload it on
+/// `plugins-changed`, and the Plugins menu follows.
+#[tauri::command]
+pub fn enable_plugin(app: AppHandle, plugins: State<'_, Plugins>, id: String, enabled: bool) -> Result<PluginInfo> {
+    let plugin = plugins.set_enabled(&id, enabled)?;
+    tracing::info!(plugin = id, enabled, "plugin toggled");
+    menu::refresh(&app)?;
+    app.emit("plugins-changed", ())?;
+    Ok(plugin)
+}
+
+/// The module of an enabled plugin, as raw bytes for `WebAssembly.compile`.
+#[tauri::command]
+pub fn read_plugin_module(plugins: State<'_, Plugins>, id: String) -> Result<Response> {
+    Ok(Response::new(plugins.module(&id)?))
+}
+
+/// Run a command of an enabled plugin in the focused board window.
+#[tauri::command]
+pub fn run_plugin_command(app: AppHandle, plugin: String, command: String) -> Result<()> {
+    dispatch(&app, &plugin, &command)
+}
@@ -0,0 +1,274 @@
+//! System power signals. Autosave pauses while the system runs in battery
+//! saver (Low Power Mode on macOS, the power-saver profile on Linux) or is
+//! asleep, and before the system sleeps queued work is flushed and the
+//! frontend gets `system-will-sleep` to commit the edit in progress.
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{autosave::Autosave, window_state::WindowState};
+
+/// Battery saver can't be observed everywhere, so it is polled.
+const LOW_POWER_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What autosave has to hold back for, as sent with `power-state-changed`.
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerState {
+    pub low_power: bool,
+    pub suspended: bool,
+}
+
+#[derive(Default)]
+pub struct Power {
+    low_power: AtomicBool,
+    suspended: AtomicBool,
+}
+
+impl Power {
+    pub fn state(&self) -> PowerState {
+        PowerState {
+            low_power: self.low_power.load(Ordering::Relaxed),
+            suspended: self.suspended.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn autosave_paused(&self) -> bool {
+        let state = self.state();
+        state.low_power || state.suspended
+    }
+}
+
+fn changed(app: &AppHandle, power: &Power) {
+    let state = power.state();
+    tracing::info!(low_power = state.low_power, suspended = state.suspended, "power state changed");
+    let _ = app.emit("power-state-changed", state);
+}
+
+fn set_low_power(app: &AppHandle, low_power: bool) {
+    let power = app.state::<Power>();
+    if power.low_power.swap(low_power, Ordering::Relaxed) != low_power {
+        changed(app, &power);
+    }
+}
+
+/// The system is about to sleep: write what is queued while there is time.
+fn will_sleep(app: &AppHandle) {
+    let power = app.state::<Power>();
+    power.suspended.store(true, Ordering::Relaxed);
+    let _ = app.emit("system-will-sleep", ());
+    app.state::<Autosave>().flush();
+    app.state::<WindowState>().save_all();
+    changed(app, &power);
+}
+
+fn did_wake(app: &AppHandle) {
+    let power = app.state::<Power>();
+    if power.suspended.swap(false, Ordering::Relaxed) {
+        changed(app, &power);
+    }
+}
+
+/// Start following the system's power state.
+pub fn start(app: &AppHandle) {
+    let handle = app.clone();
+    thread::spawn(move || loop {
+        set_low_power(&handle, platform::low_power_mode());
+        thread::sleep(LOW_POWER_POLL_INTERVAL);
+    });
+    platform::watch_sleep(app);
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{ptr::NonNull, sync::OnceLock};
+
+    use block2::RcBlock;
+    use objc2_app_kit::{NSWorkspace, NSWorkspaceDidWakeNotification, NSWorkspaceWillSleepNotification};
+    use objc2_foundation::{NSNotification, NSProcessInfo};
+    use tauri::AppHandle;
+
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+
+    pub fn low_power_mode() -> bool {
+        NSProcessInfo::processInfo().isLowPowerModeEnabled()
+    }
+
+    pub fn watch_sleep(app: &AppHandle) {
+        let _ = APP.set(app.clone());
+        let center = NSWorkspace::sharedWorkspace().notificationCenter();
+        let will_sleep = RcBlock::new(|_: NonNull<NSNotification>| {
+            if let Some(app) = APP.get() {
+                super::will_sleep(app);
+            }
+        });
+        let did_wake = RcBlock::new(|_: NonNull<NSNotification>| {
+            if let Some(app) = APP.get() {
+                super::did_wake(app);
+            }
+        });
+        // The observers live as long as the app, so their tokens are never removed
+        unsafe {
+            let _ = center.addObserverForName_object_queue_usingBlock(
+                Some(NSWorkspaceWillSleepNotification),
+                None,
+                None,
+                &will_sleep,
+            );
+            let _ = center.addObserverForName_object_queue_usingBlock(
+                Some(NSWorkspaceDidWakeNotification),
+                None,
+                None,
+                &did_wake,
+            );
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{ffi::c_void, sync::OnceLock};
+
+    use tauri::AppHandle;
+    use windows::Win32::{
+        Foundation::{ERROR_SUCCESS, HANDLE},
+        System::Power::{
+            GetSystemPowerStatus, PowerRegisterSuspendResumeNotification, DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS,
+            SYSTEM_POWER_STATUS,
+        },
+        UI::WindowsAndMessaging::{DEVICE_NOTIFY_CALLBACK, PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND},
+    };
+
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+
+    pub fn low_power_mode() -> bool {
+        let mut status = SYSTEM_POWER_STATUS::default();
+        // Bit 0 is set while battery saver is on
+        unsafe { GetSystemPowerStatus(&mut status) }.is_ok() && status.SystemStatusFlag & 1 != 0
+    }
+
+    unsafe extern "system" fn on_power_event(_context: *const c_void, kind: u32, _setting: *const c_void) -> u32 {
+        if let Some(app) = APP.get() {
+            match kind {
+                PBT_APMSUSPEND => super::will_sleep(app),
+                PBT_APMRESUMEAUTOMATIC => super::did_wake(app),
+                _ => {}
+            }
+        }
+        ERROR_SUCCESS.0
+    }
+
+    pub fn watch_sleep(app: &AppHandle) {
+        let _ = APP.set(app.clone());
+        // Windows keeps pointing at the parameters for as long as the app runs
+        let parameters = Box::leak(Box::new(DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+            Callback: Some(on_power_event),
+            Context: std::ptr::null_mut(),
+        }));
+        let recipient = HANDLE(std::ptr::from_mut(parameters).cast());
+        let mut registration = std::ptr::null_mut();
+        let result =
+            unsafe { PowerRegisterSuspendResumeNotification(DEVICE_NOTIFY_CALLBACK, recipient, &mut registration) };
+        if result != ERROR_SUCCESS {
+            tracing::warn!("power: failed to register for suspend notifications: {result:?}");
+        }
+    }
+}
+
+/// Sleep comes from logind, which waits for the delay lock held here to be
+/// released, and battery saver from power-profiles-daemon.
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::{thread, time::Duration};
+
+    use dbus::{
+        arg::OwnedFd,
+        blocking::{stdintf::org_freedesktop_dbus::Properties, Connection},
+        message::MatchRule,
+    };
+    use tauri::AppHandle;
+
+    const LOGIND: &str = "org.freedesktop.login1";
+    const LOGIND_PATH: &str = "/org/freedesktop/login1";
+    const LOGIND_MANAGER: &str = "org.freedesktop.login1.Manager";
+    /// Current and older names of power-profiles-daemon
+    const POWER_PROFILES: [(&str, &str); 2] = [
+        ("org.freedesktop.UPower.PowerProfiles", "/org/freedesktop/UPower/PowerProfiles"),
+        ("net.hadess.PowerProfiles", "/net/hadess/PowerProfiles"),
+    ];
+    const TIMEOUT: Duration = Duration::from_secs(5);
+    /// How long the frontend gets to commit its edits before sleep goes ahead
+    const SLEEP_GRACE: Duration = Duration::from_millis(1500);
+
+    pub fn low_power_mode() -> bool {
+        let Ok(connection) = Connection::new_system() else {
+            return false;
+        };
+        POWER_PROFILES.iter().any(|(name, path)| {
+            let proxy = connection.with_proxy(*name, *path, TIMEOUT);
+            proxy.get::<String>(name, "ActiveProfile").is_ok_and(|profile| profile == "power-saver")
+        })
+    }
+
+    fn inhibit(connection: &Connection) -> Option<OwnedFd> {
+        let proxy = connection.with_proxy(LOGIND, LOGIND_PATH, TIMEOUT);
+        let reply: Result<(OwnedFd,), _> =
+            proxy.method_call(LOGIND_MANAGER, "Inhibit", ("sleep", "Boardkit", "Saving open boards", "delay"));
+        reply
+            .map(|(lock,)| lock)
+            .map_err(|err| tracing::warn!("power: failed to take a sleep delay lock: {err}"))
+            .ok()
+    }
+
+    fn watch(app: AppHandle) -> Result<(), dbus::Error> {
+        let connection = Connection::new_system()?;
+        let mut lock = inhibit(&connection);
+        let rule = MatchRule::new_signal(LOGIND_MANAGER, "PrepareForSleep");
+        connection.add_match(rule, move |(sleeping,): (bool,), connection: &Connection, _| {
+            if sleeping {
+                super::will_sleep(&app);
+                thread::sleep(SLEEP_GRACE);
+                // Releasing the lock lets the system sleep
+                lock.take();
+            } else {
+                super::did_wake(&app);
+                lock = inhibit(connection);
+            }
+            true
+        })?;
+        loop {
+            connection.process(Duration::from_secs(60))?;
+        }
+    }
+
+    pub fn watch_sleep(app: &AppHandle) {
+        let app = app.clone();
+        thread::spawn(move || {
+            if let Err(err) = watch(app) {
+                tracing::warn!("power: not following system sleep: {err}");
+            }
+        });
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod platform {
+    use tauri::AppHandle;
+
+    pub fn low_power_mode() -> bool {
+        false
+    }
+
+    pub fn watch_sleep(_app: &AppHandle) {}
+}
+
+#[tauri::command]
+pub fn get_power_state(power: State<'_, Power>) -> PowerState {
+    power.state()
+}
@@ -0,0 +1,185 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder, Window, WindowEvent};
+
+use crate::{
+    document::BoardDocument,
+    error::{Error, Result},
+    export::{
+        frames::{self, Frame},
+        preset::ExportFormat,
+        svg::Resources,
+    },
+};
+
+const PRESENTATION_LABEL: &str = "presentation";
+const NOTES_LABEL: &str = "presenter-notes";
+const PRESENTATION_PAGE: &str = "presentation.html";
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PresentationOptions {
+    /// Saved .boardkit file to read image assets from
+    pub board_path: Option<String>,
+}
+
+struct Slide {
+    label: String,
+    svg: String,
+    notes: String,
+}
+
+struct Deck {
+    slides: Vec<Slide>,
+    current: usize,
+}
+
+/// The slides of the running presentation, rendered up front so moving
+/// between them is instant. Both presentation windows show `current`.
+#[derive(Default)]
+pub struct Presentation {
+    deck: Mutex<Option<Deck>>,
+}
+
+/// What the presentation and presenter-notes windows display.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlideView {
+    index: usize,
+    total: usize,
+    label: String,
+    svg: String,
+    notes: String,
+    /// Label of the slide after this one, for the presenter
+    next_label: Option<String>,
+}
+
+impl Deck {
+    fn view(&self) -> SlideView {
+        let slide = &self.slides[self.current];
+        SlideView {
+            index: self.current,
+            total: self.slides.len(),
+            label: slide.label.clone(),
+            svg: slide.svg.clone(),
+            notes: slide.notes.clone(),
+            next_label: self.slides.get(self.current + 1).map(|slide| slide.label.clone()),
+        }
+    }
+}
+
+fn render_deck(document: &BoardDocument, frame_ids: Option<&[String]>, options: &PresentationOptions) -> Result<Deck> {
+    let all = frames::frames(document);
+    let selected: Vec<&Frame> = match frame_ids {
+        Some(ids) => ids
+            .iter()
+            .map(|id| {
+                all.iter()
+                    .find(|frame| frame.id == *id)
+                    .ok_or_else(|| Error::NotFound(format!("frame {id}")))
+            })
+            .collect::<Result<_>>()?,
+        None => all.iter().collect(),
+    };
+    if selected.is_empty() {
+        return Err(Error::NotFound("frames on this board".to_string()));
+    }
+    let resources = Resources::load(options.board_path.as_deref(), true)?;
+    let slides = selected
+        .into_iter()
+        .map(|frame| {
+            let svg = frames::render_frame(document, frame, ExportFormat::Svg, &resources, 1.0)?;
+            Ok(Slide {
+                label: frame.label.clone(),
+                svg: String::from_utf8_lossy(&svg).into_owned(),
+                notes: frames::notes(document, frame),
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok(Deck { slides, current: 0 })
+}
+
+fn broadcast(app: &AppHandle, view: &SlideView) {
+    for label in [PRESENTATION_LABEL, NOTES_LABEL] {
+        let _ = app.emit_to(label, "presentation-slide", view);
+    }
+}
+
+fn close_windows(app: &AppHandle) {
+    for label in [PRESENTATION_LABEL, NOTES_LABEL] {
+        if let Some(window) = app.get_webview_window(label) {
+            let _ = window.close();
+        }
+    }
+}
+
+/// Closing either window ends the presentation.
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    if !matches!(window.label(), PRESENTATION_LABEL | NOTES_LABEL) || !matches!(event, WindowEvent::Destroyed) {
+        return;
+    }
+    let app = window.app_handle();
+    if let Some(presentation) = app.try_state::<Presentation>() {
+        presentation.deck.lock().unwrap().take();
+    }
+    close_windows(app);
+}
+
+/// Present the board's frames, or just `frames` in the order given: the
+/// slides fill a borderless fullscreen window, and a second window shows the
+/// presenter the current slide's notes and what comes next. Arrow keys and
+/// Escape in either window go through `step_presentation` and
+/// `stop_presentation`.
+#[tauri::command]
+pub async fn start_presentation(
+    app: AppHandle,
+    board_json: BoardDocument,
+    frames: Option<Vec<String>>,
+    options: Option<PresentationOptions>,
+) -> Result<()> {
+    let options = options.unwrap_or_default();
+    let deck = tauri::async_runtime::spawn_blocking(move || render_deck(&board_json, frames.as_deref(), &options))
+        .await
+        .map_err(|err| Error::Render(err.to_string()))??;
+    let slides = deck.slides.len();
+    close_windows(&app);
+    *app.state::<Presentation>().deck.lock().unwrap() = Some(deck);
+
+    WebviewWindowBuilder::new(&app, NOTES_LABEL, WebviewUrl::App(format!("{PRESENTATION_PAGE}?view=notes").into()))
+        .title("Presenter Notes")
+        .inner_size(560.0, 420.0)
+        .build()?;
+    WebviewWindowBuilder::new(&app, PRESENTATION_LABEL, WebviewUrl::App(PRESENTATION_PAGE.into()))
+        .title("Presentation")
+        .decorations(false)
+        .fullscreen(true)
+        .focused(true)
+        .build()?;
+    tracing::info!(slides, "presentation started");
+    Ok(())
+}
+
+/// The slide on screen, for a presentation window that just loaded.
+#[tauri::command]
+pub fn get_presentation_slide(presentation: State<'_, Presentation>) -> Option<SlideView> {
+    presentation.deck.lock().unwrap().as_ref().map(Deck::view)
+}
+
+/// Move `step` slides forward (negative to go back), stopping at either end.
+#[tauri::command]
+pub fn step_presentation(app: AppHandle, presentation: State<'_, Presentation>, step: isize) -> Option<SlideView> {
+    let mut deck = presentation.deck.lock().unwrap();
+    let deck = deck.as_mut()?;
+    let current = deck.current.saturating_add_signed(step).min(deck.slides.len() - 1);
+    if current != deck.current {
+        deck.current = current;
+        broadcast(&app, &deck.view());
+    }
+    Some(deck.view())
+}
+
+#[tauri::command]
+pub fn stop_presentation(app: AppHandle) {
+    close_windows(&app);
+}
@@ -0,0 +1,530 @@
+//! Minimal QR code encoder (byte mode, error correction level M), enough to
+//! put a URL on screen for phones to scan. Follows ISO/IEC 18004.
+
+use std::fmt::Write;
+
+/// Error correction codewords per block at level M, by version.
+const ECC_CODEWORDS_PER_BLOCK: [usize; 41] = [
+    0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+];
+/// Error correction blocks at level M, by version.
+const ERROR_CORRECTION_BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33,
+    35, 37, 38, 40, 43, 45, 47, 49,
+];
+/// Format bits of level M.
+const LEVEL_M: u32 = 0;
+/// Light modules around the code, as scanners expect.
+const QUIET_ZONE: usize = 4;
+
+/// A square grid of dark (`true`) and light modules.
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+    reserved: Vec<bool>,
+}
+
+fn raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        result -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn data_codewords(version: usize) -> usize {
+    raw_data_modules(version) / 8 - ECC_CODEWORDS_PER_BLOCK[version] * ERROR_CORRECTION_BLOCKS[version]
+}
+
+/// Multiply in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11d);
+        z ^= u32::from((y >> i) & 1) * u32::from(x);
+    }
+    z as u8
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (value, coefficient) in result.iter_mut().zip(divisor) {
+            *value ^= gf_multiply(*coefficient, factor);
+        }
+    }
+    result
+}
+
+/// Split data into blocks, append each block's error correction and
+/// interleave them.
+fn add_error_correction(data: &[u8], version: usize) -> Vec<u8> {
+    let blocks = ERROR_CORRECTION_BLOCKS[version];
+    let ecc_len = ECC_CODEWORDS_PER_BLOCK[version];
+    let raw_codewords = raw_data_modules(version) / 8;
+    let short_blocks = blocks - raw_codewords % blocks;
+    let short_len = raw_codewords / blocks;
+    let divisor = reed_solomon_divisor(ecc_len);
+
+    let mut split = Vec::with_capacity(blocks);
+    let mut offset = 0;
+    for i in 0..blocks {
+        let len = short_len - ecc_len + usize::from(i >= short_blocks);
+        let mut block = data[offset..offset + len].to_vec();
+        offset += len;
+        let ecc = reed_solomon_remainder(&block, &divisor);
+        if i < short_blocks {
+            block.push(0);
+        }
+        block.extend(ecc);
+        split.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..split[0].len() {
+        for (j, block) in split.iter().enumerate() {
+            // Short blocks carry a padding byte that isn't part of the code
+            if i != short_len - ecc_len || j >= short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+fn alignment_positions(version: usize, size: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let count = version / 7 + 2;
+    let step = if version == 32 { 26 } else { (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2 };
+    let mut result: Vec<usize> = (0..count - 1).map(|i| size - 7 - i * step).collect();
+    result.push(6);
+    result.reverse();
+    result
+}
+
+fn bit(value: u32, index: usize) -> bool {
+    (value >> index) & 1 != 0
+}
+
+impl QrCode {
+    /// Encode `text`, choosing the smallest version that fits. Returns `None`
+    /// when it is too long for a QR code.
+    pub fn encode(text: &str) -> Option<Self> {
+        Self::encode_with_mask(text, None)
+    }
+
+    /// Encode with the given mask pattern, or the one scoring the lowest penalty.
+    fn encode_with_mask(text: &str, mask: Option<u32>) -> Option<Self> {
+        let bytes = text.as_bytes();
+        let version = (1..=40).find(|version| {
+            let count_bits = if *version <= 9 { 8 } else { 16 };
+            4 + count_bits + bytes.len() * 8 <= data_codewords(*version) * 8
+        })?;
+
+        // Byte mode, length, data, terminator and padding
+        let capacity = data_codewords(version) * 8;
+        let mut bits: Vec<bool> = Vec::with_capacity(capacity);
+        let mut push = |value: u32, len: usize| bits.extend((0..len).rev().map(|i| bit(value, i)));
+        push(0b0100, 4);
+        push(bytes.len() as u32, if version <= 9 { 8 } else { 16 });
+        for byte in bytes {
+            push(u32::from(*byte), 8);
+        }
+        let terminator = (capacity - bits.len()).min(4);
+        bits.extend(std::iter::repeat_n(false, terminator));
+        bits.extend(std::iter::repeat_n(false, (8 - bits.len() % 8) % 8));
+        let mut data: Vec<u8> =
+            bits.chunks(8).map(|byte| byte.iter().fold(0u8, |acc, bit| (acc << 1) | u8::from(*bit))).collect();
+        for pad in [0xec, 0x11].into_iter().cycle().take(capacity / 8 - data.len()) {
+            data.push(pad);
+        }
+
+        let size = version * 4 + 17;
+        let mut code = Self {
+            size,
+            modules: vec![false; size * size],
+            reserved: vec![false; size * size],
+        };
+        code.draw_function_patterns(version);
+        code.draw_codewords(&add_error_correction(&data, version));
+
+        let mask = mask.or_else(|| {
+            (0..8).min_by_key(|mask| {
+                code.apply_mask(*mask);
+                code.draw_format_bits(*mask);
+                let penalty = code.penalty();
+                code.apply_mask(*mask);
+                penalty
+            })
+        });
+        let mask = mask.unwrap_or(0);
+        code.apply_mask(mask);
+        code.draw_format_bits(mask);
+        Some(code)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.reserved[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            self.draw_finder(x, y);
+        }
+        let positions = alignment_positions(version, size);
+        let last = positions.len().saturating_sub(1);
+        for (i, x) in positions.iter().enumerate() {
+            for (j, y) in positions.iter().enumerate() {
+                // Corners taken by finder patterns
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        let dark = dx.abs().max(dy.abs()) != 1;
+                        self.set_function((*x as i32 + dx) as usize, (*y as i32 + dy) as usize, dark);
+                    }
+                }
+            }
+        }
+        // Reserve the format areas; the real bits are drawn once a mask is chosen
+        self.draw_format_bits(0);
+        if version >= 7 {
+            let mut remainder = version as u32;
+            for _ in 0..12 {
+                remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1f25);
+            }
+            let bits = (version as u32) << 12 | remainder;
+            for i in 0..18 {
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set_function(a, b, bit(bits, i));
+                self.set_function(b, a, bit(bits, i));
+            }
+        }
+    }
+
+    /// Finder pattern centred on (x, y), with its light separator.
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let (mx, my) = (x as i32 + dx, y as i32 + dy);
+                if (0..self.size as i32).contains(&mx) && (0..self.size as i32).contains(&my) {
+                    let distance = dx.abs().max(dy.abs());
+                    self.set_function(mx as usize, my as usize, distance != 2 && distance != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u32) {
+        let data = LEVEL_M << 3 | mask;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+        let size = self.size;
+
+        for i in 0..=5 {
+            self.set_function(8, i, bit(bits, i));
+        }
+        self.set_function(8, 7, bit(bits, 6));
+        self.set_function(8, 8, bit(bits, 7));
+        self.set_function(7, 8, bit(bits, 8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(bits, i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(bits, i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(bits, i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Place codewords in the zig-zag of two-module columns, skipping
+    /// function patterns.
+    fn draw_codewords(&mut self, data: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..size {
+                for j in 0..2 {
+                    let x = right as usize - j;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vertical } else { vertical };
+                    if !self.reserved[y * size + x] && i < data.len() * 8 {
+                        self.modules[y * size + x] = (data[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    /// Flip data modules by one of the eight mask patterns; applying it again
+    /// undoes it.
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let index = y * self.size + x;
+                self.modules[index] ^= invert && !self.reserved[index];
+            }
+        }
+    }
+
+    /// Score how hard the symbol is to scan: long runs, blocks, finder-like
+    /// patterns and dark/light imbalance.
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+        let lines = |vertical: bool| {
+            (0..size).map(move |a| (0..size).map(move |b| if vertical { self.get(a, b) } else { self.get(b, a) }))
+        };
+        for vertical in [false, true] {
+            for line in lines(vertical) {
+                let line: Vec<bool> = line.collect();
+                let mut run = 1;
+                for i in 1..=size {
+                    if i < size && line[i] == line[i - 1] {
+                        run += 1;
+                        continue;
+                    }
+                    if run >= 5 {
+                        penalty += run - 2;
+                    }
+                    run = 1;
+                }
+                const FINDER: [bool; 11] = [true, false, true, true, true, false, true, false, false, false, false];
+                for window in line.windows(11) {
+                    if window == FINDER || window.iter().rev().eq(FINDER.iter()) {
+                        penalty += 40;
+                    }
+                }
+            }
+        }
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = self.get(x, y);
+                if color == self.get(x + 1, y) && color == self.get(x, y + 1) && color == self.get(x + 1, y + 1) {
+                    penalty += 3;
+                }
+            }
+        }
+        let total = size * size;
+        let dark = self.modules.iter().filter(|dark| **dark).count();
+        let deviation = (dark * 20).abs_diff(total * 10);
+        penalty + (deviation.div_ceil(total)).saturating_sub(1) * 10
+    }
+
+    /// Render as a standalone SVG, one unit per module plus the quiet zone.
+    pub fn to_svg(&self) -> String {
+        let extent = self.size + QUIET_ZONE * 2;
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.get(x, y) {
+                    let _ = write!(path, "M{} {}h1v1h-1z", x + QUIET_ZONE, y + QUIET_ZONE);
+                }
+            }
+        }
+        format!(
+            concat!(
+                r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {extent} {extent}" shape-rendering="crispEdges">"##,
+                r##"<rect width="100%" height="100%" fill="#ffffff"/><path d="{path}" fill="#000000"/></svg>"##
+            ),
+            extent = extent,
+            path = path
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    // Reference symbols from Kazuhiko Arase's QR Code generator (as vendored
+    // by the `qrcode-terminal` npm package), with the mask forced: which mask
+    // scores best differs between implementations, the rest is fixed by the
+    // standard. `#` is dark, one string per row.
+    const REFERENCE_A: [&str; 21] = [
+        "#######.##.#..#######",
+        "#.....#..##...#.....#",
+        "#.###.#..####.#.###.#",
+        "#.###.#.###...#.###.#",
+        "#.###.#.###.#.#.###.#",
+        "#.....#.####..#.....#",
+        "#######.#.#.#.#######",
+        "........#.###........",
+        "#...#.###..#.#####..#",
+        "..#.#...#..##..#.#.##",
+        "####.##...##..#####..",
+        "###....#.#...##.#.##.",
+        "###.#####...###...###",
+        "........#.#.###...###",
+        "#######.##..##.....#.",
+        "#.....#..####..#.#.#.",
+        "#.###.#.####..#######",
+        "#.###.#..#.##..#.#.##",
+        "#.###.#....#..#####..",
+        "#.....#..#...##.#.#..",
+        "#######.#.#.###...#.#",
+    ];
+    const REFERENCE_URL: [&str; 25] = [
+        "#######.#..##...#.#######",
+        "#.....#.#..#..#...#.....#",
+        "#.###.#..#.#.###..#.###.#",
+        "#.###.#.#######...#.###.#",
+        "#.###.#...#.#..#..#.###.#",
+        "#.....#......#..#.#.....#",
+        "#######.#.#.#.#.#.#######",
+        "........#####.#.#........",
+        "#.##.###.#....#...#..#.##",
+        "#.##....###...#.#..#...#.",
+        "#.##.##.###.#.#.#.#......",
+        "###.##.##.####..####.##..",
+        "#..#..#....###...##.#.###",
+        "...##....#.#.########...#",
+        ".##.#######.#.#.....#.##.",
+        "#....#.#......#..####...#",
+        "..#.#.#.#..#.#.##########",
+        "........#..####.#...#.#.#",
+        "#######.###.##..#.#.#.###",
+        "#.....#.###....##...#..##",
+        "#.###.#.....#.#.######..#",
+        "#.###.#.##.#.#.#.##.#####",
+        "#.###.#.#.###.##..#.#.##.",
+        "#.....#..##..#......#.#..",
+        "#######.#...###....######",
+    ];
+
+    fn rows(code: &QrCode) -> Vec<String> {
+        (0..code.size)
+            .map(|y| (0..code.size).map(|x| if code.get(x, y) { '#' } else { '.' }).collect())
+            .collect()
+    }
+
+    /// SHA-256 of the rows, each ending in a newline, for symbols too large to
+    /// spell out.
+    fn digest(code: &QrCode) -> String {
+        let mut hasher = Sha256::new();
+        for row in rows(code) {
+            hasher.update(row.as_bytes());
+            hasher.update(b"\n");
+        }
+        hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn version_1_matches_the_reference() {
+        let code = QrCode::encode_with_mask("A", Some(4)).unwrap();
+        assert_eq!(rows(&code), REFERENCE_A);
+    }
+
+    /// Version 2 adds an alignment pattern.
+    #[test]
+    fn version_2_matches_the_reference() {
+        let code = QrCode::encode_with_mask("https://boardkit.app/share", Some(3)).unwrap();
+        assert_eq!(rows(&code), REFERENCE_URL);
+    }
+
+    /// Version 7 adds the version information blocks and several ECC blocks.
+    #[test]
+    fn version_7_matches_the_reference() {
+        let code = QrCode::encode_with_mask(&"x".repeat(120), Some(2)).unwrap();
+        assert_eq!(code.size, 45);
+        assert_eq!(digest(&code), "b13cef5618e04fb0372d62c861de478aa2cecde2cf05772cbb64110a2dc1eb3b");
+    }
+
+    /// Version 10 switches to a 16-bit length and mixes short and long blocks.
+    #[test]
+    fn version_10_matches_the_reference() {
+        let code = QrCode::encode_with_mask(&"y".repeat(200), Some(3)).unwrap();
+        assert_eq!(code.size, 57);
+        assert_eq!(digest(&code), "6a6f28885fc5576aee97e5272e657fba2103d573d37e9e7856acd364dbca93ba");
+    }
+
+    #[test]
+    fn encode_picks_the_lowest_penalty_mask() {
+        for text in ["A", "https://boardkit.app/share", &"x".repeat(120)] {
+            let code = QrCode::encode(text).unwrap();
+            let best = (0..8)
+                .map(|mask| QrCode::encode_with_mask(text, Some(mask)).unwrap())
+                .min_by_key(QrCode::penalty)
+                .unwrap();
+            assert_eq!(rows(&code), rows(&best), "{text}");
+        }
+    }
+
+    #[test]
+    fn chooses_the_smallest_version_that_fits() {
+        // 14 bytes fill version 1 at level M, 2331 version 40
+        assert_eq!(QrCode::encode(&"a".repeat(14)).unwrap().size, 21);
+        assert_eq!(QrCode::encode(&"a".repeat(15)).unwrap().size, 25);
+        assert_eq!(QrCode::encode(&"a".repeat(2331)).unwrap().size, 177);
+        assert!(QrCode::encode(&"a".repeat(2332)).is_none());
+    }
+
+    #[test]
+    fn svg_has_a_quiet_zone() {
+        let svg = QrCode::encode("A").unwrap().to_svg();
+        assert!(svg.contains(r#"viewBox="0 0 29 29""#));
+        // The top-left finder pattern starts inside the quiet zone
+        assert!(svg.contains("M4 4h1v1h-1z"));
+    }
+}
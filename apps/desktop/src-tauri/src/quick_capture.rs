@@ -0,0 +1,81 @@
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+use crate::{error::Result, windows};
+
+const QUICK_CAPTURE_LABEL: &str = "quick-capture";
+const QUICK_CAPTURE_PAGE: &str = "quick-capture.html";
+
+/// The capture window is created on first use and hidden, not closed, afterwards.
+fn capture_window(app: &AppHandle) -> Result<WebviewWindow> {
+    if let Some(window) = app.get_webview_window(QUICK_CAPTURE_LABEL) {
+        return Ok(window);
+    }
+    Ok(
+        WebviewWindowBuilder::new(app, QUICK_CAPTURE_LABEL, WebviewUrl::App(QUICK_CAPTURE_PAGE.into()))
+            .title("Quick Capture")
+            .inner_size(360.0, 200.0)
+            .resizable(false)
+            .minimizable(false)
+            .maximizable(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .center()
+            .visible(false)
+            .build()?,
+    )
+}
+
+pub fn show(app: &AppHandle) -> Result<()> {
+    let window = capture_window(app)?;
+    window.show()?;
+    window.set_focus()?;
+    let _ = window.emit_to(QUICK_CAPTURE_LABEL, "quick-capture-shown", ());
+    Ok(())
+}
+
+pub fn hide(app: &AppHandle) -> Result<()> {
+    if let Some(window) = app.get_webview_window(QUICK_CAPTURE_LABEL) {
+        window.hide()?;
+    }
+    Ok(())
+}
+
+/// Global shortcut handler. Window creation has to happen off the event loop
+/// thread (WebView2 deadlocks otherwise), so this hops to the async runtime.
+pub fn toggle(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let visible = app
+            .get_webview_window(QUICK_CAPTURE_LABEL)
+            .is_some_and(|window| window.is_visible().unwrap_or(false));
+        let result = if visible { hide(&app) } else { show(&app) };
+        if let Err(err) = result {
+            tracing::error!("{err}");
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn show_quick_capture(app: AppHandle) -> Result<()> {
+    show(&app)
+}
+
+#[tauri::command]
+pub fn hide_quick_capture(app: AppHandle) -> Result<()> {
+    hide(&app)
+}
+
+/// Hand a captured note to the board window the user last worked in, without
+/// bringing it forward, and dismiss the capture window.
+#[tauri::command]
+pub fn submit_quick_capture(app: AppHandle, text: String) -> Result<()> {
+    hide(&app)?;
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(());
+    }
+    if let Some(window) = windows::target_window(&app) {
+        let _ = window.emit_to(window.label(), "quick-capture-note", text);
+    }
+    Ok(())
+}
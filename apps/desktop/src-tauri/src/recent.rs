@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use tauri::{AppHandle, State};
+
+use crate::{board, error::Result, menu, persist};
+
+const RECENT_FILES_STORE: &str = "recent-files.json";
+const RECENT_IDS_STORE: &str = "recent-board-ids.json";
+const MAX_RECENT_FILES: usize = 10;
+
+/// Most-recently-opened board paths, newest first, persisted in the app data dir.
+pub struct RecentFiles {
+    store_path: PathBuf,
+    entries: Mutex<Vec<String>>,
+    ids_path: PathBuf,
+    /// Board id of each entry, so boards moved outside Boardkit can be found again
+    ids: Mutex<HashMap<String, String>>,
+}
+
+impl RecentFiles {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let store_path = persist::app_data_file(app, RECENT_FILES_STORE)?;
+        let ids_path = persist::app_data_file(app, RECENT_IDS_STORE)?;
+        let entries = persist::read_json(&store_path);
+        let ids = persist::read_json(&ids_path);
+        Ok(Self {
+            store_path,
+            entries: Mutex::new(entries),
+            ids_path,
+            ids: Mutex::new(ids),
+        })
+    }
+
+    fn update_ids(&self, f: impl FnOnce(&mut HashMap<String, String>)) -> Result<()> {
+        let entries = self.entries.lock().unwrap();
+        let mut ids = self.ids.lock().unwrap();
+        f(&mut ids);
+        ids.retain(|path, _| entries.contains(path));
+        persist::write_json(&self.ids_path, &*ids)
+    }
+
+    /// Point entries whose file is gone at the board with the same id, given
+    /// the paths of known boards by id. Returns whether any entry moved.
+    pub fn relocate(&self, paths_by_id: &HashMap<String, String>) -> Result<bool> {
+        let ids = self.ids.lock().unwrap().clone();
+        let moves: Vec<(String, String)> = self
+            .entries()
+            .into_iter()
+            .filter(|entry| !Path::new(entry).exists())
+            .filter_map(|entry| {
+                let to = paths_by_id.get(ids.get(&entry)?)?;
+                Some((entry, to.clone()))
+            })
+            .collect();
+        for (from, to) in &moves {
+            tracing::info!(from, to, "recent board found at its new location");
+            self.rename(from, to)?;
+        }
+        Ok(!moves.is_empty())
+    }
+
+    pub fn entries(&self) -> Vec<String> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Vec<String>)) -> Result<Vec<String>> {
+        let mut entries = self.entries.lock().unwrap();
+        f(&mut entries);
+        persist::write_json(&self.store_path, &*entries)?;
+        Ok(entries.clone())
+    }
+
+    pub fn add(&self, path: String) -> Result<Vec<String>> {
+        let board_id = board::read_board_id(&path);
+        let entries = self.update(|entries| {
+            entries.retain(|entry| entry != &path);
+            entries.insert(0, path.clone());
+            entries.truncate(MAX_RECENT_FILES);
+        })?;
+        self.update_ids(|ids| match board_id {
+            Some(board_id) => {
+                ids.insert(path, board_id);
+            }
+            None => {
+                ids.remove(&path);
+            }
+        })?;
+        Ok(entries)
+    }
+
+    /// Point an entry at a board's new location, keeping its position.
+    pub fn rename(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        let entries = self.update(|entries| {
+            for entry in entries.iter_mut().filter(|entry| entry.as_str() == from) {
+                *entry = to.to_string();
+            }
+        })?;
+        self.update_ids(|ids| {
+            if let Some(board_id) = ids.remove(from) {
+                ids.insert(to.to_string(), board_id);
+            }
+        })?;
+        Ok(entries)
+    }
+
+    pub fn remove(&self, path: &str) -> Result<Vec<String>> {
+        let entries = self.update(|entries| entries.retain(|entry| entry != path))?;
+        self.update_ids(|_| {})?;
+        Ok(entries)
+    }
+
+    pub fn clear(&self) -> Result<Vec<String>> {
+        let entries = self.update(Vec::clear)?;
+        self.update_ids(|_| {})?;
+        Ok(entries)
+    }
+}
+
+#[tauri::command]
+pub fn add_recent_file(
+    app: AppHandle,
+    recent: State<'_, RecentFiles>,
+    path: String,
+) -> Result<Vec<String>> {
+    let entries = recent.add(path)?;
+    menu::refresh(&app)?;
+    Ok(entries)
+}
+
+#[tauri::command]
+pub fn get_recent_files(recent: State<'_, RecentFiles>) -> Vec<String> {
+    recent.entries()
+}
+
+#[tauri::command]
+pub fn clear_recent_files(app: AppHandle, recent: State<'_, RecentFiles>) -> Result<Vec<String>> {
+    let entries = recent.clear()?;
+    menu::refresh(&app)?;
+    Ok(entries)
+}
@@ -0,0 +1,195 @@
+use std::{sync::Arc, time::Instant};
+
+use percent_encoding::percent_decode_str;
+use serde::Serialize;
+use tauri::{
+    ipc::{InvokeBody, Request},
+    AppHandle, Emitter, State, Window,
+};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{
+    board,
+    error::{Error, Result},
+    file_metadata, history,
+    journal::Journals,
+    links, locks, search, share, thumbnails, timeline,
+};
+
+/// Header carrying the URI-encoded board path, since the body is the raw
+/// container.
+const PATH_HEADER: &str = "x-board-path";
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum SaveStage {
+    Compressing,
+    Writing,
+    Indexing,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveProgress<'a> {
+    path: &'a str,
+    stage: SaveStage,
+    /// Rough fraction done, for a progress bar.
+    progress: f32,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveComplete<'a> {
+    path: &'a str,
+    size: u64,
+    /// Bytes actually written, much less than `size` for journaled saves.
+    written: u64,
+    incremental: bool,
+    /// Time from queueing to the file being written.
+    duration_ms: u64,
+    error: Option<String>,
+}
+
+struct SaveRequest {
+    window: String,
+    path: String,
+    payload: Vec<u8>,
+    queued_at: Instant,
+}
+
+struct SaveJob {
+    request: SaveRequest,
+    reply: oneshot::Sender<Result<()>>,
+}
+
+/// Saves run one at a time on a background task, so compressing and
+/// writing a large board never blocks the main thread and saves to the same
+/// file can't interleave.
+pub struct SaveWorker {
+    jobs: mpsc::UnboundedSender<SaveJob>,
+}
+
+impl SaveWorker {
+    pub fn start(app: &AppHandle) -> Self {
+        let (jobs, receiver) = mpsc::unbounded_channel();
+        tauri::async_runtime::spawn(run(app.clone(), receiver));
+        Self { jobs }
+    }
+}
+
+async fn run(app: AppHandle, mut receiver: mpsc::UnboundedReceiver<SaveJob>) {
+    let journals = Arc::new(Journals::default());
+    while let Some(job) = receiver.recv().await {
+        // Only the newest queued save of each board needs writing; autosave
+        // can queue several while a large one is in progress
+        let mut batch: Vec<SaveJob> = vec![job];
+        while let Ok(job) = receiver.try_recv() {
+            match batch.iter().position(|queued| queued.request.path == job.request.path) {
+                Some(index) => {
+                    let superseded = std::mem::replace(&mut batch[index], job);
+                    let _ = superseded.reply.send(Ok(()));
+                }
+                None => batch.push(job),
+            }
+        }
+        for SaveJob { request, reply } in batch {
+            let app = app.clone();
+            let journals = journals.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || save(&app, &journals, request))
+                .await
+                .map_err(|err| Error::Io(std::io::Error::other(err)))
+                .and_then(|result| result);
+            let _ = reply.send(result);
+        }
+    }
+}
+
+fn save(app: &AppHandle, journals: &Journals, request: SaveRequest) -> Result<()> {
+    let SaveRequest {
+        window,
+        path,
+        payload,
+        queued_at,
+    } = request;
+    let progress = |stage, progress| {
+        let _ = app.emit_to(window.as_str(), "save-progress", SaveProgress { path: &path, stage, progress });
+    };
+
+    progress(SaveStage::Compressing, 0.0);
+    let result = board::finalize_payload(&payload, &path).and_then(|(bytes, document)| {
+        progress(SaveStage::Writing, 0.6);
+        let saved = journals.save(&path, &bytes, &document)?;
+        Ok((bytes, document, saved))
+    });
+    let saved = result.as_ref().ok().map(|(_, _, saved)| saved);
+    let complete = SaveComplete {
+        path: &path,
+        size: saved.map_or(0, |saved| saved.size),
+        written: saved.map_or(0, |saved| saved.written),
+        incremental: saved.is_some_and(|saved| saved.incremental),
+        duration_ms: queued_at.elapsed().as_millis() as u64,
+        error: result.as_ref().err().map(ToString::to_string),
+    };
+    let (bytes, document, _) = match result {
+        Ok(saved) => saved,
+        Err(err) => {
+            tracing::warn!(path, "save failed: {err}");
+            let _ = app.emit_to(window.as_str(), "save-complete", complete);
+            return Err(err);
+        }
+    };
+    tracing::info!(
+        path,
+        size = complete.size,
+        written = complete.written,
+        incremental = complete.incremental,
+        duration_ms = complete.duration_ms,
+        "board saved"
+    );
+
+    progress(SaveStage::Indexing, 0.9);
+    history::record_in_background(path.clone(), bytes, document.clone());
+    thumbnails::generate_in_background(app, path.clone(), document.clone());
+    file_metadata::write_in_background(path.clone(), document.clone());
+    links::record_in_background(app, path.clone(), document.clone());
+    share::reload_in_background(app, path.clone());
+    search::index_in_background(app, path.clone(), document.clone());
+    timeline::record_in_background(path.clone(), document);
+    let _ = app.emit_to(window.as_str(), "save-complete", complete);
+    Ok(())
+}
+
+/// Validate a .boardkit container produced by the frontend, compress it,
+/// refresh its manifest and write it atomically, off the main thread. The
+/// body is the raw container and the path goes in the `x-board-path`
+/// header. Emits `save-progress` while working and `save-complete` when done.
+///
+/// Resolves once the board is on disk, or as soon as a newer save of the
+/// same board replaces it in the queue.
+#[tauri::command]
+pub async fn save_board(window: Window, worker: State<'_, SaveWorker>, request: Request<'_>) -> Result<()> {
+    let InvokeBody::Raw(payload) = request.body() else {
+        return Err(Error::InvalidBoard("expected the board as raw bytes".to_string()));
+    };
+    let path = request
+        .headers()
+        .get(PATH_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| percent_decode_str(value).decode_utf8().ok())
+        .ok_or_else(|| Error::InvalidBoard(format!("missing {PATH_HEADER} header")))?
+        .into_owned();
+    locks::lock_for_save(&window, &path)?;
+
+    let (reply, done) = oneshot::channel();
+    let request = SaveRequest {
+        window: window.label().to_string(),
+        path,
+        payload: payload.clone(),
+        queued_at: Instant::now(),
+    };
+    worker
+        .jobs
+        .send(SaveJob { request, reply })
+        .map_err(|_| Error::Io(std::io::Error::other("save worker stopped")))?;
+    done.await.map_err(|_| Error::Io(std::io::Error::other("save worker stopped")))?
+}
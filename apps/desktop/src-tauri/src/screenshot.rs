@@ -0,0 +1,226 @@
+use std::{
+    env, fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Window};
+
+use crate::{
+    assets::AssetStore,
+    error::{Error, Result},
+    image_pipeline,
+    settings::Settings,
+};
+
+/// Time for the window to disappear from screen before capturing.
+const HIDE_DELAY: Duration = Duration::from_millis(300);
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureMode {
+    /// The whole screen
+    Screen,
+    /// A window the user picks
+    Window,
+    /// A rectangle the user drags out
+    Region,
+}
+
+/// A capture stored in the asset store, ready to become an image element.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Screenshot {
+    hash: String,
+    mime_type: &'static str,
+    width: u32,
+    height: u32,
+}
+
+fn capture_path() -> PathBuf {
+    env::temp_dir().join(format!("boardkit-screenshot-{}.png", std::process::id()))
+}
+
+fn screenshot_error(message: impl Into<String>) -> Error {
+    Error::Screenshot(message.into())
+}
+
+/// Run a capture tool. Interactive tools write no file when the user cancels,
+/// which the caller reports as no screenshot.
+fn run(mut command: Command) -> Result<bool> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    match command.status() {
+        Ok(status) if status.success() => Ok(true),
+        // Cancelling an interactive capture exits non-zero in some tools
+        Ok(_) => Ok(false),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            Err(screenshot_error(format!("{program} is not installed")))
+        }
+        Err(err) => Err(Error::Io(err)),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn capture(mode: CaptureMode, path: &Path) -> Result<bool> {
+    let mut command = Command::new("screencapture");
+    // -x: no sound, -o: no window shadow
+    command.args(["-x", "-o"]);
+    match mode {
+        CaptureMode::Screen => {}
+        CaptureMode::Window => {
+            command.arg("-w");
+        }
+        CaptureMode::Region => {
+            command.arg("-i");
+        }
+    }
+    command.arg(path);
+    run(command)
+}
+
+/// Full screen goes through .NET; picking a window or region uses the system
+/// snipping overlay, which only delivers its result to the clipboard.
+#[cfg(target_os = "windows")]
+fn capture(mode: CaptureMode, path: &Path) -> Result<bool> {
+    // The path goes through the environment, out of reach of PowerShell quoting
+    const SCREEN: &str = "Add-Type -AssemblyName System.Windows.Forms,System.Drawing; \
+        $b = [System.Windows.Forms.SystemInformation]::VirtualScreen; \
+        $bmp = New-Object System.Drawing.Bitmap $b.Width, $b.Height; \
+        $g = [System.Drawing.Graphics]::FromImage($bmp); \
+        $g.CopyFromScreen($b.Left, $b.Top, 0, 0, $bmp.Size); \
+        $bmp.Save($env:BOARDKIT_SCREENSHOT_PATH, [System.Drawing.Imaging.ImageFormat]::Png)";
+    const SNIP: &str = "Add-Type -AssemblyName System.Windows.Forms; \
+        [System.Windows.Forms.Clipboard]::Clear(); \
+        Start-Process 'ms-screenclip:'; \
+        for ($i = 0; $i -lt 240; $i++) { \
+          Start-Sleep -Milliseconds 250; \
+          $img = [System.Windows.Forms.Clipboard]::GetImage(); \
+          if ($img) { $img.Save($env:BOARDKIT_SCREENSHOT_PATH, [System.Drawing.Imaging.ImageFormat]::Png); break } \
+        }";
+    let script = match mode {
+        CaptureMode::Screen => SCREEN,
+        CaptureMode::Window | CaptureMode::Region => SNIP,
+    };
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-STA", "-Command", script]).env("BOARDKIT_SCREENSHOT_PATH", path);
+    run(command)
+}
+
+/// Linux has no single screenshot API, so use whichever common tool is installed.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn capture(mode: CaptureMode, path: &Path) -> Result<bool> {
+    let mut attempts: Vec<Command> = Vec::new();
+
+    let mut gnome = Command::new("gnome-screenshot");
+    match mode {
+        CaptureMode::Screen => {}
+        CaptureMode::Window => {
+            gnome.arg("-w");
+        }
+        CaptureMode::Region => {
+            gnome.arg("-a");
+        }
+    }
+    gnome.arg("-f").arg(path);
+    attempts.push(gnome);
+
+    let mut spectacle = Command::new("spectacle");
+    spectacle.args(["-b", "-n"]).arg(match mode {
+        CaptureMode::Screen => "-f",
+        CaptureMode::Window => "-a",
+        CaptureMode::Region => "-r",
+    });
+    spectacle.arg("-o").arg(path);
+    attempts.push(spectacle);
+
+    let mut scrot = Command::new("scrot");
+    match mode {
+        CaptureMode::Screen => {}
+        CaptureMode::Window => {
+            scrot.arg("-u");
+        }
+        CaptureMode::Region => {
+            scrot.arg("-s");
+        }
+    }
+    scrot.arg("--overwrite").arg(path);
+    attempts.push(scrot);
+
+    for command in attempts {
+        match run(command) {
+            Err(Error::Screenshot(reason)) => tracing::debug!("screenshot: {reason}"),
+            result => return result,
+        }
+    }
+
+    // Wayland compositors without the tools above; grim can't pick windows
+    let mut grim = Command::new("grim");
+    if !matches!(mode, CaptureMode::Screen) {
+        match Command::new("slurp").output() {
+            Ok(output) if output.status.success() => {
+                grim.arg("-g").arg(String::from_utf8_lossy(&output.stdout).trim());
+            }
+            Ok(_) => return Ok(false),
+            Err(_) => {}
+        }
+    }
+    grim.arg(path);
+    run(grim).map_err(|err| match err {
+        Error::Screenshot(_) => {
+            screenshot_error("no screenshot tool found (install gnome-screenshot, spectacle, scrot or grim)")
+        }
+        err => err,
+    })
+}
+
+fn store_capture(window: &Window, path: &Path) -> Result<Screenshot> {
+    let data = fs::read(path)?;
+    let _ = fs::remove_file(path);
+    let options = window.state::<Settings>().get().image_optimization;
+    let optimized = image_pipeline::optimize(&data, &options)?;
+    let (bytes, mime_type, width, height) = match optimized {
+        Some(optimized) => (optimized.bytes, optimized.mime_type, optimized.width, optimized.height),
+        None => {
+            let (width, height) = image::ImageReader::new(Cursor::new(&data))
+                .with_guessed_format()?
+                .into_dimensions()
+                .map_err(|err| screenshot_error(err.to_string()))?;
+            (data, "image/png", width, height)
+        }
+    };
+    Ok(Screenshot {
+        hash: window.state::<AssetStore>().store(&bytes)?,
+        mime_type,
+        width,
+        height,
+    })
+}
+
+/// Capture the screen, a window or a region with the OS tools and store the
+/// image in the asset store. The calling window hides during the capture so
+/// it doesn't end up in the shot. Returns `None` when the user cancels.
+#[tauri::command]
+pub async fn capture_screenshot(window: Window, mode: CaptureMode) -> Result<Option<Screenshot>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = capture_path();
+        let _ = fs::remove_file(&path);
+        window.hide()?;
+        thread::sleep(HIDE_DELAY);
+        let captured = capture(mode, &path);
+        window.show()?;
+        window.set_focus()?;
+
+        if !captured? || !path.exists() {
+            return Ok(None);
+        }
+        let screenshot = store_capture(&window, &path)?;
+        tracing::info!(width = screenshot.width, height = screenshot.height, "screenshot captured");
+        Ok(Some(screenshot))
+    })
+    .await
+    .map_err(|err| screenshot_error(err.to_string()))?
+}
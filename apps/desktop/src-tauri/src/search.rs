@@ -0,0 +1,240 @@
+use std::{path::Path, sync::Mutex};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    board,
+    error::{Error, Result},
+    persist,
+};
+
+const SEARCH_DB: &str = "search.sqlite";
+const DEFAULT_LIMIT: u32 = 50;
+/// Tokens of context on each side of the match in snippets
+const SNIPPET_TOKENS: u32 = 12;
+
+/// Full-text index over the text of every saved board, backed by SQLite FTS5.
+/// One row per text-bearing element or widget, plus one for the board title.
+/// Text recognized in images is kept per asset hash and indexed with every
+/// image element showing that asset.
+pub struct SearchIndex {
+    conn: Mutex<Connection>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub path: String,
+    pub title: String,
+    /// Element or widget holding the match; `None` when the title matched
+    pub element_id: Option<String>,
+    pub snippet: String,
+}
+
+/// Collect every string in a widget's module state.
+fn collect_strings(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(text) if !text.trim().is_empty() => out.push(text.clone()),
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, out)),
+        Value::Object(fields) => fields.values().for_each(|field| collect_strings(field, out)),
+        _ => {}
+    }
+}
+
+/// (element id, text) pairs worth indexing in a raw document.
+pub fn document_text(document: &Value) -> Vec<(Option<String>, String)> {
+    let mut rows = Vec::new();
+    let elements = document.pointer("/board/elements").and_then(Value::as_array);
+    for element in elements.into_iter().flatten() {
+        let id = element.get("id").and_then(Value::as_str).map(str::to_string);
+        for key in ["content", "label"] {
+            if let Some(text) = element.get(key).and_then(Value::as_str).filter(|text| !text.trim().is_empty()) {
+                rows.push((id.clone(), text.to_string()));
+            }
+        }
+    }
+    let connections = document.pointer("/board/connections").and_then(Value::as_array);
+    for connection in connections.into_iter().flatten() {
+        if let Some(label) = connection.get("label").and_then(Value::as_str).filter(|label| !label.is_empty()) {
+            let id = connection.get("id").and_then(Value::as_str).map(str::to_string);
+            rows.push((id, label.to_string()));
+        }
+    }
+    if let Some(modules) = document.get("modules").and_then(Value::as_object) {
+        for (widget_id, state) in modules {
+            let mut strings = Vec::new();
+            collect_strings(state, &mut strings);
+            if !strings.is_empty() {
+                rows.push((Some(widget_id.clone()), strings.join("\n")));
+            }
+        }
+    }
+    rows
+}
+
+/// (element id, asset hash) for every image element, resolved through the
+/// document's asset registry.
+fn document_images(document: &Value) -> Vec<(String, String)> {
+    let elements = document.pointer("/board/elements").and_then(Value::as_array);
+    elements
+        .into_iter()
+        .flatten()
+        .filter(|element| element.get("type").and_then(Value::as_str) == Some("image"))
+        .filter_map(|element| {
+            let id = element.get("id")?.as_str()?;
+            let asset_id = element.get("assetId")?.as_str()?;
+            let hash = document.get("assets")?.get("assets")?.get(asset_id)?.get("hash")?.as_str()?;
+            Some((id.to_string(), hash.to_string()))
+        })
+        .collect()
+}
+
+/// Turn free text into an FTS5 query: every word must match, the last one as
+/// a prefix so results update while typing. Quoting keeps FTS syntax inert.
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    let last = terms.len().checked_sub(1)?;
+    Some(
+        terms
+            .iter()
+            .enumerate()
+            .map(|(index, term)| if index == last { format!("{term}*") } else { term.clone() })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+impl SearchIndex {
+    pub fn open(app: &AppHandle) -> Result<Self> {
+        Self::open_at(&persist::app_data_file(app, SEARCH_DB)?)
+    }
+
+    /// Open the index stored at `path`; `:memory:` keeps it in memory.
+    pub fn open_at(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS board_text USING fts5(
+                path UNINDEXED,
+                title UNINDEXED,
+                element_id UNINDEXED,
+                content,
+                tokenize = 'unicode61 remove_diacritics 2'
+            );
+            CREATE TABLE IF NOT EXISTS asset_text (
+                hash TEXT PRIMARY KEY,
+                content TEXT NOT NULL
+            );",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// Replace the indexed text of the board at `path`.
+    pub fn index_document(&self, path: &str, document: &Value) -> Result<()> {
+        let title = document.pointer("/meta/title").and_then(Value::as_str).unwrap_or_default();
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM board_text WHERE path = ?1", params![path])?;
+        {
+            let mut insert =
+                tx.prepare("INSERT INTO board_text (path, title, element_id, content) VALUES (?1, ?2, ?3, ?4)")?;
+            insert.execute(params![path, title, Option::<String>::None, title])?;
+            for (element_id, text) in document_text(document) {
+                insert.execute(params![path, title, element_id, text])?;
+            }
+            let mut recognized = tx.prepare("SELECT content FROM asset_text WHERE hash = ?1")?;
+            for (element_id, hash) in document_images(document) {
+                let text: Option<String> = recognized.query_row(params![hash], |row| row.get(0)).optional()?;
+                if let Some(text) = text.filter(|text| !text.trim().is_empty()) {
+                    insert.execute(params![path, title, element_id, text])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Text recognized in an image, if it has been through OCR.
+    pub fn asset_text(&self, hash: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let text = conn
+            .query_row("SELECT content FROM asset_text WHERE hash = ?1", params![hash], |row| row.get(0))
+            .optional()?;
+        Ok(text)
+    }
+
+    /// Remember the text in an image; boards showing it pick it up the next
+    /// time they are indexed.
+    pub fn set_asset_text(&self, hash: &str, text: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO asset_text (hash, content) VALUES (?1, ?2)
+             ON CONFLICT (hash) DO UPDATE SET content = excluded.content",
+            params![hash, text],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove(&self, path: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM board_text WHERE path = ?1", params![path])?;
+        Ok(())
+    }
+
+    pub fn search(&self, query: &str, limit: u32) -> Result<Vec<SearchHit>> {
+        let Some(query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT path, title, element_id, snippet(board_text, 3, '', '', '…', ?3)
+             FROM board_text WHERE board_text MATCH ?1 ORDER BY rank LIMIT ?2",
+        )?;
+        let hits = statement
+            .query_map(params![query, limit, SNIPPET_TOKENS], |row| {
+                Ok(SearchHit {
+                    path: row.get(0)?,
+                    title: row.get(1)?,
+                    element_id: row.get(2)?,
+                    snippet: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(hits)
+    }
+}
+
+/// Re-index a saved board off the calling thread; failures are only logged
+/// since search is best-effort.
+pub fn index_in_background(app: &AppHandle, path: String, document: Value) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(index) = app.try_state::<SearchIndex>() else {
+            return;
+        };
+        if let Err(err) = index.index_document(&path, &document) {
+            tracing::warn!("failed to index {path}: {err}");
+        }
+    });
+}
+
+/// Index (or re-index) the saved board at `path`.
+#[tauri::command]
+pub async fn index_board(app: AppHandle, path: String) -> Result<()> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let document = board::read_board_document(&path)?;
+        app.state::<SearchIndex>().index_document(&path, &document)
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+#[tauri::command]
+pub fn search_boards(index: State<'_, SearchIndex>, query: String, limit: Option<u32>) -> Result<Vec<SearchHit>> {
+    index.search(&query, limit.unwrap_or(DEFAULT_LIMIT))
+}
@@ -0,0 +1,142 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use keyring::Entry;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    error::{Error, Result},
+    persist,
+};
+
+/// Keychain service every Boardkit secret is filed under.
+const SERVICE: &str = "com.boardkit.app";
+const SECRETS_INDEX_STORE: &str = "secrets-index.json";
+/// Secrets the frontend stores are namespaced apart from internal entries such as board keys.
+const USER_SECRET_PREFIX: &str = "secret:";
+/// Plain JSON vault written by earlier versions, relative to the app local data dir.
+const LEGACY_VAULT: &str = "boardkit-secrets/vault.json";
+
+fn entry(name: &str) -> Result<Entry> {
+    Entry::new(SERVICE, name).map_err(keychain_error)
+}
+
+fn keychain_error(err: keyring::Error) -> Error {
+    Error::Keychain(err.to_string())
+}
+
+/// Store a secret in the OS keychain, replacing any previous value.
+pub fn store(name: &str, secret: &str) -> Result<()> {
+    entry(name)?.set_password(secret).map_err(keychain_error)
+}
+
+/// `None` when nothing is stored under `name`.
+pub fn get(name: &str) -> Result<Option<String>> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(keychain_error(err)),
+    }
+}
+
+pub fn delete(name: &str) -> Result<()> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(keychain_error(err)),
+    }
+}
+
+/// Names of the frontend's secrets. Keychains can't be enumerated per app,
+/// so the names (never the values) are kept in an index file.
+pub struct Secrets {
+    index_path: PathBuf,
+    keys: Mutex<BTreeSet<String>>,
+}
+
+impl Secrets {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let index_path = persist::app_data_file(app, SECRETS_INDEX_STORE)?;
+        let secrets = Self {
+            keys: Mutex::new(persist::read_json(&index_path)),
+            index_path,
+        };
+        secrets.migrate_legacy_vault(app);
+        Ok(secrets)
+    }
+
+    /// Move secrets out of the old plain-text vault, deleting it once every
+    /// value made it into the keychain.
+    fn migrate_legacy_vault(&self, app: &AppHandle) {
+        let Ok(path) = app.path().app_local_data_dir().map(|dir| dir.join(LEGACY_VAULT)) else {
+            return;
+        };
+        let Ok(bytes) = fs::read(&path) else {
+            return;
+        };
+        let legacy: BTreeMap<String, String> = serde_json::from_slice(&bytes).unwrap_or_default();
+        let migrated = legacy.iter().all(|(key, value)| match self.store(key, value) {
+            Ok(()) => true,
+            Err(err) => {
+                tracing::warn!("failed to migrate secret {key}: {err}");
+                false
+            }
+        });
+        if migrated {
+            let _ = fs::remove_file(&path);
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut BTreeSet<String>)) -> Result<()> {
+        let mut keys = self.keys.lock().unwrap();
+        f(&mut keys);
+        persist::write_json(&self.index_path, &*keys)
+    }
+
+    fn store(&self, key: &str, value: &str) -> Result<()> {
+        store(&format!("{USER_SECRET_PREFIX}{key}"), value)?;
+        self.update(|keys| {
+            keys.insert(key.to_string());
+        })
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        delete(&format!("{USER_SECRET_PREFIX}{key}"))?;
+        self.update(|keys| {
+            keys.remove(key);
+        })
+    }
+}
+
+#[tauri::command]
+pub fn store_secret(secrets: State<'_, Secrets>, key: String, value: String) -> Result<()> {
+    secrets.store(&key, &value)
+}
+
+#[tauri::command]
+pub fn get_secret(key: String) -> Result<Option<String>> {
+    get(&format!("{USER_SECRET_PREFIX}{key}"))
+}
+
+#[tauri::command]
+pub fn delete_secret(secrets: State<'_, Secrets>, key: String) -> Result<()> {
+    secrets.delete(&key)
+}
+
+/// Keys of stored secrets; values never leave the keychain through this.
+#[tauri::command]
+pub fn list_secrets(secrets: State<'_, Secrets>) -> Vec<String> {
+    secrets.keys.lock().unwrap().iter().cloned().collect()
+}
+
+#[tauri::command]
+pub fn clear_secrets(secrets: State<'_, Secrets>) -> Result<()> {
+    let keys: Vec<String> = secrets.keys.lock().unwrap().iter().cloned().collect();
+    for key in keys {
+        secrets.delete(&key)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,197 @@
+//! The boards left open when the app last quit, reopened on the next launch
+//! when `restoreSession` is on: the main window's board, every other board
+//! window, and where each was scrolled and zoomed to.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State, WebviewWindow, Window, WindowEvent};
+
+use crate::{error::Result, persist, settings::Settings, windows};
+
+const SESSION_STORE: &str = "session.json";
+
+/// Canvas pan and zoom, as the board store's `viewport` holds them.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+    pub zoom: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SessionWindow {
+    label: String,
+    board: String,
+    #[serde(default)]
+    viewport: Option<Viewport>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SessionStore {
+    /// In the order they were opened
+    windows: Vec<SessionWindow>,
+}
+
+/// Board windows of this session, persisted as they change so a crash
+/// doesn't lose them either.
+pub struct Session {
+    app: AppHandle,
+    store_path: PathBuf,
+    store: Mutex<SessionStore>,
+    /// Set while quitting, when windows closing are still part of the session
+    quitting: AtomicBool,
+    /// Viewports of the boards being restored, per window label, until the
+    /// window loads its board
+    restored: Mutex<HashMap<String, (String, Viewport)>>,
+}
+
+impl Session {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let store_path = persist::app_data_file(app, SESSION_STORE)?;
+        let store = persist::read_json(&store_path);
+        Ok(Self {
+            app: app.clone(),
+            store_path,
+            store: Mutex::new(store),
+            quitting: AtomicBool::new(false),
+            restored: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn save(&self, store: &SessionStore) {
+        if let Err(err) = persist::write_json(&self.store_path, store) {
+            tracing::warn!("failed to save the session: {err}");
+        }
+    }
+
+    /// Record the board `label` shows; `None` when it shows none.
+    fn set_board(&self, label: &str, board: Option<&str>) {
+        // Still editing, so a quit that was cancelled is over
+        self.quitting.store(false, Ordering::Relaxed);
+        let mut store = self.store.lock().unwrap();
+        // Drop the windows kept from before every window was closed
+        store.windows.retain(|window| self.app.get_webview_window(&window.label).is_some());
+        match (store.windows.iter().position(|window| window.label == label), board) {
+            (Some(index), Some(board)) if store.windows[index].board == board => return,
+            (Some(index), Some(board)) => {
+                store.windows[index] = SessionWindow {
+                    label: label.to_string(),
+                    board: board.to_string(),
+                    viewport: None,
+                };
+            }
+            (Some(index), None) => {
+                store.windows.remove(index);
+            }
+            (None, Some(board)) => store.windows.push(SessionWindow {
+                label: label.to_string(),
+                board: board.to_string(),
+                viewport: None,
+            }),
+            (None, None) => return,
+        }
+        self.save(&store);
+    }
+
+    fn set_viewport(&self, label: &str, viewport: Viewport) {
+        let mut store = self.store.lock().unwrap();
+        if let Some(window) = store.windows.iter_mut().find(|window| window.label == label) {
+            window.viewport = Some(viewport);
+            self.save(&store);
+        }
+    }
+
+    /// Windows closing while quitting, or the last one closing, are what the
+    /// next launch reopens; any other closed window leaves the session.
+    fn window_closed(&self, label: &str) {
+        let mut store = self.store.lock().unwrap();
+        if self.quitting.load(Ordering::Relaxed) || store.windows.iter().all(|window| window.label == label) {
+            return;
+        }
+        store.windows.retain(|window| window.label != label);
+        self.save(&store);
+    }
+
+    /// Keep every open window in the session while the app quits.
+    pub fn quitting(&self) {
+        self.quitting.store(true, Ordering::Relaxed);
+    }
+
+    /// Reopen the boards of the last session, if restoring is on. Returns the
+    /// board for the main window, which opens it once its frontend is up; the
+    /// other boards get windows of their own.
+    pub fn restore(&self, app: &AppHandle) -> Option<String> {
+        if !app.state::<Settings>().get().restore_session {
+            return None;
+        }
+        let previous = std::mem::take(&mut self.store.lock().unwrap().windows);
+        let mut restored = self.restored.lock().unwrap();
+        let (mut main_board, mut reopened) = (None, 0);
+        // Boards deleted or moved since stay closed
+        for window in previous.into_iter().filter(|window| Path::new(&window.board).is_file()) {
+            let label = if main_board.is_none() && window.label == "main" {
+                main_board = Some(window.board.clone());
+                window.label
+            } else {
+                match windows::open_window(app, &window.board) {
+                    Ok(label) => label,
+                    Err(err) => {
+                        tracing::warn!("failed to reopen {}: {err}", window.board);
+                        continue;
+                    }
+                }
+            };
+            reopened += 1;
+            if let Some(viewport) = window.viewport {
+                restored.insert(label, (window.board, viewport));
+            }
+        }
+        tracing::info!(windows = reopened, "session restored");
+        main_board
+    }
+}
+
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    if !windows::is_board_window(window.label()) {
+        return;
+    }
+    if let (WindowEvent::Destroyed, Some(session)) = (event, window.try_state::<Session>()) {
+        session.window_closed(window.label());
+    }
+}
+
+/// Keep the session in step with the board `window` shows, like
+/// `set_window_board` does for its geometry.
+pub fn set_board(window: &WebviewWindow, path: Option<&str>) {
+    if let Some(session) = window.try_state::<Session>() {
+        session.set_board(window.label(), path);
+    }
+}
+
+/// Remember where the board in `window` is scrolled and zoomed to, for the
+/// next launch to restore.
+#[tauri::command]
+pub fn save_viewport(window: WebviewWindow, session: State<'_, Session>, viewport: Viewport) {
+    if [viewport.x, viewport.y, viewport.zoom].iter().all(|value| value.is_finite()) {
+        session.set_viewport(window.label(), viewport);
+    }
+}
+
+/// The viewport the last session left `window` at, if it was restored with
+/// the board at `path`. Given once, right after the board is loaded.
+#[tauri::command]
+pub fn take_session_viewport(window: WebviewWindow, session: State<'_, Session>, path: String) -> Option<Viewport> {
+    match session.restored.lock().unwrap().remove(window.label()) {
+        Some((board, viewport)) if board == path => Some(viewport),
+        _ => None,
+    }
+}
@@ -0,0 +1,241 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{
+    automation::Automation,
+    error::{Error, Result},
+    export::preset::{self, ExportPreset},
+    image_pipeline::{self, ImageOptimization},
+    persist,
+    shortcuts::{GlobalAction, GlobalShortcuts},
+    workspace::Workspace,
+};
+
+const SETTINGS_STORE: &str = "settings.json";
+/// Stores that predate settings.json, folded into it on first launch.
+const LEGACY_WORKSPACE_STORE: &str = "workspace.json";
+const LEGACY_SHORTCUTS_STORE: &str = "global-shortcuts.json";
+
+const AUTOSAVE_INTERVAL_RANGE: std::ops::RangeInclusive<u64> = 1..=600;
+const EXPORT_DPI_RANGE: std::ops::RangeInclusive<u32> = 72..=1200;
+const TRASH_RETENTION_RANGE: std::ops::RangeInclusive<u32> = 1..=365;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+/// Everything in settings.json. Missing fields take their defaults so older
+/// files keep loading.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    pub theme: Theme,
+    pub autosave_interval_secs: u64,
+    pub export_dpi: u32,
+    pub export_presets: Vec<ExportPreset>,
+    pub image_optimization: ImageOptimization,
+    /// `None` until the user changes a binding, so new defaults still apply.
+    pub global_shortcuts: Option<BTreeMap<GlobalAction, String>>,
+    /// `None` means the default `Documents/Boardkit`.
+    pub boards_directory: Option<PathBuf>,
+    /// Deleted boards stay in the trash this long before they are purged.
+    pub trash_retention_days: u32,
+    /// Tell the user through OS notifications when long work finishes in the background.
+    pub notifications_enabled: bool,
+    /// Accept commands from local scripts and tests, see `automation.rs`.
+    pub automation_enabled: bool,
+    /// Reopen the boards and windows left open at quit on the next launch.
+    pub restore_session: bool,
+    /// Language of the native menus, e.g. `fr`; `None` follows the system.
+    pub language: Option<String>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            autosave_interval_secs: 5,
+            export_dpi: 144,
+            export_presets: preset::default_presets(),
+            image_optimization: ImageOptimization::default(),
+            global_shortcuts: None,
+            boards_directory: None,
+            trash_retention_days: 30,
+            notifications_enabled: true,
+            automation_enabled: false,
+            restore_session: true,
+            language: None,
+        }
+    }
+}
+
+impl AppSettings {
+    fn validate(&self) -> Result<()> {
+        if !AUTOSAVE_INTERVAL_RANGE.contains(&self.autosave_interval_secs) {
+            return Err(Error::InvalidSettings(format!(
+                "autosave interval must be between {} and {} seconds",
+                AUTOSAVE_INTERVAL_RANGE.start(),
+                AUTOSAVE_INTERVAL_RANGE.end()
+            )));
+        }
+        if !EXPORT_DPI_RANGE.contains(&self.export_dpi) {
+            return Err(Error::InvalidSettings(format!(
+                "export DPI must be between {} and {}",
+                EXPORT_DPI_RANGE.start(),
+                EXPORT_DPI_RANGE.end()
+            )));
+        }
+        let max_dimension = &image_pipeline::MAX_DIMENSION_RANGE;
+        if !max_dimension.contains(&self.image_optimization.max_dimension) {
+            return Err(Error::InvalidSettings(format!(
+                "maximum image dimension must be between {} and {} pixels",
+                max_dimension.start(),
+                max_dimension.end()
+            )));
+        }
+        if !TRASH_RETENTION_RANGE.contains(&self.trash_retention_days) {
+            return Err(Error::InvalidSettings(format!(
+                "trash retention must be between {} and {} days",
+                TRASH_RETENTION_RANGE.start(),
+                TRASH_RETENTION_RANGE.end()
+            )));
+        }
+        if let Some(reason) = preset::validate_presets(&self.export_presets) {
+            return Err(Error::InvalidSettings(reason));
+        }
+        Ok(())
+    }
+}
+
+/// Fields the frontend may change through `update_settings`. Global shortcuts
+/// go through `register_global_shortcut` so they are registered with the OS,
+/// and the language through `set_app_language` so the menus are rebuilt.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SettingsPatch {
+    theme: Option<Theme>,
+    autosave_interval_secs: Option<u64>,
+    export_dpi: Option<u32>,
+    export_presets: Option<Vec<ExportPreset>>,
+    image_optimization: Option<ImageOptimization>,
+    boards_directory: Option<PathBuf>,
+    trash_retention_days: Option<u32>,
+    notifications_enabled: Option<bool>,
+    automation_enabled: Option<bool>,
+    restore_session: Option<bool>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct LegacyWorkspaceSettings {
+    boards_directory: Option<PathBuf>,
+}
+
+/// App-wide preferences persisted in settings.json. Every change is
+/// broadcast to all windows as `settings-changed`.
+pub struct Settings {
+    app: AppHandle,
+    store_path: PathBuf,
+    values: Mutex<AppSettings>,
+}
+
+impl Settings {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let store_path = persist::app_data_file(app, SETTINGS_STORE)?;
+        let values = if store_path.exists() {
+            persist::read_json(&store_path)
+        } else {
+            let workspace: LegacyWorkspaceSettings = persist::read_json(&persist::app_data_file(app, LEGACY_WORKSPACE_STORE)?);
+            let shortcuts_path = persist::app_data_file(app, LEGACY_SHORTCUTS_STORE)?;
+            AppSettings {
+                boards_directory: workspace.boards_directory,
+                global_shortcuts: shortcuts_path.exists().then(|| persist::read_json(&shortcuts_path)),
+                ..AppSettings::default()
+            }
+        };
+        Ok(Self {
+            app: app.clone(),
+            store_path,
+            values: Mutex::new(values),
+        })
+    }
+
+    pub fn get(&self) -> AppSettings {
+        self.values.lock().unwrap().clone()
+    }
+
+    /// Apply `f`, keeping the previous settings if the result is invalid.
+    pub fn update(&self, f: impl FnOnce(&mut AppSettings)) -> Result<AppSettings> {
+        let mut values = self.values.lock().unwrap();
+        let mut updated = values.clone();
+        f(&mut updated);
+        updated.validate()?;
+        persist::write_json(&self.store_path, &updated)?;
+        *values = updated.clone();
+        drop(values);
+
+        let _ = self.app.emit("settings-changed", effective(&self.app, updated.clone()));
+        Ok(updated)
+    }
+}
+
+/// Settings with defaults resolved, as shown to the frontend.
+fn effective(app: &AppHandle, mut settings: AppSettings) -> AppSettings {
+    if let Some(workspace) = app.try_state::<Workspace>() {
+        settings.boards_directory = Some(workspace.directory());
+    }
+    if let Some(shortcuts) = app.try_state::<GlobalShortcuts>() {
+        settings.global_shortcuts = Some(shortcuts.bindings());
+    }
+    settings
+}
+
+#[tauri::command]
+pub fn get_settings(app: AppHandle, settings: State<'_, Settings>) -> AppSettings {
+    effective(&app, settings.get())
+}
+
+#[tauri::command]
+pub fn update_settings(app: AppHandle, settings: State<'_, Settings>, patch: SettingsPatch) -> Result<AppSettings> {
+    if let Some(directory) = patch.boards_directory {
+        app.state::<Workspace>().set_directory(&app, directory)?;
+    }
+    let updated = settings.update(|values| {
+        if let Some(theme) = patch.theme {
+            values.theme = theme;
+        }
+        if let Some(interval) = patch.autosave_interval_secs {
+            values.autosave_interval_secs = interval;
+        }
+        if let Some(dpi) = patch.export_dpi {
+            values.export_dpi = dpi;
+        }
+        if let Some(presets) = patch.export_presets {
+            values.export_presets = presets;
+        }
+        if let Some(image_optimization) = patch.image_optimization {
+            values.image_optimization = image_optimization;
+        }
+        if let Some(days) = patch.trash_retention_days {
+            values.trash_retention_days = days;
+        }
+        if let Some(enabled) = patch.notifications_enabled {
+            values.notifications_enabled = enabled;
+        }
+        if let Some(enabled) = patch.automation_enabled {
+            values.automation_enabled = enabled;
+        }
+        if let Some(enabled) = patch.restore_session {
+            values.restore_session = enabled;
+        }
+    })?;
+    app.state::<Automation>().apply(&app, updated.automation_enabled)?;
+    Ok(effective(&app, updated))
+}
@@ -0,0 +1,325 @@
+use std::{
+    io::Write,
+    net::{IpAddr, Ipv4Addr, TcpListener, TcpStream, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::Duration,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    board,
+    document::BoardDocument,
+    error::{Error, Result},
+    export::{html::render_html, svg::Resources},
+    http::{self, respond, Request},
+    qr::QrCode,
+};
+
+/// Viewers that may be connected at once, live-reload streams included.
+const MAX_CONNECTIONS: usize = 64;
+/// Comment lines keep idle live-reload streams from being cut by proxies and
+/// notice viewers that went away.
+const KEEPALIVE: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShareOptions {
+    /// Port to listen on; any free port when omitted
+    pub port: Option<u16>,
+    /// Ask viewers for this password (any user name)
+    pub password: Option<String>,
+    /// Refresh open pages when the board is saved
+    pub live_reload: bool,
+}
+
+impl Default for ShareOptions {
+    fn default() -> Self {
+        Self {
+            port: None,
+            password: None,
+            live_reload: true,
+        }
+    }
+}
+
+/// The running share, as shown to the user.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareInfo {
+    pub board: String,
+    /// Address on the local network, with the share's secret path
+    pub url: String,
+    /// SVG of a QR code for `url`, for phones in the room
+    pub qr_svg: Option<String>,
+    pub password_protected: bool,
+    pub live_reload: bool,
+}
+
+/// State shared with the server threads.
+struct Shared {
+    token: String,
+    password: Option<String>,
+    live_reload: bool,
+    /// Rendered page and how many times it was re-rendered
+    page: Mutex<(u64, Arc<String>)>,
+    changed: Condvar,
+    /// Ends the live-reload streams
+    stopped: AtomicBool,
+}
+
+struct Session {
+    info: ShareInfo,
+    shared: Arc<Shared>,
+    server: http::Server,
+}
+
+impl Session {
+    fn stop(&self) {
+        self.shared.stopped.store(true, Ordering::SeqCst);
+        self.shared.changed.notify_all();
+        self.server.stop();
+    }
+}
+
+/// Serves one board read-only over HTTP on the local network, as the HTML
+/// export, so people in the room can follow along in a browser. One board is
+/// shared at a time; sharing another stops the previous share.
+#[derive(Default)]
+pub struct ShareServer {
+    session: Mutex<Option<Session>>,
+}
+
+impl ShareServer {
+    fn start(&self, board: String, options: ShareOptions) -> Result<ShareInfo> {
+        let page = with_live_reload(render_page(&board)?, 0, options.live_reload);
+        let password = options.password.filter(|password| !password.is_empty());
+        self.stop();
+
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, options.port.unwrap_or(0)))?;
+        let port = listener.local_addr()?.port();
+        let token = uuid::Uuid::new_v4().simple().to_string()[..12].to_string();
+        let url = format!("http://{}:{port}/{token}/", lan_address());
+        let info = ShareInfo {
+            board,
+            qr_svg: QrCode::encode(&url).map(|code| code.to_svg()),
+            url,
+            password_protected: password.is_some(),
+            live_reload: options.live_reload,
+        };
+        let shared = Arc::new(Shared {
+            token,
+            password,
+            live_reload: options.live_reload,
+            page: Mutex::new((0, Arc::new(page))),
+            changed: Condvar::new(),
+            stopped: AtomicBool::new(false),
+        });
+        let busy =
+            |stream: &TcpStream| respond(stream, "503 Service Unavailable", "text/plain", b"Too many viewers", false);
+        let server = {
+            let shared = shared.clone();
+            http::Server::start(listener, "share", MAX_CONNECTIONS, busy, move |stream, request| {
+                handle(stream, &request, &shared)
+            })
+        };
+        tracing::info!(port, "sharing board on the local network");
+
+        *self.session.lock().unwrap() = Some(Session {
+            info: info.clone(),
+            shared,
+            server,
+        });
+        Ok(info)
+    }
+
+    fn stop(&self) {
+        if let Some(session) = self.session.lock().unwrap().take() {
+            session.stop();
+            tracing::info!("stopped sharing board");
+        }
+    }
+
+    fn info(&self) -> Option<ShareInfo> {
+        self.session.lock().unwrap().as_ref().map(|session| session.info.clone())
+    }
+
+    /// Follow the shared board when it is renamed or moved.
+    pub fn rename(&self, from: &str, to: &str) {
+        if let Some(session) = self.session.lock().unwrap().as_mut().filter(|session| session.info.board == from) {
+            session.info.board = to.to_string();
+        }
+    }
+
+    /// Stop sharing `board`, e.g. once it is deleted.
+    pub fn remove(&self, board: &str) {
+        let shared = self.session.lock().unwrap().as_ref().is_some_and(|session| session.info.board == board);
+        if shared {
+            self.stop();
+        }
+    }
+
+    fn shared_for(&self, board: &str) -> Option<Arc<Shared>> {
+        let session = self.session.lock().unwrap();
+        session.as_ref().filter(|session| session.info.board == board).map(|session| session.shared.clone())
+    }
+}
+
+/// Address other devices on the network reach this machine at: the one the
+/// default route goes out of. Connecting a UDP socket sends nothing.
+fn lan_address() -> IpAddr {
+    UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .and_then(|socket| {
+            socket.connect((Ipv4Addr::new(192, 0, 2, 1), 80))?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip())
+        .ok()
+        .filter(|ip| !ip.is_unspecified())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
+
+fn render_page(board: &str) -> Result<String> {
+    let document: BoardDocument = serde_json::from_value(board::read_board_document(board)?)?;
+    let resources = Resources::load(Some(board), true)?;
+    render_html(&document, resources).ok_or_else(|| Error::Render("board is empty".to_string()))
+}
+
+/// Add the script reloading the page once the shared board is saved again.
+/// The events stream starts with the revision being served, so a save
+/// between loading the page and connecting to it isn't missed.
+fn with_live_reload(mut page: String, revision: u64, live_reload: bool) -> String {
+    if live_reload {
+        if let Some(end) = page.rfind("</body>") {
+            let script = format!(
+                "<script>new EventSource('events').onmessage = (event) => \
+                 event.data !== '{revision}' && location.reload()</script>"
+            );
+            page.insert_str(end, &script);
+        }
+    }
+    page
+}
+
+/// Re-render the shared page after `board` was saved and tell live-reload
+/// viewers. The previous page stays up if rendering fails.
+pub fn reload_in_background(app: &AppHandle, board: String) {
+    let Some(shared) = app.try_state::<ShareServer>().and_then(|server| server.shared_for(&board)) else {
+        return;
+    };
+    tauri::async_runtime::spawn_blocking(move || match render_page(&board) {
+        Ok(page) => {
+            let mut current = shared.page.lock().unwrap();
+            let revision = current.0 + 1;
+            *current = (revision, Arc::new(with_live_reload(page, revision, shared.live_reload)));
+            shared.changed.notify_all();
+        }
+        Err(err) => tracing::warn!("failed to refresh the shared page of {board}: {err}"),
+    });
+}
+
+/// Whether a `Basic` authorization header carries the password.
+fn authorized(header: Option<&str>, password: &str) -> bool {
+    let credentials = header
+        .and_then(|header| header.strip_prefix("Basic "))
+        .and_then(|encoded| STANDARD.decode(encoded.trim()).ok())
+        .and_then(|decoded| String::from_utf8(decoded).ok());
+    // Constant-time, so response timings don't give the password away
+    credentials.is_some_and(|credentials| {
+        credentials.split_once(':').is_some_and(|(_, given)| bool::from(given.as_bytes().ct_eq(password.as_bytes())))
+    })
+}
+
+fn handle(stream: &TcpStream, request: &Request<'_>, shared: &Shared) -> std::io::Result<()> {
+    let (method, target) = (request.method.as_str(), request.target.as_str());
+    let head = method == "HEAD";
+    if method != "GET" && !head {
+        return respond(stream, "405 Method Not Allowed", "text/plain", b"Read-only", head);
+    }
+    let path = target.split('?').next().unwrap_or_default();
+    let Some(route) = path.strip_prefix('/').and_then(|path| path.strip_prefix(shared.token.as_str())) else {
+        return respond(stream, "404 Not Found", "text/plain", b"Not found", head);
+    };
+    if let Some(password) = &shared.password {
+        if !authorized(request.header("authorization"), password) {
+            let mut stream = stream;
+            return write!(
+                stream,
+                "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Basic realm=\"Boardkit\", charset=\"UTF-8\"\r\n\
+                 Content-Length: 0\r\nConnection: close\r\n\r\n"
+            );
+        }
+    }
+    match route {
+        "" => {
+            let mut stream = stream;
+            let location = format!("/{}/", shared.token);
+            write!(stream, "HTTP/1.1 308 Permanent Redirect\r\nLocation: {location}\r\nContent-Length: 0\r\n\r\n")
+        }
+        "/" => {
+            let page = shared.page.lock().unwrap().1.clone();
+            respond(stream, "200 OK", "text/html; charset=utf-8", page.as_bytes(), head)
+        }
+        "/events" if shared.live_reload && !head => events(stream, shared),
+        _ => respond(stream, "404 Not Found", "text/plain", b"Not found", head),
+    }
+}
+
+/// Server-sent events stream sending the page's current revision, then the
+/// next one once the page was re-rendered.
+fn events(mut stream: &TcpStream, shared: &Shared) -> std::io::Result<()> {
+    let seen = shared.page.lock().unwrap().0;
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n\
+         data: {seen}\n\n"
+    )?;
+    stream.flush()?;
+    let mut page = shared.page.lock().unwrap();
+    loop {
+        if shared.stopped.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        if page.0 != seen {
+            let revision = page.0;
+            drop(page);
+            write!(stream, "data: {revision}\n\n")?;
+            return stream.flush();
+        }
+        let (next, timeout) = shared.changed.wait_timeout(page, KEEPALIVE).unwrap();
+        page = next;
+        if timeout.timed_out() {
+            drop(page);
+            write!(stream, ": keepalive\n\n")?;
+            stream.flush()?;
+            page = shared.page.lock().unwrap();
+        }
+    }
+}
+
+/// Start serving `board` read-only on the local network, replacing any
+/// previous share.
+#[tauri::command]
+pub async fn start_share_server(app: AppHandle, board: String, options: Option<ShareOptions>) -> Result<ShareInfo> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || app.state::<ShareServer>().start(board, options))
+        .await
+        .map_err(|err| Error::Render(err.to_string()))?
+}
+
+#[tauri::command]
+pub fn stop_share_server(server: State<'_, ShareServer>) {
+    server.stop();
+}
+
+/// The running share, if any.
+#[tauri::command]
+pub fn get_share_status(server: State<'_, ShareServer>) -> Option<ShareInfo> {
+    server.info()
+}
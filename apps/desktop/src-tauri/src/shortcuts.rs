@@ -0,0 +1,192 @@
+use std::{collections::BTreeMap, str::FromStr, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+use crate::{
+    error::{Error, Result},
+    quick_capture,
+    settings::Settings,
+    tray, windows,
+};
+
+// SUPER maps to the Windows key on Windows/Linux, where it is reserved by the OS
+#[cfg(target_os = "macos")]
+const DEFAULT_PALETTE_SHORTCUT: &str = "Super+Shift+Space";
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_PALETTE_SHORTCUT: &str = "Control+Shift+Space";
+#[cfg(target_os = "macos")]
+const DEFAULT_QUICK_CAPTURE_SHORTCUT: &str = "Super+Alt+N";
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_QUICK_CAPTURE_SHORTCUT: &str = "Control+Alt+N";
+
+/// Actions that can be bound to a system-wide shortcut.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlobalAction {
+    CommandPalette,
+    NewBoard,
+    QuickCapture,
+    ToggleWindow,
+}
+
+fn default_bindings() -> BTreeMap<GlobalAction, String> {
+    BTreeMap::from([
+        (GlobalAction::CommandPalette, DEFAULT_PALETTE_SHORTCUT.to_string()),
+        (GlobalAction::QuickCapture, DEFAULT_QUICK_CAPTURE_SHORTCUT.to_string()),
+    ])
+}
+
+/// Accelerators keyed by action (one shortcut per action), persisted in settings.json.
+pub struct GlobalShortcuts {
+    bindings: Mutex<BTreeMap<GlobalAction, String>>,
+}
+
+/// A binding as reported to the settings UI.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveShortcut {
+    action: GlobalAction,
+    accelerator: String,
+    /// False when the OS refused the shortcut (e.g. already taken by another app)
+    registered: bool,
+}
+
+pub fn parse(accelerator: &str) -> Result<Shortcut> {
+    Shortcut::from_str(accelerator).map_err(|_| Error::InvalidShortcut(accelerator.to_string()))
+}
+
+impl GlobalShortcuts {
+    pub fn load(app: &AppHandle) -> Self {
+        let bindings = app.state::<Settings>().get().global_shortcuts.unwrap_or_else(default_bindings);
+        Self {
+            bindings: Mutex::new(bindings),
+        }
+    }
+
+    pub fn bindings(&self) -> BTreeMap<GlobalAction, String> {
+        self.bindings.lock().unwrap().clone()
+    }
+
+    /// Register every persisted binding. A shortcut taken by another app must
+    /// not prevent startup, so failures are only logged.
+    pub fn register_all(&self, app: &AppHandle) {
+        for (action, accelerator) in self.bindings.lock().unwrap().iter() {
+            let result = parse(accelerator).and_then(|shortcut| Ok(app.global_shortcut().register(shortcut)?));
+            if let Err(err) = result {
+                tracing::warn!("global shortcut {accelerator} for {action:?} not registered: {err}");
+            }
+        }
+    }
+
+    fn action_for(&self, shortcut: &Shortcut) -> Option<GlobalAction> {
+        self.bindings
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(_, accelerator)| parse(accelerator).is_ok_and(|bound| bound.id() == shortcut.id()))
+            .map(|(action, _)| *action)
+    }
+
+    fn save(app: &AppHandle, bindings: BTreeMap<GlobalAction, String>) -> Result<()> {
+        app.state::<Settings>().update(|settings| settings.global_shortcuts = Some(bindings))?;
+        Ok(())
+    }
+}
+
+/// Bring forward the board window menu commands would act on, which need not
+/// be the main one, reopening it if every board window was closed.
+fn focus_board_window(app: &AppHandle) -> Option<tauri::WebviewWindow> {
+    let window = windows::target_or_reopen(app)?;
+    windows::focus_window(&window);
+    Some(window)
+}
+
+/// Global shortcut plugin handler: run the action bound to the pressed shortcut.
+pub fn handle_shortcut(app: &AppHandle, shortcut: &Shortcut) {
+    let Some(action) = app.state::<GlobalShortcuts>().action_for(shortcut) else {
+        return;
+    };
+
+    match action {
+        GlobalAction::CommandPalette => {
+            if let Some(window) = focus_board_window(app) {
+                let _ = window.emit_to(window.label(), "open-command-palette", ());
+            }
+        }
+        GlobalAction::NewBoard => {
+            if let Some(window) = focus_board_window(app) {
+                let _ = window.emit_to(window.label(), "menu-new-board", ());
+            }
+        }
+        GlobalAction::QuickCapture => quick_capture::toggle(app),
+        GlobalAction::ToggleWindow => tray::toggle_main_window(app),
+    }
+}
+
+/// Bind `accelerator` (e.g. `"CmdOrCtrl+Shift+Space"`) to `action`, replacing
+/// any previous shortcut for that action.
+#[tauri::command]
+pub fn register_global_shortcut(
+    app: AppHandle,
+    shortcuts: State<'_, GlobalShortcuts>,
+    accelerator: String,
+    action: GlobalAction,
+) -> Result<()> {
+    let shortcut = parse(&accelerator)?;
+    let mut bindings = shortcuts.bindings.lock().unwrap();
+
+    if let Some((other, _)) = bindings
+        .iter()
+        .find(|(other, bound)| **other != action && parse(bound).is_ok_and(|bound| bound.id() == shortcut.id()))
+    {
+        return Err(Error::ShortcutConflict(format!("{accelerator} is already bound to {other:?}")));
+    }
+
+    if let Some(previous) = bindings.get(&action) {
+        if let Ok(previous) = parse(previous) {
+            let _ = app.global_shortcut().unregister(previous);
+        }
+    }
+    app.global_shortcut().register(shortcut)?;
+
+    bindings.insert(action, accelerator);
+    let saved = bindings.clone();
+    // Settings broadcasts the new bindings, which locks them again
+    drop(bindings);
+    GlobalShortcuts::save(&app, saved)
+}
+
+#[tauri::command]
+pub fn unregister_global_shortcut(
+    app: AppHandle,
+    shortcuts: State<'_, GlobalShortcuts>,
+    action: GlobalAction,
+) -> Result<()> {
+    let mut bindings = shortcuts.bindings.lock().unwrap();
+    if let Some(accelerator) = bindings.remove(&action) {
+        if let Ok(shortcut) = parse(&accelerator) {
+            app.global_shortcut().unregister(shortcut)?;
+        }
+        let saved = bindings.clone();
+        drop(bindings);
+        GlobalShortcuts::save(&app, saved)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_active_shortcuts(app: AppHandle, shortcuts: State<'_, GlobalShortcuts>) -> Vec<ActiveShortcut> {
+    shortcuts
+        .bindings
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(action, accelerator)| ActiveShortcut {
+            action: *action,
+            accelerator: accelerator.clone(),
+            registered: parse(accelerator).is_ok_and(|shortcut| app.global_shortcut().is_registered(shortcut)),
+        })
+        .collect()
+}
@@ -0,0 +1,165 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use rstar::{RTree, RTreeObject, AABB};
+use serde::Deserialize;
+use tauri::{Manager, State, Window, WindowEvent};
+
+/// Where an element sits on the canvas, as sent by the frontend.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementBounds {
+    id: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    /// Rotation in radians around the rect's center.
+    #[serde(default)]
+    angle: f64,
+    z_index: f64,
+}
+
+#[derive(Deserialize)]
+pub struct Rect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+#[derive(Deserialize)]
+pub struct Point {
+    x: f64,
+    y: f64,
+}
+
+/// Changes to a window's index, applied in order.
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum SpatialOp {
+    /// Replace the whole index, e.g. after loading a board.
+    Reset { elements: Vec<ElementBounds> },
+    /// Add elements, or move ones already indexed.
+    Upsert { elements: Vec<ElementBounds> },
+    Remove { ids: Vec<String> },
+}
+
+#[derive(Clone, PartialEq)]
+struct Entry {
+    id: String,
+    z_index: f64,
+    envelope: AABB<[f64; 2]>,
+}
+
+impl RTreeObject for Entry {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        self.envelope
+    }
+}
+
+impl From<ElementBounds> for Entry {
+    /// Indexed by the axis-aligned box around the rotated rect.
+    fn from(bounds: ElementBounds) -> Self {
+        let (sin, cos) = bounds.angle.sin_cos();
+        let (half_width, half_height) = (bounds.width.abs() / 2.0, bounds.height.abs() / 2.0);
+        let extent_x = half_width * cos.abs() + half_height * sin.abs();
+        let extent_y = half_width * sin.abs() + half_height * cos.abs();
+        let (center_x, center_y) = (bounds.x + bounds.width / 2.0, bounds.y + bounds.height / 2.0);
+        Self {
+            id: bounds.id,
+            z_index: bounds.z_index,
+            envelope: AABB::from_corners(
+                [center_x - extent_x, center_y - extent_y],
+                [center_x + extent_x, center_y + extent_y],
+            ),
+        }
+    }
+}
+
+#[derive(Default)]
+struct BoardIndex {
+    tree: RTree<Entry>,
+    entries: HashMap<String, Entry>,
+}
+
+impl BoardIndex {
+    fn remove(&mut self, id: &str) {
+        if let Some(entry) = self.entries.remove(id) {
+            self.tree.remove(&entry);
+        }
+    }
+
+    fn apply(&mut self, op: SpatialOp) {
+        match op {
+            SpatialOp::Reset { elements } => {
+                let entries: Vec<Entry> = elements.into_iter().map(Entry::from).collect();
+                self.entries = entries.iter().map(|entry| (entry.id.clone(), entry.clone())).collect();
+                self.tree = RTree::bulk_load(entries);
+            }
+            SpatialOp::Upsert { elements } => {
+                for entry in elements.into_iter().map(Entry::from) {
+                    self.remove(&entry.id);
+                    self.entries.insert(entry.id.clone(), entry.clone());
+                    self.tree.insert(entry);
+                }
+            }
+            SpatialOp::Remove { ids } => ids.iter().for_each(|id| self.remove(id)),
+        }
+    }
+}
+
+/// An R-tree of element bounds per window, kept in step with the board by
+/// the frontend, so culling and hit-testing giant boards doesn't scan every
+/// element each frame.
+#[derive(Default)]
+pub struct SpatialIndex {
+    windows: Mutex<HashMap<String, BoardIndex>>,
+}
+
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::Destroyed = event {
+        if let Some(index) = window.try_state::<SpatialIndex>() {
+            index.windows.lock().unwrap().remove(window.label());
+        }
+    }
+}
+
+#[tauri::command]
+pub fn update_spatial_index(window: Window, index: State<'_, SpatialIndex>, ops: Vec<SpatialOp>) {
+    let mut windows = index.windows.lock().unwrap();
+    let board = windows.entry(window.label().to_string()).or_default();
+    ops.into_iter().for_each(|op| board.apply(op));
+}
+
+/// Ids of the elements overlapping `rect`, in stacking order.
+#[tauri::command]
+pub fn query_viewport(window: Window, index: State<'_, SpatialIndex>, rect: Rect) -> Vec<String> {
+    let windows = index.windows.lock().unwrap();
+    let Some(board) = windows.get(window.label()) else {
+        return Vec::new();
+    };
+    let area = AABB::from_corners([rect.x, rect.y], [rect.x + rect.width, rect.y + rect.height]);
+    let mut found: Vec<&Entry> = board.tree.locate_in_envelope_intersecting(&area).collect();
+    found.sort_by(|a, b| a.z_index.total_cmp(&b.z_index));
+    found.into_iter().map(|entry| entry.id.clone()).collect()
+}
+
+/// Ids of the elements whose bounds contain `point`, give or take
+/// `tolerance` canvas units, topmost first.
+#[tauri::command]
+pub fn hit_test(window: Window, index: State<'_, SpatialIndex>, point: Point, tolerance: Option<f64>) -> Vec<String> {
+    let windows = index.windows.lock().unwrap();
+    let Some(board) = windows.get(window.label()) else {
+        return Vec::new();
+    };
+    let tolerance = tolerance.unwrap_or(0.0).max(0.0);
+    let area = AABB::from_corners(
+        [point.x - tolerance, point.y - tolerance],
+        [point.x + tolerance, point.y + tolerance],
+    );
+    let mut found: Vec<&Entry> = board.tree.locate_in_envelope_intersecting(&area).collect();
+    found.sort_by(|a, b| b.z_index.total_cmp(&a.z_index));
+    found.into_iter().map(|entry| entry.id.clone()).collect()
+}
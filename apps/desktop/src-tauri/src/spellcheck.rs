@@ -0,0 +1,258 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+use spellbook::Dictionary;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    error::{Error, Result},
+    persist,
+};
+
+const USER_DICTIONARY_STORE: &str = "user-dictionary.json";
+/// Folder for bundled dictionaries in the resources dir, and for ones the
+/// user installs in the app data dir.
+const DICTIONARY_DIR: &str = "dictionaries";
+const DEFAULT_LANG: &str = "en_US";
+const MAX_SUGGESTIONS: usize = 8;
+/// Hunspell dictionaries that ship with the OS on Linux, or that macOS users
+/// installed system-wide. Windows has none, and none are bundled, so there
+/// the user's folder is the only source.
+#[cfg(target_os = "macos")]
+const SYSTEM_DICTIONARY_DIRS: &[&str] = &["/Library/Spelling"];
+#[cfg(target_os = "windows")]
+const SYSTEM_DICTIONARY_DIRS: &[&str] = &[];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const SYSTEM_DICTIONARY_DIRS: &[&str] = &["/usr/share/hunspell", "/usr/share/myspell", "/usr/share/myspell/dicts"];
+
+/// A misspelled word in checked text. Offsets are UTF-16 code units so the
+/// frontend can slice JavaScript strings with them directly.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Misspelling {
+    word: String,
+    start: usize,
+    end: usize,
+}
+
+/// Hunspell dictionaries by locale, loaded on first use, plus the words the
+/// user added, which count as correct in every language.
+pub struct SpellChecker {
+    search_dirs: Vec<PathBuf>,
+    store_path: PathBuf,
+    user_words: Mutex<BTreeSet<String>>,
+    /// `None` remembers that a locale has no dictionary
+    dictionaries: Mutex<HashMap<String, Option<Arc<Dictionary>>>>,
+}
+
+/// `en-US`, `en_us` and `en_US` all name the `en_US` dictionary.
+fn normalize_lang(lang: &str) -> String {
+    match lang.replace('-', "_").split_once('_') {
+        Some((language, region)) => format!("{}_{}", language.to_lowercase(), region.to_uppercase()),
+        None => lang.to_lowercase(),
+    }
+}
+
+impl SpellChecker {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let store_path = persist::app_data_file(app, USER_DICTIONARY_STORE)?;
+        let user_words = persist::read_json(&store_path);
+        let mut search_dirs = vec![app.path().app_data_dir()?.join(DICTIONARY_DIR)];
+        if let Ok(resources) = app.path().resource_dir() {
+            search_dirs.push(resources.join(DICTIONARY_DIR));
+        }
+        if let Ok(home) = app.path().home_dir() {
+            if cfg!(target_os = "macos") {
+                search_dirs.push(home.join("Library/Spelling"));
+            }
+        }
+        search_dirs.extend(SYSTEM_DICTIONARY_DIRS.iter().map(PathBuf::from));
+        Ok(Self {
+            search_dirs,
+            store_path,
+            user_words: Mutex::new(user_words),
+            dictionaries: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// `<lang>.aff` next to `<lang>.dic`; a bare language such as `de` also
+    /// matches the first regional dictionary for it.
+    fn find(&self, lang: &str) -> Option<(PathBuf, PathBuf)> {
+        let pair = |dir: &Path, name: &str| {
+            let (aff, dic) = (dir.join(format!("{name}.aff")), dir.join(format!("{name}.dic")));
+            (aff.is_file() && dic.is_file()).then_some((aff, dic))
+        };
+        self.search_dirs.iter().find_map(|dir| {
+            pair(dir, lang).or_else(|| {
+                let prefix = format!("{}_", lang.split('_').next().unwrap_or(lang));
+                let mut names: Vec<String> = fs::read_dir(dir)
+                    .ok()?
+                    .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                    .filter_map(|name| name.strip_suffix(".dic").map(str::to_string))
+                    .filter(|name| name.starts_with(&prefix))
+                    .collect();
+                names.sort();
+                names.iter().find_map(|name| pair(dir, name))
+            })
+        })
+    }
+
+    /// Points at the user's dictionary folder, where a missing one can go.
+    fn missing(&self, lang: &str) -> Error {
+        Error::MissingDictionary {
+            lang: lang.to_string(),
+            dir: self.search_dirs[0].to_string_lossy().into_owned(),
+        }
+    }
+
+    fn dictionary(&self, lang: Option<&str>) -> Result<Arc<Dictionary>> {
+        let lang = normalize_lang(lang.unwrap_or(DEFAULT_LANG));
+        if let Some(cached) = self.dictionaries.lock().unwrap().get(&lang) {
+            return cached.clone().ok_or_else(|| self.missing(&lang));
+        }
+        // Parsed outside the lock; a large dictionary takes a moment
+        let loaded = match self.find(&lang) {
+            Some((aff, dic)) => {
+                let dictionary = Dictionary::new(&fs::read_to_string(aff)?, &fs::read_to_string(dic)?)
+                    .map_err(|err| Error::Spellcheck(format!("invalid dictionary for {lang}: {err}")))?;
+                tracing::info!(lang, "spelling dictionary loaded");
+                Some(Arc::new(dictionary))
+            }
+            None => None,
+        };
+        self.dictionaries.lock().unwrap().insert(lang.clone(), loaded.clone());
+        loaded.ok_or_else(|| self.missing(&lang))
+    }
+
+    fn is_user_word(&self, word: &str) -> bool {
+        let words = self.user_words.lock().unwrap();
+        words.contains(word) || words.contains(&word.to_lowercase())
+    }
+
+    fn update(&self, f: impl FnOnce(&mut BTreeSet<String>)) -> Result<Vec<String>> {
+        let mut words = self.user_words.lock().unwrap();
+        f(&mut words);
+        persist::write_json(&self.store_path, &*words)?;
+        Ok(words.iter().cloned().collect())
+    }
+
+    /// Locales with a dictionary installed, for the language picker.
+    fn available(&self) -> Vec<String> {
+        let mut locales: BTreeSet<String> = BTreeSet::new();
+        for dir in &self.search_dirs {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            for name in entries.filter_map(|entry| entry.ok()?.file_name().into_string().ok()) {
+                if let Some(stem) = name.strip_suffix(".dic") {
+                    if dir.join(format!("{stem}.aff")).is_file() {
+                        locales.insert(stem.to_string());
+                    }
+                }
+            }
+        }
+        locales.into_iter().collect()
+    }
+}
+
+/// Words with their UTF-16 offsets. Apostrophes inside a word are kept
+/// (`don't`); URLs, paths, email addresses and anything with digits are skipped.
+fn words(text: &str) -> Vec<(&str, usize, usize)> {
+    let mut found = Vec::new();
+    let mut chunk_start = 0;
+    for chunk in text.split_inclusive(char::is_whitespace) {
+        let offset = chunk_start;
+        chunk_start += chunk.encode_utf16().count();
+        if chunk.contains(|c: char| c.is_ascii_digit() || matches!(c, '@' | '/' | '\\' | '_')) {
+            continue;
+        }
+        let mut utf16 = offset;
+        let mut word_start = None;
+        let mut chars = chunk.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            let inner_apostrophe = matches!(c, '\'' | '’')
+                && word_start.is_some()
+                && chars.peek().is_some_and(|(_, next)| next.is_alphabetic());
+            match (c.is_alphabetic() || inner_apostrophe, word_start) {
+                (true, None) => word_start = Some((index, utf16)),
+                (false, Some((start, start16))) => {
+                    found.push((&chunk[start..index], start16, utf16));
+                    word_start = None;
+                }
+                _ => {}
+            }
+            utf16 += c.len_utf16();
+        }
+        if let Some((start, start16)) = word_start {
+            found.push((&chunk[start..], start16, utf16));
+        }
+    }
+    found
+}
+
+/// Find misspelled words in `text`. `lang` is a locale such as `en-US`,
+/// usually the UI language.
+#[tauri::command]
+pub async fn check_text(app: AppHandle, text: String, lang: Option<String>) -> Result<Vec<Misspelling>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let checker = app.state::<SpellChecker>();
+        let dictionary = checker.dictionary(lang.as_deref())?;
+        Ok(words(&text)
+            .into_iter()
+            .filter(|(word, _, _)| word.chars().count() > 1 && !checker.is_user_word(word) && !dictionary.check(word))
+            .map(|(word, start, end)| Misspelling {
+                word: word.to_string(),
+                start,
+                end,
+            })
+            .collect())
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// Replacements for a misspelled word, best first.
+#[tauri::command]
+pub async fn suggest(app: AppHandle, word: String, lang: Option<String>) -> Result<Vec<String>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let dictionary = app.state::<SpellChecker>().dictionary(lang.as_deref())?;
+        let mut suggestions = Vec::new();
+        dictionary.suggest(&word, &mut suggestions);
+        suggestions.truncate(MAX_SUGGESTIONS);
+        Ok(suggestions)
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+#[tauri::command]
+pub fn get_user_dictionary(checker: State<'_, SpellChecker>) -> Vec<String> {
+    checker.user_words.lock().unwrap().iter().cloned().collect()
+}
+
+#[tauri::command]
+pub fn add_to_dictionary(checker: State<'_, SpellChecker>, word: String) -> Result<Vec<String>> {
+    checker.update(|words| {
+        let word = word.trim();
+        if !word.is_empty() {
+            words.insert(word.to_string());
+        }
+    })
+}
+
+#[tauri::command]
+pub fn remove_from_dictionary(checker: State<'_, SpellChecker>, word: String) -> Result<Vec<String>> {
+    checker.update(|words| {
+        words.remove(word.trim());
+    })
+}
+
+#[tauri::command]
+pub fn list_dictionaries(checker: State<'_, SpellChecker>) -> Vec<String> {
+    checker.available()
+}
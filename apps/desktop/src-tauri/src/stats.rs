@@ -0,0 +1,139 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+    time::UNIX_EPOCH,
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    board,
+    error::{Error, Result},
+    history, search,
+};
+
+/// A point in the board's history, from saved versions.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEntry {
+    pub at: u64,
+    pub element_count: usize,
+    pub widget_count: usize,
+    pub size: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardStats {
+    /// Elements by type (`rectangle`, `text`, `image`, …).
+    pub elements: BTreeMap<String, usize>,
+    /// Widgets by module id.
+    pub widgets: BTreeMap<String, usize>,
+    pub connections: usize,
+    pub groups: usize,
+    /// Words in element text, labels and widget content.
+    pub word_count: usize,
+    /// Distinct embedded images and their total size in bytes.
+    pub image_count: usize,
+    pub image_bytes: u64,
+    pub file_size: u64,
+    pub created_at: u64,
+    pub updated_at: u64,
+    /// When the file on disk last changed, which may be newer than
+    /// `updated_at` if another app touched it.
+    pub modified_at: Option<u64>,
+    pub timeline: Vec<TimelineEntry>,
+    /// Names from collaboration metadata other tools write into the board;
+    /// empty for boards only edited in Boardkit.
+    pub contributors: Vec<String>,
+}
+
+fn array<'a>(document: &'a Value, pointer: &str) -> &'a [Value] {
+    document.pointer(pointer).and_then(Value::as_array).map_or(&[], Vec::as_slice)
+}
+
+fn count_by(items: &[Value], key: &str) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for item in items {
+        let name = item.get(key).and_then(Value::as_str).unwrap_or("unknown");
+        *counts.entry(name.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// `meta.contributors` or `meta.authors`, as plain names or `{ "name": … }`.
+fn contributors(document: &Value) -> Vec<String> {
+    let names: BTreeSet<String> = ["/meta/contributors", "/meta/authors"]
+        .iter()
+        .flat_map(|pointer| array(document, pointer))
+        .filter_map(|entry| entry.as_str().or_else(|| entry.get("name")?.as_str()))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect();
+    names.into_iter().collect()
+}
+
+fn stats(path: &str) -> Result<BoardStats> {
+    let document = board::read_board_document(path)?;
+    let metadata = fs::metadata(path).map_err(|err| Error::from_io(err, path))?;
+
+    let word_count = search::document_text(&document)
+        .iter()
+        .map(|(_, text)| text.split_whitespace().count())
+        .sum();
+
+    // The same image pasted twice is stored once
+    let mut images = BTreeMap::new();
+    if let Some(assets) = document.pointer("/assets/assets").and_then(Value::as_object) {
+        for asset in assets.values() {
+            let is_image = asset.get("mimeType").and_then(Value::as_str).is_some_and(|mime| mime.starts_with("image/"));
+            if let (true, Some(hash)) = (is_image, asset.get("hash").and_then(Value::as_str)) {
+                images.insert(hash, asset.get("size").and_then(Value::as_u64).unwrap_or(0));
+            }
+        }
+    }
+
+    let timestamp = |key: &str| document.pointer(&format!("/meta/{key}")).and_then(Value::as_u64).unwrap_or(0);
+    let modified_at = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_millis() as u64);
+    let timeline = history::versions(Path::new(path))
+        .into_iter()
+        .map(|version| TimelineEntry {
+            at: version.created_at,
+            element_count: version.element_count,
+            widget_count: version.widget_count,
+            size: version.size,
+        })
+        .collect();
+
+    Ok(BoardStats {
+        elements: count_by(array(&document, "/board/elements"), "type"),
+        widgets: count_by(array(&document, "/board/widgets"), "moduleId"),
+        connections: array(&document, "/board/connections").len(),
+        groups: array(&document, "/board/groups").len(),
+        word_count,
+        image_count: images.len(),
+        image_bytes: images.values().sum(),
+        file_size: metadata.len(),
+        created_at: timestamp("createdAt"),
+        updated_at: timestamp("updatedAt"),
+        modified_at,
+        timeline,
+        contributors: contributors(&document),
+    })
+}
+
+/// Counts, sizes and history of the saved board at `path`, for the board
+/// info panel.
+#[tauri::command]
+pub async fn get_board_stats(path: String) -> Result<BoardStats> {
+    tauri::async_runtime::spawn_blocking(move || stats(&path))
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{ipc::Channel, Window};
+
+use crate::{
+    board, encryption,
+    error::{Error, Result},
+};
+
+/// Elements per `elements` event; small enough that the canvas renders each
+/// batch within a frame or two.
+const BATCH_SIZE: usize = 500;
+
+/// The visible part of the board, in canvas coordinates.
+#[derive(Deserialize)]
+pub struct Bounds {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+/// Size of the canvas on screen, in pixels.
+#[derive(Deserialize)]
+pub struct Screen {
+    width: f64,
+    height: f64,
+}
+
+impl Bounds {
+    /// The area the board's saved viewport shows on a canvas of `screen` size.
+    fn visible(document: &Value, screen: &Screen) -> Self {
+        let viewport = |name: &str| document["board"]["viewport"][name].as_f64();
+        let zoom = viewport("zoom").filter(|zoom| *zoom > 0.0).unwrap_or(1.0);
+        Self {
+            x: -viewport("x").unwrap_or(0.0) / zoom,
+            y: -viewport("y").unwrap_or(0.0) / zoom,
+            width: screen.width / zoom,
+            height: screen.height / zoom,
+        }
+    }
+
+    /// Gap between the viewport and an element's rect, 0 when they overlap.
+    fn distance(&self, element: &Value) -> f64 {
+        let coordinate = |name: &str| element["rect"][name].as_f64().unwrap_or(0.0);
+        let (x, y) = (coordinate("x"), coordinate("y"));
+        let (width, height) = (coordinate("width").max(0.0), coordinate("height").max(0.0));
+        let dx = (self.x - (x + width)).max(x - (self.x + self.width)).max(0.0);
+        let dy = (self.y - (y + height)).max(y - (self.y + self.height)).max(0.0);
+        dx.hypot(dy)
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct IndexedElement {
+    /// Position in the saved element list, which is also the stacking order
+    index: usize,
+    element: Value,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "camelCase")]
+pub enum LoadEvent {
+    /// The document with its element list emptied, sent before any element.
+    #[serde(rename_all = "camelCase")]
+    Started { document: Value, total_elements: usize },
+    Elements { elements: Vec<IndexedElement> },
+    Finished,
+}
+
+/// Load a board in pieces for boards too large to show at once: the
+/// document without its elements first, then the elements in batches,
+/// those within `bounds` first. Without bounds but with the `screen` size,
+/// the area shown by the board's saved viewport goes first. Encrypted boards
+/// fail with `password_required` and go through the regular loader instead.
+#[tauri::command]
+pub async fn load_board_streaming(
+    window: Window,
+    path: String,
+    bounds: Option<Bounds>,
+    screen: Option<Screen>,
+    on_event: Channel<LoadEvent>,
+) -> Result<()> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let bytes = board::read_board(&path)?;
+        if encryption::is_encrypted(&bytes) {
+            return Err(Error::PasswordRequired(path));
+        }
+        let (mut document, migrations) = board::read_migrated(&bytes)?;
+        drop(bytes);
+        let elements = document
+            .pointer_mut("/board/elements")
+            .and_then(Value::as_array_mut)
+            .map(std::mem::take)
+            .unwrap_or_default();
+        let total_elements = elements.len();
+        let bounds = bounds.or_else(|| screen.map(|screen| Bounds::visible(&document, &screen)));
+        on_event.send(LoadEvent::Started {
+            document,
+            total_elements,
+        })?;
+        board::emit_migrated(&window, path.clone(), migrations);
+
+        let mut order: Vec<usize> = (0..total_elements).collect();
+        if let Some(bounds) = bounds {
+            let distances: Vec<f64> = elements.iter().map(|element| bounds.distance(element)).collect();
+            order.sort_by(|&a, &b| distances[a].total_cmp(&distances[b]));
+        }
+        let mut elements: Vec<Option<Value>> = elements.into_iter().map(Some).collect();
+        for batch in order.chunks(BATCH_SIZE) {
+            let elements = batch
+                .iter()
+                .map(|&index| IndexedElement {
+                    index,
+                    element: elements[index].take().unwrap_or_default(),
+                })
+                .collect();
+            on_event.send(LoadEvent::Elements { elements })?;
+        }
+        on_event.send(LoadEvent::Finished)?;
+        tracing::info!(path, elements = total_elements, "board streamed");
+        Ok(())
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
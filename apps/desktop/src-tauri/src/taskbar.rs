@@ -0,0 +1,124 @@
+//! Dock and taskbar indicators: a badge counting what needs the user's
+//! attention, e.g. boards with unsaved changes, and a progress bar while long
+//! work such as a batch export runs. macOS and Linux have one badge and bar
+//! for the whole app; Windows shows them per window, with a dot over the icon
+//! standing in for the badge count it has no room for.
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use tauri::{
+    window::{ProgressBarState, ProgressBarStatus},
+    AppHandle, Manager, Window,
+};
+
+use crate::windows;
+
+/// Badge source for windows with unsaved changes, see `dirty.rs`.
+pub const UNSAVED: &str = "unsaved";
+
+/// Badge counts per source, shown summed.
+#[derive(Default)]
+pub struct Badge(Mutex<BTreeMap<String, i64>>);
+
+#[cfg(target_os = "windows")]
+fn overlay_dot() -> tauri::image::Image<'static> {
+    const SIZE: u32 = 16;
+    let center = (SIZE as f32 - 1.0) / 2.0;
+    let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let inside = (x as f32 - center).hypot(y as f32 - center) <= center;
+            rgba.extend_from_slice(if inside { &[0xe5, 0x48, 0x4d, 0xff] } else { &[0; 4] });
+        }
+    }
+    tauri::image::Image::new_owned(rgba, SIZE, SIZE)
+}
+
+fn show_badge(app: &AppHandle, total: i64) {
+    let count = (total > 0).then_some(total);
+    for (label, window) in app.webview_windows() {
+        if !windows::is_board_window(&label) {
+            continue;
+        }
+        #[cfg(target_os = "windows")]
+        let result = window.set_overlay_icon(count.map(|_| overlay_dot()));
+        #[cfg(not(target_os = "windows"))]
+        let result = window.set_badge_count(count);
+        if let Err(err) = result {
+            tracing::debug!("failed to set the badge of {label}: {err}");
+        }
+    }
+}
+
+/// Set the part of the badge `source` accounts for; 0 removes it.
+pub fn set_badge(app: &AppHandle, source: &str, count: i64) {
+    let Some(badge) = app.try_state::<Badge>() else {
+        return;
+    };
+    let total = {
+        let mut counts = badge.0.lock().unwrap();
+        if count > 0 {
+            counts.insert(source.to_string(), count);
+        } else {
+            counts.remove(source);
+        }
+        counts.values().sum()
+    };
+    show_badge(app, total);
+}
+
+fn show_progress(window: &Window, status: ProgressBarStatus, progress: Option<u64>) {
+    let state = ProgressBarState {
+        status: Some(status),
+        progress,
+    };
+    if let Err(err) = window.set_progress_bar(state) {
+        tracing::debug!("failed to set the progress of {}: {err}", window.label());
+    }
+}
+
+/// The taskbar progress of one long task in `window`, cleared when dropped
+/// so a task failing halfway doesn't leave it behind.
+pub struct Progress {
+    window: Window,
+}
+
+impl Progress {
+    /// Show an indeterminate bar until the first `set`.
+    pub fn start(window: &Window) -> Self {
+        show_progress(window, ProgressBarStatus::Indeterminate, None);
+        Self { window: window.clone() }
+    }
+
+    /// `done` of `total` steps finished.
+    pub fn set(&self, done: usize, total: usize) {
+        let percent = (done * 100).checked_div(total).unwrap_or(0).min(100) as u64;
+        show_progress(&self.window, ProgressBarStatus::Normal, Some(percent));
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        show_progress(&self.window, ProgressBarStatus::None, None);
+    }
+}
+
+/// Set the badge count for work the frontend tracks, under its own `source`
+/// so it adds to the unsaved boards rather than replacing them.
+#[tauri::command]
+pub fn set_badge_count(app: AppHandle, source: String, count: i64) {
+    set_badge(&app, &source, count);
+}
+
+/// Show the progress of long work the frontend runs in the calling window's
+/// taskbar entry, as a fraction from 0 to 1; `None` hides it.
+#[tauri::command]
+pub fn set_taskbar_progress(window: Window, progress: Option<f64>) {
+    match progress.filter(|progress| progress.is_finite()) {
+        Some(progress) => {
+            let percent = (progress.clamp(0.0, 1.0) * 100.0).round() as u64;
+            show_progress(&window, ProgressBarStatus::Normal, Some(percent));
+        }
+        None => show_progress(&window, ProgressBarStatus::None, None),
+    }
+}
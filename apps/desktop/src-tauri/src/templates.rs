@@ -0,0 +1,268 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    board, encryption,
+    error::{Error, Result},
+    persist,
+    thumbnails::Thumbnails,
+};
+
+/// Folder for bundled templates in the resources dir, and for the user's own
+/// in the app data dir.
+const TEMPLATES_DIR: &str = "templates";
+const TEMPLATE_EXTENSION: &str = "boardkit";
+
+/// `<id>.json` next to `<id>.boardkit`. Templates without one are named
+/// after their board title.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct TemplateMeta {
+    name: Option<String>,
+    description: Option<String>,
+    category: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateInfo {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    /// Shipped with the app, as opposed to saved by the user; only user
+    /// templates can be deleted.
+    pub builtin: bool,
+    pub path: String,
+    /// PNG preview, `None` for an empty template.
+    pub thumbnail: Option<String>,
+}
+
+/// Templates are ordinary .boardkit files, with optional metadata beside
+/// them. User templates shadow bundled ones with the same id.
+pub struct Templates {
+    user_dir: PathBuf,
+    bundled_dir: Option<PathBuf>,
+}
+
+fn unknown_template(id: &str) -> Error {
+    Error::NotFound(format!("template {id}"))
+}
+
+/// Ids double as file stems, so keep them to safe characters.
+fn validate_id(id: &str) -> Result<()> {
+    if !id.is_empty() && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_') {
+        Ok(())
+    } else {
+        Err(unknown_template(id))
+    }
+}
+
+/// `Weekly Planning!` becomes `weekly-planning`.
+fn slugify(name: &str) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() {
+        "template".to_string()
+    } else {
+        slug
+    }
+}
+
+/// `<dir>/<name>.boardkit`, numbered like the vault does when the name is
+/// taken.
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let safe: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ' '))
+        .collect();
+    let safe = match safe.trim() {
+        "" => "Untitled",
+        safe => safe,
+    };
+    let mut path = dir.join(format!("{safe}.{TEMPLATE_EXTENSION}"));
+    let mut counter = 1;
+    while path.exists() {
+        path = dir.join(format!("{safe} {counter}.{TEMPLATE_EXTENSION}"));
+        counter += 1;
+    }
+    path
+}
+
+impl Templates {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let user_dir = app.path().app_data_dir()?.join(TEMPLATES_DIR);
+        fs::create_dir_all(&user_dir)?;
+        let bundled_dir = app.path().resource_dir().ok().map(|dir| dir.join(TEMPLATES_DIR));
+        Ok(Self { user_dir, bundled_dir })
+    }
+
+    fn template_path(&self, id: &str) -> Result<(PathBuf, bool)> {
+        validate_id(id)?;
+        let file = format!("{id}.{TEMPLATE_EXTENSION}");
+        let user = self.user_dir.join(&file);
+        if user.is_file() {
+            return Ok((user, false));
+        }
+        self.bundled_dir
+            .as_ref()
+            .map(|dir| dir.join(&file))
+            .filter(|path| path.is_file())
+            .map(|path| (path, true))
+            .ok_or_else(|| unknown_template(id))
+    }
+
+    fn info(&self, path: &Path, builtin: bool, thumbnails: &Thumbnails) -> Option<TemplateInfo> {
+        let id = path.file_stem()?.to_str()?.to_string();
+        validate_id(&id).ok()?;
+        let meta: TemplateMeta = persist::read_json(&path.with_extension("json"));
+        let path = path.to_string_lossy().into_owned();
+        let name = match meta.name {
+            Some(name) => name,
+            None => {
+                let document = board::read_board_document(&path).ok()?;
+                document.pointer("/meta/title").and_then(Value::as_str).unwrap_or(&id).to_string()
+            }
+        };
+        let thumbnail = thumbnails.get(&path).unwrap_or_else(|err| {
+            tracing::warn!("failed to render template {id}: {err}");
+            None
+        });
+        Some(TemplateInfo {
+            id,
+            name,
+            description: meta.description,
+            category: meta.category,
+            builtin,
+            path,
+            thumbnail: thumbnail.map(|thumbnail| thumbnail.to_string_lossy().into_owned()),
+        })
+    }
+
+    fn list(&self, thumbnails: &Thumbnails) -> Vec<TemplateInfo> {
+        let mut templates: Vec<TemplateInfo> = Vec::new();
+        let dirs = [(Some(&self.user_dir), false), (self.bundled_dir.as_ref(), true)];
+        for (dir, builtin) in dirs {
+            let Some(Ok(entries)) = dir.map(fs::read_dir) else {
+                continue;
+            };
+            let mut found: Vec<TemplateInfo> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == TEMPLATE_EXTENSION))
+                .filter_map(|path| self.info(&path, builtin, thumbnails))
+                .filter(|info| !templates.iter().any(|existing| existing.id == info.id))
+                .collect();
+            found.sort_by_cached_key(|info| info.name.to_lowercase());
+            templates.extend(found);
+        }
+        templates
+    }
+}
+
+/// Bundled templates followed by the user's, each sorted by name.
+#[tauri::command]
+pub async fn list_templates(app: AppHandle) -> Result<Vec<TemplateInfo>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut templates = app.state::<Templates>().list(&app.state::<Thumbnails>());
+        templates.sort_by_key(|info| !info.builtin);
+        Ok(templates)
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// Start a new board in `dir` as a copy of a template, assets included, and
+/// return its path. The board is named `title`, or after the template.
+#[tauri::command]
+pub fn create_board_from_template(
+    templates: State<'_, Templates>,
+    id: String,
+    dir: String,
+    title: Option<String>,
+) -> Result<String> {
+    let (template, _) = templates.template_path(&id)?;
+    let bytes = board::read_board(&template.to_string_lossy())?;
+    let now = persist::now_millis();
+    let mut name = String::new();
+    let (bytes, _) = board::copy_container(&bytes, |document| {
+        let meta = &mut document["meta"];
+        if let Some(title) = title.map(|title| title.trim().to_string()).filter(|title| !title.is_empty()) {
+            meta["title"] = Value::from(title);
+        }
+        name = meta["title"].as_str().unwrap_or("Untitled Board").to_string();
+        meta["createdAt"] = Value::from(now);
+        meta["updatedAt"] = Value::from(now);
+    })?;
+    let path = unique_path(Path::new(&dir), &name);
+    let path = path.to_string_lossy().into_owned();
+    board::write_board(&path, &bytes)?;
+    tracing::info!(path, template = id, "board created from template");
+    Ok(path)
+}
+
+/// Save a copy of the board at `path` as a user template.
+#[tauri::command]
+pub async fn save_as_template(
+    app: AppHandle,
+    path: String,
+    name: String,
+    description: Option<String>,
+) -> Result<TemplateInfo> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let templates = app.state::<Templates>();
+        let bytes = board::read_board(&path)?;
+        if encryption::is_encrypted(&bytes) {
+            return Err(Error::PasswordRequired(path));
+        }
+        let name = name.trim().to_string();
+        let (bytes, _) = board::copy_container(&bytes, |document| {
+            if !name.is_empty() {
+                document["meta"]["title"] = Value::from(name.as_str());
+            }
+        })?;
+
+        let slug = slugify(&name);
+        let mut id = slug.clone();
+        let mut counter = 1;
+        while templates.user_dir.join(format!("{id}.{TEMPLATE_EXTENSION}")).exists() {
+            counter += 1;
+            id = format!("{slug}-{counter}");
+        }
+        let template = templates.user_dir.join(format!("{id}.{TEMPLATE_EXTENSION}"));
+        persist::write_atomic(&template, &bytes)?;
+        let meta = TemplateMeta {
+            name: Some(name).filter(|name| !name.is_empty()),
+            description: description.filter(|description| !description.trim().is_empty()),
+            category: None,
+        };
+        persist::write_json(&template.with_extension("json"), &meta)?;
+        tracing::info!(id, "template saved");
+        templates
+            .info(&template, false, &app.state::<Thumbnails>())
+            .ok_or_else(|| unknown_template(&id))
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// Delete a user template. Bundled templates can't be removed.
+#[tauri::command]
+pub fn delete_template(templates: State<'_, Templates>, id: String) -> Result<()> {
+    let (path, builtin) = templates.template_path(&id)?;
+    if builtin {
+        return Err(Error::PermissionDenied(format!("template {id} ships with the app")));
+    }
+    fs::remove_file(&path)?;
+    let _ = fs::remove_file(path.with_extension("json"));
+    Ok(())
+}
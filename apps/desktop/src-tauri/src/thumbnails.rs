@@ -0,0 +1,134 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    board,
+    document::BoardDocument,
+    error::{Error, Result},
+    export::{
+        parse_svg,
+        png::rasterize,
+        svg::{render_svg, Resources},
+    },
+    file_icons, persist,
+};
+
+const THUMBNAILS_DIR: &str = "thumbnails";
+/// Longest side of a thumbnail in pixels.
+const THUMBNAIL_SIZE: f32 = 320.0;
+/// Small boards are upscaled at most this much so previews stay crisp.
+const MAX_SCALE: f32 = 2.0;
+
+/// Cache of PNG previews in app data, one file per board path.
+pub struct Thumbnails {
+    dir: PathBuf,
+}
+
+fn modified(path: &Path) -> Option<std::time::SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Render a preview of `document`, or `None` when the board is empty.
+fn render_thumbnail(board_path: &str, document: &BoardDocument) -> Result<Option<Vec<u8>>> {
+    let resources = Resources::load(Some(board_path), false)?;
+    let Some(svg) = render_svg(document, &resources) else {
+        return Ok(None);
+    };
+    let tree = parse_svg(&svg)?;
+    let size = tree.size();
+    let scale = (THUMBNAIL_SIZE / size.width().max(size.height())).min(MAX_SCALE);
+    rasterize(&tree, scale).map(Some)
+}
+
+impl Thumbnails {
+    pub fn open(app: &AppHandle) -> Result<Self> {
+        let dir = app.path().app_data_dir()?.join(THUMBNAILS_DIR);
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn thumbnail_path(&self, board_path: &str) -> PathBuf {
+        let key = format!("{:x}", Sha256::digest(board_path.as_bytes()));
+        self.dir.join(format!("{}.png", &key[..32]))
+    }
+
+    /// The cached thumbnail for `board_path`, if it is at least as new as the board.
+    pub fn cached(&self, board_path: &str) -> Option<PathBuf> {
+        let path = self.thumbnail_path(board_path);
+        let thumbnail = modified(&path)?;
+        match modified(Path::new(board_path)) {
+            Some(board) if board > thumbnail => None,
+            _ => Some(path),
+        }
+    }
+
+    /// The thumbnail for `board_path`, rendered first when the cache is stale.
+    pub fn get(&self, board_path: &str) -> Result<Option<PathBuf>> {
+        match self.cached(board_path) {
+            Some(thumbnail) => Ok(Some(thumbnail)),
+            None => {
+                let document = serde_json::from_value(board::read_board_document(board_path)?)?;
+                self.generate(board_path, &document)
+            }
+        }
+    }
+
+    /// Render and cache a thumbnail, which also becomes the board file's
+    /// icon. Empty boards drop any stale preview.
+    pub fn generate(&self, board_path: &str, document: &BoardDocument) -> Result<Option<PathBuf>> {
+        let path = self.thumbnail_path(board_path);
+        match render_thumbnail(board_path, document)? {
+            Some(png) => {
+                persist::write_atomic(&path, &png)?;
+                file_icons::attach(board_path, Some(&path));
+                Ok(Some(path))
+            }
+            None => {
+                self.remove(board_path)?;
+                file_icons::attach(board_path, None);
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn remove(&self, board_path: &str) -> Result<()> {
+        match fs::remove_file(self.thumbnail_path(board_path)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Regenerate the thumbnail for a saved board off the command thread.
+pub fn generate_in_background(app: &AppHandle, path: String, document: Value) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(thumbnails) = app.try_state::<Thumbnails>() else {
+            return;
+        };
+        let result = serde_json::from_value::<BoardDocument>(document)
+            .map_err(Error::from)
+            .and_then(|document| thumbnails.generate(&path, &document));
+        if let Err(err) = result {
+            tracing::warn!("failed to render {path}: {err}");
+        }
+    });
+}
+
+/// Path to a PNG preview of the board at `path`, rendering it first when the
+/// cache is missing or older than the board. `None` for empty boards.
+#[tauri::command]
+pub async fn get_board_thumbnail(app: AppHandle, path: String) -> Result<Option<String>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let thumbnail = app.state::<Thumbnails>().get(&path)?;
+        Ok(thumbnail.map(|thumbnail| thumbnail.to_string_lossy().into_owned()))
+    })
+    .await
+    .map_err(|err| Error::Render(err.to_string()))?
+}
@@ -0,0 +1,245 @@
+//! Editing timeline. Every save appends the operations since the previous
+//! one to `<board history dir>/timeline/`, stamped with the time of the edit,
+//! so any moment of a board's past can be rebuilt by replaying them over the
+//! document the timeline starts from. Unlike the saved versions, nothing in
+//! between is lost to deduplication or the version limit.
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    error::{Error, Result},
+    history,
+    journal::{self, Op},
+    migrations, persist,
+};
+
+const TIMELINE_DIR: &str = "timeline";
+const BASE_NAME: &str = "base.json";
+const HEAD_NAME: &str = "head.json";
+const OPS_NAME: &str = "ops.jsonl";
+/// Past this size, the older half of the operations is folded into the base.
+const MAX_OPS_SIZE: u64 = 32 * 1024 * 1024;
+
+/// Saves of different boards finish on different threads; one at a time
+/// keeps each timeline's files consistent with each other.
+static RECORDING: Mutex<()> = Mutex::new(());
+
+/// The board at one point of its timeline.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    at: u64,
+    document: Value,
+    /// Length of the operation log this is the result of, for the head
+    #[serde(default)]
+    ops_len: u64,
+}
+
+/// The operations of one save.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    at: u64,
+    ops: Vec<Op>,
+}
+
+/// One point on the scrubber: a save, or where the timeline starts.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelinePoint {
+    pub at: u64,
+    /// Operations the save made; 0 for the start
+    pub changes: usize,
+    pub element_count: usize,
+    pub widget_count: usize,
+}
+
+fn timeline_dir(path: &str) -> PathBuf {
+    history::history_dir(Path::new(path)).join(TIMELINE_DIR)
+}
+
+fn read_snapshot(path: &Path) -> Option<Snapshot> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+fn write_snapshot(path: &Path, snapshot: &Snapshot) -> Result<()> {
+    persist::write_atomic(path, &serde_json::to_vec(snapshot)?)?;
+    Ok(())
+}
+
+/// The operation log, oldest first, and the length of its readable part: a
+/// line cut short by a crash ends it.
+fn read_entries(dir: &Path) -> Result<(Vec<Entry>, u64)> {
+    let Ok(file) = fs::File::open(dir.join(OPS_NAME)) else {
+        return Ok((Vec::new(), 0));
+    };
+    let (mut entries, mut len) = (Vec::new(), 0);
+    for line in BufReader::new(file).split(b'\n') {
+        let line = line?;
+        match serde_json::from_slice(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(_) => break,
+        }
+        len += line.len() as u64 + 1;
+    }
+    Ok((entries, len))
+}
+
+fn count(document: &Value, pointer: &str) -> usize {
+    document.pointer(pointer).and_then(Value::as_array).map_or(0, Vec::len)
+}
+
+/// Replay `entries` over the base up to `until`, handing each point on the
+/// way to `visit`. Returns the time of the last point and the board then.
+fn replay(
+    base: Snapshot,
+    entries: Vec<Entry>,
+    until: u64,
+    mut visit: impl FnMut(u64, usize, &Value),
+) -> Result<(u64, Value)> {
+    let (mut at, mut document) = (base.at, base.document);
+    visit(at, 0, &document);
+    for entry in entries.into_iter().take_while(|entry| entry.at <= until) {
+        at = entry.at;
+        let changes = entry.ops.len();
+        entry.ops.into_iter().try_for_each(|op| journal::apply_op(&mut document, op))?;
+        visit(at, changes, &document);
+    }
+    Ok((at, document))
+}
+
+fn start(dir: &Path, at: u64, document: &Value) -> Result<()> {
+    let snapshot = Snapshot {
+        at,
+        document: document.clone(),
+        ops_len: 0,
+    };
+    let _ = fs::remove_file(dir.join(OPS_NAME));
+    write_snapshot(&dir.join(BASE_NAME), &snapshot)?;
+    write_snapshot(&dir.join(HEAD_NAME), &snapshot)
+}
+
+/// Fold the older half of the operations into the base.
+fn compact(dir: &Path, head: &mut Snapshot) -> Result<()> {
+    let base = read_snapshot(&dir.join(BASE_NAME)).ok_or_else(|| Error::NotFound(BASE_NAME.to_string()))?;
+    let (mut entries, _) = read_entries(dir)?;
+    let kept = entries.split_off(entries.len() / 2);
+    let (at, document) = replay(base, entries, u64::MAX, |_, _, _| {})?;
+    let mut lines = Vec::new();
+    for entry in &kept {
+        serde_json::to_writer(&mut lines, entry)?;
+        lines.push(b'\n');
+    }
+    persist::write_atomic(&dir.join(OPS_NAME), &lines)?;
+    write_snapshot(&dir.join(BASE_NAME), &Snapshot { at, document, ops_len: 0 })?;
+    head.ops_len = lines.len() as u64;
+    write_snapshot(&dir.join(HEAD_NAME), head)
+}
+
+/// Append the changes from the last recorded state of the board at `path`
+/// to `document`, just saved.
+pub fn record(path: &str, document: &Value) -> Result<()> {
+    let _recording = RECORDING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let dir = timeline_dir(path);
+    fs::create_dir_all(&dir)?;
+    let edited_at = document.pointer("/meta/updatedAt").and_then(Value::as_u64).unwrap_or_else(persist::now_millis);
+
+    let ops_path = dir.join(OPS_NAME);
+    let file_len = fs::metadata(&ops_path).map_or(0, |metadata| metadata.len());
+    let head = match read_snapshot(&dir.join(HEAD_NAME)) {
+        Some(head) if head.ops_len == file_len => Some(head),
+        // Interrupted between appending and updating the head: rebuild it
+        _ => read_snapshot(&dir.join(BASE_NAME)).and_then(|base| {
+            let (entries, ops_len) = read_entries(&dir).ok()?;
+            let (at, document) = replay(base, entries, u64::MAX, |_, _, _| {}).ok()?;
+            Some(Snapshot { at, document, ops_len })
+        }),
+    };
+    let Some(mut head) = head else {
+        return start(&dir, edited_at, document);
+    };
+
+    let ops = journal::diff(&head.document, document);
+    if ops.is_empty() {
+        return Ok(());
+    }
+    let entry = Entry {
+        at: edited_at.max(head.at + 1),
+        ops,
+    };
+    let mut line = serde_json::to_vec(&entry)?;
+    line.push(b'\n');
+    // A torn line from an earlier crash would hide everything after it
+    let mut file = fs::OpenOptions::new().create(true).write(true).truncate(false).open(&ops_path)?;
+    file.set_len(head.ops_len)?;
+    file.seek(SeekFrom::End(0))?;
+    file.write_all(&line)?;
+    file.sync_all()?;
+
+    head = Snapshot {
+        at: entry.at,
+        document: document.clone(),
+        ops_len: head.ops_len + line.len() as u64,
+    };
+    if head.ops_len > MAX_OPS_SIZE {
+        compact(&dir, &mut head)
+    } else {
+        write_snapshot(&dir.join(HEAD_NAME), &head)
+    }
+}
+
+/// Record a just-saved board on the timeline off the command thread.
+pub fn record_in_background(path: String, document: Value) {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(err) = record(&path, &document) {
+            tracing::warn!("failed to record {path} on its timeline: {err}");
+        }
+    });
+}
+
+/// Every point of the board's editing timeline, oldest first, for a slider
+/// to scrub through. Empty until the board is next saved.
+#[tauri::command]
+pub async fn get_timeline(board: String) -> Result<Vec<TimelinePoint>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let dir = timeline_dir(&board);
+        let Some(base) = read_snapshot(&dir.join(BASE_NAME)) else {
+            return Ok(Vec::new());
+        };
+        let mut points = Vec::new();
+        replay(base, read_entries(&dir)?.0, u64::MAX, |at, changes, document| {
+            points.push(TimelinePoint {
+                at,
+                changes,
+                element_count: count(document, "/board/elements"),
+                widget_count: count(document, "/board/widgets"),
+            });
+        })?;
+        Ok(points)
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// The board as it was at `timestamp`, after the last save up to then and
+/// migrated to the current schema. Times before the timeline starts get its
+/// first point.
+#[tauri::command]
+pub async fn reconstruct_at(board: String, timestamp: u64) -> Result<Value> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let dir = timeline_dir(&board);
+        let base = read_snapshot(&dir.join(BASE_NAME));
+        let base = base.ok_or_else(|| Error::NotFound(format!("timeline of {board}")))?;
+        let (_, mut document) = replay(base, read_entries(&dir)?.0, timestamp, |_, _, _| {})?;
+        migrations::migrate(&mut document)?;
+        Ok(document)
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
@@ -0,0 +1,182 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    error::{Error, Result},
+    history, persist,
+    settings::Settings,
+};
+
+const TRASH_DIR: &str = "trash";
+const INDEX_NAME: &str = "index.json";
+const DAY_MILLIS: u64 = 24 * 60 * 60 * 1000;
+
+/// A deleted board waiting in the trash.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedBoard {
+    pub id: String,
+    pub title: String,
+    /// Where the board lived, so it can be put back.
+    pub original_path: String,
+    pub deleted_at: u64,
+    pub size: u64,
+    /// When the retention policy purges it; only filled in for listing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+/// Deleted boards moved into the app data dir, with their version history,
+/// until they are restored or their retention period runs out.
+pub struct Trash {
+    dir: PathBuf,
+    entries: Mutex<Vec<TrashedBoard>>,
+}
+
+/// Rename, falling back to copying when the trash is on another volume.
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
+
+impl Trash {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let dir = app.path().app_data_dir()?.join(TRASH_DIR);
+        fs::create_dir_all(&dir)?;
+        let entries = persist::read_json(&dir.join(INDEX_NAME));
+        let trash = Self {
+            dir,
+            entries: Mutex::new(entries),
+        };
+        let purged = trash.purge_expired(app.state::<Settings>().get().trash_retention_days)?;
+        if purged > 0 {
+            tracing::info!(purged, "expired boards purged from the trash");
+        }
+        Ok(trash)
+    }
+
+    fn file(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.boardkit", persist::sanitize_file_stem(id)))
+    }
+
+    fn save(&self, entries: &[TrashedBoard]) -> Result<()> {
+        persist::write_json(&self.dir.join(INDEX_NAME), &entries)
+    }
+
+    /// Move the board at `path` into the trash.
+    pub fn put(&self, path: &Path, title: String) -> Result<TrashedBoard> {
+        let mut entries = self.entries.lock().unwrap();
+        let deleted_at = persist::now_millis();
+        let mut id = deleted_at.to_string();
+        let mut counter = 1;
+        while entries.iter().any(|entry| entry.id == id) {
+            counter += 1;
+            id = format!("{deleted_at}-{counter}");
+        }
+        let file = self.file(&id);
+        let size = fs::metadata(path)?.len();
+        move_file(path, &file)?;
+        if let Err(err) = history::rename(path, &file) {
+            tracing::warn!("history of {} stays behind: {err}", path.display());
+        }
+        let entry = TrashedBoard {
+            id,
+            title,
+            original_path: path.to_string_lossy().into_owned(),
+            deleted_at,
+            size,
+            expires_at: None,
+        };
+        entries.push(entry.clone());
+        self.save(&entries)?;
+        Ok(entry)
+    }
+
+    /// Take a board out of the trash and move it to `to`.
+    pub fn restore(&self, id: &str, to: &Path) -> Result<TrashedBoard> {
+        let mut entries = self.entries.lock().unwrap();
+        let index = entries
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or_else(|| Error::NotFound(format!("trashed board {id}")))?;
+        let file = self.file(id);
+        move_file(&file, to)?;
+        if let Err(err) = history::rename(&file, to) {
+            tracing::warn!("history of {} stays in the trash: {err}", to.display());
+        }
+        let entry = entries.remove(index);
+        self.save(&entries)?;
+        Ok(entry)
+    }
+
+    pub fn get(&self, id: &str) -> Option<TrashedBoard> {
+        self.entries.lock().unwrap().iter().find(|entry| entry.id == id).cloned()
+    }
+
+    fn list(&self, retention_days: u32) -> Vec<TrashedBoard> {
+        let retention = u64::from(retention_days) * DAY_MILLIS;
+        let mut entries: Vec<TrashedBoard> = self.entries.lock().unwrap().clone();
+        for entry in &mut entries {
+            entry.expires_at = Some(entry.deleted_at + retention);
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+        entries
+    }
+
+    /// Permanently delete the boards matching `expired`.
+    fn purge(&self, expired: impl Fn(&TrashedBoard) -> bool) -> Result<usize> {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|entry| {
+            if !expired(entry) {
+                return true;
+            }
+            let file = self.file(&entry.id);
+            match fs::remove_file(&file) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    tracing::warn!("failed to purge {}: {err}", file.display());
+                    return true;
+                }
+                _ => {}
+            }
+            let _ = history::remove(&file);
+            false
+        });
+        let purged = before - entries.len();
+        if purged > 0 {
+            self.save(&entries)?;
+        }
+        Ok(purged)
+    }
+
+    pub fn purge_expired(&self, retention_days: u32) -> Result<usize> {
+        let cutoff = persist::now_millis().saturating_sub(u64::from(retention_days) * DAY_MILLIS);
+        self.purge(|entry| entry.deleted_at < cutoff)
+    }
+}
+
+/// Boards in the trash, most recently deleted first. Expired ones are
+/// purged first.
+#[tauri::command]
+pub fn list_trashed_boards(trash: State<'_, Trash>, settings: State<'_, Settings>) -> Result<Vec<TrashedBoard>> {
+    let retention_days = settings.get().trash_retention_days;
+    trash.purge_expired(retention_days)?;
+    Ok(trash.list(retention_days))
+}
+
+/// Permanently delete every board in the trash, or only those in `ids`.
+#[tauri::command]
+pub fn empty_trash(trash: State<'_, Trash>, ids: Option<Vec<String>>) -> Result<usize> {
+    let purged = trash.purge(|entry| ids.as_ref().is_none_or(|ids| ids.contains(&entry.id)))?;
+    tracing::info!(purged, "trash emptied");
+    Ok(purged)
+}
@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{
+    menu::{Menu, MenuItem, PredefinedMenuItem},
+    tray::TrayIconBuilder,
+    AppHandle,
+};
+
+use crate::{
+    error::Result,
+    i18n::{self, tr},
+    menu, persist, windows,
+};
+
+const TRAY_ID: &str = "main";
+const TRAY_STORE: &str = "tray.json";
+
+#[derive(Serialize, Deserialize)]
+struct TrayPreferences {
+    visible: bool,
+}
+
+impl Default for TrayPreferences {
+    fn default() -> Self {
+        Self { visible: true }
+    }
+}
+
+fn store_path(app: &AppHandle) -> Result<PathBuf> {
+    persist::app_data_file(app, TRAY_STORE)
+}
+
+fn create_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    Menu::with_items(
+        app,
+        &[
+            &MenuItem::with_id(app, "tray_new_board", tr(app, "New Board"), true, None::<&str>)?,
+            &MenuItem::with_id(app, "tray_command_palette", tr(app, "Open Command Palette"), true, None::<&str>)?,
+            &MenuItem::with_id(app, "tray_toggle_window", tr(app, "Show/Hide Window"), true, None::<&str>)?,
+            &PredefinedMenuItem::separator(app)?,
+            &menu::create_recent_menu(app, &tr(app, "Recent Boards"))?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::quit(app, i18n::translated(app, "Quit Boardkit").as_deref())?,
+        ],
+    )
+}
+
+/// Create the tray icon unless the user disabled it.
+pub fn init(app: &AppHandle) -> Result<()> {
+    let preferences: TrayPreferences = persist::read_json(&store_path(app)?);
+    if !preferences.visible {
+        return Ok(());
+    }
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Boardkit")
+        .menu(&create_tray_menu(app)?);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+/// Rebuild the tray menu so the recent boards list stays current.
+pub fn refresh(app: &AppHandle) -> tauri::Result<()> {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_menu(Some(create_tray_menu(app)?))?;
+    }
+    Ok(())
+}
+
+/// Hide or show the board window in front, the main one unless it was
+/// closed, reopening it if every board window was.
+pub fn toggle_main_window(app: &AppHandle) {
+    if let Some(window) = windows::target_or_reopen(app) {
+        if window.is_visible().unwrap_or(false) {
+            let _ = window.hide();
+        } else {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }
+}
+
+#[tauri::command]
+pub fn set_tray_visibility(app: AppHandle, visible: bool) -> Result<()> {
+    persist::write_json(&store_path(&app)?, &TrayPreferences { visible })?;
+
+    match app.tray_by_id(TRAY_ID) {
+        Some(tray) => tray.set_visible(visible)?,
+        None if visible => init(&app)?,
+        None => {}
+    }
+    Ok(())
+}
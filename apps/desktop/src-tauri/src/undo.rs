@@ -0,0 +1,214 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{Cursor, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    board::MAX_FILE_SIZE,
+    encryption,
+    error::{Error, Result},
+    history, migrations, persist,
+};
+
+const UNDO_LOG_NAME: &str = "undo.zip";
+const INDEX_NAME: &str = "index.json";
+const STEPS_FOLDER: &str = "steps/";
+/// Matches `maxSize` of the board store's history.
+const MAX_STEPS: usize = 50;
+/// Snapshot bytes, before compression, past which older steps are dropped.
+const MAX_LOG_SIZE: u64 = 64 * 1024 * 1024;
+
+/// One undoable step, as the board store's history labels it.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryStep {
+    pub id: String,
+    pub label: String,
+    pub timestamp: u64,
+}
+
+/// A step to persist, with the board as it was before it. `snapshot` is left
+/// out for steps the log already has.
+#[derive(Deserialize)]
+pub struct PersistedStep {
+    #[serde(flatten)]
+    step: HistoryStep,
+    #[serde(default)]
+    snapshot: Option<Value>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UndoIndex {
+    /// `meta.updatedAt` of the save the log belongs to
+    board_updated_at: u64,
+    /// Most recent first, like the board store's stack
+    steps: Vec<HistoryStep>,
+}
+
+/// The undo log of the board at `path`, a ZIP in its version history folder
+/// holding one compressed snapshot per step.
+fn log_path(path: &str) -> PathBuf {
+    history::history_dir(Path::new(path)).join(UNDO_LOG_NAME)
+}
+
+fn step_entry(id: &str) -> String {
+    format!("{STEPS_FOLDER}{}.json", persist::sanitize_file_stem(id))
+}
+
+fn open_log(path: &str) -> Option<ZipArchive<Cursor<Vec<u8>>>> {
+    ZipArchive::new(Cursor::new(fs::read(log_path(path)).ok()?)).ok()
+}
+
+fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Result<Vec<u8>> {
+    let entry = archive.by_name(name).map_err(|_| Error::NotFound(name.to_string()))?;
+    let mut data = Vec::new();
+    entry.take(MAX_FILE_SIZE + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > MAX_FILE_SIZE {
+        return Err(Error::TooLarge {
+            size: data.len() as u64,
+            max: MAX_FILE_SIZE,
+        });
+    }
+    Ok(data)
+}
+
+fn read_index(archive: &mut ZipArchive<Cursor<Vec<u8>>>) -> UndoIndex {
+    read_entry(archive, INDEX_NAME)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+/// Write the undo history of the board at `path`, just saved with
+/// `updated_at`, so the next session can undo past the reopen. Returns the
+/// ids of the steps kept: at most `MAX_STEPS`, newest first, fewer once the
+/// log grows past `MAX_LOG_SIZE`. Encrypted boards get no log, since it would
+/// hold their content in the clear.
+#[tauri::command]
+pub async fn persist_history(path: String, updated_at: u64, steps: Vec<PersistedStep>) -> Result<Vec<String>> {
+    // Compressing up to MAX_LOG_SIZE of snapshots takes a while
+    tauri::async_runtime::spawn_blocking(move || write_log(&path, updated_at, steps))
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+fn write_log(path: &str, updated_at: u64, steps: Vec<PersistedStep>) -> Result<Vec<String>> {
+    let log = log_path(path);
+    if steps.is_empty() || encryption::is_encrypted_file(Path::new(path)) {
+        if log.exists() {
+            fs::remove_file(&log)?;
+        }
+        return Ok(Vec::new());
+    }
+
+    let mut previous = open_log(path);
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut index = UndoIndex {
+        board_updated_at: updated_at,
+        steps: Vec::new(),
+    };
+    let mut written = BTreeSet::new();
+    let mut size = 0;
+    for PersistedStep { step, snapshot } in steps.into_iter().take(MAX_STEPS) {
+        let name = step_entry(&step.id);
+        if size > MAX_LOG_SIZE || !written.insert(name.clone()) {
+            break;
+        }
+        match (snapshot, previous.as_mut()) {
+            (Some(snapshot), _) => {
+                let data = serde_json::to_vec(&snapshot)?;
+                size += data.len() as u64;
+                writer.start_file(name.as_str(), options).map_err(std::io::Error::from)?;
+                writer.write_all(&data)?;
+            }
+            (None, Some(archive)) => match archive.by_name(&name) {
+                Ok(entry) => {
+                    size += entry.size();
+                    writer.raw_copy_file(entry).map_err(std::io::Error::from)?;
+                }
+                // Neither sent nor kept: the steps before it can't be undone either
+                Err(_) => break,
+            },
+            (None, None) => break,
+        }
+        index.steps.push(step);
+    }
+    writer.start_file(INDEX_NAME, options).map_err(std::io::Error::from)?;
+    writer.write_all(&serde_json::to_vec(&index)?)?;
+    let bytes = writer.finish().map_err(std::io::Error::from)?.into_inner();
+
+    fs::create_dir_all(log.parent().unwrap_or_else(|| Path::new("")))?;
+    persist::write_atomic(&log, &bytes).map_err(|err| Error::from_io(err, &log.to_string_lossy()))?;
+    tracing::debug!(path, steps = index.steps.len(), size = bytes.len(), "undo history persisted");
+    Ok(index.steps.into_iter().map(|step| step.id).collect())
+}
+
+/// A persisted step with the board as it was before it, migrated to the
+/// current schema, for the board store to restore when the step is undone.
+#[derive(Serialize)]
+pub struct RestoredStep {
+    #[serde(flatten)]
+    step: HistoryStep,
+    snapshot: Value,
+}
+
+fn read_snapshot(archive: &mut ZipArchive<Cursor<Vec<u8>>>, id: &str) -> Result<Value> {
+    let mut snapshot = serde_json::from_slice(&read_entry(archive, &step_entry(id))?)?;
+    migrations::migrate(&mut snapshot)?;
+    Ok(snapshot)
+}
+
+fn read_log(path: &str, updated_at: u64) -> Vec<RestoredStep> {
+    let Some(mut archive) = open_log(path) else {
+        return Vec::new();
+    };
+    let index = read_index(&mut archive);
+    if index.board_updated_at != updated_at {
+        return Vec::new();
+    }
+    let mut restored = Vec::new();
+    for step in index.steps {
+        match read_snapshot(&mut archive, &step.id) {
+            Ok(snapshot) => restored.push(RestoredStep { step, snapshot }),
+            // The steps before an unreadable one can't be undone either
+            Err(err) => {
+                tracing::warn!(path, "undo history step {} unreadable: {err}", step.id);
+                break;
+            }
+        }
+    }
+    restored
+}
+
+/// Steps a previous session left for the board at `path`, most recent
+/// first, with their snapshots, read from the log in one go. Empty unless the
+/// board is still as that session saved it (`updated_at` of the loaded
+/// document): a board changed since, by another app or a checkout, starts
+/// with an empty history instead.
+#[tauri::command]
+pub async fn get_persisted_history(path: String, updated_at: u64) -> Result<Vec<RestoredStep>> {
+    tauri::async_runtime::spawn_blocking(move || read_log(&path, updated_at))
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))
+}
+
+/// The board as it was before persisted step `id`, migrated to the current
+/// schema.
+#[tauri::command]
+pub async fn apply_history_step(path: String, id: String) -> Result<Value> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let log = open_log(&path);
+        let mut archive = log.ok_or_else(|| Error::NotFound(log_path(&path).to_string_lossy().into_owned()))?;
+        read_snapshot(&mut archive, &id)
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
@@ -0,0 +1,184 @@
+use std::{path::PathBuf, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State, Url, WebviewWindow};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::{error::Result, persist};
+
+const UPDATER_STORE: &str = "updater.json";
+const STABLE_ENDPOINT: &str = "https://github.com/kevinbarfleur/boardkit/releases/latest/download/latest.json";
+const BETA_ENDPOINT: &str = "https://github.com/kevinbarfleur/boardkit/releases/download/beta/latest.json";
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl UpdateChannel {
+    fn endpoint(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => STABLE_ENDPOINT,
+            UpdateChannel::Beta => BETA_ENDPOINT,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct UpdaterSettings {
+    channel: UpdateChannel,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    version: String,
+    current_version: String,
+    date: Option<String>,
+    notes: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct DownloadProgress {
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+/// Update channel preference plus the update found by the last check and its
+/// verified package once downloaded.
+pub struct Updater {
+    store_path: PathBuf,
+    /// Release builds set the signing key in `plugins.updater.pubkey`; without
+    /// it nothing downloaded could be verified, so the update commands fail
+    /// with [`Error::UpdaterNotConfigured`](crate::error::Error::UpdaterNotConfigured).
+    configured: bool,
+    channel: Mutex<UpdateChannel>,
+    pending: Mutex<Option<Update>>,
+    downloaded: Mutex<Option<Vec<u8>>>,
+}
+
+impl Updater {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let store_path = persist::app_data_file(app, UPDATER_STORE)?;
+        let settings: UpdaterSettings = persist::read_json(&store_path);
+        let configured = app
+            .config()
+            .plugins
+            .0
+            .get("updater")
+            .and_then(|config| config.get("pubkey"))
+            .and_then(|pubkey| pubkey.as_str())
+            .is_some_and(|pubkey| !pubkey.trim().is_empty());
+        Ok(Self {
+            store_path,
+            configured,
+            channel: Mutex::new(settings.channel),
+            pending: Mutex::new(None),
+            downloaded: Mutex::new(None),
+        })
+    }
+
+    fn ensure_configured(&self) -> Result<()> {
+        if self.configured {
+            Ok(())
+        } else {
+            Err(crate::error::Error::UpdaterNotConfigured)
+        }
+    }
+
+    fn pending(&self) -> Result<Update> {
+        self.pending
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| updater_error("no update available; check for updates first"))
+    }
+}
+
+fn updater_error(err: impl ToString) -> crate::error::Error {
+    crate::error::Error::Updater(err.to_string())
+}
+
+#[tauri::command]
+pub fn get_update_channel(updater: State<'_, Updater>) -> UpdateChannel {
+    *updater.channel.lock().unwrap()
+}
+
+#[tauri::command]
+pub fn set_update_channel(updater: State<'_, Updater>, channel: UpdateChannel) -> Result<()> {
+    let mut current = updater.channel.lock().unwrap();
+    persist::write_json(&updater.store_path, &UpdaterSettings { channel })?;
+    if *current != channel {
+        // An update found on the other channel no longer applies
+        *updater.pending.lock().unwrap() = None;
+        *updater.downloaded.lock().unwrap() = None;
+    }
+    *current = channel;
+    Ok(())
+}
+
+/// Ask the selected channel's release feed for a newer version.
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle, updater: State<'_, Updater>) -> Result<Option<UpdateInfo>> {
+    updater.ensure_configured()?;
+    let channel = *updater.channel.lock().unwrap();
+    let endpoint = Url::parse(channel.endpoint()).map_err(updater_error)?;
+    let update = app
+        .updater_builder()
+        .endpoints(vec![endpoint])
+        .map_err(updater_error)?
+        .build()
+        .map_err(updater_error)?
+        .check()
+        .await
+        .map_err(updater_error)?;
+
+    let info = update.as_ref().map(|update| UpdateInfo {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        date: update.date.map(|date| date.to_string()),
+        notes: update.body.clone(),
+    });
+    *updater.pending.lock().unwrap() = update;
+    *updater.downloaded.lock().unwrap() = None;
+    Ok(info)
+}
+
+/// Download and verify the update found by the last check, reporting
+/// progress to the calling window as `update-download-progress`.
+#[tauri::command]
+pub async fn download_update(window: WebviewWindow, updater: State<'_, Updater>) -> Result<()> {
+    updater.ensure_configured()?;
+    let update = updater.pending()?;
+    let mut downloaded = 0u64;
+    let bytes = update
+        .download(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = window.emit_to(window.label(), "update-download-progress", DownloadProgress { downloaded, total });
+            },
+            || {},
+        )
+        .await
+        .map_err(updater_error)?;
+    *updater.downloaded.lock().unwrap() = Some(bytes);
+    Ok(())
+}
+
+/// Install the downloaded update and relaunch into the new version.
+#[tauri::command]
+pub fn install_update(app: AppHandle, updater: State<'_, Updater>) -> Result<()> {
+    updater.ensure_configured()?;
+    let update = updater.pending()?;
+    let bytes = updater
+        .downloaded
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| updater_error("the update has not been downloaded yet"))?;
+    update.install(bytes).map_err(updater_error)?;
+    app.restart();
+}
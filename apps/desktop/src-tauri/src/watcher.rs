@@ -0,0 +1,136 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::{
+    error::{Error, Result},
+    workspace::BOARD_EXTENSION,
+};
+
+/// Quiet period before reporting, so a save's temp-file rename reports once.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+/// Events this soon after one of our own writes are that write echoing back.
+const OWN_WRITE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Paths Boardkit itself just wrote, renamed or deleted.
+static OWN_WRITES: Mutex<Option<HashMap<PathBuf, Instant>>> = Mutex::new(None);
+
+/// Canonical form used to compare paths from notify with our own, since the
+/// watcher may report symlink-resolved paths (e.g. `/private/var` on macOS).
+fn normalize(path: &Path) -> PathBuf {
+    match (path.parent().and_then(|parent| parent.canonicalize().ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Record a change Boardkit is about to make so the watcher doesn't report it.
+pub fn expect_write(path: &Path) {
+    let now = Instant::now();
+    let mut own_writes = OWN_WRITES.lock().unwrap();
+    let own_writes = own_writes.get_or_insert_with(HashMap::new);
+    own_writes.retain(|_, at| now.duration_since(*at) < OWN_WRITE_WINDOW);
+    own_writes.insert(normalize(path), now);
+}
+
+fn is_own_write(path: &Path) -> bool {
+    OWN_WRITES
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|own_writes| own_writes.get(&normalize(path)))
+        .is_some_and(|at| at.elapsed() < OWN_WRITE_WINDOW)
+}
+
+/// Board files directly in the workspace (not history copies or temp files).
+fn is_board_file(dir: &Path, path: &Path) -> bool {
+    path.parent().is_some_and(|parent| normalize(parent) == normalize(dir))
+        && path.extension().is_some_and(|extension| extension == BOARD_EXTENSION)
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExternalChange {
+    path: String,
+}
+
+/// Watches the boards directory and reports changes made outside Boardkit
+/// (sync clients, other machines, editors) as `board-changed-externally` and
+/// `board-deleted-externally`.
+pub struct BoardWatcher {
+    app: AppHandle,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl BoardWatcher {
+    pub fn new(app: &AppHandle) -> Self {
+        Self {
+            app: app.clone(),
+            watcher: Mutex::new(None),
+        }
+    }
+
+    /// Start watching `dir`, replacing any previous directory.
+    pub fn watch(&self, dir: &Path) -> Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+        watcher.watch(dir, RecursiveMode::NonRecursive).map_err(watch_error)?;
+        // Dropping the previous watcher closes its channel, which ends its thread
+        *self.watcher.lock().unwrap() = Some(watcher);
+
+        let app = self.app.clone();
+        let dir = dir.to_path_buf();
+        thread::spawn(move || debounce(&app, &dir, receiver));
+        Ok(())
+    }
+}
+
+fn watch_error(err: notify::Error) -> Error {
+    Error::Io(std::io::Error::other(err))
+}
+
+fn debounce(app: &AppHandle, dir: &Path, receiver: mpsc::Receiver<notify::Result<notify::Event>>) {
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+    loop {
+        match receiver.recv_timeout(DEBOUNCE) {
+            Ok(Ok(event)) if !event.kind.is_access() => {
+                for path in event.paths {
+                    if is_board_file(dir, &path) && !is_own_write(&path) {
+                        pending.insert(path, Instant::now());
+                    }
+                }
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => tracing::warn!("watcher: {err}"),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+
+        let settled: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, at)| at.elapsed() >= DEBOUNCE)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in settled {
+            pending.remove(&path);
+            let event = if path.exists() {
+                "board-changed-externally"
+            } else {
+                "board-deleted-externally"
+            };
+            let payload = ExternalChange {
+                path: path.to_string_lossy().into_owned(),
+            };
+            tracing::info!(path = payload.path, event, "external board change");
+            let _ = app.emit(event, payload);
+        }
+    }
+}
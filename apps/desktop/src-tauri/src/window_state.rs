@@ -0,0 +1,239 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, WebviewWindow, Window, WindowEvent};
+
+use crate::{
+    error::Result,
+    persist, session,
+    windows::{self, BoardWindows},
+};
+
+const WINDOW_STATE_STORE: &str = "window-state.json";
+const MIN_ZOOM: f64 = 0.25;
+const MAX_ZOOM: f64 = 5.0;
+/// How much of a restored window must overlap a monitor to keep its position.
+const VISIBLE_MARGIN: i32 = 40;
+
+fn default_zoom() -> f64 {
+    1.0
+}
+
+/// Restorable window geometry. Position and size are the last non-maximized
+/// bounds, in physical pixels.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+    #[serde(default)]
+    pub fullscreen: bool,
+    #[serde(default = "default_zoom")]
+    pub zoom: f64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct WindowStateStore {
+    /// Geometry of the most recently closed window, used when a board has none
+    last: Option<WindowGeometry>,
+    boards: BTreeMap<String, WindowGeometry>,
+}
+
+#[derive(Default)]
+struct TrackedWindow {
+    board: Option<String>,
+    geometry: Option<WindowGeometry>,
+}
+
+/// Window geometry per board, tracked live and persisted when windows close.
+pub struct WindowState {
+    store_path: PathBuf,
+    store: Mutex<WindowStateStore>,
+    windows: Mutex<HashMap<String, TrackedWindow>>,
+}
+
+fn capture(window: &Window, previous: Option<WindowGeometry>) -> Option<WindowGeometry> {
+    if window.is_minimized().unwrap_or(false) {
+        return previous;
+    }
+    let maximized = window.is_maximized().unwrap_or(false);
+    let fullscreen = window.is_fullscreen().unwrap_or(false);
+    let mut geometry = match previous {
+        // Keep the normal bounds so un-maximizing after a restore lands somewhere sensible
+        Some(previous) if maximized || fullscreen => previous,
+        _ => {
+            let position = window.outer_position().ok()?;
+            let size = window.inner_size().ok()?;
+            WindowGeometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                maximized: false,
+                fullscreen: false,
+                zoom: previous.map_or(1.0, |previous| previous.zoom),
+            }
+        }
+    };
+    geometry.maximized = maximized;
+    geometry.fullscreen = fullscreen;
+    Some(geometry)
+}
+
+/// Whether the top-left corner of `geometry` is on a connected monitor, so a
+/// window saved on an unplugged display doesn't open off-screen.
+fn is_visible(window: &WebviewWindow, geometry: &WindowGeometry) -> bool {
+    let (x, y) = (geometry.x + VISIBLE_MARGIN, geometry.y + VISIBLE_MARGIN);
+    window.available_monitors().unwrap_or_default().iter().any(|monitor| {
+        let (position, size) = (monitor.position(), monitor.size());
+        x >= position.x
+            && y >= position.y
+            && x < position.x + size.width as i32
+            && y < position.y + size.height as i32
+    })
+}
+
+impl WindowState {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let store_path = persist::app_data_file(app, WINDOW_STATE_STORE)?;
+        let store = persist::read_json(&store_path);
+        Ok(Self {
+            store_path,
+            store: Mutex::new(store),
+            windows: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Apply the saved geometry for `board` (or the last closed window) to `window`.
+    pub fn restore(&self, window: &WebviewWindow, board: Option<&str>) {
+        let geometry = {
+            let store = self.store.lock().unwrap();
+            board.and_then(|board| store.boards.get(board)).or(store.last.as_ref()).copied()
+        };
+        if let Some(geometry) = geometry {
+            if is_visible(window, &geometry) {
+                let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+            }
+            let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+            if geometry.maximized {
+                let _ = window.maximize();
+            }
+            if geometry.fullscreen {
+                let _ = window.set_fullscreen(true);
+            }
+            if geometry.zoom != 1.0 {
+                let _ = window.set_zoom(geometry.zoom);
+            }
+        }
+        self.windows.lock().unwrap().insert(
+            window.label().to_string(),
+            TrackedWindow {
+                board: board.map(str::to_string),
+                geometry,
+            },
+        );
+    }
+
+    fn track(&self, window: &Window) {
+        let mut windows = self.windows.lock().unwrap();
+        let tracked = windows.entry(window.label().to_string()).or_default();
+        tracked.geometry = capture(window, tracked.geometry);
+    }
+
+    /// Record the geometry of window `label` and write the store.
+    fn save(&self, label: &str) -> Result<()> {
+        let windows = self.windows.lock().unwrap();
+        let Some(TrackedWindow {
+            board,
+            geometry: Some(geometry),
+        }) = windows.get(label)
+        else {
+            return Ok(());
+        };
+        let mut store = self.store.lock().unwrap();
+        store.last = Some(*geometry);
+        if let Some(board) = board {
+            store.boards.insert(board.clone(), *geometry);
+        }
+        // Forget boards that were deleted or moved elsewhere
+        store.boards.retain(|board, _| Path::new(board).exists());
+        persist::write_json(&self.store_path, &*store)
+    }
+
+    /// Persist every open window, e.g. on quit.
+    pub fn save_all(&self) {
+        let labels: Vec<String> = self.windows.lock().unwrap().keys().cloned().collect();
+        for label in labels {
+            if let Err(err) = self.save(&label) {
+                tracing::warn!("failed to save {label}: {err}");
+            }
+        }
+    }
+
+    fn set_board(&self, label: &str, board: Option<String>) -> Result<()> {
+        // Save the outgoing board's geometry before the window switches boards
+        self.save(label)?;
+        self.windows.lock().unwrap().entry(label.to_string()).or_default().board = board;
+        Ok(())
+    }
+
+    fn set_zoom(&self, label: &str, zoom: f64) {
+        if let Some(geometry) = self
+            .windows
+            .lock()
+            .unwrap()
+            .get_mut(label)
+            .and_then(|tracked| tracked.geometry.as_mut())
+        {
+            geometry.zoom = zoom;
+        }
+    }
+}
+
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    // Auxiliary windows have fixed geometry of their own
+    if !windows::is_board_window(window.label()) {
+        return;
+    }
+    let Some(state) = window.try_state::<WindowState>() else {
+        return;
+    };
+    match event {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) | WindowEvent::CloseRequested { .. } => state.track(window),
+        // Quitting can destroy windows without a close request
+        WindowEvent::Destroyed => {
+            if let Err(err) = state.save(window.label()) {
+                tracing::warn!("failed to save {}: {err}", window.label());
+            }
+            state.windows.lock().unwrap().remove(window.label());
+        }
+        _ => {}
+    }
+}
+
+/// Tell the backend which board `window` is showing so its geometry is
+/// remembered for that board.
+#[tauri::command]
+pub fn set_window_board(window: WebviewWindow, path: Option<String>) -> Result<()> {
+    window.state::<BoardWindows>().set_board(&window, path.as_deref());
+    session::set_board(&window, path.as_deref());
+    window.state::<WindowState>().set_board(window.label(), path)
+}
+
+/// Set the webview zoom factor for `window`; returns the clamped value.
+#[tauri::command]
+pub fn set_window_zoom(window: WebviewWindow, zoom: f64) -> Result<f64> {
+    let zoom = if zoom.is_finite() { zoom.clamp(MIN_ZOOM, MAX_ZOOM) } else { 1.0 };
+    window.set_zoom(zoom)?;
+    window.state::<WindowState>().set_zoom(window.label(), zoom);
+    Ok(zoom)
+}
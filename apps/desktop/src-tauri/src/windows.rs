@@ -0,0 +1,189 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
+};
+
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder, Window, WindowEvent};
+
+use crate::{
+    error::{Error, Result},
+    menu,
+    window_state::WindowState,
+};
+
+const BOARD_WINDOW_PREFIX: &str = "board-";
+const APP_TITLE: &str = "Boardkit";
+
+/// Which board each webview window is showing, keyed by window label.
+#[derive(Default)]
+pub struct BoardWindows {
+    boards: Mutex<HashMap<String, String>>,
+    /// Board window that most recently had focus, for auxiliary windows to target
+    last_focused: Mutex<Option<String>>,
+    next_id: AtomicU32,
+}
+
+pub fn is_board_window(label: &str) -> bool {
+    label == "main" || label.starts_with(BOARD_WINDOW_PREFIX)
+}
+
+fn window_title(path: Option<&str>) -> String {
+    match path.and_then(|path| Path::new(path).file_stem()) {
+        Some(stem) => format!("{} — {APP_TITLE}", stem.to_string_lossy()),
+        None => APP_TITLE.to_string(),
+    }
+}
+
+impl BoardWindows {
+    fn window_for(&self, path: &str) -> Option<String> {
+        let boards = self.boards.lock().unwrap();
+        boards.iter().find(|(_, board)| board.as_str() == path).map(|(label, _)| label.clone())
+    }
+
+    pub fn board(&self, label: &str) -> Option<String> {
+        self.boards.lock().unwrap().get(label).cloned()
+    }
+
+    /// Record the board shown in `window` and retitle it.
+    pub fn set_board(&self, window: &WebviewWindow, path: Option<&str>) {
+        let mut boards = self.boards.lock().unwrap();
+        match path {
+            Some(path) => boards.insert(window.label().to_string(), path.to_string()),
+            None => boards.remove(window.label()),
+        };
+        drop(boards);
+        let _ = window.set_title(&window_title(path));
+        let _ = menu::refresh(window.app_handle());
+    }
+}
+
+/// The board window menu commands should act on: the focused one, else the
+/// last focused, else the main window.
+pub fn target_window(app: &AppHandle) -> Option<WebviewWindow> {
+    let mut windows = app.webview_windows();
+    windows.retain(|label, _| is_board_window(label));
+    let last_focused = app.state::<BoardWindows>().last_focused.lock().unwrap().clone();
+    windows
+        .values()
+        .find(|window| window.is_focused().unwrap_or(false))
+        .or_else(|| last_focused.and_then(|label| windows.get(&label)))
+        .or_else(|| windows.get("main"))
+        .or_else(|| windows.values().next())
+        .cloned()
+}
+
+/// Open the main window again once every board window was closed, e.g. with
+/// only the hidden quick capture window keeping the app running. It starts as
+/// at launch, reopening the last board and taking the files and links queued
+/// for it.
+pub fn reopen_main_window(app: &AppHandle) -> Option<WebviewWindow> {
+    let result = (|| {
+        let config = app.config().app.windows.iter().find(|config| config.label == "main").cloned();
+        let config = config.ok_or_else(|| Error::NotFound("main window configuration".to_string()))?;
+        let window = WebviewWindowBuilder::from_config(app, &config)?.visible(false).build()?;
+        app.state::<WindowState>().restore(&window, None);
+        focus_window(&window);
+        menu::refresh(app)?;
+        Ok::<_, Error>(window)
+    })();
+    result.map_err(|err| tracing::warn!("failed to reopen the main window: {err}")).ok()
+}
+
+/// `target_window`, reopening the main window when no board window is left.
+pub fn target_or_reopen(app: &AppHandle) -> Option<WebviewWindow> {
+    target_window(app).or_else(|| reopen_main_window(app))
+}
+
+/// Open windows ordered by creation, with their titles, for the Window menu.
+pub fn open_windows(app: &AppHandle) -> Vec<(String, String)> {
+    let mut windows: Vec<(String, String)> = app
+        .webview_windows()
+        .into_iter()
+        .filter(|(label, _)| is_board_window(label))
+        .map(|(label, window)| (label, window.title().unwrap_or_default()))
+        .collect();
+    windows.sort_by_key(|(label, _)| {
+        let id = label.strip_prefix(BOARD_WINDOW_PREFIX).and_then(|id| id.parse::<u32>().ok());
+        (label != "main", id)
+    });
+    windows
+}
+
+pub fn focus_window(window: &WebviewWindow) {
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+}
+
+/// Focus the window already showing `path`. Returns false if none is.
+pub fn focus_board(app: &AppHandle, path: &str) -> bool {
+    let label = app.state::<BoardWindows>().window_for(path);
+    match label.and_then(|label| app.get_webview_window(&label)) {
+        Some(window) => {
+            focus_window(&window);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    let Some(windows) = window.try_state::<BoardWindows>() else {
+        return;
+    };
+    match event {
+        WindowEvent::Focused(true) if is_board_window(window.label()) => {
+            *windows.last_focused.lock().unwrap() = Some(window.label().to_string());
+            menu::window_focused(window.app_handle(), window.label());
+        }
+        WindowEvent::Destroyed => {
+            windows.boards.lock().unwrap().remove(window.label());
+            if let Some(menu_state) = window.try_state::<menu::MenuState>() {
+                menu_state.forget(window.label());
+            }
+            let _ = menu::refresh(window.app_handle());
+        }
+        _ => {}
+    }
+}
+
+/// Open `path` in its own window, or focus the window already showing it.
+/// Returns the window label.
+pub fn open_window(app: &AppHandle, path: &str) -> Result<String> {
+    let windows = app.state::<BoardWindows>();
+    if let Some(label) = windows.window_for(path) {
+        if let Some(window) = app.get_webview_window(&label) {
+            focus_window(&window);
+            return Ok(label);
+        }
+    }
+
+    let label = format!("{BOARD_WINDOW_PREFIX}{}", windows.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+    windows.boards.lock().unwrap().insert(label.clone(), path.to_string());
+    // Hidden until the saved geometry is applied, so it doesn't jump on screen
+    let window = WebviewWindowBuilder::new(app, &label, WebviewUrl::App("index.html".into()))
+        .title(window_title(Some(path)))
+        .inner_size(1200.0, 800.0)
+        .min_inner_size(800.0, 600.0)
+        .visible(false)
+        .build()?;
+    app.state::<WindowState>().restore(&window, Some(path));
+    focus_window(&window);
+    menu::refresh(app)?;
+    Ok(label)
+}
+
+#[tauri::command]
+pub async fn open_board_window(app: AppHandle, path: String) -> Result<String> {
+    open_window(&app, &path)
+}
+
+/// The board a window was opened for, so a new window knows what to load.
+#[tauri::command]
+pub fn get_window_board(window: WebviewWindow) -> Option<String> {
+    window.state::<BoardWindows>().board(window.label())
+}
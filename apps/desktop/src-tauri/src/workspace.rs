@@ -0,0 +1,501 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    assets::AssetStore,
+    board,
+    document::BoardDocument,
+    encryption,
+    error::{Error, Result},
+    history,
+    library::Library,
+    links::{self, LinkGraph},
+    menu, persist,
+    recent::RecentFiles,
+    search::{self, SearchIndex},
+    settings::Settings,
+    share::{self, ShareServer},
+    thumbnails::{self, Thumbnails},
+    trash::{Trash, TrashedBoard},
+    watcher::{self, BoardWatcher},
+};
+
+const DEFAULT_DIR_NAME: &str = "Boardkit";
+pub const BOARD_EXTENSION: &str = "boardkit";
+/// Characters of the frontend's nanoid ids
+const ID_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789_-";
+const ID_LENGTH: usize = 21;
+
+/// Gallery entry for a board in the workspace directory.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardInfo {
+    pub path: String,
+    /// Stable id from the board's manifest; `None` until an older file is
+    /// saved again, and for encrypted boards
+    pub id: Option<String>,
+    /// File name without extension
+    pub name: String,
+    pub title: String,
+    pub modified_at: u64,
+    pub element_count: usize,
+    pub widget_count: usize,
+    pub thumbnail_path: Option<String>,
+    pub encrypted: bool,
+}
+
+/// The boards directory and a cache of board metadata, refreshed by
+/// modification time so rescans only parse boards that changed.
+pub struct Workspace {
+    directory: Mutex<PathBuf>,
+    cache: Mutex<HashMap<PathBuf, BoardInfo>>,
+}
+
+fn modified_millis(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+fn is_board(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(BOARD_EXTENSION))
+}
+
+/// Make a board name safe to use as a file name on every platform.
+fn file_stem_for(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect();
+    let stem = stem.trim().trim_matches('.');
+    if stem.is_empty() {
+        "Untitled".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+fn board_info(path: &Path, modified_at: u64) -> Result<BoardInfo> {
+    let path_str = path.to_string_lossy().into_owned();
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    // The title of an encrypted board is unknown until it is unlocked
+    if encryption::is_encrypted_file(path) {
+        return Ok(BoardInfo {
+            id: None,
+            title: name.clone(),
+            name,
+            modified_at,
+            element_count: 0,
+            widget_count: 0,
+            thumbnail_path: None,
+            encrypted: true,
+            path: path_str,
+        });
+    }
+    let document = board::read_board_document(&path_str)?;
+    let count = |pointer| document.pointer(pointer).and_then(Value::as_array).map_or(0, Vec::len);
+    Ok(BoardInfo {
+        id: board::read_board_id(&path_str),
+        name,
+        title: document
+            .pointer("/meta/title")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        modified_at,
+        element_count: count("/board/elements"),
+        widget_count: count("/board/widgets"),
+        thumbnail_path: None,
+        encrypted: false,
+        path: path_str,
+    })
+}
+
+impl Workspace {
+    pub fn load(app: &AppHandle) -> Result<Self> {
+        let directory = match app.state::<Settings>().get().boards_directory {
+            Some(directory) => directory,
+            None => app.path().document_dir()?.join(DEFAULT_DIR_NAME),
+        };
+        fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory: Mutex::new(directory),
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn directory(&self) -> PathBuf {
+        self.directory.lock().unwrap().clone()
+    }
+
+    pub fn set_directory(&self, app: &AppHandle, directory: PathBuf) -> Result<()> {
+        fs::create_dir_all(&directory).map_err(|err| Error::from_io(err, &directory.to_string_lossy()))?;
+        *self.directory.lock().unwrap() = directory.clone();
+        self.cache.lock().unwrap().clear();
+        app.state::<BoardWatcher>().watch(&directory)?;
+        app.state::<Settings>().update(|settings| settings.boards_directory = Some(directory))?;
+        Ok(())
+    }
+
+    /// List boards in the directory, newest first. Unreadable boards are skipped.
+    pub fn scan(&self, thumbnails: &Thumbnails) -> Result<Vec<BoardInfo>> {
+        let directory = self.directory();
+        let mut found = HashMap::new();
+        for entry in fs::read_dir(&directory)?.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() || !is_board(&path) {
+                continue;
+            }
+            let modified_at = modified_millis(&metadata);
+            let cached = self.cache.lock().unwrap().get(&path).cloned();
+            let mut info = match cached {
+                Some(info) if info.modified_at == modified_at => info,
+                _ => match board_info(&path, modified_at) {
+                    Ok(info) => info,
+                    Err(err) => {
+                        tracing::warn!("skipping {}: {err}", path.display());
+                        continue;
+                    }
+                },
+            };
+            info.thumbnail_path = thumbnails.cached(&info.path).map(|path| path.to_string_lossy().into_owned());
+            found.insert(path, info);
+        }
+
+        let mut boards: Vec<BoardInfo> = found.values().cloned().collect();
+        boards.sort_by(|a, b| b.modified_at.cmp(&a.modified_at).then_with(|| a.name.cmp(&b.name)));
+        *self.cache.lock().unwrap() = found;
+        Ok(boards)
+    }
+
+    /// Boards found by the last scan, without touching the disk.
+    pub fn boards(&self) -> Vec<BoardInfo> {
+        self.cache.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Paths of the boards found by the last scan, by board id.
+    pub fn paths_by_id(&self) -> HashMap<String, String> {
+        let cache = self.cache.lock().unwrap();
+        cache.values().filter_map(|info| Some((info.id.clone()?, info.path.clone()))).collect()
+    }
+
+    /// First free `<name>.boardkit`, `<name> 2.boardkit`, ... in the directory.
+    fn unique_path(&self, name: &str) -> PathBuf {
+        let directory = self.directory();
+        let stem = file_stem_for(name);
+        (1..)
+            .map(|n| {
+                let file_name = if n == 1 { stem.clone() } else { format!("{stem} {n}") };
+                directory.join(format!("{file_name}.{BOARD_EXTENSION}"))
+            })
+            .find(|path| !path.exists())
+            .expect("an unused file name exists")
+    }
+
+    /// Resolve `path` and make sure it is a board inside the workspace, so
+    /// rename and delete can't touch arbitrary files.
+    fn resolve(&self, path: &str) -> Result<PathBuf> {
+        let resolved = fs::canonicalize(path).map_err(|err| Error::from_io(err, path))?;
+        let directory = fs::canonicalize(self.directory())?;
+        if resolved.parent() != Some(directory.as_path()) || !is_board(&resolved) {
+            return Err(Error::PermissionDenied(format!("{path} is not a board in the workspace")));
+        }
+        Ok(resolved)
+    }
+}
+
+/// Rescan the workspace, and point recent boards moved within it at their
+/// new location.
+pub fn rescan(app: &AppHandle) -> Result<Vec<BoardInfo>> {
+    let workspace = app.state::<Workspace>();
+    let boards = workspace.scan(&app.state::<Thumbnails>())?;
+    if app.state::<RecentFiles>().relocate(&workspace.paths_by_id())? {
+        menu::refresh(app)?;
+    }
+    Ok(boards)
+}
+
+/// Current path of the board with `id`: from the last scan, else a fresh
+/// one, else the recent boards outside the workspace.
+pub fn resolve_board(app: &AppHandle, id: &str) -> Result<Option<String>> {
+    let workspace = app.state::<Workspace>();
+    if let Some(path) = workspace.paths_by_id().remove(id) {
+        return Ok(Some(path));
+    }
+    rescan(app)?;
+    if let Some(path) = workspace.paths_by_id().remove(id) {
+        return Ok(Some(path));
+    }
+    let recent = app.state::<RecentFiles>().entries();
+    Ok(recent.into_iter().find(|path| board::read_board_id(path).as_deref() == Some(id)))
+}
+
+#[tauri::command]
+pub async fn list_boards(app: AppHandle) -> Result<Vec<BoardInfo>> {
+    tauri::async_runtime::spawn_blocking(move || rescan(&app))
+        .await
+        .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// Find a board by the id in its manifest, wherever it has been moved or
+/// renamed to.
+#[tauri::command]
+pub async fn resolve_board_id(app: AppHandle, id: String) -> Result<String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        resolve_board(&app, &id)?.ok_or_else(|| Error::NotFound(format!("board {id}")))
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+#[tauri::command]
+pub fn get_boards_directory(workspace: State<'_, Workspace>) -> String {
+    workspace.directory().to_string_lossy().into_owned()
+}
+
+#[tauri::command]
+pub async fn set_boards_directory(app: AppHandle, path: String) -> Result<Vec<BoardInfo>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let workspace = app.state::<Workspace>();
+        workspace.set_directory(&app, PathBuf::from(path))?;
+        workspace.scan(&app.state::<Thumbnails>())
+    })
+    .await
+    .map_err(|err| Error::Io(std::io::Error::other(err)))?
+}
+
+/// Create an empty board named `name` in the workspace directory.
+#[tauri::command]
+pub fn create_board(app: AppHandle, workspace: State<'_, Workspace>, name: String) -> Result<BoardInfo> {
+    let document = serde_json::to_value(BoardDocument::new(name.trim(), persist::now_millis()))?;
+    let path = workspace.unique_path(&name);
+    let path_str = path.to_string_lossy().into_owned();
+    board::write_board(&path_str, &board::create_container(&document)?)?;
+    // A new board doesn't inherit what a deleted one at this path was tagged with
+    app.state::<Library>().remove(&path_str)?;
+    search::index_in_background(&app, path_str, document);
+    let metadata = fs::metadata(&path)?;
+    board_info(&path, modified_millis(&metadata))
+}
+
+/// Rename a board's file and title.
+#[tauri::command]
+pub fn rename_board(
+    app: AppHandle,
+    workspace: State<'_, Workspace>,
+    recent: State<'_, RecentFiles>,
+    path: String,
+    name: String,
+) -> Result<BoardInfo> {
+    let from = workspace.resolve(&path)?;
+    let title = name.trim().to_string();
+    let document = board::update_board_document(&path, |document| {
+        document["meta"]["title"] = json!(title);
+        document["meta"]["updatedAt"] = json!(persist::now_millis());
+    })?;
+
+    let unchanged = from.file_stem().is_some_and(|stem| stem.to_string_lossy() == file_stem_for(&name));
+    let to = if unchanged { from.clone() } else { workspace.unique_path(&name) };
+    watcher::expect_write(&from);
+    watcher::expect_write(&to);
+    fs::rename(&from, &to)?;
+    history::rename(&from, &to)?;
+
+    let (from_str, to_str) = (from.to_string_lossy().into_owned(), to.to_string_lossy().into_owned());
+    recent.rename(&from_str, &to_str)?;
+    app.state::<Library>().rename(&from_str, &to_str)?;
+    // Rewriting the title leaves the cached preview stale, so render it anew
+    app.state::<Thumbnails>().remove(&from_str)?;
+    thumbnails::generate_in_background(&app, to_str.clone(), document.clone());
+    app.state::<SearchIndex>().remove(&from_str)?;
+    app.state::<LinkGraph>().rename(&from_str, &to_str)?;
+    if from != to {
+        links::notify_broken(&app, &from_str);
+    }
+    links::record_in_background(&app, to_str.clone(), document.clone());
+    app.state::<ShareServer>().rename(&from_str, &to_str);
+    share::reload_in_background(&app, to_str.clone());
+    search::index_in_background(&app, to_str, document);
+    menu::refresh(&app)?;
+
+    let metadata = fs::metadata(&to)?;
+    board_info(&to, modified_millis(&metadata))
+}
+
+/// A random id in the frontend's nanoid format.
+fn new_element_id() -> String {
+    let random = [uuid::Uuid::new_v4(), uuid::Uuid::new_v4()];
+    random
+        .iter()
+        .flat_map(|uuid| *uuid.as_bytes())
+        .take(ID_LENGTH)
+        .map(|byte| ID_ALPHABET[usize::from(byte & 63)] as char)
+        .collect()
+}
+
+/// Swap every string and object key that is one of the old ids.
+fn replace_ids(value: &mut Value, ids: &HashMap<String, String>) {
+    match value {
+        Value::String(text) => {
+            if let Some(id) = ids.get(text.as_str()) {
+                *text = id.clone();
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| replace_ids(item, ids)),
+        Value::Object(fields) => {
+            *fields = std::mem::take(fields)
+                .into_iter()
+                .map(|(key, mut field)| {
+                    replace_ids(&mut field, ids);
+                    (ids.get(&key).cloned().unwrap_or(key), field)
+                })
+                .collect();
+        }
+        _ => {}
+    }
+}
+
+/// Give every element, widget, group and connection of a copied board a new
+/// id. References to them (group members, connection ends, arrow bindings,
+/// widget state keyed by widget id, data links) are rewritten to match.
+fn regenerate_ids(document: &mut Value) {
+    let mut ids = HashMap::new();
+    for key in ["elements", "widgets", "groups", "connections"] {
+        let items = document.get("board").and_then(|board| board.get(key)).and_then(Value::as_array);
+        for id in items.into_iter().flatten().filter_map(|item| item.get("id")?.as_str()) {
+            ids.insert(id.to_string(), new_element_id());
+        }
+    }
+    for key in ["board", "modules", "dataSharing"] {
+        if let Some(section) = document.get_mut(key) {
+            replace_ids(section, &ids);
+        }
+    }
+}
+
+/// Copy a board in the workspace as `name`. The copy gets its own board and
+/// element ids but keeps the images, library tags and asset references of the
+/// original, and is indexed and thumbnailed like a saved board.
+#[tauri::command]
+pub fn duplicate_board(app: AppHandle, workspace: State<'_, Workspace>, path: String, name: String) -> Result<BoardInfo> {
+    let from = workspace.resolve(&path)?;
+    let from_str = from.to_string_lossy().into_owned();
+    let original = board::read_board(&from_str)?;
+    if encryption::is_encrypted(&original) {
+        return Err(Error::PasswordRequired(path));
+    }
+    let now = persist::now_millis();
+    let mut title = name.trim().to_string();
+    let (bytes, document) = board::copy_container(&original, |document| {
+        if title.is_empty() {
+            let original_title = document.pointer("/meta/title").and_then(Value::as_str).unwrap_or("Untitled Board");
+            title = format!("{original_title} Copy");
+        }
+        regenerate_ids(document);
+        document["meta"]["title"] = json!(title);
+        document["meta"]["createdAt"] = json!(now);
+        document["meta"]["updatedAt"] = json!(now);
+    })?;
+    let to = workspace.unique_path(&title);
+    let to_str = to.to_string_lossy().into_owned();
+    board::write_board(&to_str, &bytes)?;
+    tracing::info!(from = from_str, to = to_str, "board duplicated");
+
+    app.state::<Library>().copy(&from_str, &to_str)?;
+    if let (Some(from_id), Some(to_id)) = (board::board_id(&original), board::board_id(&bytes)) {
+        app.state::<AssetStore>().copy_references(&from_id, &to_id)?;
+    }
+    thumbnails::generate_in_background(&app, to_str.clone(), document.clone());
+    links::record_in_background(&app, to_str.clone(), document.clone());
+    search::index_in_background(&app, to_str, document);
+
+    let metadata = fs::metadata(&to)?;
+    board_info(&to, modified_millis(&metadata))
+}
+
+/// Move a board from the workspace to the trash, where it stays until it is
+/// restored or the retention period runs out.
+#[tauri::command]
+pub fn delete_board(
+    app: AppHandle,
+    workspace: State<'_, Workspace>,
+    recent: State<'_, RecentFiles>,
+    trash: State<'_, Trash>,
+    path: String,
+) -> Result<TrashedBoard> {
+    let resolved = workspace.resolve(&path)?;
+    let title = board_info(&resolved, 0).map_or_else(
+        |_| resolved.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+        |info| if info.title.is_empty() { info.name } else { info.title },
+    );
+    watcher::expect_write(&resolved);
+    let trashed = trash.put(&resolved, title)?;
+    let resolved = resolved.to_string_lossy().into_owned();
+    tracing::info!(path = resolved, "board moved to the trash");
+    recent.remove(&resolved)?;
+    app.state::<Thumbnails>().remove(&resolved)?;
+    app.state::<SearchIndex>().remove(&resolved)?;
+    app.state::<LinkGraph>().remove(&resolved)?;
+    app.state::<ShareServer>().remove(&resolved);
+    links::notify_broken(&app, &resolved);
+    menu::refresh(&app)?;
+    Ok(trashed)
+}
+
+/// Put a trashed board back where it was, or in the workspace under a new
+/// name if that spot is taken or outside the current workspace.
+#[tauri::command]
+pub fn restore_board(
+    app: AppHandle,
+    workspace: State<'_, Workspace>,
+    trash: State<'_, Trash>,
+    id: String,
+) -> Result<BoardInfo> {
+    let entry = trash.get(&id).ok_or_else(|| Error::NotFound(format!("trashed board {id}")))?;
+    let original = PathBuf::from(&entry.original_path);
+    let in_workspace = original.parent() == Some(workspace.directory().as_path());
+    let to = if in_workspace && !original.exists() {
+        original
+    } else {
+        let name = original.file_stem().map(|stem| stem.to_string_lossy().into_owned());
+        workspace.unique_path(&name.unwrap_or(entry.title))
+    };
+    watcher::expect_write(&to);
+    trash.restore(&id, &to)?;
+
+    let to_str = to.to_string_lossy().into_owned();
+    tracing::info!(path = to_str, "board restored from the trash");
+    if to_str != entry.original_path {
+        app.state::<Library>().rename(&entry.original_path, &to_str)?;
+    }
+    let metadata = fs::metadata(&to)?;
+    let info = board_info(&to, modified_millis(&metadata))?;
+    if !info.encrypted {
+        let document = board::read_board_document(&to_str)?;
+        thumbnails::generate_in_background(&app, to_str.clone(), document.clone());
+        links::record_in_background(&app, to_str.clone(), document.clone());
+        search::index_in_background(&app, to_str, document);
+    }
+    Ok(info)
+}
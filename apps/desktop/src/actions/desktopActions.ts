@@ -1,5 +1,22 @@
-import { actionRegistry, type ActionDefinition } from '@boardkit/core'
+import { convertFileSrc, invoke } from '@tauri-apps/api/core'
+import { open, save } from '@tauri-apps/plugin-dialog'
+import { readTextFile } from '@tauri-apps/plugin-fs'
+import {
+  actionRegistry,
+  createImageElement,
+  importImageFile,
+  useBoardStore,
+  type ActionContext,
+  type BoardkitDocument,
+  type ActionDefinition,
+} from '@boardkit/core'
+import { useToast } from '@boardkit/ui'
 import { useSettingsPanel } from '../composables/useSettingsPanel'
+import { usePersistence } from '../composables/usePersistence'
+import { useVault } from '../composables/useVault'
+import { useShareBoard } from '../composables/useShareBoard'
+import type { NativeSettings } from '../composables/useNativeSettings'
+import { recognizeImageText } from '../utils/imageText'
 
 /**
  * Desktop-specific actions for Boardkit.
@@ -7,8 +24,252 @@ import { useSettingsPanel } from '../composables/useSettingsPanel'
  */
 export function registerDesktopActions(): void {
   const { openForWidget } = useSettingsPanel()
+  const boardStore = useBoardStore()
+  const { currentFilePath, openDocument, importDocument, saveDocument } = usePersistence()
+  const toaster = useToast()
+  const vault = useVault()
+  const shareBoard = useShareBoard()
+
+  // Rendered natively so the clipboard gets a real image, not a webview canvas grab
+  const copySelection = async (format: 'png' | 'svg', ctx: ActionContext) => {
+    const doc = boardStore.getDocument()
+    if (!doc) return
+    try {
+      await invoke('copy_selection_as_image', {
+        format,
+        payload: {
+          boardJson: doc,
+          selection: [...ctx.selectedElementIds, ...ctx.selectedWidgetIds],
+          boardPath: currentFilePath.value,
+        },
+      })
+    } catch (error) {
+      console.error(`[desktopActions] Copy as ${format.toUpperCase()} failed:`, error)
+    }
+  }
+
+  // Convert a file natively into a new board in the vault
+  const importBoard = async <T = BoardkitDocument>(
+    command: string,
+    filter: { name: string; extensions: string[] },
+    toDocument: (result: T) => BoardkitDocument = (result) => result as BoardkitDocument
+  ) => {
+    const path = await open({ filters: [filter] })
+    if (!path) return
+    try {
+      const document = toDocument(await invoke<T>(command, { path }))
+      if (!(await importDocument(document))) {
+        toaster.error('Could not save the imported board')
+      }
+    } catch (error) {
+      console.error(`[desktopActions] ${command} failed:`, error)
+      toaster.error(`Could not import ${filter.name} file`)
+    }
+  }
+
+  // Images from the native asset store land in the middle of the visible canvas
+  const placeStoredImage = async (
+    image: { hash: string; mimeType: string; width: number; height: number },
+    name: string,
+    ctx: ActionContext
+  ) => {
+    const response = await fetch(convertFileSrc(image.hash, 'boardkit-asset'))
+    const file = new File([await response.blob()], name, { type: image.mimeType })
+    const { assetId } = await importImageFile(file)
+    const { x, y, zoom } = ctx.viewport
+    const center = { x: (window.innerWidth / 2 - x) / zoom, y: (window.innerHeight / 2 - y) / zoom }
+    boardStore.addElement(createImageElement(assetId, { width: image.width, height: image.height }, center))
+    return assetId
+  }
+
+  const captureScreenshot = async (mode: 'screen' | 'window' | 'region', ctx: ActionContext) => {
+    try {
+      const shot = await invoke<{ hash: string; mimeType: string; width: number; height: number } | null>(
+        'capture_screenshot',
+        { mode }
+      )
+      if (!shot) return
+      recognizeImageText(await placeStoredImage(shot, 'Screenshot.png', ctx))
+    } catch (error) {
+      console.error('[desktopActions] Screenshot failed:', error)
+      toaster.error('Could not capture a screenshot')
+    }
+  }
+
+  // Templates are copies of the saved file, so save pending changes first
+  const saveAsTemplate = async () => {
+    if (!currentFilePath.value) {
+      toaster.warning('Save the board before turning it into a template')
+      return
+    }
+    try {
+      if (boardStore.isDirty) await saveDocument(false)
+      const name = boardStore.getDocument()?.meta.title ?? 'Untitled template'
+      await invoke('save_as_template', { path: currentFilePath.value, name })
+      toaster.success(`"${name}" is now available under New Board`, { title: 'Template saved' })
+    } catch (error) {
+      console.error('[desktopActions] Save as template failed:', error)
+      toaster.error('Could not save the board as a template')
+    }
+  }
+
+  // The boards directory's repository; Git's own message says what went wrong
+  type GitStatus = { branch: string | null; upstream: string | null; changed: string[]; conflicts: unknown[] }
+  const runGit = async (command: string, args: Record<string, unknown>, done: (status: GitStatus) => string) => {
+    try {
+      const status = await invoke<GitStatus>(command, args)
+      if (status.conflicts.length > 0) {
+        toaster.warning(`${status.conflicts.length} boards conflict; our side of each was kept`, {
+          title: 'Merge conflicts',
+          duration: 10000,
+        })
+        return
+      }
+      toaster.success(done(status))
+    } catch (error) {
+      console.error(`[desktopActions] ${command} failed:`, error)
+      toaster.error((error as { message?: string }).message ?? 'Git failed', { title: 'Git' })
+    }
+  }
+
+  // Commits what is on disk, so save pending changes first
+  const commitBoards = async (path: string | null) => {
+    if (boardStore.isDirty) await saveDocument(false)
+    const title = boardStore.getDocument()?.meta.title || 'board'
+    await runGit(
+      'git_commit_board',
+      { message: path ? `Update ${title}` : 'Update boards', path },
+      (status) => (status.branch ? `Committed to ${status.branch}` : 'Committed')
+    )
+  }
+
+  // Rendered offline in Rust; the extension picks the syntax
+  const insertDiagram = async (ctx: ActionContext) => {
+    const path = await open({
+      filters: [{ name: 'Diagram', extensions: ['mmd', 'mermaid', 'dot', 'gv'] }],
+    })
+    if (!path) return
+    try {
+      const source = await readTextFile(path)
+      const kind = /\.(dot|gv)$/i.test(path) ? 'dot' : 'mermaid'
+      const diagram = await invoke<{ width: number; height: number; hash: string }>('render_diagram', {
+        source,
+        kind,
+        options: { store: true },
+      })
+      await placeStoredImage({ ...diagram, mimeType: 'image/png' }, 'Diagram.png', ctx)
+    } catch (error) {
+      console.error('[desktopActions] Diagram rendering failed:', error)
+      toaster.error('Could not render the diagram')
+    }
+  }
+
+  // Lays out the selection, or everything that is connected when at most one item is selected
+  const tidyUp = async (algorithm: 'layered' | 'tree' | 'force', ctx: ActionContext) => {
+    const edges = boardStore.connections.map((connection) => ({
+      source: connection.sourceId,
+      target: connection.targetId,
+    }))
+    for (const element of boardStore.elements) {
+      if ((element.type === 'arrow' || element.type === 'line') && element.startBinding && element.endBinding) {
+        edges.push({ source: element.startBinding.elementId, target: element.endBinding.elementId })
+      }
+    }
+    const selected = new Set<string>([...ctx.selectedElementIds, ...ctx.selectedWidgetIds])
+    const included = selected.size > 1 ? selected : new Set(edges.flatMap((edge) => [edge.source, edge.target]))
+    // Bound arrows follow their shapes, so they aren't nodes themselves
+    const elements = boardStore.elements.filter(
+      (element) => included.has(element.id) && element.type !== 'arrow' && element.type !== 'line'
+    )
+    const widgets = boardStore.widgets.filter((widget) => included.has(widget.id))
+    const nodes = [...elements, ...widgets].map(({ id, rect }) => ({ id, ...rect }))
+    if (nodes.length < 2) {
+      toaster.info('Select or connect at least two items to tidy up')
+      return
+    }
+    try {
+      const positions = await invoke<{ id: string; x: number; y: number }[]>('layout_graph', {
+        nodes,
+        edges,
+        algorithm,
+      })
+      const widgetIds = new Set(widgets.map((widget) => widget.id))
+      boardStore.captureHistorySnapshot('Tidy up')
+      for (const { id, x, y } of positions) {
+        if (widgetIds.has(id)) {
+          boardStore.moveWidget(id, x, y)
+        } else {
+          boardStore.moveElement(id, x, y, true)
+        }
+      }
+      boardStore.markDirty('Tidy up')
+    } catch (error) {
+      console.error('[desktopActions] Tidy up failed:', error)
+      toaster.error('Could not lay out the board')
+    }
+  }
+
+  const tidyUpActions: ActionDefinition[] = (
+    [
+      ['layered', 'Tidy Up as Flow', 'Arrange connected items in layers following their arrows'],
+      ['tree', 'Tidy Up as Tree', 'Arrange connected items as a hierarchy, parents above children'],
+      ['force', 'Tidy Up as Network', 'Spread connected items out so linked ones sit close together'],
+    ] as const
+  ).map(([algorithm, title, subtitle], index): ActionDefinition => ({
+    id: `board.tidy-up-${algorithm}`,
+    title,
+    subtitle,
+    keywords: ['tidy', 'layout', 'arrange', 'auto layout', 'organize', 'graph', 'connections', algorithm],
+    icon: 'layout-grid',
+    group: 'board',
+    contexts: ['global'],
+    priority: 24 - index,
+    run: (ctx) => tidyUp(algorithm, ctx),
+  }))
+
+  const screenshotActions: ActionDefinition[] = (
+    [
+      ['region', 'Capture Screen Region', 'Drag out part of the screen and add it to the board'],
+      ['window', 'Capture Window', 'Pick a window and add a screenshot of it to the board'],
+      ['screen', 'Capture Full Screen', 'Add a screenshot of the whole screen to the board'],
+    ] as const
+  ).map(([mode, title, subtitle], index): ActionDefinition => ({
+    id: `board.capture-${mode}`,
+    title,
+    subtitle,
+    keywords: ['screenshot', 'capture', 'screen', 'grab', 'image', mode],
+    icon: 'camera',
+    group: 'board',
+    contexts: ['global'],
+    priority: 30 - index,
+    run: (ctx) => captureScreenshot(mode, ctx),
+  }))
 
   const actions: ActionDefinition[] = [
+    ...screenshotActions,
+    ...tidyUpActions,
+    {
+      id: 'board.insert-diagram',
+      title: 'Insert Diagram',
+      subtitle: 'Render a Mermaid flowchart or Graphviz DOT file onto the board',
+      keywords: ['diagram', 'mermaid', 'graphviz', 'dot', 'flowchart', 'graph', 'render'],
+      icon: 'image',
+      group: 'board',
+      contexts: ['global'],
+      priority: 26,
+      run: (ctx) => insertDiagram(ctx),
+    },
+    {
+      id: 'board.save-as-template',
+      title: 'Save as Template',
+      subtitle: 'Reuse this board as a starting point for new boards',
+      keywords: ['template', 'save', 'reuse', 'starter', 'new board'],
+      icon: 'layout-template',
+      group: 'board',
+      contexts: ['global'],
+      priority: 27,
+      run: () => saveAsTemplate(),
+    },
     {
       id: 'settings.open',
       title: 'Settings',
@@ -24,6 +285,479 @@ export function registerDesktopActions(): void {
         }
       },
     },
+    {
+      id: 'selection.copy-link',
+      title: 'Copy Link',
+      subtitle: 'Copy a boardkit:// link to the board or selected item',
+      keywords: ['link', 'url', 'share', 'deep link', 'reference'],
+      icon: 'link',
+      group: 'board',
+      contexts: ['global', 'widget'],
+      priority: 41,
+      when: () => currentFilePath.value !== null,
+      run: async (ctx) => {
+        const link = await invoke<string>('create_board_link', {
+          path: currentFilePath.value,
+          elementId: ctx.selectedElementId ?? ctx.selectedWidgetId,
+        })
+        await navigator.clipboard.writeText(link)
+      },
+    },
+    {
+      id: 'selection.copy-png',
+      title: 'Copy as PNG',
+      subtitle: 'Copy the selection to the clipboard as an image',
+      keywords: ['copy', 'png', 'image', 'clipboard', 'screenshot'],
+      icon: 'image',
+      group: 'element',
+      contexts: ['global', 'widget'],
+      priority: 40,
+      when: (ctx) => ctx.selectionCount > 0,
+      run: (ctx) => copySelection('png', ctx),
+    },
+    {
+      id: 'selection.copy-svg',
+      title: 'Copy as SVG',
+      subtitle: 'Copy the selection to the clipboard as SVG markup',
+      keywords: ['copy', 'svg', 'vector', 'clipboard'],
+      icon: 'code',
+      group: 'element',
+      contexts: ['global', 'widget'],
+      priority: 39,
+      when: (ctx) => ctx.selectionCount > 0,
+      run: (ctx) => copySelection('svg', ctx),
+    },
+    {
+      id: 'board.restore-previous-version',
+      title: 'Restore Previous Version',
+      subtitle: 'Roll the board back to the version before the last save',
+      keywords: ['history', 'version', 'snapshot', 'restore', 'rollback', 'undo'],
+      icon: 'history',
+      group: 'board',
+      contexts: ['global'],
+      priority: 20,
+      when: () => currentFilePath.value !== null,
+      run: async () => {
+        const path = currentFilePath.value
+        if (!path) return
+        // The newest version is the file as last saved
+        const versions = await invoke<{ id: string; createdAt: number }[]>('list_versions', { path })
+        const previous = versions[1]
+        if (!previous) {
+          toaster.info('No earlier version of this board')
+          return
+        }
+        await invoke('restore_version', { path, id: previous.id })
+        await openDocument(path)
+        toaster.success(`Restored version from ${new Date(previous.createdAt).toLocaleString()}`)
+      },
+    },
+    {
+      id: 'board.encrypt',
+      title: 'Encrypt Board',
+      subtitle: 'Protect this board with a password',
+      keywords: ['encrypt', 'password', 'lock', 'protect', 'private', 'secure'],
+      icon: 'lock',
+      group: 'board',
+      contexts: ['global'],
+      priority: 15,
+      when: () => currentFilePath.value !== null && !vault.isEncrypted(currentFilePath.value),
+      run: async () => {
+        const path = currentFilePath.value
+        const doc = boardStore.getDocument()
+        if (!path || !doc) return
+        const password = await vault.promptPassword({
+          title: 'Encrypt board',
+          description: 'The board cannot be opened without this password. It cannot be recovered if lost.',
+          submitLabel: 'Encrypt',
+        })
+        if (!password) return
+        await vault.setFileEncryption(path, doc, password)
+        boardStore.markClean()
+        toaster.success('Board encrypted')
+      },
+    },
+    {
+      id: 'board.decrypt',
+      title: 'Remove Board Encryption',
+      subtitle: 'Store this board without a password',
+      keywords: ['decrypt', 'password', 'unlock', 'encryption'],
+      icon: 'lock-open',
+      group: 'board',
+      contexts: ['global'],
+      priority: 14,
+      when: () => currentFilePath.value !== null && vault.isEncrypted(currentFilePath.value),
+      run: async () => {
+        const path = currentFilePath.value
+        const doc = boardStore.getDocument()
+        if (!path || !doc) return
+        await invoke('forget_board_key', { path }).catch(() => {})
+        await vault.setFileEncryption(path, doc, null)
+        boardStore.markClean()
+        toaster.success('Board encryption removed')
+      },
+    },
+    {
+      id: 'board.batch-export',
+      title: 'Batch Export Boards',
+      subtitle: 'Export several boards into a folder with the first export preset',
+      keywords: ['export', 'batch', 'publish', 'png', 'svg', 'pdf', 'folder', 'preset'],
+      icon: 'download',
+      group: 'board',
+      contexts: ['global'],
+      priority: 20,
+      run: async () => {
+        const { exportPresets } = await invoke<NativeSettings>('get_settings')
+        const preset = exportPresets[0]
+        if (!preset) {
+          toaster.error('No export presets configured')
+          return
+        }
+        const paths = await open({
+          multiple: true,
+          defaultPath: vault.vaultPath.value ?? undefined,
+          filters: [{ name: 'Boardkit', extensions: ['boardkit'] }],
+        })
+        if (!paths || paths.length === 0) return
+        const outputDir = await open({ directory: true, title: 'Export to folder' })
+        if (!outputDir) return
+
+        const results = await invoke<{ error: string | null }[]>('batch_export', {
+          paths,
+          preset: preset.name,
+          outputDir,
+        })
+        const failed = results.filter((result) => result.error).length
+        if (failed > 0) {
+          toaster.error(`${failed} of ${results.length} boards failed to export`)
+        } else {
+          toaster.success(`Exported ${results.length} boards as ${preset.name}`)
+        }
+      },
+    },
+    {
+      id: 'board.export-markdown',
+      title: 'Export as Markdown',
+      subtitle: 'Outline of frames, notes and connections, with pictures alongside',
+      keywords: ['export', 'markdown', 'md', 'outline', 'notes', 'retro', 'docs'],
+      icon: 'file-text',
+      group: 'board',
+      contexts: ['global'],
+      priority: 19,
+      run: async () => {
+        const doc = boardStore.getDocument()
+        if (!doc) return
+        const path = await save({
+          defaultPath: `${doc.meta.title || 'board'}.md`,
+          filters: [{ name: 'Markdown', extensions: ['md'] }],
+        })
+        if (!path) return
+        try {
+          await invoke('export_markdown', {
+            boardJson: doc,
+            path,
+            options: { boardPath: currentFilePath.value },
+          })
+          toaster.success('Markdown exported')
+        } catch (error) {
+          console.error('[desktopActions] Markdown export failed:', error)
+          toaster.error('Failed to export Markdown')
+        }
+      },
+    },
+    {
+      id: 'board.export-html',
+      title: 'Export as Web Page',
+      subtitle: 'A single HTML file with a pan and zoom viewer, for people without Boardkit',
+      keywords: ['export', 'html', 'web', 'page', 'share', 'viewer', 'browser', 'stakeholders', 'read-only'],
+      icon: 'globe',
+      group: 'board',
+      contexts: ['global'],
+      priority: 19,
+      run: async () => {
+        const doc = boardStore.getDocument()
+        if (!doc) return
+        const path = await save({
+          defaultPath: `${doc.meta.title || 'board'}.html`,
+          filters: [{ name: 'Web page', extensions: ['html'] }],
+        })
+        if (!path) return
+        try {
+          await invoke('export_html', {
+            boardJson: doc,
+            path,
+            options: { boardPath: currentFilePath.value },
+          })
+          toaster.success('Web page exported')
+        } catch (error) {
+          console.error('[desktopActions] HTML export failed:', error)
+          toaster.error('Failed to export web page')
+        }
+      },
+    },
+    {
+      id: 'board.share-local',
+      title: 'Share on Local Network',
+      subtitle: 'Let people in the room view this board in their browser, read-only',
+      keywords: ['share', 'network', 'lan', 'wifi', 'meeting', 'browser', 'qr', 'present', 'read-only'],
+      icon: 'wifi',
+      group: 'board',
+      contexts: ['global'],
+      priority: 19,
+      run: () => shareBoard.open(),
+    },
+    {
+      id: 'board.export-frames',
+      title: 'Export Frames as Slides',
+      subtitle: 'One PNG per frame, numbered in reading order',
+      keywords: ['export', 'frames', 'slides', 'png', 'deck', 'presentation', 'folder'],
+      icon: 'layers',
+      group: 'board',
+      contexts: ['global'],
+      priority: 19,
+      run: async () => {
+        const doc = boardStore.getDocument()
+        if (!doc) return
+        const dir = await open({ directory: true, title: 'Export slides to folder' })
+        if (!dir) return
+        try {
+          const written = await invoke<string[]>('export_frames', {
+            boardJson: doc,
+            format: 'png',
+            dir,
+            options: { boardPath: currentFilePath.value },
+          })
+          toaster.success(`Exported ${written.length} slides`)
+        } catch (error) {
+          console.error('[desktopActions] Frame export failed:', error)
+          toaster.error('Failed to export frames')
+        }
+      },
+    },
+    {
+      id: 'board.import-markdown',
+      title: 'Import Markdown Outline',
+      subtitle: 'Turn headings into frames and list items into sticky notes',
+      keywords: ['import', 'markdown', 'md', 'outline', 'agenda', 'notes', 'workshop'],
+      icon: 'file-text',
+      group: 'board',
+      contexts: ['global'],
+      priority: 18,
+      run: () => importBoard('import_markdown', { name: 'Markdown', extensions: ['md', 'markdown', 'txt'] }),
+    },
+    {
+      id: 'board.import-opml',
+      title: 'Import OPML Mind Map',
+      subtitle: 'Rebuild an outline from a mind-mapping app as connected notes',
+      keywords: ['import', 'opml', 'mind map', 'mindmap', 'outline', 'xmind', 'mindnode', 'tree'],
+      icon: 'file-text',
+      group: 'board',
+      contexts: ['global'],
+      priority: 17,
+      run: () => importBoard('import_opml', { name: 'OPML', extensions: ['opml', 'xml'] }),
+    },
+    {
+      id: 'board.import-figjam',
+      title: 'Import FigJam File',
+      subtitle: 'Bring stickies, shapes, stamps and sections over from a local FigJam copy',
+      keywords: ['import', 'figjam', 'figma', 'jam', 'migrate', 'whiteboard', 'sticky', 'stamp'],
+      icon: 'file-text',
+      group: 'board',
+      contexts: ['global'],
+      priority: 17,
+      run: () =>
+        importBoard<{ document: BoardkitDocument; unmapped: { kind: string; count: number }[] }>(
+          'import_figjam',
+          { name: 'FigJam file', extensions: ['jam', 'fig'] },
+          (result) => {
+            if (result.unmapped.length > 0) {
+              const dropped = result.unmapped.map(({ kind, count }) => `${count} ${kind.toLowerCase()}`).join(', ')
+              toaster.warning(`Some FigJam items have no boardkit counterpart and were left out: ${dropped}`)
+            }
+            return result.document
+          }
+        ),
+    },
+    {
+      id: 'board.import-miro',
+      title: 'Import Miro Board',
+      subtitle: 'Bring sticky notes, shapes, frames and connectors over from a Miro backup',
+      keywords: ['import', 'miro', 'rtb', 'backup', 'migrate', 'whiteboard', 'sticky'],
+      icon: 'file-text',
+      group: 'board',
+      contexts: ['global'],
+      priority: 17,
+      run: () => importBoard('import_miro', { name: 'Miro export', extensions: ['json'] }),
+    },
+    {
+      id: 'board.import-tabular',
+      title: 'Import CSV or JSON Data',
+      subtitle: 'Create a card for every row of a spreadsheet or JSON export',
+      keywords: ['import', 'csv', 'json', 'spreadsheet', 'table', 'backlog', 'survey', 'cards', 'rows'],
+      icon: 'file-text',
+      group: 'board',
+      contexts: ['global'],
+      priority: 17,
+      run: () =>
+        importBoard<{ rowCount: number; truncated: boolean; document: BoardkitDocument }>(
+          'import_tabular',
+          { name: 'CSV or JSON', extensions: ['csv', 'tsv', 'json'] },
+          (result) => {
+            if (result.truncated) {
+              toaster.warning(`Only the first 1000 of ${result.rowCount} rows were imported`)
+            }
+            return result.document
+          }
+        ),
+    },
+    {
+      id: 'git.init',
+      title: 'Set Up Git for Boards',
+      subtitle: 'Keep the boards folder in a Git repository that merges boards',
+      keywords: ['git', 'repository', 'version', 'init', 'team', 'sync'],
+      icon: 'folder',
+      group: 'board',
+      contexts: ['global'],
+      priority: 6,
+      run: () => runGit('git_init_workspace', {}, () => 'The boards folder is ready for Git'),
+    },
+    {
+      id: 'git.commit-board',
+      title: 'Commit Board',
+      subtitle: 'Record this board in the boards repository',
+      keywords: ['git', 'commit', 'version', 'save', 'snapshot'],
+      icon: 'check-circle',
+      group: 'board',
+      contexts: ['global'],
+      priority: 6,
+      run: () => {
+        if (!currentFilePath.value) {
+          toaster.warning('Save the board before committing it')
+          return
+        }
+        return commitBoards(currentFilePath.value)
+      },
+    },
+    {
+      id: 'git.commit-all',
+      title: 'Commit All Boards',
+      subtitle: 'Record every changed board in the boards repository',
+      keywords: ['git', 'commit', 'version', 'all', 'boards'],
+      icon: 'check-circle',
+      group: 'board',
+      contexts: ['global'],
+      priority: 6,
+      run: () => commitBoards(null),
+    },
+    {
+      id: 'git.pull',
+      title: 'Pull Boards',
+      subtitle: 'Merge in boards changed by others',
+      keywords: ['git', 'pull', 'fetch', 'merge', 'update', 'team'],
+      icon: 'download',
+      group: 'board',
+      contexts: ['global'],
+      priority: 6,
+      run: () => runGit('git_pull', {}, () => 'Boards are up to date'),
+    },
+    {
+      id: 'git.push',
+      title: 'Push Boards',
+      subtitle: 'Share committed boards through the remote repository',
+      keywords: ['git', 'push', 'remote', 'share', 'team'],
+      icon: 'upload',
+      group: 'board',
+      contexts: ['global'],
+      priority: 6,
+      run: () =>
+        runGit('git_push', {}, (status) => (status.upstream ? `Pushed to ${status.upstream}` : 'Pushed')),
+    },
+    {
+      id: 'app.export-logs',
+      title: 'Export Logs',
+      subtitle: 'Save recent logs as a ZIP to attach to a bug report',
+      keywords: ['logs', 'debug', 'bug', 'report', 'diagnostics'],
+      icon: 'file-archive',
+      group: 'board',
+      contexts: ['global'],
+      priority: 5,
+      run: async () => {
+        const path = await save({
+          defaultPath: 'boardkit-logs.zip',
+          filters: [{ name: 'ZIP archive', extensions: ['zip'] }],
+        })
+        if (path) {
+          await invoke('export_logs', { path })
+        }
+      },
+    },
+    {
+      id: 'app.toggle-crash-reports',
+      title: 'Toggle Crash Reports',
+      subtitle: 'Save a local report when Boardkit crashes (nothing is sent)',
+      keywords: ['crash', 'diagnostics', 'report', 'privacy', 'opt-in'],
+      icon: 'bug',
+      group: 'board',
+      contexts: ['global'],
+      priority: 4,
+      run: async () => {
+        const enabled = await invoke<boolean>('get_diagnostics_enabled')
+        await invoke('set_diagnostics_enabled', { enabled: !enabled })
+        toaster.info(enabled ? 'Crash reports disabled' : 'Crash reports enabled')
+      },
+    },
+    {
+      id: 'app.toggle-notifications',
+      title: 'Toggle Notifications',
+      subtitle: 'Notify when exports finish while Boardkit is in the background',
+      keywords: ['notifications', 'notify', 'alert', 'export', 'background'],
+      icon: 'bell',
+      group: 'board',
+      contexts: ['global'],
+      priority: 4,
+      run: async () => {
+        const { notificationsEnabled } = await invoke<NativeSettings>('get_settings')
+        await invoke('update_settings', { patch: { notificationsEnabled: !notificationsEnabled } })
+        toaster.info(notificationsEnabled ? 'Notifications disabled' : 'Notifications enabled')
+      },
+    },
+    {
+      id: 'app.toggle-automation',
+      title: 'Toggle Automation',
+      subtitle: 'Let local scripts and tests drive Boardkit',
+      keywords: ['automation', 'script', 'api', 'test', 'remote', 'control'],
+      icon: 'terminal',
+      group: 'board',
+      contexts: ['global'],
+      priority: 3,
+      run: async () => {
+        const { automationEnabled } = await invoke<NativeSettings>('get_settings')
+        try {
+          await invoke('update_settings', { patch: { automationEnabled: !automationEnabled } })
+        } catch (error) {
+          console.error('[desktopActions] Failed to toggle automation:', error)
+          toaster.error('Could not start the automation server')
+          return
+        }
+        const status = await invoke<{ url: string } | null>('get_automation_status')
+        toaster.info(status ? `Automation listening on ${status.url}` : 'Automation disabled')
+      },
+    },
+    {
+      id: 'app.toggle-restore-session',
+      title: 'Toggle Reopen Last Session',
+      subtitle: 'Reopen the boards and windows left open on the next launch',
+      keywords: ['session', 'restore', 'reopen', 'launch', 'startup', 'windows'],
+      icon: 'history',
+      group: 'board',
+      contexts: ['global'],
+      priority: 3,
+      run: async () => {
+        const { restoreSession } = await invoke<NativeSettings>('get_settings')
+        await invoke('update_settings', { patch: { restoreSession: !restoreSession } })
+        toaster.info(restoreSession ? 'Boards will not reopen on launch' : 'Boards will reopen on launch')
+      },
+    },
   ]
 
   actionRegistry.registerAll(actions)
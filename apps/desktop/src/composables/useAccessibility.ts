@@ -0,0 +1,97 @@
+import { ref, watch } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { useBoardStore } from '@boardkit/core'
+
+export interface AccessibilitySettings {
+  reducedMotion: boolean
+  highContrast: boolean
+  screenReader: boolean
+}
+
+export type AnnouncementPriority = 'polite' | 'assertive'
+
+const settings = ref<AccessibilitySettings>({ reducedMotion: false, highContrast: false, screenReader: false })
+
+const liveRegions = new Map<AnnouncementPriority, HTMLElement>()
+
+/** Visually hidden live region for screen readers the OS can't reach natively. */
+function liveRegion(priority: AnnouncementPriority): HTMLElement {
+  let region = liveRegions.get(priority)
+  if (!region) {
+    region = document.createElement('div')
+    region.setAttribute('role', priority === 'assertive' ? 'alert' : 'status')
+    region.setAttribute('aria-live', priority)
+    region.setAttribute('aria-atomic', 'true')
+    region.style.cssText =
+      'position:absolute;width:1px;height:1px;margin:-1px;padding:0;overflow:hidden;clip:rect(0 0 0 0);border:0'
+    document.body.appendChild(region)
+    liveRegions.set(priority, region)
+  }
+  return region
+}
+
+/** Flag the settings on the root element so styles can follow them. */
+function applyToDocument(next: AccessibilitySettings) {
+  const root = document.documentElement
+  root.toggleAttribute('data-reduced-motion', next.reducedMotion)
+  root.toggleAttribute('data-high-contrast', next.highContrast)
+}
+
+/**
+ * The OS accessibility settings as accessibility.rs follows them, and
+ * screen reader announcements.
+ */
+export function useAccessibility() {
+  const boardStore = useBoardStore()
+
+  /** Have the screen reader speak `text`, natively where the OS allows it. */
+  const announce = async (text: string, priority: AnnouncementPriority = 'polite') => {
+    let delivered = false
+    try {
+      delivered = await invoke<boolean>('announce', { text, priority })
+    } catch (error) {
+      console.warn('[Accessibility] Native announcement failed:', error)
+    }
+    if (!delivered) {
+      const region = liveRegion(priority)
+      // Clearing first makes a repeated message count as a change
+      region.textContent = ''
+      requestAnimationFrame(() => {
+        region.textContent = text
+      })
+    }
+  }
+
+  const describeSelection = (count: number) => {
+    if (count === 0) return 'Selection cleared'
+    return count === 1 ? '1 item selected' : `${count} items selected`
+  }
+
+  /** Follow the settings and announce selection changes while a screen reader runs. */
+  const initialize = async (): Promise<UnlistenFn> => {
+    settings.value = await invoke<AccessibilitySettings>('get_accessibility_settings')
+    applyToDocument(settings.value)
+    const unlisten = await listen<AccessibilitySettings>('accessibility-changed', (event) => {
+      settings.value = event.payload
+      applyToDocument(event.payload)
+    })
+    const stopWatch = watch(
+      () => boardStore.selectionCount,
+      (count) => {
+        if (settings.value.screenReader) announce(describeSelection(count))
+      }
+    )
+
+    return () => {
+      unlisten()
+      stopWatch()
+    }
+  }
+
+  return {
+    settings,
+    announce,
+    initialize,
+  }
+}
@@ -0,0 +1,122 @@
+import { invoke } from '@tauri-apps/api/core'
+import type { UnlistenFn } from '@tauri-apps/api/event'
+import { getCurrentWindow } from '@tauri-apps/api/window'
+import { useBoardStore, type CanvasElement } from '@boardkit/core'
+import { usePersistence } from './usePersistence'
+
+interface AutomationRequest {
+  id: number
+  method: string
+  params: Record<string, unknown> | null
+}
+
+type Handler = (params: Record<string, unknown>) => unknown
+
+const requireString = (params: Record<string, unknown>, name: string): string => {
+  const value = params[name]
+  if (typeof value !== 'string') throw new Error(`${name} must be a string`)
+  return value
+}
+
+const requireIds = (params: Record<string, unknown>, name = 'ids'): string[] => {
+  const value = params[name]
+  if (!Array.isArray(value) || !value.every((id) => typeof id === 'string')) {
+    throw new Error(`${name} must be an array of element ids`)
+  }
+  return value
+}
+
+/**
+ * Board side of the automation server (see automation.rs): runs the
+ * requests it forwards to this window against the board store. Changes go
+ * through the same store actions as edits made by hand, so they can be undone.
+ */
+export function useAutomation() {
+  const boardStore = useBoardStore()
+  const persistence = usePersistence()
+
+  const requireElement = (id: string) => {
+    const element = boardStore.elements.find((candidate) => candidate.id === id)
+    if (!element) throw new Error(`no element ${id}`)
+    return element
+  }
+
+  const handlers: Record<string, Handler> = {
+    'board.document': () => boardStore.getDocument(),
+    'board.open': async (params) => {
+      const path = requireString(params, 'path')
+      if (!(await persistence.openDocument(path))) throw new Error(`could not open ${path}`)
+      return null
+    },
+    'board.save': async () => {
+      if (!(await persistence.saveDocument())) throw new Error('could not save the board')
+      return persistence.currentFilePath.value
+    },
+    'elements.list': () => boardStore.elements,
+    'elements.create': (params) => {
+      const element = params.element as Omit<CanvasElement, 'id' | 'zIndex'> | undefined
+      if (!element || typeof element.type !== 'string' || !element.rect) {
+        throw new Error('element needs a type and a rect')
+      }
+      return boardStore.addElement(element)
+    },
+    'elements.update': (params) => {
+      const element = requireElement(requireString(params, 'id'))
+      const changes = { ...((params.changes ?? {}) as Partial<CanvasElement>) }
+      delete changes.id
+      delete changes.zIndex
+      boardStore.captureHistorySnapshot(`Updated ${element.type}`)
+      boardStore.updateElement(element.id, changes)
+      return null
+    },
+    'elements.move': (params) => {
+      const element = requireElement(requireString(params, 'id'))
+      const { x, y } = params
+      if (typeof x !== 'number' || typeof y !== 'number') throw new Error('x and y must be numbers')
+      boardStore.captureHistorySnapshot('Moved element')
+      boardStore.moveElement(element.id, x, y)
+      return null
+    },
+    'elements.delete': (params) => {
+      requireIds(params).forEach((id) => boardStore.removeElement(requireElement(id).id))
+      return null
+    },
+    'elements.group': (params) => {
+      const groupId = boardStore.createGroup(requireIds(params))
+      if (!groupId) throw new Error('needs at least two ungrouped elements')
+      return groupId
+    },
+    'elements.ungroup': (params) => {
+      if (!boardStore.ungroup(requireString(params, 'groupId'))) throw new Error('no such group')
+      return null
+    },
+    'selection.set': (params) => {
+      const ids = requireIds(params)
+      ids.forEach(requireElement)
+      boardStore.selectMultiple(ids.map((id) => ({ type: 'element' as const, id })))
+      return null
+    },
+  }
+
+  const handle = async ({ id, method, params }: AutomationRequest) => {
+    try {
+      const handler = handlers[method]
+      if (!handler) throw new Error(`unknown method ${method}`)
+      const result = await handler(params ?? {})
+      // Round-trip through JSON so reactive proxies serialize as plain data
+      await invoke('automation_respond', { id, result: JSON.parse(JSON.stringify(result ?? null)), error: null })
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error)
+      await invoke('automation_respond', { id, result: null, error: message })
+    }
+  }
+
+  const initialize = async (): Promise<UnlistenFn> =>
+    getCurrentWindow().listen<AutomationRequest>('automation-request', (event) => {
+      handle(event.payload).catch((error) => console.error('[Automation] Failed to answer a request:', error))
+    })
+
+  return {
+    initialize,
+  }
+}
@@ -0,0 +1,115 @@
+import { ref, watch } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { useTheme } from '@boardkit/ui'
+
+export interface ExportPreset {
+  name: string
+  format: 'png' | 'svg' | 'pdf'
+  scale: number
+  background: boolean
+  selectionOnly: boolean
+  /** Printed on every PDF page; header and footer may use {title}, {page} and {pages}. */
+  branding?: PdfBranding
+}
+
+/** Header, footer and logo (an asset store hash) of branded PDF exports. */
+export interface PdfBranding {
+  header?: string | null
+  footer?: string | null
+  logo?: string | null
+}
+
+/** Downscaling and re-encoding applied to pasted and dropped images. */
+export interface ImageOptimization {
+  enabled: boolean
+  maxDimension: number
+  format: 'png' | 'webp'
+}
+
+/**
+ * App settings owned by the native side (settings.json). Every window gets
+ * `settings-changed` when any of them updates a value.
+ */
+export interface NativeSettings {
+  theme: 'light' | 'dark' | 'system'
+  autosaveIntervalSecs: number
+  exportDpi: number
+  exportPresets: ExportPreset[]
+  imageOptimization: ImageOptimization
+  globalShortcuts: Record<string, string> | null
+  boardsDirectory: string | null
+  trashRetentionDays: number
+  notificationsEnabled: boolean
+  automationEnabled: boolean
+  restoreSession: boolean
+  /** Language of the native menus, e.g. `fr`; `null` follows the system. */
+  language: string | null
+}
+
+export type NativeSettingsPatch = Partial<
+  Pick<
+    NativeSettings,
+    | 'theme'
+    | 'autosaveIntervalSecs'
+    | 'exportDpi'
+    | 'exportPresets'
+    | 'imageOptimization'
+    | 'boardsDirectory'
+    | 'trashRetentionDays'
+    | 'notificationsEnabled'
+    | 'automationEnabled'
+    | 'restoreSession'
+  >
+>
+
+const settings = ref<NativeSettings | null>(null)
+
+export function useNativeSettings() {
+  const { theme, setTheme } = useTheme()
+
+  const apply = (next: NativeSettings) => {
+    settings.value = next
+    if (theme.value !== next.theme) {
+      setTheme(next.theme)
+    }
+  }
+
+  const update = async (patch: NativeSettingsPatch) => {
+    apply(await invoke<NativeSettings>('update_settings', { patch }))
+  }
+
+  /** Rebuild the native menus in `lang`. Returns the language they are shown in. */
+  const setLanguage = (lang: string | null) => invoke<string>('set_app_language', { lang })
+
+  /** Languages the native menus are translated into. */
+  const languages = () => invoke<string[]>('get_app_languages')
+
+  /**
+   * Load settings and keep them in sync. The theme picker still writes
+   * through useTheme; changes are forwarded to the native store.
+   */
+  const initialize = async (): Promise<UnlistenFn> => {
+    apply(await invoke<NativeSettings>('get_settings'))
+
+    const stopWatch = watch(theme, (next) => {
+      if (settings.value && settings.value.theme !== next) {
+        update({ theme: next }).catch((error) => console.error('Failed to save theme:', error))
+      }
+    })
+    const unlisten = await listen<NativeSettings>('settings-changed', (event) => apply(event.payload))
+
+    return () => {
+      stopWatch()
+      unlisten()
+    }
+  }
+
+  return {
+    settings,
+    initialize,
+    update,
+    setLanguage,
+    languages,
+  }
+}
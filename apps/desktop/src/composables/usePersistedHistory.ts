@@ -0,0 +1,77 @@
+import { invoke } from '@tauri-apps/api/core'
+import { useBoardStore, type BoardkitDocument, type HistoryEntry } from '@boardkit/core'
+
+// Wait for a pause in saving, as logging every autosave would rewrite the log
+const PERSIST_DELAY = 2000
+
+interface PersistedStep {
+  id: string
+  label: string
+  timestamp: number
+  snapshot: BoardkitDocument
+}
+
+// Steps the log on disk already holds a snapshot of
+let persistedIds = new Set<string>()
+let pending: { path: string; updatedAt: number } | null = null
+let timer: ReturnType<typeof setTimeout> | null = null
+
+/**
+ * Undo history that survives closing a board: the board store's stack is
+ * written next to the board after saves (see undo.rs) and restored when the
+ * board is opened again.
+ */
+export function usePersistedHistory() {
+  const boardStore = useBoardStore()
+
+  async function persist(path: string, updatedAt: number) {
+    // Steps already undone are redo steps, which don't outlive the session
+    const entries = boardStore.historyStack.slice(boardStore.historyIndex + 1)
+    const steps = entries.map(({ id, label, timestamp, snapshot }) => ({
+      id,
+      label,
+      timestamp,
+      snapshot: persistedIds.has(id) ? null : snapshot,
+    }))
+    persistedIds = new Set(await invoke<string[]>('persist_history', { path, updatedAt, steps }))
+  }
+
+  /** Write the history of the board just saved, once saving settles. */
+  function schedule(path: string, updatedAt: number) {
+    pending = { path, updatedAt }
+    if (timer) clearTimeout(timer)
+    timer = setTimeout(() => {
+      flush().catch((error) => console.warn('[PersistedHistory] Failed to persist undo history:', error))
+    }, PERSIST_DELAY)
+  }
+
+  /** Write a scheduled history now, before the board store moves on to another board. */
+  async function flush() {
+    if (timer) clearTimeout(timer)
+    timer = null
+    const target = pending
+    pending = null
+    if (target) await persist(target.path, target.updatedAt)
+  }
+
+  /** Load the history the last session left for the board just opened. */
+  async function restore(path: string, updatedAt: number) {
+    persistedIds = new Set()
+    let entries: HistoryEntry[] = []
+    try {
+      // Every step with its snapshot, from a single read of the log
+      entries = await invoke<PersistedStep[]>('get_persisted_history', { path, updatedAt })
+      persistedIds = new Set(entries.map((entry) => entry.id))
+    } catch (error) {
+      console.warn('[PersistedHistory] Failed to restore undo history:', error)
+      entries = []
+    }
+    boardStore.restoreHistory(entries)
+  }
+
+  return {
+    schedule,
+    flush,
+    restore,
+  }
+}
@@ -2,7 +2,10 @@ import { ref, computed, watch } from 'vue'
 import { useDebounceFn, useDocumentVisibility, useStorage } from '@vueuse/core'
 import { useBoardStore, useAssetStore, type BoardkitDocument } from '@boardkit/core'
 import { mkdir, exists } from '@tauri-apps/plugin-fs'
+import { invoke } from '@tauri-apps/api/core'
 import { useVault } from './useVault'
+import { usePersistedHistory } from './usePersistedHistory'
+import { usePowerState } from './usePowerState'
 import {
   saveToFile,
   openFromFile,
@@ -12,15 +15,35 @@ import {
   loadHistoryEntry,
   clearHistory,
   type HistoryEntry,
+  type SaveProgress,
 } from '../utils/boardkitFile'
 
 const AUTOSAVE_DELAY = 500 // 500ms debounce for fast saving
+// Unsaved state goes to the native recovery directory at most this often
+const RECOVERY_DELAY = 1000
+const RECOVERY_MAX_WAIT = 5000
+// Recovery key of a board that has no file yet
+const UNSAVED_RECOVERY_KEY = 'unsaved'
+// Saves quicker than this finish before their progress is shown
+const SAVE_PROGRESS_DELAY = 400
 const CURRENT_FILE_KEY = 'boardkit:current-file-path'
 
+/** Unsaved changes a crashed session left in the recovery directory. */
+export interface RecoveredBoard {
+  boardId: string
+  sourcePath: string | null
+  savedAt: number
+  title: string | null
+}
+
 // Shared state
 const currentFilePath = useStorage<string | null>(CURRENT_FILE_KEY, null)
 const isLoading = ref(false)
 const isSaving = ref(false)
+// Progress of a save that is taking a while, from the native save worker
+const saveProgress = ref<SaveProgress | null>(null)
+// A board whose elements are still arriving must not be saved half loaded
+const isStreaming = ref(false)
 const lastSaved = ref<number | null>(null)
 
 // History state
@@ -30,6 +53,8 @@ const currentHistoryIndex = ref<number>(-1) // -1 means we're at the latest vers
 export function usePersistence() {
   const boardStore = useBoardStore()
   const vault = useVault()
+  const persistedHistory = usePersistedHistory()
+  const { autosavePaused } = usePowerState()
   let stopWatch: (() => void) | null = null
 
   // Track document visibility to pause autosave when window is not visible
@@ -37,11 +62,35 @@ export function usePersistence() {
 
   // Debounced autosave function using VueUse
   const debouncedSave = useDebounceFn(async () => {
-    if (boardStore.isDirty && currentFilePath.value && visibility.value === 'visible') {
+    const canSave = visibility.value === 'visible' && !isStreaming.value && !autosavePaused.value
+    if (boardStore.isDirty && currentFilePath.value && canSave) {
       await saveDocument(true)
     }
   }, AUTOSAVE_DELAY)
 
+  // Hand unsaved changes to the native autosave, which writes them to the
+  // recovery directory so they survive a crash
+  const queueRecovery = useDebounceFn(
+    () => {
+      const doc = boardStore.getDocument()
+      if (!doc || !boardStore.isDirty || isStreaming.value) return
+      invoke('queue_autosave', {
+        boardId: currentFilePath.value ?? UNSAVED_RECOVERY_KEY,
+        sourcePath: currentFilePath.value,
+        document: doc,
+      }).catch((error) => console.warn('Failed to queue crash recovery:', error))
+    },
+    RECOVERY_DELAY,
+    { maxWait: RECOVERY_MAX_WAIT }
+  )
+
+  // Saved changes no longer need recovering
+  const clearRecovery = (filePath: string) => {
+    invoke('clear_autosave', { boardId: filePath }).catch((error) => {
+      console.warn('Failed to clear crash recovery:', error)
+    })
+  }
+
   // Actions to hide from history display (low-value, high-frequency)
   const hiddenActions = ['initial state', 'moved widget', 'resized widget']
 
@@ -257,20 +306,81 @@ export function usePersistence() {
     }
   }
 
+  // Create a document in the vault as a copy of a template, assets included
+  async function createDocumentFromTemplate(templateId: string): Promise<string | null> {
+    if (!vault.isConfigured.value || !vault.vaultPath.value) {
+      console.error('createDocumentFromTemplate: Vault not configured')
+      return null
+    }
+
+    try {
+      const filePath = await invoke<string>('create_board_from_template', {
+        id: templateId,
+        dir: vault.vaultPath.value,
+      })
+      await vault.scanVaultFiles()
+      return (await openDocument(filePath)) ? filePath : null
+    } catch (error) {
+      console.error('createDocumentFromTemplate: Failed to create document', error)
+      return null
+    }
+  }
+
+  // Show the board as soon as its document is parsed; elements fill in as
+  // they arrive, those in view first
+  async function streamDocument(filePath: string): Promise<boolean> {
+    let started = false
+    try {
+      const streamed = await vault.loadFileStreaming(
+        filePath,
+        {
+          onStarted: (doc) => {
+            started = true
+            isStreaming.value = true
+            boardStore.loadDocument(doc)
+            currentFilePath.value = filePath
+            lastSaved.value = doc.meta.updatedAt
+            boardStore.markClean()
+            isLoading.value = false
+            const assetStore = useAssetStore()
+            vault.loadBoardAssets(filePath, doc).then((blobs) => assetStore.loadAssets(blobs))
+          },
+          onElements: (elements) => boardStore.insertLoadedElements(elements),
+        },
+        { width: window.innerWidth, height: window.innerHeight }
+      )
+      return streamed || started
+    } finally {
+      isStreaming.value = false
+      // Edits made while loading were held back
+      if (started && boardStore.isDirty) debouncedSave()
+    }
+  }
+
   // Open a document from the vault
   async function openDocument(filePath: string): Promise<boolean> {
     isLoading.value = true
     try {
-      const doc = await vault.loadFile(filePath)
-      if (!doc) {
-        isLoading.value = false
-        return false
+      // The undo steps of the board being left go to its own log
+      await persistedHistory.flush().catch((error) => console.warn('Failed to persist undo history:', error))
+      if (!(await streamDocument(filePath))) {
+        const doc = await vault.loadFile(filePath)
+        if (!doc) {
+          isLoading.value = false
+          return false
+        }
+
+        boardStore.loadDocument(doc)
+        currentFilePath.value = filePath
+        lastSaved.value = doc.meta.updatedAt
+        boardStore.markClean()
       }
+      await persistedHistory.restore(filePath, lastSaved.value ?? 0)
 
-      boardStore.loadDocument(doc)
-      currentFilePath.value = filePath
-      lastSaved.value = doc.meta.updatedAt
-      boardStore.markClean()
+      // Keep the native Open Recent menu in sync
+      invoke('add_recent_file', { path: filePath }).catch((error) => {
+        console.warn('Failed to update recent files:', error)
+      })
 
       // Load history for this document
       await refreshHistoryEntries()
@@ -285,9 +395,30 @@ export function usePersistence() {
     }
   }
 
+  // Write through the vault, showing the worker's progress once it takes a while
+  async function writeDocument(path: string, doc: BoardkitDocument): Promise<boolean> {
+    let latest: SaveProgress = { path, stage: 'compressing', progress: 0 }
+    let shown = false
+    const timer = setTimeout(() => {
+      shown = true
+      saveProgress.value = latest
+    }, SAVE_PROGRESS_DELAY)
+    try {
+      return await vault.saveFile(path, doc, {
+        onProgress: (progress) => {
+          latest = progress
+          if (shown) saveProgress.value = progress
+        },
+      })
+    } finally {
+      clearTimeout(timer)
+      saveProgress.value = null
+    }
+  }
+
   // Internal save (doesn't add to history)
   async function saveDocumentInternal(): Promise<boolean> {
-    if (!currentFilePath.value || !vault.isConfigured.value) return false
+    if (!currentFilePath.value || !vault.isConfigured.value || isStreaming.value) return false
 
     const doc = boardStore.getDocument()
     if (!doc) return false
@@ -295,10 +426,12 @@ export function usePersistence() {
     isSaving.value = true
     try {
       doc.meta.updatedAt = Date.now()
-      const success = await vault.saveFile(currentFilePath.value, doc)
+      const success = await writeDocument(currentFilePath.value, doc)
       if (success) {
         boardStore.markClean()
         lastSaved.value = doc.meta.updatedAt
+        persistedHistory.schedule(currentFilePath.value, doc.meta.updatedAt)
+        clearRecovery(currentFilePath.value)
       }
       return success
     } catch (error) {
@@ -311,7 +444,7 @@ export function usePersistence() {
 
   // Save the current document (with optional history)
   async function saveDocument(addHistory = true): Promise<boolean> {
-    if (!currentFilePath.value || !vault.isConfigured.value) return false
+    if (!currentFilePath.value || !vault.isConfigured.value || isStreaming.value) return false
 
     const doc = boardStore.getDocument()
     if (!doc) return false
@@ -319,11 +452,13 @@ export function usePersistence() {
     isSaving.value = true
     try {
       doc.meta.updatedAt = Date.now()
-      const success = await vault.saveFile(currentFilePath.value, doc)
+      const success = await writeDocument(currentFilePath.value, doc)
       if (!success) return false
 
       boardStore.markClean()
       lastSaved.value = doc.meta.updatedAt
+      persistedHistory.schedule(currentFilePath.value, doc.meta.updatedAt)
+      clearRecovery(currentFilePath.value)
 
       // Add to history if requested
       const skipActions = ['Initial state', 'Moved widget', 'Resized widget', 'Moved element', 'Resized element']
@@ -422,15 +557,26 @@ export function usePersistence() {
       stopWatch()
     }
 
-    // Watch dirty state
-    stopWatch = watch(
-      () => boardStore.isDirty,
-      (isDirty) => {
-        if (isDirty && currentFilePath.value && vault.isConfigured.value) {
+    // Watch dirty state, and catch up once autosave is no longer paused for power
+    const stopSaving = watch(
+      () => [boardStore.isDirty, autosavePaused.value] as const,
+      ([isDirty, paused]) => {
+        if (isDirty && !paused && currentFilePath.value && vault.isConfigured.value) {
           debouncedSave()
         }
       }
     )
+    // Every edit bumps updatedAt; keep the recovery copy current until it is saved
+    const stopRecovery = watch(
+      () => boardStore.document?.meta.updatedAt,
+      () => {
+        if (boardStore.isDirty) queueRecovery()
+      }
+    )
+    stopWatch = () => {
+      stopSaving()
+      stopRecovery()
+    }
 
     // Save immediately if already dirty
     if (boardStore.isDirty && currentFilePath.value && vault.isConfigured.value) {
@@ -438,9 +584,48 @@ export function usePersistence() {
     }
   }
 
+  // Commit the edit in progress and save before the system sleeps, even
+  // while autosave is paused
+  async function saveBeforeSleep(): Promise<void> {
+    if (document.activeElement instanceof HTMLElement) {
+      document.activeElement.blur()
+    }
+    if (boardStore.isDirty && currentFilePath.value) {
+      await saveDocument(true)
+    }
+    await persistedHistory.flush()
+  }
+
+  // Bring back the unsaved changes of a crashed session: over the board they
+  // belong to when it is still there, otherwise as a new board
+  async function restoreRecoveredBoard(recovered: RecoveredBoard): Promise<boolean> {
+    try {
+      const doc = await invoke<BoardkitDocument>('restore_recovered_board', { boardId: recovered.boardId })
+      if (recovered.sourcePath && (await openDocument(recovered.sourcePath))) {
+        boardStore.loadDocument(doc)
+        // Saving writes the changes back to the board, which clears the recovery file
+        boardStore.markDirty('Restored unsaved changes')
+        return true
+      }
+      const imported = await importDocument(doc)
+      if (imported) {
+        await invoke('clear_autosave', { boardId: recovered.boardId })
+      }
+      return imported
+    } catch (error) {
+      console.error('Failed to restore recovered board:', error)
+      return false
+    }
+  }
+
   // Setup file watching for external changes
-  function setupFileWatching(onExternalChange: (doc: BoardkitDocument) => void): void {
-    vault.startWatching(onExternalChange)
+  function setupFileWatching(
+    onExternalChange: (doc: BoardkitDocument) => void,
+    onExternalDelete?: (path: string) => void
+  ): void {
+    vault.startWatching(onExternalChange, onExternalDelete).catch((error) => {
+      console.error('Failed to watch the vault for external changes:', error)
+    })
   }
 
   // Stop file watching
@@ -494,6 +679,31 @@ export function usePersistence() {
     }
   }
 
+  // Save a board converted natively from another format as a new vault file and open it
+  async function importDocument(document: BoardkitDocument): Promise<boolean> {
+    if (!vault.isConfigured.value) return false
+
+    try {
+      isLoading.value = true
+      const filePath = await vault.createFile(document.meta.title, document)
+      if (!filePath) return false
+
+      boardStore.loadDocument(document)
+      currentFilePath.value = filePath
+      lastSaved.value = Date.now()
+      boardStore.markClean()
+
+      await refreshHistoryEntries()
+
+      return true
+    } catch (error) {
+      console.error('Failed to import document:', error)
+      return false
+    } finally {
+      isLoading.value = false
+    }
+  }
+
   // Delete a document from the vault
   async function deleteDocument(filePath: string): Promise<boolean> {
     const success = await vault.deleteFile(filePath)
@@ -566,6 +776,7 @@ export function usePersistence() {
     currentFilePath,
     isLoading,
     isSaving,
+    saveProgress,
     lastSaved,
     historyEntries,
     currentHistoryIndex,
@@ -581,16 +792,20 @@ export function usePersistence() {
     initialize,
     onVaultSetup,
     createDocument,
+    createDocumentFromTemplate,
     openDocument,
     saveDocument,
     deleteDocument,
     renameDocument,
     duplicateDocument,
     setupAutosave,
+    saveBeforeSleep,
+    restoreRecoveredBoard,
     setupFileWatching,
     stopFileWatching,
     exportToFile,
     importFromFile,
+    importDocument,
 
     // History Actions
     undo,
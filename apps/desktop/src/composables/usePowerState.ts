@@ -0,0 +1,41 @@
+import { computed, ref } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+
+export interface PowerState {
+  lowPower: boolean
+  suspended: boolean
+}
+
+const state = ref<PowerState>({ lowPower: false, suspended: false })
+
+/** Autosave holds back on battery saver and while the system sleeps. */
+const autosavePaused = computed(() => state.value.lowPower || state.value.suspended)
+
+/**
+ * The system's power state as power.rs follows it, and the moment before
+ * the system sleeps.
+ */
+export function usePowerState() {
+  /** Follow the power state, calling `onWillSleep` to commit work before sleep. */
+  const initialize = async (onWillSleep: () => Promise<void>): Promise<UnlistenFn> => {
+    state.value = await invoke<PowerState>('get_power_state')
+    const unlistenState = await listen<PowerState>('power-state-changed', (event) => {
+      state.value = event.payload
+    })
+    const unlistenSleep = await listen('system-will-sleep', () => {
+      onWillSleep().catch((error) => console.error('[Power] Failed to save before sleep:', error))
+    })
+
+    return () => {
+      unlistenState()
+      unlistenSleep()
+    }
+  }
+
+  return {
+    state,
+    autosavePaused,
+    initialize,
+  }
+}
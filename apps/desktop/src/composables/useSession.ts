@@ -0,0 +1,47 @@
+import { watch } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { useBoardStore, type Viewport } from '@boardkit/core'
+
+// Panning fires on every frame; only where it settles matters
+const SAVE_DELAY = 1000
+
+let timer: ReturnType<typeof setTimeout> | null = null
+
+/**
+ * The window's part in the session the next launch reopens (see session.rs):
+ * where its board is scrolled and zoomed to, saved as it changes and put
+ * back once the restored board is loaded.
+ */
+export function useSession() {
+  const boardStore = useBoardStore()
+
+  /** Save the viewport whenever it settles. Returns a function to stop. */
+  function trackViewport() {
+    return watch(
+      () => ({ ...boardStore.viewport }),
+      (viewport) => {
+        if (timer) clearTimeout(timer)
+        timer = setTimeout(() => {
+          invoke('save_viewport', { viewport }).catch((error) => {
+            console.warn('[Session] Failed to save the viewport:', error)
+          })
+        }, SAVE_DELAY)
+      }
+    )
+  }
+
+  /** Put the board just opened from the last session back where it was left. */
+  async function restoreViewport(path: string) {
+    try {
+      const viewport = await invoke<Viewport | null>('take_session_viewport', { path })
+      if (viewport) boardStore.updateViewport(viewport)
+    } catch (error) {
+      console.warn('[Session] Failed to restore the viewport:', error)
+    }
+  }
+
+  return {
+    trackViewport,
+    restoreViewport,
+  }
+}
@@ -0,0 +1,25 @@
+import { ref, computed } from 'vue'
+
+const state = ref({ open: false })
+
+/**
+ * Open state of the share dialog, shared between the command palette action
+ * and the modal mounted in App.vue.
+ */
+export function useShareBoard() {
+  const isOpen = computed(() => state.value.open)
+
+  const open = () => {
+    state.value = { open: true }
+  }
+
+  const close = () => {
+    state.value = { open: false }
+  }
+
+  return {
+    isOpen,
+    open,
+    close,
+  }
+}
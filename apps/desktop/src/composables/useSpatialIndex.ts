@@ -0,0 +1,141 @@
+import { computed, onScopeDispose, ref, watch, type Ref } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { useBoardStore, type CanvasElement } from '@boardkit/core'
+
+/** Below this many elements, scanning them in JS beats a round trip. */
+export const NATIVE_INDEX_THRESHOLD = 2000
+
+export interface CanvasRect {
+  x: number
+  y: number
+  width: number
+  height: number
+}
+
+interface ElementBounds extends CanvasRect {
+  id: string
+  angle: number
+  zIndex: number
+}
+
+type SpatialOp =
+  | { op: 'reset'; elements: ElementBounds[] }
+  | { op: 'upsert'; elements: ElementBounds[] }
+  | { op: 'remove'; ids: string[] }
+
+const toBounds = (element: CanvasElement): ElementBounds => ({
+  id: element.id,
+  x: element.rect.x,
+  y: element.rect.y,
+  width: element.rect.width,
+  height: element.rect.height,
+  angle: element.angle ?? 0,
+  zIndex: element.zIndex,
+})
+
+const signature = (bounds: ElementBounds) =>
+  `${bounds.x},${bounds.y},${bounds.width},${bounds.height},${bounds.angle},${bounds.zIndex}`
+
+/**
+ * Keeps the native R-tree of element bounds in step with the board, and
+ * queries it for the elements inside `viewport`. Only active on boards with
+ * enough elements for it to pay off; `visibleIds` is null otherwise.
+ */
+export function useSpatialIndex(viewport: Ref<CanvasRect>) {
+  const boardStore = useBoardStore()
+  const isActive = computed(() => boardStore.elements.length >= NATIVE_INDEX_THRESHOLD)
+
+  /** Ids of the elements in view, in stacking order */
+  const visibleIds = ref<string[] | null>(null)
+
+  // Signature of each indexed element, to send only what moved
+  let indexed = new Map<string, string>()
+  let indexedDocument: unknown = null
+  let needsSync = true
+  let frame = 0
+  let generation = 0
+
+  const sync = async () => {
+    const elements = boardStore.elements
+    const current = new Map<string, string>()
+    const all: ElementBounds[] = []
+    const changed: ElementBounds[] = []
+    for (const element of elements) {
+      const bounds = toBounds(element)
+      const key = signature(bounds)
+      current.set(element.id, key)
+      all.push(bounds)
+      if (indexed.get(element.id) !== key) changed.push(bounds)
+    }
+
+    const ops: SpatialOp[] = []
+    if (indexedDocument !== boardStore.document) {
+      ops.push({ op: 'reset', elements: all })
+    } else {
+      const removed = [...indexed.keys()].filter((id) => !current.has(id))
+      if (removed.length > 0) ops.push({ op: 'remove', ids: removed })
+      if (changed.length > 0) ops.push({ op: 'upsert', elements: changed })
+    }
+    indexed = current
+    indexedDocument = boardStore.document
+    if (ops.length > 0) await invoke('update_spatial_index', { ops })
+  }
+
+  const update = async () => {
+    frame = 0
+    if (!isActive.value) {
+      visibleIds.value = null
+      indexedDocument = null
+      return
+    }
+    const request = ++generation
+    try {
+      if (needsSync) {
+        needsSync = false
+        await sync()
+      }
+      const ids = await invoke<string[]>('query_viewport', { rect: viewport.value })
+      // Drop answers overtaken by a newer query
+      if (request === generation && isActive.value) visibleIds.value = ids
+    } catch (error) {
+      console.error('[useSpatialIndex] Query failed:', error)
+      visibleIds.value = null
+    }
+  }
+
+  // At most one round trip per frame, however many changes came in
+  const schedule = () => {
+    if (!frame) frame = requestAnimationFrame(() => void update())
+  }
+  const invalidate = () => {
+    needsSync = true
+    schedule()
+  }
+
+  const stopActions = boardStore.$onAction(({ after }) => after(invalidate))
+  watch(() => boardStore.document, invalidate)
+  watch(isActive, invalidate, { immediate: true })
+  watch(viewport, () => {
+    if (isActive.value) schedule()
+  })
+
+  onScopeDispose(() => {
+    stopActions()
+    cancelAnimationFrame(frame)
+  })
+
+  /**
+   * Ids of the elements under a canvas point, topmost first, or null when
+   * the native index is off and callers should test in JS.
+   */
+  async function hitTest(point: { x: number; y: number }, tolerance = 0): Promise<string[] | null> {
+    if (!isActive.value) return null
+    return await invoke<string[]>('hit_test', { point, tolerance })
+  }
+
+  return {
+    isActive,
+    visibleIds,
+    hitTest,
+  }
+}
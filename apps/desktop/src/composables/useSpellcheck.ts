@@ -0,0 +1,109 @@
+import { computed, ref, watch, type Ref } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { useToast } from '@boardkit/ui'
+
+/** A misspelled word; offsets index the checked string. */
+export interface Misspelling {
+  word: string
+  start: number
+  end: number
+}
+
+export interface SpellingSegment {
+  text: string
+  misspelled: boolean
+}
+
+const CHECK_DELAY = 250
+
+// Shared across editors: words ignored for this session, and locales without a dictionary
+const ignoredWords = new Set<string>()
+const missingDictionaries = new Set<string>()
+
+/**
+ * Native spell checking for a text being edited. Checks run in Rust against
+ * Hunspell dictionaries, since the webview's own spellcheck differs per
+ * platform and can't be styled.
+ */
+export function useSpellcheck(text: Ref<string>, enabled: Ref<boolean>, lang = navigator.language) {
+  const toaster = useToast()
+  const misspellings = ref<Misspelling[]>([])
+  let timer: ReturnType<typeof setTimeout> | undefined
+  let generation = 0
+
+  const check = async () => {
+    const checked = text.value
+    const current = ++generation
+    if (!enabled.value || !checked.trim() || missingDictionaries.has(lang)) {
+      misspellings.value = []
+      return
+    }
+    try {
+      const found = await invoke<Misspelling[]>('check_text', { text: checked, lang })
+      // Ignore results for text that has changed since
+      if (current === generation) {
+        misspellings.value = found.filter((misspelling) => !ignoredWords.has(misspelling.word))
+      }
+    } catch (error) {
+      // Said once per language; the message names the folder to put one in
+      if ((error as { kind?: string }).kind === 'missing_dictionary') {
+        missingDictionaries.add(lang)
+        toaster.info((error as { message: string }).message, {
+          title: 'Spell checking unavailable',
+          duration: 10000,
+        })
+      } else {
+        console.error('[useSpellcheck] Check failed:', error)
+      }
+      misspellings.value = []
+    }
+  }
+
+  watch(
+    [text, enabled],
+    () => {
+      clearTimeout(timer)
+      timer = setTimeout(check, CHECK_DELAY)
+    },
+    { immediate: true }
+  )
+
+  /** The text split into runs, for drawing squiggles under misspelled ones. */
+  const segments = computed((): SpellingSegment[] => {
+    const result: SpellingSegment[] = []
+    let position = 0
+    for (const { start, end } of misspellings.value) {
+      if (start < position || end > text.value.length) continue
+      if (start > position) result.push({ text: text.value.slice(position, start), misspelled: false })
+      result.push({ text: text.value.slice(start, end), misspelled: true })
+      position = end
+    }
+    // A trailing space keeps a final empty line the same height as in the textarea
+    result.push({ text: text.value.slice(position) + ' ', misspelled: false })
+    return result
+  })
+
+  const misspellingAt = (offset: number): Misspelling | null =>
+    misspellings.value.find((misspelling) => offset >= misspelling.start && offset <= misspelling.end) ?? null
+
+  const suggest = (word: string) => invoke<string[]>('suggest', { word, lang })
+
+  const addToDictionary = async (word: string) => {
+    await invoke('add_to_dictionary', { word })
+    await check()
+  }
+
+  const ignore = (word: string) => {
+    ignoredWords.add(word)
+    misspellings.value = misspellings.value.filter((misspelling) => misspelling.word !== word)
+  }
+
+  return {
+    misspellings,
+    segments,
+    misspellingAt,
+    suggest,
+    addToDictionary,
+    ignore,
+  }
+}
@@ -1,9 +1,10 @@
-import { ref, computed, shallowRef } from 'vue'
+import { ref, computed, shallowRef, reactive } from 'vue'
 import { useStorage } from '@vueuse/core'
+import { invoke, Channel } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
 import { open } from '@tauri-apps/plugin-dialog'
 import {
   readDir,
-  readFile,
   writeFile,
   rename,
   remove,
@@ -13,14 +14,14 @@ import {
 } from '@tauri-apps/plugin-fs'
 import {
   type BoardkitDocument,
+  type CanvasElement,
   validateDocument,
   DocumentValidationError,
   useAssetStore,
 } from '@boardkit/core'
-import { exportBoardkit, importBoardkit } from '../utils/boardkitFile'
+import { exportBoardkit, importBoardkit, saveBoardNative, type SaveHandlers } from '../utils/boardkitFile'
 
 const VAULT_PATH_KEY = 'boardkit:vault-path'
-const WATCH_INTERVAL = 2000 // 2 seconds
 
 export interface VaultFile {
   name: string           // filename without extension
@@ -28,13 +29,82 @@ export interface VaultFile {
   modifiedAt: number     // mtime timestamp
 }
 
+export interface StreamingLoadHandlers {
+  /** The board without its elements; `totalElements` follow */
+  onStarted: (document: BoardkitDocument, totalElements: number) => void
+  /** Elements with their position in the saved element list */
+  onElements: (elements: Array<{ index: number; element: CanvasElement }>) => void
+}
+
+type StreamingLoadEvent =
+  | { event: 'started'; data: { document: BoardkitDocument; totalElements: number } }
+  | { event: 'elements'; data: { elements: Array<{ index: number; element: CanvasElement }> } }
+  | { event: 'finished' }
+
 // Shared state across all composable instances
 const vaultPath = useStorage<string | null>(VAULT_PATH_KEY, null)
 const files = shallowRef<VaultFile[]>([])
 const activeFilePath = ref<string | null>(null)
 const isWatching = ref(false)
 const lastKnownMtime = ref<number | null>(null)
-let watchInterval: ReturnType<typeof setInterval> | null = null
+const watchUnlisteners: UnlistenFn[] = []
+// Boards stored encrypted on disk; saves keep them encrypted with their cached key
+const encryptedFiles = reactive(new Set<string>())
+
+export interface PasswordPromptOptions {
+  title: string
+  description?: string
+  submitLabel?: string
+}
+
+// Supplied by a component under BkModalProvider, since modals need inject()
+let passwordPrompt: ((options: PasswordPromptOptions) => Promise<string | null>) | null = null
+
+export function setPasswordPrompt(prompt: typeof passwordPrompt): void {
+  passwordPrompt = prompt
+}
+
+function errorKind(error: unknown): string | undefined {
+  return (error as { kind?: string } | null)?.kind
+}
+
+/**
+ * Read a board through the native loader. Encrypted boards are unlocked with
+ * their cached key, or with a password from the user when none is cached.
+ * Returns null if the user cancels the password prompt.
+ */
+async function readBoard(filePath: string): Promise<Uint8Array | null> {
+  try {
+    const data = await invoke<ArrayBuffer>('load_board', { path: filePath })
+    encryptedFiles.delete(filePath)
+    return new Uint8Array(data)
+  } catch (error) {
+    if (errorKind(error) !== 'password_required') throw error
+  }
+
+  let password: string | null = null
+  for (;;) {
+    let wrongPassword = false
+    try {
+      const data = await invoke<ArrayBuffer>('load_board_encrypted', { path: filePath, password })
+      encryptedFiles.add(filePath)
+      return new Uint8Array(data)
+    } catch (error) {
+      const kind = errorKind(error)
+      if (kind !== 'password_required' && kind !== 'wrong_password') throw error
+      wrongPassword = kind === 'wrong_password'
+    }
+    const name = filePath.split(/[/\\]/).pop()
+    password = passwordPrompt
+      ? await passwordPrompt({
+          title: wrongPassword ? 'Wrong password' : 'Encrypted board',
+          description: `Enter the password for ${name}`,
+          submitLabel: 'Unlock',
+        })
+      : null
+    if (password === null) return null
+  }
+}
 
 export function useVault() {
   // ============================================================================
@@ -194,13 +264,23 @@ export function useVault() {
   }
 
   /**
-   * Save document to a specific file path
+   * Save document to a specific file path. `handlers` follow the native save
+   * worker; encrypted boards are written in one go and report nothing.
    */
-  async function saveFile(filePath: string, document: BoardkitDocument): Promise<boolean> {
+  async function saveFile(
+    filePath: string,
+    document: BoardkitDocument,
+    handlers: SaveHandlers = {}
+  ): Promise<boolean> {
     try {
       const assetStore = useAssetStore()
-      const data = await exportBoardkit(document, assetStore.getAllBlobs())
-      await writeFileAtomically(filePath, data)
+      // Native save validates, writes atomically and updates history, thumbnails and search
+      if (encryptedFiles.has(filePath)) {
+        const data = await exportBoardkit(document, assetStore.getAllBlobs())
+        await invoke('save_board_encrypted', { path: filePath, payload: Array.from(data), password: null })
+      } else {
+        await saveBoardNative(filePath, await exportBoardkit(document, assetStore.getAllBlobs(), false), handlers)
+      }
 
       // Update mtime tracking
       const fileStat = await stat(filePath)
@@ -221,13 +301,19 @@ export function useVault() {
    */
   async function loadFile(filePath: string): Promise<BoardkitDocument | null> {
     try {
-      const data = await readFile(filePath)
+      // Native load migrates older documents to the current format
+      const data = await readBoard(filePath)
+      if (!data) return null
       const result = await importBoardkit(data)
 
       // Load imported assets into the asset store
       const assetStore = useAssetStore()
       await assetStore.loadAssets(result.assets)
 
+      // Let other windows save the board this window is leaving
+      if (activeFilePath.value && activeFilePath.value !== filePath) {
+        invoke('release_board_lock', { path: activeFilePath.value }).catch(() => {})
+      }
       activeFilePath.value = filePath
 
       // Track file modification time
@@ -244,12 +330,109 @@ export function useVault() {
     }
   }
 
+  /**
+   * Load a board progressively through the native streaming loader. The
+   * document arrives first with no elements, then the elements in batches,
+   * those in the visible area first. Resolves once every element arrived,
+   * or to false for encrypted boards, which need `loadFile`.
+   */
+  async function loadFileStreaming(
+    filePath: string,
+    handlers: StreamingLoadHandlers,
+    screen?: { width: number; height: number }
+  ): Promise<boolean> {
+    const channel = new Channel<StreamingLoadEvent>()
+    const finished = new Promise<void>((resolve) => {
+      channel.onmessage = (message) => {
+        switch (message.event) {
+          case 'started':
+            handlers.onStarted(message.data.document, message.data.totalElements)
+            break
+          case 'elements':
+            handlers.onElements(message.data.elements)
+            break
+          case 'finished':
+            resolve()
+            break
+        }
+      }
+    })
+
+    try {
+      await invoke('load_board_streaming', { path: filePath, bounds: null, screen: screen ?? null, onEvent: channel })
+    } catch (error) {
+      if (errorKind(error) === 'password_required') return false
+      throw error
+    }
+    await finished
+    encryptedFiles.delete(filePath)
+
+    if (activeFilePath.value && activeFilePath.value !== filePath) {
+      invoke('release_board_lock', { path: activeFilePath.value }).catch(() => {})
+    }
+    activeFilePath.value = filePath
+    const fileStat = await stat(filePath)
+    lastKnownMtime.value = fileStat.mtime?.getTime() ?? Date.now()
+    return true
+  }
+
+  /**
+   * Read the images of a streamed board one by one, straight from the file.
+   */
+  async function loadBoardAssets(filePath: string, document: BoardkitDocument): Promise<Map<string, Blob>> {
+    const blobs = new Map<string, Blob>()
+    await Promise.all(
+      Object.values(document.assets?.assets ?? {}).map(async (asset) => {
+        try {
+          const data = await invoke<ArrayBuffer>('read_board_asset', { path: filePath, assetId: asset.id })
+          blobs.set(asset.id, new Blob([data], { type: asset.mimeType }))
+        } catch (error) {
+          console.warn(`Failed to read asset ${asset.id}:`, error)
+        }
+      })
+    )
+    return blobs
+  }
+
+  function isEncrypted(filePath: string): boolean {
+    return encryptedFiles.has(filePath)
+  }
+
+  /**
+   * Ask the user for a new password, or null if they cancel.
+   */
+  async function promptPassword(options: PasswordPromptOptions): Promise<string | null> {
+    return passwordPrompt ? passwordPrompt(options) : null
+  }
+
+  /**
+   * Rewrite a board encrypted with `password`, or as a plain board when null.
+   */
+  async function setFileEncryption(
+    filePath: string,
+    document: BoardkitDocument,
+    password: string | null
+  ): Promise<void> {
+    const assetStore = useAssetStore()
+    if (password === null) {
+      await saveBoardNative(filePath, await exportBoardkit(document, assetStore.getAllBlobs(), false))
+      encryptedFiles.delete(filePath)
+    } else {
+      const payload = Array.from(await exportBoardkit(document, assetStore.getAllBlobs()))
+      await invoke('save_board_encrypted', { path: filePath, payload, password })
+      encryptedFiles.add(filePath)
+    }
+    const fileStat = await stat(filePath)
+    lastKnownMtime.value = fileStat.mtime?.getTime() ?? Date.now()
+  }
+
   /**
    * Delete a file from the vault
    */
   async function deleteFile(filePath: string): Promise<boolean> {
     try {
-      await remove(filePath)
+      // Moved to the app's trash rather than deleted, so it can be restored
+      await invoke('delete_board', { path: filePath })
 
       // If this was the active file, clear it
       if (activeFilePath.value === filePath) {
@@ -305,11 +488,6 @@ export function useVault() {
     if (!vaultPath.value) return null
 
     try {
-      // Load the original document
-      const data = await readFile(filePath)
-      const result = await importBoardkit(data)
-      const document = result.document
-
       // Find the original name and create a new name
       const originalFile = files.value.find((f) => f.path === filePath)
       const baseName = originalFile?.name || 'Untitled'
@@ -322,20 +500,12 @@ export function useVault() {
         counter++
       }
 
-      // Update document title
-      document.meta.title = copyName
-      document.meta.createdAt = Date.now()
-      document.meta.updatedAt = Date.now()
-
-      // Save as new file with assets
-      const assetStore = useAssetStore()
-      const newPath = `${vaultPath.value}/${copyName}.boardkit`
-      const newData = await exportBoardkit(document, assetStore.getAllBlobs())
-      await writeFileAtomically(newPath, newData)
+      // Copied natively: new element ids, embedded images and library tags kept
+      const copy = await invoke<{ path: string }>('duplicate_board', { path: filePath, name: copyName })
 
       await scanVaultFiles()
 
-      return newPath
+      return copy.path
     } catch (error) {
       console.error('Failed to duplicate file:', error)
       return null
@@ -347,69 +517,65 @@ export function useVault() {
   // ============================================================================
 
   /**
-   * Check if the active file was modified externally
-   * Returns the new document if modified, null otherwise
+   * Read the active file again after it changed on disk
    */
-  async function checkForExternalChanges(): Promise<{
-    changed: boolean
-    document: BoardkitDocument | null
-    mtime: number
-  }> {
-    if (!activeFilePath.value || lastKnownMtime.value === null) {
-      return { changed: false, document: null, mtime: 0 }
-    }
+  async function readActiveFile(): Promise<BoardkitDocument | null> {
+    if (!activeFilePath.value) return null
 
     try {
-      const fileStat = await stat(activeFilePath.value)
-      const currentMtime = fileStat.mtime?.getTime() ?? 0
-
-      if (currentMtime > lastKnownMtime.value) {
-        // File was modified externally
-        const data = await readFile(activeFilePath.value)
-        const result = await importBoardkit(data)
-
-        // Load imported assets
-        const assetStore = useAssetStore()
-        await assetStore.loadAssets(result.assets)
+      const data = await readBoard(activeFilePath.value)
+      if (!data) return null
+      const result = await importBoardkit(data)
 
-        return { changed: true, document: result.document, mtime: currentMtime }
-      }
+      // Load imported assets
+      const assetStore = useAssetStore()
+      await assetStore.loadAssets(result.assets)
 
-      return { changed: false, document: null, mtime: currentMtime }
+      const fileStat = await stat(activeFilePath.value)
+      lastKnownMtime.value = fileStat.mtime?.getTime() ?? Date.now()
+      return result.document
     } catch {
-      return { changed: false, document: null, mtime: 0 }
+      return null
     }
   }
 
   /**
-   * Start watching for file changes
+   * Start watching for file changes. The native watcher reports changes made
+   * outside Boardkit (sync clients, other machines); our own saves are ignored.
    */
-  function startWatching(onExternalChange: (document: BoardkitDocument) => void): void {
-    if (isWatching.value) return
+  async function startWatching(
+    onExternalChange: (document: BoardkitDocument) => void,
+    onExternalDelete?: (path: string) => void
+  ): Promise<void> {
+    if (isWatching.value || !vaultPath.value) return
 
     isWatching.value = true
 
-    watchInterval = setInterval(async () => {
-      // Refresh file list (detect new/deleted files)
-      await scanVaultFiles()
+    // The native watcher follows the workspace directory, so point it at the vault
+    const directory = await invoke<string>('get_boards_directory')
+    if (directory !== vaultPath.value) {
+      await invoke('set_boards_directory', { path: vaultPath.value })
+    }
 
-      // Check for external changes to active file
-      const result = await checkForExternalChanges()
-      if (result.changed && result.document) {
-        lastKnownMtime.value = result.mtime
-        onExternalChange(result.document)
-      }
-    }, WATCH_INTERVAL)
+    watchUnlisteners.push(
+      await listen<{ path: string }>('board-changed-externally', async (event) => {
+        await scanVaultFiles()
+        if (event.payload.path !== activeFilePath.value) return
+        const document = await readActiveFile()
+        if (document) onExternalChange(document)
+      }),
+      await listen<{ path: string }>('board-deleted-externally', async (event) => {
+        await scanVaultFiles()
+        if (event.payload.path === activeFilePath.value) onExternalDelete?.(event.payload.path)
+      })
+    )
   }
 
   /**
    * Stop watching for file changes
    */
   function stopWatching(): void {
-    if (watchInterval) {
-      clearInterval(watchInterval)
-      watchInterval = null
-    }
+    watchUnlisteners.splice(0).forEach((unlisten) => unlisten())
     isWatching.value = false
   }
 
@@ -474,14 +640,21 @@ export function useVault() {
     createFile,
     saveFile,
     loadFile,
+    loadFileStreaming,
+    loadBoardAssets,
     deleteFile,
     renameFile,
     duplicateFile,
 
+    // Encryption
+    isEncrypted,
+    promptPassword,
+    setFileEncryption,
+
     // File Watching
     startWatching,
     stopWatching,
-    checkForExternalChanges,
+    readActiveFile,
     updateLastKnownMtime,
   }
 }
@@ -0,0 +1,219 @@
+import { ref } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { getCurrentWindow } from '@tauri-apps/api/window'
+import { actionRegistry, useBoardStore, type CanvasElement } from '@boardkit/core'
+import { useToast } from '@boardkit/ui'
+
+export type PluginPermission = 'board:read' | 'board:write'
+
+export interface PluginCommand {
+  id: string
+  title: string
+  subtitle: string | null
+  keywords: string[]
+  menu: boolean
+}
+
+/** A WebAssembly plugin found in the plugins folder (see plugins.rs). */
+export interface WasmPlugin {
+  id: string
+  name: string
+  version: string
+  description: string | null
+  author: string | null
+  commands: PluginCommand[]
+  permissions: PluginPermission[]
+  enabled: boolean
+  path: string
+}
+
+/** Board change queued by a plugin command. */
+export type PluginOp =
+  | { op: 'elements.add'; element: Record<string, unknown> }
+  | { op: 'elements.update'; id: string; changes: Record<string, unknown> }
+  | { op: 'elements.remove'; id: string }
+
+export interface PluginRunRequest {
+  module: WebAssembly.Module
+  command: string
+  permissions: PluginPermission[]
+  board: unknown
+}
+
+export interface PluginRunResult {
+  ok: boolean
+  ops: PluginOp[]
+  notifications: string[]
+  error?: string
+}
+
+/** Commands that take longer are stopped and their changes dropped. */
+const RUN_TIMEOUT_MS = 5000
+
+const plugins = ref<WasmPlugin[]>([])
+const directory = ref<string | null>(null)
+const modules = new Map<string, Promise<WebAssembly.Module>>()
+const registeredActions = new Set<string>()
+
+const actionId = (plugin: string, command: string) => `plugin.${plugin}.${command}`
+
+/**
+ * WebAssembly plugins installed in the app's plugins folder. Enabled
+ * plugins add their commands to the palette; each run happens in a fresh
+ * worker with a time limit.
+ */
+export function useWasmPlugins() {
+  const boardStore = useBoardStore()
+  const toaster = useToast()
+
+  const loadModule = (id: string) => {
+    let module = modules.get(id)
+    if (!module) {
+      module = invoke<ArrayBuffer>('read_plugin_module', { id }).then((bytes) => WebAssembly.compile(bytes))
+      module.catch(() => modules.delete(id))
+      modules.set(id, module)
+    }
+    return module
+  }
+
+  const execute = (request: PluginRunRequest) =>
+    new Promise<PluginRunResult>((resolve) => {
+      const worker = new Worker(new URL('../utils/wasmPluginWorker.ts', import.meta.url), { type: 'module' })
+      const timeout = setTimeout(() => {
+        worker.terminate()
+        resolve({ ok: false, ops: [], notifications: [], error: `timed out after ${RUN_TIMEOUT_MS / 1000}s` })
+      }, RUN_TIMEOUT_MS)
+      worker.onmessage = (event: MessageEvent<PluginRunResult>) => {
+        clearTimeout(timeout)
+        worker.terminate()
+        resolve(event.data)
+      }
+      worker.onerror = (event) => {
+        clearTimeout(timeout)
+        worker.terminate()
+        resolve({ ok: false, ops: [], notifications: [], error: event.message })
+      }
+      worker.postMessage(request)
+    })
+
+  const apply = (name: string, ops: PluginOp[]) => {
+    const doc = boardStore.getDocument()
+    if (!doc || ops.length === 0) return
+    const label = `Ran ${name}`
+    boardStore.captureHistorySnapshot(label)
+
+    const board = doc.board
+    let zIndex = boardStore.maxZIndex
+    for (const op of ops) {
+      if (op.op === 'elements.add') {
+        board.elements.push({ ...op.element, zIndex: ++zIndex } as unknown as CanvasElement)
+      } else if (op.op === 'elements.update') {
+        const element = board.elements.find((candidate) => candidate.id === op.id)
+        if (element) Object.assign(element, op.changes)
+      } else {
+        board.elements = board.elements.filter((element) => element.id !== op.id)
+        board.connections = board.connections?.filter(
+          (connection) =>
+            !(connection.sourceType === 'element' && connection.sourceId === op.id) &&
+            !(connection.targetType === 'element' && connection.targetId === op.id)
+        )
+      }
+    }
+    boardStore.markDirty(label)
+  }
+
+  const run = async (pluginId: string, commandId: string) => {
+    const plugin = plugins.value.find((candidate) => candidate.id === pluginId && candidate.enabled)
+    const doc = boardStore.getDocument()
+    if (!plugin || !doc) return
+
+    let result: PluginRunResult
+    try {
+      result = await execute({
+        module: await loadModule(plugin.id),
+        command: commandId,
+        permissions: plugin.permissions,
+        // Plain data: the worker gets a copy, never the reactive board
+        board: JSON.parse(
+          JSON.stringify({
+            title: doc.meta.title,
+            elements: doc.board.elements,
+            connections: doc.board.connections ?? [],
+            selection: boardStore.selectedElementIds,
+          })
+        ),
+      })
+    } catch (error) {
+      console.error(`[Plugins] Failed to load ${plugin.id}:`, error)
+      toaster.error(`Could not load ${plugin.name}`)
+      return
+    }
+
+    result.notifications.forEach((message) => toaster.info(message, { title: plugin.name }))
+    if (!result.ok) {
+      console.error(`[Plugins] ${plugin.id} failed to run ${commandId}:`, result.error)
+      toaster.error(`${plugin.name} failed: ${result.error}`)
+      return
+    }
+    apply(plugin.name, result.ops)
+  }
+
+  const registerActions = () => {
+    registeredActions.forEach((id) => actionRegistry.unregister(id))
+    registeredActions.clear()
+    for (const plugin of plugins.value.filter((candidate) => candidate.enabled)) {
+      for (const command of plugin.commands) {
+        const id = actionId(plugin.id, command.id)
+        actionRegistry.register({
+          id,
+          title: command.title,
+          subtitle: command.subtitle ?? plugin.name,
+          keywords: ['plugin', plugin.name.toLowerCase(), ...command.keywords],
+          icon: 'zap',
+          group: 'module',
+          contexts: ['global'],
+          run: () => run(plugin.id, command.id),
+        })
+        registeredActions.add(id)
+      }
+    }
+  }
+
+  const refresh = async () => {
+    plugins.value = await invoke<WasmPlugin[]>('list_plugins')
+    // Modules may have been replaced on disk while disabled
+    modules.clear()
+    registerActions()
+  }
+
+  const setEnabled = async (id: string, enabled: boolean) => {
+    await invoke('enable_plugin', { id, enabled })
+  }
+
+  /** Load plugins, follow enable/disable from any window and run menu commands. */
+  const initialize = async (): Promise<UnlistenFn> => {
+    directory.value = await invoke<string>('get_plugins_directory')
+    await refresh()
+    const unlistenChanged = await listen('plugins-changed', () => {
+      refresh().catch((error) => console.error('[Plugins] Failed to reload plugins:', error))
+    })
+    const unlistenCommand = await getCurrentWindow().listen<{ plugin: string; command: string }>(
+      'plugin-command',
+      (event) => run(event.payload.plugin, event.payload.command)
+    )
+    return () => {
+      unlistenChanged()
+      unlistenCommand()
+    }
+  }
+
+  return {
+    plugins,
+    directory,
+    initialize,
+    refresh,
+    run,
+    setEnabled,
+  }
+}
@@ -8,7 +8,7 @@ import App from './App.vue'
 import 'virtual:uno.css'
 import './styles/globals.css'
 
-// Initialize secrets vault for desktop (filesystem storage)
+// Initialize secrets vault for desktop (OS keychain)
 setSecretsVault(tauriSecretsVault)
 
 // Initialize plugin runtime with required dependencies
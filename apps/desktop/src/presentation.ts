@@ -0,0 +1,7 @@
+import { createApp } from 'vue'
+import Presentation from './Presentation.vue'
+import 'virtual:uno.css'
+import './styles/globals.css'
+
+// Standalone entry for the slide and presenter-notes windows
+createApp(Presentation).mount('#app')
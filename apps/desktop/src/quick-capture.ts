@@ -0,0 +1,7 @@
+import { createApp } from 'vue'
+import QuickCapture from './QuickCapture.vue'
+import 'virtual:uno.css'
+import './styles/globals.css'
+
+// Standalone entry for the always-on-top quick capture window
+createApp(QuickCapture).mount('#app')
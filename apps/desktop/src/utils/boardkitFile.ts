@@ -1,5 +1,7 @@
 import JSZip from 'jszip'
 import { save, open } from '@tauri-apps/plugin-dialog'
+import { invoke } from '@tauri-apps/api/core'
+import { getCurrentWindow } from '@tauri-apps/api/window'
 import { readFile, writeFile, rename, remove, BaseDirectory, exists } from '@tauri-apps/plugin-fs'
 import {
   type BoardkitDocument,
@@ -76,10 +78,12 @@ function cleanupOrphanAssets(document: BoardkitDocument): BoardkitDocument {
  *
  * @param document - The document to export
  * @param assetBlobs - Map of assetId -> Blob for embedded assets
+ * @param compress - Deflate entries; native saves skip this and compress in Rust
  */
 export async function exportBoardkit(
   document: BoardkitDocument,
-  assetBlobs?: Map<string, Blob>
+  assetBlobs?: Map<string, Blob>,
+  compress = true
 ): Promise<Uint8Array> {
   const zip = new JSZip()
 
@@ -122,11 +126,64 @@ export async function exportBoardkit(
   }
 
   // Generate ZIP as Uint8Array
-  return await zip.generateAsync({
-    type: 'uint8array',
-    compression: 'DEFLATE',
-    compressionOptions: { level: 6 },
-  })
+  return await zip.generateAsync(
+    compress
+      ? { type: 'uint8array', compression: 'DEFLATE', compressionOptions: { level: 6 } }
+      : { type: 'uint8array', compression: 'STORE' }
+  )
+}
+
+/** A step of a native save, from the worker's `save-progress` event. */
+export interface SaveProgress {
+  path: string
+  stage: 'compressing' | 'writing' | 'indexing'
+  /** Rough fraction done, 0 to 1 */
+  progress: number
+}
+
+/** Outcome of a native save, from the worker's `save-complete` event. */
+export interface SaveComplete {
+  path: string
+  size: number
+  /** Bytes actually written, much less than `size` for journaled saves */
+  written: number
+  incremental: boolean
+  durationMs: number
+  error: string | null
+}
+
+export interface SaveHandlers {
+  onProgress?: (progress: SaveProgress) => void
+  onComplete?: (complete: SaveComplete) => void
+}
+
+/**
+ * Hand an uncompressed container to the native save worker, which compresses
+ * and writes it off the main thread. The bytes go as the raw request body.
+ * The worker's events for this board are passed on while the save runs.
+ */
+export async function saveBoardNative(path: string, data: Uint8Array, handlers: SaveHandlers = {}): Promise<void> {
+  const appWindow = getCurrentWindow()
+  const unlisteners = await Promise.all([
+    appWindow.listen<SaveProgress>('save-progress', ({ payload }) => {
+      if (payload.path === path) handlers.onProgress?.(payload)
+    }),
+    appWindow.listen<SaveComplete>('save-complete', ({ payload }) => {
+      if (payload.path !== path) return
+      if (!payload.error) {
+        console.debug(
+          `[Save] ${payload.incremental ? 'Appended' : 'Wrote'} ${payload.written} of ${payload.size} bytes ` +
+            `in ${payload.durationMs}ms`
+        )
+      }
+      handlers.onComplete?.(payload)
+    }),
+  ])
+  try {
+    await invoke('save_board', data, { headers: { 'x-board-path': encodeURIComponent(path) } })
+  } finally {
+    unlisteners.forEach((unlisten) => unlisten())
+  }
 }
 
 /**
@@ -197,7 +254,8 @@ export async function importBoardkit(data: Uint8Array): Promise<ImportResult> {
 }
 
 /**
- * Save a .boardkit file using Tauri's file dialog (uses atomic writes)
+ * Save a .boardkit file using Tauri's file dialog.
+ * The native `save_board` command validates the container and writes atomically.
  */
 export async function saveToFile(document: BoardkitDocument): Promise<boolean> {
   const filename = `${document.meta.title.replace(/[^a-zA-Z0-9-_]/g, '_')}.boardkit`
@@ -214,8 +272,7 @@ export async function saveToFile(document: BoardkitDocument): Promise<boolean> {
 
   if (!path) return false
 
-  const data = await exportBoardkit(document)
-  await writeFileAtomically(path, data)
+  await saveBoardNative(path, await exportBoardkit(document, undefined, false))
   return true
 }
 
@@ -236,8 +293,8 @@ export async function openFromFile(): Promise<ImportResult | null> {
 
   if (!path) return null
 
-  const data = await readFile(path as string)
-  return await importBoardkit(data)
+  const data = await invoke<ArrayBuffer>('load_board', { path })
+  return await importBoardkit(new Uint8Array(data))
 }
 
 /**
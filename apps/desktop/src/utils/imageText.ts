@@ -0,0 +1,22 @@
+import { invoke } from '@tauri-apps/api/core'
+import { useAssetStore } from '@boardkit/core'
+
+// Set once Tesseract turns out to be missing, so it isn't retried per image
+let unavailable = false
+
+/**
+ * Recognize the text in an image asset in the background so that board
+ * search finds it on the next save. Best-effort: without Tesseract
+ * installed this does nothing.
+ */
+export function recognizeImageText(assetId: string): void {
+  const hash = useAssetStore().getAsset(assetId)?.hash
+  if (unavailable || !hash) return
+  invoke<string>('ocr_asset', { hash }).catch((error) => {
+    if ((error as { kind?: string }).kind === 'not_found') {
+      unavailable = true
+    } else {
+      console.error('[imageText] Text recognition failed:', error)
+    }
+  })
+}
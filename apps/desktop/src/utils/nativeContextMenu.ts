@@ -0,0 +1,62 @@
+import { invoke } from '@tauri-apps/api/core'
+import type { MenuContent, MenuItem } from '@boardkit/ui'
+
+/**
+ * Menu entries as the native `show_context_menu` command expects them.
+ */
+type NativeMenuItem =
+  | { type: 'item'; id: string; label: string; enabled: boolean }
+  | { type: 'submenu'; label: string; items: NativeMenuItem[]; enabled: boolean }
+  | { type: 'separator' }
+
+const isGrouped = (children: MenuItem[] | MenuContent): children is MenuContent =>
+  children.length > 0 && 'items' in children[0]
+
+function toNativeItem(item: MenuItem): NativeMenuItem {
+  if (item.children?.length) {
+    const groups = isGrouped(item.children) ? item.children : [{ items: item.children }]
+    return { type: 'submenu', label: item.label, items: toNativeItems(groups), enabled: !item.disabled }
+  }
+  return { type: 'item', id: item.id, label: item.label, enabled: !item.disabled }
+}
+
+/**
+ * Groups become runs of items between separators; group labels become
+ * disabled header items, the closest native equivalent.
+ */
+function toNativeItems(groups: MenuContent): NativeMenuItem[] {
+  const items: NativeMenuItem[] = []
+  for (const group of groups) {
+    if (group.items.length === 0) continue
+    if (items.length > 0) items.push({ type: 'separator' })
+    if (group.label) items.push({ type: 'item', id: `group:${group.label}`, label: group.label, enabled: false })
+    items.push(...group.items.map(toNativeItem))
+  }
+  return items
+}
+
+function findItem(groups: MenuContent, id: string): MenuItem | null {
+  for (const group of groups) {
+    for (const item of group.items) {
+      if (item.id === id) return item
+      if (item.children?.length) {
+        const children = isGrouped(item.children) ? item.children : [{ items: item.children }]
+        const found = findItem(children, id)
+        if (found) return found
+      }
+    }
+  }
+  return null
+}
+
+/**
+ * Show `groups` as a native popup menu at a window-relative position.
+ * Resolves with the chosen item, or null when the menu was dismissed.
+ */
+export async function showNativeContextMenu(
+  groups: MenuContent,
+  position: { x: number; y: number }
+): Promise<MenuItem | null> {
+  const id = await invoke<string | null>('show_context_menu', { items: toNativeItems(groups), position })
+  return id ? findItem(groups, id) : null
+}
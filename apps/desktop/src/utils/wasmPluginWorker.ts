@@ -0,0 +1,135 @@
+/**
+ * Worker that runs one command of a WebAssembly plugin.
+ *
+ * The module only gets the `boardkit` imports below: no DOM, no network, no
+ * Tauri. It sees a snapshot of the board taken when the command started,
+ * and its changes are queued and handed back to the board window, which
+ * applies them as a single undo step once the command returns.
+ *
+ * ABI: the module exports `memory`, `boardkit_alloc(len) -> ptr` and
+ * `boardkit_run(ptr, len) -> status`, where the argument is the UTF-8
+ * command id and a non-zero status is a failure. It calls
+ * `boardkit.call(ptr, len) -> len` with a UTF-8 JSON request and then
+ * `boardkit.read(ptr)` to copy the JSON response into a buffer it
+ * allocated; a negative length means the response is an error message.
+ */
+
+import type { PluginRunRequest, PluginRunResult, PluginOp } from '../composables/useWasmPlugins'
+
+interface BoardSnapshot {
+  elements: Array<{ id: string; [key: string]: unknown }>
+  connections: unknown[]
+  title: string
+  selection: string[]
+}
+
+const encoder = new TextEncoder()
+const decoder = new TextDecoder()
+
+const run = async ({ module, command, permissions, board }: PluginRunRequest): Promise<PluginRunResult> => {
+  const snapshot = board as BoardSnapshot
+  const ops: PluginOp[] = []
+  const notifications: string[] = []
+  let pending = new Uint8Array()
+  let memory: WebAssembly.Memory | null = null
+
+  const canRead = permissions.includes('board:read')
+  const canWrite = permissions.includes('board:write')
+
+  const memoryBytes = () => {
+    if (!memory) throw new Error('plugin does not export its memory')
+    return new Uint8Array(memory.buffer)
+  }
+
+  // Reads after a write see the write: the snapshot follows the queue
+  const handle = (request: Record<string, unknown>): unknown => {
+    const op = request.op
+    if (op === 'log') {
+      console.info('[plugin]', String(request.message ?? ''))
+      return null
+    }
+    if (op === 'notify') {
+      notifications.push(String(request.message ?? ''))
+      return null
+    }
+    if (op === 'board.get' || op === 'selection.get') {
+      if (!canRead) throw new Error('plugin lacks the board:read permission')
+      return op === 'board.get'
+        ? { title: snapshot.title, elements: snapshot.elements, connections: snapshot.connections }
+        : snapshot.selection
+    }
+    if (op === 'elements.add' || op === 'elements.update' || op === 'elements.remove') {
+      if (!canWrite) throw new Error('plugin lacks the board:write permission')
+    }
+    if (op === 'elements.add') {
+      const element = request.element as Record<string, unknown> | undefined
+      if (!element || typeof element.type !== 'string') throw new Error('elements.add needs an element with a type')
+      const id = crypto.randomUUID()
+      snapshot.elements.push({ ...element, id })
+      ops.push({ op, element: { ...element, id } })
+      return id
+    }
+    if (op === 'elements.update') {
+      const id = String(request.id ?? '')
+      const target = snapshot.elements.find((element) => element.id === id)
+      if (!target) throw new Error(`no element ${id}`)
+      const changes = { ...((request.changes ?? {}) as Record<string, unknown>) }
+      delete changes.id
+      Object.assign(target, changes)
+      ops.push({ op, id, changes })
+      return null
+    }
+    if (op === 'elements.remove') {
+      const id = String(request.id ?? '')
+      snapshot.elements = snapshot.elements.filter((element) => element.id !== id)
+      snapshot.selection = snapshot.selection.filter((selected) => selected !== id)
+      ops.push({ op, id })
+      return null
+    }
+    throw new Error(`unknown host call ${String(op)}`)
+  }
+
+  const imports = {
+    boardkit: {
+      call: (ptr: number, len: number): number => {
+        try {
+          const request = JSON.parse(decoder.decode(memoryBytes().subarray(ptr, ptr + len)))
+          pending = encoder.encode(JSON.stringify(handle(request) ?? null))
+          return pending.length
+        } catch (error) {
+          pending = encoder.encode(error instanceof Error ? error.message : String(error))
+          return -pending.length
+        }
+      },
+      read: (ptr: number) => {
+        memoryBytes().set(pending, ptr)
+      },
+    },
+  }
+
+  try {
+    const instance = await WebAssembly.instantiate(module, imports)
+    const exports = instance.exports as {
+      memory?: WebAssembly.Memory
+      boardkit_alloc?: (len: number) => number
+      boardkit_run?: (ptr: number, len: number) => number
+    }
+    if (!exports.memory || !exports.boardkit_alloc || !exports.boardkit_run) {
+      throw new Error('plugin must export memory, boardkit_alloc and boardkit_run')
+    }
+    memory = exports.memory
+
+    const name = encoder.encode(command)
+    const ptr = exports.boardkit_alloc(name.length)
+    memoryBytes().set(name, ptr)
+    const status = exports.boardkit_run(ptr, name.length)
+    if (status !== 0) throw new Error(`command failed with status ${status}`)
+    return { ok: true, ops, notifications }
+  } catch (error) {
+    return { ok: false, ops: [], notifications, error: error instanceof Error ? error.message : String(error) }
+  }
+}
+
+self.onmessage = async (event: MessageEvent<PluginRunRequest>) => {
+  self.postMessage(await run(event.data))
+}
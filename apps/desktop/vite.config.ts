@@ -23,5 +23,12 @@ export default defineConfig({
     target: process.env.TAURI_PLATFORM === 'windows' ? 'chrome105' : 'safari13',
     minify: !process.env.TAURI_DEBUG ? 'esbuild' : false,
     sourcemap: !!process.env.TAURI_DEBUG,
+    rollupOptions: {
+      input: {
+        main: resolve(__dirname, 'index.html'),
+        quickCapture: resolve(__dirname, 'quick-capture.html'),
+        presentation: resolve(__dirname, 'presentation.html'),
+      },
+    },
   },
 })
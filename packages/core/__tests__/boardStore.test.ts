@@ -192,6 +192,33 @@ describe('boardStore', () => {
       })
     })
 
+    describe('insertLoadedElements()', () => {
+      it('should keep streamed elements in their saved order', () => {
+        const store = useBoardStore()
+        store.loadDocument(createTestDocument())
+
+        store.insertLoadedElements([
+          { index: 2, element: createTestShapeElement({ id: 'c' }) },
+          { index: 0, element: createTestShapeElement({ id: 'a' }) },
+        ])
+        store.insertLoadedElements([{ index: 1, element: createTestTextElement({ id: 'b' }) }])
+
+        expect(store.elements.map((element) => element.id)).toEqual(['a', 'b', 'c'])
+        expect(store.isDirty).toBe(false)
+      })
+
+      it('should keep elements added while loading on top', () => {
+        const store = useBoardStore()
+        store.loadDocument(createTestDocument())
+        store.insertLoadedElements([{ index: 0, element: createTestShapeElement({ id: 'a' }) }])
+        const id = store.addElement(createTestTextElement() as Omit<CanvasElement, 'id' | 'zIndex'>)
+
+        store.insertLoadedElements([{ index: 1, element: createTestShapeElement({ id: 'b' }) }])
+
+        expect(store.elements.map((element) => element.id)).toEqual(['a', 'b', id])
+      })
+    })
+
     describe('setTitle()', () => {
       it('should update the document title', () => {
         const store = useBoardStore()
@@ -1252,4 +1279,37 @@ describe('boardStore', () => {
       })
     })
   })
+
+  // ===========================================================================
+  // Undo History
+  // ===========================================================================
+
+  describe('Undo History', () => {
+    describe('restoreHistory()', () => {
+      it('should undo to a restored entry', () => {
+        const store = useBoardStore()
+        store.createNewBoard('Before')
+        const snapshot = JSON.parse(JSON.stringify(store.getDocument()))
+        store.setTitle('After')
+
+        store.restoreHistory([{ id: 'step-1', label: 'Renamed board', timestamp: 1, snapshot }])
+
+        expect(store.canUndo).toBe(true)
+        expect(store.undoLabel).toBe('Renamed board')
+        expect(store.undo()).toBe(true)
+        expect(store.getDocument()!.meta.title).toBe('Before')
+      })
+
+      it('should replace the existing history', () => {
+        const store = useBoardStore()
+        store.createNewBoard('Test')
+        store.addWidget('mock-module')
+
+        store.restoreHistory([])
+
+        expect(store.canUndo).toBe(false)
+        expect(store.canRedo).toBe(false)
+      })
+    })
+  })
 })
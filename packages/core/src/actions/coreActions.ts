@@ -21,6 +21,7 @@ export type MenuActionEvent =
   | { type: 'vault.change' }
   | { type: 'vault.secrets' }
   | { type: 'vault.reveal' }
+  | { type: 'vault.trash' }
 
 type MenuActionEventHandler = (event: MenuActionEvent) => void
 
@@ -382,6 +383,20 @@ function createCoreActions(): ActionDefinition[] {
         menuActionBus.emit('vault.reveal')
       },
     },
+    {
+      id: 'vault.trash',
+      title: 'Trash',
+      subtitle: 'Restore or permanently delete removed boards',
+      keywords: ['trash', 'deleted', 'restore', 'recover', 'bin'],
+      icon: 'trash-2',
+      group: 'board',
+      contexts: ['global'],
+      priority: 37,
+      when: (ctx) => ctx.platform === 'desktop',
+      run: () => {
+        menuActionBus.emit('vault.trash')
+      },
+    },
 
     // ============================================
     // SELECTION ACTIONS
@@ -265,6 +265,17 @@ export function useHistory(options: { maxSize?: number } = {}) {
     state.value.liveSnapshot = null
   }
 
+  /**
+   * Replace the history with entries kept from an earlier session.
+   *
+   * @param entries - Entries to undo, most recent first
+   */
+  function restore(entries: HistoryEntry[]): void {
+    state.value.stack = entries.slice(0, state.value.maxSize)
+    state.value.currentIndex = -1
+    state.value.liveSnapshot = null
+  }
+
   return {
     pushState,
     undo,
@@ -279,6 +290,7 @@ export function useHistory(options: { maxSize?: number } = {}) {
     currentIndex,
     isAtLive,
     clear,
+    restore,
   }
 }
 
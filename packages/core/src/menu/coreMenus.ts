@@ -189,6 +189,13 @@ const vaultMenu: MenuDefinition = {
       icon: 'external-link',
       actionId: 'vault.reveal',
     },
+    menuSeparator(),
+    {
+      id: 'trash',
+      label: 'Trash...',
+      icon: 'trash-2',
+      actionId: 'vault.trash',
+    },
   ],
 }
 
@@ -76,6 +76,9 @@ export const useBoardStore = defineStore('board', () => {
     isCut: boolean
   } | null>(null)
 
+  // Saved list positions of elements delivered by a streaming load, by id
+  let loadedElementIndices = new Map<string, number>()
+
   // Flag to prevent history captures during document restoration (undo/redo/goToEntry)
   const isRestoring = ref(false)
 
@@ -346,6 +349,33 @@ export const useBoardStore = defineStore('board', () => {
     document.value = migratedDoc
     selection.value = []
     isDirty.value = false
+    loadedElementIndices = new Map()
+  }
+
+  /**
+   * Add elements delivered by a streaming load, keeping them in their saved
+   * order. Not an edit: no history entry, and the board stays clean.
+   */
+  function insertLoadedElements(entries: Array<{ index: number; element: CanvasElement }>) {
+    if (!document.value) return
+    const incoming = [...entries].sort((a, b) => a.index - b.index)
+    const current = document.value.board.elements
+    // Elements created while loading have no saved position and stay on top
+    const savedIndex = (element: CanvasElement) => loadedElementIndices.get(element.id) ?? Infinity
+
+    const merged: CanvasElement[] = []
+    let i = 0
+    let j = 0
+    while (i < current.length || j < incoming.length) {
+      if (j >= incoming.length || (i < current.length && savedIndex(current[i]) < incoming[j].index)) {
+        merged.push(current[i++])
+      } else {
+        const { index, element } = incoming[j++]
+        loadedElementIndices.set(element.id, index)
+        merged.push(element)
+      }
+    }
+    document.value.board.elements = merged
   }
 
   function setTitle(newTitle: string) {
@@ -2057,6 +2087,15 @@ export const useBoardStore = defineStore('board', () => {
     return false
   }
 
+  /**
+   * Replace the undo history, e.g. with steps persisted by the last session
+   * of the board just loaded. An empty list starts the board afresh.
+   */
+  function restoreHistory(entries: HistoryEntry[]) {
+    clearHistoryDebounceTimers()
+    history.restore(entries)
+  }
+
   /** Whether undo is available */
   const canUndo = computed(() => history.canUndo.value)
 
@@ -2260,6 +2299,7 @@ export const useBoardStore = defineStore('board', () => {
     // Actions - Document
     createNewBoard,
     loadDocument,
+    insertLoadedElements,
     setTitle,
     getDocument,
 
@@ -2378,6 +2418,7 @@ export const useBoardStore = defineStore('board', () => {
     redo,
     goToHistoryEntry,
     goToLiveState,
+    restoreHistory,
     canUndo,
     canRedo,
     undoLabel,
@@ -240,6 +240,9 @@ export interface TextInputField extends SettingsFieldBase {
 
   /** Input pattern for validation */
   pattern?: string
+
+  /** Hide the typed characters, for passwords */
+  masked?: boolean
 }
 
 // ============================================================================
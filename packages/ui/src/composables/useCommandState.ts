@@ -13,12 +13,15 @@ export interface CommandState {
 
   // Config
   loop: Ref<boolean>
+  /** When false, items are shown as given, e.g. already ranked by a backend */
+  shouldFilter: Ref<boolean>
 
   // Computed
   filteredItems: Ref<CommandItemData[]>
   selectedItem: Ref<CommandItemData | undefined>
 
   // Actions
+  matches: (item: Pick<CommandItemData, 'value' | 'keywords'>) => boolean
   registerItem: (item: CommandItemData) => void
   unregisterItem: (id: string) => void
   selectItem: (value: string) => void
@@ -42,27 +45,29 @@ export interface CommandItemData {
 
 const COMMAND_STATE_KEY: InjectionKey<CommandState> = Symbol('command-state')
 
-export function createCommandState(options: { loop?: boolean } = {}) {
+export function createCommandState(options: { loop?: boolean; shouldFilter?: boolean } = {}) {
   const search = ref('')
   const selectedValue = ref('')
   const selectedIndex = ref(0)
   const items = ref<CommandItemData[]>([])
   const loop = ref(options.loop ?? true)
+  const shouldFilter = ref(options.shouldFilter ?? true)
   const listRef = ref<HTMLElement | null>(null)
 
-  // Filter items based on search
-  const filteredItems = computed(() => {
+  // Check an item against the search
+  const matches = (item: Pick<CommandItemData, 'value' | 'keywords'>): boolean => {
     const query = search.value.toLowerCase().trim()
-    if (!query) return items.value.filter(item => !item.disabled)
+    if (!query || !shouldFilter.value) return true
 
-    return items.value.filter(item => {
-      if (item.disabled) return false
+    const valueMatch = item.value.toLowerCase().includes(query)
+    const keywordMatch = item.keywords?.some(k => k.toLowerCase().includes(query))
 
-      const valueMatch = item.value.toLowerCase().includes(query)
-      const keywordMatch = item.keywords?.some(k => k.toLowerCase().includes(query))
+    return valueMatch || !!keywordMatch
+  }
 
-      return valueMatch || keywordMatch
-    })
+  // Filter items based on search
+  const filteredItems = computed(() => {
+    return items.value.filter(item => !item.disabled && matches(item))
   })
 
   // Get currently selected item
@@ -142,8 +147,10 @@ export function createCommandState(options: { loop?: boolean } = {}) {
     selectedIndex,
     items,
     loop,
+    shouldFilter,
     filteredItems,
     selectedItem,
+    matches,
     registerItem,
     unregisterItem,
     selectItem,
@@ -0,0 +1,52 @@
+import { inject, provide, type InjectionKey } from 'vue'
+
+/** Resolves to a picked `#rrggbb` color, or null when the user cancels. */
+export type ScreenColorPicker = () => Promise<string | null>
+
+/**
+ * Injection key for picking a color from the screen.
+ * Hosts with native sampling (the desktop app) provide their own picker.
+ */
+export const SCREEN_COLOR_PICKER_KEY: InjectionKey<ScreenColorPicker> = Symbol('bk-screen-color-picker')
+
+interface EyeDropperResult {
+  sRGBHex: string
+}
+
+interface EyeDropperConstructor {
+  new (): { open: () => Promise<EyeDropperResult> }
+}
+
+/** The browser's EyeDropper API, where available (Chromium) */
+function browserPicker(): ScreenColorPicker | null {
+  const EyeDropper = (window as unknown as { EyeDropper?: EyeDropperConstructor }).EyeDropper
+  if (!EyeDropper) return null
+  return async () => {
+    try {
+      return (await new EyeDropper().open()).sRGBHex
+    } catch {
+      // Rejected when the user presses Escape
+      return null
+    }
+  }
+}
+
+/**
+ * Provide a native screen color picker to the BkColorPicker components below.
+ *
+ * @example
+ * ```ts
+ * provideScreenColorPicker(() => invoke<string | null>('pick_screen_color'))
+ * ```
+ */
+export function provideScreenColorPicker(picker: ScreenColorPicker): void {
+  provide(SCREEN_COLOR_PICKER_KEY, picker)
+}
+
+/**
+ * The screen color picker provided by the host, falling back to the
+ * browser's EyeDropper. Null when neither is available.
+ */
+export function useScreenColorPicker(): ScreenColorPicker | null {
+  return inject(SCREEN_COLOR_PICKER_KEY, null) ?? browserPicker()
+}
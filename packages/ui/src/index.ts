@@ -117,6 +117,12 @@ export { useModal, MODAL_INJECTION_KEY } from './composables/useModal'
 export { useToast, TOAST_INJECTION_KEY } from './composables/useToast'
 export { useWidgetTransform, WIDGET_TRANSFORM_KEY, type WidgetTransform } from './composables/useWidgetTransform'
 export { usePlatform, initPlatform, type Platform } from './composables/usePlatform'
+export {
+  useScreenColorPicker,
+  provideScreenColorPicker,
+  SCREEN_COLOR_PICKER_KEY,
+  type ScreenColorPicker,
+} from './composables/useScreenColorPicker'
 
 // Modal Types
 export type {